@@ -0,0 +1,285 @@
+use std::fs;
+
+use bevy::{ecs::query::Has, prelude::*, utils::HashMap};
+use bevy_rapier3d::{prelude::*, rapier::geometry::CollisionEventFlags};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    damage::Crashed,
+    plane::{Altitude, Plane, Wheel},
+    world::BridgeGap,
+};
+
+pub struct AchievementsPlugin;
+
+impl Plugin for AchievementsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(load_achievements().unwrap_or_default())
+            .add_event::<GameplayEvent>()
+            .add_event::<AchievementUnlockedEvent>()
+            .add_systems(Update, (track_flight, unlock_achievements).chain())
+            // Collision events are only kept for a couple of physics steps, so read them every step
+            .add_systems(FixedUpdate, detect_fly_unders.after(PhysicsSet::Writeback));
+    }
+}
+
+const ACHIEVEMENTS_PATH: &str = "achievements.ron";
+/// Time airborne before touching down counts as a landing, so bouncing on the takeoff roll doesn't.
+const MIN_FLIGHT_TIME: f32 = 10.0;
+/// Altitudes are reported each time the highest of the flight passes another multiple of this.
+const ALTITUDE_STEP: f32 = 100.0;
+/// Time after flying under a bridge before flying under it again counts, as each of the plane's
+/// parts leaves the gap separately.
+const FLY_UNDER_INTERVAL: f32 = 2.0;
+
+/// Things the pilot has done that achievements can be unlocked by.
+#[derive(Event, Clone, Copy, Debug)]
+pub enum GameplayEvent {
+    TookOff,
+    Landed,
+    FlewUnderBridge,
+    /// New highest altitude of the flight, rounded down to a whole step.
+    Altitude(f32),
+    /// Another minute spent airborne.
+    AirborneMinute,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum Achievement {
+    FirstLanding,
+    UnderBridge,
+    HighAltitude,
+    LongHaul,
+}
+
+impl Achievement {
+    pub const ALL: [Self; 4] = [
+        Self::FirstLanding,
+        Self::UnderBridge,
+        Self::HighAltitude,
+        Self::LongHaul,
+    ];
+
+    pub fn title(&self) -> &'static str {
+        match self {
+            Self::FirstLanding => "Wheels down",
+            Self::UnderBridge => "Low bridge",
+            Self::HighAltitude => "Thin air",
+            Self::LongHaul => "Long haul",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Self::FirstLanding => "Land for the first time",
+            Self::UnderBridge => "Fly under a bridge",
+            Self::HighAltitude => "Climb to 5,000 m",
+            Self::LongHaul => "Spend an hour airborne",
+        }
+    }
+
+    fn unlocked_by(&self, event: &GameplayEvent, achievements: &Achievements) -> bool {
+        match (self, event) {
+            (Self::FirstLanding, GameplayEvent::Landed) => true,
+            (Self::UnderBridge, GameplayEvent::FlewUnderBridge) => true,
+            (Self::HighAltitude, GameplayEvent::Altitude(altitude)) => *altitude >= 5000.0,
+            (Self::LongHaul, GameplayEvent::AirborneMinute) => achievements.airborne_time >= 3600.0,
+            _ => false,
+        }
+    }
+}
+
+#[derive(Event)]
+pub struct AchievementUnlockedEvent(pub Achievement);
+
+/// Achievements unlocked so far, kept between runs.
+#[derive(Resource, Default, Serialize, Deserialize)]
+pub struct Achievements {
+    pub unlocked: Vec<Achievement>,
+    /// Time in seconds spent airborne over all flights.
+    pub airborne_time: f32,
+}
+
+/// Progress through the current flight of the local plane.
+#[derive(Default)]
+struct FlightTracker {
+    plane: Option<Entity>,
+    airborne: bool,
+    flight_time: f32,
+    /// Airborne time not yet reported as a whole minute.
+    unreported_time: f32,
+    highest_step: i32,
+}
+
+/// Turns what the plane is doing into gameplay events.
+fn track_flight(
+    time: Res<Time>,
+    rapier_context: Res<RapierContext>,
+    mut tracker: Local<FlightTracker>,
+    plane_query: Query<(Entity, &Altitude, Has<Crashed>), With<Plane>>,
+    children_query: Query<&Children>,
+    wheel_query: Query<(), With<Wheel>>,
+    mut gameplay_events: EventWriter<GameplayEvent>,
+) {
+    let Ok((plane, Altitude(altitude), crashed)) = plane_query.get_single() else {
+        return;
+    };
+
+    // A rebuilt plane starts a new flight
+    if tracker.plane != Some(plane) {
+        *tracker = FlightTracker {
+            plane: Some(plane),
+            ..default()
+        };
+    }
+    if crashed {
+        return;
+    }
+
+    let grounded = children_query
+        .iter_descendants(plane)
+        .filter(|child| wheel_query.contains(*child))
+        .any(|wheel| {
+            rapier_context
+                .contacts_with(wheel)
+                .any(|pair| pair.has_any_active_contacts())
+        });
+
+    match (tracker.airborne, grounded) {
+        (false, false) => {
+            tracker.airborne = true;
+            tracker.flight_time = 0.0;
+            gameplay_events.send(GameplayEvent::TookOff);
+        }
+        (true, true) => {
+            tracker.airborne = false;
+            if tracker.flight_time >= MIN_FLIGHT_TIME {
+                gameplay_events.send(GameplayEvent::Landed);
+            }
+        }
+        _ => {}
+    }
+
+    if !tracker.airborne {
+        return;
+    }
+
+    tracker.flight_time += time.delta_seconds();
+    tracker.unreported_time += time.delta_seconds();
+    if tracker.unreported_time >= 60.0 {
+        tracker.unreported_time -= 60.0;
+        gameplay_events.send(GameplayEvent::AirborneMinute);
+    }
+
+    let step = (altitude / ALTITUDE_STEP).floor() as i32;
+    if step > tracker.highest_step {
+        tracker.highest_step = step;
+        gameplay_events.send(GameplayEvent::Altitude(step as f32 * ALTITUDE_STEP));
+    }
+}
+
+/// Counts a fly under when the plane comes out of the gap under a bridge, so it isn't counted for
+/// crashing into the bridge.
+fn detect_fly_unders(
+    time: Res<Time>,
+    mut collision_events: EventReader<CollisionEvent>,
+    gap_query: Query<(), With<BridgeGap>>,
+    parent_query: Query<&Parent>,
+    plane_query: Query<(), (With<Plane>, Without<Crashed>)>,
+    mut last_fly_unders: Local<HashMap<Entity, f32>>,
+    mut gameplay_events: EventWriter<GameplayEvent>,
+) {
+    let now = time.elapsed_seconds();
+
+    for event in collision_events.iter() {
+        let CollisionEvent::Stopped(e1, e2, flags) = event else {
+            continue;
+        };
+        // Rebuilding the plane removes its colliders from the gap
+        if flags.contains(CollisionEventFlags::REMOVED) {
+            continue;
+        }
+
+        for (gap, other) in [(*e1, *e2), (*e2, *e1)] {
+            if !gap_query.contains(gap)
+                || !std::iter::once(other)
+                    .chain(parent_query.iter_ancestors(other))
+                    .any(|entity| plane_query.contains(entity))
+            {
+                continue;
+            }
+
+            let last = last_fly_unders.insert(gap, now);
+            if last.is_none_or(|last| now - last > FLY_UNDER_INTERVAL) {
+                info!("Flew under a bridge");
+                gameplay_events.send(GameplayEvent::FlewUnderBridge);
+            }
+        }
+    }
+}
+
+fn unlock_achievements(
+    mut achievements: ResMut<Achievements>,
+    mut gameplay_events: EventReader<GameplayEvent>,
+    mut unlocked_events: EventWriter<AchievementUnlockedEvent>,
+) {
+    let mut changed = false;
+
+    for event in gameplay_events.iter() {
+        debug!("Gameplay event {:?}", event);
+
+        if let GameplayEvent::AirborneMinute = event {
+            achievements.airborne_time += 60.0;
+            changed = true;
+        }
+
+        for achievement in Achievement::ALL {
+            if achievements.unlocked.contains(&achievement)
+                || !achievement.unlocked_by(event, &achievements)
+            {
+                continue;
+            }
+
+            info!("Unlocked achievement {:?}", achievement);
+            achievements.unlocked.push(achievement);
+            unlocked_events.send(AchievementUnlockedEvent(achievement));
+            changed = true;
+        }
+    }
+
+    if changed {
+        save_achievements(&achievements);
+    }
+}
+
+fn load_achievements() -> Option<Achievements> {
+    let contents = fs::read_to_string(ACHIEVEMENTS_PATH).ok()?;
+
+    match ron::from_str(&contents) {
+        Ok(achievements) => {
+            info!("Loaded achievements from {}", ACHIEVEMENTS_PATH);
+            Some(achievements)
+        }
+        Err(err) => {
+            warn!(
+                "Ignoring invalid achievements in {}: {}",
+                ACHIEVEMENTS_PATH, err
+            );
+            None
+        }
+    }
+}
+
+fn save_achievements(achievements: &Achievements) {
+    let result = ron::ser::to_string_pretty(achievements, ron::ser::PrettyConfig::default())
+        .map_err(|err| err.to_string())
+        .and_then(|contents| fs::write(ACHIEVEMENTS_PATH, contents).map_err(|err| err.to_string()));
+
+    match result {
+        Ok(()) => info!("Saved achievements to {}", ACHIEVEMENTS_PATH),
+        Err(err) => warn!(
+            "Failed to save achievements to {}: {}",
+            ACHIEVEMENTS_PATH, err
+        ),
+    }
+}
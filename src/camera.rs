@@ -1,27 +1,234 @@
-use bevy::{core_pipeline::clear_color::ClearColorConfig, prelude::*};
+use bevy::{
+    core_pipeline::clear_color::ClearColorConfig, ecs::query::Has, input::mouse::MouseMotion,
+    prelude::*, render::camera::ScalingMode,
+};
+use bevy_egui::EguiContexts;
+use bevy_rapier3d::prelude::*;
+
+use crate::{
+    cockpit::COCKPIT_EYE,
+    damage::{track_previous_velocity, GForceLimits},
+    plane::{LoadFactor, Plane},
+};
+
+pub use self::{
+    padlock::LookTarget,
+    pip::{PipView, PIP_SIZE},
+};
+use self::{
+    padlock::{PadlockCamera, PadlockTarget},
+    shake::CameraShake,
+};
+
+mod padlock;
+mod pip;
+mod shake;
 
 pub struct CameraPlugin;
 
 impl Plugin for CameraPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Startup, setup)
-            .add_systems(Update, (update_fog, attach_to_follow));
+        app.insert_resource(ClearColor(Color::rgb(0.5, 0.5, 0.8)))
+            .insert_resource(CameraSettings::default())
+            .init_resource::<FollowSettings>()
+            .init_resource::<PilotVision>()
+            .init_resource::<PadlockTarget>()
+            .init_resource::<shake::ShakeImpact>()
+            .init_resource::<shake::ShakeNoise>()
+            .add_systems(Startup, (setup, pip::setup_pip))
+            .add_systems(
+                FixedUpdate,
+                shake::record_impacts
+                    .after(PhysicsSet::Writeback)
+                    .before(track_previous_velocity),
+            )
+            .add_systems(
+                Update,
+                (
+                    update_fog,
+                    (update_pilot_vision, update_vision_overlay).chain(),
+                    (
+                        shake::settle_camera,
+                        attach_to_follow,
+                        apply_deferred,
+                        update_follow_camera,
+                        update_orbit_camera,
+                        padlock::cycle_look_target,
+                        padlock::update_padlock_camera,
+                        update_map_camera,
+                        pip::update_pip_camera,
+                        shake::shake_camera,
+                    )
+                        .chain(),
+                ),
+            );
+    }
+}
+
+#[derive(Resource)]
+pub struct CameraSettings {
+    /// Time constant in seconds for the orbit camera to catch up with the plane.
+    pub lag: f32,
+    /// Vertical field of view in degrees.
+    pub fov: f32,
+    /// Distance of the orbit camera from the plane.
+    pub distance: f32,
+    /// Distance in metres from the top to the bottom of the map view.
+    pub map_span: f32,
+    /// Multiple of how hard the forces on the plane shake the camera, with none at zero.
+    pub shake: f32,
+}
+
+impl Default for CameraSettings {
+    fn default() -> Self {
+        Self {
+            lag: 0.3,
+            fov: 45.0,
+            distance: 30.0,
+            map_span: 2000.0,
+            shake: 1.0,
+        }
     }
 }
 
+/// Where the camera sits in one of the views fixed to the plane.
+#[derive(Clone, Copy, PartialEq)]
+pub struct FollowOffset {
+    /// Position relative to the plane.
+    pub translation: Vec3,
+    /// Pitch and yaw in degrees relative to the plane, applied yaw first.
+    pub pitch: f32,
+    pub yaw: f32,
+    /// Vertical field of view in degrees.
+    pub fov: f32,
+    /// Time constant in seconds for the camera to catch up with the plane, with zero holding it
+    /// rigidly to the plane.
+    pub smoothing: f32,
+}
+
+impl FollowOffset {
+    fn new(translation: Vec3, pitch: f32, yaw: f32) -> Self {
+        Self {
+            translation,
+            pitch,
+            yaw,
+            fov: 45.0,
+            smoothing: 0.0,
+        }
+    }
+
+    /// Transform of the camera relative to the plane.
+    pub fn transform(&self) -> Transform {
+        Transform::from_translation(self.translation).with_rotation(Quat::from_euler(
+            EulerRot::YXZ,
+            self.yaw.to_radians(),
+            self.pitch.to_radians(),
+            0.0,
+        ))
+    }
+}
+
+/// Placement of the camera in each of the views fixed to the plane.
+#[derive(Resource, Clone, PartialEq)]
+pub struct FollowSettings {
+    pub behind: FollowOffset,
+    pub above: FollowOffset,
+    pub side: FollowOffset,
+    pub inside: FollowOffset,
+    pub wing: FollowOffset,
+}
+
+impl Default for FollowSettings {
+    fn default() -> Self {
+        Self {
+            behind: FollowOffset::new(Vec3::new(0., 5.0, 30.), 0.0, 0.0),
+            above: FollowOffset::new(Vec3::new(0., 150.0, 0.), -90.0, 0.0),
+            side: FollowOffset::new(Vec3::new(50., 0.0, 0.), 0.0, 90.0),
+            inside: FollowOffset::new(COCKPIT_EYE, 0.0, 0.0),
+            wing: FollowOffset::new(Vec3::new(7.0, 0.3, 0.0), 0.0, 90.0),
+        }
+    }
+}
+
+impl FollowSettings {
+    /// Placement for a view fixed to the plane, or `None` for the views that move on their own.
+    pub fn offset(&self, kind: &FollowKind) -> Option<&FollowOffset> {
+        match kind {
+            FollowKind::Behind => Some(&self.behind),
+            FollowKind::Above => Some(&self.above),
+            FollowKind::Side => Some(&self.side),
+            FollowKind::Inside => Some(&self.inside),
+            FollowKind::Wing => Some(&self.wing),
+            FollowKind::Orbit | FollowKind::Padlock | FollowKind::Map => None,
+        }
+    }
+}
+
+const ORBIT_SENSITIVITY: f32 = 0.005;
+/// Load factors beyond which the pilot's vision starts to fade, greying out above and reddening
+/// below.
+const GREYOUT_ONSET: f32 = 4.5;
+const REDOUT_ONSET: f32 = -2.5;
+/// Fade in vision each second for each g beyond the onset.
+const VISION_FADE_RATE: f32 = 0.25;
+/// Recovery in vision each second once the load is back within the onsets.
+const VISION_RECOVERY_RATE: f32 = 0.3;
+/// Size of the overlay covering the screen, larger than any window.
+const OVERLAY_SIZE: f32 = 10000.0;
+/// Distance to the far clipping plane, out past the corners of the low detail chunks.
+const FAR_PLANE: f32 = 16000.0;
+/// Height of the map view above the plane, clear of the tallest towers when flying low.
+const MAP_CAMERA_HEIGHT: f32 = 1000.0;
+
 #[derive(Component)]
 pub struct MainCamera;
 
 #[derive(Component)]
 pub struct Follow(pub FollowKind);
 
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum FollowKind {
     Behind,
     Above,
     Side,
     Inside,
+    Orbit,
+    /// Out past the right wingtip looking in along the wing, to watch its angle of attack.
+    Wing,
+    /// Looking straight down on the plane with north up and no perspective, for planning a route
+    /// through the city.
+    Map,
+    /// Behind the plane on the line from the target picked with `CycleLookTarget`, looking past it
+    /// to the target, for flying in formation or filming.
+    Padlock,
+}
+
+/// Camera placed by the `FollowSettings` for a view fixed to the plane.
+#[derive(Component)]
+pub struct FollowCamera;
+
+/// Free-look camera that follows the plane with lag rather than being parented to it.
+#[derive(Component, Default)]
+pub struct OrbitCamera {
+    /// Rotation around the plane relative to its heading.
+    pub yaw: f32,
+    pub pitch: f32,
 }
 
+/// Top-down orthographic camera that keeps above the plane without turning with it.
+#[derive(Component)]
+pub struct MapCamera;
+
+/// How far the pilot's vision has faded under sustained g, from clear at zero to gone at one.
+#[derive(Resource, Default)]
+pub struct PilotVision {
+    pub greyout: f32,
+    pub redout: f32,
+}
+
+#[derive(Component)]
+struct VisionOverlay;
+
 fn setup(mut commands: Commands) {
     commands.spawn(Camera2dBundle {
         camera_2d: Camera2d {
@@ -33,11 +240,87 @@ fn setup(mut commands: Commands) {
         },
         ..default()
     });
+
+    // In front of the HUD, which fades with everything else
+    commands.spawn((
+        VisionOverlay,
+        SpriteBundle {
+            sprite: Sprite {
+                color: Color::NONE,
+                custom_size: Some(Vec2::splat(OVERLAY_SIZE)),
+                ..default()
+            },
+            transform: Transform::from_xyz(0., 0., 100.),
+            visibility: Visibility::Hidden,
+            ..default()
+        },
+    ));
+}
+
+/// Fades the pilot's vision while the load factor is beyond the onsets, recovering once it's back.
+fn update_pilot_vision(
+    time: Res<Time>,
+    limits: Res<GForceLimits>,
+    plane_query: Query<&LoadFactor, With<Plane>>,
+    mut vision: ResMut<PilotVision>,
+) {
+    let Ok(load_factor) = plane_query.get_single() else {
+        return;
+    };
+    if !limits.physiological {
+        if vision.greyout > 0.0 || vision.redout > 0.0 {
+            *vision = PilotVision::default();
+        }
+        return;
+    }
+
+    let fade = |level: f32, excess: f32| {
+        let rate = if excess > 0.0 {
+            excess * VISION_FADE_RATE
+        } else {
+            -VISION_RECOVERY_RATE
+        };
+        (level + rate * time.delta_seconds()).clamp(0.0, 1.0)
+    };
+    let greyout = fade(vision.greyout, load_factor.g - GREYOUT_ONSET);
+    let redout = fade(vision.redout, REDOUT_ONSET - load_factor.g);
+
+    if greyout != vision.greyout || redout != vision.redout {
+        vision.greyout = greyout;
+        vision.redout = redout;
+    }
+}
+
+/// Greys the screen and then blacks it out as vision fades under positive g, or reddens it under
+/// negative g.
+fn update_vision_overlay(
+    vision: Res<PilotVision>,
+    mut overlay_query: Query<(&mut Sprite, &mut Visibility), With<VisionOverlay>>,
+) {
+    if !vision.is_changed() {
+        return;
+    }
+    let Ok((mut sprite, mut visibility)) = overlay_query.get_single_mut() else {
+        return;
+    };
+
+    sprite.color = if vision.greyout >= vision.redout {
+        let grey = 0.3 * (1.0 - vision.greyout);
+        Color::rgba(grey, grey, grey, vision.greyout)
+    } else {
+        Color::rgba(0.6, 0.0, 0.0, vision.redout * 0.9)
+    };
+    *visibility = if vision.greyout > 0.0 || vision.redout > 0.0 {
+        Visibility::Inherited
+    } else {
+        Visibility::Hidden
+    };
 }
 
 #[derive(Component)]
 pub struct FogControl {
     pub visibility: f32,
+    pub color: Color,
     pub extinction_color: Color,
     pub inscattering_color: Color,
 }
@@ -53,15 +336,18 @@ fn update_fog(mut query: Query<(&mut FogSettings, &FogControl), Changed<FogContr
         fog_control.inscattering_color,
     );
 
+    fog_settings.color = fog_control.color;
     fog_settings.falloff = new_falloff;
 }
 
 fn attach_to_follow(
     mut commands: Commands,
-    follow_query: Query<(Entity, &Follow), Changed<Follow>>,
+    follow_query: Query<(Entity, &Follow, &GlobalTransform), Changed<Follow>>,
     camera_query: Query<Entity, With<MainCamera>>,
+    settings: Res<CameraSettings>,
+    follow_settings: Res<FollowSettings>,
 ) {
-    let Ok((follow_entity, Follow(follow_kind))) = follow_query.get_single() else {
+    let Ok((follow_entity, Follow(follow_kind), follow_tx)) = follow_query.get_single() else {
         return;
     };
 
@@ -75,43 +361,84 @@ fn attach_to_follow(
     let mut camera_tx = Transform::default();
 
     match follow_kind {
-        FollowKind::Behind => {
-            info!("Follow behind");
-            camera_tx.translation = Vec3::new(0., 5.0, 30.);
-            camera_tx.rotation = Quat::default();
-        }
-        FollowKind::Above => {
-            info!("Follow above");
-            camera_tx.translation = Vec3::new(0., 150.0, 0.);
+        FollowKind::Behind
+        | FollowKind::Above
+        | FollowKind::Side
+        | FollowKind::Inside
+        | FollowKind::Wing => {
+            info!("Follow {:?}", follow_kind);
+            if let Some(offset) = follow_settings.offset(follow_kind) {
+                camera_tx = offset.transform();
+            }
+        }
+        FollowKind::Orbit => {
+            info!("Follow orbit");
+            camera_tx.translation = follow_tx.translation() + follow_tx.back() * 30.;
+            camera_tx.look_at(follow_tx.translation(), Vec3::Y);
+        }
+        FollowKind::Padlock => {
+            info!("Follow padlock");
+            camera_tx.translation = follow_tx.translation() + follow_tx.back() * settings.distance;
+            camera_tx.look_at(follow_tx.translation(), Vec3::Y);
+        }
+        FollowKind::Map => {
+            info!("Follow map");
+            camera_tx.translation = follow_tx.translation() + Vec3::Y * MAP_CAMERA_HEIGHT;
             camera_tx.rotation = Quat::from_rotation_x(-90_f32.to_radians());
         }
-        FollowKind::Side => {
-            info!("Follow side");
-            camera_tx.translation = Vec3::new(50., 0.0, 0.);
-            camera_tx.rotation = Quat::from_rotation_y(90_f32.to_radians());
-        }
-        FollowKind::Inside => {
-            info!("Follow inside");
-            camera_tx.translation = Vec3::new(0., 0., 0.);
-            camera_tx.rotation = Quat::default();
-        }
-    };
-
-    commands
-        .spawn((
-            MainCamera,
-            Camera3dBundle {
-                camera_3d: Camera3d {
-                    clear_color: ClearColorConfig::Custom(Color::rgb(0.5, 0.5, 0.8)),
-                    ..default()
-                },
-                camera: Camera {
-                    order: 0,
-                    ..default()
-                },
-                transform: camera_tx,
+    };
+
+    let projection = match follow_kind {
+        FollowKind::Map => OrthographicProjection {
+            far: FAR_PLANE,
+            scaling_mode: ScalingMode::FixedVertical(settings.map_span),
+            ..default()
+        }
+        .into(),
+        _ => PerspectiveProjection {
+            far: FAR_PLANE,
+            ..default()
+        }
+        .into(),
+    };
+
+    let mut camera = commands.spawn((
+        MainCamera,
+        CameraShake::default(),
+        Camera3dBundle {
+            camera_3d: Camera3d {
+                clear_color: ClearColorConfig::Default,
                 ..default()
             },
+            camera: Camera {
+                order: 0,
+                ..default()
+            },
+            projection,
+            transform: camera_tx,
+            ..default()
+        },
+    ));
+
+    match follow_kind {
+        FollowKind::Orbit => {
+            camera.insert(OrbitCamera::default());
+        }
+        FollowKind::Padlock => {
+            camera.insert(PadlockCamera);
+        }
+        FollowKind::Map => {
+            camera.insert(MapCamera);
+        }
+        _ => {
+            // Smoothed views are let go of the plane once they start catching up with it
+            camera.insert(FollowCamera).set_parent(follow_entity);
+        }
+    }
+
+    // The map looks down from far enough that fog would hide the ground
+    if !matches!(follow_kind, FollowKind::Map) {
+        camera.insert((
             FogSettings {
                 color: Color::rgba(0.1, 0.2, 0.4, 1.0),
                 directional_light_color: Color::rgba(1.0, 0.95, 0.75, 0.5),
@@ -124,11 +451,133 @@ fn attach_to_follow(
             },
             FogControl {
                 visibility: 1500.0,
+                color: Color::rgba(0.1, 0.2, 0.4, 1.0),
                 extinction_color: Color::rgb(0.35, 0.5, 0.66), // atmospheric extinction color (after light is lost due to absorption by atmospheric particles)
                 inscattering_color: Color::rgb(0.8, 0.844, 1.0), // atmospheric inscattering color (light gained due to scattering from the sun)
             },
-        ))
-        .set_parent(follow_entity);
+        ));
+    }
 
     info!("Following {:?}", follow_entity);
 }
+
+fn update_orbit_camera(
+    mut contexts: EguiContexts,
+    mut mouse_motion: EventReader<MouseMotion>,
+    mouse_buttons: Res<Input<MouseButton>>,
+    follow_query: Query<&GlobalTransform, With<Follow>>,
+    mut camera_query: Query<(&mut Transform, &mut OrbitCamera, &mut Projection)>,
+    settings: Res<CameraSettings>,
+    time: Res<Time>,
+) {
+    let Ok((mut camera_tx, mut orbit, mut projection)) = camera_query.get_single_mut() else {
+        mouse_motion.clear();
+        return;
+    };
+    let Ok(follow_tx) = follow_query.get_single() else {
+        return;
+    };
+
+    let dragging =
+        mouse_buttons.pressed(MouseButton::Right) && !contexts.ctx_mut().wants_pointer_input();
+
+    for motion in mouse_motion.iter() {
+        if dragging {
+            orbit.yaw -= motion.delta.x * ORBIT_SENSITIVITY;
+            orbit.pitch = (orbit.pitch - motion.delta.y * ORBIT_SENSITIVITY)
+                .clamp(-85_f32.to_radians(), 85_f32.to_radians());
+        }
+    }
+
+    let target = follow_tx.translation();
+    let forward = follow_tx.forward();
+    let heading = Quat::from_rotation_y((-forward.x).atan2(-forward.z));
+    let offset = heading
+        * Quat::from_rotation_y(orbit.yaw)
+        * Quat::from_rotation_x(orbit.pitch - 10_f32.to_radians())
+        * Vec3::Z
+        * settings.distance;
+
+    let smoothing = match settings.lag {
+        lag if lag > 0.0 => 1.0 - (-time.delta_seconds() / lag).exp(),
+        _ => 1.0,
+    };
+
+    camera_tx.translation = camera_tx.translation.lerp(target + offset, smoothing);
+    camera_tx.look_at(target, Vec3::Y);
+
+    if let Projection::Perspective(perspective) = projection.as_mut() {
+        perspective.fov = settings.fov.to_radians();
+    }
+}
+
+/// Places the camera in a view fixed to the plane from the `FollowSettings`, held rigidly as a
+/// child of the plane or let go to catch up with it when the view is smoothed.
+fn update_follow_camera(
+    mut commands: Commands,
+    follow_query: Query<(Entity, &Follow, &Transform), Without<FollowCamera>>,
+    mut camera_query: Query<
+        (Entity, &mut Transform, &mut Projection, Has<Parent>),
+        With<FollowCamera>,
+    >,
+    settings: Res<FollowSettings>,
+    time: Res<Time>,
+) {
+    let Ok((camera, mut camera_tx, mut projection, parented)) = camera_query.get_single_mut()
+    else {
+        return;
+    };
+    let Ok((plane, Follow(follow_kind), follow_tx)) = follow_query.get_single() else {
+        return;
+    };
+    let Some(offset) = settings.offset(follow_kind) else {
+        return;
+    };
+
+    if offset.smoothing <= 0.0 {
+        if !parented {
+            commands.entity(camera).set_parent(plane);
+        }
+        *camera_tx = offset.transform();
+    } else {
+        let target = follow_tx.mul_transform(offset.transform());
+        if parented {
+            commands.entity(camera).remove_parent();
+            *camera_tx = target;
+        } else {
+            let smoothing = 1.0 - (-time.delta_seconds() / offset.smoothing).exp();
+            camera_tx.translation = camera_tx.translation.lerp(target.translation, smoothing);
+            camera_tx.rotation = camera_tx.rotation.slerp(target.rotation, smoothing);
+        }
+    }
+
+    if let Projection::Perspective(perspective) = projection.as_mut() {
+        let fov = offset.fov.to_radians();
+        if perspective.fov != fov {
+            perspective.fov = fov;
+        }
+    }
+}
+
+/// Keeps the map camera above the plane, showing as much of the world as the settings ask for.
+fn update_map_camera(
+    follow_query: Query<&GlobalTransform, With<Follow>>,
+    mut camera_query: Query<(&mut Transform, &mut Projection), With<MapCamera>>,
+    settings: Res<CameraSettings>,
+) {
+    let Ok((mut camera_tx, mut projection)) = camera_query.get_single_mut() else {
+        return;
+    };
+    let Ok(follow_tx) = follow_query.get_single() else {
+        return;
+    };
+
+    camera_tx.translation = follow_tx.translation() + Vec3::Y * MAP_CAMERA_HEIGHT;
+
+    if let Projection::Orthographic(orthographic) = projection.as_mut() {
+        if !matches!(orthographic.scaling_mode, ScalingMode::FixedVertical(span) if span == settings.map_span)
+        {
+            orthographic.scaling_mode = ScalingMode::FixedVertical(settings.map_span);
+        }
+    }
+}
@@ -0,0 +1,125 @@
+use bevy::prelude::*;
+use leafwing_input_manager::prelude::*;
+
+use crate::input::PlaneAction;
+
+use super::{CameraSettings, Follow};
+
+/// Height of the padlock camera above the line from the target through the plane, as a fraction
+/// of its distance, so the plane sits just below the target rather than covering it.
+const PADLOCK_RISE: f32 = 0.25;
+/// Distance ahead of the plane looked at when there's nothing to look at.
+const NO_TARGET_DISTANCE: f32 = 100.0;
+
+/// Something worth keeping in view from the padlock camera, like a waypoint, another plane or a
+/// landmark, named for the log when it's picked.
+#[derive(Component)]
+pub struct LookTarget(pub &'static str);
+
+/// Target the padlock camera looks towards, kept while switching views.
+#[derive(Resource, Default)]
+pub struct PadlockTarget(pub Option<Entity>);
+
+/// Camera that keeps behind the plane on the line from its target, so both stay in frame.
+#[derive(Component)]
+pub struct PadlockCamera;
+
+/// Picks the next target out from the plane, going back to the nearest after the furthest.
+pub fn cycle_look_target(
+    action_query: Query<&ActionState<PlaneAction>>,
+    follow_query: Query<&GlobalTransform, With<Follow>>,
+    target_query: Query<(Entity, &GlobalTransform, &LookTarget)>,
+    mut padlock_target: ResMut<PadlockTarget>,
+) {
+    let Ok(action_state) = action_query.get_single() else {
+        return;
+    };
+    if !action_state.just_pressed(PlaneAction::CycleLookTarget) {
+        return;
+    }
+    let Ok(follow_tx) = follow_query.get_single() else {
+        return;
+    };
+
+    let mut targets: Vec<_> = target_query
+        .iter()
+        .map(|(entity, target_tx, target)| {
+            let distance = target_tx.translation().distance(follow_tx.translation());
+            (entity, distance, target.0)
+        })
+        .collect();
+    targets.sort_by(|a, b| a.1.total_cmp(&b.1));
+
+    let next = padlock_target
+        .0
+        .and_then(|current| targets.iter().position(|(entity, ..)| *entity == current))
+        .map_or(0, |index| index + 1);
+    let Some(&(entity, distance, name)) = targets.get(next).or(targets.first()) else {
+        info!("Nothing to look at");
+        return;
+    };
+
+    info!("Looking at {} {:.0} m away", name, distance);
+    padlock_target.0 = Some(entity);
+}
+
+/// Moves the padlock camera behind the plane from its target, turning smoothly to look at the
+/// target. A target that's gone, like a tower streamed out, is swapped for the nearest one.
+pub fn update_padlock_camera(
+    follow_query: Query<&GlobalTransform, With<Follow>>,
+    target_query: Query<(Entity, &GlobalTransform), With<LookTarget>>,
+    mut camera_query: Query<(&mut Transform, &mut Projection), With<PadlockCamera>>,
+    mut padlock_target: ResMut<PadlockTarget>,
+    settings: Res<CameraSettings>,
+    time: Res<Time>,
+) {
+    let Ok((mut camera_tx, mut projection)) = camera_query.get_single_mut() else {
+        return;
+    };
+    let Ok(follow_tx) = follow_query.get_single() else {
+        return;
+    };
+    let plane = follow_tx.translation();
+
+    if padlock_target
+        .0
+        .is_none_or(|target| !target_query.contains(target))
+    {
+        padlock_target.0 = target_query
+            .iter()
+            .min_by(|(_, a), (_, b)| {
+                a.translation()
+                    .distance_squared(plane)
+                    .total_cmp(&b.translation().distance_squared(plane))
+            })
+            .map(|(entity, _)| entity);
+    }
+
+    let target = padlock_target
+        .0
+        .and_then(|target| target_query.get(target).ok())
+        .map_or(
+            plane + follow_tx.forward() * NO_TARGET_DISTANCE,
+            |(_, target_tx)| target_tx.translation(),
+        );
+
+    let towards = (target - plane)
+        .try_normalize()
+        .unwrap_or(follow_tx.forward());
+    let position = plane - towards * settings.distance + Vec3::Y * settings.distance * PADLOCK_RISE;
+    let rotation = Transform::from_translation(position)
+        .looking_at(target, Vec3::Y)
+        .rotation;
+
+    let smoothing = match settings.lag {
+        lag if lag > 0.0 => 1.0 - (-time.delta_seconds() / lag).exp(),
+        _ => 1.0,
+    };
+
+    camera_tx.translation = camera_tx.translation.lerp(position, smoothing);
+    camera_tx.rotation = camera_tx.rotation.slerp(rotation, smoothing);
+
+    if let Projection::Perspective(perspective) = projection.as_mut() {
+        perspective.fov = settings.fov.to_radians();
+    }
+}
@@ -0,0 +1,125 @@
+use bevy::{
+    core_pipeline::clear_color::ClearColorConfig,
+    prelude::*,
+    render::{
+        camera::RenderTarget,
+        render_resource::{
+            Extent3d, TextureDescriptor, TextureDimension, TextureFormat, TextureUsages,
+        },
+    },
+};
+
+use super::{Follow, FollowKind, FollowSettings, MainCamera, FAR_PLANE};
+
+/// Size in pixels of the texture the picture-in-picture view is drawn to.
+pub const PIP_SIZE: UVec2 = UVec2::new(480, 270);
+
+/// Second view of the plane drawn to a texture, for watching one view while flying from another.
+#[derive(Resource)]
+pub struct PipView {
+    /// Whether the view is drawn at all, so it costs nothing while it isn't shown.
+    pub enabled: bool,
+    /// View to draw, one of those fixed to the plane.
+    pub kind: FollowKind,
+    pub image: Handle<Image>,
+}
+
+#[derive(Component)]
+pub struct PipCamera;
+
+pub fn setup_pip(mut commands: Commands, mut images: ResMut<Assets<Image>>) {
+    let size = Extent3d {
+        width: PIP_SIZE.x,
+        height: PIP_SIZE.y,
+        ..default()
+    };
+    let mut image = Image {
+        texture_descriptor: TextureDescriptor {
+            label: Some("pip"),
+            size,
+            dimension: TextureDimension::D2,
+            format: TextureFormat::Bgra8UnormSrgb,
+            mip_level_count: 1,
+            sample_count: 1,
+            usage: TextureUsages::TEXTURE_BINDING
+                | TextureUsages::COPY_DST
+                | TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[],
+        },
+        ..default()
+    };
+    image.resize(size);
+    let image = images.add(image);
+
+    commands.spawn((
+        PipCamera,
+        Camera3dBundle {
+            camera_3d: Camera3d {
+                clear_color: ClearColorConfig::Default,
+                ..default()
+            },
+            camera: Camera {
+                // Drawn before the main camera, and only while the view is shown
+                order: -1,
+                target: RenderTarget::Image(image.clone()),
+                is_active: false,
+                ..default()
+            },
+            projection: PerspectiveProjection {
+                far: FAR_PLANE,
+                ..default()
+            }
+            .into(),
+            ..default()
+        },
+    ));
+
+    commands.insert_resource(PipView {
+        enabled: false,
+        kind: FollowKind::Wing,
+        image,
+    });
+}
+
+/// Places the picture-in-picture camera on the plane from the `FollowSettings` for its view, held
+/// rigidly whatever the view's smoothing, and gives it the main camera's fog.
+#[allow(clippy::type_complexity)]
+pub fn update_pip_camera(
+    mut commands: Commands,
+    pip: Res<PipView>,
+    settings: Res<FollowSettings>,
+    follow_query: Query<&Transform, (With<Follow>, Without<PipCamera>)>,
+    main_fog_query: Query<&FogSettings, (With<MainCamera>, Changed<FogSettings>)>,
+    mut camera_query: Query<
+        (Entity, &mut Camera, &mut Transform, &mut Projection),
+        (With<PipCamera>, Without<MainCamera>),
+    >,
+) {
+    let Ok((entity, mut camera, mut camera_tx, mut projection)) = camera_query.get_single_mut()
+    else {
+        return;
+    };
+
+    if let Ok(main_fog) = main_fog_query.get_single() {
+        commands.entity(entity).insert(main_fog.clone());
+    }
+
+    let offset = settings.offset(&pip.kind).filter(|_| pip.enabled);
+    let follow_tx = follow_query.get_single().ok();
+    let active = offset.is_some() && follow_tx.is_some();
+    if camera.is_active != active {
+        camera.is_active = active;
+    }
+    let (Some(offset), Some(follow_tx)) = (offset, follow_tx) else {
+        return;
+    };
+
+    *camera_tx = follow_tx.mul_transform(offset.transform());
+
+    if let Projection::Perspective(perspective) = projection.as_mut() {
+        let fov = offset.fov.to_radians();
+        if perspective.fov != fov {
+            perspective.fov = fov;
+        }
+    }
+}
@@ -0,0 +1,227 @@
+use bevy::prelude::*;
+use bevy_rapier3d::{prelude::*, rapier::geometry::CollisionEventFlags};
+use noise::{NoiseFn, Perlin};
+
+use crate::{
+    damage::PreviousVelocity,
+    flight_model::{Airspeed, PlaneFlight, AIR_DENSITY},
+    plane::{flutter::Flutter, spec::PlaneSpec, Plane, Wheel},
+    world::Runway,
+};
+
+use super::{CameraSettings, Follow, MapCamera};
+
+/// Distance and angle in degrees the camera is shaken by at the worst of each source.
+const FLUTTER_SHAKE: (f32, f32) = (0.15, 0.3);
+const BUFFET_SHAKE: (f32, f32) = (0.05, 1.0);
+const PRESSURE_SHAKE: (f32, f32) = (0.02, 0.2);
+const GROUND_SHAKE: (f32, f32) = (0.04, 0.4);
+const IMPACT_SHAKE: (f32, f32) = (0.3, 3.0);
+/// Shakes per second of each source, low for the heavy ones and high for the buzz of the air.
+const FLUTTER_FREQUENCY: f64 = 20.0;
+const BUFFET_FREQUENCY: f64 = 7.0;
+const PRESSURE_FREQUENCY: f64 = 15.0;
+const GROUND_FREQUENCY: f64 = 11.0;
+const IMPACT_FREQUENCY: f64 = 5.0;
+/// Angle of attack in degrees before the stall at which the wings start to buffet, and the further
+/// angle past it over which the buffet builds to its worst.
+const BUFFET_ONSET: f32 = 2.0;
+const BUFFET_RANGE: f32 = 6.0;
+/// Airspeed in m/s over which the buffet builds, so a plane standing still doesn't shake.
+const BUFFET_AIRSPEED: f32 = 20.0;
+/// Fraction of the dynamic pressure at the never exceed speed where the air starts to buzz.
+const PRESSURE_ONSET: f32 = 0.5;
+/// Ground speed in m/s at which rolling over rough ground shakes the most.
+const GROUND_SPEED: f32 = 30.0;
+/// How much less a runway shakes the plane rolling along it than the rough ground.
+const RUNWAY_ROUGHNESS: f32 = 0.3;
+/// Change of speed in m/s from a jolt that shakes the camera the most.
+const IMPACT_SPEED: f32 = 8.0;
+/// Rate each second at which the shake from a jolt dies away.
+const IMPACT_DECAY: f32 = 3.0;
+
+/// Offset the camera is currently shaken by, taken off again before the camera is next moved.
+#[derive(Component, Default)]
+pub struct CameraShake {
+    translation: Vec3,
+    rotation: Quat,
+}
+
+/// Strength of the last jolt to the plane, from none at zero to the worst at one, dying away.
+#[derive(Resource, Default)]
+pub struct ShakeImpact(f32);
+
+/// Noise the shake is sampled from, smooth enough that each source rocks the camera rather than
+/// jittering it.
+#[derive(Resource)]
+pub struct ShakeNoise(Perlin);
+
+impl Default for ShakeNoise {
+    fn default() -> Self {
+        Self(Perlin::new(7))
+    }
+}
+
+/// Takes the last shake off the camera, so it's moved from where it would be without it.
+pub fn settle_camera(mut camera_query: Query<(&mut Transform, &mut CameraShake)>) {
+    for (mut camera_tx, mut shake) in camera_query.iter_mut() {
+        if shake.translation == Vec3::ZERO && shake.rotation == Quat::IDENTITY {
+            continue;
+        }
+        camera_tx.rotation *= shake.rotation.inverse();
+        let offset = camera_tx.rotation * shake.translation;
+        camera_tx.translation -= offset;
+        *shake = CameraShake::default();
+    }
+}
+
+/// Records how hard the plane was jolted by anything it hit, from how much its velocity changed
+/// over the physics step.
+pub fn record_impacts(
+    mut collision_events: EventReader<CollisionEvent>,
+    parent_query: Query<&Parent>,
+    plane_query: Query<(&Velocity, &PreviousVelocity), With<Plane>>,
+    mut impact: ResMut<ShakeImpact>,
+) {
+    for event in collision_events.iter() {
+        let CollisionEvent::Started(e1, e2, flags) = event else {
+            continue;
+        };
+        if flags.contains(CollisionEventFlags::SENSOR) {
+            continue;
+        }
+
+        for collider in [*e1, *e2] {
+            let Some((velocity, PreviousVelocity(previous))) = parent_query
+                .iter_ancestors(collider)
+                .find_map(|entity| plane_query.get(entity).ok())
+            else {
+                continue;
+            };
+            let jolt = ((velocity.linvel - *previous).length() / IMPACT_SPEED).min(1.0);
+            impact.0 = impact.0.max(jolt);
+        }
+    }
+}
+
+/// Shakes the camera with the forces on the plane it follows: the wings buffeting near the stall,
+/// the air buzzing at high speed, the wheels rolling over the ground, surfaces fluttering and
+/// anything the plane hits.
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+pub fn shake_camera(
+    time: Res<Time>,
+    settings: Res<CameraSettings>,
+    noise: Res<ShakeNoise>,
+    mut impact: ResMut<ShakeImpact>,
+    rapier_context: Res<RapierContext>,
+    follow_query: Query<
+        (
+            Entity,
+            &PlaneSpec,
+            &PlaneFlight,
+            &Airspeed,
+            &Velocity,
+            Option<&Flutter>,
+        ),
+        With<Follow>,
+    >,
+    children_query: Query<&Children>,
+    wheel_query: Query<(), With<Wheel>>,
+    runway_query: Query<(), With<Runway>>,
+    mut camera_query: Query<(&mut Transform, &mut CameraShake), Without<MapCamera>>,
+) {
+    impact.0 *= (-IMPACT_DECAY * time.delta_seconds()).exp();
+
+    let Ok((mut camera_tx, mut shake)) = camera_query.get_single_mut() else {
+        return;
+    };
+    let Ok((plane, spec, flight, Airspeed(airspeed), velocity, flutter)) =
+        follow_query.get_single()
+    else {
+        return;
+    };
+
+    let stall = stall_angle(spec);
+    let buffet = ((flight.angle_of_attack.to_degrees().abs() - stall + BUFFET_ONSET)
+        / BUFFET_RANGE)
+        .clamp(0.0, 1.0)
+        * (airspeed / BUFFET_AIRSPEED).min(1.0);
+
+    let pressure = 0.5 * AIR_DENSITY * airspeed * airspeed;
+    let max_pressure = 0.5 * AIR_DENSITY * spec.never_exceed_speed.powi(2);
+    let pressure =
+        ((pressure / max_pressure - PRESSURE_ONSET) / (1.0 - PRESSURE_ONSET)).clamp(0.0, 1.0);
+
+    // The roughest surface under any wheel sets how much the rolling shakes
+    let roughness = children_query
+        .iter_descendants(plane)
+        .filter(|child| wheel_query.contains(*child))
+        .flat_map(|wheel| rapier_context.contacts_with(wheel))
+        .filter(|pair| pair.has_any_active_contacts())
+        .map(|pair| {
+            let touching = [pair.collider1(), pair.collider2()];
+            if touching.iter().any(|entity| runway_query.contains(*entity)) {
+                RUNWAY_ROUGHNESS
+            } else {
+                1.0
+            }
+        })
+        .reduce(f32::max)
+        .unwrap_or(0.0);
+    let ground = roughness * (velocity.linvel.length() / GROUND_SPEED).min(1.0);
+
+    let flutter = flutter.map_or(0.0, |flutter| flutter.intensity);
+
+    let t = time.elapsed_seconds_wrapped_f64();
+    let mut translation = Vec3::ZERO;
+    let mut angles = Vec3::ZERO;
+    for (index, (level, (distance, angle), frequency)) in [
+        (flutter, FLUTTER_SHAKE, FLUTTER_FREQUENCY),
+        (buffet, BUFFET_SHAKE, BUFFET_FREQUENCY),
+        (pressure, PRESSURE_SHAKE, PRESSURE_FREQUENCY),
+        (ground, GROUND_SHAKE, GROUND_FREQUENCY),
+        (impact.0, IMPACT_SHAKE, IMPACT_FREQUENCY),
+    ]
+    .into_iter()
+    .enumerate()
+    {
+        if level <= 0.0 {
+            continue;
+        }
+        // Each source and axis reads its own line through the noise
+        let sample = |axis: usize| {
+            noise
+                .0
+                .get([t * frequency, (index * 6 + axis) as f64 * 10.0]) as f32
+                * level
+        };
+        translation += Vec3::new(sample(0), sample(1), sample(2)) * distance;
+        angles += Vec3::new(sample(3), sample(4), sample(5)) * angle.to_radians();
+    }
+
+    let intensity = settings.shake;
+    if intensity <= 0.0 || (translation == Vec3::ZERO && angles == Vec3::ZERO) {
+        return;
+    }
+
+    shake.translation = translation * intensity;
+    shake.rotation = Quat::from_euler(
+        EulerRot::YXZ,
+        angles.y * intensity,
+        angles.x * intensity,
+        angles.z * intensity,
+    );
+    let offset = camera_tx.rotation * shake.translation;
+    camera_tx.translation += offset;
+    camera_tx.rotation *= shake.rotation;
+}
+
+/// Angle of attack in degrees where the main wings make the most lift.
+fn stall_angle(spec: &PlaneSpec) -> f32 {
+    spec.main_wing()
+        .lift_coefficient_curve
+        .iter()
+        .copied()
+        .reduce(|best, point| if point.0 > best.0 { point } else { best })
+        .map_or(15.0, |(_, angle)| angle)
+}
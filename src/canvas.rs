@@ -0,0 +1,171 @@
+use bevy::{
+    math::vec2,
+    prelude::*,
+    render::render_resource::{Extent3d, TextureDimension, TextureFormat},
+};
+
+/// Characters in a 3x5 pixel font, a row of three bits per byte from the top. Letters are all
+/// capitals, with lower case drawn the same.
+const GLYPHS: [(char, [u8; 5]); 50] = [
+    ('0', [0b111, 0b101, 0b101, 0b101, 0b111]),
+    ('1', [0b010, 0b110, 0b010, 0b010, 0b111]),
+    ('2', [0b111, 0b001, 0b111, 0b100, 0b111]),
+    ('3', [0b111, 0b001, 0b111, 0b001, 0b111]),
+    ('4', [0b101, 0b101, 0b111, 0b001, 0b001]),
+    ('5', [0b111, 0b100, 0b111, 0b001, 0b111]),
+    ('6', [0b111, 0b100, 0b111, 0b101, 0b111]),
+    ('7', [0b111, 0b001, 0b001, 0b001, 0b001]),
+    ('8', [0b111, 0b101, 0b111, 0b101, 0b111]),
+    ('9', [0b111, 0b101, 0b111, 0b001, 0b111]),
+    ('A', [0b010, 0b101, 0b111, 0b101, 0b101]),
+    ('B', [0b110, 0b101, 0b110, 0b101, 0b110]),
+    ('C', [0b011, 0b100, 0b100, 0b100, 0b011]),
+    ('D', [0b110, 0b101, 0b101, 0b101, 0b110]),
+    ('E', [0b111, 0b100, 0b110, 0b100, 0b111]),
+    ('F', [0b111, 0b100, 0b110, 0b100, 0b100]),
+    ('G', [0b011, 0b100, 0b101, 0b101, 0b011]),
+    ('H', [0b101, 0b101, 0b111, 0b101, 0b101]),
+    ('I', [0b111, 0b010, 0b010, 0b010, 0b111]),
+    ('J', [0b001, 0b001, 0b001, 0b101, 0b010]),
+    ('K', [0b101, 0b101, 0b110, 0b101, 0b101]),
+    ('L', [0b100, 0b100, 0b100, 0b100, 0b111]),
+    ('M', [0b101, 0b111, 0b111, 0b101, 0b101]),
+    ('N', [0b110, 0b101, 0b101, 0b101, 0b101]),
+    ('O', [0b010, 0b101, 0b101, 0b101, 0b010]),
+    ('P', [0b110, 0b101, 0b110, 0b100, 0b100]),
+    ('Q', [0b010, 0b101, 0b101, 0b110, 0b011]),
+    ('R', [0b110, 0b101, 0b110, 0b101, 0b101]),
+    ('S', [0b011, 0b100, 0b010, 0b001, 0b110]),
+    ('T', [0b111, 0b010, 0b010, 0b010, 0b010]),
+    ('U', [0b101, 0b101, 0b101, 0b101, 0b111]),
+    ('V', [0b101, 0b101, 0b101, 0b101, 0b010]),
+    ('W', [0b101, 0b101, 0b111, 0b111, 0b101]),
+    ('X', [0b101, 0b101, 0b010, 0b101, 0b101]),
+    ('Y', [0b101, 0b101, 0b010, 0b010, 0b010]),
+    ('Z', [0b111, 0b001, 0b010, 0b100, 0b111]),
+    ('-', [0b000, 0b000, 0b111, 0b000, 0b000]),
+    ('+', [0b000, 0b010, 0b111, 0b010, 0b000]),
+    ('=', [0b000, 0b111, 0b000, 0b111, 0b000]),
+    ('.', [0b000, 0b000, 0b000, 0b000, 0b010]),
+    (',', [0b000, 0b000, 0b000, 0b010, 0b100]),
+    (':', [0b000, 0b010, 0b000, 0b010, 0b000]),
+    ('/', [0b001, 0b001, 0b010, 0b100, 0b100]),
+    ('%', [0b101, 0b001, 0b010, 0b100, 0b101]),
+    ('(', [0b001, 0b010, 0b010, 0b010, 0b001]),
+    (')', [0b100, 0b010, 0b010, 0b010, 0b100]),
+    ('°', [0b010, 0b101, 0b010, 0b000, 0b000]),
+    ('²', [0b110, 0b010, 0b100, 0b110, 0b000]),
+    ('\'', [0b010, 0b010, 0b000, 0b000, 0b000]),
+    (' ', [0b000, 0b000, 0b000, 0b000, 0b000]),
+];
+
+/// Image drawn pixel by pixel on the CPU, for saving rather than showing in the game.
+pub struct Canvas {
+    pub width: u32,
+    pub height: u32,
+    pixels: Vec<[u8; 4]>,
+}
+
+impl Canvas {
+    pub fn new(width: u32, height: u32, color: [u8; 4]) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![color; (width * height) as usize],
+        }
+    }
+
+    /// Width of text drawn at a scale, including the gap after the last character.
+    pub fn text_width(text: &str, scale: u32) -> u32 {
+        text.chars().count() as u32 * 4 * scale
+    }
+
+    pub fn set(&mut self, x: i64, y: i64, color: [u8; 4]) {
+        if x >= 0 && y >= 0 && x < self.width as i64 && y < self.height as i64 {
+            self.pixels[(y as u32 * self.width + x as u32) as usize] = color;
+        }
+    }
+
+    pub fn blend(&mut self, x: u32, y: u32, color: [u8; 4], alpha: f32) {
+        let pixel = &mut self.pixels[(y * self.width + x) as usize];
+        for (channel, value) in pixel.iter_mut().zip(color).take(3) {
+            *channel = (*channel as f32 + (value as f32 - *channel as f32) * alpha) as u8;
+        }
+    }
+
+    /// Fills the pixels between two corners, always at least a pixel so small things show.
+    pub fn fill_rect(&mut self, min: Vec2, max: Vec2, color: [u8; 4]) {
+        let min = min.floor();
+        let max = max.ceil().max(min + 1.0);
+
+        for y in min.y as i64..max.y as i64 {
+            for x in min.x as i64..max.x as i64 {
+                self.set(x, y, color);
+            }
+        }
+    }
+
+    /// Outlines the pixels between two corners.
+    pub fn stroke_rect(&mut self, min: Vec2, max: Vec2, color: [u8; 4]) {
+        let corners = [min, vec2(max.x, min.y), max, vec2(min.x, max.y)];
+        for (i, from) in corners.iter().enumerate() {
+            self.line(*from, corners[(i + 1) % corners.len()], color);
+        }
+    }
+
+    pub fn line(&mut self, from: Vec2, to: Vec2, color: [u8; 4]) {
+        let steps = (to - from).abs().max_element().ceil().max(1.0);
+        for step in 0..=steps as u32 {
+            let pos = from.lerp(to, step as f32 / steps);
+            self.set(pos.x as i64, pos.y as i64, color);
+        }
+    }
+
+    /// Draws text with its top left corner at a pixel, skipping any characters missing from the
+    /// font.
+    pub fn draw_text(&mut self, x: u32, y: u32, text: &str, scale: u32, color: [u8; 4]) {
+        for (i, c) in text.chars().enumerate() {
+            let c = c.to_ascii_uppercase();
+            let Some((_, rows)) = GLYPHS.iter().find(|(glyph, _)| *glyph == c) else {
+                continue;
+            };
+            let left = x + i as u32 * 4 * scale;
+
+            for (row, bits) in rows.iter().enumerate() {
+                for column in 0..3 {
+                    if bits & (0b100 >> column) == 0 {
+                        continue;
+                    }
+                    for dy in 0..scale {
+                        for dx in 0..scale {
+                            self.set(
+                                (left + column * scale + dx) as i64,
+                                (y + row as u32 * scale + dy) as i64,
+                                color,
+                            );
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn save(self, path: &str) -> Result<(), String> {
+        let image = Image::new(
+            Extent3d {
+                width: self.width,
+                height: self.height,
+                depth_or_array_layers: 1,
+            },
+            TextureDimension::D2,
+            self.pixels.into_iter().flatten().collect(),
+            TextureFormat::Rgba8UnormSrgb,
+        );
+
+        image
+            .try_into_dynamic()
+            .map_err(|err| err.to_string())?
+            .save(path)
+            .map_err(|err| err.to_string())
+    }
+}
@@ -0,0 +1,127 @@
+use std::{fs, path::PathBuf};
+
+use bevy::{prelude::*, render::view::screenshot::ScreenshotManager, window::PrimaryWindow};
+use leafwing_input_manager::prelude::ActionState;
+
+use crate::input::PlaneAction;
+
+pub struct CapturePlugin;
+
+impl Plugin for CapturePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Recording>()
+            .add_event::<CapturedEvent>()
+            .add_systems(Update, capture_screen);
+    }
+}
+
+const CAPTURES_DIR: &str = "captures";
+
+/// Sent once a capture has started or finished, to let the pilot know where it went.
+#[derive(Event)]
+pub struct CapturedEvent(pub String);
+
+/// Sequence of frames being saved, one for every frame rendered, to be made into a video.
+#[derive(Resource, Default)]
+pub struct Recording {
+    dir: Option<PathBuf>,
+    frames: u32,
+}
+
+/// Saves a screenshot, or starts and stops saving every frame, with the capture keys.
+fn capture_screen(
+    action_query: Query<&ActionState<PlaneAction>>,
+    window_query: Query<Entity, With<PrimaryWindow>>,
+    mut screenshot_manager: ResMut<ScreenshotManager>,
+    mut recording: ResMut<Recording>,
+    mut captured_events: EventWriter<CapturedEvent>,
+    // Confirmation waits a frame, so it isn't in the screenshot it confirms
+    mut saved_screenshot: Local<Option<PathBuf>>,
+) {
+    let Ok(window) = window_query.get_single() else {
+        return;
+    };
+    if let Some(path) = saved_screenshot.take() {
+        captured_events.send(CapturedEvent(format!("Saved {}", path.display())));
+    }
+    let Ok(action_state) = action_query.get_single() else {
+        return;
+    };
+
+    if action_state.just_pressed(PlaneAction::Screenshot) {
+        let path = PathBuf::from(CAPTURES_DIR)
+            .join(format!("screenshot-{:04}.png", next_number("screenshot-")));
+        match fs::create_dir_all(CAPTURES_DIR) {
+            Ok(()) => {
+                if screenshot_manager
+                    .save_screenshot_to_disk(window, &path)
+                    .is_ok()
+                {
+                    *saved_screenshot = Some(path);
+                }
+            }
+            Err(err) => warn!("Failed to create {}: {}", CAPTURES_DIR, err),
+        }
+    }
+
+    if action_state.just_pressed(PlaneAction::ToggleRecording) {
+        match recording.dir.take() {
+            Some(dir) => {
+                info!("Recorded {} frames to {}", recording.frames, dir.display());
+                captured_events.send(CapturedEvent(format!(
+                    "Saved {} frames to {}",
+                    recording.frames,
+                    dir.display()
+                )));
+            }
+            None => {
+                let dir = PathBuf::from(CAPTURES_DIR)
+                    .join(format!("recording-{:04}", next_number("recording-")));
+                match fs::create_dir_all(&dir) {
+                    Ok(()) => {
+                        captured_events.send(CapturedEvent(format!(
+                            "Recording frames to {}",
+                            dir.display()
+                        )));
+                        *recording = Recording {
+                            dir: Some(dir),
+                            frames: 0,
+                        };
+                    }
+                    Err(err) => warn!("Failed to create {}: {}", dir.display(), err),
+                }
+            }
+        }
+    }
+
+    // A frame saved as a screenshot can't also be saved to the recording, so it skips that frame
+    let Some(dir) = recording.dir.clone() else {
+        return;
+    };
+    let path = dir.join(format!("frame-{:05}.png", recording.frames));
+    if screenshot_manager
+        .save_screenshot_to_disk(window, path)
+        .is_ok()
+    {
+        recording.frames += 1;
+    }
+}
+
+/// Number after the highest one already given to a capture with the prefix, so captures sort in
+/// the order they were taken. Reading the directory rather than the clock also works on the web,
+/// where there's no system time.
+fn next_number(prefix: &str) -> u32 {
+    let Ok(entries) = fs::read_dir(CAPTURES_DIR) else {
+        return 1;
+    };
+
+    entries
+        .filter_map(|entry| {
+            let name = entry.ok()?.file_name().into_string().ok()?;
+            let number = name.strip_prefix(prefix)?;
+            let number = number.split('.').next()?;
+            number.parse::<u32>().ok()
+        })
+        .max()
+        .map_or(1, |max| max + 1)
+}
@@ -0,0 +1,189 @@
+use std::f32::consts::PI;
+
+use bevy::{math::vec3, prelude::*};
+use bevy_rapier3d::prelude::*;
+
+use crate::{
+    flight_model::Airspeed,
+    plane::{attitude, Altitude, Plane},
+};
+
+pub struct CockpitPlugin;
+
+impl Plugin for CockpitPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, (build_cockpit, update_instruments));
+    }
+}
+
+/// Position of the pilot's eye relative to the plane.
+pub const COCKPIT_EYE: Vec3 = vec3(0., 0.6, -0.5);
+
+const PANEL_SIZE: Vec3 = vec3(1.0, 0.4, 0.05);
+const DIAL_RADIUS: f32 = 0.08;
+/// Airspeed in m/s at full deflection of the airspeed needle.
+const MAX_INDICATED_AIRSPEED: f32 = 100.0;
+/// Altitude in metres for one revolution of the altimeter needle.
+const ALTIMETER_REVOLUTION: f32 = 1000.0;
+/// Turn rate of a standard rate turn in radians per second.
+const STANDARD_RATE_TURN: f32 = 3.0 * PI / 180.0;
+
+#[derive(Component, Clone, Copy)]
+pub enum Instrument {
+    AttitudeHorizon,
+    Altimeter,
+    AirspeedIndicator,
+    TurnCoordinator,
+    SlipBall,
+}
+
+fn build_cockpit(
+    mut commands: Commands,
+    plane_query: Query<Entity, Added<Plane>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    for entity in plane_query.iter() {
+        let panel_material = materials.add(Color::rgb(0.15, 0.15, 0.15).into());
+        let dial_material = materials.add(Color::BLACK.into());
+        let needle_material = materials.add(StandardMaterial {
+            base_color: Color::WHITE,
+            unlit: true,
+            ..default()
+        });
+        let horizon_material = materials.add(StandardMaterial {
+            base_color: Color::ORANGE,
+            unlit: true,
+            ..default()
+        });
+
+        let dial_mesh = meshes.add(Mesh::from(shape::Circle::new(DIAL_RADIUS)));
+        let needle_mesh = meshes.add(Mesh::from(shape::Box::new(0.006, DIAL_RADIUS * 0.9, 0.002)));
+        let horizon_mesh = meshes.add(Mesh::from(shape::Box::new(DIAL_RADIUS * 1.8, 0.006, 0.002)));
+        let ball_mesh = meshes.add(Mesh::from(shape::UVSphere {
+            radius: 0.01,
+            ..default()
+        }));
+
+        let panel_pos = COCKPIT_EYE + vec3(0., -0.3, -0.7);
+        let dial_z = PANEL_SIZE.z * 0.5 + 0.001;
+
+        commands.entity(entity).with_children(|parent| {
+            parent
+                .spawn(PbrBundle {
+                    mesh: meshes.add(Mesh::from(shape::Box::new(
+                        PANEL_SIZE.x,
+                        PANEL_SIZE.y,
+                        PANEL_SIZE.z,
+                    ))),
+                    material: panel_material,
+                    transform: Transform::from_translation(panel_pos)
+                        .with_rotation(Quat::from_rotation_x(-15_f32.to_radians())),
+                    ..default()
+                })
+                .with_children(|panel| {
+                    let dials = [
+                        (-0.3, Instrument::AirspeedIndicator, &needle_mesh),
+                        (-0.1, Instrument::AttitudeHorizon, &horizon_mesh),
+                        (0.1, Instrument::Altimeter, &needle_mesh),
+                        (0.3, Instrument::TurnCoordinator, &horizon_mesh),
+                    ];
+
+                    for (x, instrument, indicator_mesh) in dials {
+                        panel
+                            .spawn(PbrBundle {
+                                mesh: dial_mesh.clone(),
+                                material: dial_material.clone(),
+                                transform: Transform::from_xyz(x, 0., dial_z),
+                                ..default()
+                            })
+                            .with_children(|dial| {
+                                // Needles pivot about the dial centre so are offset within their parent
+                                let offset = match instrument {
+                                    Instrument::AirspeedIndicator | Instrument::Altimeter => {
+                                        DIAL_RADIUS * 0.45
+                                    }
+                                    _ => 0.0,
+                                };
+
+                                dial.spawn((instrument, SpatialBundle::default()))
+                                    .with_children(|pivot| {
+                                        pivot.spawn(PbrBundle {
+                                            mesh: indicator_mesh.clone(),
+                                            material: match instrument {
+                                                Instrument::AttitudeHorizon => {
+                                                    horizon_material.clone()
+                                                }
+                                                _ => needle_material.clone(),
+                                            },
+                                            transform: Transform::from_xyz(0., offset, 0.002),
+                                            ..default()
+                                        });
+                                    });
+
+                                if let Instrument::TurnCoordinator = instrument {
+                                    dial.spawn((
+                                        Instrument::SlipBall,
+                                        PbrBundle {
+                                            mesh: ball_mesh.clone(),
+                                            material: needle_material.clone(),
+                                            transform: Transform::from_xyz(
+                                                0.,
+                                                -DIAL_RADIUS * 0.6,
+                                                0.01,
+                                            ),
+                                            ..default()
+                                        },
+                                    ));
+                                }
+                            });
+                    }
+                });
+        });
+    }
+}
+
+fn update_instruments(
+    plane_query: Query<(&GlobalTransform, &Velocity, &Airspeed, &Altitude), With<Plane>>,
+    mut instrument_query: Query<(&Instrument, &mut Transform)>,
+) {
+    let Ok((global_tx, velocity, Airspeed(airspeed), Altitude(altitude))) =
+        plane_query.get_single()
+    else {
+        return;
+    };
+
+    let right = global_tx.right();
+
+    let (pitch, roll) = attitude(global_tx);
+    let turn_rate = velocity.angvel.y;
+    let slip = match velocity.linvel.length() {
+        speed if speed > 1.0 => velocity.linvel.dot(right) / speed,
+        _ => 0.0,
+    };
+
+    for (instrument, mut tx) in instrument_query.iter_mut() {
+        match instrument {
+            Instrument::AttitudeHorizon => {
+                let offset = (-pitch / (PI * 0.5) * DIAL_RADIUS).clamp(-DIAL_RADIUS, DIAL_RADIUS);
+                tx.translation = vec3(0., offset, 0.);
+                tx.rotation = Quat::from_rotation_z(roll);
+            }
+            Instrument::Altimeter => {
+                let fraction = altitude.rem_euclid(ALTIMETER_REVOLUTION) / ALTIMETER_REVOLUTION;
+                tx.rotation = Quat::from_rotation_z(-fraction * 2.0 * PI);
+            }
+            Instrument::AirspeedIndicator => {
+                let fraction = (airspeed / MAX_INDICATED_AIRSPEED).clamp(0.0, 1.0);
+                tx.rotation = Quat::from_rotation_z(-fraction * 300_f32.to_radians());
+            }
+            Instrument::TurnCoordinator => {
+                let deflection = (turn_rate / STANDARD_RATE_TURN).clamp(-2.0, 2.0);
+                tx.rotation = Quat::from_rotation_z(deflection * 20_f32.to_radians());
+            }
+            Instrument::SlipBall => {
+                tx.translation.x = (slip * DIAL_RADIUS * 2.0).clamp(-DIAL_RADIUS, DIAL_RADIUS);
+            }
+        }
+    }
+}
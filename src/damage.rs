@@ -0,0 +1,343 @@
+use bevy::{prelude::*, utils::HashSet};
+use bevy_rapier3d::{prelude::*, rapier::geometry::CollisionEventFlags};
+
+use crate::{
+    flight_model::{Airfoil, Thrust},
+    physics::PHYSICS_DT,
+    plane::{flutter::Flutter, AirfoilPosition, ControlSurface, LoadFactor, Plane, SurfaceFailure},
+};
+
+pub struct DamagePlugin;
+
+impl Plugin for DamagePlugin {
+    fn build(&self, app: &mut App) {
+        // Collision events are only kept for a couple of physics steps, so read them every step
+        app.add_event::<CrashedEvent>()
+            .add_event::<DamageEvent>()
+            .init_resource::<GForceLimits>()
+            .add_systems(
+                FixedUpdate,
+                (
+                    apply_collision_damage,
+                    apply_damage_events,
+                    apply_overstress_damage,
+                    apply_flutter_damage,
+                    jam_damaged_control_surfaces,
+                    handle_destroyed_parts,
+                    track_previous_velocity,
+                )
+                    .chain()
+                    .after(PhysicsSet::Writeback),
+            );
+    }
+}
+
+const MAX_HEALTH: f32 = 100.0;
+/// Health lost for each m/s of impact speed above a part's tolerance.
+const DAMAGE_PER_IMPACT_SPEED: f32 = 20.0;
+/// Impact speed at which the plane is considered crashed regardless of part health.
+const CRASH_IMPACT_SPEED: f32 = 15.0;
+/// Load factors the wings are built to carry, beyond which they start to fail.
+pub const WING_LOAD_LIMITS: std::ops::RangeInclusive<f32> = -3.0..=6.0;
+/// Wing health lost each second for each g beyond the limits.
+const DAMAGE_PER_OVERSTRESS: f32 = 50.0;
+
+/// Health lost each second by every flying surface at the worst of flutter.
+const DAMAGE_PER_FLUTTER: f32 = 40.0;
+/// Health of an airfoil below which its control surface jams where it is.
+const JAM_HEALTH: f32 = 50.0;
+
+/// Which consequences of pulling too hard are simulated.
+#[derive(Resource)]
+pub struct GForceLimits {
+    /// Wings fail when loaded beyond their limits.
+    pub structural: bool,
+    /// The pilot's vision greys out and blacks out under sustained positive g, and reddens under
+    /// negative g.
+    pub physiological: bool,
+}
+
+impl Default for GForceLimits {
+    fn default() -> Self {
+        Self {
+            structural: true,
+            physiological: true,
+        }
+    }
+}
+
+#[derive(Event)]
+pub struct CrashedEvent {
+    pub impact_speed: f32,
+}
+
+#[derive(Component)]
+pub struct Crashed;
+
+/// Damage to a collider on the plane from something the physics doesn't resolve as an impact, like
+/// hitting a bird.
+#[derive(Event)]
+pub struct DamageEvent {
+    pub part: Entity,
+    pub damage: f32,
+}
+
+/// A collider on the plane that can take damage from impacts.
+#[derive(Component)]
+pub struct StructuralPart {
+    pub health: f32,
+    /// Speed of the most recent damaging impact.
+    pub last_impact_speed: f32,
+    /// Impact speed in m/s that the part can absorb without damage.
+    pub tolerance: f32,
+    /// Whether losing this part means the plane has crashed.
+    pub critical: bool,
+}
+
+impl StructuralPart {
+    pub fn new(tolerance: f32, critical: bool) -> Self {
+        Self {
+            health: MAX_HEALTH,
+            last_impact_speed: 0.0,
+            tolerance,
+            critical,
+        }
+    }
+}
+
+/// Plane velocity from the previous physics step, before the physics step resolved any contacts.
+#[derive(Component, Default)]
+pub struct PreviousVelocity(pub Vec3);
+
+pub fn track_previous_velocity(
+    mut plane_query: Query<(&Velocity, &mut PreviousVelocity), With<Plane>>,
+) {
+    for (velocity, mut previous) in plane_query.iter_mut() {
+        previous.0 = velocity.linvel;
+    }
+}
+
+fn apply_collision_damage(
+    mut collision_events: EventReader<CollisionEvent>,
+    rapier_context: Res<RapierContext>,
+    parent_query: Query<&Parent>,
+    plane_query: Query<&PreviousVelocity, With<Plane>>,
+    velocity_query: Query<&Velocity>,
+    mut part_query: Query<&mut StructuralPart>,
+) {
+    for event in collision_events.iter() {
+        let CollisionEvent::Started(e1, e2, flags) = event else {
+            continue;
+        };
+        // Flying through mission rings isn't an impact
+        if flags.contains(CollisionEventFlags::SENSOR) {
+            continue;
+        }
+
+        for (collider, other) in [(*e1, *e2), (*e2, *e1)] {
+            let Some(plane) = parent_query
+                .iter_ancestors(collider)
+                .find(|entity| plane_query.contains(*entity))
+            else {
+                continue;
+            };
+            let Ok(PreviousVelocity(plane_velocity)) = plane_query.get(plane) else {
+                continue;
+            };
+
+            let other_velocity = velocity_query
+                .get(other)
+                .map(|v| v.linvel)
+                .unwrap_or(Vec3::ZERO);
+            let relative_velocity = *plane_velocity - other_velocity;
+
+            // Only the velocity along the contact normal contributes to the impact
+            let impact_speed = rapier_context
+                .contact_pair(collider, other)
+                .and_then(|pair| {
+                    pair.manifolds()
+                        .map(|manifold| relative_velocity.dot(manifold.normal()).abs())
+                        .reduce(f32::max)
+                })
+                .unwrap_or(0.0);
+
+            let part_entity = std::iter::once(collider)
+                .chain(parent_query.iter_ancestors(collider))
+                .find(|entity| part_query.contains(*entity));
+
+            let Some(mut part) = part_entity.and_then(|entity| part_query.get_mut(entity).ok())
+            else {
+                continue;
+            };
+
+            let damage = (impact_speed - part.tolerance).max(0.0) * DAMAGE_PER_IMPACT_SPEED;
+
+            if damage > 0.0 {
+                part.health -= damage;
+                part.last_impact_speed = impact_speed;
+                info!(
+                    "Impact of {:.1} m/s caused {:.1} damage, health now {:.1}",
+                    impact_speed, damage, part.health
+                );
+            }
+
+            if impact_speed > CRASH_IMPACT_SPEED {
+                part.health = part.health.min(0.0);
+                part.last_impact_speed = impact_speed;
+            }
+        }
+    }
+}
+
+/// Applies damage sent as events to the structural part it hit, or the part holding it.
+fn apply_damage_events(
+    mut damage_events: EventReader<DamageEvent>,
+    parent_query: Query<&Parent>,
+    mut part_query: Query<&mut StructuralPart>,
+) {
+    for DamageEvent { part, damage } in damage_events.iter() {
+        let Some(mut part) = std::iter::once(*part)
+            .chain(parent_query.iter_ancestors(*part))
+            .find(|entity| part_query.contains(*entity))
+            .and_then(|entity| part_query.get_mut(entity).ok())
+        else {
+            continue;
+        };
+
+        part.health -= damage;
+        info!("Took {:.1} damage, health now {:.1}", damage, part.health);
+    }
+}
+
+/// Weakens the main wings while they're loaded beyond their limits, until they shear off.
+#[allow(clippy::type_complexity)]
+fn apply_overstress_damage(
+    limits: Res<GForceLimits>,
+    plane_query: Query<(Entity, &LoadFactor), (With<Plane>, Without<Crashed>)>,
+    children_query: Query<&Children>,
+    mut wing_query: Query<(&AirfoilPosition, &mut StructuralPart)>,
+) {
+    if !limits.structural {
+        return;
+    }
+
+    for (plane, load_factor) in plane_query.iter() {
+        let overstress = (load_factor.g - WING_LOAD_LIMITS.end())
+            .max(WING_LOAD_LIMITS.start() - load_factor.g)
+            .max(0.0);
+        if overstress <= 0.0 {
+            continue;
+        }
+
+        for child in children_query.iter_descendants(plane) {
+            let Ok((position, mut part)) = wing_query.get_mut(child) else {
+                continue;
+            };
+            if !matches!(
+                position,
+                AirfoilPosition::Wing(_) | AirfoilPosition::Elevon(_)
+            ) {
+                continue;
+            }
+
+            part.health -= overstress * DAMAGE_PER_OVERSTRESS * PHYSICS_DT;
+            if part.health <= 0.0 {
+                info!("Wing failed at {:.1} g", load_factor.g);
+            }
+        }
+    }
+}
+
+/// Shakes the flying surfaces apart while they flutter.
+#[allow(clippy::type_complexity)]
+fn apply_flutter_damage(
+    plane_query: Query<(Entity, &Flutter), (With<Plane>, Without<Crashed>)>,
+    children_query: Query<&Children>,
+    mut surface_query: Query<&mut StructuralPart, With<Airfoil>>,
+) {
+    for (plane, flutter) in plane_query.iter() {
+        if flutter.intensity <= 0.0 {
+            continue;
+        }
+
+        for child in children_query.iter_descendants(plane) {
+            if let Ok(mut part) = surface_query.get_mut(child) {
+                part.health -= flutter.intensity * DAMAGE_PER_FLUTTER * PHYSICS_DT;
+            }
+        }
+    }
+}
+
+/// Jams the control surface of an airfoil damaged past half its health at the angle it's at, so
+/// hitting something or fluttering can leave one side stuck while the other still works.
+#[allow(clippy::type_complexity)]
+fn jam_damaged_control_surfaces(
+    mut commands: Commands,
+    part_query: Query<(&StructuralPart, &Children), (With<Airfoil>, Changed<StructuralPart>)>,
+    surface_query: Query<&ControlSurface, Without<SurfaceFailure>>,
+) {
+    for (part, children) in part_query.iter() {
+        if part.health >= JAM_HEALTH {
+            continue;
+        }
+
+        for child in children.iter() {
+            if let Ok(surface) = surface_query.get(*child) {
+                info!(
+                    "Control surface jammed at {:.1}°",
+                    surface.angle.to_degrees()
+                );
+                commands
+                    .entity(*child)
+                    .insert(SurfaceFailure::Jammed(surface.angle));
+            }
+        }
+    }
+}
+
+fn handle_destroyed_parts(
+    mut commands: Commands,
+    part_query: Query<(Entity, &StructuralPart, Option<&Airfoil>), Changed<StructuralPart>>,
+    parent_query: Query<&Parent>,
+    mut plane_query: Query<&mut Thrust, (With<Plane>, Without<Crashed>)>,
+    mut crashed_event: EventWriter<CrashedEvent>,
+) {
+    // Crashed is only inserted once commands are applied, so another critical part of the same
+    // plane destroyed in this step would crash it again
+    let mut crashed = HashSet::new();
+
+    for (entity, part, airfoil) in part_query.iter() {
+        if part.health > 0.0 {
+            continue;
+        }
+
+        if airfoil.is_some() {
+            info!("Airfoil sheared off");
+            commands.entity(entity).despawn_recursive();
+        }
+
+        if !part.critical {
+            continue;
+        }
+
+        let Some(plane) = parent_query
+            .iter_ancestors(entity)
+            .find(|entity| plane_query.contains(*entity))
+        else {
+            continue;
+        };
+        if !crashed.insert(plane) {
+            continue;
+        }
+        let Ok(mut thrust) = plane_query.get_mut(plane) else {
+            continue;
+        };
+
+        info!("Plane crashed");
+        thrust.0 = 0.0;
+        commands.entity(plane).insert(Crashed);
+        crashed_event.send(CrashedEvent {
+            impact_speed: part.last_impact_speed,
+        });
+    }
+}
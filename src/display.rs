@@ -0,0 +1,165 @@
+use std::fs;
+
+use bevy::{
+    prelude::*,
+    window::{PresentMode, PrimaryWindow, WindowMode},
+};
+use bevy_egui::EguiSettings;
+use serde::{Deserialize, Serialize};
+
+use crate::units::Units;
+
+const DISPLAY_PATH: &str = "display.ron";
+
+pub struct DisplayPlugin;
+
+impl Plugin for DisplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DisplaySettings>()
+            .init_resource::<Units>()
+            .add_systems(
+                Update,
+                (apply_display_settings, apply_units).run_if(resource_changed::<DisplaySettings>()),
+            );
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+pub enum DisplayMode {
+    #[default]
+    Windowed,
+    Borderless,
+    Fullscreen,
+}
+
+impl DisplayMode {
+    pub const ALL: [DisplayMode; 3] = [Self::Windowed, Self::Borderless, Self::Fullscreen];
+
+    fn window_mode(&self) -> WindowMode {
+        match self {
+            Self::Windowed => WindowMode::Windowed,
+            Self::Borderless => WindowMode::BorderlessFullscreen,
+            // Sized so the chosen resolution is used rather than the monitor's best video mode
+            Self::Fullscreen => WindowMode::SizedFullscreen,
+        }
+    }
+}
+
+/// Resolutions offered in the settings window.
+pub const RESOLUTIONS: [(u32, u32); 5] = [
+    (1280, 720),
+    (1366, 768),
+    (1600, 900),
+    (1920, 1080),
+    (2560, 1440),
+];
+
+/// Window and UI options changed from the settings window and saved between runs.
+#[derive(Resource, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DisplaySettings {
+    pub mode: DisplayMode,
+    /// Size of the window, or of the screen in fullscreen mode.
+    pub resolution: (u32, u32),
+    pub vsync: bool,
+    /// Scale of the egui windows and panels.
+    pub ui_scale: f32,
+    #[serde(default)]
+    pub units: Units,
+}
+
+impl Default for DisplaySettings {
+    fn default() -> Self {
+        Self {
+            mode: DisplayMode::default(),
+            resolution: (1280, 720),
+            vsync: true,
+            ui_scale: 1.0,
+            units: Units::default(),
+        }
+    }
+}
+
+impl DisplaySettings {
+    /// Primary window to open with, so the saved mode is used from the first frame.
+    pub fn window(&self) -> Window {
+        let mut window = Window {
+            fit_canvas_to_parent: true,
+            ..default()
+        };
+        self.apply(&mut window);
+        window
+    }
+
+    fn apply(&self, window: &mut Window) {
+        window.mode = self.mode.window_mode();
+        window.present_mode = if self.vsync {
+            PresentMode::AutoVsync
+        } else {
+            PresentMode::AutoNoVsync
+        };
+
+        let (width, height) = (self.resolution.0 as f32, self.resolution.1 as f32);
+        if window.resolution.width() != width || window.resolution.height() != height {
+            window.resolution.set(width, height);
+        }
+    }
+}
+
+fn apply_display_settings(
+    settings: Res<DisplaySettings>,
+    mut egui_settings: ResMut<EguiSettings>,
+    mut window_query: Query<&mut Window, With<PrimaryWindow>>,
+) {
+    let Ok(mut window) = window_query.get_single_mut() else {
+        return;
+    };
+
+    info!(
+        "Applying display settings: {:?} {}x{} vsync={} ui scale={}",
+        settings.mode,
+        settings.resolution.0,
+        settings.resolution.1,
+        settings.vsync,
+        settings.ui_scale
+    );
+
+    settings.apply(&mut window);
+    egui_settings.scale_factor = settings.ui_scale as f64;
+}
+
+fn apply_units(settings: Res<DisplaySettings>, mut units: ResMut<Units>) {
+    units.set_if_neq(settings.units);
+}
+
+/// Reads display settings saved by the settings window, if there are any.
+pub fn load_display_settings() -> Option<DisplaySettings> {
+    let contents = fs::read_to_string(DISPLAY_PATH).ok()?;
+
+    match ron::from_str(&contents) {
+        Ok(settings) => {
+            info!("Loaded display settings from {}", DISPLAY_PATH);
+            Some(settings)
+        }
+        Err(err) => {
+            warn!(
+                "Ignoring invalid display settings in {}: {}",
+                DISPLAY_PATH, err
+            );
+            None
+        }
+    }
+}
+
+pub fn save_display_settings(settings: &DisplaySettings) {
+    let result = ron::ser::to_string_pretty(settings, ron::ser::PrettyConfig::default())
+        .map_err(|err| err.to_string())
+        .and_then(|contents| fs::write(DISPLAY_PATH, contents).map_err(|err| err.to_string()));
+
+    match result {
+        Ok(()) => info!("Saved display settings to {}", DISPLAY_PATH),
+        Err(err) => warn!(
+            "Failed to save display settings to {}: {}",
+            DISPLAY_PATH, err
+        ),
+    }
+}
@@ -0,0 +1,442 @@
+pub mod damping;
+
+use core::f32;
+use std::ops::AddAssign;
+
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+/// Aerodynamic forces on rigid bodies flying through the air, independent of how they're drawn or
+/// controlled, so any project can fly its own entities with it.
+///
+/// A body gets a `FlightBundle` alongside its Rapier rigid body, and each descendant entity with
+/// an `Airfoil` adds its lift and drag to the body, along with the damping of the body's rotation
+/// as the airfoil moves across the airflow. Bodies with a `ThrustPoint` are pushed forward by their
+/// `Thrust` from it. The game describes the air around each body in its `AmbientAir`, any local
+/// disturbance at each airfoil in its `induced_velocity`, and control surface deflections in its
+/// `lift_coefficient_modifier`, before `FlightModelSet::Forces` runs.
+///
+/// Forces accumulate on the body's `ExternalForce`, which is cleared at the start of
+/// `FlightModelSet::Forces`, so other forces on the body should be added after it.
+pub struct FlightModelPlugin;
+
+impl Plugin for FlightModelPlugin {
+    fn build(&self, app: &mut App) {
+        app.configure_sets(
+            FixedUpdate,
+            (FlightModelSet::Airflow, FlightModelSet::Forces)
+                .chain()
+                .before(PhysicsSet::SyncBackend),
+        )
+        .add_systems(
+            FixedUpdate,
+            (update_centre_of_gravity, update_airspeed)
+                .chain()
+                .in_set(FlightModelSet::Airflow),
+        )
+        .add_systems(
+            FixedUpdate,
+            (
+                reset_forces,
+                update_thrust_forces,
+                update_airfoil_forces,
+                damping::update_damping_forces,
+            )
+                .chain()
+                .in_set(FlightModelSet::Forces),
+        );
+    }
+}
+
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub enum FlightModelSet {
+    /// Measuring the centre of gravity and airspeed of each body, each physics step.
+    Airflow,
+    /// Clearing the forces on each body and accumulating its thrust and aerodynamic forces, each
+    /// physics step.
+    Forces,
+}
+
+/// Density of the air at sea level in kg/m³.
+pub const AIR_DENSITY: f32 = 1.225;
+/// Pressure of the air at sea level in pascals.
+pub const SEA_LEVEL_PRESSURE: f32 = 101_325.0;
+/// Fall in the temperature of the standard atmosphere with height, as a fraction of its sea level
+/// temperature per metre.
+const LAPSE_RATE: f32 = 2.25577e-5;
+/// Power of the temperature ratio that the pressure falls with in the standard atmosphere.
+const PRESSURE_EXPONENT: f32 = 5.25588;
+
+/// Density of the standard atmosphere in kg/m³ at a height in metres.
+pub fn air_density(altitude: f32) -> f32 {
+    AIR_DENSITY
+        * (1.0 - LAPSE_RATE * altitude)
+            .max(0.0)
+            .powf(PRESSURE_EXPONENT - 1.0)
+}
+
+/// Pressure of the standard atmosphere in pascals at a height in metres.
+pub fn static_pressure(altitude: f32) -> f32 {
+    SEA_LEVEL_PRESSURE
+        * (1.0 - LAPSE_RATE * altitude)
+            .max(0.0)
+            .powf(PRESSURE_EXPONENT)
+}
+
+/// Height in metres the standard atmosphere has a pressure at, as an altimeter reads it.
+fn pressure_altitude(pressure: f32) -> f32 {
+    (1.0 - (pressure / SEA_LEVEL_PRESSURE)
+        .max(0.0)
+        .powf(PRESSURE_EXPONENT.recip()))
+        / LAPSE_RATE
+}
+
+/// Components a rigid body needs to be flown by the flight model.
+#[derive(Bundle, Default)]
+pub struct FlightBundle {
+    pub flight: PlaneFlight,
+    pub airspeed: Airspeed,
+    pub pitot_static: PitotStatic,
+    pub ambient_air: AmbientAir,
+    pub centre_of_gravity: CentreOfGravity,
+    pub velocity: Velocity,
+    pub external_force: ExternalForce,
+    pub mass_properties: ReadMassProperties,
+}
+
+#[derive(Component, Default)]
+pub struct CentreOfGravity {
+    pub local: Vec3,
+    pub global: Vec3,
+}
+
+#[derive(Component, Default)]
+pub struct Airspeed(pub f32);
+
+/// Pitot tube and static port measuring the pressures the airspeed indicator and altimeter read.
+/// Either can be blocked, like by ice, trapping the pressure it last had.
+#[derive(Component, Default)]
+pub struct PitotStatic {
+    pub pitot_blocked: bool,
+    pub static_blocked: bool,
+    /// Pressure in the pitot tube, the static pressure plus the dynamic pressure of the air rammed
+    /// into it, or zero until it's first measured.
+    total_pressure: f32,
+    static_pressure: f32,
+    /// Airspeed in m/s that gives the measured dynamic pressure in air at sea level, which is less
+    /// than the true airspeed higher up where the air is thinner.
+    pub indicated_airspeed: f32,
+    /// Height in metres of the measured static pressure in the standard atmosphere.
+    pub indicated_altitude: f32,
+}
+
+#[derive(Component, Default)]
+pub struct Thrust(pub f32);
+
+/// Local position that a body's `Thrust` pushes it forward from. Bodies without one aren't pushed
+/// by their thrust, like helicopters whose engine drives a rotor instead.
+#[derive(Component, Default)]
+pub struct ThrustPoint(pub Vec3);
+
+/// Air around a body, for the game to fill in from its weather.
+#[derive(Component)]
+pub struct AmbientAir {
+    /// Velocity the air moves with, like wind or rising air.
+    pub velocity: Vec3,
+    /// Multiple of the lift the airfoils make, for air that lifts less, like in a storm.
+    pub lift_factor: f32,
+}
+
+impl Default for AmbientAir {
+    fn default() -> Self {
+        Self {
+            velocity: Vec3::ZERO,
+            lift_factor: 1.0,
+        }
+    }
+}
+
+#[derive(Component, Default)]
+pub struct PlaneFlight {
+    pub angle_of_attack: f32,
+    /// Angle of the airflow to the side of the nose in radians, positive when the plane is slipping
+    /// to the right.
+    pub sideslip: f32,
+    pub weight: f32,
+    pub drag: f32,
+}
+
+#[derive(Component, Default)]
+pub struct Lift(pub f32);
+
+#[derive(Component, Default)]
+pub struct AngleOfAttack(pub f32);
+
+#[derive(Debug, Clone, Copy)]
+pub enum AirfoilOrientation {
+    Horizontal,
+    Vertical,
+}
+
+#[derive(Component)]
+pub struct Airfoil {
+    pub orientation: AirfoilOrientation,
+    pub area: f32,
+    pub lift_coefficient_samples: Vec<f32>,
+    pub drag_coefficient_samples: Vec<f32>,
+    pub lift_coefficient_modifier: f32,
+    /// Span squared over area, of the pair for wings built either side of the plane.
+    pub aspect_ratio: f32,
+    pub oswald_efficiency: f32,
+    /// Velocity the air at the airfoil is disturbed by on top of the body's ambient air, like the
+    /// downwash of a wake it's flying through.
+    pub induced_velocity: Vec3,
+}
+
+impl Airfoil {
+    pub fn force_base_dir(&self, global_tx: &GlobalTransform) -> Vec3 {
+        match self.orientation {
+            AirfoilOrientation::Horizontal => global_tx.up(),
+            AirfoilOrientation::Vertical => global_tx.right(),
+        }
+    }
+
+    pub fn span_dir(&self, global_tx: &GlobalTransform) -> Vec3 {
+        match self.orientation {
+            AirfoilOrientation::Horizontal => global_tx.right(),
+            AirfoilOrientation::Vertical => global_tx.up(),
+        }
+    }
+
+    /// Change in lift coefficient per radian of angle of attack, less than the 2π of an airfoil of
+    /// infinite span as the tip vortices wash the flow down more the shorter the span.
+    pub fn lift_slope(&self) -> f32 {
+        f32::consts::TAU * self.aspect_ratio / (self.aspect_ratio + 2.0)
+    }
+
+    /// Lift coefficient from the curve at an angle of attack in radians, without the control
+    /// surface's change.
+    pub fn lift_coefficient(&self, angle_of_attack: f32) -> f32 {
+        sample(&self.lift_coefficient_samples, angle_of_attack)
+    }
+
+    /// Drag coefficient from the curve at an angle of attack in radians, without induced drag.
+    pub fn drag_coefficient(&self, angle_of_attack: f32) -> f32 {
+        sample(&self.drag_coefficient_samples, angle_of_attack)
+    }
+
+    /// Whether the angle of attack in radians is past the largest lift coefficient either way, so
+    /// more angle makes less lift.
+    pub fn is_stalled(&self, angle_of_attack: f32) -> bool {
+        let samples = &self.lift_coefficient_samples;
+        let peak = |better: fn(f32, f32) -> bool| {
+            let (index, _) = samples.iter().enumerate().fold(
+                (0, samples.first().copied().unwrap_or_default()),
+                |best, (i, cl)| {
+                    if better(*cl, best.1) {
+                        (i, *cl)
+                    } else {
+                        best
+                    }
+                },
+            );
+            (index as f32 - 90.0).to_radians()
+        };
+        angle_of_attack > peak(|a, b| a > b) || angle_of_attack < peak(|a, b| a < b)
+    }
+
+    /// Drag from the wing tip vortices that lift leaves behind, which grows with the square of lift.
+    /// Validation keeps the span and efficiency above zero, but the divisor is kept off zero too so
+    /// a degenerate airfoil can't hand Rapier a NaN force.
+    pub fn induced_drag_coefficient(&self, lift_coefficient: f32) -> f32 {
+        lift_coefficient * lift_coefficient
+            / (f32::consts::PI * self.aspect_ratio * self.oswald_efficiency).max(f32::EPSILON)
+    }
+}
+
+/// Coefficient from samples taken every degree from -90°, at an angle of attack in radians.
+fn sample(samples: &[f32], angle_of_attack: f32) -> f32 {
+    let index = (angle_of_attack.to_degrees() + 90.0) as usize;
+    samples.get(index).copied().unwrap_or_default()
+}
+
+fn angle_of_attack(velocity: Vec3, up: Vec3, forward: Vec3) -> f32 {
+    let a1 = up.angle_between(forward);
+    let a2 = up.angle_between(velocity.normalize());
+
+    a2 - a1
+}
+
+fn sideslip(velocity: Vec3, right: Vec3) -> f32 {
+    velocity
+        .normalize_or_zero()
+        .dot(right)
+        .clamp(-1.0, 1.0)
+        .asin()
+}
+
+// Taken from https://aviation.stackexchange.com/questions/46217/how-does-rudder-size-influence-its-ability-to-produce-lateral-lift
+pub fn calculate_control_surface_lift_coefficient_modifier(
+    flap_relative_chord: f32,
+    flap_deflection_angle: f32,
+) -> f32 {
+    flap_relative_chord.sqrt() * flap_deflection_angle
+}
+
+fn update_centre_of_gravity(
+    mut query: Query<(&GlobalTransform, &ReadMassProperties, &mut CentreOfGravity)>,
+) {
+    for (global_tx, ReadMassProperties(mass_properties), mut centre_of_gravity) in query.iter_mut()
+    {
+        let global_centre_of_mass = global_tx.mul_transform(Transform::from_translation(
+            mass_properties.local_center_of_mass,
+        ));
+
+        centre_of_gravity.local = mass_properties.local_center_of_mass;
+        centre_of_gravity.global = global_centre_of_mass.translation();
+    }
+}
+
+/// Measures the true airspeed along the nose, and the indicated airspeed and altitude from the
+/// pressures in the pitot-static system.
+fn update_airspeed(
+    mut body_query: Query<(
+        &GlobalTransform,
+        &Velocity,
+        &AmbientAir,
+        &mut Airspeed,
+        Option<&mut PitotStatic>,
+    )>,
+) {
+    for (global_tx, velocity, ambient_air, mut airspeed, pitot_static) in body_query.iter_mut() {
+        let air_velocity = velocity.linvel - ambient_air.velocity;
+        airspeed.0 = air_velocity.dot(global_tx.forward());
+
+        let Some(mut pitot_static) = pitot_static else {
+            continue;
+        };
+        let altitude = global_tx.translation().y;
+        let outside_pressure = static_pressure(altitude);
+        if !pitot_static.static_blocked || pitot_static.static_pressure <= 0.0 {
+            pitot_static.static_pressure = outside_pressure;
+        }
+        if !pitot_static.pitot_blocked || pitot_static.total_pressure <= 0.0 {
+            let speed = airspeed.0.max(0.0);
+            pitot_static.total_pressure =
+                outside_pressure + 0.5 * air_density(altitude) * speed * speed;
+        }
+
+        let dynamic_pressure =
+            (pitot_static.total_pressure - pitot_static.static_pressure).max(0.0);
+        pitot_static.indicated_airspeed = (2.0 * dynamic_pressure / AIR_DENSITY).sqrt();
+        pitot_static.indicated_altitude = pressure_altitude(pitot_static.static_pressure);
+    }
+}
+
+fn reset_forces(mut body_query: Query<&mut ExternalForce, With<PlaneFlight>>) {
+    for mut external_force in body_query.iter_mut() {
+        *external_force = ExternalForce::default();
+    }
+}
+
+fn update_thrust_forces(
+    mut body_query: Query<(
+        &Thrust,
+        &ThrustPoint,
+        &GlobalTransform,
+        &CentreOfGravity,
+        &mut ExternalForce,
+    )>,
+) {
+    for (Thrust(thrust), ThrustPoint(point), global_tx, centre_of_gravity, mut external_force) in
+        body_query.iter_mut()
+    {
+        external_force.add_assign(ExternalForce::at_point(
+            global_tx.forward() * *thrust,
+            global_tx.transform_point(*point),
+            centre_of_gravity.global,
+        ));
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn update_airfoil_forces(
+    mut body_query: Query<(
+        Entity,
+        &GlobalTransform,
+        &mut PlaneFlight,
+        &Airspeed,
+        &Velocity,
+        &AmbientAir,
+        &CentreOfGravity,
+        &mut ExternalForce,
+    )>,
+    children_query: Query<&Children>,
+    mut airfoil_query: Query<(&Airfoil, &GlobalTransform, &mut AngleOfAttack, &mut Lift)>,
+) {
+    for (
+        body_entity,
+        global_tx,
+        mut flight,
+        Airspeed(airspeed),
+        velocity,
+        ambient_air,
+        centre_of_gravity,
+        mut external_force,
+    ) in body_query.iter_mut()
+    {
+        // The air thins with height, as the pitot measures it, so the loads fall with the
+        // indicated airspeed rather than the true one
+        let dynamic_pressure = 0.5 * air_density(global_tx.translation().y) * airspeed * airspeed;
+
+        let airflow = velocity.linvel - ambient_air.velocity;
+        flight.angle_of_attack = angle_of_attack(airflow, global_tx.up(), global_tx.forward());
+        flight.sideslip = sideslip(airflow, global_tx.right());
+
+        for child in children_query.iter_descendants(body_entity) {
+            if let Ok((airfoil, airfoil_global_tx, mut aoa, mut airfoil_lift)) =
+                airfoil_query.get_mut(child)
+            {
+                let airflow = airflow - airfoil.induced_velocity;
+                let angle_of_attack = angle_of_attack(
+                    airflow,
+                    airfoil.force_base_dir(airfoil_global_tx),
+                    airfoil_global_tx.forward(),
+                );
+
+                aoa.0 = angle_of_attack;
+
+                let lift_coefficient =
+                    airfoil.lift_coefficient(angle_of_attack) + airfoil.lift_coefficient_modifier;
+                // Only airflow across the span makes lift, so swept wings lift less, and more on
+                // the side facing into a sideslip, which rolls the plane away from it. Dihedral
+                // does the same through the angle of attack, as each wing is tilted to the flow.
+                let crossflow = 1.0
+                    - airflow
+                        .normalize_or_zero()
+                        .dot(airfoil.span_dir(airfoil_global_tx))
+                        .powi(2);
+                let lift = lift_coefficient
+                    * dynamic_pressure
+                    * crossflow
+                    * airfoil.area
+                    * ambient_air.lift_factor;
+                airfoil_lift.0 = lift;
+
+                external_force.add_assign(ExternalForce::at_point(
+                    airfoil.force_base_dir(airfoil_global_tx) * lift,
+                    airfoil_global_tx.translation(),
+                    centre_of_gravity.global,
+                ));
+
+                let drag_coefficient = airfoil.drag_coefficient(angle_of_attack)
+                    + airfoil.induced_drag_coefficient(lift_coefficient);
+                let drag = drag_coefficient * dynamic_pressure * airfoil.area;
+
+                external_force.force += -airflow.normalize_or_zero() * drag;
+
+                flight.drag = drag;
+            }
+        }
+    }
+}
@@ -0,0 +1,114 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+use super::{air_density, Airfoil, Airspeed, AmbientAir, AngleOfAttack, CentreOfGravity};
+
+/// Fraction of the damping from the change in each airfoil's angle of attack that's applied. The
+/// control surfaces change the lift coefficient far less than real ones, so the full damping would
+/// hold the roll rate to a few degrees per second.
+pub const DAMPING_SCALE: f32 = 0.1;
+
+/// Moments resisting the rotation of a plane about its roll, pitch and yaw axes. As the plane
+/// rotates each airfoil moves across the airflow, and the change in its angle of attack adds lift
+/// against the movement, so the wings damp roll, the tail pitch and the fin yaw. The damping about
+/// each axis grows with the square of each airfoil's distance from it.
+///
+/// A stalled airfoil loses lift as its angle of attack grows instead, so rather than damping it
+/// drives the rotation on, and gains drag. A wing dropping in a stall stalls deeper and keeps
+/// dropping while the rising one recovers lift, and the drag of the dropping wing yaws the plane
+/// after it, autorotating into a spin. This is worked out from the change along each curve rather
+/// than its slope, so it eases off once both wings are far enough along it, and the spin settles.
+#[allow(clippy::type_complexity)]
+pub fn update_damping_forces(
+    mut body_query: Query<(
+        Entity,
+        &GlobalTransform,
+        &Airspeed,
+        &Velocity,
+        &AmbientAir,
+        &ReadMassProperties,
+        &CentreOfGravity,
+        &mut ExternalForce,
+    )>,
+    children_query: Query<&Children>,
+    airfoil_query: Query<(&Airfoil, &GlobalTransform, &AngleOfAttack)>,
+    fixed_time: Res<FixedTime>,
+) {
+    let dt = fixed_time.period.as_secs_f32();
+
+    for (
+        body_entity,
+        global_tx,
+        Airspeed(airspeed),
+        velocity,
+        ambient_air,
+        ReadMassProperties(mass_properties),
+        centre_of_gravity,
+        mut external_force,
+    ) in body_query.iter_mut()
+    {
+        let axes = [global_tx.forward(), global_tx.right(), global_tx.up()];
+        let mut damping = [0.0; 3];
+        let airflow = velocity.linvel - ambient_air.velocity;
+        let airflow_speed = airflow.length();
+        let density = air_density(global_tx.translation().y);
+
+        for child in children_query.iter_descendants(body_entity) {
+            let Ok((airfoil, airfoil_global_tx, AngleOfAttack(aoa))) = airfoil_query.get(child)
+            else {
+                continue;
+            };
+
+            let offset = airfoil_global_tx.translation() - centre_of_gravity.global;
+            let normal = airfoil.force_base_dir(airfoil_global_tx);
+
+            if airfoil.is_stalled(*aoa) && airflow_speed > 0.0 {
+                // Moving along its lift direction brings the air at it from that side, lowering
+                // its angle of attack
+                let motion = velocity.angvel.cross(offset).dot(normal);
+                let stalled_aoa = *aoa - (motion / airflow_speed).atan();
+                let force_per_coefficient =
+                    DAMPING_SCALE * 0.5 * density * airflow_speed * airflow_speed * airfoil.area;
+                let lift = (airfoil.lift_coefficient(stalled_aoa) - airfoil.lift_coefficient(*aoa))
+                    * force_per_coefficient
+                    * ambient_air.lift_factor;
+                let drag = (airfoil.drag_coefficient(stalled_aoa) - airfoil.drag_coefficient(*aoa))
+                    * force_per_coefficient;
+                external_force.torque +=
+                    offset.cross(normal * lift - airflow / airflow_speed * drag);
+                continue;
+            }
+
+            // Moment arm of the airfoil's lift about each axis, which is also the speed it moves
+            // across the airflow for each rad/s about that axis
+            let arm = offset.cross(normal);
+            let lift_per_speed = DAMPING_SCALE
+                * airfoil.lift_slope()
+                * 0.5
+                * density
+                * airspeed
+                * airfoil.area
+                * ambient_air.lift_factor;
+
+            for (damping, axis) in damping.iter_mut().zip(axes) {
+                *damping += lift_per_speed * arm.dot(axis).powi(2);
+            }
+        }
+
+        let principal_frame =
+            global_tx.compute_transform().rotation * mass_properties.principal_inertia_local_frame;
+        for (damping, axis) in damping.into_iter().zip(axes) {
+            let local_axis = principal_frame.inverse() * axis;
+            let inertia = (local_axis * local_axis).dot(mass_properties.principal_inertia);
+            if inertia <= 0.0 {
+                continue;
+            }
+
+            // Light planes with long wings are damped within a single step, so the moment is
+            // limited to what would stop the rotation rather than reverse it
+            let rate = velocity.angvel.dot(axis);
+            let moment = damping * rate / (1.0 + damping * dt / inertia);
+            external_force.torque -= axis * moment;
+        }
+    }
+}
@@ -1,13 +1,18 @@
-use bevy::prelude::*;
+use std::fs;
+
+use bevy::{prelude::*, utils::HashMap, window::PrimaryWindow};
+use bevy_egui::EguiContexts;
 
 use leafwing_input_manager::{
     prelude::{ActionState, InputManagerPlugin, InputMap, SingleAxis},
     Actionlike, InputManagerBundle,
 };
+use serde::{Deserialize, Serialize};
 
 use crate::{
     camera::{self, Follow},
-    plane::{spec::PlaneSpec, Plane, PlaneControl, Thrust},
+    flight_model::Thrust,
+    plane::{assist::AssistLevel, spec::PlaneSpec, Plane, PlaneControl, PlaneSet, PropellorPitch},
 };
 
 pub struct InputPlugin;
@@ -15,12 +20,39 @@ pub struct InputPlugin;
 impl Plugin for InputPlugin {
     fn build(&self, app: &mut App) {
         app.add_plugins(InputManagerPlugin::<PlaneAction>::default())
+            .insert_resource(InputRebind::default())
+            .insert_resource(AxisResponses::default())
+            .insert_resource(MouseYoke::default())
             .add_systems(Startup, add_plane_input)
-            .add_systems(Update, (handle_keyboard_input, handle_gamepad_input));
+            .add_systems(
+                Update,
+                (
+                    (
+                        handle_keyboard_input,
+                        handle_gamepad_input,
+                        handle_mouse_yoke.after(handle_keyboard_input),
+                    )
+                        .in_set(PlaneSet::Input),
+                    capture_rebind.run_if(rebinding),
+                ),
+            );
     }
 }
 
-#[derive(Actionlike, PartialEq, Eq, Clone, Copy, Hash, Debug, Reflect)]
+#[derive(
+    Actionlike,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Clone,
+    Copy,
+    Hash,
+    Debug,
+    Reflect,
+    Serialize,
+    Deserialize,
+)]
 pub enum PlaneAction {
     // Keyboard
     RollLeft,
@@ -31,6 +63,8 @@ pub enum PlaneAction {
     PitchDown,
     ThrustUp,
     ThrustDown,
+    ReverseThrust,
+    Brake,
 
     // Gamepad
     Pitch,
@@ -43,109 +77,483 @@ pub enum PlaneAction {
     FollowAbove,
     FollowSide,
     FollowInside,
+    FollowOrbit,
+    FollowMap,
+    FollowPadlock,
+    CycleLookTarget,
+
+    // HUD
+    CycleHudMode,
+
+    // Mouse
+    ToggleMouseYoke,
+
+    // Snapshot
+    QuickSave,
+    QuickLoad,
+    SwapSpec,
+
+    // World
+    ExportMap,
+
+    // Time
+    TogglePause,
+    SlowDown,
+    SpeedUp,
+
+    // Minimap
+    ZoomMapIn,
+    ZoomMapOut,
+
+    // Capture
+    Screenshot,
+    ToggleRecording,
+
+    // Smoke
+    ToggleSmoke,
+
+    // Payload
+    DropPayload,
+
+    // Towing
+    ReleaseTow,
 }
 
-const STICK_THRESHOLD: f32 = 0.2;
+/// Axis movement needed for an axis action to be pressed, large enough that a drifting stick at rest
+/// doesn't override the keys. The response deadzone is applied on top.
+const AXIS_THRESHOLD: f32 = 0.1;
+/// How far an axis must move while rebinding before it is captured.
+const REBIND_AXIS_THRESHOLD: f32 = 0.5;
+const CONTROLS_PATH: &str = "controls.ron";
+
+/// Actions driven by an analog axis, which have a configurable response.
+pub const AXIS_ACTIONS: [PlaneAction; 4] = [
+    PlaneAction::Pitch,
+    PlaneAction::Roll,
+    PlaneAction::Throttle,
+    PlaneAction::Rudder,
+];
+
+/// Shapes the raw value of an axis before it drives a control.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct AxisResponse {
+    /// Axis deflection below which the output is zero.
+    pub deadzone: f32,
+    /// Axis deflection at which the output reaches full scale.
+    pub saturation: f32,
+    /// Blend from a linear response at zero to a cubic one at one, for finer control around the centre.
+    pub expo: f32,
+}
+
+impl Default for AxisResponse {
+    fn default() -> Self {
+        Self {
+            deadzone: 0.2,
+            saturation: 1.0,
+            expo: 0.0,
+        }
+    }
+}
+
+impl AxisResponse {
+    pub fn apply(&self, value: f32) -> f32 {
+        let range = (self.saturation - self.deadzone).max(f32::EPSILON);
+        let magnitude = ((value.abs() - self.deadzone) / range).clamp(0.0, 1.0);
+
+        value.signum() * (magnitude * (1.0 - self.expo) + magnitude.powi(3) * self.expo)
+    }
+}
+
+#[derive(Resource, Debug, Clone, Serialize, Deserialize)]
+pub struct AxisResponses(pub HashMap<PlaneAction, AxisResponse>);
+
+impl Default for AxisResponses {
+    fn default() -> Self {
+        Self(
+            AXIS_ACTIONS
+                .iter()
+                .map(|action| (*action, AxisResponse::default()))
+                .collect(),
+        )
+    }
+}
+
+impl AxisResponses {
+    pub fn get(&self, action: PlaneAction) -> AxisResponse {
+        self.0.get(&action).copied().unwrap_or_default()
+    }
+}
+
+/// Bindings, axis responses and assist level saved by the controls window.
+#[derive(Serialize, Deserialize)]
+struct ControlsConfig {
+    bindings: InputMap<PlaneAction>,
+    axes: AxisResponses,
+    #[serde(default)]
+    assist: AssistLevel,
+    /// Every action there was when the controls were saved, so actions added since then get their
+    /// default bindings while ones cleared in the controls window stay cleared.
+    #[serde(default)]
+    actions: Vec<PlaneAction>,
+}
+
+/// Fraction of the smaller window dimension the cursor moves from the centre for full deflection.
+const MOUSE_YOKE_RANGE: f32 = 0.4;
+
+/// Mouse control of ailerons and elevators from the cursor's offset from the window centre.
+#[derive(Resource, Default)]
+pub struct MouseYoke {
+    pub enabled: bool,
+    /// Yoke deflection in the range `-1..1`, with +x rolling right and +y pitching up.
+    pub position: Vec2,
+}
+
+/// Action waiting for the next key, button or axis to bind to it.
+#[derive(Resource, Default)]
+pub struct InputRebind(pub Option<PlaneAction>);
+
+fn rebinding(rebind: Res<InputRebind>) -> bool {
+    rebind.0.is_some()
+}
 
 fn add_plane_input(mut commands: Commands) {
     info!("Adding input");
 
+    let (input_map, axes, assist) = match load_controls() {
+        Some(ControlsConfig {
+            mut bindings,
+            axes,
+            assist,
+            actions,
+        }) => {
+            bind_new_actions(&mut bindings, &actions);
+            (bindings, axes, assist)
+        }
+        None => (
+            default_bindings(),
+            AxisResponses::default(),
+            AssistLevel::default(),
+        ),
+    };
+
+    commands.insert_resource(axes);
+    commands.insert_resource(assist);
     commands.spawn(InputManagerBundle::<PlaneAction> {
         action_state: ActionState::default(),
-        input_map: InputMap::default()
-            .insert(KeyCode::Up, PlaneAction::PitchUp)
-            .insert(KeyCode::Down, PlaneAction::PitchDown)
-            .insert(KeyCode::Left, PlaneAction::RollLeft)
-            .insert(KeyCode::Right, PlaneAction::RollRight)
-            .insert(KeyCode::Q, PlaneAction::YawLeft)
-            .insert(KeyCode::W, PlaneAction::YawRight)
-            .insert(KeyCode::A, PlaneAction::ThrustUp)
-            .insert(KeyCode::Z, PlaneAction::ThrustDown)
-            .insert(KeyCode::F1, PlaneAction::FollowBehind)
-            .insert(KeyCode::F2, PlaneAction::FollowAbove)
-            .insert(KeyCode::F3, PlaneAction::FollowSide)
-            .insert(KeyCode::F4, PlaneAction::FollowInside)
-            .insert(
-                SingleAxis::symmetric(GamepadAxisType::LeftStickY, STICK_THRESHOLD),
-                PlaneAction::Pitch,
-            )
-            .insert(
-                SingleAxis::symmetric(GamepadAxisType::LeftStickX, STICK_THRESHOLD),
-                PlaneAction::Roll,
-            )
-            .insert(
-                SingleAxis::symmetric(GamepadAxisType::RightStickY, STICK_THRESHOLD),
-                PlaneAction::Throttle,
-            )
-            .insert(
-                SingleAxis::symmetric(GamepadAxisType::RightStickX, STICK_THRESHOLD),
-                PlaneAction::Rudder,
-            )
-            .insert(GamepadButtonType::DPadDown, PlaneAction::FollowBehind)
-            .insert(GamepadButtonType::DPadUp, PlaneAction::FollowAbove)
-            .insert(GamepadButtonType::DPadRight, PlaneAction::FollowSide)
-            .insert(GamepadButtonType::DPadLeft, PlaneAction::FollowInside)
-            .build(),
+        input_map,
     });
 }
 
+/// Reads controls saved by the controls window, if there are any.
+fn load_controls() -> Option<ControlsConfig> {
+    let contents = fs::read_to_string(CONTROLS_PATH).ok()?;
+
+    match ron::from_str(&contents) {
+        Ok(config) => {
+            info!("Loaded controls from {}", CONTROLS_PATH);
+            Some(config)
+        }
+        Err(err) => {
+            warn!("Ignoring invalid controls in {}: {}", CONTROLS_PATH, err);
+            None
+        }
+    }
+}
+
+/// Gives actions missing from saved controls their default bindings, unless they already have
+/// some.
+fn bind_new_actions(input_map: &mut InputMap<PlaneAction>, saved_actions: &[PlaneAction]) {
+    let defaults = default_bindings();
+    for action in PlaneAction::variants() {
+        let inputs = defaults.get(action);
+        if saved_actions.contains(&action) || !input_map.get(action).is_empty() || inputs.is_empty()
+        {
+            continue;
+        }
+        for input in inputs.iter() {
+            input_map.insert(input.clone(), action);
+        }
+        info!("Added default bindings for {:?}", action);
+    }
+}
+
+pub fn save_controls(input_map: &InputMap<PlaneAction>, axes: &AxisResponses, assist: AssistLevel) {
+    let config = ControlsConfig {
+        bindings: input_map.clone(),
+        axes: axes.clone(),
+        assist,
+        actions: PlaneAction::variants().collect(),
+    };
+    let result = ron::ser::to_string_pretty(&config, ron::ser::PrettyConfig::default())
+        .map_err(|err| err.to_string())
+        .and_then(|contents| fs::write(CONTROLS_PATH, contents).map_err(|err| err.to_string()));
+
+    match result {
+        Ok(()) => info!("Saved controls to {}", CONTROLS_PATH),
+        Err(err) => warn!("Failed to save controls to {}: {}", CONTROLS_PATH, err),
+    }
+}
+
+pub fn default_bindings() -> InputMap<PlaneAction> {
+    InputMap::default()
+        .insert(KeyCode::Up, PlaneAction::PitchUp)
+        .insert(KeyCode::Down, PlaneAction::PitchDown)
+        .insert(KeyCode::Left, PlaneAction::RollLeft)
+        .insert(KeyCode::Right, PlaneAction::RollRight)
+        .insert(KeyCode::Q, PlaneAction::YawLeft)
+        .insert(KeyCode::W, PlaneAction::YawRight)
+        .insert(KeyCode::A, PlaneAction::ThrustUp)
+        .insert(KeyCode::Z, PlaneAction::ThrustDown)
+        .insert(KeyCode::R, PlaneAction::ReverseThrust)
+        .insert(KeyCode::B, PlaneAction::Brake)
+        .insert(KeyCode::F1, PlaneAction::FollowBehind)
+        .insert(KeyCode::F2, PlaneAction::FollowAbove)
+        .insert(KeyCode::F3, PlaneAction::FollowSide)
+        .insert(KeyCode::F4, PlaneAction::FollowInside)
+        .insert(KeyCode::F6, PlaneAction::FollowOrbit)
+        .insert(KeyCode::F10, PlaneAction::FollowMap)
+        .insert(KeyCode::F11, PlaneAction::FollowPadlock)
+        .insert(KeyCode::T, PlaneAction::CycleLookTarget)
+        .insert(KeyCode::H, PlaneAction::CycleHudMode)
+        .insert(KeyCode::Y, PlaneAction::ToggleMouseYoke)
+        .insert(KeyCode::F5, PlaneAction::QuickSave)
+        .insert(KeyCode::F9, PlaneAction::QuickLoad)
+        .insert(KeyCode::X, PlaneAction::SwapSpec)
+        .insert(KeyCode::F12, PlaneAction::ExportMap)
+        .insert(KeyCode::P, PlaneAction::TogglePause)
+        .insert(KeyCode::BracketLeft, PlaneAction::SlowDown)
+        .insert(KeyCode::BracketRight, PlaneAction::SpeedUp)
+        .insert(KeyCode::Equals, PlaneAction::ZoomMapIn)
+        .insert(KeyCode::Minus, PlaneAction::ZoomMapOut)
+        .insert(KeyCode::F7, PlaneAction::Screenshot)
+        .insert(KeyCode::F8, PlaneAction::ToggleRecording)
+        .insert(KeyCode::S, PlaneAction::ToggleSmoke)
+        .insert(KeyCode::D, PlaneAction::DropPayload)
+        .insert(KeyCode::G, PlaneAction::ReleaseTow)
+        .insert(
+            SingleAxis::symmetric(GamepadAxisType::LeftStickY, AXIS_THRESHOLD),
+            PlaneAction::Pitch,
+        )
+        .insert(
+            SingleAxis::symmetric(GamepadAxisType::LeftStickX, AXIS_THRESHOLD),
+            PlaneAction::Roll,
+        )
+        .insert(
+            SingleAxis::symmetric(GamepadAxisType::RightStickY, AXIS_THRESHOLD),
+            PlaneAction::Throttle,
+        )
+        .insert(
+            SingleAxis::symmetric(GamepadAxisType::RightStickX, AXIS_THRESHOLD),
+            PlaneAction::Rudder,
+        )
+        .insert(GamepadButtonType::LeftTrigger2, PlaneAction::Brake)
+        .insert(GamepadButtonType::DPadDown, PlaneAction::FollowBehind)
+        .insert(GamepadButtonType::DPadUp, PlaneAction::FollowAbove)
+        .insert(GamepadButtonType::DPadRight, PlaneAction::FollowSide)
+        .insert(GamepadButtonType::DPadLeft, PlaneAction::FollowInside)
+        .build()
+}
+
+#[allow(clippy::too_many_arguments)]
+fn capture_rebind(
+    mut rebind: ResMut<InputRebind>,
+    keys: Res<Input<KeyCode>>,
+    gamepad_buttons: Res<Input<GamepadButton>>,
+    gamepad_axes: Res<Axis<GamepadAxis>>,
+    axes: Res<AxisResponses>,
+    assist: Res<AssistLevel>,
+    mut input_map_query: Query<&mut InputMap<PlaneAction>>,
+    mut axis_rest: Local<HashMap<GamepadAxis, f32>>,
+) {
+    let Some(action) = rebind.0 else {
+        return;
+    };
+    let Ok(mut input_map) = input_map_query.get_single_mut() else {
+        return;
+    };
+
+    if keys.just_pressed(KeyCode::Escape) {
+        rebind.0 = None;
+        axis_rest.clear();
+        return;
+    }
+
+    // Joystick axes such as throttle levers rest away from the centre, so look for
+    // movement from where each axis was when rebinding started
+    if axis_rest.is_empty() {
+        axis_rest.extend(
+            gamepad_axes
+                .devices()
+                .filter_map(|axis| gamepad_axes.get(*axis).map(|value| (*axis, value))),
+        );
+    }
+    let moved_axis = gamepad_axes.devices().find(|axis| {
+        let rest = axis_rest.get(*axis).copied().unwrap_or_default();
+        gamepad_axes
+            .get(**axis)
+            .is_some_and(|value| (value - rest).abs() > REBIND_AXIS_THRESHOLD)
+    });
+
+    if let Some(key) = keys.get_just_pressed().next() {
+        input_map.insert(*key, action);
+    } else if let Some(button) = gamepad_buttons.get_just_pressed().next() {
+        input_map.insert(button.button_type, action);
+    } else if let Some(axis) = moved_axis {
+        input_map.insert(
+            SingleAxis::symmetric(axis.axis_type, AXIS_THRESHOLD),
+            action,
+        );
+    } else {
+        return;
+    }
+
+    info!("Rebound {:?}", action);
+    rebind.0 = None;
+    axis_rest.clear();
+    save_controls(&input_map, &axes, *assist);
+}
+
+/// Range the throttle moves the thrust through, and which way opening it moves the thrust, which is
+/// backwards from idle while the propellor is reversed.
+fn thrust_range(spec: &PlaneSpec, pitch: &PropellorPitch) -> (f32, f32, f32) {
+    match pitch.reversed {
+        true => (-spec.thrust * spec.engine.reverse_thrust, 0.0, -1.0),
+        false => (0.0, spec.thrust, 1.0),
+    }
+}
+
 fn handle_keyboard_input(
     mut action_query: Query<&ActionState<PlaneAction>>,
-    mut plane_query: Query<(&PlaneSpec, &mut PlaneControl, &mut Thrust), With<Plane>>,
+    mut plane_query: Query<
+        (
+            &PlaneSpec,
+            &mut PlaneControl,
+            &mut Thrust,
+            &mut PropellorPitch,
+        ),
+        With<Plane>,
+    >,
     time: Res<Time>,
 ) {
     let Ok(action_state) = action_query.get_single_mut() else {
         return;
     };
-    let Ok((spec, mut control, mut thrust)) = plane_query.get_single_mut() else {
+    let Ok((spec, mut control, mut thrust, mut pitch)) = plane_query.get_single_mut() else {
         return;
     };
 
+    // The throttle comes back to idle as the blades are turned, and helicopters' rotors don't reverse
+    if action_state.just_pressed(PlaneAction::ReverseThrust)
+        && spec.rotor.is_none()
+        && spec.engine.reverse_thrust > 0.0
+    {
+        pitch.reversed = !pitch.reversed;
+        thrust.0 = 0.0;
+        info!("Propellor pitch reversed: {}", pitch.reversed);
+    }
+    let (min_thrust, max_thrust, direction) = thrust_range(spec, &pitch);
+
     let inc_clamped = |current: f32, max_angle: f32| -> f32 {
+        let max_angle = max_angle.abs();
         (current + (max_angle / 5.)).clamp(-max_angle, max_angle)
     };
     let dec_clamped = |current: f32, max_angle: f32| -> f32 {
+        let max_angle = max_angle.abs();
         (current - (max_angle / 5.)).clamp(-max_angle, max_angle)
     };
 
     if action_state.just_pressed(PlaneAction::RollLeft) {
-        control.ailerons = dec_clamped(control.ailerons, spec.wings.max_control_angle)
+        control.ailerons = dec_clamped(control.ailerons, spec.main_wing().max_control_angle)
     }
     if action_state.just_pressed(PlaneAction::RollRight) {
-        control.ailerons = inc_clamped(control.ailerons, spec.wings.max_control_angle)
+        control.ailerons = inc_clamped(control.ailerons, spec.main_wing().max_control_angle)
     }
     if action_state.just_pressed(PlaneAction::YawLeft) {
-        control.rudder = dec_clamped(control.rudder, spec.tail.vertical.max_control_angle)
+        control.rudder = dec_clamped(control.rudder, spec.fin().max_control_angle)
     }
     if action_state.just_pressed(PlaneAction::YawRight) {
-        control.rudder = inc_clamped(control.rudder, spec.tail.vertical.max_control_angle)
+        control.rudder = inc_clamped(control.rudder, spec.fin().max_control_angle)
     }
     if action_state.just_pressed(PlaneAction::PitchUp) {
-        control.elevators = inc_clamped(control.elevators, spec.tail.horizontal.max_control_angle)
+        control.elevators = inc_clamped(control.elevators, spec.pitch_wing().max_control_angle)
     }
     if action_state.just_pressed(PlaneAction::PitchDown) {
-        control.elevators = dec_clamped(control.elevators, spec.tail.horizontal.max_control_angle)
+        control.elevators = dec_clamped(control.elevators, spec.pitch_wing().max_control_angle)
     }
     if action_state.pressed(PlaneAction::ThrustUp) {
-        thrust.0 += 50.0 * time.delta_seconds();
+        thrust.0 += direction * 50.0 * time.delta_seconds();
     }
     if action_state.pressed(PlaneAction::ThrustDown) {
-        thrust.0 -= 50.0 * time.delta_seconds();
+        thrust.0 -= direction * 50.0 * time.delta_seconds();
+    }
+
+    thrust.0 = thrust.0.clamp(min_thrust, max_thrust);
+
+    control.brake = match action_state.pressed(PlaneAction::Brake) {
+        true => 1.0,
+        false => 0.0,
+    };
+}
+
+fn handle_mouse_yoke(
+    mut contexts: EguiContexts,
+    mut yoke: ResMut<MouseYoke>,
+    action_query: Query<&ActionState<PlaneAction>>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    mut plane_query: Query<(&PlaneSpec, &mut PlaneControl), With<Plane>>,
+) {
+    let Ok(action_state) = action_query.get_single() else {
+        return;
+    };
+
+    if action_state.just_pressed(PlaneAction::ToggleMouseYoke) {
+        yoke.enabled = !yoke.enabled;
+        info!("Mouse yoke {}", if yoke.enabled { "on" } else { "off" });
     }
 
-    thrust.0 = thrust.0.clamp(0., spec.thrust);
+    if !yoke.enabled || contexts.ctx_mut().wants_pointer_input() {
+        return;
+    }
+
+    let Ok(window) = window_query.get_single() else {
+        return;
+    };
+    let Some(cursor) = window.cursor_position() else {
+        return;
+    };
+    let Ok((spec, mut control)) = plane_query.get_single_mut() else {
+        return;
+    };
+
+    // Cursor positions start at the top left, so pulling the mouse back pitches up like a yoke
+    let centre = Vec2::new(window.width(), window.height()) * 0.5;
+    let range = window.width().min(window.height()) * MOUSE_YOKE_RANGE;
+    yoke.position = ((cursor - centre) / range).clamp(Vec2::NEG_ONE, Vec2::ONE);
+
+    control.ailerons = yoke.position.x * spec.main_wing().max_control_angle;
+    control.elevators = yoke.position.y * spec.pitch_wing().max_control_angle;
 }
 
 fn handle_gamepad_input(
     mut commands: Commands,
     mut action_query: Query<&ActionState<PlaneAction>>,
-    mut plane_query: Query<(Entity, &PlaneSpec, &mut PlaneControl, &mut Thrust), With<Plane>>,
+    mut plane_query: Query<
+        (
+            Entity,
+            &PlaneSpec,
+            &mut PlaneControl,
+            &mut Thrust,
+            &PropellorPitch,
+        ),
+        With<Plane>,
+    >,
+    axes: Res<AxisResponses>,
     time: Res<Time>,
 ) {
     let Ok(action_state) = action_query.get_single_mut() else {
         return;
     };
-    let Ok((entity, spec, mut control, mut thrust)) = plane_query.get_single_mut() else {
+    let Ok((entity, spec, mut control, mut thrust, pitch)) = plane_query.get_single_mut() else {
         return;
     };
 
@@ -158,23 +566,21 @@ fn handle_gamepad_input(
     }
 
     let scaled_value =
-        |v: f32| -> f32 { v.signum() * (v.abs() - STICK_THRESHOLD) / (1. - STICK_THRESHOLD) };
+        |action: PlaneAction| -> f32 { axes.get(action).apply(action_state.clamped_value(action)) };
 
     if action_state.pressed(PlaneAction::Pitch) {
-        control.elevators = scaled_value(action_state.clamped_value(PlaneAction::Pitch))
-            * spec.tail.horizontal.max_control_angle;
+        control.elevators = scaled_value(PlaneAction::Pitch) * spec.pitch_wing().max_control_angle;
     }
     if action_state.pressed(PlaneAction::Roll) {
-        control.ailerons = scaled_value(action_state.clamped_value(PlaneAction::Roll))
-            * spec.wings.max_control_angle;
+        control.ailerons = scaled_value(PlaneAction::Roll) * spec.main_wing().max_control_angle;
     }
     if action_state.pressed(PlaneAction::Throttle) {
-        thrust.0 += action_state.clamped_value(PlaneAction::Throttle) * time.delta_seconds() * 50.0;
-        thrust.0 = thrust.0.clamp(0., spec.thrust);
+        let (min_thrust, max_thrust, direction) = thrust_range(spec, pitch);
+        thrust.0 += direction * scaled_value(PlaneAction::Throttle) * time.delta_seconds() * 50.0;
+        thrust.0 = thrust.0.clamp(min_thrust, max_thrust);
     }
     if action_state.pressed(PlaneAction::Rudder) {
-        control.rudder = scaled_value(action_state.clamped_value(PlaneAction::Rudder))
-            * spec.tail.vertical.max_control_angle;
+        control.rudder = scaled_value(PlaneAction::Rudder) * spec.fin().max_control_angle;
     }
 
     if action_state.just_pressed(PlaneAction::FollowAbove) {
@@ -197,4 +603,19 @@ fn handle_gamepad_input(
             .entity(entity)
             .insert(Follow(camera::FollowKind::Inside));
     }
+    if action_state.just_pressed(PlaneAction::FollowOrbit) {
+        commands
+            .entity(entity)
+            .insert(Follow(camera::FollowKind::Orbit));
+    }
+    if action_state.just_pressed(PlaneAction::FollowMap) {
+        commands
+            .entity(entity)
+            .insert(Follow(camera::FollowKind::Map));
+    }
+    if action_state.just_pressed(PlaneAction::FollowPadlock) {
+        commands
+            .entity(entity)
+            .insert(Follow(camera::FollowKind::Padlock));
+    }
 }
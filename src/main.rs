@@ -1,24 +1,54 @@
+mod achievements;
 mod camera;
+mod canvas;
+mod capture;
+mod cockpit;
+mod damage;
+mod display;
+mod flight_model;
 mod input;
+mod navigation;
+mod net;
 mod physics;
 mod plane;
+mod recorder;
+mod scenario;
+mod settings;
+mod snapshot;
+mod sound;
+mod tutorial;
 mod ui;
+mod units;
 mod world;
 
-use bevy::{diagnostic::FrameTimeDiagnosticsPlugin, prelude::*};
+use bevy::{
+    asset::ChangeWatcher, diagnostic::FrameTimeDiagnosticsPlugin, prelude::*, utils::Duration,
+};
+use display::DisplayPlugin;
 use ui::HudUiPlugin;
-use world::WorldPlugin;
+use world::{WorldGenConfig, WorldPlugin};
 
 fn main() {
+    let display_settings = display::load_display_settings().unwrap_or_default();
+    let world_gen_config = WorldGenConfig::from_args(std::env::args().skip(1));
+
     App::new()
-        .add_plugins(DefaultPlugins.set(WindowPlugin {
-            primary_window: Some(Window {
-                fit_canvas_to_parent: true,
-                ..default()
-            }),
-            ..default()
-        }))
+        .add_plugins(
+            DefaultPlugins
+                .set(WindowPlugin {
+                    primary_window: Some(display_settings.window()),
+                    ..default()
+                })
+                // Reloads plane spec files as they're saved
+                .set(AssetPlugin {
+                    watch_for_changes: ChangeWatcher::with_delay(Duration::from_millis(200)),
+                    ..default()
+                }),
+        )
+        .insert_resource(display_settings)
+        .add_plugins(DisplayPlugin)
         .add_plugins(FrameTimeDiagnosticsPlugin)
+        .insert_resource(world_gen_config)
         .add_plugins(WorldPlugin)
         .add_plugins(HudUiPlugin)
         .run();
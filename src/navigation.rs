@@ -0,0 +1,145 @@
+use bevy::{pbr::NotShadowCaster, prelude::*};
+
+use crate::{
+    camera::LookTarget,
+    plane::Plane,
+    world::{compass_heading, OriginShiftedEvent},
+};
+
+pub struct NavigationPlugin;
+
+impl Plugin for NavigationPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Waypoints>()
+            .add_systems(Startup, setup_beacons)
+            .add_systems(
+                Update,
+                (shift_waypoints, pass_waypoints, update_beacons).chain(),
+            );
+    }
+}
+
+/// Horizontal distance from a waypoint within which it counts as passed.
+const WAYPOINT_RADIUS: f32 = 300.0;
+const BEACON_RADIUS: f32 = 4.0;
+/// Beacons reach from below the river beds to well above the hills, so they show wherever they are.
+const BEACON_BOTTOM: f32 = -20.0;
+const BEACON_TOP: f32 = 1500.0;
+
+/// Route of waypoints to fly, in local positions on the ground, with the next one first.
+#[derive(Resource, Default)]
+pub struct Waypoints(pub Vec<Vec3>);
+
+impl Waypoints {
+    /// Horizontal distance and compass bearing from a position to the next waypoint.
+    pub fn next_from(&self, pos: Vec3) -> Option<(f32, f32)> {
+        let to_waypoint = (*self.0.first()? - pos) * Vec3::new(1., 0., 1.);
+        Some((to_waypoint.length(), compass_heading(to_waypoint)))
+    }
+}
+
+#[derive(Component)]
+pub struct Beacon;
+
+#[derive(Resource)]
+struct BeaconAssets {
+    mesh: Handle<Mesh>,
+    next_material: Handle<StandardMaterial>,
+    material: Handle<StandardMaterial>,
+}
+
+fn setup_beacons(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    commands.insert_resource(BeaconAssets {
+        mesh: meshes.add(Mesh::from(shape::Cylinder {
+            radius: BEACON_RADIUS,
+            height: BEACON_TOP - BEACON_BOTTOM,
+            ..default()
+        })),
+        next_material: materials.add(StandardMaterial {
+            base_color: Color::CYAN,
+            unlit: true,
+            ..default()
+        }),
+        material: materials.add(StandardMaterial {
+            base_color: Color::rgba(0.0, 1.0, 1.0, 0.3),
+            alpha_mode: AlphaMode::Blend,
+            unlit: true,
+            ..default()
+        }),
+    });
+}
+
+/// Moves the waypoints with everything else when the origin is rebased.
+fn shift_waypoints(
+    mut shifted_events: EventReader<OriginShiftedEvent>,
+    mut waypoints: ResMut<Waypoints>,
+) {
+    for OriginShiftedEvent(shift) in shifted_events.iter() {
+        for waypoint in waypoints.0.iter_mut() {
+            *waypoint -= *shift;
+        }
+    }
+}
+
+/// Moves on to the next waypoint once the plane flies over the current one.
+fn pass_waypoints(
+    plane_query: Query<&GlobalTransform, With<Plane>>,
+    mut waypoints: ResMut<Waypoints>,
+) {
+    let Ok(global_tx) = plane_query.get_single() else {
+        return;
+    };
+    let Some((distance, _)) = waypoints.next_from(global_tx.translation()) else {
+        return;
+    };
+
+    if distance < WAYPOINT_RADIUS {
+        waypoints.0.remove(0);
+        info!("Passed waypoint, {} left", waypoints.0.len());
+    }
+}
+
+/// Spawns a beacon over each waypoint, with the next one solid, whenever the route changes.
+fn update_beacons(
+    mut commands: Commands,
+    waypoints: Res<Waypoints>,
+    assets: Res<BeaconAssets>,
+    beacon_query: Query<Entity, With<Beacon>>,
+) {
+    if !waypoints.is_changed() {
+        return;
+    }
+
+    for entity in beacon_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    for (i, waypoint) in waypoints.0.iter().enumerate() {
+        let centre = Vec3::Y * (BEACON_TOP + BEACON_BOTTOM) * 0.5;
+        commands
+            .spawn((
+                Beacon,
+                NotShadowCaster,
+                PbrBundle {
+                    mesh: assets.mesh.clone(),
+                    material: if i == 0 {
+                        assets.next_material.clone()
+                    } else {
+                        assets.material.clone()
+                    },
+                    transform: Transform::from_translation(*waypoint + centre),
+                    ..default()
+                },
+            ))
+            // The padlock camera looks at the waypoint on the ground rather than the beacon's middle
+            .with_children(|parent| {
+                parent.spawn((
+                    LookTarget("waypoint"),
+                    SpatialBundle::from_transform(Transform::from_translation(-centre)),
+                ));
+            });
+    }
+}
@@ -0,0 +1,469 @@
+use std::{
+    collections::VecDeque,
+    io::ErrorKind,
+    net::{SocketAddr, UdpSocket},
+    time::Duration,
+};
+
+use bevy::{prelude::*, time::common_conditions::on_timer};
+use bevy_rapier3d::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    camera::LookTarget,
+    plane::{spec::PlaneSpec, wake::WakeGenerator, ControlDeflection, Plane, Side},
+    world::WorldOrigin,
+};
+
+pub struct NetPlugin;
+
+impl Plugin for NetPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<NetSession>().add_systems(
+            Update,
+            (
+                send_plane_state.run_if(on_timer(Duration::from_millis(SEND_INTERVAL_MS))),
+                receive_plane_states,
+                interpolate_remote_planes,
+                update_remote_control_surfaces,
+            )
+                .chain(),
+        );
+    }
+}
+
+const DEFAULT_PORT: u16 = 7777;
+const SEND_INTERVAL_MS: u64 = 50;
+/// Remote planes are shown this far in the past so there are usually two states to blend between.
+const INTERPOLATION_DELAY: f32 = 0.1;
+/// Longest time a remote plane is moved along its last velocity when states stop arriving.
+const MAX_EXTRAPOLATION: f32 = 0.5;
+/// Time without a state after which a remote plane is removed.
+const TIMEOUT: f32 = 5.0;
+/// States kept for each remote plane, which at the send rate covers well over the delay.
+const MAX_STATES: usize = 20;
+const MAX_PACKET_SIZE: usize = 2048;
+/// Fraction of the remote model's wing chord taken by its ailerons and elevator.
+const REMOTE_SURFACE_CHORD: f32 = 0.3;
+
+/// UDP socket used to exchange plane states with a peer.
+#[derive(Resource)]
+pub struct NetSession {
+    /// Local port to listen on.
+    pub port: u16,
+    /// Address of the peer to send to, e.g. `192.168.1.10:7777`.
+    pub peer: String,
+    socket: Option<UdpSocket>,
+    peer_addr: Option<SocketAddr>,
+    seq: u32,
+}
+
+impl Default for NetSession {
+    fn default() -> Self {
+        Self {
+            port: DEFAULT_PORT,
+            peer: format!("127.0.0.1:{}", DEFAULT_PORT + 1),
+            socket: None,
+            peer_addr: None,
+            seq: 0,
+        }
+    }
+}
+
+impl NetSession {
+    pub fn connected(&self) -> bool {
+        self.socket.is_some()
+    }
+
+    pub fn connect(&mut self) {
+        let peer_addr = match self.peer.parse::<SocketAddr>() {
+            Ok(addr) => addr,
+            Err(err) => {
+                warn!("Invalid peer address {}: {}", self.peer, err);
+                return;
+            }
+        };
+
+        let socket = UdpSocket::bind(("0.0.0.0", self.port))
+            .and_then(|socket| socket.set_nonblocking(true).map(|_| socket));
+
+        match socket {
+            Ok(socket) => {
+                info!("Listening on port {} for peer {}", self.port, peer_addr);
+                self.socket = Some(socket);
+                self.peer_addr = Some(peer_addr);
+            }
+            Err(err) => warn!("Failed to listen on port {}: {}", self.port, err),
+        }
+    }
+
+    pub fn disconnect(&mut self) {
+        info!("Disconnected from peer");
+        self.socket = None;
+        self.peer_addr = None;
+    }
+}
+
+/// State of a plane sent to peers. Positions are absolute so peers agree whatever their origin.
+#[derive(Clone, Serialize, Deserialize)]
+struct PlaneState {
+    seq: u32,
+    fuselage: Vec3,
+    wings: Vec3,
+    mass: f32,
+    translation: Vec3,
+    rotation: Quat,
+    linvel: Vec3,
+    angvel: Vec3,
+    ailerons: f32,
+    elevators: f32,
+    rudder: f32,
+}
+
+impl PlaneState {
+    /// Checks a received state builds a plane and moves it somewhere real, normalising its rotation
+    /// as a peer's may have drifted or been tampered with.
+    fn validated(mut self) -> Option<Self> {
+        let finite = [self.translation, self.linvel, self.angvel]
+            .iter()
+            .all(|v| v.is_finite())
+            && [self.ailerons, self.elevators, self.rudder]
+                .iter()
+                .all(|v| v.is_finite());
+        let sized = [self.fuselage, self.wings]
+            .iter()
+            .all(|size| size.is_finite() && size.min_element() > 0.0)
+            && self.mass.is_finite()
+            && self.mass > 0.0;
+        let length = self.rotation.length();
+        if !finite || !sized || !length.is_finite() || length < f32::EPSILON {
+            return None;
+        }
+
+        self.rotation = self.rotation.normalize();
+        Some(self)
+    }
+}
+
+/// Plane flown by a peer, moved by interpolating between the states received from it.
+#[derive(Component)]
+pub struct RemotePlane {
+    pub addr: SocketAddr,
+    pub ailerons: f32,
+    pub elevators: f32,
+    pub rudder: f32,
+    /// States with the time they were received, oldest first.
+    states: VecDeque<(f32, PlaneState)>,
+}
+
+/// Hinge of one of a remote plane's control surfaces, turned to the deflection the peer flies with.
+#[derive(Component, Clone, Copy)]
+enum RemoteSurface {
+    Aileron(Side),
+    Elevator,
+    Rudder,
+}
+
+fn send_plane_state(
+    mut session: ResMut<NetSession>,
+    origin: Res<WorldOrigin>,
+    plane_query: Query<
+        (
+            &PlaneSpec,
+            &Transform,
+            &Velocity,
+            &ReadMassProperties,
+            &ControlDeflection,
+        ),
+        With<Plane>,
+    >,
+) {
+    let (Some(socket), Some(peer_addr)) = (&session.socket, session.peer_addr) else {
+        return;
+    };
+    let Ok((spec, tx, velocity, ReadMassProperties(mass_properties), deflection)) =
+        plane_query.get_single()
+    else {
+        return;
+    };
+
+    let state = PlaneState {
+        seq: session.seq,
+        fuselage: spec.fuselage.size,
+        wings: spec.main_wing().size,
+        mass: mass_properties.mass,
+        translation: origin.to_absolute(tx.translation),
+        rotation: tx.rotation,
+        linvel: velocity.linvel,
+        angvel: velocity.angvel,
+        ailerons: deflection.ailerons,
+        elevators: deflection.elevators,
+        rudder: deflection.rudder,
+    };
+
+    match ron::to_string(&state) {
+        Ok(message) => {
+            // Lost packets don't matter as the next state replaces them
+            if let Err(err) = socket.send_to(message.as_bytes(), peer_addr) {
+                debug!("Failed to send plane state to {}: {}", peer_addr, err);
+            }
+        }
+        Err(err) => warn!("Failed to encode plane state: {}", err),
+    }
+
+    session.seq = session.seq.wrapping_add(1);
+}
+
+fn receive_plane_states(
+    mut commands: Commands,
+    time: Res<Time>,
+    session: Res<NetSession>,
+    origin: Res<WorldOrigin>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut remote_query: Query<(Entity, &mut RemotePlane)>,
+) {
+    let now = time.raw_elapsed_seconds();
+
+    if let Some(socket) = &session.socket {
+        let mut buf = [0; MAX_PACKET_SIZE];
+        // Spawned planes only appear once commands are applied, so later packets are dropped
+        let mut joined = Vec::new();
+
+        loop {
+            let (len, addr) = match socket.recv_from(&mut buf) {
+                Ok(received) => received,
+                Err(err) if err.kind() == ErrorKind::WouldBlock => break,
+                Err(err) => {
+                    debug!("Failed to receive plane state: {}", err);
+                    break;
+                }
+            };
+            let Some(state) = std::str::from_utf8(&buf[..len])
+                .ok()
+                .and_then(|message| ron::from_str::<PlaneState>(message).ok())
+                .and_then(PlaneState::validated)
+            else {
+                debug!("Ignoring invalid packet from {}", addr);
+                continue;
+            };
+
+            match remote_query
+                .iter_mut()
+                .find(|(_, remote)| remote.addr == addr)
+            {
+                Some((_, mut remote)) => {
+                    // Packets can arrive out of order, and older states are no use
+                    let newer = remote
+                        .states
+                        .back()
+                        .is_none_or(|(_, last)| state.seq.wrapping_sub(last.seq) as i32 > 0);
+                    if newer {
+                        remote.states.push_back((now, state));
+                        if remote.states.len() > MAX_STATES {
+                            remote.states.pop_front();
+                        }
+                    }
+                }
+                None if !joined.contains(&addr) => {
+                    info!("Peer {} joined", addr);
+                    joined.push(addr);
+                    spawn_remote_plane(
+                        &mut commands,
+                        &mut meshes,
+                        &mut materials,
+                        &origin,
+                        addr,
+                        now,
+                        state,
+                    );
+                }
+                None => {}
+            }
+        }
+    }
+
+    for (entity, remote) in remote_query.iter() {
+        let last_received = remote.states.back().map_or(0.0, |(received, _)| *received);
+        if !session.connected() || now - last_received > TIMEOUT {
+            info!("Peer {} left", remote.addr);
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+fn spawn_remote_plane(
+    commands: &mut Commands,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<StandardMaterial>,
+    origin: &WorldOrigin,
+    addr: SocketAddr,
+    received: f32,
+    state: PlaneState,
+) {
+    let material = materials.add(Color::CRIMSON.into());
+    let fuselage = state.fuselage;
+    let wings = state.wings;
+
+    commands
+        .spawn((
+            Name::new(format!("Remote plane {}", addr)),
+            SpatialBundle::from_transform(
+                Transform::from_translation(origin.to_local(state.translation))
+                    .with_rotation(state.rotation),
+            ),
+            // Moved by the received states, but solid so the local plane can collide with it
+            RigidBody::KinematicPositionBased,
+            Collider::compound(vec![
+                (
+                    Vec3::ZERO,
+                    Quat::IDENTITY,
+                    Collider::cuboid(fuselage.x * 0.5, fuselage.y * 0.5, fuselage.z * 0.5),
+                ),
+                (
+                    Vec3::ZERO,
+                    Quat::IDENTITY,
+                    Collider::cuboid(wings.x, wings.y * 0.5, wings.z * 0.5),
+                ),
+            ]),
+            Velocity::linear(state.linvel),
+            WakeGenerator {
+                span: wings.x * 2.0,
+                mass: state.mass,
+            },
+            LookTarget("peer's plane"),
+            RemotePlane {
+                addr,
+                ailerons: state.ailerons,
+                elevators: state.elevators,
+                rudder: state.rudder,
+                states: VecDeque::from([(received, state)]),
+            },
+        ))
+        .with_children(|parent| {
+            parent.spawn(PbrBundle {
+                mesh: meshes.add(Mesh::from(shape::Box::new(
+                    fuselage.x, fuselage.y, fuselage.z,
+                ))),
+                material: material.clone(),
+                ..default()
+            });
+            parent.spawn(PbrBundle {
+                mesh: meshes.add(Mesh::from(shape::Box::new(wings.x * 2.0, wings.y, wings.z))),
+                material: material.clone(),
+                ..default()
+            });
+
+            // Surfaces hang off hinges along the trailing edges so they turn about the edge, with
+            // ailerons on the outer half of each wing and a simple tail at the back of the fuselage
+            let chord = wings.z * REMOTE_SURFACE_CHORD;
+            let tail = fuselage.z * 0.5;
+            let surfaces = [
+                (
+                    RemoteSurface::Aileron(Side::Left),
+                    Vec3::new(-wings.x * 0.75, 0.0, wings.z * 0.5),
+                    Vec3::new(wings.x * 0.5, wings.y, chord),
+                ),
+                (
+                    RemoteSurface::Aileron(Side::Right),
+                    Vec3::new(wings.x * 0.75, 0.0, wings.z * 0.5),
+                    Vec3::new(wings.x * 0.5, wings.y, chord),
+                ),
+                (
+                    RemoteSurface::Elevator,
+                    Vec3::new(0.0, 0.0, tail),
+                    Vec3::new(wings.x * 0.6, wings.y, chord),
+                ),
+                (
+                    RemoteSurface::Rudder,
+                    Vec3::new(0.0, fuselage.y * 1.25, tail),
+                    Vec3::new(wings.y, fuselage.y * 1.5, chord),
+                ),
+            ];
+            for (surface, hinge, size) in surfaces {
+                parent
+                    .spawn((
+                        surface,
+                        SpatialBundle::from_transform(Transform::from_translation(hinge)),
+                    ))
+                    .with_children(|parent| {
+                        parent.spawn(PbrBundle {
+                            mesh: meshes.add(Mesh::from(shape::Box::new(size.x, size.y, size.z))),
+                            material: material.clone(),
+                            transform: Transform::from_xyz(0.0, 0.0, size.z * 0.5),
+                            ..default()
+                        });
+                    });
+            }
+        });
+}
+
+/// Moves remote planes to where they were a short delay ago, blending between received states.
+fn interpolate_remote_planes(
+    time: Res<Time>,
+    origin: Res<WorldOrigin>,
+    mut remote_query: Query<(&mut RemotePlane, &mut Transform, &mut Velocity)>,
+) {
+    let render_time = time.raw_elapsed_seconds() - INTERPOLATION_DELAY;
+
+    for (mut remote, mut tx, mut velocity) in remote_query.iter_mut() {
+        // Drop states that are no longer needed to blend towards the render time
+        while remote.states.len() > 2 && remote.states[1].0 <= render_time {
+            remote.states.pop_front();
+        }
+
+        let (translation, rotation, latest) = match (remote.states.front(), remote.states.get(1)) {
+            (Some((t0, s0)), Some((t1, s1))) if render_time < *t1 => {
+                let s = ((render_time - t0) / (t1 - t0)).clamp(0.0, 1.0);
+                (
+                    s0.translation.lerp(s1.translation, s),
+                    s0.rotation.slerp(s1.rotation, s),
+                    s1,
+                )
+            }
+            _ => {
+                let Some((received, latest)) = remote.states.back() else {
+                    continue;
+                };
+                let ahead = (render_time - received).clamp(0.0, MAX_EXTRAPOLATION);
+                let spin = Quat::from_scaled_axis(latest.angvel * ahead);
+                (
+                    latest.translation + latest.linvel * ahead,
+                    spin * latest.rotation,
+                    latest,
+                )
+            }
+        };
+        let (ailerons, elevators, rudder) = (latest.ailerons, latest.elevators, latest.rudder);
+        // Used for impact speeds in collisions and for the strength of the wake
+        velocity.linvel = latest.linvel;
+        velocity.angvel = latest.angvel;
+
+        tx.translation = origin.to_local(translation);
+        tx.rotation = rotation;
+        remote.ailerons = ailerons;
+        remote.elevators = elevators;
+        remote.rudder = rudder;
+    }
+}
+
+/// Turns remote planes' control surfaces to the peer's deflections, the same way round as the
+/// local plane's so a peer's rolls and turns can be seen coming.
+fn update_remote_control_surfaces(
+    remote_query: Query<(&RemotePlane, &Children)>,
+    mut surface_query: Query<(&RemoteSurface, &mut Transform)>,
+) {
+    for (remote, children) in remote_query.iter() {
+        for child in children.iter() {
+            let Ok((surface, mut tx)) = surface_query.get_mut(*child) else {
+                continue;
+            };
+            tx.rotation = match surface {
+                RemoteSurface::Aileron(Side::Left) => Quat::from_rotation_x(-remote.ailerons),
+                RemoteSurface::Aileron(Side::Right) => Quat::from_rotation_x(remote.ailerons),
+                RemoteSurface::Elevator => Quat::from_rotation_x(remote.elevators),
+                RemoteSurface::Rudder => Quat::from_rotation_y(remote.rudder),
+            };
+        }
+    }
+}
@@ -1,30 +1,115 @@
+use std::ops::RangeInclusive;
+
 use bevy::prelude::*;
-use bevy_rapier3d::prelude::ReadMassProperties;
+use bevy_rapier3d::prelude::*;
+use leafwing_input_manager::prelude::*;
+
+use crate::input::PlaneAction;
 
+/// Length of each physics step, which forces and control laws are integrated over.
+pub const PHYSICS_DT: f32 = 1.0 / 60.0;
+/// Speeds the simulation can run at, from slow motion for studying a spin to time acceleration.
+pub const SIM_SPEEDS: RangeInclusive<f32> = 0.25..=4.0;
+
+/// Steps the physics in `FixedUpdate` so the simulation advances by the same step however fast the
+/// game is rendered or time is accelerated. Requires `RapierPhysicsPlugin` without its default
+/// system setup.
 pub struct PhysicsPlugin;
 
 impl Plugin for PhysicsPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Update, update_centre_of_gravity);
+        app.insert_resource(FixedTime::new_from_secs(PHYSICS_DT))
+            .init_resource::<SimTimeControl>()
+            .insert_resource(RapierConfiguration {
+                timestep_mode: TimestepMode::Fixed {
+                    dt: PHYSICS_DT,
+                    substeps: 1,
+                },
+                ..default()
+            })
+            .configure_sets(
+                FixedUpdate,
+                (
+                    PhysicsSet::SyncBackend,
+                    PhysicsSet::SyncBackendFlush,
+                    PhysicsSet::StepSimulation,
+                    PhysicsSet::Writeback,
+                )
+                    .chain(),
+            )
+            .add_systems(
+                FixedUpdate,
+                (
+                    RapierPhysicsPlugin::<NoUserData>::get_systems(PhysicsSet::SyncBackend)
+                        .in_set(PhysicsSet::SyncBackend),
+                    RapierPhysicsPlugin::<NoUserData>::get_systems(PhysicsSet::SyncBackendFlush)
+                        .in_set(PhysicsSet::SyncBackendFlush),
+                    RapierPhysicsPlugin::<NoUserData>::get_systems(PhysicsSet::StepSimulation)
+                        .in_set(PhysicsSet::StepSimulation),
+                    RapierPhysicsPlugin::<NoUserData>::get_systems(PhysicsSet::Writeback)
+                        .in_set(PhysicsSet::Writeback),
+                ),
+            )
+            .add_systems(Update, (control_sim_time, update_sim_time).chain());
     }
 }
 
-#[derive(Component, Default)]
-pub struct CentreOfGravity {
-    pub local: Vec3,
-    pub global: Vec3,
+/// Pausing and scaling of time, with the physics taking more or fewer fixed steps per frame rather
+/// than longer or shorter ones.
+#[derive(Resource)]
+pub struct SimTimeControl {
+    pub paused: bool,
+    /// Multiple of real time, within `SIM_SPEEDS`.
+    pub speed: f32,
+}
+
+impl Default for SimTimeControl {
+    fn default() -> Self {
+        Self {
+            paused: false,
+            speed: 1.0,
+        }
+    }
 }
 
-fn update_centre_of_gravity(
-    mut query: Query<(&GlobalTransform, &ReadMassProperties, &mut CentreOfGravity)>,
+fn control_sim_time(
+    action_query: Query<&ActionState<PlaneAction>>,
+    mut control: ResMut<SimTimeControl>,
 ) {
-    for (global_tx, ReadMassProperties(mass_properties), mut centre_of_gravity) in query.iter_mut()
-    {
-        let global_centre_of_mass = global_tx.mul_transform(Transform::from_translation(
-            mass_properties.local_center_of_mass,
-        ));
-
-        centre_of_gravity.local = mass_properties.local_center_of_mass;
-        centre_of_gravity.global = global_centre_of_mass.translation();
+    let Ok(action_state) = action_query.get_single() else {
+        return;
+    };
+
+    if action_state.just_pressed(PlaneAction::TogglePause) {
+        control.paused = !control.paused;
+    }
+    if action_state.just_pressed(PlaneAction::SlowDown) {
+        control.speed = (control.speed * 0.5).max(*SIM_SPEEDS.start());
+    }
+    if action_state.just_pressed(PlaneAction::SpeedUp) {
+        control.speed = (control.speed * 2.0).min(*SIM_SPEEDS.end());
+    }
+}
+
+fn update_sim_time(control: Res<SimTimeControl>, mut time: ResMut<Time>) {
+    if !control.is_changed() {
+        return;
+    }
+
+    // Paused time stops the fixed steps and everything else driven by time, but not the UI
+    match (control.paused, time.is_paused()) {
+        (true, false) => {
+            info!("Pausing simulation");
+            time.pause();
+        }
+        (false, true) => {
+            info!("Resuming simulation");
+            time.unpause();
+        }
+        _ => {}
+    }
+    if time.relative_speed() != control.speed {
+        info!("Simulation speed {}x", control.speed);
+        time.set_relative_speed(control.speed);
     }
 }
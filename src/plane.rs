@@ -1,44 +1,208 @@
+pub mod assist;
 mod build;
+pub mod buoyancy;
+pub mod flutter;
+pub mod fly_by_wire;
+mod lod;
+pub mod payload;
+mod reload;
+pub mod rotor;
+pub mod sheet;
+pub mod smoke;
 pub mod spec;
+mod test_flight;
+pub mod tow;
+pub mod tuning;
+pub mod wake;
+pub mod wind_tunnel;
 
-use core::f32;
 use std::ops::AddAssign;
 
-use bevy::prelude::*;
+use bevy::{ecs::query::Has, prelude::*};
 use bevy_rapier3d::prelude::*;
 
-use crate::{physics::CentreOfGravity, world::GizmosControl};
-
-use self::spec::PlaneSpec;
+use crate::{
+    camera::Follow,
+    flight_model::{
+        air_density, calculate_control_surface_lift_coefficient_modifier, Airfoil, Airspeed,
+        AmbientAir, CentreOfGravity, FlightModelSet, Lift, PitotStatic, PlaneFlight, Thrust,
+    },
+    physics::PHYSICS_DT,
+    world::{gizmos_shown, Thermals, Weather},
+};
+
+use self::{
+    assist::AssistedControl,
+    fly_by_wire::FlyByWire,
+    lod::PlaneLod,
+    reload::{PlaneSpecFile, PlaneSpecLoader},
+    spec::{PlaneSpec, Steering},
+    wake::Wake,
+    wind_tunnel::{FlightMode, WindTunnel},
+};
 
 pub struct PlanePlugin;
 
 impl Plugin for PlanePlugin {
     fn build(&self, app: &mut App) {
         app.add_event::<BuildPlaneEvent>()
-            .add_systems(Startup, (setup_plane, apply_deferred).chain())
+            .add_asset::<PlaneSpecFile>()
+            .init_asset_loader::<PlaneSpecLoader>()
+            .init_resource::<SelectedPlane>()
+            .init_resource::<Wake>()
+            .init_resource::<smoke::SmokeSettings>()
+            .init_resource::<tow::Tow>()
+            .add_event::<tow::TowEvent>()
+            .add_event::<tow::AerotowEvent>()
+            .add_state::<FlightMode>()
+            .init_resource::<assist::AssistLevel>()
+            .init_resource::<WindTunnel>()
+            .configure_sets(Update, (PlaneSet::Build, PlaneSet::Input).chain())
+            .configure_sets(
+                FixedUpdate,
+                (PlaneSet::Controls, PlaneSet::Forces)
+                    .chain()
+                    .before(PhysicsSet::SyncBackend),
+            )
+            .configure_sets(
+                FixedUpdate,
+                (FlightModelSet::Airflow, FlightModelSet::Forces).in_set(PlaneSet::Forces),
+            )
+            .add_systems(
+                Startup,
+                (setup_plane, reload::load_plane_specs, apply_deferred).chain(),
+            )
+            .add_systems(Startup, (smoke::setup_smoke, payload::setup_payloads))
+            .add_systems(Update, reload::reload_plane_specs.before(PlaneSet::Build))
+            .add_systems(
+                Update,
+                (tow::release_tow_on_key, tow::attach_tow)
+                    .chain()
+                    .after(PlaneSet::Build),
+            )
+            .add_systems(
+                FixedUpdate,
+                (
+                    tow::fly_tug.before(PhysicsSet::SyncBackend),
+                    tow::update_tow_forces.in_set(PlaneSet::Forces),
+                ),
+            )
+            .add_systems(
+                Update,
+                (
+                    (wind_tunnel::hold_plane, wind_tunnel::sweep_angle).after(PlaneSet::Build),
+                    wind_tunnel::draw_tunnel_gizmos,
+                )
+                    .run_if(in_state(FlightMode::WindTunnel)),
+            )
+            .add_systems(
+                FixedUpdate,
+                (
+                    wind_tunnel::impose_airflow
+                        .after(update_ambient_air)
+                        .before(FlightModelSet::Airflow)
+                        .in_set(PlaneSet::Forces),
+                    wind_tunnel::measure_forces
+                        .after(PlaneSet::Forces)
+                        .before(PhysicsSet::SyncBackend),
+                )
+                    .run_if(in_state(FlightMode::WindTunnel)),
+            )
+            .add_systems(OnExit(FlightMode::WindTunnel), wind_tunnel::release_plane)
+            .add_systems(
+                Update,
+                (build_plane, build::build_plane, update_selected_plane)
+                    .chain()
+                    .in_set(PlaneSet::Build),
+            )
+            .add_systems(
+                FixedUpdate,
+                (
+                    assist::apply_assist,
+                    fly_by_wire::apply_control_law,
+                    actuate_control_surfaces,
+                    update_airfoil_control_surfaces,
+                    flutter::apply_flutter,
+                )
+                    .chain()
+                    .in_set(PlaneSet::Controls),
+            )
+            .add_systems(
+                FixedUpdate,
+                (
+                    update_ambient_air.before(FlightModelSet::Airflow),
+                    (
+                        flutter::update_flutter,
+                        update_altitude,
+                        wake::update_wake,
+                        wake::update_airfoil_downwash,
+                    )
+                        .chain()
+                        .after(FlightModelSet::Airflow)
+                        .before(FlightModelSet::Forces),
+                    // The flight model clears the accumulated forces so the rest must come after it
+                    (
+                        update_fuselage_forces,
+                        rotor::update_rotor_forces,
+                        update_wheel_forces,
+                        buoyancy::update_buoyancy_forces,
+                    )
+                        .chain()
+                        .after(FlightModelSet::Forces),
+                )
+                    .in_set(PlaneSet::Forces)
+                    .run_if(any_with_component::<Plane>()),
+            )
+            .add_systems(
+                FixedUpdate,
+                (
+                    update_load_factor,
+                    update_vertical_speed,
+                    update_turn_coordinator,
+                    payload::land_payloads,
+                )
+                    .after(PhysicsSet::Writeback),
+            )
             .add_systems(
                 Update,
                 (
                     (
-                        (build_plane, build::build_plane).chain(),
-                        update_propellor,
-                        update_airfoil_control_surfaces,
-                        update_airspeed,
-                        update_thrust_forces,
-                        update_airfoil_forces,
+                        draw_plane_gizmos,
+                        draw_airfoil_gizmos,
+                        wake::draw_wake_gizmos,
                     )
-                        .chain(),
-                    draw_plane_gizmos,
-                    draw_airfoil_gizmos,
+                        .run_if(gizmos_shown),
+                    lod::update_plane_lod,
+                    update_propellor,
+                    rotor::spin_rotor,
+                    wake::rebase_wake,
+                    (smoke::toggle_smoke, smoke::emit_smoke, smoke::update_smoke).chain(),
+                    payload::release_payload,
+                    payload::update_splashes,
                 ),
             );
     }
 }
 
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub enum PlaneSet {
+    /// Spawning planes and their parts from a `PlaneSpec`.
+    Build,
+    /// Reading the pilot's input into `PlaneControl`.
+    Input,
+    /// Running control laws and applying them to the control surfaces, each physics step.
+    Controls,
+    /// Updating flight data and accumulating the forces acting on the plane, each physics step.
+    Forces,
+}
+
 #[derive(Event)]
 pub struct BuildPlaneEvent(pub PlaneSpec);
 
+/// Plane that debug gizmos, the Stats window and telemetry plots refer to.
+#[derive(Resource, Default)]
+pub struct SelectedPlane(pub Option<Entity>);
+
 #[derive(Component)]
 pub struct Plane;
 
@@ -47,6 +211,8 @@ pub struct PlaneControl {
     pub ailerons: f32,
     pub elevators: f32,
     pub rudder: f32,
+    /// Wheel brake pressure in the range `0..1`.
+    pub brake: f32,
 }
 
 impl PlaneControl {
@@ -55,61 +221,81 @@ impl PlaneControl {
     }
 }
 
+/// Angles the control surfaces are actually deflected to, following the `AssistedControl` inputs no
+/// faster than the spec's max control rates.
 #[derive(Component, Default)]
-pub struct Thrust(pub f32);
-
-#[derive(Component, Default)]
-pub struct Airspeed(pub f32);
+pub struct ControlDeflection {
+    pub ailerons: f32,
+    pub elevators: f32,
+    pub rudder: f32,
+}
 
 #[derive(Component, Default)]
 pub struct Altitude(pub f32);
 
+/// Acceleration felt along the plane's up axis in multiples of gravity, as the pilot pulling
+/// through a turn feels it.
 #[derive(Component, Default)]
-pub struct Lift(pub f32);
+pub struct LoadFactor {
+    /// Smoothed over a few physics steps, so knocks from the wheels don't show as spikes.
+    pub g: f32,
+    last_velocity: Option<Vec3>,
+}
 
+/// Rate of climb as the vertical speed indicator shows it, from the change in the indicated altitude,
+/// so it stops with the altimeter when the static port is blocked.
 #[derive(Component, Default)]
-pub struct AngleOfAttack(pub f32);
+pub struct VerticalSpeed {
+    /// Rate in m/s, lagging the climb a little as the instrument does.
+    pub rate: f32,
+    last_altitude: Option<f32>,
+}
 
+/// Turn rate and slip as the turn coordinator shows them.
 #[derive(Component, Default)]
-pub struct PlaneFlight {
-    pub angle_of_attack: f32,
-    pub weight: f32,
-    pub drag: f32,
+pub struct TurnCoordinator {
+    /// Rate the heading is changing in rad/s, positive turning right.
+    pub turn_rate: f32,
+    /// Sideways acceleration in multiples of gravity that throws the ball, positive to the right
+    /// as in a skid to the left, and zero in a coordinated turn.
+    pub slip: f32,
+    last_velocity: Option<Vec3>,
 }
 
 #[derive(Component, PartialEq, Eq, Debug, Clone, Copy)]
 pub enum AirfoilPosition {
     Wing(Side),
     TailWing(Side),
+    Canard(Side),
+    Elevon(Side),
     VerticalTail,
 }
 
-#[derive(Debug, Clone, Copy)]
-pub enum AirfoilOrientation {
-    Horizontal,
-    Vertical,
-}
+/// Fraction of each airfoil's chord taken by the control surface along its trailing edge.
+pub const CONTROL_SURFACE_CHORD: f32 = 0.25;
 
+/// Hinge of a control surface along the trailing edge of its airfoil, turned to the surface's
+/// deflection. The surface's mesh and collider are children of it, behind the hinge line.
+///
+/// Each side's surface is deflected on its own, so one can fail while the other keeps working.
 #[derive(Component)]
-pub struct Airfoil {
-    pub orientation: AirfoilOrientation,
-    pub area: f32,
-    pub lift_coefficient_samples: Vec<f32>,
-    pub drag_coefficient_samples: Vec<f32>,
-    pub lift_coefficient_modifier: f32,
-}
-
-impl Airfoil {
-    pub fn force_base_dir(&self, global_tx: &GlobalTransform) -> Vec3 {
-        match self.orientation {
-            AirfoilOrientation::Horizontal => global_tx.up(),
-            AirfoilOrientation::Vertical => global_tx.right(),
-        }
-    }
+pub struct ControlSurface {
+    /// Hinge line in the airfoil's frame, which the surface turns about with its trailing edge down,
+    /// or to the right for the rudder, for a positive deflection.
+    pub hinge_axis: Vec3,
+    /// Angle the surface is deflected to in radians, which differs from the one commanded once
+    /// it's failed.
+    pub angle: f32,
 }
 
-#[derive(Component)]
-pub struct ControlSurface;
+/// Failure stopping a control surface from following the controls.
+#[derive(Component, Clone, Copy, Debug, PartialEq)]
+pub enum SurfaceFailure {
+    /// Stuck at the angle it was at when it jammed.
+    Jammed(f32),
+    /// Broken free of its actuator, trailing in the airflow with no deflection.
+    Floating,
+}
 
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
 pub enum Side {
@@ -129,6 +315,33 @@ impl Side {
 #[derive(Component)]
 pub struct Propellor;
 
+/// Engine speed above which the propellor is drawn as a blurred disk rather than spinning blades.
+const PROPELLOR_BLUR_RPM: f32 = 1200.0;
+
+/// Meshes and materials a propellor swaps between, its blades when turning slowly and a
+/// translucent disk once they'd blur.
+#[derive(Component)]
+pub struct PropellorBlur {
+    pub blades: (Handle<Mesh>, Handle<StandardMaterial>),
+    pub disk: (Handle<Mesh>, Handle<StandardMaterial>),
+}
+
+/// Pitch of the propellor blades, which can be turned past flat into reverse for braking after
+/// touchdown. In reverse the throttle drives the thrust backwards, down to the spec's
+/// `reverse_thrust`.
+#[derive(Component, Default)]
+pub struct PropellorPitch {
+    pub reversed: bool,
+}
+
+#[derive(Component)]
+pub struct Wheel {
+    /// Side of the plane used for differential braking, or `None` for a centreline wheel.
+    pub side: Option<Side>,
+    /// How the wheel turns to the direction it rolls in.
+    pub steering: Steering,
+}
+
 fn setup_plane(mut build_plane_event: EventWriter<BuildPlaneEvent>) {
     build_plane_event.send(BuildPlaneEvent(PlaneSpec::default()));
 }
@@ -139,196 +352,405 @@ fn build_plane(
     mut build_plane_event: EventReader<BuildPlaneEvent>,
 ) {
     for BuildPlaneEvent(spec) in build_plane_event.iter() {
+        // Keep flying the current plane rather than swap it for one that can't be built
+        if let Err(errors) = spec.validate() {
+            for error in errors {
+                warn!("Not building plane spec {}: {}", spec.name, error);
+            }
+            continue;
+        }
+
         if let Ok(entity) = plane_query.get_single() {
             info!("Removing existing plane");
             commands.entity(entity).despawn_recursive();
         }
 
-        info!("Building plane");
+        info!("Building plane {}", spec.name);
         commands.spawn(spec.clone());
     }
 }
 
-fn angle_of_attack(velocity: Vec3, up: Vec3, forward: Vec3) -> f32 {
-    let a1 = up.angle_between(forward);
-    let a2 = up.angle_between(velocity.normalize());
+/// Keeps a plane selected for debugging, falling back to the one the camera follows when the
+/// selection is despawned, e.g. by rebuilding the plane.
+fn update_selected_plane(
+    mut selected: ResMut<SelectedPlane>,
+    plane_query: Query<(Entity, Has<Follow>), With<Plane>>,
+) {
+    if selected
+        .0
+        .is_some_and(|entity| plane_query.contains(entity))
+    {
+        return;
+    }
+
+    let fallback = plane_query
+        .iter()
+        .max_by_key(|(_, followed)| *followed)
+        .map(|(entity, _)| entity);
+
+    if selected.0 != fallback {
+        selected.0 = fallback;
+    }
+}
+
+/// Pitch and roll of a plane in radians, positive for nose up and right wing down.
+pub fn attitude(global_tx: &GlobalTransform) -> (f32, f32) {
+    let pitch = global_tx.forward().y.clamp(-1.0, 1.0).asin();
+    let roll = (-global_tx.right().y).atan2(global_tx.up().y);
 
-    a2 - a1
+    (pitch, roll)
 }
 
-// Taken from https://aviation.stackexchange.com/questions/46217/how-does-rudder-size-influence-its-ability-to-produce-lateral-lift
-fn calculate_control_surface_lift_coefficient_modifier(
-    flap_relative_chord: f32,
-    flap_deflection_angle: f32,
-) -> f32 {
-    flap_relative_chord.sqrt() * flap_deflection_angle
+/// Moves each control surface towards the deflection asked for, so even an instant full input from
+/// a key press takes time to reach the surfaces.
+fn actuate_control_surfaces(
+    mut plane_query: Query<(
+        &AssistedControl,
+        Option<&FlyByWire>,
+        &PlaneSpec,
+        &mut ControlDeflection,
+    )>,
+) {
+    let actuate = |current: f32, target: f32, rate: f32| {
+        let step = rate.max(0.0) * PHYSICS_DT;
+        current + (target - current).clamp(-step, step)
+    };
+
+    for (control, fbw, spec, mut deflection) in plane_query.iter_mut() {
+        // Fly by wire planes deflect the surfaces the control law asks for rather than the pilot's
+        // assisted command
+        let (ailerons, elevators) = fbw.map_or((control.ailerons, control.elevators), |fbw| {
+            (fbw.ailerons, fbw.elevators)
+        });
+
+        deflection.ailerons = actuate(
+            deflection.ailerons,
+            ailerons,
+            spec.main_wing().max_control_rate,
+        );
+        deflection.elevators = actuate(
+            deflection.elevators,
+            elevators,
+            spec.pitch_wing().max_control_rate,
+        );
+        deflection.rudder = actuate(
+            deflection.rudder,
+            control.rudder,
+            spec.fin().max_control_rate,
+        );
+    }
 }
 
+/// Turns each control surface about its hinge to the deflection it's actuated to, or holds it where
+/// it's failed, and changes the lift of its airfoil by the same angle, so what's drawn is what's
+/// flown. Surfaces on either side that end up at different angles roll or yaw the plane.
 fn update_airfoil_control_surfaces(
-    control_query: Query<&PlaneControl>,
+    control_query: Query<&ControlDeflection>,
     mut wing_query: Query<(&mut Airfoil, &AirfoilPosition, &Parent, &Children)>,
-    mut control_airfoil_query: Query<&mut Transform, With<ControlSurface>>,
+    mut control_surface_query: Query<(
+        &mut Transform,
+        &mut ControlSurface,
+        Option<&SurfaceFailure>,
+    )>,
 ) {
     for (mut airfoil, position, entity, children) in wing_query.iter_mut() {
-        if let Ok(control) = control_query.get(**entity) {
-            for child in children.iter() {
-                if let Ok(mut control_airfoil_tx) = control_airfoil_query.get_mut(*child) {
-                    match position {
-                        AirfoilPosition::Wing(Side::Left) => {
-                            control_airfoil_tx.rotation = Quat::from_rotation_x(-control.ailerons);
-                            airfoil.lift_coefficient_modifier =
-                                calculate_control_surface_lift_coefficient_modifier(
-                                    0.25,
-                                    -control.ailerons,
-                                );
-                        }
-                        AirfoilPosition::Wing(Side::Right) => {
-                            control_airfoil_tx.rotation = Quat::from_rotation_x(control.ailerons);
-                            airfoil.lift_coefficient_modifier =
-                                calculate_control_surface_lift_coefficient_modifier(
-                                    0.25,
-                                    control.ailerons,
-                                );
-                        }
-                        AirfoilPosition::TailWing(_) => {
-                            control_airfoil_tx.rotation = Quat::from_rotation_x(control.elevators);
-                            airfoil.lift_coefficient_modifier =
-                                calculate_control_surface_lift_coefficient_modifier(
-                                    0.25,
-                                    control.elevators,
-                                );
-                        }
-                        AirfoilPosition::VerticalTail => {
-                            control_airfoil_tx.rotation = Quat::from_rotation_y(control.rudder);
-                            airfoil.lift_coefficient_modifier =
-                                calculate_control_surface_lift_coefficient_modifier(
-                                    0.25,
-                                    control.rudder,
-                                );
-                        }
-                    }
-                }
+        let Ok(deflection) = control_query.get(**entity) else {
+            continue;
+        };
+
+        let commanded = match position {
+            AirfoilPosition::Wing(Side::Left) => -deflection.ailerons,
+            AirfoilPosition::Wing(Side::Right) => deflection.ailerons,
+            AirfoilPosition::TailWing(_) => deflection.elevators,
+            // Foreplanes are in front of the centre of gravity so pitch the other way
+            AirfoilPosition::Canard(_) => -deflection.elevators,
+            AirfoilPosition::Elevon(side) => {
+                deflection.elevators - deflection.ailerons * side.offset()
+            }
+            AirfoilPosition::VerticalTail => deflection.rudder,
+        };
+
+        for child in children.iter() {
+            if let Ok((mut surface_tx, mut surface, failure)) =
+                control_surface_query.get_mut(*child)
+            {
+                surface.angle = match failure {
+                    Some(SurfaceFailure::Jammed(angle)) => *angle,
+                    Some(SurfaceFailure::Floating) => 0.0,
+                    None => commanded,
+                };
+                surface_tx.rotation = Quat::from_axis_angle(surface.hinge_axis, surface.angle);
+                airfoil.lift_coefficient_modifier =
+                    calculate_control_surface_lift_coefficient_modifier(
+                        CONTROL_SURFACE_CHORD,
+                        surface.angle,
+                    );
             }
         }
     }
 }
 
+/// Spins the propellor with the engine, drawing it as a blurred disk once it's turning too fast to
+/// pick out the blades.
+#[allow(clippy::type_complexity)]
 fn update_propellor(
     plane_query: Query<(&Thrust, &PlaneSpec)>,
-    mut propellor_query: Query<&mut Transform, With<Propellor>>,
+    mut propellor_query: Query<
+        (
+            &mut Transform,
+            &PropellorBlur,
+            &mut Handle<Mesh>,
+            &mut Handle<StandardMaterial>,
+        ),
+        With<Propellor>,
+    >,
     time: Res<Time>,
 ) {
     let Ok((Thrust(thrust), spec)) = plane_query.get_single() else {
         return;
     };
 
-    for mut tx in propellor_query.iter_mut() {
-        let rate = (*thrust / spec.thrust) * 3600_f32.to_radians();
+    // The blades turn the same way whichever way they're pitched
+    let power = thrust.abs() / spec.thrust;
+    let blurred = spec.engine.rpm(power) > PROPELLOR_BLUR_RPM;
+
+    for (mut tx, blur, mut mesh, mut material) in propellor_query.iter_mut() {
+        let rate = power * 3600_f32.to_radians();
         tx.rotate_local_z(rate * time.delta_seconds());
+
+        let (shown_mesh, shown_material) = if blurred { &blur.disk } else { &blur.blades };
+        if *mesh != *shown_mesh {
+            *mesh = shown_mesh.clone();
+            *material = shown_material.clone();
+        }
+    }
+}
+
+/// Measures the load factor from the change in velocity over each physics step. Transforms are
+/// used rather than global transforms, which aren't updated from the physics until later.
+fn update_load_factor(
+    mut plane_query: Query<(&Transform, &Velocity, &mut LoadFactor), With<Plane>>,
+) {
+    for (tx, velocity, mut load_factor) in plane_query.iter_mut() {
+        let Some(last_velocity) = load_factor.last_velocity.replace(velocity.linvel) else {
+            load_factor.g = 1.0;
+            continue;
+        };
+
+        let acceleration = (velocity.linvel - last_velocity) / PHYSICS_DT;
+        let g = (acceleration + Vec3::Y * GRAVITY).dot(tx.up()) / GRAVITY;
+        load_factor.g += (g - load_factor.g) * (PHYSICS_DT / LOAD_FACTOR_SMOOTHING).min(1.0);
+    }
+}
+
+fn update_vertical_speed(mut plane_query: Query<(&PitotStatic, &mut VerticalSpeed), With<Plane>>) {
+    for (pitot_static, mut vertical_speed) in plane_query.iter_mut() {
+        let altitude = pitot_static.indicated_altitude;
+        let Some(last_altitude) = vertical_speed.last_altitude.replace(altitude) else {
+            continue;
+        };
+
+        let rate = (altitude - last_altitude) / PHYSICS_DT;
+        vertical_speed.rate +=
+            (rate - vertical_speed.rate) * (PHYSICS_DT / VERTICAL_SPEED_LAG).min(1.0);
+    }
+}
+
+/// Measures the turn rate from the rotation about the vertical, and the slip from the sideways
+/// part of the acceleration felt over each physics step, smoothed like the load factor.
+fn update_turn_coordinator(
+    mut plane_query: Query<(&Transform, &Velocity, &mut TurnCoordinator), With<Plane>>,
+) {
+    for (tx, velocity, mut turn_coordinator) in plane_query.iter_mut() {
+        turn_coordinator.turn_rate = -velocity.angvel.y;
+
+        let Some(last_velocity) = turn_coordinator.last_velocity.replace(velocity.linvel) else {
+            continue;
+        };
+
+        let acceleration = (velocity.linvel - last_velocity) / PHYSICS_DT;
+        let slip = -(acceleration + Vec3::Y * GRAVITY).dot(tx.right()) / GRAVITY;
+        turn_coordinator.slip +=
+            (slip - turn_coordinator.slip) * (PHYSICS_DT / LOAD_FACTOR_SMOOTHING).min(1.0);
     }
 }
 
-fn update_airspeed(mut plane_query: Query<(&GlobalTransform, &Velocity, &mut Airspeed)>) {
-    for (global_tx, velocity, mut airspeed) in plane_query.iter_mut() {
-        let local_velocity = (global_tx.translation() + velocity.linvel) - global_tx.translation();
-        airspeed.0 = -local_velocity.z;
+fn update_altitude(mut plane_query: Query<(&GlobalTransform, &mut Altitude)>) {
+    for (global_tx, mut altitude) in plane_query.iter_mut() {
+        altitude.0 = global_tx.translation().y;
     }
 }
 
-fn update_thrust_forces(
+/// Fills in the air each plane flies through from the weather. The air moves with the wind, and
+/// rising air in thermals comes at the wings from below.
+fn update_ambient_air(
+    mut plane_query: Query<(&GlobalTransform, &mut AmbientAir), With<Plane>>,
+    weather: Res<Weather>,
+    thermals: Res<Thermals>,
+) {
+    for (global_tx, mut ambient_air) in plane_query.iter_mut() {
+        ambient_air.velocity = weather.wind + Vec3::Y * thermals.updraft(global_tx.translation());
+        ambient_air.lift_factor = weather.preset.lift_factor();
+    }
+}
+
+/// Side force coefficient of the fuselage per unit of the sine of the sideslip angle.
+const FUSELAGE_SIDE_FORCE: f32 = 0.3;
+/// Yaw damping coefficient of the fuselage, scaling the torque from the side of the fuselage
+/// moving through the air as the plane yaws.
+const FUSELAGE_YAW_DAMPING: f32 = 0.02;
+
+/// The side of the fuselage pushes back against the airflow in a sideslip, and resists yawing as
+/// its ends swing through the air.
+#[allow(clippy::type_complexity)]
+fn update_fuselage_forces(
     mut plane_query: Query<
         (
-            &PlaneSpec,
-            &Thrust,
             &GlobalTransform,
+            &PlaneFlight,
+            &Airspeed,
+            &Velocity,
             &CentreOfGravity,
+            &PlaneSpec,
             &mut ExternalForce,
         ),
         With<Plane>,
     >,
 ) {
-    for (spec, Thrust(thrust), global_tx, centre_of_gravity, mut external_force) in
-        plane_query.iter_mut()
+    for (
+        global_tx,
+        flight,
+        Airspeed(airspeed),
+        velocity,
+        centre_of_gravity,
+        spec,
+        mut external_force,
+    ) in plane_query.iter_mut()
     {
-        external_force.force = Vec3::ZERO;
-        external_force.torque = Vec3::ZERO;
+        let density = air_density(global_tx.translation().y);
+        let dynamic_pressure = 0.5 * density * airspeed * airspeed;
+        let side_area = spec.fuselage.size.y * spec.fuselage.size.z;
+        let side_force = FUSELAGE_SIDE_FORCE * flight.sideslip.sin() * dynamic_pressure * side_area;
         external_force.add_assign(ExternalForce::at_point(
-            global_tx.forward() * *thrust,
-            global_tx.translation() + (global_tx.forward() * spec.fuselage.size.z * 0.5),
+            -global_tx.right() * side_force,
+            global_tx.translation(),
             centre_of_gravity.global,
         ));
+
+        let yaw_rate = velocity.angvel.dot(global_tx.up());
+        let length = spec.fuselage.size.z;
+        let yaw_damping =
+            FUSELAGE_YAW_DAMPING * 0.5 * density * airspeed * side_area * length * length;
+        external_force.torque -= global_tx.up() * yaw_rate * yaw_damping;
     }
 }
 
-fn update_airfoil_forces(
+const GRAVITY: f32 = 9.81;
+/// Time constant in seconds of the smoothing applied to the load factor.
+const LOAD_FACTOR_SMOOTHING: f32 = 0.2;
+/// Time constant in seconds of the vertical speed indicator's lag behind the climb.
+const VERTICAL_SPEED_LAG: f32 = 0.5;
+const ROLLING_RESISTANCE: f32 = 0.02;
+const BRAKE_FRICTION: f32 = 0.6;
+const LATERAL_FRICTION: f32 = 0.8;
+const MAX_STEERING_ANGLE: f32 = 30.0;
+/// Wheel speed below which friction forces are scaled down to avoid jitter when stationary.
+const FRICTION_SPEED_THRESHOLD: f32 = 0.5;
+
+#[allow(clippy::type_complexity)]
+fn update_wheel_forces(
     mut plane_query: Query<
         (
             Entity,
-            &mut PlaneFlight,
-            &Airspeed,
+            &GlobalTransform,
+            &PlaneControl,
+            &PlaneSpec,
             &Velocity,
+            &ReadMassProperties,
             &CentreOfGravity,
             &mut ExternalForce,
         ),
         With<Plane>,
     >,
     children_query: Query<&Children>,
-    mut airfoil_query: Query<(&Airfoil, &GlobalTransform, &mut AngleOfAttack, &mut Lift)>,
+    wheel_query: Query<(Entity, &Wheel, &GlobalTransform)>,
+    rapier_context: Res<RapierContext>,
 ) {
     for (
         plane_entity,
-        mut flight,
-        Airspeed(airspeed),
+        global_tx,
+        control,
+        spec,
         velocity,
+        ReadMassProperties(mass_properties),
         centre_of_gravity,
         mut external_force,
     ) in plane_query.iter_mut()
     {
-        let air_density = 1.225; // 1.225 kg/m^3 at sea level
-        let dynamic_pressure = 0.5 * air_density * airspeed * airspeed;
-
-        for child in children_query.iter_descendants(plane_entity) {
-            if let Ok((airfoil, airfoil_global_tx, mut aoa, mut airfoil_lift)) =
-                airfoil_query.get_mut(child)
-            {
-                let angle_of_attack = angle_of_attack(
-                    velocity.linvel,
-                    airfoil.force_base_dir(airfoil_global_tx),
-                    airfoil_global_tx.forward(),
-                );
-
-                aoa.0 = angle_of_attack;
-
-                let lift_coefficient_index = (angle_of_attack.to_degrees() + 90.0) as usize;
-
-                let lift_coefficient = airfoil
-                    .lift_coefficient_samples
-                    .get(lift_coefficient_index)
-                    .unwrap_or(&0.0);
-
-                let lift = (lift_coefficient + airfoil.lift_coefficient_modifier)
-                    * dynamic_pressure
-                    * airfoil.area;
-                airfoil_lift.0 = lift;
-
-                external_force.add_assign(ExternalForce::at_point(
-                    airfoil.force_base_dir(airfoil_global_tx) * lift,
-                    airfoil_global_tx.translation(),
-                    centre_of_gravity.global,
-                ));
-
-                let drag_coefficient_index = (angle_of_attack.to_degrees() + 90.0) as usize;
-
-                let drag_coefficient = airfoil
-                    .drag_coefficient_samples
-                    .get(drag_coefficient_index)
-                    .unwrap_or(&0.0);
-                let drag = drag_coefficient * dynamic_pressure * airfoil.area;
-
-                external_force.force += -velocity.linvel.normalize_or_zero() * drag;
+        let grounded_wheels: Vec<_> = children_query
+            .iter_descendants(plane_entity)
+            .filter_map(|child| wheel_query.get(child).ok())
+            .filter(|(entity, _, _)| {
+                rapier_context
+                    .contacts_with(*entity)
+                    .any(|pair| pair.has_any_active_contacts())
+            })
+            .collect();
+
+        if grounded_wheels.is_empty() {
+            continue;
+        }
 
-                flight.drag = drag;
-            }
+        let load = mass_properties.mass * GRAVITY / grounded_wheels.len() as f32;
+        let rudder = match spec.fin().max_control_angle {
+            max if max > 0.0 => (control.rudder / max).clamp(-1.0, 1.0),
+            _ => 0.0,
+        };
+
+        for (_, wheel, wheel_global_tx) in grounded_wheels {
+            let wheel_pos = wheel_global_tx.translation();
+            let point_velocity =
+                velocity.linvel + velocity.angvel.cross(wheel_pos - centre_of_gravity.global);
+
+            let forward = match wheel.steering {
+                Steering::Fixed => global_tx.forward(),
+                Steering::Rudder => {
+                    Quat::from_axis_angle(global_tx.up(), -rudder * MAX_STEERING_ANGLE.to_radians())
+                        * global_tx.forward()
+                }
+                // Trails behind its pivot, so rolls whichever way it's moving with no side force
+                Steering::Castoring => {
+                    let rolling = point_velocity.reject_from(global_tx.up());
+                    match rolling.length() > FRICTION_SPEED_THRESHOLD {
+                        true => rolling.normalize() * rolling.dot(global_tx.forward()).signum(),
+                        false => global_tx.forward(),
+                    }
+                }
+            };
+            let right = forward.cross(global_tx.up());
+
+            // Rudder input shifts brake pressure towards the wheel on the side of the turn
+            let brake = match wheel.side {
+                Some(Side::Left) => control.brake * (1.0 - rudder.max(0.0)),
+                Some(Side::Right) => control.brake * (1.0 + rudder.min(0.0)),
+                None => 0.0,
+            };
+
+            let forward_speed = point_velocity.dot(forward);
+            let lateral_speed = point_velocity.dot(right);
+
+            let longitudinal_force = -(forward_speed / FRICTION_SPEED_THRESHOLD).clamp(-1.0, 1.0)
+                * (ROLLING_RESISTANCE + BRAKE_FRICTION * brake)
+                * load;
+            let lateral_force = -(lateral_speed / FRICTION_SPEED_THRESHOLD).clamp(-1.0, 1.0)
+                * LATERAL_FRICTION
+                * load;
+
+            external_force.add_assign(ExternalForce::at_point(
+                forward * longitudinal_force + right * lateral_force,
+                wheel_pos,
+                centre_of_gravity.global,
+            ));
         }
     }
 }
@@ -345,44 +767,52 @@ fn draw_plane_gizmos(
         ),
         With<Plane>,
     >,
-    gizmos_control: Res<GizmosControl>,
+    selected: Res<SelectedPlane>,
     mut gizmos: Gizmos,
 ) {
-    if !gizmos_control.show {
+    let Some(Ok((global_tx, velocity, centre_of_gravity, external_force))) =
+        selected.0.map(|entity| plane_query.get(entity))
+    else {
         return;
-    }
+    };
 
-    for (global_tx, velocity, centre_of_gravity, external_force) in plane_query.iter() {
-        gizmos.sphere(centre_of_gravity.global, Quat::IDENTITY, 2., Color::GRAY);
+    gizmos.sphere(centre_of_gravity.global, Quat::IDENTITY, 2., Color::GRAY);
 
-        gizmos.line(
-            global_tx.translation(),
-            global_tx.translation() + external_force.force,
-            FORCE_COLOR,
-        );
+    gizmos.line(
+        global_tx.translation(),
+        global_tx.translation() + external_force.force,
+        FORCE_COLOR,
+    );
 
-        gizmos.line(
-            global_tx.translation(),
-            global_tx.translation() + velocity.linvel,
-            Color::GREEN,
-        );
-    }
+    gizmos.line(
+        global_tx.translation(),
+        global_tx.translation() + velocity.linvel,
+        Color::GREEN,
+    );
 }
 
 fn draw_airfoil_gizmos(
+    plane_query: Query<(Entity, &PlaneLod), With<Plane>>,
+    children_query: Query<&Children>,
     airfoil_query: Query<(&Airfoil, &GlobalTransform, &Lift)>,
-    gizmos_control: Res<GizmosControl>,
+    selected: Res<SelectedPlane>,
     mut gizmos: Gizmos,
 ) {
-    if !gizmos_control.show {
+    let Some(Ok((plane_entity, lod))) = selected.0.map(|entity| plane_query.get(entity)) else {
+        return;
+    };
+
+    if *lod != PlaneLod::Detailed {
         return;
     }
 
-    for (airfoil, airfoil_global_tx, Lift(lift)) in airfoil_query.iter() {
-        gizmos.line(
-            airfoil_global_tx.translation(),
-            airfoil_global_tx.translation() + airfoil.force_base_dir(airfoil_global_tx) * *lift,
-            FORCE_COLOR,
-        );
+    for child in children_query.iter_descendants(plane_entity) {
+        if let Ok((airfoil, airfoil_global_tx, Lift(lift))) = airfoil_query.get(child) {
+            gizmos.line(
+                airfoil_global_tx.translation(),
+                airfoil_global_tx.translation() + airfoil.force_base_dir(airfoil_global_tx) * *lift,
+                FORCE_COLOR,
+            );
+        }
     }
 }
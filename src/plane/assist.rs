@@ -0,0 +1,140 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::flight_model::{Airspeed, PlaneFlight};
+
+use super::{
+    attitude,
+    fly_by_wire::{authority, excess},
+    spec::PlaneSpec,
+    PlaneControl,
+};
+
+/// Bank angle the arcade assist rolls the plane back from.
+const ARCADE_MAX_BANK: f32 = 45.0;
+/// Pitch attitude limits of the arcade assist, nose up and nose down.
+const ARCADE_MAX_PITCH_UP: f32 = 25.0;
+const ARCADE_MAX_PITCH_DOWN: f32 = -20.0;
+/// Degrees below the wing's stall that the angle of attack is held under.
+const STALL_MARGIN: f32 = 3.0;
+/// Degrees of surface deflection for each degree beyond a limit.
+const RECOVERY_GAIN: f32 = 0.5;
+/// Radians of rudder for each radian of sideslip, turning the nose into the airflow.
+const COORDINATION_GAIN: f32 = 1.0;
+/// Airspeed in m/s the assists are fully in by, fading them out on the ground where the angle of
+/// attack and sideslip mean little.
+const ASSIST_SPEED: f32 = 15.0;
+
+/// How much the pilot is helped to keep the plane flying, chosen in the Controls window.
+#[derive(Resource, Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AssistLevel {
+    /// Limits bank and pitch on top of the normal assists, so the plane can't be rolled over or
+    /// dived into the ground.
+    Arcade,
+    /// Coordinates the rudder in turns and keeps the angle of attack short of the stall.
+    #[default]
+    Normal,
+    /// Passes the pilot's command straight to the control surfaces.
+    Realistic,
+}
+
+impl AssistLevel {
+    pub const ALL: [Self; 3] = [Self::Arcade, Self::Normal, Self::Realistic];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Arcade => "arcade",
+            Self::Normal => "normal",
+            Self::Realistic => "realistic",
+        }
+    }
+}
+
+/// Command for the control surfaces after the assists have filtered the pilot's `PlaneControl`.
+#[derive(Component, Default)]
+pub struct AssistedControl {
+    pub ailerons: f32,
+    pub elevators: f32,
+    pub rudder: f32,
+}
+
+/// Filters each plane's `PlaneControl` through the assist level into its `AssistedControl`.
+/// Helicopters fly from the pilot's command directly, so are left as they are.
+pub fn apply_assist(
+    level: Res<AssistLevel>,
+    mut plane_query: Query<(
+        &PlaneSpec,
+        &PlaneFlight,
+        &Airspeed,
+        &GlobalTransform,
+        &PlaneControl,
+        &mut AssistedControl,
+    )>,
+) {
+    for (spec, flight, Airspeed(airspeed), global_tx, control, mut assisted) in
+        plane_query.iter_mut()
+    {
+        let mut ailerons = control.ailerons;
+        let mut elevators = control.elevators;
+        let mut rudder = control.rudder;
+
+        if *level != AssistLevel::Realistic && spec.rotor.is_none() {
+            let fade = (airspeed / ASSIST_SPEED).clamp(0.0, 1.0);
+
+            // Less tail lift pitches the nose up, so pulling back is negative elevator, which
+            // fades out short of the stall and is pushed back beyond it
+            let stall = stall_angle(spec) - STALL_MARGIN;
+            let aoa = flight.angle_of_attack.to_degrees();
+            if elevators < 0.0 {
+                elevators *= 1.0 - fade * (1.0 - authority(aoa, stall));
+            }
+            elevators += fade * (excess(aoa, stall) * RECOVERY_GAIN).to_radians();
+
+            // Rudder into the sideslip keeps the turn coordinated
+            rudder += fade * flight.sideslip * COORDINATION_GAIN;
+
+            if *level == AssistLevel::Arcade {
+                let (pitch, roll) = attitude(global_tx);
+                let pitch = pitch.to_degrees();
+                let roll = roll.to_degrees();
+
+                if ailerons.signum() == roll.signum() {
+                    ailerons *= authority(roll.abs(), ARCADE_MAX_BANK);
+                }
+                ailerons -= roll.signum()
+                    * (excess(roll.abs(), ARCADE_MAX_BANK) * RECOVERY_GAIN).to_radians();
+
+                if elevators < 0.0 {
+                    elevators *= authority(pitch, ARCADE_MAX_PITCH_UP);
+                } else {
+                    elevators *= authority(-pitch, -ARCADE_MAX_PITCH_DOWN);
+                }
+                elevators += ((excess(pitch, ARCADE_MAX_PITCH_UP)
+                    - excess(-pitch, -ARCADE_MAX_PITCH_DOWN))
+                    * RECOVERY_GAIN)
+                    .to_radians();
+            }
+        }
+
+        // Sizes of the limits, so a negative angle in a spec that skipped validation can't give a
+        // clamp its bounds the wrong way round
+        let max_aileron = spec.main_wing().max_control_angle.abs();
+        let max_elevator = spec.pitch_wing().max_control_angle.abs();
+        let max_rudder = spec.fin().max_control_angle.abs();
+        assisted.ailerons = ailerons.clamp(-max_aileron, max_aileron);
+        assisted.elevators = elevators.clamp(-max_elevator, max_elevator);
+        assisted.rudder = rudder.clamp(-max_rudder, max_rudder);
+    }
+}
+
+/// Angle of attack of the plane in degrees at which its wings reach their largest lift
+/// coefficient.
+fn stall_angle(spec: &PlaneSpec) -> f32 {
+    let peak = spec
+        .main_wing()
+        .lift_coefficient_curve
+        .iter()
+        .max_by(|a, b| a.0.total_cmp(&b.0))
+        .map_or(0.0, |(_, angle)| *angle);
+    peak - spec.main_wing().angle.to_degrees()
+}
@@ -3,186 +3,334 @@ use bevy_rapier3d::prelude::*;
 
 use crate::{
     camera,
-    physics::CentreOfGravity,
-    world::{self, BlockPos},
+    damage::{PreviousVelocity, StructuralPart},
+    flight_model::{
+        Airfoil, AirfoilOrientation, AngleOfAttack, FlightBundle, Lift, Thrust, ThrustPoint,
+    },
+    recorder::FlightRecorder,
+    world::{Airports, BlockPos},
 };
 
 use super::{
-    spec::{FuselageSpec, PlaneSpec, TailSpec, WingSpec},
-    Airfoil, AirfoilOrientation, AirfoilPosition, Airspeed, Altitude, AngleOfAttack,
-    ControlSurface, Lift, Plane, PlaneControl, PlaneFlight, Propellor, Side, Thrust,
+    assist::AssistedControl,
+    buoyancy::Buoyant,
+    flutter::Flutter,
+    fly_by_wire::FlyByWire,
+    lod::{imposter_mesh, PlaneImposter, PlaneLod},
+    payload::{self, Payload},
+    rotor::Rotor,
+    smoke::SmokeGenerator,
+    spec::{PartKind, PartSpec, PayloadSpec, PlaneSpec, RotorSpec, Steering, WingRole, WingSpec},
+    wake::WakeGenerator,
+    AirfoilPosition, Altitude, ControlDeflection, ControlSurface, LoadFactor, Plane, PlaneControl,
+    Propellor, PropellorBlur, PropellorPitch, Side, TurnCoordinator, VerticalSpeed, Wheel,
+    CONTROL_SURFACE_CHORD,
 };
 
+const FUSELAGE_COLOR: Color = Color::rgb(1.0, 0.0, 0.0);
+const METAL_COLOR: Color = Color::rgb(0.835, 0.835, 0.843);
+
 pub fn build_plane(
     mut commands: Commands,
     plane_query: Query<(Entity, &PlaneSpec), Added<PlaneSpec>>,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
+    airports: Res<Airports>,
 ) {
     for (entity, plane) in plane_query.iter() {
-        let metal_color = Color::hex("d5d5d7").unwrap();
-        let fuselage_color = Color::rgb(1.0, 0.0, 0.0);
-        let propellor_color = metal_color;
-        let wing_color = metal_color;
+        let plane_y = plane.fuselage.ground_clearance() + 0.2;
+
+        // Seaplanes start out on the water
+        let airport = match plane.fuselage.undercarriage.is_seaplane() {
+            true => airports.water().unwrap_or(airports.home()),
+            false => airports.home(),
+        };
+        let mut plane_tx = airport.threshold();
+        plane_tx.translation.y += plane_y;
 
-        let plane_y = plane.fuselage.size.y * 0.5
-            + plane.fuselage.wheel_y_offset
-            + plane.fuselage.wheel_radius * 0.5
-            + 0.2;
+        if plane.fly_by_wire {
+            commands.entity(entity).insert(FlyByWire::default());
+        }
+        // The engine of a helicopter drives the rotor instead of pushing from the nose
+        if plane.rotor.is_none() {
+            commands
+                .entity(entity)
+                .insert(ThrustPoint(Vec3::NEG_Z * plane.fuselage.size.z * 0.5));
+        }
 
         commands
             .entity(entity)
             .insert((
                 Plane,
-                PlaneControl::default(),
-                PlaneFlight::default(),
-                CentreOfGravity::default(),
+                (
+                    PlaneControl::default(),
+                    AssistedControl::default(),
+                    ControlDeflection::default(),
+                    PropellorPitch::default(),
+                ),
+                FlightBundle::default(),
                 Thrust(0.0),
-                Airspeed::default(),
                 Altitude::default(),
-                SpatialBundle::from_transform(Transform::from_xyz(
-                    world::SPACING as f32 * 0.5,
-                    plane_y,
-                    0.,
-                )),
+                SpatialBundle::from_transform(plane_tx),
                 RigidBody::Dynamic,
-                Velocity::zero(),
-                ExternalForce::default(),
-                ReadMassProperties::default(),
-                camera::Follow(camera::FollowKind::Behind),
-                BlockPos(0, 0),
+                PreviousVelocity::default(),
+                // Ballast is a point mass so only adds inertia through its distance from the centre
+                AdditionalMassProperties::MassProperties(MassProperties {
+                    local_center_of_mass: vec3(0., 0., plane.ballast.position),
+                    mass: plane.ballast.mass,
+                    ..default()
+                }),
+                (
+                    camera::Follow(camera::FollowKind::Behind),
+                    BlockPos(0, 0),
+                    PlaneLod::default(),
+                    FlightRecorder::default(),
+                    LoadFactor::default(),
+                    VerticalSpeed::default(),
+                    TurnCoordinator::default(),
+                    Flutter::default(),
+                    SmokeGenerator::new(plane),
+                    WakeGenerator {
+                        span: plane.wings.size.x * 2.0,
+                        mass: 0.0,
+                    },
+                ),
             ))
             .with_children(|parent| {
-                build_fuselage(
-                    parent,
-                    &mut meshes,
-                    &mut materials,
-                    &plane.fuselage,
-                    fuselage_color,
-                );
-
-                build_wheels(parent, &mut meshes, &mut materials, &plane.fuselage);
+                parent.spawn((
+                    PlaneImposter,
+                    PbrBundle {
+                        mesh: meshes.add(imposter_mesh(plane)),
+                        material: materials.add(FUSELAGE_COLOR.into()),
+                        visibility: Visibility::Hidden,
+                        ..default()
+                    },
+                ));
 
-                build_propellor(
+                build_part(
                     parent,
                     &mut meshes,
                     &mut materials,
-                    &plane.fuselage,
-                    propellor_color,
+                    &plane.parts(),
+                    Vec3::ZERO,
+                    None,
                 );
 
-                build_wings(
-                    parent,
-                    &mut meshes,
-                    &mut materials,
-                    vec3(0., 0., 1.0),
-                    &plane.wings,
-                    wing_color,
-                );
+                for spec in plane.payloads.iter() {
+                    build_payload(parent, &mut meshes, &mut materials, spec);
+                }
 
-                build_tail(
-                    parent,
-                    &mut meshes,
-                    &mut materials,
-                    vec3(0., 0., plane.fuselage.size.z / 2.0),
-                    &plane.tail,
-                    fuselage_color,
-                );
+                if let Some(rotor) = &plane.rotor {
+                    build_rotor(parent, &mut meshes, &mut materials, rotor);
+                }
             });
     }
 }
 
-pub fn build_fuselage(
+/// Builds a part and the parts attached to it, with mirrored parts built on both sides.
+fn build_part(
+    parent: &mut ChildBuilder<'_, '_, '_>,
+    meshes: &mut ResMut<Assets<Mesh>>,
+    materials: &mut ResMut<Assets<StandardMaterial>>,
+    part: &PartSpec,
+    attachment: Vec3,
+    side: Option<Side>,
+) {
+    let sides = match (part.mirrored, side) {
+        (true, None) => vec![Some(Side::Left), Some(Side::Right)],
+        _ => vec![side],
+    };
+
+    for side in sides {
+        // Offsets are given for the left, so are flipped across the plane for the right
+        let offset = match side {
+            Some(side) => part.offset * vec3(side.offset(), 1., 1.),
+            None => part.offset,
+        };
+        let pos = attachment + offset;
+
+        match &part.kind {
+            PartKind::Fuselage { size, mass } => {
+                build_fuselage(parent, meshes, materials, pos, *size, *mass);
+            }
+            PartKind::Wing { spec, role } => {
+                build_wings(parent, meshes, materials, pos, spec, *role, side);
+            }
+            PartKind::Pylon { size } => build_pylon(parent, meshes, materials, pos, *size, side),
+            PartKind::Engine { diameter } => {
+                build_propellor(parent, meshes, materials, pos, *diameter);
+            }
+            PartKind::Gear { radius, steering } => {
+                build_wheel(parent, meshes, materials, pos, *radius, *steering, side)
+            }
+            PartKind::Float { size } => build_float(parent, meshes, materials, pos, *size, side),
+        }
+
+        for attached in part.attachments.iter() {
+            build_part(parent, meshes, materials, attached, pos, side);
+        }
+    }
+}
+
+/// Name of a part for listing it, with its side when it's one of a pair.
+fn part_name(name: &str, side: Option<Side>) -> Name {
+    match side {
+        Some(side) => Name::new(format!("{:?} {}", side, name)),
+        None => Name::new(name[..1].to_uppercase() + &name[1..]),
+    }
+}
+
+fn build_fuselage(
     parent: &mut ChildBuilder<'_, '_, '_>,
     meshes: &mut ResMut<Assets<Mesh>>,
     materials: &mut ResMut<Assets<StandardMaterial>>,
-    spec: &FuselageSpec,
-    fuselage_color: Color,
+    pos: Vec3,
+    size: Vec3,
+    mass: f32,
 ) {
     parent.spawn((
+        Name::new("Fuselage"),
         PbrBundle {
-            mesh: meshes.add(Mesh::from(shape::Box::new(
-                spec.size.x,
-                spec.size.y,
-                spec.size.z,
-            ))),
-            material: materials.add(fuselage_color.into()),
+            mesh: meshes.add(Mesh::from(shape::Box::new(size.x, size.y, size.z))),
+            material: materials.add(FUSELAGE_COLOR.into()),
+            transform: Transform::from_translation(pos),
             ..default()
         },
         Friction::new(0.0),
-        Collider::cuboid(spec.size.x * 0.5, spec.size.y * 0.5, spec.size.z * 0.5),
-        ColliderMassProperties::Mass(spec.mass),
+        Collider::cuboid(size.x * 0.5, size.y * 0.5, size.z * 0.5),
+        ColliderMassProperties::Mass(mass),
+        ActiveEvents::COLLISION_EVENTS,
+        StructuralPart::new(4.0, true),
+        Buoyant::new(size),
     ));
 }
 
-pub fn build_wheels(
+fn build_wheel(
     parent: &mut ChildBuilder<'_, '_, '_>,
     meshes: &mut ResMut<Assets<Mesh>>,
     materials: &mut ResMut<Assets<StandardMaterial>>,
-    spec: &FuselageSpec,
+    pos: Vec3,
+    radius: f32,
+    steering: Steering,
+    side: Option<Side>,
 ) {
-    let wheel_x = spec.size.x * 0.5 + spec.wheel_x_offset;
-    let wheel_y = -(spec.size.y * 0.5 + spec.wheel_y_offset);
     let wheel_width = 0.2;
 
-    for side in [Side::Left, Side::Right] {
-        parent.spawn((
-            PbrBundle {
-                mesh: meshes.add(Mesh::from(shape::Cylinder {
-                    radius: spec.wheel_radius,
-                    height: wheel_width,
-                    ..default()
-                })),
-                transform: Transform::from_xyz(wheel_x * side.offset(), wheel_y, spec.size.z * 0.5)
-                    .with_rotation(Quat::from_rotation_z(90_f32.to_radians())),
-                material: materials.add(Color::BLACK.into()),
-                ..default()
-            },
-            Friction::new(0.0),
-            Collider::ball(spec.wheel_radius),
-        ));
-    }
-
     parent.spawn((
+        Wheel { side, steering },
+        part_name("wheel", side),
         PbrBundle {
             mesh: meshes.add(Mesh::from(shape::Cylinder {
-                radius: spec.wheel_radius,
+                radius,
                 height: wheel_width,
                 ..default()
             })),
-            transform: Transform::from_xyz(0.0, wheel_y, -spec.size.z * 0.5)
+            transform: Transform::from_translation(pos)
                 .with_rotation(Quat::from_rotation_z(90_f32.to_radians())),
             material: materials.add(Color::BLACK.into()),
             ..default()
         },
         Friction::new(0.0),
-        Collider::ball(spec.wheel_radius),
+        Collider::ball(radius),
+        ActiveEvents::COLLISION_EVENTS,
+        StructuralPart::new(6.0, false),
+    ));
+}
+
+fn build_float(
+    parent: &mut ChildBuilder<'_, '_, '_>,
+    meshes: &mut ResMut<Assets<Mesh>>,
+    materials: &mut ResMut<Assets<StandardMaterial>>,
+    pos: Vec3,
+    size: Vec3,
+    side: Option<Side>,
+) {
+    parent.spawn((
+        part_name("float", side),
+        PbrBundle {
+            mesh: meshes.add(Mesh::from(shape::Box::new(size.x, size.y, size.z))),
+            material: materials.add(METAL_COLOR.into()),
+            transform: Transform::from_translation(pos),
+            ..default()
+        },
+        Collider::cuboid(size.x * 0.5, size.y * 0.5, size.z * 0.5),
+        ActiveEvents::COLLISION_EVENTS,
+        StructuralPart::new(6.0, false),
+        Buoyant::new(size),
+    ));
+}
+
+fn build_pylon(
+    parent: &mut ChildBuilder<'_, '_, '_>,
+    meshes: &mut ResMut<Assets<Mesh>>,
+    materials: &mut ResMut<Assets<StandardMaterial>>,
+    pos: Vec3,
+    size: Vec3,
+    side: Option<Side>,
+) {
+    parent.spawn((
+        part_name("pylon", side),
+        PbrBundle {
+            mesh: meshes.add(Mesh::from(shape::Box::new(size.x, size.y, size.z))),
+            material: materials.add(METAL_COLOR.into()),
+            transform: Transform::from_translation(pos),
+            ..default()
+        },
+        Collider::cuboid(size.x * 0.5, size.y * 0.5, size.z * 0.5),
+        ActiveEvents::COLLISION_EVENTS,
+        StructuralPart::new(3.0, false),
     ));
 }
 
-pub fn build_wings(
+/// Builds a wing, or for horizontal wings outside a mirrored part a pair of them either side.
+fn build_wings(
     parent: &mut ChildBuilder<'_, '_, '_>,
     meshes: &mut ResMut<Assets<Mesh>>,
     materials: &mut ResMut<Assets<StandardMaterial>>,
     pos: Vec3,
     spec: &WingSpec,
-    wing_color: Color,
+    role: WingRole,
+    side: Option<Side>,
 ) {
-    [Side::Left, Side::Right].iter().for_each(|side| {
+    let color = match role {
+        WingRole::Main | WingRole::Elevon => METAL_COLOR,
+        WingRole::Stabiliser | WingRole::Canard | WingRole::Fin => Color::BLUE,
+    };
+
+    let sides = match (role, side) {
+        (WingRole::Fin, _) => vec![None],
+        (_, Some(side)) => vec![Some(side)],
+        (_, None) => vec![Some(Side::Left), Some(Side::Right)],
+    };
+
+    for side in sides {
+        let position = match (role, side) {
+            (WingRole::Main, Some(side)) => AirfoilPosition::Wing(side),
+            (WingRole::Stabiliser, Some(side)) => AirfoilPosition::TailWing(side),
+            (WingRole::Canard, Some(side)) => AirfoilPosition::Canard(side),
+            (WingRole::Elevon, Some(side)) => AirfoilPosition::Elevon(side),
+            _ => AirfoilPosition::VerticalTail,
+        };
+        let orientation = match position {
+            AirfoilPosition::VerticalTail => AirfoilOrientation::Vertical,
+            _ => AirfoilOrientation::Horizontal,
+        };
+
         build_wing(
             parent,
             meshes,
             materials,
             pos,
             spec,
-            wing_color,
-            Some(*side),
-            AirfoilPosition::Wing(*side),
-            AirfoilOrientation::Horizontal,
+            color,
+            side,
+            position,
+            orientation,
         );
-    });
+    }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn build_wing(
     parent: &mut ChildBuilder<'_, '_, '_>,
     meshes: &mut ResMut<Assets<Mesh>>,
@@ -194,20 +342,55 @@ fn build_wing(
     position: AirfoilPosition,
     orientation: AirfoilOrientation,
 ) {
+    let (lift_coefficient_samples, drag_coefficient_samples) = match (
+        spec.lift_coefficient_samples(),
+        spec.drag_coefficient_samples(),
+    ) {
+        (Ok(lift), Ok(drag)) => (lift, drag),
+        (Err(err), _) | (_, Err(err)) => {
+            warn!(
+                "Not building {:?} wing with invalid curve: {}",
+                position, err
+            );
+            return;
+        }
+    };
     let offset = match side {
         Some(side) => side.offset(),
         None => 0.0,
     };
+    // Wings are built out from their root, tilted up by the dihedral and back by the sweep
+    let (root_to_tip, tilt) = match orientation {
+        AirfoilOrientation::Horizontal => (
+            Vec3::X * spec.size.x * 0.5 * offset,
+            Quat::from_rotation_y(-spec.sweep * offset)
+                * Quat::from_rotation_z(spec.dihedral * offset),
+        ),
+        AirfoilOrientation::Vertical => (
+            Vec3::Y * spec.size.y * 0.5,
+            Quat::from_rotation_x(spec.sweep),
+        ),
+    };
+    // Wings built either side of the plane act as one wing of twice the span
+    let span = match orientation {
+        AirfoilOrientation::Horizontal if side.is_some() => spec.size.x * 2.0,
+        AirfoilOrientation::Horizontal => spec.size.x,
+        AirfoilOrientation::Vertical => spec.size.y,
+    };
 
     parent
         .spawn((
             position,
+            Name::new(format!("{:?}", position)),
             Airfoil {
                 orientation,
                 area: spec.size.x * spec.size.z,
-                lift_coefficient_samples: spec.lift_coefficient_samples(),
-                drag_coefficient_samples: spec.drag_coefficient_samples(),
+                lift_coefficient_samples,
+                drag_coefficient_samples,
                 lift_coefficient_modifier: 0.,
+                aspect_ratio: span / spec.size.z,
+                oswald_efficiency: spec.oswald_efficiency,
+                induced_velocity: Vec3::ZERO,
             },
             AngleOfAttack::default(),
             Lift::default(),
@@ -218,116 +401,153 @@ fn build_wing(
                     spec.size.z,
                 ))),
                 material: materials.add(wing_color.into()),
-                transform: Transform::from_translation(
-                    pos + vec3(spec.size.x * 0.5 * offset, 0.0, 0.0),
-                )
-                .with_rotation(Quat::from_rotation_x(spec.angle)),
+                transform: Transform::from_translation(pos + tilt * root_to_tip)
+                    .with_rotation(tilt * Quat::from_rotation_x(spec.angle)),
                 ..default()
             },
             Collider::cuboid(spec.size.x * 0.5, spec.size.y * 0.5, spec.size.z * 0.5),
+            ActiveEvents::COLLISION_EVENTS,
+            StructuralPart::new(3.0, false),
         ))
         .with_children(|parent| {
             let control_width = spec.size.x;
             let control_height = spec.size.y;
-            let control_length = spec.size.z * 0.25;
-
-            parent.spawn((
-                ControlSurface,
-                PbrBundle {
-                    mesh: meshes.add(Mesh::from(shape::Box::new(
-                        control_width,
-                        control_height,
-                        control_length,
-                    ))),
-                    material: materials.add(wing_color.into()),
-                    transform: Transform::from_xyz(
+            let control_length = spec.size.z * CONTROL_SURFACE_CHORD;
+            let hinge_axis = match orientation {
+                AirfoilOrientation::Horizontal => Vec3::X,
+                AirfoilOrientation::Vertical => Vec3::Y,
+            };
+
+            // The surface hangs off a hinge along the trailing edge, so it turns about the edge
+            // rather than its own middle
+            parent
+                .spawn((
+                    ControlSurface {
+                        hinge_axis,
+                        angle: 0.0,
+                    },
+                    SpatialBundle::from_transform(Transform::from_xyz(
                         (spec.size.x * 0.5 - control_width * 0.5) * offset,
                         0.0,
-                        spec.size.z / 2.0 + control_length / 2.0,
-                    ),
-                    ..default()
-                },
-                Collider::cuboid(
-                    control_width * 0.5,
-                    control_height * 0.5,
-                    control_length * 0.5,
-                ),
-            ));
+                        spec.size.z / 2.0,
+                    )),
+                ))
+                .with_children(|parent| {
+                    parent.spawn((
+                        Name::new(format!("{:?} control surface", position)),
+                        PbrBundle {
+                            mesh: meshes.add(Mesh::from(shape::Box::new(
+                                control_width,
+                                control_height,
+                                control_length,
+                            ))),
+                            material: materials.add(wing_color.into()),
+                            transform: Transform::from_xyz(0.0, 0.0, control_length / 2.0),
+                            ..default()
+                        },
+                        Collider::cuboid(
+                            control_width * 0.5,
+                            control_height * 0.5,
+                            control_length * 0.5,
+                        ),
+                        ActiveEvents::COLLISION_EVENTS,
+                    ));
+                });
         });
 }
 
-pub fn build_propellor(
+/// Builds a payload held in place by the plane, its collider adding its mass to the plane's.
+fn build_payload(
     parent: &mut ChildBuilder<'_, '_, '_>,
     meshes: &mut ResMut<Assets<Mesh>>,
     materials: &mut ResMut<Assets<StandardMaterial>>,
-    spec: &FuselageSpec,
-    propellor_color: Color,
+    spec: &PayloadSpec,
 ) {
-    let size = Vec3::new(spec.size.x * 2.5, 0.4, 0.1);
-
     parent.spawn((
-        Propellor,
+        Payload {
+            kind: spec.kind,
+            mass: spec.mass,
+        },
+        Name::new(format!("{:?} payload", spec.kind)),
         PbrBundle {
-            mesh: meshes.add(Mesh::from(shape::Box::new(size.x, size.y, size.z))),
-            material: materials.add(propellor_color.into()),
-            transform: Transform::from_xyz(0.0, 0.0, -spec.size.z * 0.5),
+            mesh: meshes.add(payload::mesh(spec.kind)),
+            material: materials.add(payload::color(spec.kind).into()),
+            transform: Transform::from_translation(spec.position),
             ..default()
         },
-        // Collider::cuboid(size.x * 0.5, size.y * 0.5, size.z * 0.5),
+        payload::collider(spec.kind),
+        ColliderMassProperties::Mass(spec.mass),
     ));
 }
 
-pub fn build_tail(
+/// Builds the blades of a main rotor on a mast, without colliders, as its lift is applied to the
+/// plane as a whole.
+fn build_rotor(
+    parent: &mut ChildBuilder<'_, '_, '_>,
+    meshes: &mut ResMut<Assets<Mesh>>,
+    materials: &mut ResMut<Assets<StandardMaterial>>,
+    spec: &RotorSpec,
+) {
+    let material = materials.add(METAL_COLOR.into());
+
+    parent.spawn(PbrBundle {
+        mesh: meshes.add(Mesh::from(shape::Cylinder {
+            radius: 0.08,
+            height: spec.height,
+            ..default()
+        })),
+        material: material.clone(),
+        transform: Transform::from_xyz(0., spec.height * 0.5, 0.),
+        ..default()
+    });
+    parent
+        .spawn((
+            Rotor,
+            SpatialBundle::from_transform(Transform::from_xyz(0., spec.height, 0.)),
+        ))
+        .with_children(|parent| {
+            for angle in [0.0, 90_f32.to_radians()] {
+                parent.spawn(PbrBundle {
+                    mesh: meshes.add(Mesh::from(shape::Box::new(spec.diameter, 0.05, 0.25))),
+                    material: material.clone(),
+                    transform: Transform::from_rotation(Quat::from_rotation_y(angle)),
+                    ..default()
+                });
+            }
+        });
+}
+
+fn build_propellor(
     parent: &mut ChildBuilder<'_, '_, '_>,
     meshes: &mut ResMut<Assets<Mesh>>,
     materials: &mut ResMut<Assets<StandardMaterial>>,
     pos: Vec3,
-    spec: &TailSpec,
-    color: Color,
+    diameter: f32,
 ) {
+    let size = Vec3::new(diameter, 0.4, 0.1);
+    let blades = (
+        meshes.add(Mesh::from(shape::Box::new(size.x, size.y, size.z))),
+        materials.add(METAL_COLOR.into()),
+    );
+    let disk = (
+        meshes.add(Mesh::from(shape::Circle::new(diameter * 0.5))),
+        materials.add(StandardMaterial {
+            base_color: METAL_COLOR.with_a(0.25),
+            alpha_mode: AlphaMode::Blend,
+            cull_mode: None,
+            ..default()
+        }),
+    );
+
     parent.spawn((
+        Propellor,
         PbrBundle {
-            mesh: meshes.add(Mesh::from(shape::Box::new(
-                spec.size.x,
-                spec.size.y,
-                spec.size.z,
-            ))),
-            transform: Transform::from_xyz(0.0, 0.0, pos.z + spec.size.z / 2.0),
-            material: materials.add(color.into()),
+            mesh: blades.0.clone(),
+            material: blades.1.clone(),
+            transform: Transform::from_translation(pos),
             ..default()
         },
-        Friction::new(0.01),
-        Collider::cuboid(spec.size.x * 0.5, spec.size.y * 0.5, spec.size.z * 0.5),
-        ColliderMassProperties::Mass(10.),
+        PropellorBlur { blades, disk },
+        // Collider::cuboid(size.x * 0.5, size.y * 0.5, size.z * 0.5),
     ));
-
-    let end_pos = pos + vec3(0., 0., spec.size.z);
-
-    // Vertical tail surfaces
-    build_wing(
-        parent,
-        meshes,
-        materials,
-        end_pos + Vec3::Y * spec.vertical.size.y * 0.5,
-        &spec.vertical,
-        Color::BLUE,
-        None,
-        AirfoilPosition::VerticalTail,
-        AirfoilOrientation::Vertical,
-    );
-
-    // Horizontal tail surfaces
-    for side in [Side::Left, Side::Right] {
-        build_wing(
-            parent,
-            meshes,
-            materials,
-            end_pos,
-            &spec.horizontal,
-            Color::BLUE,
-            Some(side),
-            AirfoilPosition::TailWing(side),
-            AirfoilOrientation::Horizontal,
-        );
-    }
 }
@@ -0,0 +1,91 @@
+use std::ops::AddAssign;
+
+use bevy::{math::vec3, prelude::*};
+use bevy_rapier3d::prelude::*;
+
+use crate::{
+    flight_model::CentreOfGravity,
+    world::{water_height, Airports},
+};
+
+use super::Plane;
+
+const WATER_DENSITY: f32 = 1000.0;
+const GRAVITY: f32 = 9.81;
+/// Columns sampled across the width and along the length of each buoyant part.
+const COLUMNS: usize = 4;
+/// Damping of the plane bobbing up and down, per second, for each kilogram of water displaced.
+const WATER_DAMPING: f32 = 10.0;
+/// Drag through the water for each kilogram of water displaced, proportional to speed squared.
+/// Lift takes the weight off the floats as the plane speeds up, so the drag falls away on a takeoff
+/// run.
+const WATER_DRAG: f32 = 0.003;
+
+/// Box shaped part of a plane that displaces water when submerged, such as a float or hull.
+#[derive(Component)]
+pub struct Buoyant {
+    half_size: Vec3,
+}
+
+impl Buoyant {
+    pub fn new(size: Vec3) -> Self {
+        Self {
+            half_size: size * 0.5,
+        }
+    }
+}
+
+/// Buoyancy and drag on the parts of a plane below the water, sampled over columns through each
+/// part so the force moves with how deep each end is in the water.
+pub fn update_buoyancy_forces(
+    mut plane_query: Query<(Entity, &Velocity, &CentreOfGravity, &mut ExternalForce), With<Plane>>,
+    children_query: Query<&Children>,
+    buoyant_query: Query<(&Buoyant, &GlobalTransform)>,
+    airports: Res<Airports>,
+) {
+    for (plane_entity, velocity, centre_of_gravity, mut external_force) in plane_query.iter_mut() {
+        for (buoyant, global_tx) in children_query
+            .iter_descendants(plane_entity)
+            .filter_map(|child| buoyant_query.get(child).ok())
+        {
+            let half = buoyant.half_size;
+            let column_volume = half.x * half.y * half.z * 8.0 / (COLUMNS * COLUMNS) as f32;
+
+            for i in 0..COLUMNS {
+                for j in 0..COLUMNS {
+                    let offset = |n: usize, half: f32| {
+                        ((n as f32 + 0.5) / COLUMNS as f32 * 2.0 - 1.0) * half
+                    };
+                    let (x, z) = (offset(i, half.x), offset(j, half.z));
+                    let a = global_tx.transform_point(vec3(x, -half.y, z));
+                    let b = global_tx.transform_point(vec3(x, half.y, z));
+                    let (low, high) = if a.y < b.y { (a, b) } else { (b, a) };
+
+                    let water = water_height(&airports, low);
+                    if low.y >= water {
+                        continue;
+                    }
+
+                    let depth = high.y - low.y;
+                    let submerged = match depth > f32::EPSILON {
+                        true => ((water - low.y) / depth).min(1.0),
+                        false => 1.0,
+                    };
+                    let centre = low.lerp(high, submerged * 0.5);
+                    let displaced = WATER_DENSITY * column_volume * submerged;
+
+                    let point_velocity =
+                        velocity.linvel + velocity.angvel.cross(centre - centre_of_gravity.global);
+                    let force = Vec3::Y * displaced * (GRAVITY - point_velocity.y * WATER_DAMPING)
+                        - point_velocity * point_velocity.length() * displaced * WATER_DRAG;
+
+                    external_force.add_assign(ExternalForce::at_point(
+                        force,
+                        centre,
+                        centre_of_gravity.global,
+                    ));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,58 @@
+use std::f32::consts::TAU;
+
+use bevy::prelude::*;
+
+use crate::{
+    flight_model::{Airfoil, Airspeed},
+    physics::PHYSICS_DT,
+};
+
+use super::{spec::PlaneSpec, AirfoilPosition, Plane};
+
+/// Fraction over the never exceed speed at which flutter is at its worst.
+const FULL_FLUTTER_OVERSPEED: f32 = 0.3;
+/// Oscillations per second of the fluttering surfaces.
+const FLUTTER_FREQUENCY: f32 = 8.0;
+/// Lift coefficient swing of each fluttering surface at its worst.
+const FLUTTER_AMPLITUDE: f32 = 0.3;
+
+/// Oscillation of the control surfaces once a plane is flown past its never exceed speed.
+#[derive(Component, Default)]
+pub struct Flutter {
+    /// How violently the surfaces flutter, from none at zero to the worst at one.
+    pub intensity: f32,
+    phase: f32,
+}
+
+pub fn update_flutter(mut plane_query: Query<(&PlaneSpec, &Airspeed, &mut Flutter), With<Plane>>) {
+    for (spec, Airspeed(airspeed), mut flutter) in plane_query.iter_mut() {
+        let overspeed = airspeed / spec.never_exceed_speed.max(f32::EPSILON) - 1.0;
+        flutter.intensity = (overspeed / FULL_FLUTTER_OVERSPEED).clamp(0.0, 1.0);
+        flutter.phase = (flutter.phase + FLUTTER_FREQUENCY * TAU * PHYSICS_DT) % TAU;
+    }
+}
+
+/// Adds the flutter on top of the lift from the control surface deflections. The two sides move
+/// against each other, so the plane buzzes in roll as well as pitch.
+pub fn apply_flutter(
+    plane_query: Query<&Flutter>,
+    mut airfoil_query: Query<(&mut Airfoil, &AirfoilPosition, &Parent)>,
+) {
+    for (mut airfoil, position, parent) in airfoil_query.iter_mut() {
+        let Ok(flutter) = plane_query.get(parent.get()) else {
+            continue;
+        };
+        if flutter.intensity <= 0.0 {
+            continue;
+        }
+
+        let phase = match position {
+            AirfoilPosition::Wing(side)
+            | AirfoilPosition::TailWing(side)
+            | AirfoilPosition::Canard(side)
+            | AirfoilPosition::Elevon(side) => flutter.phase * side.offset(),
+            AirfoilPosition::VerticalTail => flutter.phase + TAU * 0.25,
+        };
+        airfoil.lift_coefficient_modifier += phase.sin() * FLUTTER_AMPLITUDE * flutter.intensity;
+    }
+}
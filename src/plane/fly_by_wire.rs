@@ -0,0 +1,97 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+use crate::flight_model::PlaneFlight;
+
+use super::{assist::AssistedControl, attitude, spec::PlaneSpec};
+
+/// Bank angle beyond which the control law rolls the plane back towards wings level.
+const MAX_BANK: f32 = 67.0;
+/// Pitch attitude limits, nose up and nose down.
+const MAX_PITCH_UP: f32 = 30.0;
+const MAX_PITCH_DOWN: f32 = -15.0;
+/// Angle of attack limits kept below the stall of the wing.
+const MAX_AOA: f32 = 10.0;
+const MIN_AOA: f32 = -5.0;
+/// Degrees before a limit over which pilot authority towards it fades out.
+const SOFT_ZONE: f32 = 10.0;
+/// Degrees of surface deflection for each degree beyond a limit.
+const RECOVERY_GAIN: f32 = 0.5;
+/// Degrees of elevator trim added for each degree the nose moves while the stick is neutral.
+const TRIM_GAIN: f32 = 0.5;
+/// Pilot elevator command below which the stick counts as neutral, as a fraction of full deflection.
+const NEUTRAL_COMMAND: f32 = 0.05;
+/// Largest trim deflection in degrees.
+const MAX_TRIM: f32 = 2.5;
+
+/// Envelope protection between the pilot's command in `AssistedControl` and the control surfaces.
+///
+/// Holds the deflections the control law asks for, which are applied to the surfaces in place of
+/// the pilot's command.
+#[derive(Component, Default)]
+pub struct FlyByWire {
+    pub ailerons: f32,
+    pub elevators: f32,
+    pub trim: f32,
+}
+
+/// Runs each physics step so the trim integrates over the same time as the simulation.
+pub fn apply_control_law(
+    fixed_time: Res<FixedTime>,
+    mut plane_query: Query<(
+        &PlaneSpec,
+        &PlaneFlight,
+        &GlobalTransform,
+        &Velocity,
+        &AssistedControl,
+        &mut FlyByWire,
+    )>,
+) {
+    let dt = fixed_time.period.as_secs_f32();
+
+    for (spec, flight, global_tx, velocity, control, mut fbw) in plane_query.iter_mut() {
+        let max_aileron = spec.main_wing().max_control_angle.abs();
+        let max_elevator = spec.pitch_wing().max_control_angle.abs();
+        let (pitch, roll) = attitude(global_tx);
+        let pitch = pitch.to_degrees();
+        let roll = roll.to_degrees();
+        let aoa = flight.angle_of_attack.to_degrees();
+
+        // Bank protection
+        let mut ailerons = control.ailerons;
+        if ailerons.signum() == roll.signum() {
+            ailerons *= authority(roll.abs(), MAX_BANK);
+        }
+        ailerons -= roll.signum() * (excess(roll.abs(), MAX_BANK) * RECOVERY_GAIN).to_radians();
+
+        // Auto trim holds the attitude when the pilot lets go of the stick
+        let pitch_rate = velocity.angvel.dot(global_tx.right());
+        if control.elevators.abs() < NEUTRAL_COMMAND * max_elevator {
+            let max_trim = MAX_TRIM.to_radians();
+            fbw.trim = (fbw.trim - pitch_rate * TRIM_GAIN * dt).clamp(-max_trim, max_trim);
+        }
+
+        // Pitch attitude and angle of attack protection, with whichever limit is closest taking over
+        let mut elevators = control.elevators + fbw.trim;
+        if elevators > 0.0 {
+            elevators *= authority(pitch, MAX_PITCH_UP).min(authority(aoa, MAX_AOA));
+        } else {
+            elevators *= authority(-pitch, -MAX_PITCH_DOWN).min(authority(-aoa, -MIN_AOA));
+        }
+        let nose_down = excess(pitch, MAX_PITCH_UP).max(excess(aoa, MAX_AOA));
+        let nose_up = excess(-pitch, -MAX_PITCH_DOWN).max(excess(-aoa, -MIN_AOA));
+        elevators += ((nose_up - nose_down) * RECOVERY_GAIN).to_radians();
+
+        fbw.ailerons = ailerons.clamp(-max_aileron, max_aileron);
+        fbw.elevators = elevators.clamp(-max_elevator, max_elevator);
+    }
+}
+
+/// Fraction of pilot authority left when approaching a limit.
+pub(super) fn authority(value: f32, limit: f32) -> f32 {
+    ((limit - value) / SOFT_ZONE).clamp(0.0, 1.0)
+}
+
+pub(super) fn excess(value: f32, limit: f32) -> f32 {
+    (value - limit).max(0.0)
+}
@@ -0,0 +1,152 @@
+use bevy::{
+    prelude::*,
+    render::{mesh::Indices, render_resource::PrimitiveTopology},
+};
+
+use crate::camera::MainCamera;
+
+use super::{
+    spec::{PartKind, PartSpec, PlaneSpec, WingRole},
+    Plane, Side,
+};
+
+/// Camera distance beyond which the imposter replaces the detailed plane.
+const LOW_DETAIL_DISTANCE: f32 = 400.0;
+/// Camera distance within which the detailed plane is restored.
+const HIGH_DETAIL_DISTANCE: f32 = 350.0;
+
+#[derive(Component, Default, PartialEq, Eq, Debug, Clone, Copy)]
+pub enum PlaneLod {
+    #[default]
+    Detailed,
+    Imposter,
+}
+
+#[derive(Component)]
+pub struct PlaneImposter;
+
+pub fn update_plane_lod(
+    camera_query: Query<&GlobalTransform, With<MainCamera>>,
+    mut plane_query: Query<(&GlobalTransform, &Children, &mut PlaneLod), With<Plane>>,
+    mut part_query: Query<(&mut Visibility, Option<&PlaneImposter>), With<Handle<Mesh>>>,
+) {
+    let Ok(camera_tx) = camera_query.get_single() else {
+        return;
+    };
+
+    for (global_tx, children, mut lod) in plane_query.iter_mut() {
+        let distance = camera_tx.translation().distance(global_tx.translation());
+
+        let new_lod = match *lod {
+            PlaneLod::Detailed if distance > LOW_DETAIL_DISTANCE => PlaneLod::Imposter,
+            PlaneLod::Imposter if distance < HIGH_DETAIL_DISTANCE => PlaneLod::Detailed,
+            current => current,
+        };
+
+        if new_lod == *lod {
+            continue;
+        }
+
+        info!("Switching plane to {:?}", new_lod);
+        *lod = new_lod;
+
+        for child in children.iter() {
+            if let Ok((mut visibility, imposter)) = part_query.get_mut(*child) {
+                let show = imposter.is_some() == (new_lod == PlaneLod::Imposter);
+                *visibility = if show {
+                    Visibility::Inherited
+                } else {
+                    Visibility::Hidden
+                };
+            }
+        }
+    }
+}
+
+/// Rearmost point of the parts, on the centreline, that things trail from.
+pub(super) fn tail_point(spec: &PlaneSpec) -> Vec3 {
+    let mut boxes = vec![];
+    outline_part(&spec.parts(), Vec3::ZERO, None, &mut boxes);
+
+    boxes
+        .iter()
+        .map(|(size, pos)| Vec3::new(0., pos.y, pos.z + size.z * 0.5))
+        .reduce(|a, b| if b.z > a.z { b } else { a })
+        .unwrap_or_default()
+}
+
+/// Adds boxes outlining a part and the parts attached to it, mirrored the same way as when built.
+pub(super) fn outline_part(
+    part: &PartSpec,
+    attachment: Vec3,
+    side: Option<Side>,
+    boxes: &mut Vec<(Vec3, Vec3)>,
+) {
+    let sides = match (part.mirrored, side) {
+        (true, None) => vec![Some(Side::Left), Some(Side::Right)],
+        _ => vec![side],
+    };
+
+    for side in sides {
+        let mirror = side.map_or(1.0, |side| side.offset());
+        let pos = attachment + part.offset * Vec3::new(mirror, 1., 1.);
+
+        match &part.kind {
+            PartKind::Fuselage { size, .. }
+            | PartKind::Pylon { size }
+            | PartKind::Float { size } => boxes.push((*size, pos)),
+            PartKind::Wing {
+                spec,
+                role: WingRole::Fin,
+            } => boxes.push((spec.size, pos + Vec3::Y * spec.size.y * 0.5)),
+            PartKind::Wing { spec, .. } => match side {
+                Some(side) => {
+                    boxes.push((spec.size, pos + Vec3::X * spec.size.x * 0.5 * side.offset()))
+                }
+                None => boxes.push((spec.size * Vec3::new(2., 1., 1.), pos)),
+            },
+            PartKind::Engine { .. } | PartKind::Gear { .. } => {}
+        }
+
+        for attached in part.attachments.iter() {
+            outline_part(attached, pos, side, boxes);
+        }
+    }
+}
+
+/// Builds a single mesh roughly outlining the fuselage, wings and tail.
+pub fn imposter_mesh(spec: &PlaneSpec) -> Mesh {
+    let mut boxes = vec![];
+    outline_part(&spec.parts(), Vec3::ZERO, None, &mut boxes);
+
+    let mut positions: Vec<[f32; 3]> = vec![];
+    let mut normals: Vec<[f32; 3]> = vec![];
+    let mut indices: Vec<u32> = vec![];
+
+    for (size, offset) in boxes {
+        let mesh = Mesh::from(shape::Box::new(size.x, size.y, size.z));
+        let base = positions.len() as u32;
+
+        if let Some(values) = mesh
+            .attribute(Mesh::ATTRIBUTE_POSITION)
+            .and_then(|attr| attr.as_float3())
+        {
+            positions.extend(values.iter().map(|p| (Vec3::from(*p) + offset).to_array()));
+        }
+        if let Some(values) = mesh
+            .attribute(Mesh::ATTRIBUTE_NORMAL)
+            .and_then(|attr| attr.as_float3())
+        {
+            normals.extend_from_slice(values);
+        }
+        if let Some(mesh_indices) = mesh.indices() {
+            indices.extend(mesh_indices.iter().map(|i| base + i as u32));
+        }
+    }
+
+    let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
+    mesh.set_indices(Some(Indices::U32(indices)));
+    mesh
+}
@@ -0,0 +1,261 @@
+use std::f32::consts::TAU;
+
+use bevy::prelude::*;
+use bevy_rapier3d::{prelude::*, rapier::geometry::CollisionEventFlags};
+use leafwing_input_manager::prelude::ActionState;
+use rand::Rng;
+
+use crate::{
+    flight_model::CentreOfGravity, input::PlaneAction, physics::PHYSICS_DT,
+    world::CrateSettledEvent,
+};
+
+use super::{spec::PayloadKind, Plane};
+
+/// Time a dropped payload is left lying around before it is removed.
+const DROPPED_LIFETIME: f32 = 120.0;
+/// Speed in m/s, and turn rate in rad/s, below which a landed crate is at rest.
+const SETTLED_SPEED: f32 = 0.05;
+/// Time a landed crate must stay at rest before it's left in the world for good.
+const SETTLE_TIME: f32 = 1.0;
+/// Drops of water thrown out by a bursting water bomb.
+const SPLASH_DROPS: usize = 40;
+const SPLASH_SPEED: std::ops::Range<f32> = 3.0..8.0;
+const SPLASH_LIFETIME: f32 = 1.5;
+const GRAVITY: f32 = 9.81;
+
+/// Payload carried by a plane, as a child of it.
+#[derive(Component)]
+pub struct Payload {
+    pub kind: PayloadKind,
+    pub mass: f32,
+}
+
+/// Payload falling, or lying where it landed, after being dropped.
+#[derive(Component)]
+pub struct DroppedPayload {
+    kind: PayloadKind,
+    age: f32,
+    landed: bool,
+    /// Time it has been at rest since it last moved.
+    at_rest: f32,
+}
+
+#[derive(Component)]
+pub struct SplashDrop {
+    velocity: Vec3,
+    age: f32,
+}
+
+#[derive(Resource)]
+pub struct PayloadAssets {
+    splash_mesh: Handle<Mesh>,
+    splash_material: Handle<StandardMaterial>,
+}
+
+pub fn mesh(kind: PayloadKind) -> Mesh {
+    match kind {
+        PayloadKind::Crate => Mesh::from(shape::Cube { size: kind.size() }),
+        PayloadKind::WaterBomb => Mesh::from(shape::UVSphere {
+            radius: kind.size() * 0.5,
+            ..default()
+        }),
+    }
+}
+
+pub fn collider(kind: PayloadKind) -> Collider {
+    match kind {
+        PayloadKind::Crate => {
+            Collider::cuboid(kind.size() * 0.5, kind.size() * 0.5, kind.size() * 0.5)
+        }
+        PayloadKind::WaterBomb => Collider::ball(kind.size() * 0.5),
+    }
+}
+
+pub fn color(kind: PayloadKind) -> Color {
+    match kind {
+        PayloadKind::Crate => Color::rgb(0.55, 0.4, 0.2),
+        PayloadKind::WaterBomb => Color::rgb(0.2, 0.45, 0.9),
+    }
+}
+
+pub fn setup_payloads(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    commands.insert_resource(PayloadAssets {
+        splash_mesh: meshes.add(Mesh::from(shape::Cube { size: 0.12 })),
+        splash_material: materials.add(StandardMaterial {
+            base_color: Color::rgba(0.6, 0.75, 0.95, 0.7),
+            alpha_mode: AlphaMode::Blend,
+            unlit: true,
+            ..default()
+        }),
+    });
+}
+
+/// Lets go of the next payload, in the order they're listed in the spec, carrying on with the
+/// velocity of the point on the plane it was held at.
+pub fn release_payload(
+    mut commands: Commands,
+    action_query: Query<&ActionState<PlaneAction>>,
+    plane_query: Query<(&Velocity, &CentreOfGravity, &Children), With<Plane>>,
+    payload_query: Query<(
+        &Payload,
+        &GlobalTransform,
+        &Handle<Mesh>,
+        &Handle<StandardMaterial>,
+    )>,
+) {
+    let Ok(action_state) = action_query.get_single() else {
+        return;
+    };
+    if !action_state.just_pressed(PlaneAction::DropPayload) {
+        return;
+    }
+
+    for (velocity, centre_of_gravity, children) in plane_query.iter() {
+        let Some((entity, (payload, global_tx, mesh, material))) = children
+            .iter()
+            .find_map(|child| payload_query.get(*child).ok().map(|p| (*child, p)))
+        else {
+            continue;
+        };
+
+        let tx = global_tx.compute_transform();
+        let linvel = velocity.linvel
+            + velocity
+                .angvel
+                .cross(tx.translation - centre_of_gravity.global);
+        info!("Dropping {}", payload.kind.name());
+
+        // Removing the collider from the plane takes its mass away with it
+        commands.entity(entity).despawn_recursive();
+        commands.spawn((
+            DroppedPayload {
+                kind: payload.kind,
+                age: 0.0,
+                landed: false,
+                at_rest: 0.0,
+            },
+            PbrBundle {
+                mesh: mesh.clone(),
+                material: material.clone(),
+                transform: tx,
+                ..default()
+            },
+            RigidBody::Dynamic,
+            collider(payload.kind),
+            ColliderMassProperties::Mass(payload.mass),
+            Velocity {
+                linvel,
+                angvel: velocity.angvel,
+            },
+            ActiveEvents::COLLISION_EVENTS,
+        ));
+    }
+}
+
+/// Notes where dropped payloads first hit something other than a plane, bursting water bombs, and
+/// clears away old ones.
+pub fn land_payloads(
+    mut commands: Commands,
+    mut collision_events: EventReader<CollisionEvent>,
+    assets: Res<PayloadAssets>,
+    mut payload_query: Query<(Entity, &mut DroppedPayload, &GlobalTransform, &Velocity)>,
+    mut settled_events: EventWriter<CrateSettledEvent>,
+    parent_query: Query<&Parent>,
+    plane_query: Query<(), With<Plane>>,
+) {
+    for event in collision_events.iter() {
+        let CollisionEvent::Started(e1, e2, flags) = event else {
+            continue;
+        };
+        if flags.contains(CollisionEventFlags::SENSOR) {
+            continue;
+        }
+
+        for (entity, other) in [(*e1, *e2), (*e2, *e1)] {
+            let Ok((entity, mut payload, global_tx, _)) = payload_query.get_mut(entity) else {
+                continue;
+            };
+            // Touching the plane it was dropped from isn't landing
+            let hit_plane = std::iter::once(other)
+                .chain(parent_query.iter_ancestors(other))
+                .any(|entity| plane_query.contains(entity));
+            if payload.landed || hit_plane {
+                continue;
+            }
+
+            payload.landed = true;
+            let pos = global_tx.translation();
+            info!("{} landed at {:?}", payload.kind.name(), pos);
+
+            if payload.kind == PayloadKind::WaterBomb {
+                commands.entity(entity).despawn_recursive();
+                spawn_splash(&mut commands, &assets, pos);
+            }
+        }
+    }
+
+    for (entity, mut payload, global_tx, velocity) in payload_query.iter_mut() {
+        payload.age += PHYSICS_DT;
+        let still =
+            velocity.linvel.length() < SETTLED_SPEED && velocity.angvel.length() < SETTLED_SPEED;
+        payload.at_rest = if still {
+            payload.at_rest + PHYSICS_DT
+        } else {
+            0.0
+        };
+
+        // A crate that has come to rest becomes part of the world, kept for as long as it is
+        if payload.landed && payload.kind == PayloadKind::Crate && payload.at_rest > SETTLE_TIME {
+            commands.entity(entity).despawn_recursive();
+            settled_events.send(CrateSettledEvent(global_tx.compute_transform()));
+        } else if payload.age > DROPPED_LIFETIME {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+fn spawn_splash(commands: &mut Commands, assets: &PayloadAssets, pos: Vec3) {
+    let mut rng = rand::thread_rng();
+
+    for _ in 0..SPLASH_DROPS {
+        let angle = rng.gen_range(0.0..TAU);
+        let direction = Vec3::new(angle.cos(), rng.gen_range(0.5..1.5), angle.sin()).normalize();
+
+        commands.spawn((
+            SplashDrop {
+                velocity: direction * rng.gen_range(SPLASH_SPEED),
+                age: 0.0,
+            },
+            PbrBundle {
+                mesh: assets.splash_mesh.clone(),
+                material: assets.splash_material.clone(),
+                transform: Transform::from_translation(pos),
+                ..default()
+            },
+        ));
+    }
+}
+
+pub fn update_splashes(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut drop_query: Query<(Entity, &mut SplashDrop, &mut Transform)>,
+) {
+    let dt = time.delta_seconds();
+
+    for (entity, mut drop, mut tx) in drop_query.iter_mut() {
+        drop.age += dt;
+        if drop.age > SPLASH_LIFETIME {
+            commands.entity(entity).despawn();
+            continue;
+        }
+
+        drop.velocity.y -= GRAVITY * dt;
+        tx.translation += drop.velocity * dt;
+    }
+}
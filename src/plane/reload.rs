@@ -0,0 +1,84 @@
+use bevy::{
+    asset::{AssetLoader, LoadContext, LoadedAsset},
+    prelude::*,
+    reflect::{TypePath, TypeUuid},
+    utils::BoxedFuture,
+};
+
+use super::{spec::PlaneSpec, BuildPlaneEvent};
+
+/// Folder in the assets folder watched for plane spec files.
+const PLANES_FOLDER: &str = "planes";
+
+/// Plane spec loaded from a `.plane.ron` file.
+#[derive(TypeUuid, TypePath)]
+#[uuid = "5d0e7a3c-2f41-4c8b-a6e9-1b73c94f08d2"]
+pub struct PlaneSpecFile(pub PlaneSpec);
+
+#[derive(Default)]
+pub struct PlaneSpecLoader;
+
+impl AssetLoader for PlaneSpecLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), bevy::asset::Error>> {
+        Box::pin(async move {
+            let spec = ron::de::from_bytes::<PlaneSpec>(bytes)?;
+            load_context.set_default_asset(LoadedAsset::new(PlaneSpecFile(spec)));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["plane.ron"]
+    }
+}
+
+/// Handles to the watched spec files, kept so they stay loaded.
+#[derive(Resource)]
+pub struct PlaneSpecFiles {
+    _handles: Vec<HandleUntyped>,
+}
+
+pub fn load_plane_specs(mut commands: Commands, asset_server: Res<AssetServer>) {
+    match asset_server.load_folder(PLANES_FOLDER) {
+        Ok(handles) => {
+            info!(
+                "Watching {} plane specs in {}",
+                handles.len(),
+                PLANES_FOLDER
+            );
+            commands.insert_resource(PlaneSpecFiles { _handles: handles });
+        }
+        Err(err) => info!("Not watching plane specs: {}", err),
+    }
+}
+
+/// Rebuilds the plane from a spec file each time it's saved.
+pub fn reload_plane_specs(
+    mut asset_events: EventReader<AssetEvent<PlaneSpecFile>>,
+    spec_files: Res<Assets<PlaneSpecFile>>,
+    mut build_plane_event: EventWriter<BuildPlaneEvent>,
+) {
+    for event in asset_events.iter() {
+        // Loading the files at startup creates them, which shouldn't replace the plane
+        let AssetEvent::Modified { handle } = event else {
+            continue;
+        };
+        let Some(PlaneSpecFile(spec)) = spec_files.get(handle) else {
+            continue;
+        };
+
+        if let Err(errors) = spec.validate() {
+            for error in errors {
+                warn!("Not rebuilding plane spec {}: {}", spec.name, error);
+            }
+            continue;
+        }
+
+        info!("Reloaded plane spec {}", spec.name);
+        build_plane_event.send(BuildPlaneEvent(spec.clone()));
+    }
+}
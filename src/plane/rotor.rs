@@ -0,0 +1,84 @@
+use bevy::{math::vec3, prelude::*};
+use bevy_rapier3d::prelude::*;
+
+use crate::flight_model::{CentreOfGravity, Thrust};
+
+use super::{spec::PlaneSpec, Plane, PlaneControl};
+
+/// Yaw damping from the tail rotor, as a fraction of its full torque for each rad/s of yaw rate.
+const TAIL_ROTOR_DAMPING: f32 = 0.5;
+/// Rotor speed at full collective, in rad/s.
+const ROTOR_SPEED: f32 = 40.0;
+
+/// Blades of a helicopter's main rotor, spun for show.
+#[derive(Component)]
+pub struct Rotor;
+
+/// Lift from the main rotor for helicopters. The throttle sets the collective, the pitch and roll
+/// controls tilt the rotor disc as the cyclic, and the rudder sets the tail rotor's anti-torque.
+#[allow(clippy::type_complexity)]
+pub fn update_rotor_forces(
+    mut plane_query: Query<
+        (
+            &PlaneSpec,
+            &PlaneControl,
+            &Thrust,
+            &GlobalTransform,
+            &Velocity,
+            &CentreOfGravity,
+            &mut ExternalForce,
+        ),
+        With<Plane>,
+    >,
+) {
+    for (
+        spec,
+        control,
+        Thrust(thrust),
+        global_tx,
+        velocity,
+        centre_of_gravity,
+        mut external_force,
+    ) in plane_query.iter_mut()
+    {
+        let Some(rotor) = &spec.rotor else {
+            continue;
+        };
+
+        let fraction = |value: f32, max: f32| (value / max.max(f32::EPSILON)).clamp(-1.0, 1.0);
+        let collective = fraction(*thrust, spec.thrust).max(0.0);
+        let pitch = fraction(control.elevators, spec.pitch_wing().max_control_angle);
+        let roll = fraction(control.ailerons, spec.main_wing().max_control_angle);
+        let yaw = fraction(control.rudder, spec.fin().max_control_angle);
+
+        // Pulling back tilts the disc back, and rolling right tilts it right, so the thrust above
+        // the centre of gravity turns the helicopter that way
+        let tilt = Quat::from_axis_angle(global_tx.right(), pitch * rotor.max_cyclic)
+            * Quat::from_axis_angle(global_tx.forward(), roll * rotor.max_cyclic);
+        let hub = global_tx.transform_point(vec3(0., rotor.height, 0.));
+        *external_force += ExternalForce::at_point(
+            tilt * global_tx.up() * collective * rotor.max_thrust,
+            hub,
+            centre_of_gravity.global,
+        );
+
+        let up = global_tx.up();
+        let yaw_rate = velocity.angvel.dot(up);
+        external_force.torque -= up * rotor.anti_torque * (yaw + yaw_rate * TAIL_ROTOR_DAMPING);
+    }
+}
+
+pub fn spin_rotor(
+    plane_query: Query<(&Thrust, &PlaneSpec)>,
+    mut rotor_query: Query<&mut Transform, With<Rotor>>,
+    time: Res<Time>,
+) {
+    let Ok((Thrust(thrust), spec)) = plane_query.get_single() else {
+        return;
+    };
+
+    for mut tx in rotor_query.iter_mut() {
+        let rate = (*thrust / spec.thrust.max(f32::EPSILON)) * ROTOR_SPEED;
+        tx.rotate_local_y(rate * time.delta_seconds());
+    }
+}
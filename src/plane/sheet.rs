@@ -0,0 +1,350 @@
+use bevy::{math::vec2, prelude::*};
+
+use crate::{
+    canvas::Canvas,
+    units::{Quantity, Units},
+};
+
+use super::{
+    lod::outline_part,
+    spec::{PartKind, PartSpec, PlaneSpec, WingRole},
+    tuning,
+};
+
+const DESIGN_SHEET_PATH: &str = "design_sheet.png";
+const WIDTH: u32 = 1400;
+const HEIGHT: u32 = 900;
+const MARGIN: f32 = 20.0;
+/// Left edge of the column of numbers and plots, right of the views.
+const COLUMN_X: f32 = 900.0;
+const TITLE_SCALE: u32 = 4;
+const TEXT_SCALE: u32 = 2;
+const LINE_HEIGHT: f32 = 16.0;
+/// Range of angles of attack plotted for the main wings, in degrees.
+const PLOT_ANGLES: std::ops::RangeInclusive<f32> = -30.0..=30.0;
+const PLOT_SIZE: Vec2 = Vec2::new(480.0, 130.0);
+
+const BACKGROUND_COLOR: [u8; 4] = [24, 56, 104, 255];
+const LINE_COLOR: [u8; 4] = [230, 240, 255, 255];
+const FILL_COLOR: [u8; 4] = [60, 100, 160, 255];
+const LABEL_COLOR: [u8; 4] = [150, 180, 220, 255];
+const MARKER_COLOR: [u8; 4] = [255, 200, 60, 255];
+
+/// Direction a view looks along, with the plane's axes shown across and down it.
+#[derive(Clone, Copy)]
+enum View {
+    Top,
+    Side,
+    Front,
+}
+
+impl View {
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Top => "top",
+            Self::Side => "side",
+            Self::Front => "front",
+        }
+    }
+
+    /// Position across and down the view, with the nose up in the top view and to the left in the
+    /// side view.
+    fn project(&self, pos: Vec3) -> Vec2 {
+        match self {
+            Self::Top => vec2(pos.x, pos.z),
+            Self::Side => vec2(pos.z, -pos.y),
+            Self::Front => vec2(pos.x, -pos.y),
+        }
+    }
+}
+
+/// Saves a sheet describing a design, to share or compare it outside the game.
+pub fn export_design_sheet(spec: &PlaneSpec, units: Units) {
+    match render_design_sheet(spec, units).save(DESIGN_SHEET_PATH) {
+        Ok(()) => info!("Saved design sheet to {}", DESIGN_SHEET_PATH),
+        Err(err) => warn!(
+            "Failed to save design sheet to {}: {}",
+            DESIGN_SHEET_PATH, err
+        ),
+    }
+}
+
+fn render_design_sheet(spec: &PlaneSpec, units: Units) -> Canvas {
+    let mut canvas = Canvas::new(WIDTH, HEIGHT, BACKGROUND_COLOR);
+    let parts = spec.parts();
+    let mut boxes = vec![];
+    outline_part(&parts, Vec3::ZERO, None, &mut boxes);
+
+    canvas.draw_text(
+        MARGIN as u32,
+        MARGIN as u32,
+        "Design sheet",
+        TITLE_SCALE,
+        LINE_COLOR,
+    );
+    let subtitle = match spec.wing_rows.len() {
+        0 => format!("{} layout", spec.layout.name()),
+        rows => format!("{} layout, {} extra wing rows", spec.layout.name(), rows),
+    };
+    canvas.draw_text(MARGIN as u32, 48, &subtitle, TEXT_SCALE, LABEL_COLOR);
+
+    // Views share a scale so they can be compared, fitted to the smallest of them
+    let (min, max) = boxes
+        .iter()
+        .fold((Vec3::MAX, Vec3::MIN), |(min, max), (size, pos)| {
+            (min.min(*pos - *size * 0.5), max.max(*pos + *size * 0.5))
+        });
+    let panels = [
+        (
+            View::Top,
+            vec2(MARGIN, 80.0),
+            vec2(COLUMN_X - MARGIN * 2.0, 420.0),
+        ),
+        (View::Side, vec2(MARGIN, 520.0), vec2(420.0, 360.0)),
+        (View::Front, vec2(460.0, 520.0), vec2(420.0, 360.0)),
+    ];
+    let pixels_per_metre = panels
+        .iter()
+        .map(|(view, _, size)| {
+            let extent = (view.project(max) - view.project(min)).abs();
+            (*size * 0.8 / extent).min_element()
+        })
+        .fold(f32::INFINITY, f32::min);
+
+    let balance = tuning::balance(spec);
+    for (view, corner, size) in panels {
+        canvas.stroke_rect(corner, corner + size, LABEL_COLOR);
+        canvas.draw_text(
+            (corner.x + 6.0) as u32,
+            (corner.y + 6.0) as u32,
+            view.name(),
+            TEXT_SCALE,
+            LABEL_COLOR,
+        );
+
+        let centre = (view.project(min) + view.project(max)) * 0.5;
+        let to_pixel =
+            |pos: Vec3| corner + size * 0.5 + (view.project(pos) - centre) * pixels_per_metre;
+        let rects: Vec<_> = boxes
+            .iter()
+            .map(|(box_size, pos)| {
+                let a = to_pixel(*pos - *box_size * 0.5);
+                let b = to_pixel(*pos + *box_size * 0.5);
+                (a.min(b), a.max(b))
+            })
+            .collect();
+
+        // Fill every part before outlining any, so the edges of overlapping parts still show
+        for (min, max) in rects.iter() {
+            canvas.fill_rect(*min, *max, FILL_COLOR);
+        }
+        for (min, max) in rects.iter() {
+            canvas.stroke_rect(*min, *max, LINE_COLOR);
+        }
+
+        if let View::Side = view {
+            for (label, z) in [
+                ("CG", balance.centre_of_gravity),
+                ("NP", balance.neutral_point),
+            ] {
+                let pixel = to_pixel(Vec3::new(0., 0., z));
+                canvas.fill_rect(pixel - 3.0, pixel + 3.0, MARKER_COLOR);
+                canvas.draw_text(
+                    (pixel.x - 7.0) as u32,
+                    (pixel.y - 18.0) as u32,
+                    label,
+                    TEXT_SCALE,
+                    MARKER_COLOR,
+                );
+            }
+        }
+
+        // Metre scale bar in the bottom left corner
+        let bar_start = corner + vec2(10.0, size.y - 12.0);
+        canvas.line(
+            bar_start,
+            bar_start + Vec2::X * pixels_per_metre,
+            LINE_COLOR,
+        );
+        canvas.draw_text(
+            (bar_start.x + pixels_per_metre + 6.0) as u32,
+            (bar_start.y - 4.0) as u32,
+            "1 m",
+            TEXT_SCALE,
+            LINE_COLOR,
+        );
+    }
+
+    // Key dimensions, then the numbers from analysing the design
+    let extent = max - min;
+    let wing_area = lifting_area(&parts, false);
+    let mut y = 80.0;
+    let heading = |canvas: &mut Canvas, y: &mut f32, text: &str| {
+        canvas.draw_text(COLUMN_X as u32, *y as u32, text, TEXT_SCALE, LABEL_COLOR);
+        *y += LINE_HEIGHT * 1.5;
+    };
+    let line = |canvas: &mut Canvas, y: &mut f32, label: &str, value: &str| {
+        canvas.draw_text(
+            COLUMN_X as u32,
+            *y as u32,
+            &format!("{}: {}", label, value),
+            TEXT_SCALE,
+            LINE_COLOR,
+        );
+        *y += LINE_HEIGHT;
+    };
+
+    heading(&mut canvas, &mut y, "Dimensions");
+    let length = |value| units.format(Quantity::Altitude, value, 1);
+    line(&mut canvas, &mut y, "span", &length(extent.x));
+    line(&mut canvas, &mut y, "length", &length(extent.z));
+    line(&mut canvas, &mut y, "height", &length(extent.y));
+    line(
+        &mut canvas,
+        &mut y,
+        "wing area",
+        &format!("{:.1} m²", wing_area),
+    );
+    if wing_area > 0.0 {
+        let aspect_ratio = extent.x * extent.x / wing_area;
+        line(
+            &mut canvas,
+            &mut y,
+            "aspect ratio",
+            &format!("{:.1}", aspect_ratio),
+        );
+    }
+
+    y += LINE_HEIGHT;
+    heading(&mut canvas, &mut y, "Performance");
+    for (label, value) in tuning::analyse(spec, units).characteristics.iter() {
+        line(&mut canvas, &mut y, label, value);
+    }
+
+    y += LINE_HEIGHT;
+    let curves = [
+        (
+            "Wing lift coefficient",
+            spec.main_wing().lift_coefficient_samples(),
+        ),
+        (
+            "Wing drag coefficient",
+            spec.main_wing().drag_coefficient_samples(),
+        ),
+    ];
+    // A curve that can't be sampled is left off the sheet
+    for (label, samples) in curves {
+        let Ok(samples) = samples else {
+            continue;
+        };
+        plot_curve(&mut canvas, vec2(COLUMN_X, y), label, &samples);
+        y += PLOT_SIZE.y + LINE_HEIGHT * 4.0;
+    }
+
+    canvas
+}
+
+/// Total area of the wings that lift the plane, not counting the tail surfaces.
+fn lifting_area(part: &PartSpec, mirrored: bool) -> f32 {
+    let sides = if part.mirrored && !mirrored { 2.0 } else { 1.0 };
+    let area = match &part.kind {
+        PartKind::Wing {
+            spec,
+            role: WingRole::Main | WingRole::Elevon,
+        } => spec.size.x * spec.size.z * 2.0,
+        _ => 0.0,
+    };
+    let attached: f32 = part
+        .attachments
+        .iter()
+        .map(|attached| lifting_area(attached, mirrored || part.mirrored))
+        .sum();
+
+    (area + attached) * sides
+}
+
+/// Plots a coefficient curve sampled evenly from -90° to 90° over the plotted angles, with its
+/// label above.
+fn plot_curve(canvas: &mut Canvas, corner: Vec2, label: &str, samples: &[f32]) {
+    canvas.draw_text(
+        corner.x as u32,
+        corner.y as u32,
+        label,
+        TEXT_SCALE,
+        LABEL_COLOR,
+    );
+    let corner = corner + Vec2::Y * LINE_HEIGHT * 1.5;
+    canvas.stroke_rect(corner, corner + PLOT_SIZE, LABEL_COLOR);
+
+    let points: Vec<_> = samples
+        .iter()
+        .enumerate()
+        .map(|(i, value)| {
+            let angle = -90.0 + 180.0 * i as f32 / (samples.len() - 1).max(1) as f32;
+            vec2(angle, *value)
+        })
+        .filter(|point| PLOT_ANGLES.contains(&point.x))
+        .collect();
+    if points.len() < 2 {
+        return;
+    }
+
+    // Always include zero so the axis shows
+    let (low, high) = points
+        .iter()
+        .fold((0.0_f32, 0.0_f32), |(low, high), point| {
+            (low.min(point.y), high.max(point.y))
+        });
+    let range = (high - low).max(0.01);
+    let to_pixel = |point: Vec2| {
+        let x = (point.x - PLOT_ANGLES.start()) / (PLOT_ANGLES.end() - PLOT_ANGLES.start());
+        let y = (high - point.y) / range;
+        corner + vec2(x, y) * PLOT_SIZE
+    };
+
+    let zero = to_pixel(Vec2::ZERO);
+    canvas.line(
+        vec2(corner.x, zero.y),
+        vec2(corner.x + PLOT_SIZE.x, zero.y),
+        LABEL_COLOR,
+    );
+    canvas.line(
+        vec2(zero.x, corner.y),
+        vec2(zero.x, corner.y + PLOT_SIZE.y),
+        LABEL_COLOR,
+    );
+    for pair in points.windows(2) {
+        canvas.line(to_pixel(pair[0]), to_pixel(pair[1]), MARKER_COLOR);
+    }
+
+    let text_x = (corner.x + 4.0) as u32;
+    canvas.draw_text(
+        text_x,
+        corner.y as u32 + 4,
+        &format!("{:.2}", high),
+        TEXT_SCALE,
+        LINE_COLOR,
+    );
+    canvas.draw_text(
+        text_x,
+        (corner.y + PLOT_SIZE.y) as u32 - 14,
+        &format!("{:.2}", low),
+        TEXT_SCALE,
+        LINE_COLOR,
+    );
+    let end_label = format!("{:.0}°", PLOT_ANGLES.end());
+    canvas.draw_text(
+        (corner.x + PLOT_SIZE.x) as u32 - Canvas::text_width(&end_label, TEXT_SCALE),
+        (corner.y + PLOT_SIZE.y) as u32 + 4,
+        &end_label,
+        TEXT_SCALE,
+        LABEL_COLOR,
+    );
+    canvas.draw_text(
+        corner.x as u32,
+        (corner.y + PLOT_SIZE.y) as u32 + 4,
+        &format!("{:.0}°", PLOT_ANGLES.start()),
+        TEXT_SCALE,
+        LABEL_COLOR,
+    );
+}
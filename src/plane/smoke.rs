@@ -0,0 +1,199 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use leafwing_input_manager::prelude::ActionState;
+
+use crate::input::PlaneAction;
+
+use super::{
+    lod::{outline_part, tail_point},
+    spec::PlaneSpec,
+    Plane,
+};
+
+/// Puffs of smoke emitted per second from each emitter.
+const SMOKE_RATE: f32 = 60.0;
+const SMOKE_LIFETIME: f32 = 20.0;
+const SMOKE_SIZE: f32 = 0.6;
+/// Size a puff spreads to by the end of its life, as a multiple of its starting size.
+const SMOKE_SPREAD: f32 = 6.0;
+/// Speed the smoke sinks at as it cools.
+const SMOKE_SINK_SPEED: f32 = 0.3;
+
+/// Where on the plane smoke comes from.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum SmokeEmitter {
+    #[default]
+    Wingtips,
+    Tail,
+}
+
+impl SmokeEmitter {
+    pub const ALL: [Self; 2] = [Self::Wingtips, Self::Tail];
+}
+
+/// Smoke trailed by the plane, for showing off aerobatics and the path flown.
+#[derive(Resource)]
+pub struct SmokeSettings {
+    pub enabled: bool,
+    pub color: Color,
+    pub emitter: SmokeEmitter,
+}
+
+impl Default for SmokeSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            color: Color::WHITE,
+            emitter: SmokeEmitter::default(),
+        }
+    }
+}
+
+/// Points on a plane that smoke can come from, in its local space.
+#[derive(Component)]
+pub struct SmokeGenerator {
+    wingtips: [Vec3; 2],
+    tail: Vec3,
+}
+
+impl SmokeGenerator {
+    /// Finds the wingtips and tail from the outline of the parts, so it works for any layout.
+    pub fn new(spec: &PlaneSpec) -> Self {
+        let mut boxes = vec![];
+        outline_part(&spec.parts(), Vec3::ZERO, None, &mut boxes);
+
+        let wingtip = boxes
+            .iter()
+            .map(|(size, pos)| Vec3::new(pos.x.abs() + size.x * 0.5, pos.y, pos.z))
+            .reduce(|a, b| if b.x > a.x { b } else { a })
+            .unwrap_or_default();
+
+        Self {
+            wingtips: [wingtip, wingtip * Vec3::new(-1., 1., 1.)],
+            tail: tail_point(spec),
+        }
+    }
+
+    fn points(&self, emitter: SmokeEmitter) -> &[Vec3] {
+        match emitter {
+            SmokeEmitter::Wingtips => &self.wingtips,
+            SmokeEmitter::Tail => std::slice::from_ref(&self.tail),
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct SmokePuff {
+    age: f32,
+}
+
+/// Material for the puffs in the current colour. Puffs already emitted keep their colour.
+#[derive(Resource)]
+pub struct SmokeAssets {
+    mesh: Handle<Mesh>,
+    material: Handle<StandardMaterial>,
+    color: Color,
+}
+
+pub fn setup_smoke(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    settings: Res<SmokeSettings>,
+) {
+    commands.insert_resource(SmokeAssets {
+        mesh: meshes.add(Mesh::from(shape::Cube { size: SMOKE_SIZE })),
+        material: materials.add(smoke_material(settings.color)),
+        color: settings.color,
+    });
+}
+
+fn smoke_material(color: Color) -> StandardMaterial {
+    StandardMaterial {
+        base_color: color,
+        unlit: true,
+        ..default()
+    }
+}
+
+pub fn toggle_smoke(
+    action_query: Query<&ActionState<PlaneAction>>,
+    mut settings: ResMut<SmokeSettings>,
+) {
+    let Ok(action_state) = action_query.get_single() else {
+        return;
+    };
+
+    if action_state.just_pressed(PlaneAction::ToggleSmoke) {
+        settings.enabled = !settings.enabled;
+    }
+}
+
+pub fn emit_smoke(
+    mut commands: Commands,
+    time: Res<Time>,
+    settings: Res<SmokeSettings>,
+    mut assets: ResMut<SmokeAssets>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    plane_query: Query<(&GlobalTransform, &Velocity, &SmokeGenerator), With<Plane>>,
+    mut carry: Local<f32>,
+) {
+    if !settings.enabled {
+        *carry = 0.0;
+        return;
+    }
+    if settings.color != assets.color {
+        assets.material = materials.add(smoke_material(settings.color));
+        assets.color = settings.color;
+    }
+
+    // Accumulate fractional puffs so low frame times still emit at the right rate
+    let dt = time.delta_seconds();
+    *carry += SMOKE_RATE * dt;
+    let count = carry.floor();
+    *carry -= count;
+
+    for (global_tx, velocity, generator) in plane_query.iter() {
+        for point in generator.points(settings.emitter) {
+            let pos = global_tx.transform_point(*point);
+
+            // Spread the puffs back along the path flown since the last frame, so fast planes
+            // leave a solid trail rather than a dotted one
+            for i in 0..count as usize {
+                let back = velocity.linvel * dt * i as f32 / count;
+                commands.spawn((
+                    SmokePuff { age: 0.0 },
+                    PbrBundle {
+                        mesh: assets.mesh.clone(),
+                        material: assets.material.clone(),
+                        transform: Transform::from_translation(pos - back),
+                        ..default()
+                    },
+                ));
+            }
+        }
+    }
+}
+
+pub fn update_smoke(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut puff_query: Query<(Entity, &mut SmokePuff, &mut Transform)>,
+) {
+    let dt = time.delta_seconds();
+
+    for (entity, mut puff, mut tx) in puff_query.iter_mut() {
+        puff.age += dt;
+        if puff.age > SMOKE_LIFETIME {
+            commands.entity(entity).despawn();
+            continue;
+        }
+
+        tx.translation.y -= SMOKE_SINK_SPEED * dt;
+
+        // Spreads out then thins away towards the end of its life
+        let life = puff.age / SMOKE_LIFETIME;
+        let fade = ((1.0 - life) * 4.0).min(1.0);
+        tx.scale = Vec3::splat(1.0 + life * (SMOKE_SPREAD - 1.0)) * fade;
+    }
+}
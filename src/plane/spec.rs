@@ -1,13 +1,41 @@
 use bevy::{math::vec3, prelude::*};
-use enterpolation::{linear::Linear, Curve};
+use enterpolation::{
+    linear::{Linear, LinearError},
+    Curve,
+};
+use serde::{Deserialize, Serialize};
 
-#[derive(Component, Debug, Clone)]
+mod validate;
+
+#[derive(Component, Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct PlaneSpec {
     pub name: String,
     pub thrust: f32,
+    pub engine: EngineSpec,
+    /// Whether the control law protecting the flight envelope sits between the pilot and the surfaces.
+    #[serde(default)]
+    pub fly_by_wire: bool,
+    /// Airspeed in m/s above which the control surfaces start to flutter.
+    #[serde(default = "default_never_exceed_speed")]
+    pub never_exceed_speed: f32,
     pub fuselage: FuselageSpec,
     pub wings: WingSpec,
     pub tail: TailSpec,
+    /// Extra mass for moving the centre of gravity.
+    #[serde(default)]
+    pub ballast: BallastSpec,
+    /// How the parts are put together.
+    #[serde(default)]
+    pub layout: Layout,
+    /// Further pairs of wings, such as the upper wing of a biplane or a foreplane.
+    #[serde(default)]
+    pub wing_rows: Vec<WingRowSpec>,
+    /// Objects carried under the plane until they are dropped.
+    #[serde(default)]
+    pub payloads: Vec<PayloadSpec>,
+    /// Main rotor lifting the plane as a helicopter, in place of the propellor's thrust.
+    #[serde(default)]
+    pub rotor: Option<RotorSpec>,
 }
 
 impl Default for PlaneSpec {
@@ -15,12 +43,17 @@ impl Default for PlaneSpec {
         Self {
             name: "Default".to_string(),
             thrust: 500.0,
+            engine: EngineSpec::default(),
+            fly_by_wire: false,
+            never_exceed_speed: default_never_exceed_speed(),
             fuselage: FuselageSpec {
                 size: vec3(1.12, 2.0, 5.3),
                 mass: 100.0,
                 wheel_y_offset: 0.5,
                 wheel_x_offset: 0.7,
                 wheel_radius: 0.2,
+                undercarriage: Undercarriage::default(),
+                gear: GearLayout::default(),
             },
             wings: WingSpec {
                 size: vec3(5.5, 0.2, 1.5),
@@ -33,6 +66,7 @@ impl Default for PlaneSpec {
                     (0.0, 90.0),
                 ],
                 max_control_angle: 2.5_f32.to_radians(),
+                dihedral: 3_f32.to_radians(),
                 ..default()
             },
             tail: TailSpec {
@@ -65,17 +99,541 @@ impl Default for PlaneSpec {
                     ..default()
                 },
             },
+            ballast: BallastSpec::default(),
+            layout: Layout::default(),
+            wing_rows: vec![],
+            payloads: vec![],
+            rotor: None,
+        }
+    }
+}
+
+/// Mass of the boom carrying the tail surfaces.
+pub const TAIL_BOOM_MASS: f32 = 10.0;
+/// Distance of the main wings' leading edge behind the centre of the fuselage.
+pub const WING_OFFSET: f32 = 1.0;
+/// Gap between the wings of a biplane.
+const BIPLANE_GAP: f32 = 1.8;
+/// Sideways distance between payloads carried side by side.
+const PAYLOAD_SPACING: f32 = 0.7;
+/// Width and depth of each float.
+const FLOAT_SIZE: f32 = 0.35;
+/// Size of a tail wheel against the main wheels.
+const TAIL_WHEEL_SCALE: f32 = 0.5;
+
+impl PlaneSpec {
+    /// Graph of the parts to build, from the fuselage at its root. A custom layout saves its own
+    /// graph, which the wing rows are added onto.
+    pub fn parts(&self) -> PartSpec {
+        let fuselage = &self.fuselage;
+        let nose = -fuselage.size.z * 0.5;
+        let tail_boom_z = fuselage.size.z * 0.5 + self.tail.size.z * 0.5;
+
+        let main_wing =
+            |role| PartSpec::new(PartKind::wing(&self.wings, role), vec3(0., 0., WING_OFFSET));
+        let tail_end = vec3(0., 0., self.tail.size.z * 0.5);
+
+        let mut layout_parts = match &self.layout {
+            Layout::Custom(root) => {
+                let mut root = root.clone();
+                root.attachments
+                    .extend(self.wing_rows.iter().map(WingRowSpec::part));
+                return root;
+            }
+            Layout::Conventional => vec![
+                main_wing(WingRole::Main),
+                PartSpec::new(
+                    PartKind::Fuselage {
+                        size: self.tail.size,
+                        mass: TAIL_BOOM_MASS,
+                    },
+                    vec3(0., 0., tail_boom_z),
+                )
+                .with_attachments(vec![
+                    PartSpec::new(
+                        PartKind::wing(&self.tail.horizontal, WingRole::Stabiliser),
+                        tail_end,
+                    ),
+                    PartSpec::new(PartKind::wing(&self.tail.vertical, WingRole::Fin), tail_end),
+                ]),
+            ],
+            Layout::FlyingWing => vec![
+                main_wing(WingRole::Elevon),
+                PartSpec::new(
+                    PartKind::wing(&self.tail.vertical, WingRole::Fin),
+                    vec3(0., fuselage.size.y * 0.5, fuselage.size.z * 0.5),
+                ),
+            ],
+        };
+        layout_parts.extend(self.wing_rows.iter().map(WingRowSpec::part));
+
+        PartSpec::new(
+            PartKind::Fuselage {
+                size: fuselage.size,
+                mass: fuselage.mass,
+            },
+            Vec3::ZERO,
+        )
+        .with_attachments(
+            self.undercarriage_parts()
+                .into_iter()
+                .chain([PartSpec::new(
+                    PartKind::Engine {
+                        diameter: fuselage.size.x * 2.5,
+                    },
+                    vec3(0., 0., nose),
+                )])
+                .chain(layout_parts)
+                .collect(),
+        )
+    }
+
+    /// Wings the ailerons are on, whose chord stability is measured against. For a custom layout
+    /// this is the first main wing or elevon built, as the wings field isn't built.
+    pub fn main_wing(&self) -> &WingSpec {
+        self.custom_wing(&[WingRole::Main, WingRole::Elevon])
+            .unwrap_or(&self.wings)
+    }
+
+    /// Wings the elevators are on, for a custom layout the first stabiliser, canard or elevon built.
+    pub fn pitch_wing(&self) -> &WingSpec {
+        self.custom_wing(&[WingRole::Stabiliser, WingRole::Canard, WingRole::Elevon])
+            .unwrap_or(&self.tail.horizontal)
+    }
+
+    /// Fin the rudder is on, for a custom layout the first one built.
+    pub fn fin(&self) -> &WingSpec {
+        self.custom_wing(&[WingRole::Fin])
+            .unwrap_or(&self.tail.vertical)
+    }
+
+    /// First wing with one of the roles in a custom layout's graph or its wing rows. Other layouts
+    /// are built from the wings and tail fields, so have none.
+    fn custom_wing(&self, roles: &[WingRole]) -> Option<&WingSpec> {
+        let Layout::Custom(root) = &self.layout else {
+            return None;
+        };
+
+        root.placed_wings()
+            .into_iter()
+            .find(|wing| roles.contains(&wing.role))
+            .map(|wing| wing.spec)
+            .or_else(|| {
+                self.wing_rows
+                    .iter()
+                    .find(|row| roles.contains(&row.role))
+                    .map(|row| &row.wing)
+            })
+    }
+
+    /// Wheels laid out by the fuselage's gear, or floats on struts reaching up to the main wings, at
+    /// the same height as the wheels would be. A hull has no parts of its own, as the fuselage
+    /// floats on the water.
+    fn undercarriage_parts(&self) -> Vec<PartSpec> {
+        let fuselage = &self.fuselage;
+        let wheel_y = -(fuselage.size.y * 0.5 + fuselage.wheel_y_offset);
+        let wheel_x = fuselage.size.x * 0.5 + fuselage.wheel_x_offset;
+        let nose = -fuselage.size.z * 0.5;
+        // Main wheels sit just behind the wings' centre of lift for a nose wheel, so the plane
+        // rests on them and can rotate about them, or just ahead of the wings for a tail wheel
+        let behind_wing = WING_OFFSET + self.wings.size.z * 0.5;
+        let ahead_of_wing = WING_OFFSET - self.wings.size.z * 0.5;
+
+        let wheel = |steering, position| {
+            PartSpec::new(
+                PartKind::Gear {
+                    radius: fuselage.wheel_radius,
+                    steering,
+                },
+                position,
+            )
+        };
+
+        match fuselage.undercarriage {
+            Undercarriage::Wheels => match fuselage.gear {
+                GearLayout::Tricycle => vec![
+                    wheel(Steering::Fixed, vec3(wheel_x, wheel_y, behind_wing)).mirrored(),
+                    wheel(Steering::Rudder, vec3(0., wheel_y, nose)),
+                ],
+                GearLayout::Taildragger => {
+                    // Small and close under the tail, so the plane sits nose up on its main wheels
+                    let tail_radius = fuselage.wheel_radius * TAIL_WHEEL_SCALE;
+                    vec![
+                        wheel(Steering::Fixed, vec3(wheel_x, wheel_y, ahead_of_wing)).mirrored(),
+                        PartSpec::new(
+                            PartKind::Gear {
+                                radius: tail_radius,
+                                steering: Steering::Castoring,
+                            },
+                            vec3(
+                                0.,
+                                -(fuselage.size.y * 0.5 + tail_radius),
+                                fuselage.size.z * 0.5,
+                            ),
+                        ),
+                    ]
+                }
+                GearLayout::Quad => vec![
+                    wheel(Steering::Rudder, vec3(wheel_x, wheel_y, nose)).mirrored(),
+                    wheel(Steering::Fixed, vec3(wheel_x, wheel_y, behind_wing)).mirrored(),
+                ],
+            },
+            Undercarriage::Floats => {
+                let float_y = wheel_y - fuselage.wheel_radius + FLOAT_SIZE * 0.5;
+                let strut_height = -(float_y + FLOAT_SIZE * 0.5);
+                let strut_z = WING_OFFSET + self.wings.size.z * 0.3;
+
+                vec![PartSpec::new(
+                    PartKind::Pylon {
+                        size: vec3(0.1, strut_height, 0.2),
+                    },
+                    vec3(wheel_x, -strut_height * 0.5, strut_z),
+                )
+                .with_attachments(vec![PartSpec::new(
+                    PartKind::Float {
+                        size: vec3(FLOAT_SIZE, FLOAT_SIZE, fuselage.size.z),
+                    },
+                    vec3(0., -(strut_height + FLOAT_SIZE) * 0.5, -strut_z),
+                )])
+                .mirrored()]
+            }
+            Undercarriage::Hull => vec![],
+        }
+    }
+}
+
+/// Arrangement of the fuselage, wings and tail.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub enum Layout {
+    /// Wings at the front with the tail surfaces behind on a boom.
+    #[default]
+    Conventional,
+    /// Wings without horizontal tail surfaces, pitching with their elevons.
+    FlyingWing,
+    /// Any other arrangement of parts, given as the whole graph from the fuselage at its root,
+    /// including the undercarriage and engine.
+    Custom(PartSpec),
+}
+
+impl Layout {
+    pub const PRESETS: [Self; 2] = [Self::Conventional, Self::FlyingWing];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Conventional => "Conventional",
+            Self::FlyingWing => "Flying wing",
+            Self::Custom(_) => "Custom",
+        }
+    }
+}
+
+/// Pair of wings placed relative to the main wings, built with the other wings of the layout.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WingRowSpec {
+    pub role: WingRole,
+    /// Height of the root above the main wings' root.
+    pub height: f32,
+    /// Distance of the root behind the main wings' root, negative towards the nose.
+    pub position: f32,
+    pub wing: WingSpec,
+}
+
+impl WingRowSpec {
+    /// Upper wing of a biplane, a copy of the main wings set forward of them.
+    pub fn biplane(spec: &PlaneSpec) -> Self {
+        Self {
+            role: WingRole::Main,
+            height: BIPLANE_GAP,
+            position: -0.3,
+            wing: spec.wings.clone(),
+        }
+    }
+
+    /// Foreplane near the nose, a copy of the horizontal tail.
+    pub fn canard(spec: &PlaneSpec) -> Self {
+        Self {
+            role: WingRole::Canard,
+            height: 0.,
+            position: -(spec.fuselage.size.z * 0.5 + WING_OFFSET) + 0.5,
+            wing: spec.tail.horizontal.clone(),
+        }
+    }
+
+    fn part(&self) -> PartSpec {
+        let wing = PartSpec::new(
+            PartKind::wing(&self.wing, self.role),
+            vec3(0., self.height, WING_OFFSET + self.position),
+        );
+        if self.role != WingRole::Main || self.height == 0.0 {
+            return wing;
+        }
+
+        // Stacked main wings are held apart by struts following the wings out towards their tips
+        let strut = Quat::from_rotation_y(-self.wing.sweep)
+            * Quat::from_rotation_z(self.wing.dihedral)
+            * vec3(self.wing.size.x * 0.7, 0., 0.)
+            + vec3(0., -self.height * 0.5, self.wing.size.z * 0.3);
+
+        wing.with_attachments(vec![PartSpec::new(
+            PartKind::Pylon {
+                size: vec3(0.1, self.height.abs(), 0.2),
+            },
+            strut,
+        )
+        .mirrored()])
+    }
+}
+
+/// Object carried by the plane, adding to its mass until it is dropped.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PayloadSpec {
+    pub kind: PayloadKind,
+    pub mass: f32,
+    /// Position of its centre relative to the centre of the fuselage.
+    pub position: Vec3,
+}
+
+impl PayloadSpec {
+    /// Payload hung under the fuselage in line with the wings, alternating either side of those
+    /// already carried.
+    pub fn under_fuselage(spec: &PlaneSpec, kind: PayloadKind) -> Self {
+        let count = spec.payloads.len();
+        let side = if count.is_multiple_of(2) { 1.0 } else { -1.0 };
+
+        Self {
+            kind,
+            mass: kind.default_mass(),
+            position: vec3(
+                count.div_ceil(2) as f32 * PAYLOAD_SPACING * side,
+                -(spec.fuselage.size.y + kind.size()) * 0.5,
+                WING_OFFSET,
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PayloadKind {
+    /// Box that lands and stays where it falls.
+    Crate,
+    /// Balloon of water that bursts on impact.
+    WaterBomb,
+}
+
+impl PayloadKind {
+    pub const ALL: [Self; 2] = [Self::Crate, Self::WaterBomb];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Crate => "Crate",
+            Self::WaterBomb => "Water bomb",
+        }
+    }
+
+    /// Width of a crate or diameter of a water bomb.
+    pub fn size(&self) -> f32 {
+        match self {
+            Self::Crate => 0.5,
+            Self::WaterBomb => 0.4,
+        }
+    }
+
+    fn default_mass(&self) -> f32 {
+        match self {
+            Self::Crate => 20.0,
+            Self::WaterBomb => 15.0,
+        }
+    }
+}
+
+/// Part of a plane, with the parts attached to it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PartSpec {
+    pub kind: PartKind,
+    /// Position relative to the part it is attached to, on the left for mirrored parts.
+    pub offset: Vec3,
+    /// Whether the part and its attachments are built on both sides of the plane.
+    #[serde(default)]
+    pub mirrored: bool,
+    #[serde(default)]
+    pub attachments: Vec<PartSpec>,
+}
+
+/// Wing in a parts graph, placed where it's built.
+pub struct PlacedWing<'a> {
+    pub spec: &'a WingSpec,
+    pub role: WingRole,
+    /// Position of its root from the part the graph was walked from.
+    pub position: Vec3,
+    /// Number of wings built from it, two for a pair either side of the plane.
+    pub count: u32,
+}
+
+impl PlacedWing<'_> {
+    /// Area of all the wings built from it, of their sides for a fin.
+    pub fn area(&self) -> f32 {
+        let size = self.spec.size;
+        match self.role {
+            WingRole::Fin => size.y * size.z * self.count as f32,
+            _ => size.x * size.z * self.count as f32,
         }
     }
 }
 
-#[derive(Debug, Clone)]
+impl PartSpec {
+    pub fn new(kind: PartKind, offset: Vec3) -> Self {
+        Self {
+            kind,
+            offset,
+            mirrored: false,
+            attachments: vec![],
+        }
+    }
+
+    pub fn mirrored(mut self) -> Self {
+        self.mirrored = true;
+        self
+    }
+
+    pub fn with_attachments(mut self, attachments: Vec<PartSpec>) -> Self {
+        self.attachments = attachments;
+        self
+    }
+
+    /// Wings in the graph from this part down, as they're built: in pairs, except for fins that
+    /// aren't mirrored.
+    pub fn placed_wings(&self) -> Vec<PlacedWing<'_>> {
+        let mut wings = Vec::new();
+        self.place_wings(Vec3::ZERO, false, &mut wings);
+        wings
+    }
+
+    fn place_wings<'a>(&'a self, parent: Vec3, mirrored: bool, wings: &mut Vec<PlacedWing<'a>>) {
+        let position = parent + self.offset;
+        let mirrored = mirrored || self.mirrored;
+        if let PartKind::Wing { spec, role } = &self.kind {
+            wings.push(PlacedWing {
+                spec,
+                role: *role,
+                position,
+                count: match (role, mirrored) {
+                    (WingRole::Fin, false) => 1,
+                    _ => 2,
+                },
+            });
+        }
+        for attached in self.attachments.iter() {
+            attached.place_wings(position, mirrored, wings);
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum PartKind {
+    /// Section of the fuselage, carrying most of the mass.
+    Fuselage {
+        size: Vec3,
+        mass: f32,
+    },
+    /// Wing built out from its root at the part's position, on both sides of the plane unless it is
+    /// a fin or mirrored with its parent.
+    Wing {
+        spec: Box<WingSpec>,
+        role: WingRole,
+    },
+    /// Strut or pod joining parts together.
+    Pylon {
+        size: Vec3,
+    },
+    /// Propellor the thrust is shown by.
+    Engine {
+        diameter: f32,
+    },
+    Gear {
+        radius: f32,
+        steering: Steering,
+    },
+    /// Float keeping the plane up on water.
+    Float {
+        size: Vec3,
+    },
+}
+
+impl PartKind {
+    fn wing(spec: &WingSpec, role: WingRole) -> Self {
+        Self::Wing {
+            spec: Box::new(spec.clone()),
+            role,
+        }
+    }
+}
+
+/// Which controls move a wing's control surfaces.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WingRole {
+    /// Ailerons for roll.
+    Main,
+    /// Elevators behind the wings for pitch.
+    Stabiliser,
+    /// Elevators in front of the wings for pitch, working the other way.
+    Canard,
+    /// Combined ailerons and elevators for roll and pitch.
+    Elevon,
+    /// Vertical surface with a rudder for yaw.
+    Fin,
+}
+
+impl WingRole {
+    pub const ALL: [Self; 5] = [
+        Self::Main,
+        Self::Stabiliser,
+        Self::Canard,
+        Self::Elevon,
+        Self::Fin,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Main => "Main",
+            Self::Stabiliser => "Stabiliser",
+            Self::Canard => "Canard",
+            Self::Elevon => "Elevon",
+            Self::Fin => "Fin",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct WingSpec {
     pub size: Vec3,
     pub lift_coefficient_curve: Vec<(f32, f32)>,
     pub drag_coefficient_curve: Vec<(f32, f32)>,
     pub angle: f32,
     pub max_control_angle: f32,
+    /// Fastest the control surfaces move, in radians per second.
+    #[serde(default = "default_max_control_rate")]
+    pub max_control_rate: f32,
+    /// How close the spanwise lift distribution is to elliptical, which scales the induced drag.
+    #[serde(default = "default_oswald_efficiency")]
+    pub oswald_efficiency: f32,
+    /// Upward tilt of the wing from its root to its tip.
+    #[serde(default)]
+    pub dihedral: f32,
+    /// Backward tilt of the wing from its root to its tip.
+    #[serde(default)]
+    pub sweep: f32,
+}
+
+fn default_never_exceed_speed() -> f32 {
+    70.0
+}
+
+fn default_max_control_rate() -> f32 {
+    20_f32.to_radians()
+}
+
+fn default_oswald_efficiency() -> f32 {
+    0.8
 }
 
 impl Default for WingSpec {
@@ -83,50 +641,210 @@ impl Default for WingSpec {
         Self {
             size: vec3(2., 0.2, 1.0),
             lift_coefficient_curve: vec![(-0.0, -90.0), (-0.15, -10.0), (0.15, 10.0), (0.0, 90.0)],
-            drag_coefficient_curve: vec![(0.032, -90.0), (0.032, 90.0)],
+            // Parasitic drag only, rising to that of a flat plate side on to the flow. Drag from
+            // lift is added separately from the aspect ratio.
+            drag_coefficient_curve: vec![
+                (1.28, -90.0),
+                (0.15, -20.0),
+                (0.03, -10.0),
+                (0.02, 0.0),
+                (0.03, 10.0),
+                (0.15, 20.0),
+                (1.28, 90.0),
+            ],
             angle: 0.,
             max_control_angle: 1_f32.to_radians(),
+            max_control_rate: default_max_control_rate(),
+            oswald_efficiency: default_oswald_efficiency(),
+            dihedral: 0.,
+            sweep: 0.,
         }
     }
 }
 
 impl WingSpec {
-    fn build_samples(curve: Vec<(f32, f32)>) -> Vec<f32> {
+    /// Samples a curve of coefficients at angles in degrees, evenly over its angles from first to
+    /// last. Fails when the curve has fewer than two points or its angles are out of order.
+    pub fn build_samples(curve: Vec<(f32, f32)>) -> Result<Vec<f32>, LinearError> {
         let elements: Vec<_> = curve.iter().map(|(l, _)| *l).collect();
         let knots: Vec<_> = curve.iter().map(|(_, a)| *a).collect();
 
-        info!("Building curve elements {:?} knots {:?}", elements, knots);
+        debug!("Building curve elements {:?} knots {:?}", elements, knots);
 
-        Linear::builder()
+        Ok(Linear::builder()
             .elements(elements)
             .knots(knots)
-            .build()
-            .unwrap()
+            .build()?
             .take(180)
-            .collect()
+            .collect())
     }
 
-    pub fn lift_coefficient_samples(&self) -> Vec<f32> {
+    pub fn lift_coefficient_samples(&self) -> Result<Vec<f32>, LinearError> {
         Self::build_samples(self.lift_coefficient_curve.clone())
     }
 
-    pub fn drag_coefficient_samples(&self) -> Vec<f32> {
+    pub fn drag_coefficient_samples(&self) -> Result<Vec<f32>, LinearError> {
         Self::build_samples(self.drag_coefficient_curve.clone())
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EngineSpec {
+    pub cylinders: u32,
+    pub idle_rpm: f32,
+    pub max_rpm: f32,
+    /// Fraction of full thrust the propellor gives with its pitch reversed, or zero for one that
+    /// can't reverse.
+    #[serde(default = "default_reverse_thrust")]
+    pub reverse_thrust: f32,
+}
+
+impl Default for EngineSpec {
+    fn default() -> Self {
+        Self {
+            cylinders: 4,
+            idle_rpm: 700.0,
+            max_rpm: 2700.0,
+            reverse_thrust: default_reverse_thrust(),
+        }
+    }
+}
+
+impl EngineSpec {
+    /// Engine speed at a fraction of full power, forwards or in reverse.
+    pub fn rpm(&self, power: f32) -> f32 {
+        self.idle_rpm + (self.max_rpm - self.idle_rpm) * power.abs().clamp(0.0, 1.0)
+    }
+}
+
+fn default_reverse_thrust() -> f32 {
+    0.4
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct FuselageSpec {
     pub size: Vec3,
     pub mass: f32,
     pub wheel_x_offset: f32,
     pub wheel_y_offset: f32,
     pub wheel_radius: f32,
+    #[serde(default)]
+    pub undercarriage: Undercarriage,
+    /// Where the wheels go, when the plane stands on wheels.
+    #[serde(default)]
+    pub gear: GearLayout,
+}
+
+impl FuselageSpec {
+    /// Height of the centre of the fuselage above the bottom of the undercarriage.
+    pub fn ground_clearance(&self) -> f32 {
+        match self.undercarriage {
+            Undercarriage::Wheels | Undercarriage::Floats => {
+                self.size.y * 0.5 + self.wheel_y_offset + self.wheel_radius
+            }
+            Undercarriage::Hull => self.size.y * 0.5,
+        }
+    }
+}
+
+/// What the plane sits on, on the ground or on water.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Undercarriage {
+    #[default]
+    Wheels,
+    /// Pair of floats for landing on water.
+    Floats,
+    /// Fuselage shaped as a boat hull, floating on the water itself.
+    Hull,
+}
+
+impl Undercarriage {
+    pub const ALL: [Self; 3] = [Self::Wheels, Self::Floats, Self::Hull];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Wheels => "Wheels",
+            Self::Floats => "Floats",
+            Self::Hull => "Hull",
+        }
+    }
+
+    /// Whether the plane can land on water.
+    pub fn is_seaplane(&self) -> bool {
+        *self != Self::Wheels
+    }
 }
 
-#[derive(Debug, Clone, Default)]
+/// Arrangement of the wheels under the plane.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GearLayout {
+    /// Main wheels behind the centre of gravity and a steered wheel under the nose.
+    #[default]
+    Tricycle,
+    /// Main wheels ahead of the centre of gravity and a castoring wheel under the tail.
+    Taildragger,
+    /// Steered pair of wheels under the nose and a pair behind the wings.
+    Quad,
+}
+
+impl GearLayout {
+    pub const ALL: [Self; 3] = [Self::Tricycle, Self::Taildragger, Self::Quad];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Tricycle => "Tricycle",
+            Self::Taildragger => "Taildragger",
+            Self::Quad => "Quad",
+        }
+    }
+}
+
+/// How a wheel turns to the direction it rolls in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Steering {
+    /// Always rolls straight ahead.
+    Fixed,
+    /// Turned with the rudder.
+    Rudder,
+    /// Swivels freely to roll the way it's moving, leaving the brakes and rudder to steer.
+    Castoring,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct TailSpec {
     pub size: Vec3,
     pub vertical: WingSpec,
     pub horizontal: WingSpec,
 }
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RotorSpec {
+    pub diameter: f32,
+    /// Height of the hub above the centre of the fuselage.
+    pub height: f32,
+    /// Thrust at full collective.
+    pub max_thrust: f32,
+    /// Tilt of the rotor disc at full cyclic.
+    pub max_cyclic: f32,
+    /// Yaw torque of the tail rotor at full pedal.
+    pub anti_torque: f32,
+}
+
+impl Default for RotorSpec {
+    fn default() -> Self {
+        Self {
+            diameter: 8.0,
+            height: 1.6,
+            max_thrust: 2500.0,
+            max_cyclic: 8_f32.to_radians(),
+            anti_torque: 300.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct BallastSpec {
+    pub mass: f32,
+    /// Distance behind the centre of the fuselage, negative towards the nose.
+    pub position: f32,
+}
@@ -0,0 +1,330 @@
+use std::fmt;
+
+use bevy::prelude::*;
+
+use super::{Layout, PartKind, PartSpec, PlaneSpec, Undercarriage, WingSpec};
+
+/// Problem with a spec that would build a broken plane, naming the field it's in.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SpecError {
+    /// Value that isn't a finite number, like a typo in the Build window.
+    NotANumber(String),
+    /// Size, mass or count that has to be above zero.
+    NotPositive(String),
+    /// Mass or angle that can be zero but not below it.
+    Negative(String),
+    /// Wing with no area to make lift from.
+    ZeroAreaWing(String),
+    /// Coefficient curve with fewer than two points, or with its angles out of order.
+    UnorderedCurve(String),
+    /// Custom parts graph that doesn't start from a fuselage section for the rest to hang from.
+    RootNotFuselage,
+}
+
+impl fmt::Display for SpecError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotANumber(field) => write!(f, "{} isn't a number", field),
+            Self::NotPositive(field) => write!(f, "{} must be more than zero", field),
+            Self::Negative(field) => write!(f, "{} can't be negative", field),
+            Self::ZeroAreaWing(wing) => write!(f, "{} has no area", wing),
+            Self::UnorderedCurve(curve) => {
+                write!(f, "{} needs at least two points in order of angle", curve)
+            }
+            Self::RootNotFuselage => write!(f, "custom parts must start from a fuselage section"),
+        }
+    }
+}
+
+impl PlaneSpec {
+    /// Checks the spec builds a plane that can fly, returning every problem found.
+    pub fn validate(&self) -> Result<(), Vec<SpecError>> {
+        let mut errors = Errors::default();
+
+        errors.positive("thrust", self.thrust);
+        errors.positive("never exceed speed", self.never_exceed_speed);
+        if self.engine.cylinders == 0 {
+            errors
+                .0
+                .push(SpecError::NotPositive("cylinders".to_string()));
+        }
+        errors.finite("idle rpm", self.engine.idle_rpm);
+        errors.finite("max rpm", self.engine.max_rpm);
+        errors.finite("reverse thrust", self.engine.reverse_thrust);
+
+        let fuselage = &self.fuselage;
+        errors.positive_vec3("fuselage", fuselage.size);
+        errors.positive("fuselage mass", fuselage.mass);
+        errors.finite("wheel x offset", fuselage.wheel_x_offset);
+        errors.finite("wheel y offset", fuselage.wheel_y_offset);
+        match fuselage.undercarriage {
+            Undercarriage::Wheels => errors.positive("wheel radius", fuselage.wheel_radius),
+            _ => errors.finite("wheel radius", fuselage.wheel_radius),
+        }
+
+        // Custom layouts are built from their own parts rather than the wings and tail
+        match &self.layout {
+            Layout::Custom(root) => {
+                if !matches!(root.kind, PartKind::Fuselage { .. }) {
+                    errors.0.push(SpecError::RootNotFuselage);
+                }
+                errors.part("part 1", root);
+            }
+            layout => {
+                errors.wing("wings", &self.wings);
+                errors.wing("tail vertical", &self.tail.vertical);
+                if *layout == Layout::Conventional {
+                    errors.positive_vec3("tail", self.tail.size);
+                    errors.wing("tail horizontal", &self.tail.horizontal);
+                }
+            }
+        }
+
+        errors.non_negative("ballast mass", self.ballast.mass);
+        errors.finite("ballast position", self.ballast.position);
+
+        for (i, row) in self.wing_rows.iter().enumerate() {
+            let name = format!("wing row {}", i + 1);
+            errors.finite(&format!("{} height", name), row.height);
+            errors.finite(&format!("{} position", name), row.position);
+            errors.wing(&name, &row.wing);
+        }
+
+        for (i, payload) in self.payloads.iter().enumerate() {
+            let name = format!("payload {}", i + 1);
+            errors.positive(&format!("{} mass", name), payload.mass);
+            errors.finite_vec3(&format!("{} position", name), payload.position);
+        }
+
+        if let Some(rotor) = &self.rotor {
+            errors.positive("rotor diameter", rotor.diameter);
+            errors.finite("rotor height", rotor.height);
+            errors.finite("rotor max thrust", rotor.max_thrust);
+            errors.finite("rotor max cyclic", rotor.max_cyclic);
+            errors.finite("rotor anti-torque", rotor.anti_torque);
+        }
+
+        match errors.0.is_empty() {
+            true => Ok(()),
+            false => Err(errors.0),
+        }
+    }
+}
+
+#[derive(Default)]
+struct Errors(Vec<SpecError>);
+
+impl Errors {
+    fn finite(&mut self, field: &str, value: f32) {
+        if !value.is_finite() {
+            self.0.push(SpecError::NotANumber(field.to_string()));
+        }
+    }
+
+    fn positive(&mut self, field: &str, value: f32) {
+        if !value.is_finite() {
+            self.0.push(SpecError::NotANumber(field.to_string()));
+        } else if value <= 0.0 {
+            self.0.push(SpecError::NotPositive(field.to_string()));
+        }
+    }
+
+    fn non_negative(&mut self, field: &str, value: f32) {
+        if !value.is_finite() {
+            self.0.push(SpecError::NotANumber(field.to_string()));
+        } else if value < 0.0 {
+            self.0.push(SpecError::Negative(field.to_string()));
+        }
+    }
+
+    fn finite_vec3(&mut self, field: &str, value: Vec3) {
+        if !value.is_finite() {
+            self.0.push(SpecError::NotANumber(field.to_string()));
+        }
+    }
+
+    fn positive_vec3(&mut self, field: &str, value: Vec3) {
+        if !value.is_finite() {
+            self.0.push(SpecError::NotANumber(field.to_string()));
+        } else if value.min_element() <= 0.0 {
+            self.0.push(SpecError::NotPositive(field.to_string()));
+        }
+    }
+
+    /// Checks a part of a custom graph and the parts attached to it, named by their path from the
+    /// root, such as "part 1.2".
+    fn part(&mut self, name: &str, part: &PartSpec) {
+        self.finite_vec3(&format!("{} offset", name), part.offset);
+        match &part.kind {
+            PartKind::Fuselage { size, mass } => {
+                self.positive_vec3(name, *size);
+                self.positive(&format!("{} mass", name), *mass);
+            }
+            PartKind::Wing { spec, .. } => self.wing(name, spec),
+            PartKind::Pylon { size } | PartKind::Float { size } => self.positive_vec3(name, *size),
+            PartKind::Engine { diameter } => {
+                self.positive(&format!("{} diameter", name), *diameter)
+            }
+            PartKind::Gear { radius, .. } => self.positive(&format!("{} radius", name), *radius),
+        }
+
+        for (i, attached) in part.attachments.iter().enumerate() {
+            self.part(&format!("{}.{}", name, i + 1), attached);
+        }
+    }
+
+    fn wing(&mut self, name: &str, spec: &WingSpec) {
+        if !spec.size.is_finite() {
+            self.0.push(SpecError::NotANumber(format!("{} size", name)));
+        } else if spec.size.x * spec.size.z <= 0.0 {
+            self.0.push(SpecError::ZeroAreaWing(name.to_string()));
+        } else if spec.size.y <= 0.0 {
+            // The height is the span of a fin, which the induced drag divides by
+            self.0
+                .push(SpecError::NotPositive(format!("{} height", name)));
+        }
+
+        for (curve, points) in [
+            ("lift", &spec.lift_coefficient_curve),
+            ("drag", &spec.drag_coefficient_curve),
+        ] {
+            let in_order = points.len() >= 2
+                && points.iter().all(|(c, a)| c.is_finite() && a.is_finite())
+                && points.windows(2).all(|pair| pair[0].1 < pair[1].1);
+            if !in_order {
+                self.0.push(SpecError::UnorderedCurve(format!(
+                    "{} {} curve",
+                    name, curve
+                )));
+            }
+        }
+
+        self.finite(&format!("{} angle", name), spec.angle);
+        self.non_negative(
+            &format!("{} max control angle", name),
+            spec.max_control_angle,
+        );
+        self.positive(&format!("{} max control rate", name), spec.max_control_rate);
+        self.positive(
+            &format!("{} oswald efficiency", name),
+            spec.oswald_efficiency,
+        );
+        self.finite(&format!("{} dihedral", name), spec.dihedral);
+        self.finite(&format!("{} sweep", name), spec.sweep);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    fn errors(spec: &PlaneSpec) -> Vec<SpecError> {
+        spec.validate().err().unwrap_or_default()
+    }
+
+    #[test]
+    fn default_spec_is_valid() {
+        assert_eq!(PlaneSpec::default().validate(), Ok(()));
+    }
+
+    #[test]
+    fn bundled_specs_are_valid() {
+        let dir = concat!(env!("CARGO_MANIFEST_DIR"), "/assets/planes");
+        for entry in fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            let spec: PlaneSpec = ron::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+            assert_eq!(spec.validate(), Ok(()), "{}", path.display());
+        }
+    }
+
+    #[test]
+    fn rejects_thrust_that_isnt_positive() {
+        for thrust in [0.0, -1.0] {
+            let spec = PlaneSpec {
+                thrust,
+                ..default()
+            };
+            assert_eq!(
+                errors(&spec),
+                vec![SpecError::NotPositive("thrust".to_string())]
+            );
+        }
+
+        let spec = PlaneSpec {
+            thrust: f32::NAN,
+            ..default()
+        };
+        assert_eq!(
+            errors(&spec),
+            vec![SpecError::NotANumber("thrust".to_string())]
+        );
+    }
+
+    #[test]
+    fn rejects_control_rate_that_isnt_positive() {
+        let mut spec = PlaneSpec::default();
+        spec.wings.max_control_rate = 0.0;
+        assert_eq!(
+            errors(&spec),
+            vec![SpecError::NotPositive("wings max control rate".to_string())]
+        );
+    }
+
+    #[test]
+    fn rejects_negative_ballast() {
+        let mut spec = PlaneSpec::default();
+        spec.ballast.mass = -1.0;
+        assert_eq!(
+            errors(&spec),
+            vec![SpecError::Negative("ballast mass".to_string())]
+        );
+
+        spec.ballast.mass = 0.0;
+        assert_eq!(spec.validate(), Ok(()));
+    }
+
+    #[test]
+    fn rejects_negative_max_control_angle() {
+        let mut spec = PlaneSpec::default();
+        spec.tail.vertical.max_control_angle = -0.1;
+        assert_eq!(
+            errors(&spec),
+            vec![SpecError::Negative(
+                "tail vertical max control angle".to_string()
+            )]
+        );
+
+        spec.tail.vertical.max_control_angle = 0.0;
+        assert_eq!(spec.validate(), Ok(()));
+    }
+
+    #[test]
+    fn rejects_curves_that_cant_be_sampled() {
+        let mut spec = PlaneSpec::default();
+        spec.wings.lift_coefficient_curve.truncate(1);
+        spec.wings.drag_coefficient_curve.reverse();
+        assert_eq!(
+            errors(&spec),
+            vec![
+                SpecError::UnorderedCurve("wings lift curve".to_string()),
+                SpecError::UnorderedCurve("wings drag curve".to_string()),
+            ]
+        );
+        assert!(WingSpec::build_samples(spec.wings.lift_coefficient_curve.clone()).is_err());
+        assert!(WingSpec::build_samples(spec.wings.drag_coefficient_curve.clone()).is_err());
+    }
+
+    #[test]
+    fn rejects_custom_graph_not_rooted_at_fuselage() {
+        let spec = PlaneSpec {
+            layout: Layout::Custom(PartSpec::new(
+                PartKind::Engine { diameter: 1.0 },
+                Vec3::ZERO,
+            )),
+            ..default()
+        };
+        assert_eq!(errors(&spec), vec![SpecError::RootNotFuselage]);
+    }
+}
@@ -0,0 +1,218 @@
+use bevy::{ecs::schedule::ExecutorKind, prelude::*, scene::SceneSpawner};
+use bevy_rapier3d::prelude::*;
+
+use crate::{
+    flight_model::{Airfoil, AngleOfAttack, FlightModelPlugin, FlightModelSet, Thrust},
+    physics::PhysicsPlugin,
+    world::Airports,
+};
+
+use super::{
+    actuate_control_surfaces, assist, attitude, build, rotor, spec::PlaneSpec,
+    update_airfoil_control_surfaces, update_fuselage_forces, AirfoilPosition, PlaneControl,
+    PlaneSet,
+};
+
+/// Height test flights start at, well clear of anything they could fly into.
+const TEST_ALTITUDE: f32 = 2000.0;
+/// Distance between the copies of a plane flown side by side, so they never touch.
+const COPY_SPACING: f32 = 1000.0;
+
+/// Copies of a plane built from a spec in an `App` of their own, flown by the game's flight model
+/// without rendering, input or the world, so a design can be test flown without touching the game.
+///
+/// Each physics step runs the same `FixedUpdate` systems as the game for the controls, the flight
+/// model and Rapier, but in calm air and without the assists or fly by wire, so it's the airframe
+/// that's measured. The copies are flown side by side, each held at a state of its own or left to
+/// fly, so a batch of flights takes no longer than the longest of them.
+pub struct TestFlight {
+    app: App,
+    planes: Vec<Entity>,
+}
+
+impl TestFlight {
+    pub fn new(spec: &PlaneSpec, copies: usize) -> Self {
+        let mut app = App::new();
+        app.add_plugins((
+            MinimalPlugins,
+            AssetPlugin::default(),
+            TransformPlugin,
+            HierarchyPlugin,
+        ))
+        .add_asset::<Mesh>()
+        .add_asset::<StandardMaterial>()
+        .init_resource::<SceneSpawner>()
+        .add_plugins((
+            RapierPhysicsPlugin::<NoUserData>::default().with_default_system_setup(false),
+            PhysicsPlugin,
+            FlightModelPlugin,
+        ))
+        .insert_resource(Airports::default())
+        .insert_resource(assist::AssistLevel::Realistic)
+        .configure_sets(
+            FixedUpdate,
+            (PlaneSet::Controls, PlaneSet::Forces)
+                .chain()
+                .before(PhysicsSet::SyncBackend),
+        )
+        .configure_sets(
+            FixedUpdate,
+            (FlightModelSet::Airflow, FlightModelSet::Forces).in_set(PlaneSet::Forces),
+        )
+        .add_systems(Update, build::build_plane)
+        .add_systems(
+            FixedUpdate,
+            (
+                assist::apply_assist,
+                actuate_control_surfaces,
+                update_airfoil_control_surfaces,
+            )
+                .chain()
+                .in_set(PlaneSet::Controls),
+        )
+        .add_systems(
+            FixedUpdate,
+            (update_fuselage_forces, rotor::update_rotor_forces)
+                .chain()
+                .after(FlightModelSet::Forces)
+                .in_set(PlaneSet::Forces),
+        );
+
+        let single_threaded = |schedule: &mut Schedule| {
+            schedule.set_executor_kind(ExecutorKind::SingleThreaded);
+        };
+        app.edit_schedule(Update, single_threaded)
+            .edit_schedule(PostUpdate, single_threaded)
+            .edit_schedule(FixedUpdate, single_threaded);
+
+        // The fly by wire control law would fight the test inputs
+        let spec = PlaneSpec {
+            fly_by_wire: false,
+            ..spec.clone()
+        };
+        let planes = (0..copies)
+            .map(|_| app.world.spawn(spec.clone()).id())
+            .collect();
+        app.world.run_schedule(Update);
+
+        let mut flight = Self { app, planes };
+        // Rapier works out the mass of each copy as its colliders are added in the first step
+        for copy in 0..copies {
+            flight.hold(copy, 0.0, 0.0);
+        }
+        flight.step();
+        flight
+    }
+
+    /// Puts a copy in level flight at a speed in m/s, heading north with its nose raised by an
+    /// angle of attack in radians, with nothing turning.
+    pub fn hold(&mut self, copy: usize, speed: f32, angle_of_attack: f32) {
+        let mut plane = self.app.world.entity_mut(self.planes[copy]);
+        *plane.get_mut::<Transform>().unwrap() =
+            Transform::from_xyz(copy as f32 * COPY_SPACING, TEST_ALTITUDE, 0.0)
+                .with_rotation(Quat::from_rotation_x(angle_of_attack));
+        *plane.get_mut::<Velocity>().unwrap() = Velocity::linear(Vec3::NEG_Z * speed);
+    }
+
+    /// Keeps a copy from rolling, yawing or sliding sideways, so a flight in the pitch plane isn't
+    /// spoiled by a slow spiral that a pilot would have caught long before.
+    pub fn lock_to_pitch(&mut self, copy: usize, locked: bool) {
+        let axes = if locked {
+            LockedAxes::ROTATION_LOCKED_Y
+                | LockedAxes::ROTATION_LOCKED_Z
+                | LockedAxes::TRANSLATION_LOCKED_X
+        } else {
+            LockedAxes::empty()
+        };
+        self.app.world.entity_mut(self.planes[copy]).insert(axes);
+    }
+
+    pub fn set_thrust(&mut self, copy: usize, thrust: f32) {
+        self.app
+            .world
+            .get_mut::<Thrust>(self.planes[copy])
+            .unwrap()
+            .0 = thrust;
+    }
+
+    pub fn control_mut(&mut self, copy: usize) -> Mut<'_, PlaneControl> {
+        self.app
+            .world
+            .get_mut::<PlaneControl>(self.planes[copy])
+            .unwrap()
+    }
+
+    /// Advances every copy by a physics step.
+    pub fn step(&mut self) {
+        // The flight model reads the global transforms, which Rapier only moves the local ones of
+        self.app.world.run_schedule(PostUpdate);
+        self.app.world.run_schedule(FixedUpdate);
+    }
+
+    pub fn mass(&self) -> f32 {
+        self.mass_properties().mass
+    }
+
+    /// Centre of gravity in the plane's frame, behind the centre of the fuselage for a larger z.
+    pub fn centre_of_gravity(&self) -> Vec3 {
+        self.mass_properties().local_center_of_mass
+    }
+
+    fn mass_properties(&self) -> &MassProperties {
+        &self
+            .app
+            .world
+            .get::<ReadMassProperties>(self.planes[0])
+            .unwrap()
+            .0
+    }
+
+    /// Force and moment about the centre of gravity the flight model put on a copy in the last
+    /// step, without gravity.
+    pub fn forces(&self, copy: usize) -> ExternalForce {
+        *self
+            .app
+            .world
+            .get::<ExternalForce>(self.planes[copy])
+            .unwrap()
+    }
+
+    pub fn velocity(&self, copy: usize) -> Velocity {
+        *self.app.world.get::<Velocity>(self.planes[copy]).unwrap()
+    }
+
+    /// Rate the nose is pitching up at in rad/s.
+    pub fn pitch_rate(&self, copy: usize) -> f32 {
+        let global_tx = self.global_transform(copy);
+        self.velocity(copy).angvel.dot(global_tx.right())
+    }
+
+    /// Bank in radians, positive to the right.
+    pub fn bank(&self, copy: usize) -> f32 {
+        attitude(self.global_transform(copy)).1
+    }
+
+    fn global_transform(&self, copy: usize) -> &GlobalTransform {
+        self.app
+            .world
+            .get::<GlobalTransform>(self.planes[copy])
+            .unwrap()
+    }
+
+    /// Whether either main wing is past its largest lift in the last step.
+    pub fn wings_stalled(&mut self, copy: usize) -> bool {
+        let plane = self.planes[copy];
+        self.app
+            .world
+            .query::<(&Airfoil, &AirfoilPosition, &AngleOfAttack, &Parent)>()
+            .iter(&self.app.world)
+            .any(|(airfoil, position, aoa, parent)| {
+                parent.get() == plane
+                    && matches!(
+                        position,
+                        AirfoilPosition::Wing(_) | AirfoilPosition::Elevon(_)
+                    )
+                    && airfoil.is_stalled(aoa.0)
+            })
+    }
+}
@@ -0,0 +1,633 @@
+use std::f32::consts::{PI, TAU};
+
+use bevy::{math::vec3, prelude::*};
+use bevy_rapier3d::prelude::*;
+use leafwing_input_manager::prelude::*;
+
+use crate::{flight_model::air_density, input::PlaneAction, physics::PHYSICS_DT};
+
+use super::{lod::tail_point, spec::PlaneSpec, Plane};
+
+/// Number of links in the tow rope, each a body free to swing on the one before.
+const ROPE_SEGMENTS: usize = 8;
+const ROPE_SEGMENT_LENGTH: f32 = 2.5;
+const ROPE_SEGMENT_MASS: f32 = 1.0;
+const ROPE_DRAG_AREA: f32 = 0.005;
+const ROPE_COLOR: Color = Color::rgb(0.9, 0.85, 0.6);
+const ROPE_LENGTH: f32 = ROPE_SEGMENT_LENGTH * ROPE_SEGMENTS as f32;
+
+const GRAVITY: f32 = 9.81;
+const TUG_FUSELAGE: Vec3 = vec3(1.0, 1.2, 7.0);
+const TUG_WING: Vec3 = vec3(11.0, 0.15, 1.6);
+const TUG_TAILPLANE: Vec3 = vec3(3.4, 0.1, 0.8);
+const TUG_FIN: Vec3 = vec3(0.1, 1.2, 0.9);
+/// Height of the bottom of the tug's fuselage above its wheels' contact with the ground.
+const TUG_GEAR_HEIGHT: f32 = 0.6;
+const TUG_COLOR: Color = Color::rgb(0.85, 0.2, 0.15);
+/// Distance off to the right of where it lines up that the tug appears, to taxi in from.
+const TUG_SPAWN_OFFSET: f32 = 40.0;
+const TUG_TAXI_SPEED: f32 = 5.0;
+/// Fastest the tug turns on the ground, in radians per second.
+const TUG_TAXI_TURN_RATE: f32 = 0.6;
+/// Distance from the line up point the tug stops taxiing at.
+const TUG_LINEUP_TOLERANCE: f32 = 0.5;
+const TUG_ACCELERATION: f32 = 2.0;
+/// Speed the tug lifts off at, and climbs once past.
+const TUG_ROTATE_SPEED: f32 = 26.0;
+/// Speed the glider is towed at, comfortably over its stall.
+const TOW_SPEED: f32 = 32.0;
+const TOW_CLIMB_RATE: f32 = 2.5;
+/// How quickly the tug's climb rate changes, in m/s per second.
+const TUG_CLIMB_ACCELERATION: f32 = 0.5;
+/// Height climbed on the runway heading before the tow starts circling to the left.
+const TOW_PATTERN_HEIGHT: f32 = 150.0;
+/// Rate of the gentle left turn the tow circles at, in radians per second.
+const TOW_TURN_RATE: f32 = 0.035;
+/// Height climbed at which the tug levels off and circles until the glider lets go.
+const TOW_RELEASE_HEIGHT: f32 = 600.0;
+/// Rate of the tug's turn to the right away from a released glider, in radians per second.
+const TUG_DEPART_TURN_RATE: f32 = 0.15;
+/// Time the tug flies on for after the rope is let go before it's removed.
+const TUG_DEPART_TIME: f32 = 60.0;
+
+/// Things that can be towed behind the plane.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TowKind {
+    Banner,
+    Glider,
+}
+
+impl TowKind {
+    pub const ALL: [Self; 2] = [Self::Banner, Self::Glider];
+}
+
+/// Sent to attach something to the selected plane on a tow rope, replacing anything towed already,
+/// or with `None` to let go of the rope.
+#[derive(Event)]
+pub struct TowEvent(pub Option<TowKind>);
+
+/// Sent to call a tow plane to launch the selected plane as a glider, replacing anything towed
+/// already. It's let go of with a `TowEvent` of `None` like any other tow.
+#[derive(Event)]
+pub struct AerotowEvent;
+
+/// Rope and towed body behind a plane, or the tow plane pulling it, kept so they can be let go
+/// together.
+#[derive(Resource, Default)]
+pub struct Tow {
+    plane: Option<Entity>,
+    entities: Vec<Entity>,
+    /// Tow plane hooked onto the rope, or on its way to hook on.
+    tug: Option<Entity>,
+}
+
+impl Tow {
+    pub fn is_towing(&self) -> bool {
+        self.plane.is_some()
+    }
+}
+
+/// Air forces on something towed, which has no airfoils of its own.
+#[derive(Component)]
+pub struct TowedBody {
+    /// Drag coefficient times the area it acts on.
+    drag_area: f32,
+    /// Area of the wings, or zero for bodies without lift.
+    wing_area: f32,
+    aspect_ratio: f32,
+    /// Torque for each radian it's turned away from the airflow or banked, per unit of dynamic
+    /// pressure, standing in for the tail surfaces keeping it straight and level.
+    stability: f32,
+}
+
+/// Tow plane flown along a fixed pattern rather than by the flight model. It's moved kinematically,
+/// so the rope pulls the glider without the glider's weight pulling back on it.
+#[derive(Component)]
+pub struct Tug {
+    phase: TugPhase,
+    /// Plane being launched.
+    glider: Entity,
+    /// Direction the tug is pointing in, turned left from north about the Y axis.
+    heading: f32,
+    speed: f32,
+    climb_rate: f32,
+    /// Rate of turn to the left.
+    turn_rate: f32,
+    /// Height climbed since lifting off.
+    climbed: f32,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum TugPhase {
+    /// Taxiing to line up ahead of the glider and hook on the rope.
+    Taxi,
+    /// Taking off and climbing with the glider, first straight out and then circling.
+    Tow,
+    /// Flying away from the glider after it let go, for the time so far.
+    Departing(f32),
+}
+
+/// Lets go of the tow rope when the release key is pressed.
+pub fn release_tow_on_key(
+    action_query: Query<&ActionState<PlaneAction>>,
+    tow: Res<Tow>,
+    mut tow_events: EventWriter<TowEvent>,
+) {
+    let Ok(action_state) = action_query.get_single() else {
+        return;
+    };
+    if tow.is_towing() && action_state.just_pressed(PlaneAction::ReleaseTow) {
+        tow_events.send(TowEvent(None));
+    }
+}
+
+pub fn attach_tow(
+    mut commands: Commands,
+    mut tow_events: EventReader<TowEvent>,
+    mut aerotow_events: EventReader<AerotowEvent>,
+    mut tow: ResMut<Tow>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    plane_query: Query<(Entity, &PlaneSpec, &GlobalTransform, &Velocity), With<Plane>>,
+) {
+    // A rebuilt plane takes the end of the rope with it
+    if tow.plane.is_some_and(|plane| !plane_query.contains(plane)) {
+        release_tow(&mut commands, &mut tow);
+    }
+
+    for TowEvent(kind) in tow_events.iter() {
+        release_tow(&mut commands, &mut tow);
+        let Some(kind) = kind else {
+            continue;
+        };
+        let Ok((plane, spec, plane_tx, velocity)) = plane_query.get_single() else {
+            continue;
+        };
+
+        info!("Towing {:?}", kind);
+        let rotation = plane_tx.compute_transform().rotation;
+        let hook = tail_point(spec);
+        let hook_pos = plane_tx.transform_point(hook);
+        let backward = plane_tx.back();
+        let moving = Velocity::linear(velocity.linvel);
+
+        let previous = spawn_rope(
+            &mut commands,
+            &mut tow,
+            &mut meshes,
+            &mut materials,
+            (plane, hook),
+            hook_pos,
+            rotation,
+            moving,
+        );
+
+        let length = match kind {
+            TowKind::Banner => 6.0,
+            TowKind::Glider => 5.0,
+        };
+        let pos = hook_pos + backward * (ROPE_SEGMENT_LENGTH * ROPE_SEGMENTS as f32 + length * 0.5);
+        let joint = SphericalJointBuilder::new()
+            .local_anchor1(previous.1)
+            .local_anchor2(vec3(0., 0., -length * 0.5));
+
+        let mut towed = commands.spawn((
+            SpatialBundle::from_transform(Transform::from_translation(pos).with_rotation(rotation)),
+            RigidBody::Dynamic,
+            ImpulseJoint::new(previous.0, joint),
+            moving,
+            ExternalForce::default(),
+            Damping {
+                linear_damping: 0.0,
+                angular_damping: 2.0,
+            },
+        ));
+        match kind {
+            TowKind::Banner => {
+                let size = vec3(0.02, 1.5, length);
+                towed.insert((
+                    Collider::cuboid(size.x * 0.5, size.y * 0.5, size.z * 0.5),
+                    ColliderMassProperties::Mass(5.0),
+                    TowedBody {
+                        drag_area: 0.2,
+                        wing_area: 0.0,
+                        aspect_ratio: 0.0,
+                        stability: 1.0,
+                    },
+                ));
+                towed.with_children(|parent| {
+                    parent.spawn(PbrBundle {
+                        mesh: meshes.add(Mesh::from(shape::Box::new(size.x, size.y, size.z))),
+                        material: materials.add(Color::rgb(1.0, 0.9, 0.1).into()),
+                        ..default()
+                    });
+                });
+            }
+            TowKind::Glider => {
+                let fuselage = vec3(0.6, 0.8, length);
+                let wing = vec3(12.0, 0.1, 0.5);
+                let wing_pos = vec3(0., 0.3, -0.5);
+                towed.insert((
+                    Collider::compound(vec![
+                        (
+                            Vec3::ZERO,
+                            Quat::IDENTITY,
+                            Collider::cuboid(fuselage.x * 0.5, fuselage.y * 0.5, fuselage.z * 0.5),
+                        ),
+                        (
+                            wing_pos,
+                            Quat::IDENTITY,
+                            Collider::cuboid(wing.x * 0.5, wing.y * 0.5, wing.z * 0.5),
+                        ),
+                    ]),
+                    ColliderMassProperties::Mass(60.0),
+                    TowedBody {
+                        drag_area: 0.08,
+                        wing_area: wing.x * wing.z,
+                        aspect_ratio: wing.x / wing.z,
+                        stability: 4.0,
+                    },
+                ));
+                towed.with_children(|parent| {
+                    let material = materials.add(Color::WHITE.into());
+                    parent.spawn(PbrBundle {
+                        mesh: meshes.add(Mesh::from(shape::Box::new(
+                            fuselage.x, fuselage.y, fuselage.z,
+                        ))),
+                        material: material.clone(),
+                        ..default()
+                    });
+                    parent.spawn(PbrBundle {
+                        mesh: meshes.add(Mesh::from(shape::Box::new(wing.x, wing.y, wing.z))),
+                        material,
+                        transform: Transform::from_translation(wing_pos),
+                        ..default()
+                    });
+                });
+            }
+        }
+
+        tow.entities.push(towed.id());
+        tow.plane = Some(plane);
+    }
+
+    for _ in aerotow_events.iter() {
+        release_tow(&mut commands, &mut tow);
+        let Ok((plane, spec, plane_tx, _)) = plane_query.get_single() else {
+            continue;
+        };
+
+        // Standing on the same ground as the glider, off to the side of where it lines up
+        let ground = plane_tx.translation().y
+            - (spec.fuselage.size.y * 0.5
+                + spec.fuselage.wheel_y_offset
+                + spec.fuselage.wheel_radius);
+        let tug_y = ground + TUG_GEAR_HEIGHT + TUG_FUSELAGE.y * 0.5;
+        let lineup = lineup_point(spec, plane_tx, tug_y);
+        let pos = lineup + plane_tx.right() * TUG_SPAWN_OFFSET;
+
+        info!("Calling tow plane");
+        let tug = spawn_tug(
+            &mut commands,
+            &mut meshes,
+            &mut materials,
+            Tug {
+                phase: TugPhase::Taxi,
+                glider: plane,
+                heading: heading_of(lineup - pos),
+                speed: 0.0,
+                climb_rate: 0.0,
+                turn_rate: 0.0,
+                climbed: 0.0,
+            },
+            pos,
+        );
+        tow.tug = Some(tug);
+        tow.plane = Some(plane);
+    }
+}
+
+/// Chain of rope links hanging from an anchor on a body, laid out straight along the rotation's Z
+/// axis from the anchor's position. Returns the last link with the anchor at its free end.
+#[allow(clippy::too_many_arguments)]
+fn spawn_rope(
+    commands: &mut Commands,
+    tow: &mut Tow,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<StandardMaterial>,
+    anchor: (Entity, Vec3),
+    anchor_pos: Vec3,
+    rotation: Quat,
+    velocity: Velocity,
+) -> (Entity, Vec3) {
+    let direction = rotation * Vec3::Z;
+    let half_segment = ROPE_SEGMENT_LENGTH * 0.5;
+    let rope_mesh = meshes.add(Mesh::from(shape::Box::new(0.05, 0.05, ROPE_SEGMENT_LENGTH)));
+    let rope_material = materials.add(ROPE_COLOR.into());
+
+    // Each link hangs from the back of the one before, or from the anchor for the first
+    let mut previous = anchor;
+    for i in 0..ROPE_SEGMENTS {
+        let pos = anchor_pos + direction * ROPE_SEGMENT_LENGTH * (i as f32 + 0.5);
+        let joint = SphericalJointBuilder::new()
+            .local_anchor1(previous.1)
+            .local_anchor2(vec3(0., 0., -half_segment));
+
+        let segment = commands
+            .spawn((
+                PbrBundle {
+                    mesh: rope_mesh.clone(),
+                    material: rope_material.clone(),
+                    transform: Transform::from_translation(pos).with_rotation(rotation),
+                    ..default()
+                },
+                RigidBody::Dynamic,
+                // The rope has no collider, so can't snag on the plane, and is given its mass
+                // directly instead
+                AdditionalMassProperties::MassProperties(MassProperties {
+                    mass: ROPE_SEGMENT_MASS,
+                    principal_inertia: Vec3::splat(
+                        ROPE_SEGMENT_MASS * ROPE_SEGMENT_LENGTH * ROPE_SEGMENT_LENGTH / 12.0,
+                    ),
+                    ..default()
+                }),
+                ImpulseJoint::new(previous.0, joint),
+                velocity,
+                ExternalForce::default(),
+                TowedBody {
+                    drag_area: ROPE_DRAG_AREA,
+                    wing_area: 0.0,
+                    aspect_ratio: 0.0,
+                    stability: 0.0,
+                },
+            ))
+            .id();
+        tow.entities.push(segment);
+        previous = (segment, vec3(0., 0., half_segment));
+    }
+
+    previous
+}
+
+/// Tow plane outlined in boxes, with no collider so a glider overrunning it on the rope can't crash
+/// into it.
+fn spawn_tug(
+    commands: &mut Commands,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<StandardMaterial>,
+    tug: Tug,
+    pos: Vec3,
+) -> Entity {
+    let rotation = Quat::from_rotation_y(tug.heading);
+    let material = materials.add(TUG_COLOR.into());
+    let tail = TUG_FUSELAGE.z * 0.5 - TUG_TAILPLANE.z * 0.5;
+    let wheel_y = -(TUG_FUSELAGE.y + TUG_GEAR_HEIGHT) * 0.5;
+    let parts = [
+        (TUG_FUSELAGE, Vec3::ZERO),
+        (TUG_WING, vec3(0., TUG_FUSELAGE.y * 0.3, -1.0)),
+        (TUG_TAILPLANE, vec3(0., 0., tail)),
+        (TUG_FIN, vec3(0., (TUG_FUSELAGE.y + TUG_FIN.y) * 0.5, tail)),
+        (
+            Vec3::splat(TUG_GEAR_HEIGHT),
+            vec3(TUG_FUSELAGE.x * 0.8, wheel_y, -1.0),
+        ),
+        (
+            Vec3::splat(TUG_GEAR_HEIGHT),
+            vec3(-TUG_FUSELAGE.x * 0.8, wheel_y, -1.0),
+        ),
+    ];
+
+    commands
+        .spawn((
+            SpatialBundle::from_transform(Transform::from_translation(pos).with_rotation(rotation)),
+            RigidBody::KinematicPositionBased,
+            tug,
+        ))
+        .with_children(|parent| {
+            for (size, offset) in parts {
+                parent.spawn(PbrBundle {
+                    mesh: meshes.add(Mesh::from(shape::Box::new(size.x, size.y, size.z))),
+                    material: material.clone(),
+                    transform: Transform::from_translation(offset),
+                    ..default()
+                });
+            }
+        })
+        .id()
+}
+
+/// Point under the nose the rope hooks onto when the plane is launched as a glider, clear of the
+/// propellor.
+fn nose_hook(spec: &PlaneSpec) -> Vec3 {
+    vec3(
+        0.,
+        -spec.fuselage.size.y * 0.25,
+        -spec.fuselage.size.z * 0.5,
+    )
+}
+
+/// Where the tug stands to hook on, straight ahead of the glider with the rope pulled tight from
+/// its nose to the tug's tail.
+fn lineup_point(spec: &PlaneSpec, plane_tx: &GlobalTransform, tug_y: f32) -> Vec3 {
+    let hook_pos = plane_tx.transform_point(nose_hook(spec));
+    let forward = (plane_tx.forward() * vec3(1., 0., 1.)).normalize_or_zero();
+    let rise = tug_y - hook_pos.y;
+    let reach = (ROPE_LENGTH * ROPE_LENGTH - rise * rise).max(0.0).sqrt();
+
+    vec3(hook_pos.x, tug_y, hook_pos.z) + forward * (reach + TUG_FUSELAGE.z * 0.5)
+}
+
+/// Heading about the Y axis of a direction, zero to the north and increasing to the left.
+fn heading_of(direction: Vec3) -> f32 {
+    (-direction.x).atan2(-direction.z)
+}
+
+/// Steps a tug along its phase: taxiing in and hooking on, towing the glider up and circling
+/// until the glider lets go, and then flying away until it's removed.
+pub fn fly_tug(
+    mut commands: Commands,
+    mut tow: ResMut<Tow>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut tug_query: Query<(Entity, &mut Tug, &mut Transform)>,
+    plane_query: Query<(&PlaneSpec, &GlobalTransform), With<Plane>>,
+) {
+    for (entity, mut tug, mut tx) in tug_query.iter_mut() {
+        // Letting go of the rope, or calling another tug, sends this one on its way
+        if tow.tug != Some(entity) && !matches!(tug.phase, TugPhase::Departing(_)) {
+            tug.phase = TugPhase::Departing(0.0);
+        }
+
+        match tug.phase {
+            TugPhase::Taxi => {
+                // A glider gone before the rope is hooked on sends the tug away as if it let go
+                let Ok((spec, plane_tx)) = plane_query.get(tug.glider) else {
+                    release_tow(&mut commands, &mut tow);
+                    tug.phase = TugPhase::Departing(0.0);
+                    continue;
+                };
+                let lineup = lineup_point(spec, plane_tx, tx.translation.y);
+                let to_lineup = lineup - tx.translation;
+                if to_lineup.length() > TUG_LINEUP_TOLERANCE {
+                    tug.speed = TUG_TAXI_SPEED.min(to_lineup.length() / PHYSICS_DT);
+                    let target = heading_of(to_lineup);
+                    tug.heading = turn_towards(tug.heading, target, TUG_TAXI_TURN_RATE);
+                } else {
+                    // Swings round on the spot to point the way the glider does, then hooks on
+                    tug.speed = 0.0;
+                    tx.translation = lineup;
+                    let target = heading_of(plane_tx.forward());
+                    tug.heading = turn_towards(tug.heading, target, TUG_TAXI_TURN_RATE);
+                    if tug.heading == target {
+                        let hook_pos = plane_tx.transform_point(nose_hook(spec));
+                        let tug_hook = vec3(0., 0., TUG_FUSELAGE.z * 0.5);
+                        let tug_hook_pos = lineup + Quat::from_rotation_y(target) * tug_hook;
+                        let rotation = Quat::from_rotation_arc(
+                            Vec3::Z,
+                            (tug_hook_pos - hook_pos).normalize_or_zero(),
+                        );
+                        let (segment, anchor) = spawn_rope(
+                            &mut commands,
+                            &mut tow,
+                            &mut meshes,
+                            &mut materials,
+                            (tug.glider, nose_hook(spec)),
+                            hook_pos,
+                            rotation,
+                            Velocity::zero(),
+                        );
+                        let joint = SphericalJointBuilder::new()
+                            .local_anchor1(anchor)
+                            .local_anchor2(tug_hook);
+                        commands
+                            .entity(entity)
+                            .insert(ImpulseJoint::new(segment, joint));
+                        tug.phase = TugPhase::Tow;
+                    }
+                }
+            }
+            TugPhase::Tow => {
+                // The rope goes with a glider that's rebuilt, reset or gone mid-tow
+                if !plane_query.contains(tug.glider) {
+                    release_tow(&mut commands, &mut tow);
+                    tug.phase = TugPhase::Departing(0.0);
+                    continue;
+                }
+                accelerate(&mut tug);
+                let climb_rate = if tug.climbed < TOW_RELEASE_HEIGHT {
+                    TOW_CLIMB_RATE
+                } else {
+                    0.0
+                };
+                climb(&mut tug, climb_rate);
+                tug.turn_rate = if tug.climbed > TOW_PATTERN_HEIGHT {
+                    TOW_TURN_RATE
+                } else {
+                    0.0
+                };
+            }
+            TugPhase::Departing(time) => {
+                if time > TUG_DEPART_TIME {
+                    commands.entity(entity).despawn_recursive();
+                    continue;
+                }
+                tug.phase = TugPhase::Departing(time + PHYSICS_DT);
+                accelerate(&mut tug);
+                climb(&mut tug, TOW_CLIMB_RATE);
+                tug.turn_rate = if tug.climbed > 0.0 {
+                    -TUG_DEPART_TURN_RATE
+                } else {
+                    0.0
+                };
+            }
+        }
+
+        tug.heading = (tug.heading + tug.turn_rate * PHYSICS_DT).rem_euclid(TAU);
+        tug.climbed += tug.climb_rate * PHYSICS_DT;
+        let heading = Quat::from_rotation_y(tug.heading);
+        tx.translation +=
+            heading * Vec3::NEG_Z * tug.speed * PHYSICS_DT + Vec3::Y * tug.climb_rate * PHYSICS_DT;
+
+        // Nose up along the climb and banked into the turn, as a plane flying it would be
+        let pitch = tug.climb_rate.atan2(tug.speed.max(1.0));
+        let bank = (tug.turn_rate * tug.speed / GRAVITY).atan();
+        tx.rotation = heading * Quat::from_rotation_x(pitch) * Quat::from_rotation_z(bank);
+    }
+}
+
+fn accelerate(tug: &mut Tug) {
+    tug.speed = (tug.speed + TUG_ACCELERATION * PHYSICS_DT).min(TOW_SPEED);
+}
+
+/// Eases the tug's climb rate towards a target, staying on the ground below the lift off speed.
+fn climb(tug: &mut Tug, target: f32) {
+    let target = if tug.speed >= TUG_ROTATE_SPEED {
+        target
+    } else {
+        0.0
+    };
+    let step = TUG_CLIMB_ACCELERATION * PHYSICS_DT;
+    tug.climb_rate += (target - tug.climb_rate).clamp(-step, step);
+}
+
+/// Heading turned towards a target by at most the turn rate over a physics step, taking the
+/// shorter way round.
+fn turn_towards(heading: f32, target: f32, turn_rate: f32) -> f32 {
+    let step = turn_rate * PHYSICS_DT;
+    let difference = (target - heading + PI).rem_euclid(TAU) - PI;
+    if difference.abs() <= step {
+        target
+    } else {
+        heading + step * difference.signum()
+    }
+}
+
+fn release_tow(commands: &mut Commands, tow: &mut Tow) {
+    for entity in tow.entities.drain(..) {
+        commands.entity(entity).despawn_recursive();
+    }
+    if let Some(tug) = tow.tug.take() {
+        commands.entity(tug).remove::<ImpulseJoint>();
+    }
+    tow.plane = None;
+}
+
+/// Drag on the rope and towed body, and lift on a towed glider, with a turning force keeping it
+/// pointing into the airflow with its wings level.
+pub fn update_tow_forces(
+    mut towed_query: Query<(&TowedBody, &GlobalTransform, &Velocity, &mut ExternalForce)>,
+) {
+    for (body, global_tx, velocity, mut external_force) in towed_query.iter_mut() {
+        let airflow = velocity.linvel;
+        let speed = airflow.length();
+        *external_force = ExternalForce::default();
+        if speed < 0.1 {
+            continue;
+        }
+
+        let dynamic_pressure = 0.5 * air_density(global_tx.translation().y) * speed * speed;
+        let direction = airflow / speed;
+        let mut drag_area = body.drag_area;
+
+        if body.wing_area > 0.0 {
+            let local = global_tx.affine().inverse().transform_vector3(direction);
+            let angle_of_attack = (-local.y).atan2(-local.z);
+            // Cambered wing that stalls beyond its maximum lift
+            let lift_coefficient = (0.3 + std::f32::consts::TAU * angle_of_attack).clamp(-1.2, 1.4);
+            let lift_direction = global_tx.right().cross(direction).normalize_or_zero();
+            external_force.force +=
+                lift_direction * lift_coefficient * dynamic_pressure * body.wing_area;
+
+            let induced_drag = lift_coefficient * lift_coefficient
+                / (std::f32::consts::PI * 0.8 * body.aspect_ratio);
+            drag_area += induced_drag * body.wing_area;
+        }
+
+        external_force.force -= direction * drag_area * dynamic_pressure;
+
+        let forward = global_tx.forward();
+        let weathervane = forward.cross(direction);
+        let level = forward * forward.dot(global_tx.up().cross(Vec3::Y));
+        external_force.torque += (weathervane + level) * body.stability * dynamic_pressure;
+    }
+}
@@ -0,0 +1,723 @@
+use std::f32::consts::{FRAC_PI_3, PI};
+
+use crate::{
+    physics::PHYSICS_DT,
+    units::{Quantity, Units},
+};
+
+use super::{
+    spec::{PlaneSpec, WingRole, WING_OFFSET},
+    test_flight::TestFlight,
+};
+
+const GRAVITY: f32 = 9.81;
+
+/// Speed each test flight starts at.
+const START_SPEED: f32 = 35.0;
+/// Difference in angle of attack in degrees the plane is held at to measure its stability.
+const STABILITY_AOA_STEP: f32 = 2.0;
+const TRIM_DURATION: f32 = 90.0;
+/// Final part of the trim flight averaged for the trim speed.
+const TRIM_AVERAGE: f32 = 30.0;
+/// Time over which the elevator is pulled to its limit in the stall test.
+const STALL_PULL_DURATION: f32 = 30.0;
+const STALL_RECOVERY_TIME: f32 = 10.0;
+/// Pitch rate beyond which a test flight is considered to have departed controlled flight.
+const TUMBLE_RATE: f32 = 3.0;
+/// Speed oscillation in m/s below which the phugoid is considered to have died out.
+const PHUGOID_MIN_AMPLITUDE: f32 = 0.1;
+/// Pitch rate oscillation in rad/s below which the short period is considered to have died out.
+const SHORT_PERIOD_MIN_AMPLITUDE: f32 = 0.05;
+/// Time the speed is averaged over to filter out the short period before looking for the phugoid.
+const PHUGOID_FILTER: f32 = 3.0;
+/// Damping ratio below which an oscillation is considered to grow.
+const MIN_DAMPING: f32 = -0.01;
+
+const MIN_TAIL_VOLUME: f32 = 0.35;
+const MAX_TAIL_VOLUME: f32 = 0.8;
+const MIN_FIN_VOLUME: f32 = 0.02;
+/// Static margin as a fraction of the wing chord.
+pub const MIN_STATIC_MARGIN: f32 = 0.05;
+pub const MAX_STATIC_MARGIN: f32 = 0.5;
+/// Stall speed in m/s above which the wing is suggested to be bigger.
+const MAX_STALL_SPEED: f32 = 25.0;
+/// Time to bank 60° with full aileron, in seconds.
+const MIN_ROLL_TIME: f32 = 0.3;
+const MAX_ROLL_TIME: f32 = 3.0;
+/// Time given for the roll test to reach its bank angle before it's considered never to.
+const ROLL_TEST_DURATION: f32 = 10.0;
+/// Range and step of the speeds in m/s the performance sweep flies level at.
+const SWEEP_MIN_SPEED: f32 = 5.0;
+const SWEEP_MAX_SPEED: f32 = 150.0;
+const SWEEP_SPEED_STEP: f32 = 0.5;
+/// Lowest angle of attack, and the step in angle, the performance sweep holds copies of the plane at.
+/// The coefficient curves are sampled every degree, so level flight is found between them.
+const SWEEP_MIN_AOA: f32 = -10.0;
+const SWEEP_AOA_STEP: f32 = 1.0;
+/// Factor over the stall speed the final approach is flown at.
+const APPROACH_FACTOR: f32 = 1.3;
+
+/// Measured characteristics of a spec with suggestions for improving its handling.
+pub struct TuningReport {
+    pub characteristics: Vec<(&'static str, String)>,
+    pub suggestions: Vec<String>,
+}
+
+/// Where the plane balances compared to where it needs to for stability, measured in a test flight.
+#[derive(Clone, Copy)]
+pub struct Balance {
+    /// Distance of the centre of gravity behind the centre of the fuselage.
+    pub centre_of_gravity: f32,
+    /// Distance behind the centre of the fuselage of the point the change in lift with angle of
+    /// attack acts through. The plane is stable in pitch when this is behind the centre of gravity.
+    pub neutral_point: f32,
+    /// Distance of the neutral point behind the centre of gravity as a fraction of the wing chord.
+    pub static_margin: f32,
+}
+
+pub fn balance(spec: &PlaneSpec) -> Balance {
+    Stability::measure(&mut TestFlight::new(spec, 2)).balance(spec.main_wing().size.z)
+}
+
+/// Speeds in m/s to fly the plane at, from steady level flight in a test flight.
+pub struct ReferenceSpeeds {
+    /// Slowest speed the wings hold the plane up at. There are no flaps to lower it for landing.
+    pub stall: Option<f32>,
+    /// Speed of the fastest climb at full thrust.
+    pub best_climb: Option<f32>,
+    /// Speed of the flattest glide with the engine stopped.
+    pub best_glide: Option<f32>,
+    /// Speed of the final approach, with a margin over the stall.
+    pub approach: Option<f32>,
+    pub max_level: Option<f32>,
+}
+
+pub fn reference_speeds(spec: &PlaneSpec) -> ReferenceSpeeds {
+    let performance = performance(spec);
+
+    ReferenceSpeeds {
+        stall: performance.stall_speed,
+        best_climb: performance.best_climb.map(|(speed, _)| speed),
+        best_glide: performance.best_glide.map(|(speed, _)| speed),
+        approach: performance.stall_speed.map(|speed| speed * APPROACH_FACTOR),
+        max_level: performance.max_level_speed,
+    }
+}
+
+/// Builds the plane in a `TestFlight` and flies it through a set of test manoeuvres, with the game's
+/// flight model but without the rest of the game.
+pub fn analyse(spec: &PlaneSpec, units: Units) -> TuningReport {
+    let mut flight = TestFlight::new(spec, TEST_COPIES);
+    let mass = flight.mass();
+    let stability = Stability::measure(&mut flight);
+    let tests = fly_tests(&mut flight, spec);
+    let mut report = TuningReport {
+        characteristics: vec![],
+        suggestions: vec![],
+    };
+
+    report
+        .characteristics
+        .push(("mass", format!("{:.0} kg", mass)));
+
+    if let Some(rotor) = &spec.rotor {
+        let thrust_to_weight = rotor.max_thrust / (mass * GRAVITY);
+        report
+            .characteristics
+            .push(("rotor thrust to weight", format!("{:.2}", thrust_to_weight)));
+        if thrust_to_weight < 1.0 {
+            report
+                .suggestions
+                .push("Rotor can't lift the helicopter - increase its max thrust".to_string());
+        }
+    }
+
+    // Static stability from the geometry
+    // Measured on the parts graph, so a custom layout's tail counts as it's built, and a layout
+    // without a tail behind the wings, like a flying wing, has no tail volume
+    let parts = spec.parts();
+    let wings = parts.placed_wings();
+    let main_wing = spec.main_wing();
+    let wing_area = main_wing.size.x * main_wing.size.z * 2.0;
+    let main_z = wings
+        .iter()
+        .find(|wing| matches!(wing.role, WingRole::Main | WingRole::Elevon))
+        .map_or(WING_OFFSET, |wing| wing.position.z);
+    // Area of the wings with a role, and how far their centre is behind the main wings
+    let area_and_arm = |role| {
+        let (area, moment) = wings
+            .iter()
+            .filter(|wing| wing.role == role)
+            .fold((0.0, 0.0), |(area, moment), wing| {
+                (area + wing.area(), moment + wing.area() * wing.position.z)
+            });
+        (area > 0.0).then(|| (area, moment / area - main_z))
+    };
+    let tail_volume = area_and_arm(WingRole::Stabiliser)
+        .map(|(tail_area, tail_arm)| tail_area * tail_arm / (wing_area * main_wing.size.z));
+    let fin_volume = area_and_arm(WingRole::Fin).map_or(0.0, |(fin_area, fin_arm)| {
+        fin_area * fin_arm / (wing_area * main_wing.size.x * 2.0)
+    });
+    if let Some(tail_volume) = tail_volume {
+        report
+            .characteristics
+            .push(("tail volume", format!("{:.2}", tail_volume)));
+        if tail_volume < MIN_TAIL_VOLUME {
+            report.suggestions.push(
+                "Tail volume low - increase horizontal tail area or lengthen the tail".to_string(),
+            );
+        } else if tail_volume > MAX_TAIL_VOLUME {
+            report.suggestions.push(
+                "Tail volume high - pitch will be sluggish, reduce horizontal tail area"
+                    .to_string(),
+            );
+        }
+    }
+    report
+        .characteristics
+        .push(("fin volume", format!("{:.3}", fin_volume)));
+    if fin_volume < MIN_FIN_VOLUME {
+        report
+            .suggestions
+            .push("Fin volume low - increase vertical tail area".to_string());
+    }
+
+    report.characteristics.push((
+        "pitch stiffness",
+        format!("{:.0} Nm/°", stability.pitch_stiffness.to_radians()),
+    ));
+
+    let balance = stability.balance(spec.main_wing().size.z);
+    report.characteristics.push((
+        "static margin",
+        format!("{:.0}% of chord", balance.static_margin * 100.0),
+    ));
+    if balance.static_margin < 0.0 {
+        report.suggestions.push(
+            "Statically unstable in pitch - enlarge the horizontal tail or move ballast forward to move the centre of gravity ahead of the neutral point"
+                .to_string(),
+        );
+    } else if balance.static_margin < MIN_STATIC_MARGIN {
+        report
+            .suggestions
+            .push("Static margin small - pitch will be twitchy, move ballast forward".to_string());
+    } else if balance.static_margin > MAX_STATIC_MARGIN {
+        report.suggestions.push(
+            "Static margin large - the plane will be nose heavy and hard to flare, move ballast back"
+                .to_string(),
+        );
+    }
+
+    // Trim and phugoid at full power, then trim at idle for the glide
+    match &tests.full_power {
+        Some(trim) => {
+            report.characteristics.push((
+                "trim speed (full power)",
+                units.format(Quantity::Speed, trim.speed, 0),
+            ));
+            report.characteristics.push((
+                "climb rate (full power)",
+                units.format_signed(Quantity::VerticalSpeed, trim.climb, 1),
+            ));
+            if trim.climb < 0.0 {
+                report.suggestions.push(
+                    "Can't hold height at full power - increase thrust or wing area".to_string(),
+                );
+            }
+            if trim.speed > spec.never_exceed_speed {
+                report.suggestions.push(
+                    "Flies faster than the never exceed speed at full power - reduce thrust or raise the never exceed speed"
+                        .to_string(),
+                );
+            }
+
+            let oscillations = [
+                (
+                    "short period",
+                    trim.short_period,
+                    "Pitch oscillation grows - reduce tail volume or move the wings closer to the centre of gravity",
+                ),
+                (
+                    "phugoid",
+                    trim.phugoid,
+                    "Phugoid diverges - increase wing drag or tail volume to damp it",
+                ),
+            ];
+            for (name, oscillation, suggestion) in oscillations {
+                match oscillation {
+                    Some(Oscillation { period, damping }) => {
+                        report
+                            .characteristics
+                            .push((name, format!("{:.1} s, damping {:.2}", period, damping)));
+                        if damping < MIN_DAMPING {
+                            report.suggestions.push(suggestion.to_string());
+                        }
+                    }
+                    None => report
+                        .characteristics
+                        .push((name, "no oscillation".to_string())),
+                }
+            }
+        }
+        None => {
+            report
+                .characteristics
+                .push(("trim speed (full power)", "departs".to_string()));
+            report.suggestions.push(
+                "No trimmed flight at full power - check the wing and tail incidence angles"
+                    .to_string(),
+            );
+        }
+    }
+
+    match &tests.idle {
+        Some(trim) => {
+            report
+                .characteristics
+                .push(("glide speed", units.format(Quantity::Speed, trim.speed, 0)));
+            report.characteristics.push((
+                "sink rate",
+                units.format(Quantity::VerticalSpeed, -trim.climb, 1),
+            ));
+        }
+        None => report
+            .characteristics
+            .push(("glide speed", "departs".to_string())),
+    }
+
+    match &tests.stall {
+        Some(stall) => {
+            report
+                .characteristics
+                .push(("stall speed", units.format(Quantity::Speed, stall.speed, 0)));
+            report.characteristics.push((
+                "stall recovery",
+                if stall.recovers {
+                    "nose drops"
+                } else {
+                    "stays stalled"
+                }
+                .to_string(),
+            ));
+            if stall.speed > MAX_STALL_SPEED {
+                report
+                    .suggestions
+                    .push("Stall speed high - increase wing area or reduce mass".to_string());
+            }
+            if !stall.recovers {
+                report
+                    .suggestions
+                    .push("Doesn't recover from the stall - increase tail volume".to_string());
+            }
+        }
+        None => {
+            report
+                .characteristics
+                .push(("stall speed", "can't be stalled".to_string()));
+            report.suggestions.push(
+                "Elevator can't reach the stall - increase the tail's max control angle"
+                    .to_string(),
+            );
+        }
+    }
+
+    // Steady flight across the speed range, where the trim flights only find one speed each
+    let performance = performance(spec);
+    let speed_or = |speed: Option<f32>, otherwise: &str| {
+        speed.map_or(otherwise.to_string(), |speed| {
+            units.format(Quantity::Speed, speed, 0)
+        })
+    };
+    report.characteristics.push((
+        "level stall speed",
+        speed_or(performance.stall_speed, "can't fly level"),
+    ));
+    report.characteristics.push((
+        "max level speed",
+        speed_or(performance.max_level_speed, "can't hold height"),
+    ));
+    match performance.best_climb {
+        Some((speed, rate)) => report.characteristics.push((
+            "best climb",
+            format!(
+                "{} at {}",
+                units.format_signed(Quantity::VerticalSpeed, rate, 1),
+                units.format(Quantity::Speed, speed, 0)
+            ),
+        )),
+        None => report
+            .characteristics
+            .push(("best climb", "none".to_string())),
+    }
+    match performance.best_glide {
+        Some((speed, ratio)) => report.characteristics.push((
+            "best glide",
+            format!(
+                "{:.1}:1 at {}",
+                ratio,
+                units.format(Quantity::Speed, speed, 0)
+            ),
+        )),
+        None => report
+            .characteristics
+            .push(("best glide", "none".to_string())),
+    }
+
+    match tests.roll_time {
+        Some(roll_time) => {
+            report
+                .characteristics
+                .push(("time to 60° bank", format!("{:.2} s", roll_time)));
+            if roll_time > MAX_ROLL_TIME {
+                report
+                    .suggestions
+                    .push("Roll rate low - increase the wings' max control angle".to_string());
+            } else if roll_time < MIN_ROLL_TIME {
+                report
+                    .suggestions
+                    .push("Roll very sensitive - reduce the wings' max control angle".to_string());
+            }
+        }
+        None => report
+            .characteristics
+            .push(("time to 60° bank", "departs".to_string())),
+    }
+
+    if report.suggestions.is_empty() {
+        report.suggestions.push("No problems found".to_string());
+    }
+
+    report
+}
+
+#[derive(Clone, Copy)]
+struct Oscillation {
+    period: f32,
+    damping: f32,
+}
+
+struct Trim {
+    speed: f32,
+    climb: f32,
+    /// Quick oscillation in pitch about the trimmed angle of attack.
+    short_period: Option<Oscillation>,
+    /// Slow exchange of speed and height about the trimmed speed.
+    phugoid: Option<Oscillation>,
+}
+
+struct Stall {
+    speed: f32,
+    recovers: bool,
+}
+
+/// Steady level flight across the speed range, each speed flown at the angle of attack whose lift
+/// holds up the weight.
+struct Performance {
+    /// Slowest speed the wings can hold the plane up at before they stall.
+    stall_speed: Option<f32>,
+    /// Fastest speed the thrust overcomes the drag at.
+    max_level_speed: Option<f32>,
+    /// Speed and rate of the fastest climb, from the thrust left over after the drag.
+    best_climb: Option<(f32, f32)>,
+    /// Speed and ratio of lift to drag of the flattest glide.
+    best_glide: Option<(f32, f32)>,
+}
+
+/// Forces on the plane flying level at a speed.
+struct LevelFlight {
+    angle_of_attack: f32,
+    lift: f32,
+    drag: f32,
+}
+
+/// Change in pitching moment and lift with angle of attack, from the first two copies of a test
+/// flight held a little apart in angle of attack at the start speed.
+struct Stability {
+    /// Distance of the centre of gravity behind the centre of the fuselage.
+    centre_of_gravity: f32,
+    /// Change in nose up pitching moment per radian of angle of attack, negative when stable.
+    pitch_stiffness: f32,
+    /// Change in lift at right angles to the flight path per radian of angle of attack.
+    lift_slope: f32,
+}
+
+impl Stability {
+    fn measure(flight: &mut TestFlight) -> Self {
+        let delta = STABILITY_AOA_STEP.to_radians();
+        flight.hold(0, START_SPEED, delta);
+        flight.hold(1, START_SPEED, delta * 2.0);
+        flight.step();
+
+        // Heading north, the nose pitches up about the right wing along x and lift is straight up
+        let (low, high) = (flight.forces(0), flight.forces(1));
+        Self {
+            centre_of_gravity: flight.centre_of_gravity().z,
+            pitch_stiffness: (high.torque.x - low.torque.x) / delta,
+            lift_slope: (high.force.y - low.force.y) / delta,
+        }
+    }
+
+    fn balance(&self, chord: f32) -> Balance {
+        // Moving the reference point back by d changes the stiffness by d times the lift slope, so
+        // the neutral point is where that cancels it out
+        let behind_centre_of_gravity = if self.lift_slope.abs() > f32::EPSILON {
+            -self.pitch_stiffness / self.lift_slope
+        } else {
+            0.0
+        };
+
+        Balance {
+            centre_of_gravity: self.centre_of_gravity,
+            neutral_point: self.centre_of_gravity + behind_centre_of_gravity,
+            static_margin: if chord > 0.0 {
+                behind_centre_of_gravity / chord
+            } else {
+                0.0
+            },
+        }
+    }
+}
+
+/// Speeds, pitch rates and climb rates of a copy flown hands off, until it settles or departs.
+#[derive(Default)]
+struct TrimRecorder {
+    speeds: Vec<f32>,
+    pitch_rates: Vec<f32>,
+    climbs: Vec<f32>,
+    departed: bool,
+}
+
+impl TrimRecorder {
+    fn record(&mut self, flight: &TestFlight, copy: usize) {
+        let pitch_rate = flight.pitch_rate(copy);
+        self.departed |= pitch_rate.abs() > TUMBLE_RATE;
+        if self.departed {
+            return;
+        }
+
+        let velocity = flight.velocity(copy).linvel;
+        self.speeds.push(velocity.length());
+        self.pitch_rates.push(pitch_rate);
+        self.climbs.push(velocity.y);
+    }
+
+    /// Trim from the end of the flight, or `None` if it departed.
+    fn finish(&self) -> Option<Trim> {
+        if self.departed {
+            return None;
+        }
+
+        let average_from = self.speeds.len() - (TRIM_AVERAGE / PHYSICS_DT) as usize;
+        let average = |samples: &[f32]| {
+            let samples = &samples[average_from..];
+            samples.iter().sum::<f32>() / samples.len() as f32
+        };
+        let speed = average(&self.speeds);
+
+        // Averaging over a few seconds leaves only the much slower phugoid in the speed
+        let filter = (PHUGOID_FILTER / PHYSICS_DT) as usize;
+        let filtered_speeds: Vec<f32> = self
+            .speeds
+            .windows(filter)
+            .map(|window| window.iter().sum::<f32>() / filter as f32)
+            .collect();
+
+        Some(Trim {
+            speed,
+            climb: average(&self.climbs),
+            short_period: oscillation(&self.pitch_rates, 0.0, SHORT_PERIOD_MIN_AMPLITUDE),
+            phugoid: oscillation(&filtered_speeds, speed, PHUGOID_MIN_AMPLITUDE),
+        })
+    }
+}
+
+/// Results of the test flights flown side by side by `fly_tests`.
+struct TestFlights {
+    full_power: Option<Trim>,
+    idle: Option<Trim>,
+    stall: Option<Stall>,
+    /// Time to roll to 60° with full aileron from the trim at full power.
+    roll_time: Option<f32>,
+}
+
+/// Copies of the plane each test is flown with.
+const FULL_POWER: usize = 0;
+const IDLE: usize = 1;
+const STALL: usize = 2;
+const TEST_COPIES: usize = 3;
+
+/// Flies hands off at full power and at idle until the speed settles, while a third copy slowly
+/// pulls the elevator at idle until the wings stall and then lets go. The copy at full power then
+/// rolls with full aileron.
+fn fly_tests(flight: &mut TestFlight, spec: &PlaneSpec) -> TestFlights {
+    for copy in 0..TEST_COPIES {
+        flight.hold(copy, START_SPEED, 0.0);
+        flight.set_thrust(copy, 0.0);
+        flight.lock_to_pitch(copy, true);
+    }
+    flight.set_thrust(FULL_POWER, spec.thrust);
+
+    let mut full_power = TrimRecorder::default();
+    let mut idle = TrimRecorder::default();
+
+    let pull_steps = (STALL_PULL_DURATION / PHYSICS_DT) as usize;
+    let recovery_steps = (STALL_RECOVERY_TIME / PHYSICS_DT) as usize;
+    // Less tail lift pitches the nose up
+    let pull = -spec.pitch_wing().max_control_angle;
+    let mut stalled_at = None;
+    let mut recovers = false;
+
+    for i in 0..(TRIM_DURATION / PHYSICS_DT) as usize {
+        flight.control_mut(STALL).elevators = match stalled_at {
+            None if i < pull_steps => pull * i as f32 / pull_steps as f32,
+            _ => 0.0,
+        };
+        flight.step();
+
+        full_power.record(flight, FULL_POWER);
+        idle.record(flight, IDLE);
+        match stalled_at {
+            None if i < pull_steps && flight.wings_stalled(STALL) => {
+                stalled_at = Some((i, flight.velocity(STALL).linvel.length()));
+            }
+            Some((at, _)) if !recovers && i - at < recovery_steps => {
+                recovers = !flight.wings_stalled(STALL);
+            }
+            _ => {}
+        }
+    }
+
+    let full_power = full_power.finish();
+    let roll_time = full_power.is_some().then(|| {
+        flight.lock_to_pitch(FULL_POWER, false);
+        let start = flight.bank(FULL_POWER);
+        flight.control_mut(FULL_POWER).ailerons = spec.main_wing().max_control_angle;
+        let steps = (ROLL_TEST_DURATION / PHYSICS_DT) as usize;
+        (1..=steps).find_map(|i| {
+            flight.step();
+            ((flight.bank(FULL_POWER) - start).abs() >= FRAC_PI_3).then_some(i as f32 * PHYSICS_DT)
+        })
+    });
+
+    TestFlights {
+        full_power,
+        idle: idle.finish(),
+        stall: stalled_at.map(|(_, speed)| Stall { speed, recovers }),
+        roll_time: roll_time.map(|time| time.unwrap_or(f32::INFINITY)),
+    }
+}
+
+/// Holds a copy of the plane level at each angle of attack in the sweep, and for each speed in
+/// turn flies them all for a physics step to measure their lift and drag. The speed range's limits
+/// and the best climb and glide within it come from the angle of attack whose lift holds up the
+/// weight at each speed.
+fn performance(spec: &PlaneSpec) -> Performance {
+    let mut performance = Performance {
+        stall_speed: None,
+        max_level_speed: None,
+        best_climb: None,
+        best_glide: None,
+    };
+
+    // Up to the peak of the main wings' lift curve, less the angle they're set at to the fuselage
+    let stall_angle = spec
+        .main_wing()
+        .lift_coefficient_curve
+        .iter()
+        .max_by(|a, b| a.0.total_cmp(&b.0))
+        .map_or(0.0, |(_, angle)| *angle)
+        - spec.main_wing().angle.to_degrees();
+    let angles: Vec<f32> = (0..)
+        .map(|i| (SWEEP_MIN_AOA + i as f32 * SWEEP_AOA_STEP).to_radians())
+        .take_while(|aoa| aoa.to_degrees() <= stall_angle)
+        .collect();
+    if angles.is_empty() {
+        return performance;
+    }
+
+    let mut flight = TestFlight::new(spec, angles.len());
+    let weight = flight.mass() * GRAVITY;
+    let thrust = spec.thrust;
+
+    let steps = ((SWEEP_MAX_SPEED - SWEEP_MIN_SPEED) / SWEEP_SPEED_STEP) as usize;
+    for speed in (0..=steps).map(|i| SWEEP_MIN_SPEED + i as f32 * SWEEP_SPEED_STEP) {
+        for (copy, aoa) in angles.iter().enumerate() {
+            flight.hold(copy, speed, *aoa);
+        }
+        flight.step();
+
+        // Heading north, lift is straight up and drag points south
+        let forces: Vec<(f32, f32)> = (0..angles.len())
+            .map(|copy| {
+                let force = flight.forces(copy).force;
+                (force.y, force.z)
+            })
+            .collect();
+        let Some(level) = level_flight(&angles, &forces, weight) else {
+            continue;
+        };
+        performance.stall_speed.get_or_insert(speed);
+
+        // Thrust pushes along the nose, which is raised by the angle of attack
+        let excess = thrust * level.angle_of_attack.cos() - level.drag;
+        if excess >= 0.0 {
+            performance.max_level_speed = Some(speed);
+        }
+        let climb = excess * speed / weight;
+        if climb > 0.0 && performance.best_climb.is_none_or(|(_, best)| climb > best) {
+            performance.best_climb = Some((speed, climb));
+        }
+
+        if level.drag > 0.0 {
+            let ratio = level.lift / level.drag;
+            if performance.best_glide.is_none_or(|(_, best)| ratio > best) {
+                performance.best_glide = Some((speed, ratio));
+            }
+        }
+    }
+
+    performance
+}
+
+/// Lowest angle of attack whose lift holds up the weight, between the angles measured, or `None`
+/// if the wings stall first.
+fn level_flight(angles: &[f32], forces: &[(f32, f32)], weight: f32) -> Option<LevelFlight> {
+    let i = forces.iter().position(|(lift, _)| *lift >= weight)?;
+    let (lift, drag) = forces[i];
+    if i == 0 {
+        return Some(LevelFlight {
+            angle_of_attack: angles[0],
+            lift,
+            drag,
+        });
+    }
+
+    let (below_lift, below_drag) = forces[i - 1];
+    let s = (weight - below_lift) / (lift - below_lift);
+    Some(LevelFlight {
+        angle_of_attack: angles[i - 1] + (angles[i] - angles[i - 1]) * s,
+        lift: weight,
+        drag: below_drag + (drag - below_drag) * s,
+    })
+}
+
+/// Period and damping ratio of an oscillation about a centre value, from its peaks.
+fn oscillation(samples: &[f32], centre: f32, min_amplitude: f32) -> Option<Oscillation> {
+    let peaks: Vec<(usize, f32)> = samples
+        .windows(3)
+        .enumerate()
+        .filter(|(_, w)| w[1] > w[0] && w[1] >= w[2] && w[1] > centre + min_amplitude)
+        .map(|(i, w)| (i + 1, w[1] - centre))
+        .collect();
+    if peaks.len() < 3 {
+        return None;
+    }
+
+    let period =
+        (peaks[peaks.len() - 1].0 - peaks[0].0) as f32 * PHYSICS_DT / (peaks.len() - 1) as f32;
+    // Logarithmic decrement between the first two peaks
+    let decrement = (peaks[0].1 / peaks[1].1).ln();
+    let damping = decrement / (4.0 * PI * PI + decrement * decrement).sqrt();
+
+    Some(Oscillation { period, damping })
+}
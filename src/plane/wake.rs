@@ -0,0 +1,179 @@
+use std::f32::consts::{FRAC_PI_4, TAU};
+
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+use crate::{
+    flight_model::{air_density, Airfoil},
+    world::OriginShiftedEvent,
+};
+
+use super::Plane;
+
+/// Time between samples of the vortices trailing each plane.
+const SAMPLE_INTERVAL: f32 = 0.1;
+/// Time for a vortex to decay away completely.
+const LIFETIME: f32 = 30.0;
+/// Speed below which a plane is taxiing and leaves no wake.
+const MIN_SPEED: f32 = 10.0;
+/// Radius of the vortex core as a fraction of the span.
+const CORE_RADIUS: f32 = 0.1;
+/// Distance in core radii beyond which a vortex is ignored.
+const MAX_RANGE: f32 = 20.0;
+/// Age below which a plane's own vortices don't affect it, so it doesn't fly in its own wake.
+const SELF_WAKE_AGE: f32 = 2.0;
+const GRAVITY: f32 = 9.81;
+
+/// Makes a plane leave a pair of wing tip vortices behind it, stronger for heavier and slower planes.
+#[derive(Component)]
+pub struct WakeGenerator {
+    pub span: f32,
+    /// Mass of planes without mass properties from the physics, like those flown by peers.
+    pub mass: f32,
+}
+
+/// A short length of trailing vortex.
+pub struct Vortex {
+    source: Entity,
+    position: Vec3,
+    axis: Vec3,
+    half_length: f32,
+    /// Circulation in m²/s, positive for a left wing tip.
+    circulation: f32,
+    core_radius: f32,
+    age: f32,
+}
+
+impl Vortex {
+    fn strength(&self) -> f32 {
+        1.0 - self.age / LIFETIME
+    }
+
+    /// Air velocity induced at a point, using the Lamb-Oseen profile so it stays finite in the core.
+    fn induced_velocity(&self, point: Vec3) -> Vec3 {
+        let offset = point - self.position;
+        let along = offset.dot(self.axis);
+        if along.abs() > self.half_length {
+            return Vec3::ZERO;
+        }
+
+        let radial = offset - self.axis * along;
+        let r = radial.length();
+        if r < f32::EPSILON || r > self.core_radius * MAX_RANGE {
+            return Vec3::ZERO;
+        }
+
+        let core = 1.0 - (-(r * r) / (self.core_radius * self.core_radius)).exp();
+        let speed = self.circulation * self.strength() / (TAU * r) * core;
+        self.axis.cross(radial / r) * speed
+    }
+}
+
+/// Vortices left behind by all planes.
+#[derive(Resource, Default)]
+pub struct Wake(pub Vec<Vortex>);
+
+impl Wake {
+    /// Air velocity induced at a point on a plane by the wake of other planes, and its own older wake.
+    pub fn induced_velocity(&self, point: Vec3, plane: Entity) -> Vec3 {
+        self.0
+            .iter()
+            .filter(|vortex| vortex.source != plane || vortex.age > SELF_WAKE_AGE)
+            .map(|vortex| vortex.induced_velocity(point))
+            .sum()
+    }
+}
+
+/// Ages, sinks and decays the wake and adds new samples behind each plane. Runs each physics step.
+pub fn update_wake(
+    fixed_time: Res<FixedTime>,
+    mut wake: ResMut<Wake>,
+    mut since_sample: Local<f32>,
+    generator_query: Query<(
+        Entity,
+        &GlobalTransform,
+        &Velocity,
+        &WakeGenerator,
+        Option<&ReadMassProperties>,
+    )>,
+) {
+    let dt = fixed_time.period.as_secs_f32();
+
+    wake.0.retain_mut(|vortex| {
+        vortex.age += dt;
+        // Each vortex is pushed down by the other of its pair
+        let spacing = vortex.core_radius / CORE_RADIUS * FRAC_PI_4;
+        vortex.position.y -= vortex.circulation.abs() * vortex.strength() / (TAU * spacing) * dt;
+        vortex.age < LIFETIME
+    });
+
+    *since_sample += dt;
+    if *since_sample < SAMPLE_INTERVAL {
+        return;
+    }
+    *since_sample = 0.0;
+
+    for (entity, global_tx, velocity, generator, mass_properties) in generator_query.iter() {
+        let speed = velocity.linvel.length();
+        if speed < MIN_SPEED {
+            continue;
+        }
+
+        let mass = mass_properties.map_or(generator.mass, |props| props.0.mass);
+        // Tip vortices roll up slightly inboard of the tips for an elliptical lift distribution
+        let spacing = generator.span * FRAC_PI_4;
+        let density = air_density(global_tx.translation().y);
+        let circulation = mass * GRAVITY / (density * speed * spacing);
+        let axis = velocity.linvel / speed;
+        let right = axis.cross(Vec3::Y).normalize_or_zero();
+
+        for (side, sign) in [(-0.5, 1.0), (0.5, -1.0)] {
+            wake.0.push(Vortex {
+                source: entity,
+                position: global_tx.translation() + right * spacing * side,
+                axis,
+                half_length: speed * SAMPLE_INTERVAL * 0.5,
+                circulation: circulation * sign,
+                core_radius: generator.span * CORE_RADIUS,
+                age: 0.0,
+            });
+        }
+    }
+}
+
+/// Disturbs the airflow over each airfoil of a plane by any wake it's flying through.
+pub fn update_airfoil_downwash(
+    wake: Res<Wake>,
+    plane_query: Query<Entity, With<Plane>>,
+    children_query: Query<&Children>,
+    mut airfoil_query: Query<(&mut Airfoil, &GlobalTransform)>,
+) {
+    for plane_entity in plane_query.iter() {
+        for child in children_query.iter_descendants(plane_entity) {
+            if let Ok((mut airfoil, airfoil_global_tx)) = airfoil_query.get_mut(child) {
+                airfoil.induced_velocity =
+                    wake.induced_velocity(airfoil_global_tx.translation(), plane_entity);
+            }
+        }
+    }
+}
+
+/// Vortices are held outside of transforms so need moving along with the origin.
+pub fn rebase_wake(mut shifted_events: EventReader<OriginShiftedEvent>, mut wake: ResMut<Wake>) {
+    for OriginShiftedEvent(shift) in shifted_events.iter() {
+        for vortex in wake.0.iter_mut() {
+            vortex.position -= *shift;
+        }
+    }
+}
+
+pub fn draw_wake_gizmos(mut gizmos: Gizmos, wake: Res<Wake>) {
+    for vortex in wake.0.iter() {
+        let half = vortex.axis * vortex.half_length;
+        gizmos.line(
+            vortex.position - half,
+            vortex.position + half,
+            Color::rgba(0.6, 0.8, 1.0, vortex.strength()),
+        );
+    }
+}
@@ -0,0 +1,321 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+use crate::flight_model::{air_density, AmbientAir, CentreOfGravity, Thrust, ThrustPoint};
+
+use super::{spec::PlaneSpec, Plane, SelectedPlane};
+
+/// Height the plane is lifted by while held in the tunnel, clear of the ground it was sat on.
+const HOLD_HEIGHT: f32 = 10.0;
+const SWEEP_MIN_ANGLE: f32 = -10.0;
+const SWEEP_MAX_ANGLE: f32 = 25.0;
+/// Rate of the angle of attack sweep in degrees per second.
+const SWEEP_RATE: f32 = 5.0;
+/// Length in metres of a force gizmo as long as the plane's weight.
+const WEIGHT_GIZMO_LENGTH: f32 = 5.0;
+const STREAMLINE_COUNT: i32 = 5;
+const STREAMLINE_SPACING: f32 = 1.5;
+const STREAMLINE_LENGTH: f32 = 20.0;
+const LIFT_COLOR: Color = Color::BLUE;
+const DRAG_COLOR: Color = Color::RED;
+const NOSE_UP_COLOR: Color = Color::GREEN;
+const NOSE_DOWN_COLOR: Color = Color::ORANGE;
+const STREAMLINE_COLOR: Color = Color::rgba(1.0, 1.0, 1.0, 0.3);
+
+/// Whether the selected plane is flying freely or held in the wind tunnel.
+#[derive(States, Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum FlightMode {
+    #[default]
+    Flying,
+    WindTunnel,
+}
+
+/// Air forces measured on the plane at one angle of attack in the tunnel, as coefficients of the
+/// main wing area and chord.
+#[derive(Clone, Copy, Default)]
+pub struct TunnelSample {
+    /// Angle of attack in degrees.
+    pub angle: f32,
+    pub lift: f32,
+    pub drag: f32,
+    /// Pitching moment about the centre of gravity, positive nose up.
+    pub moment: f32,
+}
+
+/// Airflow imposed on the plane held in the tunnel and the forces it makes.
+#[derive(Resource)]
+pub struct WindTunnel {
+    /// Speed of the airflow in m/s.
+    pub speed: f32,
+    /// Angle of attack of the airflow in degrees, swept back and forth between its limits or set
+    /// by hand.
+    pub angle: f32,
+    pub sweeping: bool,
+    sweep_direction: f32,
+    /// Lift, drag and pitching moment in newtons and newton metres, less the engine's thrust.
+    pub lift: f32,
+    pub drag: f32,
+    pub moment: f32,
+    pub coefficients: TunnelSample,
+    /// Coefficients at each angle the sweep has passed, in order of angle.
+    pub samples: Vec<TunnelSample>,
+    held: Option<Held>,
+}
+
+impl Default for WindTunnel {
+    fn default() -> Self {
+        Self {
+            speed: 30.0,
+            angle: 0.0,
+            sweeping: true,
+            sweep_direction: 1.0,
+            lift: 0.0,
+            drag: 0.0,
+            moment: 0.0,
+            coefficients: TunnelSample::default(),
+            samples: Vec::new(),
+            held: None,
+        }
+    }
+}
+
+impl WindTunnel {
+    /// Direction of the plane's motion through the air, pitched down from its nose by the angle of
+    /// attack.
+    fn airflow_dir(&self, global_tx: &GlobalTransform) -> Vec3 {
+        let angle = self.angle.to_radians();
+        global_tx.forward() * angle.cos() - global_tx.up() * angle.sin()
+    }
+
+    fn record(&mut self, sample: TunnelSample) {
+        let step = (sample.angle * 2.0).round();
+        match self
+            .samples
+            .binary_search_by(|s| (s.angle * 2.0).round().total_cmp(&step))
+        {
+            Ok(i) => self.samples[i] = sample,
+            Err(i) => self.samples.insert(i, sample),
+        }
+    }
+}
+
+/// Plane fixed to an anchor in the tunnel, and where to put it back when it leaves.
+struct Held {
+    plane: Entity,
+    anchor: Entity,
+    transform: Transform,
+}
+
+/// Holds the selected plane level on a fixed joint, picking up a rebuilt plane in place of the
+/// last.
+pub fn hold_plane(
+    mut commands: Commands,
+    mut tunnel: ResMut<WindTunnel>,
+    mut plane_query: Query<(&mut Transform, &mut Velocity), With<Plane>>,
+    selected: Res<SelectedPlane>,
+) {
+    let plane = selected.0.filter(|plane| plane_query.contains(*plane));
+    if plane == tunnel.held.as_ref().map(|held| held.plane) {
+        return;
+    }
+
+    let transform = match tunnel.held.take() {
+        Some(held) => {
+            commands.entity(held.anchor).despawn();
+            held.transform
+        }
+        None => match plane.and_then(|plane| plane_query.get(plane).ok()) {
+            Some((transform, _)) => *transform,
+            None => return,
+        },
+    };
+    let Some(plane) = plane else {
+        return;
+    };
+    let Ok((mut plane_tx, mut velocity)) = plane_query.get_mut(plane) else {
+        return;
+    };
+
+    // Facing straight down the tunnel keeps the airspeed measured along the nose
+    let position = transform.translation + Vec3::Y * HOLD_HEIGHT;
+    *plane_tx = Transform::from_translation(position);
+    *velocity = Velocity::zero();
+
+    let anchor = commands
+        .spawn((
+            TransformBundle::from_transform(Transform::from_translation(position)),
+            RigidBody::Fixed,
+        ))
+        .id();
+    commands
+        .entity(plane)
+        .insert(ImpulseJoint::new(anchor, FixedJointBuilder::new()));
+
+    info!("Holding plane in the wind tunnel");
+    tunnel.held = Some(Held {
+        plane,
+        anchor,
+        transform,
+    });
+    tunnel.samples.clear();
+}
+
+/// Lets the plane go from the tunnel where it was picked up from.
+pub fn release_plane(
+    mut commands: Commands,
+    mut tunnel: ResMut<WindTunnel>,
+    mut plane_query: Query<(&mut Transform, &mut Velocity), With<Plane>>,
+) {
+    let Some(held) = tunnel.held.take() else {
+        return;
+    };
+    commands.entity(held.anchor).despawn();
+    if let Ok((mut plane_tx, mut velocity)) = plane_query.get_mut(held.plane) {
+        commands.entity(held.plane).remove::<ImpulseJoint>();
+        *plane_tx = held.transform;
+        *velocity = Velocity::zero();
+    }
+}
+
+/// Steps the sweep through the angles of attack, turning back at each end.
+pub fn sweep_angle(mut tunnel: ResMut<WindTunnel>, time: Res<Time>) {
+    if !tunnel.sweeping {
+        return;
+    }
+
+    tunnel.angle += tunnel.sweep_direction * SWEEP_RATE * time.delta_seconds();
+    if tunnel.angle >= SWEEP_MAX_ANGLE {
+        tunnel.angle = SWEEP_MAX_ANGLE;
+        tunnel.sweep_direction = -1.0;
+    } else if tunnel.angle <= SWEEP_MIN_ANGLE {
+        tunnel.angle = SWEEP_MIN_ANGLE;
+        tunnel.sweep_direction = 1.0;
+    }
+}
+
+/// Blows the tunnel's airflow over the held plane, replacing the weather.
+pub fn impose_airflow(
+    tunnel: Res<WindTunnel>,
+    mut plane_query: Query<(&GlobalTransform, &mut AmbientAir), With<Plane>>,
+) {
+    let Some(held) = &tunnel.held else {
+        return;
+    };
+    if let Ok((global_tx, mut ambient_air)) = plane_query.get_mut(held.plane) {
+        ambient_air.velocity = -tunnel.airflow_dir(global_tx) * tunnel.speed;
+        ambient_air.lift_factor = 1.0;
+    }
+}
+
+/// Splits the force the flight model puts on the held plane into lift and drag across and along
+/// the airflow, after taking out the thrust.
+#[allow(clippy::type_complexity)]
+pub fn measure_forces(
+    mut tunnel: ResMut<WindTunnel>,
+    plane_query: Query<
+        (
+            &GlobalTransform,
+            &ExternalForce,
+            &CentreOfGravity,
+            &PlaneSpec,
+            Option<(&Thrust, &ThrustPoint)>,
+        ),
+        With<Plane>,
+    >,
+) {
+    let Some(Ok((global_tx, external_force, centre_of_gravity, spec, thrust))) =
+        tunnel.held.as_ref().map(|held| plane_query.get(held.plane))
+    else {
+        return;
+    };
+
+    let mut force = external_force.force;
+    let mut torque = external_force.torque;
+    if let Some((Thrust(thrust), ThrustPoint(point))) = thrust {
+        let thrust = global_tx.forward() * *thrust;
+        force -= thrust;
+        torque -= (global_tx.transform_point(*point) - centre_of_gravity.global).cross(thrust);
+    }
+
+    let airflow_dir = tunnel.airflow_dir(global_tx);
+    let lift_dir = global_tx.right().cross(airflow_dir);
+    tunnel.lift = force.dot(lift_dir);
+    tunnel.drag = -force.dot(airflow_dir);
+    tunnel.moment = torque.dot(global_tx.right());
+
+    let chord = spec.main_wing().size.z;
+    let area = spec.main_wing().size.x * chord * 2.0;
+    let dynamic_pressure =
+        0.5 * air_density(global_tx.translation().y) * tunnel.speed * tunnel.speed;
+    if dynamic_pressure * area <= 0.0 {
+        return;
+    }
+    let sample = TunnelSample {
+        angle: tunnel.angle,
+        lift: tunnel.lift / (dynamic_pressure * area),
+        drag: tunnel.drag / (dynamic_pressure * area),
+        moment: tunnel.moment / (dynamic_pressure * area * chord),
+    };
+    tunnel.coefficients = sample;
+    if tunnel.sweeping {
+        tunnel.record(sample);
+    }
+}
+
+/// Draws the airflow streaming past the held plane, with the lift and drag at its centre of
+/// gravity to the scale of its weight, and a ring for the pitching moment as large as the lift
+/// that would make it acting a chord away.
+pub fn draw_tunnel_gizmos(
+    tunnel: Res<WindTunnel>,
+    plane_query: Query<
+        (
+            &GlobalTransform,
+            &CentreOfGravity,
+            &ReadMassProperties,
+            &PlaneSpec,
+        ),
+        With<Plane>,
+    >,
+    mut gizmos: Gizmos,
+) {
+    let Some(Ok((global_tx, centre_of_gravity, ReadMassProperties(mass_properties), spec))) =
+        tunnel.held.as_ref().map(|held| plane_query.get(held.plane))
+    else {
+        return;
+    };
+
+    let airflow_dir = tunnel.airflow_dir(global_tx);
+    let lift_dir = global_tx.right().cross(airflow_dir);
+    let centre = centre_of_gravity.global;
+
+    for i in -STREAMLINE_COUNT / 2..=STREAMLINE_COUNT / 2 {
+        let start = centre
+            + lift_dir * i as f32 * STREAMLINE_SPACING
+            + airflow_dir * STREAMLINE_LENGTH * 0.5;
+        gizmos.line(
+            start,
+            start - airflow_dir * STREAMLINE_LENGTH,
+            STREAMLINE_COLOR,
+        );
+    }
+
+    let weight = mass_properties.mass * 9.81;
+    if weight <= 0.0 {
+        return;
+    }
+    let scale = WEIGHT_GIZMO_LENGTH / weight;
+    gizmos.line(centre, centre + lift_dir * tunnel.lift * scale, LIFT_COLOR);
+    gizmos.line(
+        centre,
+        centre - airflow_dir * tunnel.drag * scale,
+        DRAG_COLOR,
+    );
+
+    let chord = spec.main_wing().size.z.max(0.1);
+    let radius = (tunnel.moment.abs() / chord * scale).min(WEIGHT_GIZMO_LENGTH);
+    let color = match tunnel.moment >= 0.0 {
+        true => NOSE_UP_COLOR,
+        false => NOSE_DOWN_COLOR,
+    };
+    gizmos.circle(centre, global_tx.right(), radius, color);
+}
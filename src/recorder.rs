@@ -0,0 +1,140 @@
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+use crate::{
+    damage::CrashedEvent,
+    flight_model::{Airspeed, PlaneFlight, Thrust},
+    plane::{attitude, LoadFactor, Plane},
+};
+
+pub struct RecorderPlugin;
+
+impl Plugin for RecorderPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, (record_telemetry, capture_crash_report).chain());
+    }
+}
+
+/// Length of flight history kept by the recorder in seconds.
+const RECORD_DURATION: f32 = 10.0;
+
+#[derive(Clone, Copy, Default)]
+pub struct TelemetrySample {
+    pub time: f32,
+    pub airspeed: f32,
+    pub altitude: f32,
+    pub vertical_speed: f32,
+    /// Acceleration along the plane's up axis in multiples of gravity.
+    pub load_factor: f32,
+    pub pitch: f32,
+    pub roll: f32,
+    pub thrust: f32,
+    pub drag: f32,
+}
+
+#[derive(Component, Default)]
+pub struct FlightRecorder {
+    pub samples: VecDeque<TelemetrySample>,
+}
+
+/// Snapshot of the flight recorder taken when the plane crashed.
+#[derive(Resource)]
+pub struct CrashReport {
+    pub impact_speed: f32,
+    pub samples: Vec<TelemetrySample>,
+}
+
+impl CrashReport {
+    pub fn last_sample(&self) -> TelemetrySample {
+        self.samples.last().copied().unwrap_or_default()
+    }
+
+    /// Largest load factor in the final second before impact.
+    pub fn peak_load_factor(&self) -> f32 {
+        let end = self.last_sample().time;
+
+        self.samples
+            .iter()
+            .filter(|sample| end - sample.time <= 1.0)
+            .map(|sample| sample.load_factor)
+            .fold(
+                0.0,
+                |peak: f32, g| if g.abs() > peak.abs() { g } else { peak },
+            )
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn record_telemetry(
+    time: Res<Time>,
+    mut plane_query: Query<
+        (
+            &GlobalTransform,
+            &Velocity,
+            &Airspeed,
+            &Thrust,
+            &PlaneFlight,
+            &LoadFactor,
+            &mut FlightRecorder,
+        ),
+        With<Plane>,
+    >,
+) {
+    if time.delta_seconds() <= 0.0 {
+        return;
+    }
+
+    for (
+        global_tx,
+        velocity,
+        Airspeed(airspeed),
+        Thrust(thrust),
+        flight,
+        load_factor,
+        mut recorder,
+    ) in plane_query.iter_mut()
+    {
+        let (pitch, roll) = attitude(global_tx);
+
+        let sample = TelemetrySample {
+            time: time.elapsed_seconds(),
+            airspeed: *airspeed,
+            altitude: global_tx.translation().y,
+            vertical_speed: velocity.linvel.y,
+            load_factor: load_factor.g,
+            pitch,
+            roll,
+            thrust: *thrust,
+            drag: flight.drag,
+        };
+
+        recorder.samples.push_back(sample);
+
+        while recorder
+            .samples
+            .front()
+            .is_some_and(|oldest| sample.time - oldest.time > RECORD_DURATION)
+        {
+            recorder.samples.pop_front();
+        }
+    }
+}
+
+fn capture_crash_report(
+    mut commands: Commands,
+    mut crashed_event: EventReader<CrashedEvent>,
+    recorder_query: Query<&FlightRecorder, With<Plane>>,
+) {
+    for CrashedEvent { impact_speed } in crashed_event.iter() {
+        let Ok(recorder) = recorder_query.get_single() else {
+            continue;
+        };
+
+        commands.insert_resource(CrashReport {
+            impact_speed: *impact_speed,
+            samples: recorder.samples.iter().copied().collect(),
+        });
+    }
+}
@@ -0,0 +1,481 @@
+use std::{
+    fs,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
+
+use bevy::{ecs::query::Has, prelude::*, utils::HashMap};
+use bevy_rapier3d::prelude::*;
+use rhai::{CallFnOptions, Dynamic, Engine, EvalAltResult, Map, Scope, AST, FLOAT, INT};
+
+use crate::{
+    damage::Crashed,
+    flight_model::{Airspeed, PitotStatic},
+    plane::Plane,
+    world::{compass_heading, TimeOfDay, Weather, WeatherPreset, WorldOrigin},
+};
+
+pub struct ScenarioPlugin;
+
+impl Plugin for ScenarioPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Scenarios>()
+            .add_event::<RunScenarioEvent>()
+            .add_event::<StopScenarioEvent>()
+            .add_event::<ScenarioMessageEvent>()
+            .add_systems(Startup, setup_scenarios)
+            .add_systems(Update, (start_scenario, run_scenario).chain());
+    }
+}
+
+pub const SCENARIOS_DIR: &str = "scenarios";
+/// Operations a script may run in one call before it's stopped, so a runaway loop can't hang the
+/// sim.
+const MAX_OPERATIONS: u64 = 1_000_000;
+
+type ScriptResult<T> = Result<T, Box<EvalAltResult>>;
+
+#[derive(Event)]
+pub struct RunScenarioEvent(pub PathBuf);
+
+#[derive(Event)]
+pub struct StopScenarioEvent;
+
+/// Message from the running scenario to show the pilot.
+#[derive(Event)]
+pub struct ScenarioMessageEvent(pub String);
+
+/// Change to the world asked for by a script, applied once it returns.
+enum ScenarioCommand {
+    Message(String),
+    SetWeather(WeatherPreset),
+    SetWind(Vec3),
+    SetTimeOfDay(f32),
+    BlockPitot(bool),
+    BlockStatic(bool),
+    SpawnBox { id: INT, position: Vec3, size: f32 },
+    Despawn(INT),
+    End,
+}
+
+#[derive(Clone, Copy)]
+struct PlaneState {
+    /// Absolute position, so scripts place things in the same spot wherever the origin is.
+    position: Vec3,
+    airspeed: f32,
+    heading: f32,
+    crashed: bool,
+}
+
+/// State shared with the functions registered with the engine, which read the plane from it and
+/// queue their changes to the world on it.
+#[derive(Default)]
+struct ScriptState {
+    plane: Option<PlaneState>,
+    elapsed: f32,
+    commands: Vec<ScenarioCommand>,
+    next_object: INT,
+}
+
+struct RunningScenario {
+    name: String,
+    ast: AST,
+    scope: Scope<'static>,
+    /// Whether the top level of the script has run yet.
+    started: bool,
+    /// Map bound to `this` in `update`, which can't see the variables of the top level, to keep
+    /// values between frames.
+    memory: Dynamic,
+    /// Objects the script spawned, by the id it was given for each.
+    objects: HashMap<INT, Entity>,
+}
+
+/// Scripts found in the scenarios folder, and the one running.
+#[derive(Resource)]
+pub struct Scenarios {
+    engine: Engine,
+    state: Arc<Mutex<ScriptState>>,
+    pub available: Vec<PathBuf>,
+    running: Option<RunningScenario>,
+}
+
+impl Default for Scenarios {
+    fn default() -> Self {
+        let state = Arc::default();
+        Self {
+            engine: build_engine(&state),
+            state,
+            available: find_scenarios(),
+            running: None,
+        }
+    }
+}
+
+impl Scenarios {
+    /// Name of the running scenario, if there is one.
+    pub fn running(&self) -> Option<&str> {
+        self.running.as_ref().map(|scenario| scenario.name.as_str())
+    }
+
+    /// Time since the running scenario started.
+    pub fn elapsed(&self) -> f32 {
+        self.state.lock().unwrap().elapsed
+    }
+
+    /// Looks for scripts added to or removed from the scenarios folder.
+    pub fn rescan(&mut self) {
+        self.available = find_scenarios();
+    }
+
+    /// Stops the running scenario, removing everything it spawned.
+    fn stop(&mut self, commands: &mut Commands) {
+        let Some(scenario) = self.running.take() else {
+            return;
+        };
+        info!("Stopped scenario {}", scenario.name);
+        for entity in scenario.objects.into_values() {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct ScenarioObject;
+
+#[derive(Resource)]
+pub struct ScenarioAssets {
+    material: Handle<StandardMaterial>,
+}
+
+fn setup_scenarios(mut commands: Commands, mut materials: ResMut<Assets<StandardMaterial>>) {
+    commands.insert_resource(ScenarioAssets {
+        material: materials.add(Color::rgb(0.9, 0.45, 0.1).into()),
+    });
+}
+
+/// Rhai scripts in the scenarios folder, sorted by name.
+fn find_scenarios() -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(SCENARIOS_DIR) else {
+        return vec![];
+    };
+    let mut paths: Vec<_> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "rhai"))
+        .collect();
+    paths.sort();
+    paths
+}
+
+/// Number from a script, which may be written either as an integer or with a decimal point.
+fn number(value: &Dynamic) -> ScriptResult<f32> {
+    value
+        .as_float()
+        .map(|value| value as f32)
+        .or_else(|_| value.as_int().map(|value| value as f32))
+        .map_err(|type_name| format!("expected a number but got {}", type_name).into())
+}
+
+/// Engine with the functions scripts use to read the plane and change the world.
+///
+/// Scripts work in absolute positions with x to the east, y up and z to the south, and headings
+/// in compass degrees.
+fn build_engine(state: &Arc<Mutex<ScriptState>>) -> Engine {
+    let mut engine = Engine::new();
+    engine.set_max_operations(MAX_OPERATIONS);
+    engine.on_print(|text| info!("Scenario: {}", text));
+
+    let queue = |state: &Arc<Mutex<ScriptState>>| {
+        let state = state.clone();
+        move |command| state.lock().unwrap().commands.push(command)
+    };
+    let plane = |state: &Arc<Mutex<ScriptState>>| {
+        let state = state.clone();
+        move || -> ScriptResult<PlaneState> {
+            state
+                .lock()
+                .unwrap()
+                .plane
+                .ok_or_else(|| "there's no plane".into())
+        }
+    };
+
+    let push = queue(state);
+    engine.register_fn("message", move |text: &str| {
+        push(ScenarioCommand::Message(text.to_string()))
+    });
+
+    let push = queue(state);
+    engine.register_fn("set_weather", move |name: &str| -> ScriptResult<()> {
+        let preset = WeatherPreset::ALL
+            .into_iter()
+            .find(|preset| format!("{:?}", preset).eq_ignore_ascii_case(name))
+            .ok_or_else(|| format!("unknown weather '{}'", name))?;
+        push(ScenarioCommand::SetWeather(preset));
+        Ok(())
+    });
+
+    // Wind is given as the compass heading it blows from, like a weather report
+    let push = queue(state);
+    engine.register_fn(
+        "set_wind",
+        move |direction: Dynamic, speed: Dynamic| -> ScriptResult<()> {
+            let wind = Weather::wind_from(number(&direction)?, number(&speed)?);
+            push(ScenarioCommand::SetWind(wind));
+            Ok(())
+        },
+    );
+
+    let push = queue(state);
+    engine.register_fn(
+        "set_time_of_day",
+        move |hour: Dynamic| -> ScriptResult<()> {
+            push(ScenarioCommand::SetTimeOfDay(
+                number(&hour)?.rem_euclid(24.0),
+            ));
+            Ok(())
+        },
+    );
+
+    // Failures of the plane's instruments, to practise flying on the others
+    let push = queue(state);
+    engine.register_fn("block_pitot", move |blocked: bool| {
+        push(ScenarioCommand::BlockPitot(blocked))
+    });
+
+    let push = queue(state);
+    engine.register_fn("block_static", move |blocked: bool| {
+        push(ScenarioCommand::BlockStatic(blocked))
+    });
+
+    let spawn_state = state.clone();
+    engine.register_fn(
+        "spawn_box",
+        move |x: Dynamic, y: Dynamic, z: Dynamic, size: Dynamic| -> ScriptResult<INT> {
+            let position = Vec3::new(number(&x)?, number(&y)?, number(&z)?);
+            let size = number(&size)?;
+            if size <= 0.0 {
+                return Err("box size must be positive".into());
+            }
+
+            let mut state = spawn_state.lock().unwrap();
+            state.next_object += 1;
+            let id = state.next_object;
+            state
+                .commands
+                .push(ScenarioCommand::SpawnBox { id, position, size });
+            Ok(id)
+        },
+    );
+
+    let push = queue(state);
+    engine.register_fn("despawn", move |id: INT| push(ScenarioCommand::Despawn(id)));
+
+    let push = queue(state);
+    engine.register_fn("end_scenario", move || push(ScenarioCommand::End));
+
+    let elapsed_state = state.clone();
+    engine.register_fn("elapsed", move || {
+        elapsed_state.lock().unwrap().elapsed as FLOAT
+    });
+
+    let get = plane(state);
+    engine.register_fn("plane_position", move || -> ScriptResult<Map> {
+        let position = get()?.position;
+        Ok(Map::from([
+            ("x".into(), Dynamic::from_float(position.x as FLOAT)),
+            ("y".into(), Dynamic::from_float(position.y as FLOAT)),
+            ("z".into(), Dynamic::from_float(position.z as FLOAT)),
+        ]))
+    });
+
+    let get = plane(state);
+    engine.register_fn("altitude", move || -> ScriptResult<FLOAT> {
+        Ok(get()?.position.y as FLOAT)
+    });
+
+    let get = plane(state);
+    engine.register_fn("airspeed", move || -> ScriptResult<FLOAT> {
+        Ok(get()?.airspeed as FLOAT)
+    });
+
+    let get = plane(state);
+    engine.register_fn("heading", move || -> ScriptResult<FLOAT> {
+        Ok(get()?.heading as FLOAT)
+    });
+
+    let get = plane(state);
+    engine.register_fn("crashed", move || -> ScriptResult<bool> {
+        Ok(get()?.crashed)
+    });
+
+    engine
+}
+
+/// Compiles a scenario to run, in place of any already running.
+fn start_scenario(
+    mut commands: Commands,
+    mut scenarios: ResMut<Scenarios>,
+    mut run_events: EventReader<RunScenarioEvent>,
+    mut stop_events: EventReader<StopScenarioEvent>,
+    mut message_events: EventWriter<ScenarioMessageEvent>,
+) {
+    let stop = stop_events.iter().count() > 0;
+    let run = run_events.iter().last();
+    if !stop && run.is_none() {
+        return;
+    }
+
+    scenarios.stop(&mut commands);
+    let Some(RunScenarioEvent(path)) = run else {
+        return;
+    };
+
+    let name = path.file_stem().map_or_else(
+        || path.display().to_string(),
+        |stem| stem.to_string_lossy().to_string(),
+    );
+    match scenarios.engine.compile_file(path.clone()) {
+        Ok(ast) => {
+            info!("Running scenario {}", name);
+            *scenarios.state.lock().unwrap() = ScriptState::default();
+            scenarios.running = Some(RunningScenario {
+                name,
+                ast,
+                scope: Scope::new(),
+                started: false,
+                memory: Map::new().into(),
+                objects: HashMap::default(),
+            });
+        }
+        Err(err) => {
+            warn!("Failed to load scenario {}: {}", path.display(), err);
+            message_events.send(ScenarioMessageEvent(format!(
+                "Failed to load {}: {}",
+                name, err
+            )));
+        }
+    }
+}
+
+/// Runs the top level of the scenario's script when it starts, and its `update` function, if it
+/// has one, each frame after. The changes the script asked for are made once it returns, and a
+/// script that fails is stopped.
+#[allow(clippy::too_many_arguments)]
+fn run_scenario(
+    mut commands: Commands,
+    time: Res<Time>,
+    origin: Res<WorldOrigin>,
+    assets: Res<ScenarioAssets>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut scenarios: ResMut<Scenarios>,
+    mut weather: ResMut<Weather>,
+    mut time_of_day: ResMut<TimeOfDay>,
+    plane_query: Query<(&Transform, &Airspeed, Has<Crashed>), With<Plane>>,
+    mut pitot_static_query: Query<&mut PitotStatic, With<Plane>>,
+    mut message_events: EventWriter<ScenarioMessageEvent>,
+) {
+    let Scenarios {
+        engine,
+        state,
+        running: Some(scenario),
+        ..
+    } = &mut *scenarios
+    else {
+        return;
+    };
+
+    {
+        let mut state = state.lock().unwrap();
+        state.plane = plane_query
+            .get_single()
+            .ok()
+            .map(|(tx, airspeed, crashed)| PlaneState {
+                position: origin.to_absolute(tx.translation),
+                airspeed: airspeed.0,
+                heading: compass_heading(tx.forward()),
+                crashed,
+            });
+        if scenario.started {
+            state.elapsed += time.delta_seconds();
+        }
+    }
+
+    let result = if !scenario.started {
+        scenario.started = true;
+        engine.run_ast_with_scope(&mut scenario.scope, &scenario.ast)
+    } else if scenario
+        .ast
+        .iter_functions()
+        .any(|f| f.name == "update" && f.params.len() == 1)
+    {
+        engine
+            .call_fn_with_options::<Dynamic>(
+                CallFnOptions::new()
+                    .eval_ast(false)
+                    .bind_this_ptr(&mut scenario.memory),
+                &mut scenario.scope,
+                &scenario.ast,
+                "update",
+                (time.delta_seconds() as FLOAT,),
+            )
+            .map(|_| ())
+    } else {
+        Ok(())
+    };
+
+    let mut end = false;
+    for command in std::mem::take(&mut state.lock().unwrap().commands) {
+        match command {
+            ScenarioCommand::Message(text) => {
+                info!("{}: {}", scenario.name, text);
+                message_events.send(ScenarioMessageEvent(text));
+            }
+            ScenarioCommand::SetWeather(preset) => weather.preset = preset,
+            ScenarioCommand::SetWind(wind) => weather.wind = wind,
+            ScenarioCommand::SetTimeOfDay(hour) => time_of_day.hour = hour,
+            ScenarioCommand::BlockPitot(blocked) => {
+                for mut pitot_static in pitot_static_query.iter_mut() {
+                    pitot_static.pitot_blocked = blocked;
+                }
+            }
+            ScenarioCommand::BlockStatic(blocked) => {
+                for mut pitot_static in pitot_static_query.iter_mut() {
+                    pitot_static.static_blocked = blocked;
+                }
+            }
+            ScenarioCommand::SpawnBox { id, position, size } => {
+                let entity = commands
+                    .spawn((
+                        ScenarioObject,
+                        PbrBundle {
+                            mesh: meshes.add(Mesh::from(shape::Cube { size })),
+                            material: assets.material.clone(),
+                            transform: Transform::from_translation(origin.to_local(position)),
+                            ..default()
+                        },
+                        Collider::cuboid(size * 0.5, size * 0.5, size * 0.5),
+                    ))
+                    .id();
+                scenario.objects.insert(id, entity);
+            }
+            ScenarioCommand::Despawn(id) => {
+                if let Some(entity) = scenario.objects.remove(&id) {
+                    commands.entity(entity).despawn_recursive();
+                }
+            }
+            ScenarioCommand::End => end = true,
+        }
+    }
+
+    if let Err(err) = result {
+        warn!("Scenario {} failed: {}", scenario.name, err);
+        message_events.send(ScenarioMessageEvent(format!(
+            "{} stopped: {}",
+            scenario.name, err
+        )));
+        end = true;
+    }
+    if end {
+        scenarios.stop(&mut commands);
+    }
+}
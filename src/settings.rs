@@ -0,0 +1,187 @@
+use std::fs;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    camera::FogControl,
+    world::{update_visibility, GizmosControl, TimeOfDay, Weather, WeatherPreset},
+};
+
+const ENVIRONMENT_PATH: &str = "environment.ron";
+
+pub struct SettingsPlugin;
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(load_environment_presets().unwrap_or_default())
+            .add_event::<ApplyEnvironmentEvent>()
+            .add_systems(Startup, apply_startup_preset)
+            .add_systems(
+                Update,
+                // The fog is reset to suit the weather, so the preset's visibility goes on after
+                apply_environment_preset.after(update_visibility),
+            );
+    }
+}
+
+/// Environment saved under a name from the Environment window, so it doesn't need setting up again
+/// every run. The sun follows the time of day.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct EnvironmentPreset {
+    pub name: String,
+    pub weather: WeatherPreset,
+    /// Steady wind in m/s, calm in presets saved before it was added.
+    #[serde(default)]
+    pub wind: Vec3,
+    /// Distance in metres at which objects fade into the fog.
+    pub visibility: f32,
+    pub hour: f32,
+    pub day_length: f32,
+    pub time_paused: bool,
+    pub gizmos: bool,
+}
+
+impl EnvironmentPreset {
+    pub fn capture(
+        name: String,
+        weather: &Weather,
+        fog_control: &FogControl,
+        time_of_day: &TimeOfDay,
+        gizmos_control: &GizmosControl,
+    ) -> Self {
+        Self {
+            name,
+            weather: weather.preset,
+            wind: weather.wind,
+            visibility: fog_control.visibility,
+            hour: time_of_day.hour,
+            day_length: time_of_day.day_length,
+            time_paused: time_of_day.paused,
+            gizmos: gizmos_control.show,
+        }
+    }
+}
+
+/// Presets saved from the Environment window, and the one restored at startup.
+#[derive(Resource, Default, Serialize, Deserialize)]
+pub struct EnvironmentPresets {
+    pub presets: Vec<EnvironmentPreset>,
+    /// Name of the preset restored at startup, if any.
+    pub startup: Option<String>,
+}
+
+impl EnvironmentPresets {
+    pub fn get(&self, name: &str) -> Option<&EnvironmentPreset> {
+        self.presets.iter().find(|preset| preset.name == name)
+    }
+
+    /// Adds a preset, replacing any already saved with the same name.
+    pub fn save(&mut self, preset: EnvironmentPreset) {
+        match self.presets.iter_mut().find(|p| p.name == preset.name) {
+            Some(existing) => *existing = preset,
+            None => self.presets.push(preset),
+        }
+        save_environment_presets(self);
+    }
+
+    pub fn remove(&mut self, name: &str) {
+        self.presets.retain(|preset| preset.name != name);
+        if self.startup.as_deref() == Some(name) {
+            self.startup = None;
+        }
+        save_environment_presets(self);
+    }
+
+    pub fn set_startup(&mut self, name: Option<String>) {
+        self.startup = name;
+        save_environment_presets(self);
+    }
+}
+
+/// Sent to restore a saved environment.
+#[derive(Event)]
+pub struct ApplyEnvironmentEvent(pub EnvironmentPreset);
+
+fn apply_startup_preset(
+    presets: Res<EnvironmentPresets>,
+    mut apply_events: EventWriter<ApplyEnvironmentEvent>,
+) {
+    let Some(preset) = presets
+        .startup
+        .as_deref()
+        .and_then(|name| presets.get(name))
+    else {
+        return;
+    };
+
+    info!("Restoring environment preset {}", preset.name);
+    apply_events.send(ApplyEnvironmentEvent(preset.clone()));
+}
+
+fn apply_environment_preset(
+    mut apply_events: EventReader<ApplyEnvironmentEvent>,
+    mut weather: ResMut<Weather>,
+    mut time_of_day: ResMut<TimeOfDay>,
+    mut gizmos_control: ResMut<GizmosControl>,
+    mut fog_query: Query<&mut FogControl>,
+    // A new weather preset resets the fog on the next frame, so the visibility is set again after it
+    mut pending_visibility: Local<Option<f32>>,
+) {
+    let mut visibility = pending_visibility.take();
+
+    for ApplyEnvironmentEvent(preset) in apply_events.iter() {
+        if weather.preset != preset.weather {
+            weather.preset = preset.weather;
+            *pending_visibility = Some(preset.visibility);
+        }
+        if weather.wind != preset.wind {
+            weather.wind = preset.wind;
+        }
+        *time_of_day = TimeOfDay {
+            hour: preset.hour,
+            day_length: preset.day_length,
+            paused: preset.time_paused,
+        };
+        gizmos_control.show = preset.gizmos;
+        visibility = Some(preset.visibility);
+    }
+
+    if let Some(visibility) = visibility {
+        for mut fog_control in fog_query.iter_mut() {
+            fog_control.visibility = visibility;
+        }
+    }
+}
+
+fn load_environment_presets() -> Option<EnvironmentPresets> {
+    let contents = fs::read_to_string(ENVIRONMENT_PATH).ok()?;
+
+    match ron::from_str(&contents) {
+        Ok(presets) => {
+            info!("Loaded environment presets from {}", ENVIRONMENT_PATH);
+            Some(presets)
+        }
+        Err(err) => {
+            warn!(
+                "Ignoring invalid environment presets in {}: {}",
+                ENVIRONMENT_PATH, err
+            );
+            None
+        }
+    }
+}
+
+fn save_environment_presets(presets: &EnvironmentPresets) {
+    let result = ron::ser::to_string_pretty(presets, ron::ser::PrettyConfig::default())
+        .map_err(|err| err.to_string())
+        .and_then(|contents| fs::write(ENVIRONMENT_PATH, contents).map_err(|err| err.to_string()));
+
+    match result {
+        Ok(()) => info!("Saved environment presets to {}", ENVIRONMENT_PATH),
+        Err(err) => warn!(
+            "Failed to save environment presets to {}: {}",
+            ENVIRONMENT_PATH, err
+        ),
+    }
+}
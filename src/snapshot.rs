@@ -0,0 +1,212 @@
+use std::fs;
+
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use leafwing_input_manager::prelude::ActionState;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    flight_model::Thrust,
+    input::PlaneAction,
+    plane::{spec::PlaneSpec, BuildPlaneEvent, Plane, PlaneControl, PlaneSet, PropellorPitch},
+    recorder::CrashReport,
+    world::WorldOrigin,
+};
+
+pub struct SnapshotPlugin;
+
+impl Plugin for SnapshotPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SpecSlots>().add_systems(
+            Update,
+            (handle_snapshot_input, restore_snapshot)
+                .chain()
+                .after(PlaneSet::Build),
+        );
+    }
+}
+
+const SNAPSHOT_PATH: &str = "quicksave.ron";
+pub const SLOT_NAMES: [&str; 2] = ["A", "B"];
+
+/// Two designs to fly back to back, swapped between in the same state to compare how they handle.
+#[derive(Resource, Default)]
+pub struct SpecSlots {
+    pub specs: [Option<PlaneSpec>; 2],
+    /// Slot the flying plane was last swapped to.
+    pub active: usize,
+}
+
+/// State needed to put a plane back exactly where it was.
+#[derive(Serialize, Deserialize)]
+struct Snapshot {
+    spec: PlaneSpec,
+    /// Absolute position, so it doesn't depend on where the origin was when saved.
+    translation: Vec3,
+    rotation: Quat,
+    linvel: Vec3,
+    angvel: Vec3,
+    thrust: f32,
+    ailerons: f32,
+    elevators: f32,
+    rudder: f32,
+}
+
+impl Snapshot {
+    fn capture(
+        spec: PlaneSpec,
+        tx: &Transform,
+        velocity: &Velocity,
+        thrust: f32,
+        control: &PlaneControl,
+        origin: &WorldOrigin,
+    ) -> Self {
+        Self {
+            spec,
+            translation: origin.to_absolute(tx.translation),
+            rotation: tx.rotation,
+            linvel: velocity.linvel,
+            angvel: velocity.angvel,
+            thrust,
+            ailerons: control.ailerons,
+            elevators: control.elevators,
+            rudder: control.rudder,
+        }
+    }
+}
+
+/// Snapshot waiting to be applied to the plane rebuilt from its spec.
+#[derive(Resource)]
+struct PendingRestore(Snapshot);
+
+fn handle_snapshot_input(
+    mut commands: Commands,
+    action_query: Query<&ActionState<PlaneAction>>,
+    plane_query: Query<(&PlaneSpec, &Transform, &Velocity, &Thrust, &PlaneControl), With<Plane>>,
+    mut build_plane_event: EventWriter<BuildPlaneEvent>,
+    mut slots: ResMut<SpecSlots>,
+    origin: Res<WorldOrigin>,
+) {
+    let Ok(action_state) = action_query.get_single() else {
+        return;
+    };
+
+    if action_state.just_pressed(PlaneAction::QuickSave) {
+        let Ok((spec, tx, velocity, Thrust(thrust), control)) = plane_query.get_single() else {
+            return;
+        };
+
+        save_snapshot(&Snapshot::capture(
+            spec.clone(),
+            tx,
+            velocity,
+            *thrust,
+            control,
+            &origin,
+        ));
+    }
+
+    // The other design is built just as this one is flying, so only the handling changes
+    if action_state.just_pressed(PlaneAction::SwapSpec) {
+        let Ok((spec, tx, velocity, Thrust(thrust), control)) = plane_query.get_single() else {
+            return;
+        };
+        let other = 1 - slots.active;
+        let Some(next) = slots.specs[other].clone() else {
+            info!("Nothing stored in slot {} to swap to", SLOT_NAMES[other]);
+            return;
+        };
+        // Keep the flying design to swap back to, unless something was stored in its place
+        let active = slots.active;
+        slots.specs[active].get_or_insert_with(|| spec.clone());
+        slots.active = other;
+
+        info!("Swapping to slot {}: {}", SLOT_NAMES[other], next.name);
+        build_plane_event.send(BuildPlaneEvent(next.clone()));
+        commands.insert_resource(PendingRestore(Snapshot::capture(
+            next, tx, velocity, *thrust, control, &origin,
+        )));
+        commands.remove_resource::<CrashReport>();
+    }
+
+    if action_state.just_pressed(PlaneAction::QuickLoad) {
+        let Some(snapshot) = load_snapshot() else {
+            return;
+        };
+
+        // Rebuilding from the spec also repairs any damage and clears the crash
+        build_plane_event.send(BuildPlaneEvent(snapshot.spec.clone()));
+        commands.insert_resource(PendingRestore(snapshot));
+        commands.remove_resource::<CrashReport>();
+    }
+}
+
+fn restore_snapshot(
+    mut commands: Commands,
+    pending: Option<Res<PendingRestore>>,
+    mut plane_query: Query<
+        (
+            &mut Transform,
+            &mut Velocity,
+            &mut Thrust,
+            &mut PlaneControl,
+            &mut PropellorPitch,
+        ),
+        Added<Plane>,
+    >,
+    origin: Res<WorldOrigin>,
+) {
+    let Some(PendingRestore(snapshot)) = pending.as_deref() else {
+        return;
+    };
+    let Ok((mut tx, mut velocity, mut thrust, mut control, mut pitch)) =
+        plane_query.get_single_mut()
+    else {
+        return;
+    };
+
+    info!("Restoring snapshot at {}", snapshot.translation);
+
+    tx.translation = origin.to_local(snapshot.translation);
+    tx.rotation = snapshot.rotation;
+    velocity.linvel = snapshot.linvel;
+    velocity.angvel = snapshot.angvel;
+    thrust.0 = snapshot.thrust;
+    pitch.reversed = snapshot.thrust < 0.0;
+    control.ailerons = snapshot.ailerons;
+    control.elevators = snapshot.elevators;
+    control.rudder = snapshot.rudder;
+
+    commands.remove_resource::<PendingRestore>();
+}
+
+fn save_snapshot(snapshot: &Snapshot) {
+    let result = ron::ser::to_string_pretty(snapshot, ron::ser::PrettyConfig::default())
+        .map_err(|err| err.to_string())
+        .and_then(|contents| fs::write(SNAPSHOT_PATH, contents).map_err(|err| err.to_string()));
+
+    match result {
+        Ok(()) => info!("Saved snapshot to {}", SNAPSHOT_PATH),
+        Err(err) => warn!("Failed to save snapshot to {}: {}", SNAPSHOT_PATH, err),
+    }
+}
+
+fn load_snapshot() -> Option<Snapshot> {
+    let contents = fs::read_to_string(SNAPSHOT_PATH)
+        .map_err(|err| warn!("No snapshot to load from {}: {}", SNAPSHOT_PATH, err))
+        .ok()?;
+
+    let snapshot: Snapshot = ron::from_str(&contents)
+        .map_err(|err| warn!("Ignoring invalid snapshot in {}: {}", SNAPSHOT_PATH, err))
+        .ok()?;
+
+    // The plane would never be built, leaving the restore waiting for it
+    if let Err(errors) = snapshot.spec.validate() {
+        for error in errors {
+            warn!("Ignoring snapshot in {}: {}", SNAPSHOT_PATH, error);
+        }
+        return None;
+    }
+
+    Some(snapshot)
+}
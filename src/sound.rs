@@ -0,0 +1,229 @@
+mod music;
+
+use std::{
+    f32::consts::TAU,
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc,
+    },
+};
+
+use bevy::{
+    audio::{AddAudioSource, Source},
+    prelude::*,
+    reflect::{TypePath, TypeUuid},
+    utils::Duration,
+};
+
+use crate::{
+    flight_model::Thrust,
+    plane::{spec::PlaneSpec, Plane},
+};
+
+use self::music::MusicPlugin;
+
+pub use self::music::MusicSettings;
+
+pub struct SoundPlugin;
+
+impl Plugin for SoundPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_audio_source::<EngineSound>()
+            .add_plugins(MusicPlugin)
+            .add_systems(Update, (start_engine_sound, update_engine_sound));
+    }
+}
+
+const SAMPLE_RATE: u32 = 44_100;
+/// Number of harmonics of the cam frequency summed for the engine note.
+const HARMONICS: usize = 24;
+/// Gain applied to harmonics at multiples of the firing frequency.
+const FIRING_GAIN: f32 = 3.0;
+const NOISE_LEVEL: f32 = 0.05;
+/// Samples between reads of the shared engine state.
+const CONTROL_INTERVAL: u32 = 64;
+/// Rate at which the synthesized rpm follows the engine, to avoid steps in pitch.
+const RPM_SMOOTHING: f32 = 0.02;
+
+/// Engine state shared between the game and the audio thread.
+#[derive(Clone, Default)]
+pub struct EngineSoundControl {
+    rpm: Arc<AtomicU32>,
+    load: Arc<AtomicU32>,
+}
+
+impl EngineSoundControl {
+    fn set(&self, rpm: f32, load: f32) {
+        self.rpm.store(rpm.to_bits(), Ordering::Relaxed);
+        self.load.store(load.to_bits(), Ordering::Relaxed);
+    }
+
+    fn get(&self) -> (f32, f32) {
+        (
+            f32::from_bits(self.rpm.load(Ordering::Relaxed)),
+            f32::from_bits(self.load.load(Ordering::Relaxed)),
+        )
+    }
+}
+
+/// Procedurally synthesized engine note, built from harmonics of the engine's cycle.
+#[derive(TypeUuid, TypePath)]
+#[uuid = "5d9b5a8e-3e7c-4d0f-9a57-2f0f3c6e9b41"]
+pub struct EngineSound {
+    cylinders: u32,
+    control: EngineSoundControl,
+}
+
+#[derive(Component)]
+pub struct EngineSoundEmitter {
+    control: EngineSoundControl,
+}
+
+pub struct EngineSoundDecoder {
+    cylinders: u32,
+    control: EngineSoundControl,
+    phases: [f32; HARMONICS],
+    rpm: f32,
+    target_rpm: f32,
+    load: f32,
+    sample: u32,
+    noise_seed: u32,
+}
+
+impl Decodable for EngineSound {
+    type DecoderItem = f32;
+    type Decoder = EngineSoundDecoder;
+
+    fn decoder(&self) -> Self::Decoder {
+        EngineSoundDecoder {
+            cylinders: self.cylinders.max(1),
+            control: self.control.clone(),
+            phases: [0.0; HARMONICS],
+            rpm: 0.0,
+            target_rpm: 0.0,
+            load: 0.0,
+            sample: 0,
+            noise_seed: 1,
+        }
+    }
+}
+
+impl EngineSoundDecoder {
+    fn noise(&mut self) -> f32 {
+        // Cheap xorshift noise for the combustion rasp
+        self.noise_seed ^= self.noise_seed << 13;
+        self.noise_seed ^= self.noise_seed >> 17;
+        self.noise_seed ^= self.noise_seed << 5;
+        self.noise_seed as f32 / u32::MAX as f32 * 2.0 - 1.0
+    }
+}
+
+impl Iterator for EngineSoundDecoder {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.sample.is_multiple_of(CONTROL_INTERVAL) {
+            (self.target_rpm, self.load) = self.control.get();
+        }
+        self.sample = self.sample.wrapping_add(1);
+        self.rpm += (self.target_rpm - self.rpm) * RPM_SMOOTHING / CONTROL_INTERVAL as f32;
+
+        // A four stroke engine fires each cylinder once every two revolutions, so harmonics are
+        // multiples of half the crank frequency with the firing frequency emphasised
+        let cam_frequency = self.rpm / 60.0 * 0.5;
+        let mut value = 0.0;
+        let mut total_gain = 0.0;
+
+        for (i, phase) in self.phases.iter_mut().enumerate() {
+            let harmonic = (i + 1) as f32;
+            let frequency = cam_frequency * harmonic;
+            if frequency > SAMPLE_RATE as f32 * 0.5 {
+                break;
+            }
+
+            let gain = match (i + 1) as u32 % self.cylinders {
+                0 => FIRING_GAIN,
+                _ => 1.0,
+            } / harmonic;
+
+            *phase = (*phase + frequency / SAMPLE_RATE as f32).fract();
+            value += (*phase * TAU).sin() * gain;
+            total_gain += gain;
+        }
+
+        if total_gain > 0.0 {
+            value /= total_gain;
+        }
+
+        let noise = self.noise() * NOISE_LEVEL * self.load;
+        let volume = 0.3 + self.load * 0.7;
+
+        Some((value + noise) * volume)
+    }
+}
+
+impl Source for EngineSoundDecoder {
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    fn channels(&self) -> u16 {
+        1
+    }
+
+    fn sample_rate(&self) -> u32 {
+        SAMPLE_RATE
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        None
+    }
+}
+
+fn start_engine_sound(
+    mut commands: Commands,
+    plane_query: Query<(Entity, &PlaneSpec), Added<Plane>>,
+    mut engine_sounds: ResMut<Assets<EngineSound>>,
+) {
+    for (entity, spec) in plane_query.iter() {
+        info!(
+            "Starting engine sound for {} cylinder engine",
+            spec.engine.cylinders
+        );
+
+        let control = EngineSoundControl::default();
+        let source = engine_sounds.add(EngineSound {
+            cylinders: spec.engine.cylinders,
+            control: control.clone(),
+        });
+
+        commands.entity(entity).with_children(|parent| {
+            parent.spawn((
+                EngineSoundEmitter { control },
+                SpatialBundle::default(),
+                // The synthesized source never ends, so doesn't need looping
+                AudioSourceBundle {
+                    source,
+                    settings: PlaybackSettings::ONCE,
+                },
+            ));
+        });
+    }
+}
+
+fn update_engine_sound(
+    plane_query: Query<(&Thrust, &PlaneSpec), With<Plane>>,
+    emitter_query: Query<&EngineSoundEmitter>,
+) {
+    let Ok((Thrust(thrust), spec)) = plane_query.get_single() else {
+        return;
+    };
+
+    // Reverse thrust works the engine as hard as forward thrust
+    let load = (thrust.abs() / spec.thrust).clamp(0.0, 1.0);
+    let rpm = spec.engine.rpm(load);
+
+    for emitter in emitter_query.iter() {
+        emitter.control.set(rpm, load);
+    }
+}
@@ -0,0 +1,346 @@
+use std::{
+    f64::consts::{PI, TAU},
+    sync::{
+        atomic::{AtomicBool, AtomicU32, Ordering},
+        Arc,
+    },
+};
+
+use bevy::{
+    audio::{AddAudioSource, Source},
+    ecs::query::Has,
+    prelude::*,
+    reflect::{TypePath, TypeUuid},
+    utils::Duration,
+};
+use bevy_rapier3d::prelude::*;
+
+use crate::{
+    achievements::GameplayEvent, damage::Crashed, flight_model::PlaneFlight, plane::Plane,
+};
+
+pub struct MusicPlugin;
+
+impl Plugin for MusicPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_audio_source::<Music>()
+            .init_resource::<MusicSettings>()
+            .add_systems(Startup, start_music)
+            .add_systems(Update, update_music);
+    }
+}
+
+const SAMPLE_RATE: u32 = 44_100;
+/// Samples between reads of the shared music state.
+const CONTROL_INTERVAL: u64 = 256;
+const VOLUME: f32 = 0.12;
+/// Length of a beat in seconds, at 96 beats a minute.
+const BEAT: f64 = 0.625;
+const BEATS_PER_BAR: usize = 4;
+/// Time to cross fade between the calm and tense music.
+const CROSS_FADE_TIME: f32 = 4.0;
+/// Time to fade out when muted or back in when unmuted.
+const MUTE_FADE_TIME: f32 = 0.5;
+/// Time between the notes of the landing stinger as it climbs the chord.
+const STINGER_NOTE_TIME: f64 = 0.15;
+const STINGER_LENGTH: f64 = 2.5;
+
+/// Chords of the calm music, a bar each, as MIDI notes: C major, A minor, F major, G major.
+const CALM_CHORDS: [[u8; 3]; 4] = [[60, 64, 67], [57, 60, 64], [53, 57, 60], [55, 59, 62]];
+/// Bass line of the tense music, a sixteenth note each, circling A with a tritone.
+const TENSE_BASS: [u8; 8] = [45, 45, 57, 45, 51, 45, 57, 46];
+/// High notes held over the tense bass line, a semitone apart.
+const TENSE_CLUSTER: [u8; 2] = [76, 77];
+const STINGER_NOTES: [u8; 4] = [72, 76, 79, 84];
+
+/// Height above the ground below which flying counts as low level, above the height of a plane
+/// standing on its wheels.
+const LOW_LEVEL_HEIGHT: std::ops::Range<f32> = 5.0..60.0;
+/// Angle of attack approaching the stall of the default wing.
+const NEAR_STALL_ANGLE: f32 = 10.0;
+/// Time the music stays tense after the last reason to be, so it doesn't flick back and forth.
+const TENSE_HOLD_TIME: f32 = 5.0;
+
+#[derive(Resource, Default)]
+pub struct MusicSettings {
+    pub muted: bool,
+}
+
+/// Music state shared between the game and the audio thread.
+#[derive(Clone, Default)]
+pub struct MusicControl {
+    tense: Arc<AtomicBool>,
+    muted: Arc<AtomicBool>,
+    /// Number of stingers asked for so far.
+    stingers: Arc<AtomicU32>,
+}
+
+/// Procedurally synthesized music, cross fading between calm and tense moods with stingers over
+/// the top.
+#[derive(TypeUuid, TypePath)]
+#[uuid = "a3c1e07b-64d2-4b8e-9f1a-7c52d8e4b630"]
+pub struct Music {
+    control: MusicControl,
+}
+
+#[derive(Component)]
+pub struct MusicPlayer {
+    control: MusicControl,
+}
+
+pub struct MusicDecoder {
+    control: MusicControl,
+    sample: u64,
+    /// Mix between the calm music at 0 and the tense music at 1.
+    tension: f32,
+    target_tension: f32,
+    volume: f32,
+    target_volume: f32,
+    stingers_played: u32,
+    stinger_start: Option<f64>,
+}
+
+impl Decodable for Music {
+    type DecoderItem = f32;
+    type Decoder = MusicDecoder;
+
+    fn decoder(&self) -> Self::Decoder {
+        MusicDecoder {
+            control: self.control.clone(),
+            sample: 0,
+            tension: 0.0,
+            target_tension: 0.0,
+            volume: 0.0,
+            target_volume: 0.0,
+            stingers_played: 0,
+            stinger_start: None,
+        }
+    }
+}
+
+fn frequency(note: u8) -> f64 {
+    440.0 * 2_f64.powf((note as f64 - 69.0) / 12.0)
+}
+
+fn sine(time: f64, frequency: f64) -> f32 {
+    ((time * frequency).fract() * TAU).sin() as f32
+}
+
+fn triangle(time: f64, frequency: f64) -> f32 {
+    ((time * frequency).fract() - 0.5).abs() as f32 * 4.0 - 1.0
+}
+
+/// Envelope of a plucked note, with a short attack so it doesn't click.
+fn pluck(time: f64, decay: f64) -> f32 {
+    ((time / 0.01).min(1.0) * (-time * decay).exp()) as f32
+}
+
+/// Moves a value towards a target by at most a step.
+fn approach(value: f32, target: f32, step: f32) -> f32 {
+    value + (target - value).clamp(-step, step)
+}
+
+impl MusicDecoder {
+    /// Swelling pads under an arpeggio climbing each chord.
+    fn calm(time: f64) -> f32 {
+        let bar_length = BEAT * BEATS_PER_BAR as f64;
+        let bar = (time / bar_length) as usize;
+        let chord = CALM_CHORDS[bar % CALM_CHORDS.len()];
+
+        let swell = ((time % bar_length) / bar_length * PI).sin() as f32;
+        let pad = chord
+            .iter()
+            .map(|note| sine(time, frequency(note - 12)))
+            .sum::<f32>()
+            / chord.len() as f32
+            * swell;
+
+        let beat = (time / BEAT) as usize;
+        let note = frequency(chord[beat % chord.len()] + 12);
+        let arpeggio =
+            (sine(time, note) + sine(time, note * 2.0) * 0.3) * pluck(time % BEAT, 8.0) * 0.5;
+
+        pad * 0.6 + arpeggio
+    }
+
+    /// Driving bass line under a trembling dissonant cluster.
+    fn tense(time: f64) -> f32 {
+        let step_length = BEAT / 4.0;
+        let step = (time / step_length) as usize;
+        let bass = triangle(time, frequency(TENSE_BASS[step % TENSE_BASS.len()]))
+            * pluck(time % step_length, 12.0);
+
+        let tremolo = 0.5 + 0.5 * sine(time, 6.0);
+        let cluster = TENSE_CLUSTER
+            .iter()
+            .map(|note| sine(time, frequency(*note)))
+            .sum::<f32>()
+            / TENSE_CLUSTER.len() as f32
+            * tremolo;
+
+        bass * 0.7 + cluster * 0.25
+    }
+
+    /// Brassy fanfare climbing a major chord and ringing out.
+    fn stinger(time: f64, elapsed: f64) -> f32 {
+        let release = (1.0 - elapsed / STINGER_LENGTH) as f32;
+
+        STINGER_NOTES
+            .iter()
+            .enumerate()
+            .filter_map(|(i, note)| {
+                let note_time = elapsed - i as f64 * STINGER_NOTE_TIME;
+                (note_time >= 0.0).then(|| {
+                    let note = frequency(*note);
+                    let brass = (1..=4)
+                        .map(|harmonic| sine(time, note * harmonic as f64) / harmonic as f32)
+                        .sum::<f32>();
+                    brass * (note_time / 0.02).min(1.0) as f32
+                })
+            })
+            .sum::<f32>()
+            / STINGER_NOTES.len() as f32
+            * release
+    }
+}
+
+impl Iterator for MusicDecoder {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let time = self.sample as f64 / SAMPLE_RATE as f64;
+
+        if self.sample.is_multiple_of(CONTROL_INTERVAL) {
+            self.target_tension = match self.control.tense.load(Ordering::Relaxed) {
+                true => 1.0,
+                false => 0.0,
+            };
+            self.target_volume = match self.control.muted.load(Ordering::Relaxed) {
+                true => 0.0,
+                false => 1.0,
+            };
+
+            let stingers = self.control.stingers.load(Ordering::Relaxed);
+            if stingers != self.stingers_played {
+                self.stingers_played = stingers;
+                self.stinger_start = Some(time);
+            }
+        }
+        self.sample += 1;
+
+        self.tension = approach(
+            self.tension,
+            self.target_tension,
+            1.0 / (CROSS_FADE_TIME * SAMPLE_RATE as f32),
+        );
+        self.volume = approach(
+            self.volume,
+            self.target_volume,
+            1.0 / (MUTE_FADE_TIME * SAMPLE_RATE as f32),
+        );
+
+        let stinger_elapsed = self.stinger_start.map(|start| time - start);
+        if stinger_elapsed.is_some_and(|elapsed| elapsed > STINGER_LENGTH) {
+            self.stinger_start = None;
+        }
+
+        if self.volume <= 0.0 {
+            return Some(0.0);
+        }
+
+        let mut value = Self::calm(time) * (1.0 - self.tension) + Self::tense(time) * self.tension;
+        if let Some(elapsed) = stinger_elapsed {
+            value += Self::stinger(time, elapsed);
+        }
+
+        Some(value * self.volume * VOLUME)
+    }
+}
+
+impl Source for MusicDecoder {
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    fn channels(&self) -> u16 {
+        1
+    }
+
+    fn sample_rate(&self) -> u32 {
+        SAMPLE_RATE
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        None
+    }
+}
+
+fn start_music(mut commands: Commands, mut music: ResMut<Assets<Music>>) {
+    info!("Starting music");
+
+    let control = MusicControl::default();
+    let source = music.add(Music {
+        control: control.clone(),
+    });
+
+    commands.spawn((
+        Name::new("Music"),
+        MusicPlayer { control },
+        // The synthesized source never ends, so doesn't need looping
+        AudioSourceBundle {
+            source,
+            settings: PlaybackSettings::ONCE,
+        },
+    ));
+}
+
+/// Picks the mood of the music from what the plane is doing, with a stinger for each landing.
+fn update_music(
+    time: Res<Time>,
+    settings: Res<MusicSettings>,
+    rapier_context: Res<RapierContext>,
+    plane_query: Query<(&GlobalTransform, &PlaneFlight, Has<Crashed>), With<Plane>>,
+    player_query: Query<&MusicPlayer>,
+    mut gameplay_events: EventReader<GameplayEvent>,
+    mut last_tense: Local<Option<f32>>,
+) {
+    let Ok(player) = player_query.get_single() else {
+        return;
+    };
+    let now = time.elapsed_seconds();
+
+    if let Ok((global_tx, flight, crashed)) = plane_query.get_single() {
+        // Only fixed bodies so the ray doesn't hit the plane itself
+        let height = rapier_context
+            .cast_ray(
+                global_tx.translation(),
+                Vec3::NEG_Y,
+                LOW_LEVEL_HEIGHT.end,
+                true,
+                QueryFilter::only_fixed().exclude_sensors(),
+            )
+            .map_or(f32::INFINITY, |(_, toi)| toi);
+        let airborne = height > LOW_LEVEL_HEIGHT.start;
+        let low_level = LOW_LEVEL_HEIGHT.contains(&height);
+        let near_stall = airborne && flight.angle_of_attack.to_degrees() > NEAR_STALL_ANGLE;
+
+        if !crashed && (low_level || near_stall) {
+            *last_tense = Some(now);
+        }
+    }
+
+    let tense = last_tense.is_some_and(|last| now - last < TENSE_HOLD_TIME);
+    player.control.tense.store(tense, Ordering::Relaxed);
+    player
+        .control
+        .muted
+        .store(settings.muted, Ordering::Relaxed);
+
+    if gameplay_events
+        .iter()
+        .any(|event| matches!(event, GameplayEvent::Landed))
+    {
+        info!("Playing landing stinger");
+        player.control.stingers.fetch_add(1, Ordering::Relaxed);
+    }
+}
@@ -0,0 +1,395 @@
+use bevy::{ecs::query::Has, prelude::*};
+use bevy_rapier3d::prelude::*;
+
+use crate::{
+    achievements::GameplayEvent,
+    damage::Crashed,
+    flight_model::{Airspeed, Thrust},
+    plane::{attitude, spec::PlaneSpec, Altitude, BuildPlaneEvent, Plane},
+    units::{Quantity, Units},
+};
+
+pub struct TutorialPlugin;
+
+impl Plugin for TutorialPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Tutorial>()
+            .add_event::<StartLessonEvent>()
+            .add_event::<AbortLessonEvent>()
+            .add_systems(Update, (start_lesson, update_tutorial).chain());
+    }
+}
+
+/// Farthest the ground is looked for below the plane, above which it counts as far below.
+const MAX_GROUND_DISTANCE: f32 = 1000.0;
+/// Time before touching down that the sink rate is taken from, as the wheels stop it on contact.
+const TOUCHDOWN_WINDOW: f32 = 0.5;
+/// Bank within which the wings count as level.
+const WINGS_LEVEL_ANGLE: f32 = 5.0;
+
+/// What the pilot has to do to finish a step of a lesson.
+#[derive(Clone, Copy, Debug)]
+pub enum Goal {
+    /// Thrust at least a fraction of the plane's maximum.
+    ThrottleAbove(f32),
+    /// Thrust at most a fraction of the plane's maximum.
+    ThrottleBelow(f32),
+    /// Airspeed above a speed in m/s.
+    SpeedAbove(f32),
+    /// Airspeed below a speed in m/s.
+    SpeedBelow(f32),
+    TakeOff,
+    /// Height above the ground in metres.
+    HeightAbove(f32),
+    /// Altitude held within a tolerance in metres of where it was at the start of the step, for a
+    /// time in seconds.
+    HoldAltitude {
+        tolerance: f32,
+        time: f32,
+    },
+    /// Bank to either side within a tolerance of an angle in degrees, held for a time in seconds.
+    Bank {
+        angle: f32,
+        tolerance: f32,
+        time: f32,
+    },
+    WingsLevel,
+    /// Touch down sinking slower than a rate in m/s.
+    Touchdown {
+        max_sink_rate: f32,
+    },
+}
+
+impl Goal {
+    /// Time the goal has to be held for, or zero when reaching it is enough.
+    pub fn hold_time(&self) -> f32 {
+        match self {
+            Self::HoldAltitude { time, .. } | Self::Bank { time, .. } => *time,
+            _ => 0.0,
+        }
+    }
+
+    /// Speed, height or rate the goal is set at, written in the chosen units.
+    fn amount(&self, units: &Units) -> Option<String> {
+        match *self {
+            Self::SpeedAbove(speed) | Self::SpeedBelow(speed) => {
+                Some(units.format(Quantity::Speed, speed, 0))
+            }
+            Self::HeightAbove(height) => Some(units.format(Quantity::Altitude, height, 0)),
+            Self::HoldAltitude { tolerance, .. } => {
+                Some(units.format(Quantity::Altitude, tolerance, 0))
+            }
+            Self::Touchdown { max_sink_rate } => {
+                Some(units.format(Quantity::VerticalSpeed, max_sink_rate, 0))
+            }
+            _ => None,
+        }
+    }
+}
+
+pub struct Step {
+    /// What to do, with `{}` standing for the goal's speed, height or rate.
+    pub instruction: &'static str,
+    pub goal: Goal,
+}
+
+impl Step {
+    /// Instruction with the goal's amount written in the chosen units.
+    pub fn text(&self, units: &Units) -> String {
+        match self.goal.amount(units) {
+            Some(amount) => self.instruction.replacen("{}", &amount, 1),
+            None => self.instruction.to_string(),
+        }
+    }
+}
+
+const TAKEOFF: [Step; 4] = [
+    Step {
+        instruction: "Open the throttle fully by holding A",
+        goal: Goal::ThrottleAbove(0.95),
+    },
+    Step {
+        instruction: "Keep straight down the runway with Q and W as the speed builds past {}",
+        goal: Goal::SpeedAbove(25.0),
+    },
+    Step {
+        instruction: "Raise the nose gently with the Up arrow to lift off",
+        goal: Goal::TakeOff,
+    },
+    Step {
+        instruction: "Climb away to {} above the ground",
+        goal: Goal::HeightAbove(100.0),
+    },
+];
+
+const LEVEL_FLIGHT: [Step; 2] = [
+    Step {
+        instruction: "Climb to {} above the ground",
+        goal: Goal::HeightAbove(200.0),
+    },
+    Step {
+        instruction:
+            "Level off and hold this altitude within {} for 15 s, trading pitch against throttle",
+        goal: Goal::HoldAltitude {
+            tolerance: 10.0,
+            time: 15.0,
+        },
+    },
+];
+
+const TURNS: [Step; 3] = [
+    Step {
+        instruction: "Climb to {} above the ground to give yourself room",
+        goal: Goal::HeightAbove(150.0),
+    },
+    Step {
+        instruction: "Roll into a 30° bank with the Left or Right arrow and hold it within 10° for 10 s, keeping the nose up with the Up arrow",
+        goal: Goal::Bank {
+            angle: 30.0,
+            tolerance: 10.0,
+            time: 10.0,
+        },
+    },
+    Step {
+        instruction: "Roll back to wings level",
+        goal: Goal::WingsLevel,
+    },
+];
+
+const LANDING: [Step; 3] = [
+    Step {
+        instruction: "Line up with a runway and reduce the throttle below 30% with Z",
+        goal: Goal::ThrottleBelow(0.3),
+    },
+    Step {
+        instruction: "Descend towards the runway, letting the speed fall below {}",
+        goal: Goal::SpeedBelow(36.0),
+    },
+    Step {
+        instruction: "Flare just above the runway and touch down sinking slower than {}",
+        goal: Goal::Touchdown { max_sink_rate: 2.0 },
+    },
+];
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Lesson {
+    Takeoff,
+    LevelFlight,
+    Turns,
+    Landing,
+}
+
+impl Lesson {
+    pub const ALL: [Self; 4] = [Self::Takeoff, Self::LevelFlight, Self::Turns, Self::Landing];
+
+    pub fn title(&self) -> &'static str {
+        match self {
+            Self::Takeoff => "Takeoff",
+            Self::LevelFlight => "Level flight",
+            Self::Turns => "Turns",
+            Self::Landing => "Landing",
+        }
+    }
+
+    pub fn steps(&self) -> &'static [Step] {
+        match self {
+            Self::Takeoff => &TAKEOFF,
+            Self::LevelFlight => &LEVEL_FLIGHT,
+            Self::Turns => &TURNS,
+            Self::Landing => &LANDING,
+        }
+    }
+}
+
+#[derive(Event)]
+pub struct StartLessonEvent(pub Lesson);
+
+#[derive(Event)]
+pub struct AbortLessonEvent;
+
+/// Progress through the current lesson.
+#[derive(Resource, Default)]
+pub struct Tutorial {
+    pub lesson: Option<Lesson>,
+    pub step: usize,
+    /// Time the current step's goal has been held for.
+    pub held: f32,
+    /// Altitude at the start of the current step, for goals relative to it.
+    start_altitude: Option<f32>,
+    /// Why the last attempt at the step didn't count, or what happened when the lesson ended.
+    pub feedback: Option<String>,
+    /// Lessons finished since the game started.
+    pub completed: Vec<Lesson>,
+}
+
+impl Tutorial {
+    pub fn current_step(&self) -> Option<&'static Step> {
+        self.lesson.and_then(|lesson| lesson.steps().get(self.step))
+    }
+
+    fn next_step(&mut self) {
+        self.step += 1;
+        self.held = 0.0;
+        self.start_altitude = None;
+        self.feedback = None;
+    }
+
+    fn restart(&mut self) {
+        self.step = 0;
+        self.held = 0.0;
+        self.start_altitude = None;
+        self.feedback = None;
+    }
+}
+
+fn start_lesson(
+    mut start_events: EventReader<StartLessonEvent>,
+    mut abort_events: EventReader<AbortLessonEvent>,
+    mut tutorial: ResMut<Tutorial>,
+    plane_query: Query<&PlaneSpec, With<Plane>>,
+    mut build_plane_event: EventWriter<BuildPlaneEvent>,
+) {
+    if abort_events.iter().count() > 0 {
+        tutorial.lesson = None;
+    }
+    let Some(StartLessonEvent(lesson)) = start_events.iter().last() else {
+        return;
+    };
+
+    info!("Starting lesson {:?}", lesson);
+    tutorial.lesson = Some(*lesson);
+    tutorial.restart();
+
+    // Taking off starts from the runway, so put the plane back on it
+    if *lesson == Lesson::Takeoff {
+        if let Ok(spec) = plane_query.get_single() {
+            build_plane_event.send(BuildPlaneEvent(spec.clone()));
+        }
+    }
+}
+
+/// Checks the plane against the goal of the current step, moving on to the next when it's met.
+#[allow(clippy::type_complexity)]
+fn update_tutorial(
+    time: Res<Time>,
+    rapier_context: Res<RapierContext>,
+    mut tutorial: ResMut<Tutorial>,
+    plane_query: Query<
+        (
+            &GlobalTransform,
+            &PlaneSpec,
+            &Thrust,
+            &Airspeed,
+            &Altitude,
+            &Velocity,
+            Has<Crashed>,
+        ),
+        With<Plane>,
+    >,
+    mut gameplay_events: EventReader<GameplayEvent>,
+    mut sink_rates: Local<Vec<(f32, f32)>>,
+    units: Res<Units>,
+) {
+    let events: Vec<_> = gameplay_events.iter().copied().collect();
+    let Some(step) = tutorial.current_step() else {
+        return;
+    };
+    let Ok((
+        global_tx,
+        spec,
+        Thrust(thrust),
+        Airspeed(airspeed),
+        Altitude(altitude),
+        velocity,
+        crashed,
+    )) = plane_query.get_single()
+    else {
+        return;
+    };
+    let now = time.elapsed_seconds();
+
+    if crashed {
+        tutorial.restart();
+        tutorial.feedback =
+            Some("Crashed - restart the plane and try the lesson again".to_string());
+        return;
+    }
+
+    // The wheels stop the plane sinking as they touch, so remember how fast it was sinking before
+    sink_rates.retain(|(t, _)| now - t < TOUCHDOWN_WINDOW);
+    sink_rates.push((now, -velocity.linvel.y));
+
+    let altitude = *altitude;
+    let start_altitude = *tutorial.start_altitude.get_or_insert(altitude);
+    let throttle = thrust / spec.thrust.max(f32::EPSILON);
+    let bank = attitude(global_tx).1.to_degrees().abs();
+
+    let reached = match step.goal {
+        Goal::ThrottleAbove(fraction) => throttle >= fraction,
+        Goal::ThrottleBelow(fraction) => throttle <= fraction,
+        Goal::SpeedAbove(target) => *airspeed > target,
+        Goal::SpeedBelow(target) => *airspeed < target,
+        Goal::TakeOff => events
+            .iter()
+            .any(|event| matches!(event, GameplayEvent::TookOff)),
+        Goal::HeightAbove(target) => {
+            // Only fixed bodies so the ray doesn't hit the plane itself
+            let height = rapier_context
+                .cast_ray(
+                    global_tx.translation(),
+                    Vec3::NEG_Y,
+                    MAX_GROUND_DISTANCE,
+                    true,
+                    QueryFilter::only_fixed().exclude_sensors(),
+                )
+                .map_or(MAX_GROUND_DISTANCE, |(_, toi)| toi);
+            height > target
+        }
+        Goal::HoldAltitude { tolerance, .. } => (altitude - start_altitude).abs() <= tolerance,
+        Goal::Bank {
+            angle, tolerance, ..
+        } => (bank - angle).abs() <= tolerance,
+        Goal::WingsLevel => bank < WINGS_LEVEL_ANGLE,
+        Goal::Touchdown { max_sink_rate } => {
+            let landed = events
+                .iter()
+                .any(|event| matches!(event, GameplayEvent::Landed));
+            let sink_rate = sink_rates.iter().map(|(_, rate)| *rate).fold(0.0, f32::max);
+
+            if landed && sink_rate > max_sink_rate {
+                tutorial.feedback = Some(format!(
+                    "Touched down sinking at {} - take off and try again",
+                    units.format(Quantity::VerticalSpeed, sink_rate, 1)
+                ));
+            }
+            landed && sink_rate <= max_sink_rate
+        }
+    };
+
+    // Goals to hold restart their clock whenever they're lost
+    let hold_time = step.goal.hold_time();
+    if !reached {
+        if hold_time > 0.0 && tutorial.held > 0.0 {
+            tutorial.held = 0.0;
+            // Altitude is held from wherever the plane is when it was lost
+            tutorial.start_altitude = Some(altitude);
+        }
+        return;
+    }
+    tutorial.held += time.delta_seconds();
+    if tutorial.held < hold_time {
+        return;
+    }
+
+    tutorial.next_step();
+    if tutorial.current_step().is_none() {
+        let Some(lesson) = tutorial.lesson.take() else {
+            return;
+        };
+        info!("Completed lesson {:?}", lesson);
+        if !tutorial.completed.contains(&lesson) {
+            tutorial.completed.push(lesson);
+        }
+        tutorial.feedback = Some(format!("{} lesson complete", lesson.title()));
+    }
+}
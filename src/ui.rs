@@ -1,7 +1,29 @@
+mod achievements;
+mod balance;
+mod camera;
+mod capture;
+mod controls;
+mod debounce;
+mod debrief;
+mod debug;
+mod efb;
+mod environment;
 mod hud;
+mod instruments;
+mod kneeboard;
+mod minimap;
+mod missions;
+mod navigation;
+mod pip;
+mod scenarios;
+mod settings;
+mod smoke;
 mod spec;
+mod tow;
+mod tutorial;
+mod wind_tunnel;
 
-use std::{f32::consts::PI, time::Duration};
+use std::time::Duration;
 
 use bevy::{
     diagnostic::{DiagnosticsStore, FrameTimeDiagnosticsPlugin},
@@ -9,33 +31,113 @@ use bevy::{
     time::common_conditions::on_timer,
 };
 use bevy_egui::{
-    egui::{self, Color32, FontDefinitions, RichText, Ui},
+    egui::{
+        self,
+        plot::{Line, Plot, PlotPoint, PlotPoints, Points},
+        Color32, FontDefinitions, Key, Modifiers, PointerButton, RichText, Ui,
+    },
     EguiContexts, EguiPlugin,
 };
+use bevy_rapier3d::prelude::*;
+use leafwing_input_manager::{plugin::InputManagerSystem, prelude::*};
 
 use crate::{
-    camera::FogControl,
+    achievements::Achievements,
+    damage::CrashedEvent,
+    flight_model::{Airfoil, Airspeed, AngleOfAttack, Lift, PitotStatic, PlaneFlight, Thrust},
+    input::PlaneAction,
+    navigation::Waypoints,
+    physics::{SimTimeControl, SIM_SPEEDS},
     plane::{
-        spec::PlaneSpec, AirfoilPosition, Airspeed, AngleOfAttack, BuildPlaneEvent, Lift,
-        PlaneControl, PlaneFlight, Side, Thrust,
+        sheet,
+        spec::{
+            GearLayout, Layout, PayloadKind, PayloadSpec, PlaneSpec, RotorSpec, Undercarriage,
+            WingRole, WingRowSpec, WingSpec,
+        },
+        tuning::{self, Balance, TuningReport},
+        AirfoilPosition, BuildPlaneEvent, LoadFactor, PlaneControl, SelectedPlane, Side,
+        TurnCoordinator, VerticalSpeed,
     },
-    world::{GizmosControl, SunControl},
+    recorder::CrashReport,
+    snapshot::{SpecSlots, SLOT_NAMES},
+    units::{Quantity, Units},
+    world::{compass_heading, Airports, AirshowVenue, TunedApproach},
 };
 
-use self::spec::{PlaneSpecModel, WingModel};
+use self::debounce::Debounced;
+use self::spec::{PayloadModel, PlaneSpecModel, RotorModel, SpecHistory, WingModel, WingRowModel};
+
+/// Height of the coefficient curve plots in the Build window.
+const CURVE_PLOT_HEIGHT: f32 = 120.0;
+/// Distance in pixels from a point on a curve plot within which it's picked up.
+const CURVE_GRAB_DISTANCE: f32 = 8.0;
+/// Closest in degrees that neighbouring points of a curve can be, keeping them in order.
+const CURVE_MIN_GAP: f32 = 0.5;
 
 pub struct HudUiPlugin;
 
 impl Plugin for HudUiPlugin {
     fn build(&self, app: &mut App) {
         app.add_plugins(EguiPlugin)
-            .add_systems(Startup, (setup, setup_indicators, hud::setup))
-            .add_systems(Update, (update_hud_ui, hud::hud_indicators))
+            .insert_resource(hud::HudDeclutter::default())
+            .init_resource::<hud::HudScreen>()
+            .insert_resource(minimap::Minimap::default())
+            .add_systems(
+                PreUpdate,
+                block_undo_shortcut_actions.after(InputManagerSystem::Update),
+            )
+            .add_systems(
+                Startup,
+                (
+                    setup,
+                    setup_indicators,
+                    hud::setup,
+                    instruments::setup,
+                    efb::setup,
+                ),
+            )
+            .add_systems(
+                Update,
+                (
+                    update_hud_ui,
+                    (efb::update_efb_ui, kneeboard::update_kneeboard_ui),
+                    environment::update_environment_ui,
+                    (camera::update_camera_ui, pip::update_pip_ui),
+                    controls::update_controls_ui,
+                    (debug::update_debug_ui, balance::update_balance_ui),
+                    missions::update_missions_ui,
+                    settings::update_settings_ui,
+                    achievements::update_achievements_ui,
+                    achievements::achievement_toasts,
+                    capture::capture_toasts,
+                    scenarios::update_scenarios_ui,
+                    scenarios::scenario_toasts,
+                    tutorial::update_tutorial_ui,
+                    navigation::update_navigation_ui,
+                    smoke::update_smoke_ui,
+                    (tow::update_tow_ui, wind_tunnel::update_wind_tunnel_ui),
+                    (minimap::control_minimap, minimap::update_minimap_ui).chain(),
+                    hud::hud_indicators,
+                    instruments::update_instrument_text,
+                ),
+            )
             .add_systems(
                 Update,
                 (
                     update_hud_model.run_if(on_timer(Duration::from_millis(100))),
+                    (
+                        hud::update_hud_screen,
+                        (hud::layout_hud_labels, instruments::layout_instrument_text)
+                            .run_if(resource_changed::<hud::HudScreen>()),
+                    )
+                        .chain()
+                        .after(update_hud_ui),
+                    (hud::update_hud_phase, hud::hud_declutter).chain(),
                     hud::hud_gizmos,
+                    instruments::instrument_gizmos,
+                    hud::hud_attitude_gizmos,
+                    hud::hud_yoke_gizmos,
+                    hud::hud_waypoint_gizmos,
                 ),
             );
     }
@@ -51,13 +153,41 @@ struct AirfoilModel {
 pub struct HudModel {
     fps: f32,
     altitude: f32,
+    aoa: f32,
+    sideslip: f32,
     thrust: f32,
     ailerons: f32,
     elevators: f32,
     rudder: f32,
     max_thrust: f32,
+    /// Indicated airspeed in m/s, as the pitot-static system measures it.
     airspeed: f32,
+    true_airspeed: f32,
     bearing: f32,
+    heading: f32,
+    display_multiplier: f32,
+    load_factor: f32,
+    /// Rate of climb in m/s.
+    vertical_speed: f32,
+    /// Turn rate in degrees per second, positive turning right.
+    turn_rate: f32,
+    /// Sideways acceleration throwing the turn coordinator's ball in g, positive to the right.
+    slip: f32,
+    pitch_rate: f32,
+    roll_rate: f32,
+    nearest_airport: String,
+    airport_distance: f32,
+    airport_bearing: f32,
+    height_above_airport: f32,
+    /// Degrees above the glideslope and right of the localizer of the tuned approach.
+    glideslope: Option<f32>,
+    localizer: Option<f32>,
+    /// Distance and bearing to the next waypoint.
+    waypoint: Option<(f32, f32)>,
+    waypoints_left: usize,
+    /// Yaw rate in degrees per second, positive to the right, while the main wing is stalled, or
+    /// zero while it's flying.
+    spin_rate: f32,
     wing_left: AirfoilModel,
     wing_right: AirfoilModel,
     tail_wing_left: AirfoilModel,
@@ -70,10 +200,27 @@ pub struct HudModel {
 pub struct WindowModel {
     show_stats: bool,
     show_environment: bool,
+    show_camera: bool,
+    show_pip: bool,
     show_build: bool,
+    show_balance: bool,
+    show_efb: bool,
+    show_kneeboard: bool,
+    show_controls: bool,
+    show_debug: bool,
+    show_missions: bool,
+    show_scenarios: bool,
+    show_settings: bool,
+    show_achievements: bool,
+    show_debrief: bool,
+    show_tutorial: bool,
+    show_navigation: bool,
+    show_smoke: bool,
+    show_towing: bool,
+    show_wind_tunnel: bool,
 }
 
-fn setup(mut commands: Commands, mut contexts: EguiContexts) {
+fn setup(mut commands: Commands, mut contexts: EguiContexts, achievements: Res<Achievements>) {
     let ctx = contexts.ctx_mut();
 
     let mut fonts = FontDefinitions::default();
@@ -97,13 +244,19 @@ fn setup(mut commands: Commands, mut contexts: EguiContexts) {
 
     ctx.set_fonts(fonts);
 
+    // New pilots, who haven't even landed yet, start with the lessons open
     commands.spawn((
-        WindowModel::default(),
+        WindowModel {
+            show_tutorial: achievements.unlocked.is_empty(),
+            ..default()
+        },
         HudModel::default(),
         PlaneSpecModel::new(&PlaneSpec::default()),
+        SpecHistory::new(&PlaneSpecModel::new(&PlaneSpec::default())),
     ));
 }
 
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
 fn update_hud_model(
     plane_query: Query<(
         &GlobalTransform,
@@ -111,14 +264,39 @@ fn update_hud_model(
         &PlaneControl,
         &Thrust,
         &Airspeed,
+        &PitotStatic,
         &PlaneSpec,
+        &Velocity,
+        &LoadFactor,
+        &VerticalSpeed,
+        &TurnCoordinator,
     )>,
-    airfoil_query: Query<(&AirfoilPosition, &AngleOfAttack, &Lift)>,
+    children_query: Query<&Children>,
+    airfoil_query: Query<(&AirfoilPosition, &Airfoil, &AngleOfAttack, &Lift)>,
+    selected: Res<SelectedPlane>,
     mut model_query: Query<&mut HudModel>,
     diagnostics: Res<DiagnosticsStore>,
+    airshow_venue: Res<AirshowVenue>,
+    airports: Res<Airports>,
+    tuned_approach: Res<TunedApproach>,
+    waypoints: Res<Waypoints>,
 ) {
-    let Ok((global_tx, flight, control, Thrust(thrust), Airspeed(airspeed), spec)) =
-        plane_query.get_single()
+    let Some(plane_entity) = selected.0 else {
+        return;
+    };
+    let Ok((
+        global_tx,
+        flight,
+        control,
+        Thrust(thrust),
+        Airspeed(airspeed),
+        pitot_static,
+        spec,
+        velocity,
+        load_factor,
+        vertical_speed,
+        turn_coordinator,
+    )) = plane_query.get(plane_entity)
     else {
         return;
     };
@@ -132,8 +310,11 @@ fn update_hud_model(
         .map(|m| m.value)
         .unwrap_or(-1.0) as f32;
 
-    model.altitude = global_tx.translation().y;
-    model.airspeed = *airspeed * 60. * 60. / 1000.;
+    model.altitude = pitot_static.indicated_altitude;
+    model.aoa = flight.angle_of_attack.to_degrees();
+    model.sideslip = flight.sideslip.to_degrees();
+    model.airspeed = pitot_static.indicated_airspeed;
+    model.true_airspeed = *airspeed;
     model.drag = flight.drag;
     model.thrust = *thrust;
     model.ailerons = control.ailerons;
@@ -147,28 +328,68 @@ fn update_hud_model(
         .to_euler(EulerRot::XYZ)
         .1
         .to_degrees();
+    model.heading = compass_heading(global_tx.forward());
+    model.display_multiplier = airshow_venue.score_multiplier(global_tx.translation());
+
+    let local_angvel = global_tx.compute_transform().rotation.inverse() * velocity.angvel;
+    model.pitch_rate = local_angvel.x.to_degrees();
+    model.roll_rate = -local_angvel.z.to_degrees();
+    model.load_factor = load_factor.g;
+    model.vertical_speed = vertical_speed.rate;
+    model.turn_rate = turn_coordinator.turn_rate.to_degrees();
+    model.slip = turn_coordinator.slip;
+
+    let pos = global_tx.translation();
+    let airport = airports.nearest(pos);
+    let to_airport = airport.position - pos;
+    model.nearest_airport = airport.name.clone();
+    model.airport_distance = (to_airport * Vec3::new(1., 0., 1.)).length();
+    model.airport_bearing = compass_heading(to_airport);
+    model.height_above_airport = pos.y - airport.position.y;
+
+    let approach = airports.approach(&tuned_approach, pos);
+    model.glideslope = approach.glideslope_deviation(pos);
+    model.localizer = approach.localizer_deviation(pos);
+    model.waypoint = waypoints.next_from(pos);
+    model.waypoints_left = waypoints.0.len();
+
+    let airfoils = children_query
+        .iter_descendants(plane_entity)
+        .filter_map(|child| airfoil_query.get(child).ok());
+
+    let mut wing_stalled = false;
+    for (position, airfoil, AngleOfAttack(aoa), Lift(lift)) in airfoils {
+        if matches!(
+            position,
+            AirfoilPosition::Wing(_) | AirfoilPosition::Elevon(_)
+        ) {
+            wing_stalled |= airfoil.is_stalled(*aoa);
+        }
 
-    for (position, AngleOfAttack(aoa), Lift(lift)) in airfoil_query.iter() {
         match position {
-            crate::plane::AirfoilPosition::Wing(Side::Left) => {
+            crate::plane::AirfoilPosition::Wing(Side::Left)
+            | crate::plane::AirfoilPosition::Elevon(Side::Left) => {
                 model.wing_left = AirfoilModel {
                     lift: *lift,
                     aoa: aoa.to_degrees(),
                 };
             }
-            crate::plane::AirfoilPosition::Wing(Side::Right) => {
+            crate::plane::AirfoilPosition::Wing(Side::Right)
+            | crate::plane::AirfoilPosition::Elevon(Side::Right) => {
                 model.wing_right = AirfoilModel {
                     lift: *lift,
                     aoa: aoa.to_degrees(),
                 };
             }
-            crate::plane::AirfoilPosition::TailWing(Side::Left) => {
+            crate::plane::AirfoilPosition::TailWing(Side::Left)
+            | crate::plane::AirfoilPosition::Canard(Side::Left) => {
                 model.tail_wing_left = AirfoilModel {
                     lift: *lift,
                     aoa: aoa.to_degrees(),
                 };
             }
-            crate::plane::AirfoilPosition::TailWing(Side::Right) => {
+            crate::plane::AirfoilPosition::TailWing(Side::Right)
+            | crate::plane::AirfoilPosition::Canard(Side::Right) => {
                 model.tail_wing_right = AirfoilModel {
                     lift: *lift,
                     aoa: aoa.to_degrees(),
@@ -177,9 +398,14 @@ fn update_hud_model(
             _ => {}
         }
     }
+
+    model.spin_rate = match wing_stalled {
+        true => -local_angvel.y.to_degrees(),
+        false => 0.0,
+    };
 }
 
-#[derive(Default)]
+#[derive(Default, Clone, PartialEq)]
 pub struct Vec3Model {
     x: String,
     y: String,
@@ -200,7 +426,7 @@ trait UiExt {
     fn float_label(&mut self, txt: &str, val: f32, color: Color32, width: usize);
     fn float_edit(&mut self, label: &str, value: &mut String);
     fn vec3(&mut self, label: &str, value: &mut Vec3Model);
-    fn coefficient_curve(&mut self, label: &str, value: &mut Vec<(String, String)>);
+    fn coefficient_curve(&mut self, label: &str, value: &mut Vec<(f32, f32)>);
     fn wing(&mut self, label: &str, value: &mut WingModel);
 }
 
@@ -246,23 +472,119 @@ impl UiExt for Ui {
         });
     }
 
-    fn coefficient_curve(&mut self, label: &str, value: &mut Vec<(String, String)>) {
+    /// Plots a coefficient curve as it's sampled for the flight model, with its points dragged to
+    /// change it. Double clicking adds a point and right clicking one removes it. The end points
+    /// only move up and down, so the curve still covers every angle, and the others are kept
+    /// between their neighbours.
+    fn coefficient_curve(&mut self, label: &str, value: &mut Vec<(f32, f32)>) {
         self.label(label);
-        self.group(|ui| {
-            egui::Grid::new(format!("{}-coefficient-grid", label))
-                .min_col_width(75.)
-                .show(ui, |ui| {
-                    ui.label("angle");
-                    ui.label("lift");
-                    ui.end_row();
-
-                    for val in value.iter_mut() {
-                        ui.text_edit_singleline(&mut val.1);
-                        ui.text_edit_singleline(&mut val.0);
-                        ui.end_row();
-                    }
-                });
-        });
+
+        let id = self.make_persistent_id(label);
+        let mut dragged: Option<usize> = self.data(|data| data.get_temp(id)).flatten();
+
+        let (first, last) = match (value.first(), value.last()) {
+            (Some(first), Some(last)) if value.len() > 1 => (first.1, last.1),
+            _ => return,
+        };
+        // A curve mid-edit can be out of order, which plots its points without the sampled line
+        let curve: Option<PlotPoints> = WingSpec::build_samples(value.clone())
+            .ok()
+            .filter(|samples| samples.len() > 1)
+            .map(|samples| {
+                let step = (last - first) / (samples.len() - 1) as f32;
+                samples
+                    .iter()
+                    .enumerate()
+                    .map(|(i, c)| [(first + i as f32 * step) as f64, *c as f64])
+                    .collect()
+            });
+        let points: PlotPoints = value.iter().map(|(c, a)| [*a as f64, *c as f64]).collect();
+
+        let plot = Plot::new(id)
+            .height(CURVE_PLOT_HEIGHT)
+            .include_y(0.0)
+            .allow_drag(false)
+            .allow_zoom(false)
+            .allow_scroll(false)
+            .allow_boxed_zoom(false)
+            .allow_double_click_reset(false)
+            .show(self, |plot_ui| {
+                if let Some(curve) = curve {
+                    plot_ui.line(Line::new(curve));
+                }
+                plot_ui.points(Points::new(points).radius(4.0));
+            });
+        let response = plot.response;
+        let transform = plot.transform;
+
+        let nearest = |value: &[(f32, f32)], pos: egui::Pos2| {
+            value
+                .iter()
+                .enumerate()
+                .map(|(i, (c, a))| {
+                    let point = transform.position_from_point(&PlotPoint::new(*a, *c));
+                    (i, point.distance(pos))
+                })
+                .filter(|(_, distance)| *distance < CURVE_GRAB_DISTANCE)
+                .min_by(|a, b| a.1.total_cmp(&b.1))
+                .map(|(i, _)| i)
+        };
+        let end = value.len() - 1;
+
+        if response.drag_started() {
+            dragged = response
+                .interact_pointer_pos()
+                .and_then(|pos| nearest(value, pos));
+        }
+        if let (true, Some(index), Some(pos)) =
+            (response.dragged(), dragged, response.interact_pointer_pos())
+        {
+            let point = transform.value_from_position(pos);
+            let angle = match index {
+                0 => first,
+                i if i == end => last,
+                i => (point.x as f32)
+                    .max(value[i - 1].1 + CURVE_MIN_GAP)
+                    .min(value[i + 1].1 - CURVE_MIN_GAP),
+            };
+            value[index] = (point.y as f32, angle);
+        }
+        if response.drag_released() {
+            dragged = None;
+        }
+        self.data_mut(|data| data.insert_temp(id, dragged));
+
+        let hovered = response.hover_pos();
+        if let Some(pos) = hovered {
+            let (add, remove) = self.input(|input| {
+                (
+                    input.pointer.button_double_clicked(PointerButton::Primary),
+                    input.pointer.button_clicked(PointerButton::Secondary),
+                )
+            });
+            if remove {
+                if let Some(index) = nearest(value, pos).filter(|i| *i > 0 && *i < end) {
+                    value.remove(index);
+                }
+            } else if add {
+                let point = transform.value_from_position(pos);
+                let angle = point.x as f32;
+                let index = value.partition_point(|(_, a)| *a < angle);
+                if index > 0
+                    && index <= end
+                    && angle - value[index - 1].1 >= CURVE_MIN_GAP
+                    && value[index].1 - angle >= CURVE_MIN_GAP
+                {
+                    value.insert(index, (point.y as f32, angle));
+                }
+            }
+        }
+
+        let shown = dragged.or_else(|| hovered.and_then(|pos| nearest(value, pos)));
+        match shown.and_then(|index| value.get(index)) {
+            Some((c, a)) => self.label(format!("{:.1}°: {:.3}", a, c)),
+            None => self.weak("drag points, double click to add, right click to remove"),
+        };
     }
 
     fn wing(&mut self, label: &str, value: &mut WingModel) {
@@ -274,21 +596,64 @@ impl UiExt for Ui {
                 ui.coefficient_curve("drag coefficient curve", &mut value.drag_coefficient_curve);
                 ui.float_edit("angle", &mut value.angle);
                 ui.float_edit("max control angle", &mut value.max_control_angle);
+                ui.float_edit("max control rate", &mut value.max_control_rate);
+                ui.float_edit("oswald efficiency", &mut value.oswald_efficiency);
+                ui.float_edit("dihedral", &mut value.dihedral);
+                ui.float_edit("sweep", &mut value.sweep);
             });
         });
     }
 }
 
+/// Keeps the letters of the Build window's undo and redo shortcuts from also pressing the plane's
+/// actions, such as Z for throttle down. The shortcuts are taken outside text boxes, where egui
+/// doesn't claim the keyboard, so the actions are consumed until their keys are let go.
+fn block_undo_shortcut_actions(
+    mut contexts: EguiContexts,
+    keys: Res<Input<KeyCode>>,
+    window_model_query: Query<&WindowModel>,
+    mut action_query: Query<&mut ActionState<PlaneAction>>,
+) {
+    let Ok(window_model) = window_model_query.get_single() else {
+        return;
+    };
+    let command = [
+        KeyCode::ControlLeft,
+        KeyCode::ControlRight,
+        KeyCode::SuperLeft,
+        KeyCode::SuperRight,
+    ];
+    if !window_model.show_build
+        || !keys.any_pressed(command)
+        || !keys.any_just_pressed([KeyCode::Z, KeyCode::Y])
+        || contexts.ctx_mut().memory(|memory| memory.focus().is_some())
+    {
+        return;
+    }
+
+    for mut action_state in action_query.iter_mut() {
+        for action in action_state.get_just_pressed() {
+            action_state.consume(action);
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
 fn update_hud_ui(
     mut contexts: EguiContexts,
     model_query: Query<&mut HudModel>,
     mut window_model_query: Query<&mut WindowModel>,
-    plane_spec_query: Query<&PlaneSpec>,
-    mut plane_spec_model_query: Query<&mut PlaneSpecModel>,
-    mut fog_control: Query<&mut FogControl>,
-    mut sun_control: Query<&mut SunControl>,
-    mut gizmos_control: ResMut<GizmosControl>,
+    plane_spec_query: Query<Ref<PlaneSpec>>,
+    mut plane_spec_model_query: Query<(&mut PlaneSpecModel, &mut SpecHistory)>,
+    mut sim_time: ResMut<SimTimeControl>,
     mut build_plane_event: EventWriter<BuildPlaneEvent>,
+    mut crashed_event: EventReader<CrashedEvent>,
+    crash_report: Option<Res<CrashReport>>,
+    mut tuning_report: Local<Option<TuningReport>>,
+    mut balance_check: Local<Debounced<PlaneSpec, Balance>>,
+    time: Res<Time>,
+    mut spec_slots: ResMut<SpecSlots>,
+    units: Res<Units>,
 ) {
     let Ok(model) = model_query.get_single() else {
         return;
@@ -299,9 +664,18 @@ fn update_hud_ui(
     let Ok(plane_spec) = plane_spec_query.get_single() else {
         return;
     };
-    let Ok(mut plane_spec_model) = plane_spec_model_query.get_single_mut() else {
+    let Ok((mut plane_spec_model, mut history)) = plane_spec_model_query.get_single_mut() else {
         return;
     };
+    // Keep up with planes built from elsewhere, such as reloaded spec files, so restarting doesn't
+    // go back to an older design
+    if plane_spec.is_added() {
+        *plane_spec_model = PlaneSpecModel::new(&plane_spec);
+    }
+
+    if crashed_event.iter().count() > 0 {
+        window_model.show_debrief = true;
+    }
 
     let ctx = contexts.ctx_mut();
 
@@ -323,8 +697,26 @@ fn update_hud_ui(
 
                 ui.float_label("fps", model.fps, normal_color, width);
                 ui.float_label("weight", model.weight, normal_color, width);
-                ui.float_label("altitude", model.altitude, normal_color, width);
-                ui.float_label("airspeed", model.airspeed, normal_color, width);
+                let mut unit_label = |txt: &str, quantity: Quantity, val: f32| {
+                    ui.float_label(
+                        &format!("{} ({})", txt, units.symbol(quantity)),
+                        units.convert(quantity, val),
+                        normal_color,
+                        width,
+                    );
+                };
+                unit_label("altitude", Quantity::Altitude, model.altitude);
+                unit_label("airspeed", Quantity::Speed, model.airspeed);
+                unit_label("tas", Quantity::Speed, model.true_airspeed);
+                unit_label(
+                    "vertical speed",
+                    Quantity::VerticalSpeed,
+                    model.vertical_speed,
+                );
+                ui.float_label("turn rate", model.turn_rate, normal_color, width);
+                ui.float_label("slip", model.slip, normal_color, width);
+                ui.float_label("aoa", model.aoa, normal_color, width);
+                ui.float_label("sideslip", model.sideslip, normal_color, width);
                 ui.float_label("drag", model.drag, normal_color, width);
                 ui.float_label("thrust", model.thrust, normal_color, width);
                 ui.float_label("bearing", model.bearing, normal_color, width);
@@ -354,32 +746,19 @@ fn update_hud_ui(
             });
         });
 
-    egui::Window::new("Environment")
-        .open(&mut window_model.show_environment)
-        .show(ctx, |ui| {
-            if let Ok(mut fog_control) = fog_control.get_single_mut() {
-                ui.group(|ui| {
-                    ui.label("Fog");
-                    ui.add(
-                        egui::Slider::new(&mut fog_control.visibility, 0.0..=5000.0)
-                            .text("visibility"),
-                    );
-                });
-            }
-
-            if let Ok(mut sun_control) = sun_control.get_single_mut() {
-                let (mut x, y, z) = sun_control.rotation.to_euler(EulerRot::XYZ);
-
-                ui.group(|ui| {
-                    ui.label("Sun direction");
-                    ui.add(egui::Slider::new(&mut x, 0.0..=-PI).text("x"));
-                });
-
-                sun_control.rotation = Quat::from_euler(EulerRot::YXZ, y, x, z);
-            }
-
-            ui.checkbox(&mut gizmos_control.show, "Gizmos");
+    // Text boxes have their own undo for what's typed in them, so these only act outside them
+    if window_model.show_build && ctx.memory(|memory| memory.focus().is_none()) {
+        let (undo, redo) = ctx.input_mut(|input| {
+            let redo = input.consume_key(Modifiers::COMMAND | Modifiers::SHIFT, Key::Z)
+                || input.consume_key(Modifiers::COMMAND, Key::Y);
+            (input.consume_key(Modifiers::COMMAND, Key::Z), redo)
         });
+        if undo {
+            history.undo(&mut plane_spec_model);
+        } else if redo {
+            history.redo(&mut plane_spec_model);
+        }
+    }
 
     egui::Window::new("Build")
         .open(&mut window_model.show_build)
@@ -388,22 +767,323 @@ fn update_hud_ui(
                 ui.style_mut().spacing.item_spacing.y = 10.;
                 ui.style_mut().spacing.text_edit_width = 100.;
 
+                ui.horizontal(|ui| {
+                    if ui
+                        .add_enabled(history.can_undo(), egui::Button::new("Undo"))
+                        .on_hover_text("Ctrl+Z")
+                        .clicked()
+                    {
+                        history.undo(&mut plane_spec_model);
+                    }
+                    if ui
+                        .add_enabled(history.can_redo(), egui::Button::new("Redo"))
+                        .on_hover_text("Ctrl+Y")
+                        .clicked()
+                    {
+                        history.redo(&mut plane_spec_model);
+                    }
+                    if ui.button("Revert to last built").clicked() {
+                        *plane_spec_model = PlaneSpecModel::new(&plane_spec);
+                    }
+                });
+
                 egui::ScrollArea::vertical().show(ui, |ui| {
                     ui.float_edit("thrust", &mut plane_spec_model.thrust);
+                    ui.label("engine");
+                    ui.group(|ui| {
+                        ui.float_edit("cylinders", &mut plane_spec_model.engine.cylinders);
+                        ui.float_edit("idle rpm", &mut plane_spec_model.engine.idle_rpm);
+                        ui.float_edit("max rpm", &mut plane_spec_model.engine.max_rpm);
+                        ui.float_edit(
+                            "reverse thrust",
+                            &mut plane_spec_model.engine.reverse_thrust,
+                        );
+                    });
+                    ui.checkbox(&mut plane_spec_model.fly_by_wire, "fly by wire");
+                    // Helicopters lift on a rotor, with the throttle as the collective
+                    let mut helicopter = plane_spec_model.rotor.is_some();
+                    if ui.checkbox(&mut helicopter, "helicopter").changed() {
+                        plane_spec_model.rotor =
+                            helicopter.then(|| RotorModel::new(&RotorSpec::default()));
+                    }
+                    if let Some(rotor) = plane_spec_model.rotor.as_mut() {
+                        ui.label("rotor");
+                        ui.group(|ui| {
+                            ui.float_edit("diameter", &mut rotor.diameter);
+                            ui.float_edit("height", &mut rotor.height);
+                            ui.float_edit("max thrust", &mut rotor.max_thrust);
+                            ui.float_edit("max cyclic (°)", &mut rotor.max_cyclic);
+                            ui.float_edit("anti-torque", &mut rotor.anti_torque);
+                        });
+                    }
+                    ui.label("never exceed speed");
+                    let mut never_exceed_speed =
+                        units.convert(Quantity::Speed, plane_spec_model.never_exceed_speed);
+                    if ui
+                        .add(
+                            egui::DragValue::new(&mut never_exceed_speed)
+                                .suffix(format!(" {}", units.symbol(Quantity::Speed))),
+                        )
+                        .changed()
+                    {
+                        plane_spec_model.never_exceed_speed =
+                            units.convert_back(Quantity::Speed, never_exceed_speed);
+                    }
+                    // Custom layouts come from saved specs, so can be kept but not picked
+                    egui::ComboBox::from_label("layout")
+                        .selected_text(plane_spec_model.layout.name())
+                        .show_ui(ui, |ui| {
+                            for option in Layout::PRESETS {
+                                let name = option.name();
+                                ui.selectable_value(&mut plane_spec_model.layout, option, name);
+                            }
+                        });
                     ui.vec3("fuselage", &mut plane_spec_model.fuselage.size);
                     ui.float_edit("mass", &mut plane_spec_model.fuselage.mass);
+                    egui::ComboBox::from_label("undercarriage")
+                        .selected_text(plane_spec_model.fuselage.undercarriage.name())
+                        .show_ui(ui, |ui| {
+                            for option in Undercarriage::ALL {
+                                ui.selectable_value(
+                                    &mut plane_spec_model.fuselage.undercarriage,
+                                    option,
+                                    option.name(),
+                                );
+                            }
+                        });
+                    if plane_spec_model.fuselage.undercarriage == Undercarriage::Wheels {
+                        egui::ComboBox::from_label("gear")
+                            .selected_text(plane_spec_model.fuselage.gear.name())
+                            .show_ui(ui, |ui| {
+                                for option in GearLayout::ALL {
+                                    ui.selectable_value(
+                                        &mut plane_spec_model.fuselage.gear,
+                                        option,
+                                        option.name(),
+                                    );
+                                }
+                            });
+                    }
                     ui.wing("wings", &mut plane_spec_model.wings);
                     ui.vec3("tail", &mut plane_spec_model.tail);
                     ui.wing("tail horizontal", &mut plane_spec_model.tail_horizontal);
                     ui.wing("tail vertical", &mut plane_spec_model.tail_vertical);
+                    ui.label("wing rows");
+                    ui.group(|ui| {
+                        let mut removed = None;
+                        for (i, row) in plane_spec_model.wing_rows.iter_mut().enumerate() {
+                            ui.push_id(i, |ui| {
+                                egui::ComboBox::from_label("role")
+                                    .selected_text(row.role.name())
+                                    .show_ui(ui, |ui| {
+                                        for option in WingRole::ALL {
+                                            ui.selectable_value(
+                                                &mut row.role,
+                                                option,
+                                                option.name(),
+                                            );
+                                        }
+                                    });
+                                ui.float_edit("height", &mut row.height);
+                                ui.float_edit("position", &mut row.position);
+                                ui.wing("wing", &mut row.wing);
+                                if ui.button("Remove").clicked() {
+                                    removed = Some(i);
+                                }
+                            });
+                        }
+                        if let Some(i) = removed {
+                            plane_spec_model.wing_rows.remove(i);
+                        }
 
-                    if ui.button("Build").clicked() {
-                        build_plane_event.send(BuildPlaneEvent(plane_spec_model.to_spec()));
+                        ui.horizontal(|ui| {
+                            let spec = plane_spec_model.to_spec();
+                            if ui.button("Add biplane wing").clicked() {
+                                plane_spec_model
+                                    .wing_rows
+                                    .push(WingRowModel::new(&WingRowSpec::biplane(&spec)));
+                            }
+                            if ui.button("Add canard").clicked() {
+                                plane_spec_model
+                                    .wing_rows
+                                    .push(WingRowModel::new(&WingRowSpec::canard(&spec)));
+                            }
+                        });
+                    });
+                    ui.label("ballast");
+                    ui.group(|ui| {
+                        ui.float_edit("mass", &mut plane_spec_model.ballast.mass);
+                        ui.float_edit("position", &mut plane_spec_model.ballast.position);
+                    });
+                    ui.label("payloads");
+                    ui.group(|ui| {
+                        let mut removed = None;
+                        for (i, payload) in plane_spec_model.payloads.iter_mut().enumerate() {
+                            ui.push_id(i, |ui| {
+                                egui::ComboBox::from_label("kind")
+                                    .selected_text(payload.kind.name())
+                                    .show_ui(ui, |ui| {
+                                        for option in PayloadKind::ALL {
+                                            ui.selectable_value(
+                                                &mut payload.kind,
+                                                option,
+                                                option.name(),
+                                            );
+                                        }
+                                    });
+                                ui.float_edit("mass", &mut payload.mass);
+                                ui.vec3("position", &mut payload.position);
+                                if ui.button("Remove").clicked() {
+                                    removed = Some(i);
+                                }
+                            });
+                        }
+                        if let Some(i) = removed {
+                            plane_spec_model.payloads.remove(i);
+                        }
+
+                        ui.horizontal(|ui| {
+                            let spec = plane_spec_model.to_spec();
+                            for kind in PayloadKind::ALL {
+                                if ui
+                                    .button(format!("Add {}", kind.name().to_lowercase()))
+                                    .clicked()
+                                {
+                                    plane_spec_model.payloads.push(PayloadModel::new(
+                                        &PayloadSpec::under_fuselage(&spec, kind),
+                                    ));
+                                }
+                            }
+                        });
+                    });
+
+                    let spec = plane_spec_model.to_spec();
+                    let errors = spec.validate().err().unwrap_or_default();
+                    let valid = errors.is_empty();
+
+                    // Measured in a test flight off the main thread, so only for a design that
+                    // builds
+                    if valid {
+                        balance_check.update(&spec, time.raw_elapsed_seconds(), tuning::balance);
+                    }
+                    ui.horizontal(|ui| {
+                        ui.label("balance");
+                        if balance_check.is_calculating() {
+                            ui.weak("calculating…");
+                        }
+                    });
+                    ui.add_enabled_ui(valid, |ui| {
+                        let Some(balance) = balance_check.value() else {
+                            return;
+                        };
+                        let margin_color = match balance.static_margin {
+                            m if m < 0.0 => Color32::RED,
+                            m if !(tuning::MIN_STATIC_MARGIN..=tuning::MAX_STATIC_MARGIN)
+                                .contains(&m) =>
+                            {
+                                Color32::YELLOW
+                            }
+                            _ => Color32::GREEN,
+                        };
+                        ui.group(|ui| {
+                            ui.float_label(
+                                "centre of gravity",
+                                balance.centre_of_gravity,
+                                normal_color,
+                                width,
+                            );
+                            ui.float_label(
+                                "neutral point",
+                                balance.neutral_point,
+                                normal_color,
+                                width,
+                            );
+                            ui.float_label(
+                                "static margin (%)",
+                                balance.static_margin * 100.0,
+                                margin_color,
+                                width,
+                            );
+                        });
+                    });
+
+                    // A spec that won't build a working plane is held back until it's fixed
+                    for error in errors.iter() {
+                        ui.colored_label(Color32::RED, error.to_string());
+                    }
+                    ui.add_enabled_ui(valid, |ui| {
+                        ui.horizontal(|ui| {
+                            if ui.button("Build").clicked() {
+                                build_plane_event.send(BuildPlaneEvent(spec.clone()));
+                            }
+                            if ui.button("Analyse").clicked() {
+                                *tuning_report = Some(tuning::analyse(&spec, *units));
+                            }
+                            if ui.button("Export design sheet").clicked() {
+                                sheet::export_design_sheet(&spec, *units);
+                            }
+                        });
+                        ui.horizontal(|ui| {
+                            for (i, name) in SLOT_NAMES.iter().enumerate() {
+                                if ui.button(format!("Store as {}", name)).clicked() {
+                                    spec_slots.specs[i] = Some(spec.clone());
+                                }
+                            }
+                        });
+                    });
+                    ui.label(format!(
+                        "{} (flying {}, X swaps)",
+                        SLOT_NAMES
+                            .iter()
+                            .zip(spec_slots.specs.iter())
+                            .map(|(name, spec)| match spec {
+                                Some(_) => format!("{} stored", name),
+                                None => format!("{} empty", name),
+                            })
+                            .collect::<Vec<_>>()
+                            .join(", "),
+                        SLOT_NAMES[spec_slots.active],
+                    ));
+
+                    if let Some(report) = tuning_report.as_ref() {
+                        ui.group(|ui| {
+                            egui::Grid::new("tuning_report").show(ui, |ui| {
+                                for (label, value) in report.characteristics.iter() {
+                                    ui.label(*label);
+                                    ui.label(value);
+                                    ui.end_row();
+                                }
+                            });
+                            ui.separator();
+                            for suggestion in report.suggestions.iter() {
+                                ui.label(suggestion);
+                            }
+                        });
                     }
                 });
             });
         });
 
+    history.record(&plane_spec_model, ctx.input(|input| input.time));
+
+    if let Some(report) = crash_report.filter(|_| window_model.show_debrief) {
+        egui::Window::new("Crashed")
+            .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                debrief::crash_report(ui, &report, *units);
+
+                if ui.button("Restart").clicked() {
+                    build_plane_event.send(BuildPlaneEvent(restart_spec(
+                        &plane_spec_model,
+                        &plane_spec,
+                    )));
+                    window_model.show_debrief = false;
+                }
+            });
+    }
+
     egui::TopBottomPanel::top("top_panel")
         .show_separator_line(false)
         .frame(egui::Frame {
@@ -414,22 +1094,96 @@ fn update_hud_ui(
         .show(ctx, |ui| {
             ui.horizontal(|ui| {
                 if ui.button("Restart").clicked() {
-                    build_plane_event.send(BuildPlaneEvent(plane_spec_model.to_spec()));
+                    build_plane_event.send(BuildPlaneEvent(restart_spec(
+                        &plane_spec_model,
+                        &plane_spec,
+                    )));
+                    window_model.show_debrief = false;
                 }
                 if ui.button("Stats").clicked() {
                     window_model.show_stats = !window_model.show_stats;
                 }
                 if ui.button("Build").clicked() {
-                    *plane_spec_model = PlaneSpecModel::new(plane_spec);
+                    *plane_spec_model = PlaneSpecModel::new(&plane_spec);
                     window_model.show_build = !window_model.show_build;
                 }
+                if ui.button("Weight & Balance").clicked() {
+                    window_model.show_balance = !window_model.show_balance;
+                }
                 if ui.button("Environment").clicked() {
                     window_model.show_environment = !window_model.show_environment;
                 }
+                if ui.button("Camera").clicked() {
+                    window_model.show_camera = !window_model.show_camera;
+                }
+                if ui.button("Picture in picture").clicked() {
+                    window_model.show_pip = !window_model.show_pip;
+                }
+                if ui.button("Flight bag").clicked() {
+                    window_model.show_efb = !window_model.show_efb;
+                }
+                if ui.button("Kneeboard").clicked() {
+                    window_model.show_kneeboard = !window_model.show_kneeboard;
+                }
+                if ui.button("Tutorial").clicked() {
+                    window_model.show_tutorial = !window_model.show_tutorial;
+                }
+                if ui.button("Navigation").clicked() {
+                    window_model.show_navigation = !window_model.show_navigation;
+                }
+                if ui.button("Smoke").clicked() {
+                    window_model.show_smoke = !window_model.show_smoke;
+                }
+                if ui.button("Towing").clicked() {
+                    window_model.show_towing = !window_model.show_towing;
+                }
+                if ui.button("Wind Tunnel").clicked() {
+                    window_model.show_wind_tunnel = !window_model.show_wind_tunnel;
+                }
+                if ui.button("Missions").clicked() {
+                    window_model.show_missions = !window_model.show_missions;
+                }
+                if ui.button("Scenarios").clicked() {
+                    window_model.show_scenarios = !window_model.show_scenarios;
+                }
+                if ui.button("Achievements").clicked() {
+                    window_model.show_achievements = !window_model.show_achievements;
+                }
+                if ui.button("Controls").clicked() {
+                    window_model.show_controls = !window_model.show_controls;
+                }
+                if ui.button("Settings").clicked() {
+                    window_model.show_settings = !window_model.show_settings;
+                }
+                if ui.button("Debug").clicked() {
+                    window_model.show_debug = !window_model.show_debug;
+                }
+
+                ui.separator();
+                let pause_label = if sim_time.paused { "Resume" } else { "Pause" };
+                if ui.button(pause_label).clicked() {
+                    sim_time.paused = !sim_time.paused;
+                }
+                ui.add(
+                    egui::Slider::new(&mut sim_time.speed, SIM_SPEEDS)
+                        .logarithmic(true)
+                        .suffix("x")
+                        .text("speed"),
+                );
             });
         });
 }
 
+/// Spec to restart with, the one in the Build window unless it has errors, when the plane that's
+/// flying is rebuilt instead.
+fn restart_spec(model: &PlaneSpecModel, current: &PlaneSpec) -> PlaneSpec {
+    let spec = model.to_spec();
+    match spec.validate() {
+        Ok(()) => spec,
+        Err(_) => current.clone(),
+    }
+}
+
 fn setup_indicators(mut commands: Commands) {
     commands.spawn(SpriteBundle {
         sprite: Sprite {
@@ -0,0 +1,115 @@
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+
+use crate::{
+    achievements::{Achievement, AchievementUnlockedEvent, Achievements, GameplayEvent},
+    world::PadLandingEvent,
+};
+
+use super::WindowModel;
+
+/// Time a toast is shown for after an achievement is unlocked or a stunt is pulled off.
+const TOAST_DURATION: f32 = 5.0;
+
+pub fn update_achievements_ui(
+    mut contexts: EguiContexts,
+    mut window_model_query: Query<&mut WindowModel>,
+    achievements: Res<Achievements>,
+) {
+    let Ok(mut window_model) = window_model_query.get_single_mut() else {
+        return;
+    };
+
+    egui::Window::new("Achievements")
+        .open(&mut window_model.show_achievements)
+        .show(contexts.ctx_mut(), |ui| {
+            egui::Grid::new("achievements").show(ui, |ui| {
+                for achievement in Achievement::ALL {
+                    let unlocked = achievements.unlocked.contains(&achievement);
+                    let color = if unlocked {
+                        egui::Color32::GREEN
+                    } else {
+                        egui::Color32::GRAY
+                    };
+                    ui.colored_label(color, achievement.title());
+                    ui.colored_label(color, achievement.description());
+                    ui.end_row();
+                }
+            });
+
+            ui.separator();
+            ui.label(format!(
+                "Time airborne: {:.0} min",
+                achievements.airborne_time / 60.0
+            ));
+        });
+}
+
+/// Shows a notification for each achievement as it is unlocked, for each bridge flown under, and with
+/// the score for each landing on a rooftop pad.
+pub fn achievement_toasts(
+    mut contexts: EguiContexts,
+    time: Res<Time>,
+    mut gameplay_events: EventReader<GameplayEvent>,
+    mut unlocked_events: EventReader<AchievementUnlockedEvent>,
+    mut landing_events: EventReader<PadLandingEvent>,
+    mut toasts: Local<Vec<(f32, String, String)>>,
+) {
+    let now = time.raw_elapsed_seconds();
+
+    toasts.extend(gameplay_events.iter().filter_map(|event| match event {
+        GameplayEvent::FlewUnderBridge => Some((
+            now,
+            "Under the bridge!".to_string(),
+            "Threaded the needle".to_string(),
+        )),
+        _ => None,
+    }));
+    toasts.extend(
+        unlocked_events
+            .iter()
+            .map(|AchievementUnlockedEvent(achievement)| {
+                (
+                    now,
+                    format!("Achievement unlocked: {}", achievement.title()),
+                    achievement.description().to_string(),
+                )
+            }),
+    );
+    toasts.extend(landing_events.iter().map(|landing| {
+        let wire = if landing.caught_wire {
+            ", caught a wire"
+        } else {
+            ""
+        };
+        (
+            now,
+            format!(
+                "{} landing: {:.0} points",
+                landing.kind.name(),
+                landing.score
+            ),
+            format!(
+                "{:.1} m/s sink, {:.0} m from the mark{}",
+                landing.sink_rate, landing.distance, wire
+            ),
+        )
+    }));
+    toasts.retain(|(shown, ..)| now - shown < TOAST_DURATION);
+
+    if toasts.is_empty() {
+        return;
+    }
+
+    egui::Area::new("achievement_toasts")
+        .anchor(egui::Align2::CENTER_TOP, egui::vec2(0., 40.))
+        .interactable(false)
+        .show(contexts.ctx_mut(), |ui| {
+            for (_, title, detail) in toasts.iter() {
+                egui::Frame::popup(ui.style()).show(ui, |ui| {
+                    ui.label(egui::RichText::new(title).color(egui::Color32::GOLD));
+                    ui.label(detail);
+                });
+            }
+        });
+}
@@ -0,0 +1,130 @@
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+use bevy_rapier3d::prelude::*;
+
+use crate::{
+    camera::Follow,
+    flight_model::CentreOfGravity,
+    plane::{spec::PlaneSpec, AirfoilPosition, Plane},
+};
+
+use super::WindowModel;
+
+/// Lists the mass of each part of the flown plane and its arm behind the leading edge of the main
+/// wings, read back from the physics so it shows what's actually flying, with where the centre of
+/// gravity falls along the wing chord.
+#[allow(clippy::type_complexity)]
+pub fn update_balance_ui(
+    mut contexts: EguiContexts,
+    mut window_model_query: Query<&mut WindowModel>,
+    plane_query: Query<
+        (
+            Entity,
+            &PlaneSpec,
+            &GlobalTransform,
+            &ReadMassProperties,
+            &CentreOfGravity,
+        ),
+        (With<Plane>, With<Follow>),
+    >,
+    children_query: Query<&Children>,
+    collider_query: Query<(&RapierColliderHandle, Option<&Name>)>,
+    wing_query: Query<(&AirfoilPosition, &GlobalTransform)>,
+    rapier_context: Res<RapierContext>,
+) {
+    let Ok(mut window_model) = window_model_query.get_single_mut() else {
+        return;
+    };
+
+    egui::Window::new("Weight & Balance")
+        .open(&mut window_model.show_balance)
+        .show(contexts.ctx_mut(), |ui| {
+            let Ok((
+                plane,
+                spec,
+                global_tx,
+                ReadMassProperties(mass_properties),
+                centre_of_gravity,
+            )) = plane_query.get_single()
+            else {
+                ui.label("No plane");
+                return;
+            };
+            let to_local = global_tx.compute_matrix().inverse();
+
+            // The main wings' mean centre less half their chord stands in for the leading edge
+            // of the mean chord, which arms are measured back from
+            let chord = spec.main_wing().size.z;
+            let wing_z: Vec<_> = children_query
+                .iter_descendants(plane)
+                .filter_map(|child| wing_query.get(child).ok())
+                .filter(|(position, _)| {
+                    matches!(
+                        position,
+                        AirfoilPosition::Wing(_) | AirfoilPosition::Elevon(_)
+                    )
+                })
+                .map(|(_, wing_tx)| to_local.transform_point3(wing_tx.translation()).z)
+                .collect();
+            let datum = match wing_z.is_empty() {
+                true => 0.0,
+                false => wing_z.iter().sum::<f32>() / wing_z.len() as f32 - chord * 0.5,
+            };
+
+            let scale = rapier_context.physics_scale();
+            let mut parts: Vec<_> = children_query
+                .iter_descendants(plane)
+                .filter_map(|child| collider_query.get(child).ok())
+                .filter_map(|(handle, name)| {
+                    let collider = rapier_context.colliders.get(handle.0)?;
+                    let centre = collider
+                        .position_wrt_parent()
+                        .map_or(collider.mass_properties().local_com, |position| {
+                            position * collider.mass_properties().local_com
+                        });
+                    let name = name.map_or("Part".to_string(), |name| name.to_string());
+                    Some((name, collider.mass(), centre.z / scale - datum))
+                })
+                .collect();
+            if spec.ballast.mass > 0.0 {
+                parts.push((
+                    "Ballast".to_string(),
+                    spec.ballast.mass,
+                    spec.ballast.position - datum,
+                ));
+            }
+
+            egui::ScrollArea::vertical()
+                .max_height(300.0)
+                .show(ui, |ui| {
+                    egui::Grid::new("balance").striped(true).show(ui, |ui| {
+                        ui.strong("part");
+                        ui.strong("mass (kg)");
+                        ui.strong("arm (m)");
+                        ui.strong("moment (kg m)");
+                        ui.end_row();
+
+                        for (name, mass, arm) in parts.iter() {
+                            ui.label(name);
+                            ui.label(format!("{:.1}", mass));
+                            ui.label(format!("{:.2}", arm));
+                            ui.label(format!("{:.1}", mass * arm));
+                            ui.end_row();
+                        }
+                    });
+                });
+
+            ui.separator();
+
+            let arm = centre_of_gravity.local.z - datum;
+            ui.label(format!("total mass: {:.1} kg", mass_properties.mass));
+            ui.label(format!(
+                "centre of gravity: {:.2} m behind leading edge",
+                arm
+            ));
+            ui.label(format!(
+                "centre of gravity: {:.0}% of chord",
+                arm / chord * 100.0
+            ));
+        });
+}
@@ -0,0 +1,54 @@
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+
+use crate::camera::{FollowOffset, FollowSettings};
+
+use super::WindowModel;
+
+pub fn update_camera_ui(
+    mut contexts: EguiContexts,
+    mut window_model_query: Query<&mut WindowModel>,
+    mut follow_settings: ResMut<FollowSettings>,
+) {
+    let Ok(mut window_model) = window_model_query.get_single_mut() else {
+        return;
+    };
+
+    // Edit a copy so the cameras are only moved when something actually changes
+    let mut settings = follow_settings.clone();
+
+    egui::Window::new("Camera")
+        .open(&mut window_model.show_camera)
+        .show(contexts.ctx_mut(), |ui| {
+            for (name, offset) in [
+                ("Behind (F1)", &mut settings.behind),
+                ("Above (F2)", &mut settings.above),
+                ("Side (F3)", &mut settings.side),
+                ("Inside (F4)", &mut settings.inside),
+                ("Wing", &mut settings.wing),
+            ] {
+                egui::CollapsingHeader::new(name).show(ui, |ui| {
+                    follow_offset_ui(ui, offset);
+                });
+            }
+
+            if ui.button("Reset to defaults").clicked() {
+                settings = FollowSettings::default();
+            }
+        });
+
+    follow_settings.set_if_neq(settings);
+}
+
+fn follow_offset_ui(ui: &mut egui::Ui, offset: &mut FollowOffset) {
+    ui.horizontal(|ui| {
+        ui.add(egui::DragValue::new(&mut offset.translation.x).speed(0.1));
+        ui.add(egui::DragValue::new(&mut offset.translation.y).speed(0.1));
+        ui.add(egui::DragValue::new(&mut offset.translation.z).speed(0.1));
+        ui.label("offset (m)");
+    });
+    ui.add(egui::Slider::new(&mut offset.pitch, -90.0..=90.0).text("pitch"));
+    ui.add(egui::Slider::new(&mut offset.yaw, -180.0..=180.0).text("yaw"));
+    ui.add(egui::Slider::new(&mut offset.fov, 20.0..=100.0).text("fov"));
+    ui.add(egui::Slider::new(&mut offset.smoothing, 0.0..=2.0).text("smoothing (s)"));
+}
@@ -0,0 +1,39 @@
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+
+use crate::capture::CapturedEvent;
+
+/// Time a capture notification is shown for.
+const TOAST_DURATION: f32 = 3.0;
+
+/// Shows where each screenshot and recording was saved.
+pub fn capture_toasts(
+    mut contexts: EguiContexts,
+    time: Res<Time>,
+    mut captured_events: EventReader<CapturedEvent>,
+    mut toasts: Local<Vec<(f32, String)>>,
+) {
+    let now = time.raw_elapsed_seconds();
+
+    toasts.extend(
+        captured_events
+            .iter()
+            .map(|CapturedEvent(message)| (now, message.clone())),
+    );
+    toasts.retain(|(shown, _)| now - shown < TOAST_DURATION);
+
+    if toasts.is_empty() {
+        return;
+    }
+
+    egui::Area::new("capture_toasts")
+        .anchor(egui::Align2::LEFT_BOTTOM, egui::vec2(10., -10.))
+        .interactable(false)
+        .show(contexts.ctx_mut(), |ui| {
+            for (_, message) in toasts.iter() {
+                egui::Frame::popup(ui.style()).show(ui, |ui| {
+                    ui.label(message);
+                });
+            }
+        });
+}
@@ -0,0 +1,179 @@
+use bevy::prelude::*;
+use bevy_egui::{
+    egui::{
+        self,
+        plot::{Line, Plot, PlotPoints, Points},
+    },
+    EguiContexts,
+};
+use leafwing_input_manager::{
+    prelude::{ActionState, InputMap, SingleAxis},
+    user_input::{InputKind, UserInput},
+    Actionlike,
+};
+
+use crate::{
+    input::{
+        default_bindings, save_controls, AxisResponse, AxisResponses, InputRebind, PlaneAction,
+        AXIS_ACTIONS,
+    },
+    plane::assist::AssistLevel,
+};
+
+const CURVE_PLOT_SIZE: f32 = 80.0;
+
+use super::WindowModel;
+
+pub fn update_controls_ui(
+    mut contexts: EguiContexts,
+    mut window_model_query: Query<&mut WindowModel>,
+    mut input_query: Query<(&mut InputMap<PlaneAction>, &ActionState<PlaneAction>)>,
+    mut rebind: ResMut<InputRebind>,
+    mut axes: ResMut<AxisResponses>,
+    mut assist: ResMut<AssistLevel>,
+) {
+    let Ok(mut window_model) = window_model_query.get_single_mut() else {
+        return;
+    };
+    let Ok((mut input_map, action_state)) = input_query.get_single_mut() else {
+        return;
+    };
+
+    let mut changed = false;
+
+    egui::Window::new("Controls")
+        .open(&mut window_model.show_controls)
+        .show(contexts.ctx_mut(), |ui| {
+            egui::ComboBox::from_label("assist")
+                .selected_text(assist.name())
+                .show_ui(ui, |ui| {
+                    for option in AssistLevel::ALL {
+                        changed |= ui
+                            .selectable_value(&mut *assist, option, option.name())
+                            .changed();
+                    }
+                });
+
+            egui::Grid::new("bindings").striped(true).show(ui, |ui| {
+                for action in PlaneAction::variants() {
+                    ui.label(format!("{:?}", action));
+
+                    ui.horizontal(|ui| {
+                        let inputs: Vec<UserInput> =
+                            input_map.get(action).iter().cloned().collect();
+
+                        for input in inputs {
+                            ui.label(binding_label(&input));
+
+                            // Axes can be inverted in place, e.g. for a throttle lever that reads backwards
+                            if let UserInput::Single(InputKind::SingleAxis(axis)) = input {
+                                let mut inverted = axis.inverted;
+                                if ui.checkbox(&mut inverted, "invert").changed() {
+                                    input_map.remove(action, input.clone());
+                                    input_map.insert(SingleAxis { inverted, ..axis }, action);
+                                    changed = true;
+                                }
+                            }
+                        }
+                    });
+
+                    ui.horizontal(|ui| {
+                        if rebind.0 == Some(action) {
+                            ui.label("Press a key, button or move an axis (Esc to cancel)");
+                        } else if ui.button("Add").clicked() {
+                            rebind.0 = Some(action);
+                        }
+                        if ui.button("Clear").clicked() {
+                            input_map.clear_action(action);
+                            changed = true;
+                        }
+                    });
+                    ui.end_row();
+                }
+            });
+
+            ui.separator();
+            ui.label("Axis response");
+
+            for action in AXIS_ACTIONS {
+                let mut response = axes.get(action);
+
+                ui.push_id(action.index(), |ui| {
+                    ui.horizontal(|ui| {
+                        ui.vertical(|ui| {
+                            ui.label(format!("{:?}", action));
+                            changed |= ui
+                                .add(
+                                    egui::Slider::new(&mut response.deadzone, 0.0..=0.9)
+                                        .text("deadzone"),
+                                )
+                                .changed();
+                            changed |= ui
+                                .add(
+                                    egui::Slider::new(
+                                        &mut response.saturation,
+                                        (response.deadzone + 0.05)..=1.0,
+                                    )
+                                    .text("saturation"),
+                                )
+                                .changed();
+                            changed |= ui
+                                .add(egui::Slider::new(&mut response.expo, 0.0..=1.0).text("expo"))
+                                .changed();
+                        });
+
+                        response_plot(ui, &response, action_state.clamped_value(action));
+                    });
+                });
+
+                axes.0.insert(action, response);
+            }
+
+            if ui.button("Reset to defaults").clicked() {
+                *input_map = default_bindings();
+                *axes = AxisResponses::default();
+                *assist = AssistLevel::default();
+                changed = true;
+            }
+        });
+
+    if changed {
+        save_controls(&input_map, &axes, *assist);
+    }
+}
+
+/// Plots the response curve with a marker at the current axis position.
+fn response_plot(ui: &mut egui::Ui, response: &AxisResponse, value: f32) {
+    let curve: PlotPoints = (0..=40)
+        .map(|i| {
+            let x = i as f32 / 20.0 - 1.0;
+            [x as f64, response.apply(x) as f64]
+        })
+        .collect();
+    let marker = Points::new(vec![[value as f64, response.apply(value) as f64]]).radius(3.0);
+
+    Plot::new("response")
+        .width(CURVE_PLOT_SIZE)
+        .height(CURVE_PLOT_SIZE)
+        .include_x(-1.0)
+        .include_x(1.0)
+        .include_y(-1.0)
+        .include_y(1.0)
+        .show_axes([false, false])
+        .allow_drag(false)
+        .allow_zoom(false)
+        .allow_scroll(false)
+        .show(ui, |plot_ui| {
+            plot_ui.line(Line::new(curve));
+            plot_ui.points(marker);
+        });
+}
+
+fn binding_label(input: &UserInput) -> String {
+    match input {
+        UserInput::Single(InputKind::Keyboard(key)) => format!("{:?}", key),
+        UserInput::Single(InputKind::GamepadButton(button)) => format!("{:?}", button),
+        UserInput::Single(InputKind::SingleAxis(axis)) => format!("{:?}", axis.axis_type),
+        other => format!("{:?}", other),
+    }
+}
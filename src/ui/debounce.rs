@@ -0,0 +1,80 @@
+use bevy::tasks::{AsyncComputeTaskPool, Task};
+use futures_lite::future;
+
+/// Seconds an input has to stay the same before it's worked out, so dragging a slider doesn't
+/// start a calculation every frame.
+const SETTLE_TIME: f32 = 0.5;
+
+/// Slow calculation worked out off the main thread once its input has settled.
+///
+/// A started calculation runs to the end as it never awaits, so dropping its task wouldn't stop
+/// it. Only one runs at once, and the latest input is started after it finishes.
+pub struct Debounced<K, T> {
+    /// Latest input, with the time it last changed.
+    wanted: Option<(K, f32)>,
+    /// Input the running calculation was started for.
+    running: Option<(K, Task<T>)>,
+    /// Latest finished result, with the input it was worked out for.
+    done: Option<(K, T)>,
+}
+
+impl<K, T> Default for Debounced<K, T> {
+    fn default() -> Self {
+        Self {
+            wanted: None,
+            running: None,
+            done: None,
+        }
+    }
+}
+
+impl<K, T> Debounced<K, T>
+where
+    K: PartialEq + Clone + Send + 'static,
+    T: Send + 'static,
+{
+    /// Picks up a finished calculation and starts one for `input` once it has been unchanged for
+    /// [`SETTLE_TIME`] and nothing else is running.
+    pub fn update(&mut self, input: &K, now: f32, calculate: fn(&K) -> T) {
+        if self.wanted.as_ref().map(|(wanted, _)| wanted) != Some(input) {
+            self.wanted = Some((input.clone(), now));
+        }
+
+        if let Some((_, task)) = self.running.as_mut() {
+            let Some(result) = future::block_on(future::poll_once(task)) else {
+                return;
+            };
+            let (input, _) = self.running.take().unwrap();
+            self.done = Some((input, result));
+        }
+
+        let Some((wanted, changed_at)) = &self.wanted else {
+            return;
+        };
+        let up_to_date = self.done.as_ref().map(|(done, _)| done) == Some(wanted);
+        if up_to_date || now - changed_at < SETTLE_TIME {
+            return;
+        }
+        let input = wanted.clone();
+        let task = AsyncComputeTaskPool::get().spawn({
+            let input = input.clone();
+            async move { calculate(&input) }
+        });
+        self.running = Some((input, task));
+    }
+
+    /// Latest finished result, which may be for an earlier input.
+    pub fn value(&self) -> Option<&T> {
+        self.done.as_ref().map(|(_, value)| value)
+    }
+
+    /// Whether the result is behind the latest input, either waiting for it to settle or being
+    /// worked out.
+    pub fn is_calculating(&self) -> bool {
+        match (&self.wanted, &self.done) {
+            (Some((wanted, _)), Some((done, _))) => wanted != done,
+            (Some(_), None) => true,
+            (None, _) => false,
+        }
+    }
+}
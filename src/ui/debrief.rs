@@ -0,0 +1,71 @@
+use bevy_egui::egui::{
+    self,
+    plot::{Line, Plot, PlotPoints},
+};
+
+use crate::{
+    recorder::{CrashReport, TelemetrySample},
+    units::{Quantity, Units},
+};
+
+use super::UiExt;
+
+const PLOT_HEIGHT: f32 = 60.0;
+
+pub fn crash_report(ui: &mut egui::Ui, report: &CrashReport, units: Units) {
+    let last = report.last_sample();
+    let width = 8;
+    let color = egui::Color32::WHITE;
+
+    ui.group(|ui| {
+        ui.label("Impact");
+        ui.float_label(
+            &format!("speed ({})", units.symbol(Quantity::Speed)),
+            units.convert(Quantity::Speed, report.impact_speed),
+            color,
+            width,
+        );
+        ui.float_label("vertical g", report.peak_load_factor(), color, width);
+        ui.float_label("pitch", last.pitch.to_degrees(), color, width);
+        ui.float_label("roll", last.roll.to_degrees(), color, width);
+    });
+
+    ui.group(|ui| {
+        ui.label("Forces at impact");
+        ui.float_label("thrust", last.thrust, color, width);
+        ui.float_label("drag", last.drag, color, width);
+    });
+
+    ui.label("Last 10 seconds");
+    telemetry_plots(ui, "crash", &report.samples);
+}
+
+/// Plots of the main flight parameters against seconds before the last sample.
+pub fn telemetry_plots(ui: &mut egui::Ui, id: &str, samples: &[TelemetrySample]) {
+    telemetry_plot(ui, id, "airspeed", samples, |s| s.airspeed);
+    telemetry_plot(ui, id, "altitude", samples, |s| s.altitude);
+    telemetry_plot(ui, id, "vertical speed", samples, |s| s.vertical_speed);
+    telemetry_plot(ui, id, "g", samples, |s| s.load_factor);
+}
+
+fn telemetry_plot(
+    ui: &mut egui::Ui,
+    id: &str,
+    label: &str,
+    samples: &[TelemetrySample],
+    value: impl Fn(&TelemetrySample) -> f32,
+) {
+    let end = samples.last().map(|sample| sample.time).unwrap_or_default();
+    let points: PlotPoints = samples
+        .iter()
+        .map(|sample| [(sample.time - end) as f64, value(sample) as f64])
+        .collect();
+
+    ui.label(label);
+    Plot::new(format!("{}-{}", id, label))
+        .height(PLOT_HEIGHT)
+        .allow_drag(false)
+        .allow_zoom(false)
+        .allow_scroll(false)
+        .show(ui, |plot_ui| plot_ui.line(Line::new(points)));
+}
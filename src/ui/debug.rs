@@ -0,0 +1,126 @@
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+
+use crate::{
+    camera::Follow,
+    flight_model::PitotStatic,
+    plane::{
+        spec::PlaneSpec, AirfoilPosition, ControlSurface, Plane, SelectedPlane, SurfaceFailure,
+    },
+    recorder::{FlightRecorder, TelemetrySample},
+    world::GizmosControl,
+};
+
+use super::{debrief, WindowModel};
+
+#[allow(clippy::too_many_arguments)]
+pub fn update_debug_ui(
+    mut commands: Commands,
+    mut contexts: EguiContexts,
+    mut window_model_query: Query<&mut WindowModel>,
+    plane_query: Query<(Entity, &PlaneSpec, &FlightRecorder, Option<&Follow>), With<Plane>>,
+    mut selected: ResMut<SelectedPlane>,
+    mut gizmos_control: ResMut<GizmosControl>,
+    children_query: Query<&Children>,
+    surface_query: Query<(Entity, &ControlSurface, &Parent, Option<&SurfaceFailure>)>,
+    position_query: Query<&AirfoilPosition>,
+    mut pitot_static_query: Query<&mut PitotStatic>,
+) {
+    let Ok(mut window_model) = window_model_query.get_single_mut() else {
+        return;
+    };
+
+    let label = |entity: Entity| -> String {
+        match plane_query.get(entity) {
+            Ok((_, spec, _, follow)) => format!(
+                "{} {:?}{}",
+                spec.name,
+                entity,
+                if follow.is_some() { " (camera)" } else { "" }
+            ),
+            Err(_) => "None".to_string(),
+        }
+    };
+
+    egui::Window::new("Debug")
+        .open(&mut window_model.show_debug)
+        .show(contexts.ctx_mut(), |ui| {
+            let mut selection = selected.0;
+
+            egui::ComboBox::from_label("plane")
+                .selected_text(selection.map(label).unwrap_or_default())
+                .show_ui(ui, |ui| {
+                    for (entity, ..) in plane_query.iter() {
+                        ui.selectable_value(&mut selection, Some(entity), label(entity));
+                    }
+                });
+
+            if selection != selected.0 {
+                selected.0 = selection;
+            }
+
+            ui.checkbox(&mut gizmos_control.show, "Gizmos");
+
+            let Some(Ok((plane, _, recorder, _))) = selection.map(|entity| plane_query.get(entity))
+            else {
+                return;
+            };
+
+            ui.collapsing("Control surfaces", |ui| {
+                egui::Grid::new("control_surfaces").show(ui, |ui| {
+                    for (surface_entity, surface, parent, failure) in children_query
+                        .iter_descendants(plane)
+                        .filter_map(|child| surface_query.get(child).ok())
+                    {
+                        let position = position_query.get(parent.get()).ok();
+                        ui.label(position.map_or("?".to_string(), |p| format!("{:?}", p)));
+                        ui.label(format!("{:.1}°", surface.angle.to_degrees()));
+
+                        let mut selected_failure = failure.copied();
+                        egui::ComboBox::from_id_source(surface_entity)
+                            .selected_text(match selected_failure {
+                                None => "Working",
+                                Some(SurfaceFailure::Jammed(_)) => "Jammed",
+                                Some(SurfaceFailure::Floating) => "Floating",
+                            })
+                            .show_ui(ui, |ui| {
+                                ui.selectable_value(&mut selected_failure, None, "Working");
+                                ui.selectable_value(
+                                    &mut selected_failure,
+                                    Some(SurfaceFailure::Jammed(surface.angle)),
+                                    "Jammed",
+                                );
+                                ui.selectable_value(
+                                    &mut selected_failure,
+                                    Some(SurfaceFailure::Floating),
+                                    "Floating",
+                                );
+                            });
+
+                        if selected_failure != failure.copied() {
+                            match selected_failure {
+                                Some(failure) => {
+                                    commands.entity(surface_entity).insert(failure);
+                                }
+                                None => {
+                                    commands.entity(surface_entity).remove::<SurfaceFailure>();
+                                }
+                            }
+                        }
+                        ui.end_row();
+                    }
+                });
+            });
+
+            if let Ok(mut pitot_static) = pitot_static_query.get_mut(plane) {
+                ui.collapsing("Pitot-static", |ui| {
+                    ui.checkbox(&mut pitot_static.pitot_blocked, "pitot blocked");
+                    ui.checkbox(&mut pitot_static.static_blocked, "static port blocked");
+                });
+            }
+
+            let samples: Vec<TelemetrySample> = recorder.samples.iter().copied().collect();
+            ui.label("Last 10 seconds");
+            debrief::telemetry_plots(ui, "debug", &samples);
+        });
+}
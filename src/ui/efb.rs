@@ -0,0 +1,179 @@
+use bevy::prelude::*;
+use bevy_egui::{
+    egui::{self, Color32, Pos2, Stroke},
+    EguiContexts,
+};
+
+use crate::{
+    plane::Plane,
+    units::{Quantity, Units},
+    world::{compass_heading, AirportSpec, Airports, RunwaySurface, TunedApproach},
+};
+
+use super::WindowModel;
+
+const DIAGRAM_SIZE: f32 = 200.0;
+
+#[derive(Component, Default)]
+pub struct EfbModel {
+    selected_airport: usize,
+    scratchpad: String,
+}
+
+pub fn setup(mut commands: Commands) {
+    commands.spawn(EfbModel::default());
+}
+
+pub fn update_efb_ui(
+    mut contexts: EguiContexts,
+    mut window_model_query: Query<&mut WindowModel>,
+    mut efb_model_query: Query<&mut EfbModel>,
+    plane_query: Query<&GlobalTransform, With<Plane>>,
+    airports: Res<Airports>,
+    mut tuned_approach: ResMut<TunedApproach>,
+    units: Res<Units>,
+) {
+    let Ok(mut window_model) = window_model_query.get_single_mut() else {
+        return;
+    };
+    let Ok(mut efb_model) = efb_model_query.get_single_mut() else {
+        return;
+    };
+
+    let plane_pos = plane_query.get_single().map(|tx| tx.translation()).ok();
+
+    egui::Window::new("Flight bag")
+        .open(&mut window_model.show_efb)
+        .show(contexts.ctx_mut(), |ui| {
+            ui.horizontal(|ui| {
+                for (i, airport) in airports.0.iter().enumerate() {
+                    ui.selectable_value(&mut efb_model.selected_airport, i, &airport.name);
+                }
+            });
+
+            let Some(airport) = airports.0.get(efb_model.selected_airport) else {
+                return;
+            };
+
+            ui.horizontal(|ui| {
+                airport_diagram(ui, airport);
+
+                ui.vertical(|ui| {
+                    let (designator, reciprocal) = airport.designators();
+
+                    let runway = match airport.surface {
+                        RunwaySurface::Asphalt => "Runway",
+                        RunwaySurface::Water => "Water lane",
+                    };
+
+                    ui.label(format!("{} {}/{}", runway, designator, reciprocal));
+                    ui.label(format!("Heading: {:03.0}°", airport.runway_heading()));
+                    let altitude = |value: f32| units.format(Quantity::Altitude, value, 0);
+                    ui.label(format!("Length: {}", altitude(airport.runway_length)));
+                    ui.label(format!("Width: {}", altitude(airport.runway_width)));
+                    ui.label(format!("Elevation: {}", altitude(airport.position.y)));
+                    ui.label(format!(
+                        "Pattern altitude: {}",
+                        altitude(airport.pattern_altitude())
+                    ));
+
+                    // Tuning an approach keeps the HUD's ILS needles on it instead of following
+                    // the nearest airport
+                    let selected = Some(efb_model.selected_airport);
+                    let mut tuned = tuned_approach.0 == selected;
+                    if ui
+                        .checkbox(&mut tuned, format!("Tune ILS {}", designator))
+                        .changed()
+                    {
+                        tuned_approach.0 = if tuned { selected } else { None };
+                    }
+
+                    if let Some(plane_pos) = plane_pos {
+                        let to_airport = airport.position - plane_pos;
+
+                        ui.separator();
+                        ui.label(format!(
+                            "Distance: {}",
+                            units.format(
+                                Quantity::Distance,
+                                (to_airport * Vec3::new(1., 0., 1.)).length(),
+                                1
+                            )
+                        ));
+                        ui.label(format!("Bearing: {:03.0}°", compass_heading(to_airport)));
+                    }
+                });
+            });
+
+            ui.label("Scratchpad");
+            ui.text_edit_multiline(&mut efb_model.scratchpad);
+        });
+}
+
+fn airport_diagram(ui: &mut egui::Ui, airport: &AirportSpec) {
+    let (response, painter) =
+        ui.allocate_painter(egui::vec2(DIAGRAM_SIZE, DIAGRAM_SIZE), egui::Sense::hover());
+    let rect = response.rect;
+    let centre = rect.center();
+
+    painter.rect_filled(rect, 4.0, Color32::from_rgb(20, 40, 20));
+
+    // Scale the runway to fill most of the diagram, keeping a minimum width so it stays visible
+    let scale = DIAGRAM_SIZE * 0.8 / airport.runway_length;
+    let half_length = airport.runway_length * 0.5 * scale;
+    let half_width = (airport.runway_width * 0.5 * scale).max(3.0);
+
+    // Screen space has north up, so world -Z maps to -y and +X maps to +x
+    let rotation = airport.rotation();
+    let to_screen = |x: f32, z: f32| -> Pos2 {
+        let world = rotation * Vec3::new(x, 0., z);
+        centre + egui::vec2(world.x, world.z)
+    };
+
+    let corners = vec![
+        to_screen(-half_width, -half_length),
+        to_screen(half_width, -half_length),
+        to_screen(half_width, half_length),
+        to_screen(-half_width, half_length),
+    ];
+    let fill = match airport.surface {
+        RunwaySurface::Asphalt => Color32::DARK_GRAY,
+        RunwaySurface::Water => Color32::from_rgb(30, 80, 120),
+    };
+    painter.add(egui::Shape::convex_polygon(
+        corners,
+        fill,
+        Stroke::new(1.0, Color32::WHITE),
+    ));
+
+    let (designator, reciprocal) = airport.designators();
+    let font = egui::FontId::proportional(12.0);
+    painter.text(
+        to_screen(0., half_length + 10.),
+        egui::Align2::CENTER_CENTER,
+        designator,
+        font.clone(),
+        Color32::WHITE,
+    );
+    painter.text(
+        to_screen(0., -half_length - 10.),
+        egui::Align2::CENTER_CENTER,
+        reciprocal,
+        font.clone(),
+        Color32::WHITE,
+    );
+
+    let north = rect.right_top() + egui::vec2(-15., 25.);
+    painter.arrow(
+        north,
+        egui::vec2(0., -15.),
+        Stroke::new(1.5, Color32::WHITE),
+    );
+    painter.text(
+        north + egui::vec2(0., 8.),
+        egui::Align2::CENTER_CENTER,
+        "N",
+        font,
+        Color32::WHITE,
+    );
+}
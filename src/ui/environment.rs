@@ -0,0 +1,277 @@
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+
+use crate::{
+    camera::{CameraSettings, FogControl},
+    settings::{ApplyEnvironmentEvent, EnvironmentPreset, EnvironmentPresets},
+    units::{Quantity, Units},
+    world::{
+        ClearWorldHistoryEvent, GizmosControl, TimeOfDay, Weather, WeatherPreset, WorldGenConfig,
+        WorldHistory, BLOCK_SPACINGS,
+    },
+};
+
+use super::WindowModel;
+
+#[allow(clippy::too_many_arguments)]
+pub fn update_environment_ui(
+    mut contexts: EguiContexts,
+    mut window_model_query: Query<&mut WindowModel>,
+    mut fog_control: Query<&mut FogControl>,
+    mut time_of_day: ResMut<TimeOfDay>,
+    mut weather: ResMut<Weather>,
+    mut camera_settings: ResMut<CameraSettings>,
+    mut gizmos_control: ResMut<GizmosControl>,
+    mut presets: ResMut<EnvironmentPresets>,
+    mut apply_events: EventWriter<ApplyEnvironmentEvent>,
+    mut world_gen_config: ResMut<WorldGenConfig>,
+    world_history: Res<WorldHistory>,
+    mut clear_history_events: EventWriter<ClearWorldHistoryEvent>,
+    mut preset_name: Local<String>,
+    // Edited apart from the config so the world is only rebuilt once the edits are applied
+    mut world_gen_draft: Local<Option<WorldGenConfig>>,
+    // Kept while the wind is calm, when it can't be worked out from the wind
+    mut wind_from: Local<f32>,
+    units: Res<Units>,
+) {
+    let Ok(mut window_model) = window_model_query.get_single_mut() else {
+        return;
+    };
+
+    egui::Window::new("Environment")
+        .open(&mut window_model.show_environment)
+        .show(contexts.ctx_mut(), |ui| {
+            ui.group(|ui| {
+                ui.label("Weather");
+                let mut preset = weather.preset;
+                egui::ComboBox::from_label("preset")
+                    .selected_text(format!("{:?}", preset))
+                    .show_ui(ui, |ui| {
+                        for option in WeatherPreset::ALL {
+                            ui.selectable_value(&mut preset, option, format!("{:?}", option));
+                        }
+                    });
+                // Only touch the resource on a real change so the cloud layer isn't rebuilt every frame
+                if preset != weather.preset {
+                    weather.preset = preset;
+                }
+
+                let (heading, mut speed) = weather.wind_heading_and_speed();
+                if speed > 0.0 {
+                    *wind_from = heading;
+                }
+                let mut from = *wind_from;
+                let mut shown_speed = units.convert(Quantity::Speed, speed);
+                let max_speed = units.convert(Quantity::Speed, 30.0);
+                let changed = ui
+                    .add(
+                        egui::Slider::new(&mut shown_speed, 0.0..=max_speed)
+                            .text(format!("wind ({})", units.symbol(Quantity::Speed))),
+                    )
+                    .changed()
+                    | ui.add(
+                        egui::Slider::new(&mut from, 0.0..=360.0)
+                            .text("wind from (°)")
+                            .fixed_decimals(0),
+                    )
+                    .changed();
+                if changed {
+                    speed = units.convert_back(Quantity::Speed, shown_speed);
+                    *wind_from = from;
+                    weather.wind = Weather::wind_from(from, speed);
+                }
+            });
+
+            // Edited as copies and only written back on a change, as the fog, sun, sky and cameras
+            // are all updated when these change
+            if let Ok(mut fog_control) = fog_control.get_single_mut() {
+                ui.group(|ui| {
+                    ui.label("Fog");
+                    let mut visibility = fog_control.visibility;
+                    if ui
+                        .add(egui::Slider::new(&mut visibility, 0.0..=5000.0).text("visibility"))
+                        .changed()
+                    {
+                        fog_control.visibility = visibility;
+                    }
+                });
+            }
+
+            ui.group(|ui| {
+                ui.label("Time of day");
+                let mut hour = time_of_day.hour;
+                if ui
+                    .add(egui::Slider::new(&mut hour, 0.0..=24.0).text("hour"))
+                    .changed()
+                {
+                    time_of_day.hour = hour;
+                }
+                let mut day_length = time_of_day.day_length;
+                if ui
+                    .add(egui::Slider::new(&mut day_length, 60.0..=3600.0).text("day length (s)"))
+                    .changed()
+                {
+                    time_of_day.day_length = day_length;
+                }
+                let mut paused = time_of_day.paused;
+                if ui.checkbox(&mut paused, "Paused").changed() {
+                    time_of_day.paused = paused;
+                }
+            });
+
+            ui.group(|ui| {
+                ui.label("Orbit and padlock cameras");
+                let mut lag = camera_settings.lag;
+                if ui
+                    .add(egui::Slider::new(&mut lag, 0.0..=2.0).text("lag (s)"))
+                    .changed()
+                {
+                    camera_settings.lag = lag;
+                }
+                let mut fov = camera_settings.fov;
+                if ui
+                    .add(egui::Slider::new(&mut fov, 20.0..=100.0).text("fov"))
+                    .changed()
+                {
+                    camera_settings.fov = fov;
+                }
+                let mut distance = camera_settings.distance;
+                if ui
+                    .add(egui::Slider::new(&mut distance, 5.0..=200.0).text("distance"))
+                    .changed()
+                {
+                    camera_settings.distance = distance;
+                }
+            });
+
+            ui.group(|ui| {
+                ui.label("Map view");
+                let mut map_span = camera_settings.map_span;
+                if ui
+                    .add(
+                        egui::Slider::new(&mut map_span, 200.0..=20000.0)
+                            .logarithmic(true)
+                            .text("span (m)"),
+                    )
+                    .changed()
+                {
+                    camera_settings.map_span = map_span;
+                }
+            });
+
+            ui.group(|ui| {
+                ui.label("World generation");
+                let draft = world_gen_draft.get_or_insert(*world_gen_config);
+
+                ui.horizontal(|ui| {
+                    ui.add(egui::DragValue::new(&mut draft.seed));
+                    ui.label("seed");
+                    if ui.button("Random").clicked() {
+                        draft.seed = rand::random();
+                    }
+                });
+                ui.add(egui::Slider::new(&mut draft.density, 0.05..=0.95).text("density"));
+                ui.add(
+                    egui::Slider::new(&mut draft.max_height, 50.0..=600.0).text("max height (m)"),
+                );
+                egui::ComboBox::from_label("spacing (m)")
+                    .selected_text(draft.spacing.to_string())
+                    .show_ui(ui, |ui| {
+                        for spacing in BLOCK_SPACINGS {
+                            ui.selectable_value(&mut draft.spacing, spacing, spacing.to_string());
+                        }
+                    });
+
+                ui.horizontal(|ui| {
+                    let changed = *draft != *world_gen_config;
+                    if ui
+                        .add_enabled(changed, egui::Button::new("Generate"))
+                        .on_hover_text("Rebuilds the world around the plane from these settings")
+                        .clicked()
+                    {
+                        *world_gen_config = *draft;
+                    }
+                    if ui
+                        .add_enabled(changed, egui::Button::new("Reset"))
+                        .clicked()
+                    {
+                        *draft = *world_gen_config;
+                    }
+                });
+
+                ui.horizontal(|ui| {
+                    let crates = world_history.crates(&world_gen_config);
+                    ui.label(format!("{} crates left in this world", crates));
+                    if ui
+                        .add_enabled(crates > 0, egui::Button::new("Clear"))
+                        .on_hover_text(
+                            "Removes everything left in the world generated with these settings",
+                        )
+                        .clicked()
+                    {
+                        clear_history_events.send(ClearWorldHistoryEvent);
+                    }
+                });
+            });
+
+            let mut show_gizmos = gizmos_control.show;
+            if ui.checkbox(&mut show_gizmos, "Gizmos").changed() {
+                gizmos_control.show = show_gizmos;
+            }
+
+            ui.group(|ui| {
+                ui.label("Presets");
+
+                let mut remove = None;
+                let mut startup = presets.startup.clone();
+                egui::Grid::new("environment_presets").show(ui, |ui| {
+                    for preset in presets.presets.iter() {
+                        ui.label(&preset.name);
+                        if ui.button("Load").clicked() {
+                            apply_events.send(ApplyEnvironmentEvent(preset.clone()));
+                        }
+                        let mut is_startup = startup.as_ref() == Some(&preset.name);
+                        if ui.checkbox(&mut is_startup, "at startup").changed() {
+                            startup = is_startup.then(|| preset.name.clone());
+                        }
+                        if ui.button("Delete").clicked() {
+                            remove = Some(preset.name.clone());
+                        }
+                        ui.end_row();
+                    }
+                });
+                if startup != presets.startup {
+                    presets.set_startup(startup);
+                }
+                if let Some(name) = remove {
+                    presets.remove(&name);
+                }
+
+                ui.horizontal(|ui| {
+                    ui.text_edit_singleline(&mut *preset_name);
+                    let name = preset_name.trim();
+                    let fog_control = fog_control.get_single().ok();
+                    if ui
+                        .add_enabled(
+                            !name.is_empty() && fog_control.is_some(),
+                            egui::Button::new("Save"),
+                        )
+                        .on_hover_text(
+                            "Saves the current environment, replacing any with the same name",
+                        )
+                        .clicked()
+                    {
+                        if let Some(fog_control) = fog_control {
+                            presets.save(EnvironmentPreset::capture(
+                                name.to_string(),
+                                &weather,
+                                fog_control,
+                                &time_of_day,
+                                &gizmos_control,
+                            ));
+                        }
+                    }
+                });
+            });
+        });
+}
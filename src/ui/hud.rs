@@ -1,53 +1,304 @@
 use bevy::{
-    math::{vec2, vec3},
+    math::vec2,
     prelude::*,
+    window::{PrimaryWindow, WindowResized},
 };
+use bevy_egui::{egui, EguiContexts, EguiSettings};
+use bevy_rapier3d::prelude::*;
+use leafwing_input_manager::prelude::ActionState;
+use serde::Deserialize;
 
-use crate::plane::spec::PlaneSpec;
+use crate::{
+    input::{MouseYoke, PlaneAction},
+    plane::{attitude, spec::PlaneSpec, Plane},
+    units::{Quantity, Units},
+    world::compass_heading,
+};
 
 use super::HudModel;
 
-#[derive(Component)]
-pub struct HudAirspeed;
+pub const HUD_COLOR: Color = Color::ORANGE;
+pub const HUD_FONT_SIZE: f32 = 16.0;
+/// Height of the HUD area that sizes on the HUD are given for, the height of the default window.
+const REFERENCE_HEIGHT: f32 = 720.0;
+/// Limits on the HUD's scale, so it stays legible on small screens and doesn't sprawl on big ones.
+const MIN_SCALE: f32 = 0.6;
+const MAX_SCALE: f32 = 3.0;
+/// Screen pixels per degree of pitch on the ladder and flight path vector.
+const PIXELS_PER_DEGREE: f32 = 6.0;
+/// Vertical extent of the pitch ladder either side of the centre.
+const LADDER_EXTENT: f32 = 90.0;
+const LADDER_STEP: usize = 10;
+const BANK_RADIUS: f32 = 120.0;
+const HEADING_TAPE_Y: f32 = -160.0;
+/// Degrees of heading either side of the centre shown on the heading tape.
+const HEADING_TAPE_RANGE: i32 = 30;
+const HEADING_TAPE_SCALE: f32 = 3.0;
+/// Half size of the box the mouse yoke cross moves within.
+const YOKE_BOX_SIZE: f32 = 60.0;
+/// Half length of the ILS needles, and their travel either side of the centre at full deflection.
+const ILS_EXTENT: f32 = 60.0;
+/// Space between the ends of the ILS needles and their scales.
+const ILS_SCALE_GAP: f32 = 10.0;
+/// Degrees of deviation at full deflection of the ILS needles.
+const GLIDESLOPE_MAX_DEVIATION: f32 = 1.4;
+const LOCALIZER_MAX_DEVIATION: f32 = 2.5;
+
+/// Distance and height from an airport within which the HUD switches to takeoff and landing.
+const APPROACH_DISTANCE: f32 = 5000.0;
+const APPROACH_HEIGHT: f32 = 300.0;
+/// Bank and pitch angles in degrees beyond which the HUD switches to aerobatics.
+const AEROBATIC_BANK: f32 = 60.0;
+const AEROBATIC_PITCH: f32 = 30.0;
+const AEROBATIC_LOAD_FACTOR: f32 = 2.5;
+/// Seconds the aerobatic HUD is kept after manoeuvring stops, so it doesn't flicker between figures.
+const AEROBATIC_HOLD: f32 = 3.0;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Deserialize)]
+pub enum HudPhase {
+    TakeoffLanding,
+    #[default]
+    Cruise,
+    Aerobatic,
+}
 
+#[derive(Resource, Default)]
+pub struct HudDeclutter {
+    /// Phase chosen with the cycle key, overriding the automatic choice.
+    manual: Option<HudPhase>,
+    phase: HudPhase,
+    aerobatic_hold: f32,
+}
+
+impl HudDeclutter {
+    pub fn phase(&self) -> HudPhase {
+        self.phase
+    }
+}
+
+/// Part of the window the HUD is laid out in, clear of the egui panels, in the 2D camera's
+/// coordinates with the origin at the centre of the window and +y up.
+#[derive(Resource)]
+pub struct HudScreen {
+    pub area: Rect,
+}
+
+impl Default for HudScreen {
+    fn default() -> Self {
+        Self {
+            area: Rect::from_center_size(Vec2::ZERO, vec2(1280., REFERENCE_HEIGHT)),
+        }
+    }
+}
+
+impl HudScreen {
+    /// Multiple of the sizes the HUD is designed at, so it takes up the same share of the height of
+    /// any screen.
+    pub fn scale(&self) -> f32 {
+        (self.area.height() / REFERENCE_HEIGHT).clamp(MIN_SCALE, MAX_SCALE)
+    }
+
+    /// Screen position of a point `offset` design pixels from an anchor, given as fractions of the
+    /// area's width and height from its bottom left.
+    pub fn place(&self, anchor: Vec2, offset: Vec2) -> Vec2 {
+        self.area.min + anchor * self.area.size() + offset * self.scale()
+    }
+
+    /// Screen position of a point offset from the centre of the area, where the flight
+    /// instruments are.
+    pub fn centre(&self, offset: Vec2) -> Vec2 {
+        self.place(Vec2::splat(0.5), offset)
+    }
+}
+
+/// Text that is built from more than a single value, which the instruments in the layout can't show.
 #[derive(Component)]
 pub enum HudLabel {
-    Altitude,
-    Airspeed,
+    DisplayBox,
+    Mode,
+    Navigation,
+    Waypoint,
+}
+
+impl HudLabel {
+    /// Design position from the centre of the HUD.
+    fn position(&self) -> Vec2 {
+        match self {
+            HudLabel::DisplayBox => vec2(0., -120.),
+            HudLabel::Mode => vec2(0., 210.),
+            HudLabel::Navigation => vec2(0., HEADING_TAPE_Y - 45.),
+            HudLabel::Waypoint => vec2(0., HEADING_TAPE_Y - 65.),
+        }
+    }
+
+    fn shown(&self, phase: HudPhase) -> bool {
+        match self {
+            HudLabel::Mode => true,
+            HudLabel::DisplayBox => phase != HudPhase::TakeoffLanding,
+            HudLabel::Navigation => phase == HudPhase::Cruise,
+            HudLabel::Waypoint => phase != HudPhase::Aerobatic,
+        }
+    }
 }
 
 pub fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
     let font = asset_server.load("fonts/FiraMono-Medium.ttf");
     let text_style = TextStyle {
         font: font.clone(),
-        font_size: 16.0,
-        color: Color::ORANGE,
+        font_size: HUD_FONT_SIZE,
+        color: HUD_COLOR,
     };
 
-    commands.spawn((
-        Text2dBundle {
-            text: Text::from_section("000", text_style.clone())
-                .with_alignment(TextAlignment::Center),
-            transform: Transform::from_translation(vec3(100., -10., 0.)),
-            ..default()
-        },
-        HudLabel::Altitude,
-    ));
-    commands.spawn((
-        Text2dBundle {
-            text: Text::from_section("000", text_style.clone())
-                .with_alignment(TextAlignment::Center),
-            transform: Transform::from_translation(vec3(100., 10., 0.)),
-            ..default()
-        },
-        HudAirspeed,
-        HudLabel::Airspeed,
-    ));
+    let labels = [
+        HudLabel::DisplayBox,
+        HudLabel::Mode,
+        HudLabel::Navigation,
+        HudLabel::Waypoint,
+    ];
+    for label in labels {
+        commands.spawn((
+            Text2dBundle {
+                text: Text::from_section("", text_style.clone())
+                    .with_alignment(TextAlignment::Center),
+                transform: Transform::from_translation(label.position().extend(0.)),
+                ..default()
+            },
+            label,
+        ));
+    }
 }
 
-pub fn hud_indicators(
+/// Measures the part of the window left for the HUD when the window is resized, the UI is rescaled
+/// or the egui panels around it change, so the HUD is centred in it and sized to it.
+pub fn update_hud_screen(
+    mut contexts: EguiContexts,
+    egui_settings: Res<EguiSettings>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    mut resized_events: EventReader<WindowResized>,
+    mut screen: ResMut<HudScreen>,
+    mut last_available: Local<Option<egui::Rect>>,
+) {
+    let resized = resized_events.iter().count() > 0;
+    let available = contexts.ctx_mut().available_rect();
+    if !resized && !egui_settings.is_changed() && *last_available == Some(available) {
+        return;
+    }
+    let Ok(window) = window_query.get_single() else {
+        return;
+    };
+    *last_available = Some(available);
+
+    // egui measures in points from the top left, each UI scale logical pixels
+    let ui_scale = egui_settings.scale_factor as f32;
+    let half = vec2(window.width(), window.height()) * 0.5;
+    let area = Rect::new(
+        available.min.x * ui_scale - half.x,
+        half.y - available.max.y * ui_scale,
+        available.max.x * ui_scale - half.x,
+        half.y - available.min.y * ui_scale,
+    );
+
+    if screen.area != area {
+        debug!("Laying out HUD in {:?}", area);
+        screen.area = area;
+    }
+}
+
+pub fn layout_hud_labels(
+    screen: Res<HudScreen>,
+    mut labels_query: Query<(&mut Transform, &mut Text, &HudLabel)>,
+) {
+    for (mut tx, mut text, label) in labels_query.iter_mut() {
+        tx.translation = screen.centre(label.position()).extend(0.);
+        text.sections[0].style.font_size = HUD_FONT_SIZE * screen.scale();
+    }
+}
+
+pub fn update_hud_phase(
+    time: Res<Time>,
+    action_query: Query<&ActionState<PlaneAction>>,
+    plane_query: Query<&GlobalTransform, With<Plane>>,
     hud_model: Query<&HudModel>,
+    mut declutter: ResMut<HudDeclutter>,
+) {
+    let Ok(hud) = hud_model.get_single() else {
+        return;
+    };
+
+    if let Ok(action_state) = action_query.get_single() {
+        if action_state.just_pressed(PlaneAction::CycleHudMode) {
+            declutter.manual = match declutter.manual {
+                None => Some(HudPhase::TakeoffLanding),
+                Some(HudPhase::TakeoffLanding) => Some(HudPhase::Cruise),
+                Some(HudPhase::Cruise) => Some(HudPhase::Aerobatic),
+                Some(HudPhase::Aerobatic) => None,
+            };
+        }
+    }
+
+    let manoeuvring = plane_query.get_single().is_ok_and(|global_tx| {
+        let (pitch, roll) = attitude(global_tx);
+        roll.to_degrees().abs() > AEROBATIC_BANK || pitch.to_degrees().abs() > AEROBATIC_PITCH
+    }) || hud.load_factor.abs() > AEROBATIC_LOAD_FACTOR;
+
+    declutter.aerobatic_hold = if manoeuvring {
+        AEROBATIC_HOLD
+    } else {
+        (declutter.aerobatic_hold - time.delta_seconds()).max(0.0)
+    };
+
+    let auto = if declutter.aerobatic_hold > 0.0 {
+        HudPhase::Aerobatic
+    } else if hud.airport_distance < APPROACH_DISTANCE && hud.height_above_airport < APPROACH_HEIGHT
+    {
+        HudPhase::TakeoffLanding
+    } else {
+        HudPhase::Cruise
+    };
+
+    let phase = declutter.manual.unwrap_or(auto);
+    if phase != declutter.phase {
+        info!("Switching HUD to {:?}", phase);
+        declutter.phase = phase;
+    }
+}
+
+pub fn hud_declutter(
+    declutter: Res<HudDeclutter>,
+    mut labels_query: Query<(&mut Visibility, &mut Text, &HudLabel)>,
+) {
+    if !declutter.is_changed() {
+        return;
+    }
+
+    for (mut visibility, mut text, label) in labels_query.iter_mut() {
+        visibility.set_if_neq(if label.shown(declutter.phase) {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        });
+
+        if let HudLabel::Mode = label {
+            let mode = format!(
+                "{:?}{}",
+                declutter.phase,
+                if declutter.manual.is_some() {
+                    ""
+                } else {
+                    " (auto)"
+                }
+            );
+            if text.sections[0].value != mode {
+                text.sections[0].value = mode;
+            }
+        }
+    }
+}
+
+pub fn hud_indicators(
+    hud_model: Query<&HudModel, Changed<HudModel>>,
     mut labels_query: Query<(&mut Text, &HudLabel)>,
+    units: Res<Units>,
 ) {
     let Ok(hud) = hud_model.get_single() else {
         return;
@@ -55,11 +306,32 @@ pub fn hud_indicators(
 
     for (mut text, label) in labels_query.iter_mut() {
         match label {
-            HudLabel::Airspeed => {
-                text.sections[0].value = format!("{:0width$.1}", hud.airspeed.abs(), width = 5)
+            HudLabel::DisplayBox => {
+                text.sections[0].value = if hud.display_multiplier > 1.0 {
+                    format!("DISPLAY BOX x{:.1}", hud.display_multiplier)
+                } else {
+                    String::new()
+                }
             }
-            HudLabel::Altitude => {
-                text.sections[0].value = format!("{:0width$.1}", hud.altitude.abs(), width = 5)
+            HudLabel::Mode => {}
+            HudLabel::Navigation => {
+                text.sections[0].value = format!(
+                    "{} {} {:03.0}",
+                    hud.nearest_airport,
+                    units.format(Quantity::Distance, hud.airport_distance, 1),
+                    hud.airport_bearing
+                );
+            }
+            HudLabel::Waypoint => {
+                text.sections[0].value = match hud.waypoint {
+                    Some((distance, bearing)) => format!(
+                        "WPT {} {:03.0} ({} left)",
+                        units.format(Quantity::Distance, distance, 1),
+                        bearing,
+                        hud.waypoints_left
+                    ),
+                    None => String::new(),
+                };
             }
         }
     }
@@ -68,6 +340,8 @@ pub fn hud_indicators(
 pub fn hud_gizmos(
     hud_model: Query<&HudModel>,
     plane_spec_query: Query<&PlaneSpec>,
+    declutter: Res<HudDeclutter>,
+    screen: Res<HudScreen>,
     mut gizmos: Gizmos,
 ) {
     let Ok(hud) = hud_model.get_single() else {
@@ -77,24 +351,245 @@ pub fn hud_gizmos(
         return;
     };
 
-    let h = 100.;
-    let x = -100.;
-    let y = (-h / 2.) + (h / hud.max_thrust * hud.thrust);
-    gizmos.rect_2d(vec2(x, 0.), 0., vec2(12., h + 8.), Color::ORANGE);
-    gizmos.line_2d(vec2(x - 5., y), vec2(x + 5., y), Color::ORANGE);
+    // Control deflections are only of interest when flying close to the limits
+    if declutter.phase == HudPhase::Cruise {
+        return;
+    }
+
+    let mut line = |start: Vec2, end: Vec2| {
+        gizmos.line_2d(screen.centre(start), screen.centre(end), HUD_COLOR);
+    };
 
     let y = 150.;
-    gizmos.line_2d(vec2(-100., y), vec2(100., y), Color::ORANGE);
-    let x = 100. / spec.wings.max_control_angle * hud.ailerons;
-    gizmos.line_2d(vec2(x, y - 5.), vec2(x, y + 5.), Color::ORANGE);
+    line(vec2(-100., y), vec2(100., y));
+    let x = 100. / spec.main_wing().max_control_angle * hud.ailerons;
+    line(vec2(x, y - 5.), vec2(x, y + 5.));
 
     let y = 180.;
-    gizmos.line_2d(vec2(-100., y), vec2(100., y), Color::ORANGE);
-    let x = 100. / spec.tail.vertical.max_control_angle * hud.rudder;
-    gizmos.line_2d(vec2(x, y - 5.), vec2(x, y + 5.), Color::ORANGE);
+    line(vec2(-100., y), vec2(100., y));
+    let x = 100. / spec.fin().max_control_angle * hud.rudder;
+    line(vec2(x, y - 5.), vec2(x, y + 5.));
 
     let x = 150.;
-    gizmos.line_2d(vec2(x, -100.), vec2(x, 100.), Color::ORANGE);
-    let y = 100. / spec.tail.horizontal.max_control_angle * hud.elevators;
-    gizmos.line_2d(vec2(x - 5., y), vec2(x + 5., y), Color::ORANGE);
+    line(vec2(x, -100.), vec2(x, 100.));
+    let y = 100. / spec.pitch_wing().max_control_angle * hud.elevators;
+    line(vec2(x - 5., y), vec2(x + 5., y));
+}
+
+pub fn hud_attitude_gizmos(
+    plane_query: Query<(&GlobalTransform, &Velocity), With<Plane>>,
+    hud_model: Query<&HudModel>,
+    declutter: Res<HudDeclutter>,
+    screen: Res<HudScreen>,
+    mut gizmos: Gizmos,
+) {
+    let Ok((global_tx, velocity)) = plane_query.get_single() else {
+        return;
+    };
+
+    let (pitch, roll) = attitude(global_tx);
+    let pitch = pitch.to_degrees();
+    let scale = screen.scale();
+    let centre = screen.centre(Vec2::ZERO);
+    let at = |p: Vec2| screen.centre(p);
+
+    // The pitch ladder rotates with the horizon as the plane rolls
+    let to_screen = |p: Vec2| at(Vec2::from_angle(roll).rotate(p));
+
+    for step in (-90_i32..=90).step_by(LADDER_STEP) {
+        let y = (step as f32 - pitch) * PIXELS_PER_DEGREE;
+
+        if y.abs() < LADDER_EXTENT {
+            let (gap, width) = if step == 0 { (15., 80.) } else { (20., 50.) };
+
+            for side in [-1., 1.] {
+                let inner = to_screen(vec2(side * gap, y));
+                let outer = to_screen(vec2(side * width, y));
+                gizmos.line_2d(inner, outer, HUD_COLOR);
+
+                // Ticks point towards the horizon, so it's clear whether the nose is above or below it
+                if step != 0 {
+                    let tick = to_screen(vec2(side * width, y - step.signum() as f32 * 6.));
+                    gizmos.line_2d(outer, tick, HUD_COLOR);
+                }
+            }
+        }
+    }
+
+    // Bank scale fixed at the top of the screen with a pointer that follows the horizon
+    gizmos
+        .arc_2d(
+            centre,
+            0.,
+            120_f32.to_radians(),
+            BANK_RADIUS * scale,
+            HUD_COLOR,
+        )
+        .segments(48);
+    for bank in [-60_f32, -45., -30., -20., -10., 0., 10., 20., 30., 45., 60.] {
+        let dir = Vec2::from_angle(f32::to_radians(bank)).rotate(Vec2::Y);
+        let length = if bank % 30. == 0. { 10. } else { 5. };
+        gizmos.line_2d(
+            at(dir * BANK_RADIUS),
+            at(dir * (BANK_RADIUS + length)),
+            HUD_COLOR,
+        );
+    }
+    let pointer = Vec2::from_angle(roll).rotate(Vec2::Y);
+    let tip = pointer * (BANK_RADIUS - 2.);
+    let side = pointer.perp() * 5.;
+    gizmos.linestrip_2d(
+        [
+            tip,
+            tip - pointer * 10. + side,
+            tip - pointer * 10. - side,
+            tip,
+        ]
+        .map(at),
+        HUD_COLOR,
+    );
+
+    // Flight path vector shows where the plane is actually going relative to the nose
+    let local_velocity = global_tx
+        .affine()
+        .inverse()
+        .transform_vector3(velocity.linvel);
+    if local_velocity.z < -1.0 {
+        let fpv = vec2(
+            local_velocity.x.atan2(-local_velocity.z).to_degrees(),
+            local_velocity.y.atan2(-local_velocity.z).to_degrees(),
+        ) * PIXELS_PER_DEGREE;
+        let fpv = fpv.clamp_length_max(LADDER_EXTENT);
+
+        gizmos.circle_2d(at(fpv), 6. * scale, HUD_COLOR);
+        gizmos.line_2d(at(fpv + vec2(6., 0.)), at(fpv + vec2(18., 0.)), HUD_COLOR);
+        gizmos.line_2d(at(fpv - vec2(6., 0.)), at(fpv - vec2(18., 0.)), HUD_COLOR);
+        gizmos.line_2d(at(fpv + vec2(0., 6.)), at(fpv + vec2(0., 14.)), HUD_COLOR);
+    }
+
+    if declutter.phase == HudPhase::Aerobatic {
+        return;
+    }
+
+    // ILS needles cross the centre, the localizer needle left of it when right of the centreline
+    // and the glideslope needle below it when above the glideslope, with two dots each side on the
+    // scales to full deflection
+    let ils = hud_model
+        .get_single()
+        .ok()
+        .and_then(|hud| hud.localizer.map(|localizer| (localizer, hud.glideslope)));
+    if let Some((localizer, glideslope)) = ils {
+        let scale_offset = ILS_EXTENT + ILS_SCALE_GAP;
+        for i in [-2., -1., 1., 2.] {
+            let dot = i * ILS_EXTENT / 2.;
+            gizmos.circle_2d(at(vec2(dot, -scale_offset)), 3. * scale, HUD_COLOR);
+            gizmos.circle_2d(at(vec2(scale_offset, dot)), 3. * scale, HUD_COLOR);
+        }
+
+        let x = (-localizer / LOCALIZER_MAX_DEVIATION * ILS_EXTENT).clamp(-ILS_EXTENT, ILS_EXTENT);
+        gizmos.line_2d(at(vec2(x, -ILS_EXTENT)), at(vec2(x, ILS_EXTENT)), HUD_COLOR);
+
+        if let Some(glideslope) = glideslope {
+            let y = (-glideslope / GLIDESLOPE_MAX_DEVIATION * ILS_EXTENT)
+                .clamp(-ILS_EXTENT, ILS_EXTENT);
+            gizmos.line_2d(at(vec2(-ILS_EXTENT, y)), at(vec2(ILS_EXTENT, y)), HUD_COLOR);
+        }
+    }
+
+    // Heading tape with a tick every 5 degrees and a taller tick every 10
+    let heading = compass_heading(global_tx.forward());
+    let start = (heading as i32 - HEADING_TAPE_RANGE).div_euclid(5) * 5;
+    gizmos.line_2d(
+        at(vec2(
+            -HEADING_TAPE_RANGE as f32 * HEADING_TAPE_SCALE,
+            HEADING_TAPE_Y,
+        )),
+        at(vec2(
+            HEADING_TAPE_RANGE as f32 * HEADING_TAPE_SCALE,
+            HEADING_TAPE_Y,
+        )),
+        HUD_COLOR,
+    );
+    for tick in (start..=start + HEADING_TAPE_RANGE * 2 + 5).step_by(5) {
+        let x = (tick as f32 - heading) * HEADING_TAPE_SCALE;
+        if x.abs() > HEADING_TAPE_RANGE as f32 * HEADING_TAPE_SCALE {
+            continue;
+        }
+
+        let length = if tick % 10 == 0 { 10. } else { 5. };
+        gizmos.line_2d(
+            at(vec2(x, HEADING_TAPE_Y)),
+            at(vec2(x, HEADING_TAPE_Y + length)),
+            HUD_COLOR,
+        );
+    }
+    gizmos.linestrip_2d(
+        [
+            vec2(-5., HEADING_TAPE_Y - 8.),
+            vec2(0., HEADING_TAPE_Y),
+            vec2(5., HEADING_TAPE_Y - 8.),
+        ]
+        .map(at),
+        HUD_COLOR,
+    );
+}
+
+pub fn hud_yoke_gizmos(yoke: Res<MouseYoke>, screen: Res<HudScreen>, mut gizmos: Gizmos) {
+    if !yoke.enabled {
+        return;
+    }
+
+    // Screen space has +y up, so the cross moves down as the yoke is pulled back
+    let pos = vec2(yoke.position.x, -yoke.position.y) * YOKE_BOX_SIZE;
+    let at = |p: Vec2| screen.centre(p);
+
+    gizmos.rect_2d(
+        at(Vec2::ZERO),
+        0.,
+        Vec2::splat(YOKE_BOX_SIZE * 2. * screen.scale()),
+        HUD_COLOR,
+    );
+    gizmos.line_2d(at(pos - vec2(8., 0.)), at(pos + vec2(8., 0.)), HUD_COLOR);
+    gizmos.line_2d(at(pos - vec2(0., 8.)), at(pos + vec2(0., 8.)), HUD_COLOR);
+}
+
+/// Chevron over the heading tape pointing at the next waypoint, pinned to the nearer end of the
+/// tape when the waypoint is off it.
+pub fn hud_waypoint_gizmos(
+    plane_query: Query<&GlobalTransform, With<Plane>>,
+    hud_model: Query<&HudModel>,
+    declutter: Res<HudDeclutter>,
+    screen: Res<HudScreen>,
+    mut gizmos: Gizmos,
+) {
+    let Ok(global_tx) = plane_query.get_single() else {
+        return;
+    };
+    let Some((_, bearing)) = hud_model.get_single().ok().and_then(|hud| hud.waypoint) else {
+        return;
+    };
+    if declutter.phase == HudPhase::Aerobatic {
+        return;
+    }
+
+    let heading = compass_heading(global_tx.forward());
+    let relative = (bearing - heading + 180.).rem_euclid(360.) - 180.;
+    let half_width = HEADING_TAPE_RANGE as f32 * HEADING_TAPE_SCALE;
+    let x = relative * HEADING_TAPE_SCALE;
+    let y = HEADING_TAPE_Y + 14.;
+    let at = |p: Vec2| screen.centre(p);
+
+    if x.abs() <= half_width {
+        gizmos.linestrip_2d(
+            [vec2(x - 6., y + 8.), vec2(x, y), vec2(x + 6., y + 8.)].map(at),
+            HUD_COLOR,
+        );
+    } else {
+        let side = x.signum();
+        let tip = vec2(side * (half_width + 14.), HEADING_TAPE_Y);
+        gizmos.linestrip_2d(
+            [tip + vec2(-side * 8., 6.), tip, tip + vec2(-side * 8., -6.)].map(at),
+            HUD_COLOR,
+        );
+    }
 }
@@ -0,0 +1,487 @@
+use std::{f32::consts::PI, fs};
+
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::units::{Quantity, Units};
+
+use super::{
+    hud::{HudDeclutter, HudPhase, HudScreen, HUD_COLOR, HUD_FONT_SIZE},
+    HudModel,
+};
+
+const LAYOUT_PATH: &str = "assets/hud.ron";
+/// Layout used when the file can't be read, e.g. when run from outside the project directory.
+const DEFAULT_LAYOUT: &str = include_str!("../../assets/hud.ron");
+/// Angle swept by a dial's scale, open at the bottom.
+const DIAL_SWEEP: f32 = PI * 1.5;
+const SLIP_BALL_RADIUS: f32 = 5.0;
+/// Height the slip ball's tube curves up by at its ends.
+const SLIP_TUBE_RISE: f32 = 6.0;
+
+/// Flight data that instruments can show, taken from the `HudModel`. Speeds, heights and
+/// distances are in SI units, and converted to the chosen `Units` by readouts.
+#[derive(Clone, Copy, Debug, Deserialize)]
+pub enum TelemetryField {
+    /// Indicated airspeed in m/s.
+    Airspeed,
+    /// True airspeed in m/s.
+    TrueAirspeed,
+    /// Indicated altitude in metres.
+    Altitude,
+    Heading,
+    AngleOfAttack,
+    Sideslip,
+    /// Thrust as a percentage of the plane's maximum.
+    Thrust,
+    LoadFactor,
+    /// Rate of climb in m/s.
+    VerticalSpeed,
+    /// Rate of turn in degrees per second, positive to the right.
+    TurnRate,
+    /// Sideways acceleration in g that throws a turn coordinator's ball, positive to the right.
+    Slip,
+    PitchRate,
+    RollRate,
+    /// Yaw rate while the main wing is stalled, positive to the right, or zero while it's flying.
+    SpinRate,
+    Drag,
+    Weight,
+    /// Height above the nearest airport in metres.
+    HeightAboveAirport,
+    /// Distance to the nearest airport in metres.
+    AirportDistance,
+    Fps,
+}
+
+impl TelemetryField {
+    fn value(&self, hud: &HudModel) -> f32 {
+        match self {
+            Self::Airspeed => hud.airspeed,
+            Self::TrueAirspeed => hud.true_airspeed,
+            Self::Altitude => hud.altitude,
+            Self::Heading => hud.heading,
+            Self::AngleOfAttack => hud.aoa,
+            Self::Sideslip => hud.sideslip,
+            Self::Thrust if hud.max_thrust > 0.0 => hud.thrust / hud.max_thrust * 100.,
+            Self::Thrust => 0.0,
+            Self::LoadFactor => hud.load_factor,
+            Self::VerticalSpeed => hud.vertical_speed,
+            Self::TurnRate => hud.turn_rate,
+            Self::Slip => hud.slip,
+            Self::PitchRate => hud.pitch_rate,
+            Self::RollRate => hud.roll_rate,
+            Self::SpinRate => hud.spin_rate,
+            Self::Drag => hud.drag,
+            Self::Weight => hud.weight,
+            Self::HeightAboveAirport => hud.height_above_airport,
+            Self::AirportDistance => hud.airport_distance,
+            Self::Fps => hud.fps,
+        }
+    }
+
+    /// Kind of value the field holds when it's one shown in a choice of units.
+    fn quantity(&self) -> Option<Quantity> {
+        match self {
+            Self::Airspeed | Self::TrueAirspeed => Some(Quantity::Speed),
+            Self::Altitude | Self::HeightAboveAirport => Some(Quantity::Altitude),
+            Self::VerticalSpeed => Some(Quantity::VerticalSpeed),
+            Self::AirportDistance => Some(Quantity::Distance),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub enum Widget {
+    /// Value as text, zero padded to `width` characters.
+    Readout {
+        field: TelemetryField,
+        position: Vec2,
+        #[serde(default)]
+        label: String,
+        #[serde(default)]
+        decimals: usize,
+        #[serde(default)]
+        width: usize,
+        #[serde(default)]
+        signed: bool,
+    },
+    /// Scale that slides past a fixed pointer at its centre.
+    Tape {
+        field: TelemetryField,
+        position: Vec2,
+        length: f32,
+        #[serde(default)]
+        vertical: bool,
+        /// Pixels per unit of the field.
+        scale: f32,
+        /// Units between ticks.
+        step: f32,
+    },
+    /// Fixed scale from `min` to `max` with a marker that moves along it.
+    Bar {
+        field: TelemetryField,
+        position: Vec2,
+        length: f32,
+        #[serde(default)]
+        vertical: bool,
+        min: f32,
+        max: f32,
+    },
+    /// Needle sweeping round a circular scale from `min` to `max`.
+    Dial {
+        field: TelemetryField,
+        position: Vec2,
+        radius: f32,
+        min: f32,
+        max: f32,
+    },
+    /// Ball in a curved tube, rolling off centre towards the side the field is positive, up to
+    /// `max` at the end of the tube.
+    SlipBall {
+        field: TelemetryField,
+        position: Vec2,
+        length: f32,
+        max: f32,
+    },
+    /// Warning text shown while the field is above or below a threshold.
+    Annunciator {
+        text: String,
+        field: TelemetryField,
+        position: Vec2,
+        #[serde(default)]
+        above: Option<f32>,
+        #[serde(default)]
+        below: Option<f32>,
+    },
+}
+
+impl Widget {
+    /// Offset from the instrument's anchor in design pixels.
+    fn position(&self) -> Vec2 {
+        match self {
+            Self::Readout { position, .. }
+            | Self::Tape { position, .. }
+            | Self::Bar { position, .. }
+            | Self::Dial { position, .. }
+            | Self::SlipBall { position, .. }
+            | Self::Annunciator { position, .. } => *position,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct Instrument {
+    pub widget: Widget,
+    /// Point on the HUD the widget's position is offset from, as fractions of its width and height
+    /// from the bottom left, so instruments can stay by an edge of any shape of window.
+    #[serde(default = "default_anchor")]
+    pub anchor: Vec2,
+    /// HUD phases the instrument is shown in, or all of them if empty.
+    #[serde(default)]
+    pub phases: Vec<HudPhase>,
+}
+
+fn default_anchor() -> Vec2 {
+    Vec2::splat(0.5)
+}
+
+impl Instrument {
+    fn shown(&self, phase: HudPhase) -> bool {
+        self.phases.is_empty() || self.phases.contains(&phase)
+    }
+
+    /// Text for instruments drawn as text, or `None` for those drawn with gizmos.
+    fn text(&self, hud: &HudModel, units: Units) -> Option<String> {
+        match &self.widget {
+            Widget::Readout {
+                field,
+                label,
+                decimals,
+                width,
+                signed,
+                ..
+            } => {
+                let value = field.value(hud);
+                let value = field
+                    .quantity()
+                    .map_or(value, |quantity| units.convert(quantity, value));
+                Some(if *signed {
+                    format!("{}{:+0width$.decimals$}", label, value)
+                } else {
+                    format!("{}{:0width$.decimals$}", label, value)
+                })
+            }
+            Widget::Annunciator {
+                text,
+                field,
+                above,
+                below,
+                ..
+            } => {
+                let value = field.value(hud);
+                let active = above.is_some_and(|limit| value > limit)
+                    || below.is_some_and(|limit| value < limit);
+                Some(if active { text.clone() } else { String::new() })
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Instruments on the HUD, read from a layout file at startup.
+#[derive(Resource, Deserialize)]
+pub struct HudLayout {
+    pub instruments: Vec<Instrument>,
+}
+
+impl HudLayout {
+    /// Checks the tapes' scales and steps are above zero, as a tape steps through its ticks by
+    /// them every frame and would go on almost forever at zero.
+    fn validate(&self) -> Result<(), String> {
+        for (index, instrument) in self.instruments.iter().enumerate() {
+            if let Widget::Tape { scale, step, .. } = &instrument.widget {
+                if !(*scale > 0.0 && *step > 0.0) {
+                    return Err(format!(
+                        "tape at instrument {} needs a scale and step above zero",
+                        index
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Text entity for the instrument at this index in the layout.
+#[derive(Component)]
+pub struct HudInstrument(usize);
+
+pub fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let layout = load_layout();
+    let text_style = TextStyle {
+        font: asset_server.load("fonts/FiraMono-Medium.ttf"),
+        font_size: HUD_FONT_SIZE,
+        color: HUD_COLOR,
+    };
+
+    for (index, instrument) in layout.instruments.iter().enumerate() {
+        if !matches!(
+            instrument.widget,
+            Widget::Readout { .. } | Widget::Annunciator { .. }
+        ) {
+            continue;
+        }
+
+        commands.spawn((
+            Text2dBundle {
+                text: Text::from_section("", text_style.clone())
+                    .with_alignment(TextAlignment::Center),
+                ..default()
+            },
+            HudInstrument(index),
+        ));
+    }
+
+    commands.insert_resource(layout);
+}
+
+fn load_layout() -> HudLayout {
+    let from_file = fs::read_to_string(LAYOUT_PATH)
+        .map_err(|err| err.to_string())
+        .and_then(|contents| ron::from_str(&contents).map_err(|err| err.to_string()))
+        .and_then(|layout: HudLayout| layout.validate().map(|()| layout));
+
+    match from_file {
+        Ok(layout) => {
+            info!("Loaded HUD layout from {}", LAYOUT_PATH);
+            layout
+        }
+        Err(err) => {
+            warn!(
+                "Using default HUD layout as {} couldn't be loaded: {}",
+                LAYOUT_PATH, err
+            );
+            ron::from_str(DEFAULT_LAYOUT).expect("default HUD layout is valid")
+        }
+    }
+}
+
+pub fn update_instrument_text(
+    layout: Res<HudLayout>,
+    declutter: Res<HudDeclutter>,
+    hud_model: Query<&HudModel>,
+    mut text_query: Query<(&mut Text, &mut Visibility, &HudInstrument)>,
+    units: Res<Units>,
+) {
+    let Ok(hud) = hud_model.get_single() else {
+        return;
+    };
+
+    for (mut text, mut visibility, HudInstrument(index)) in text_query.iter_mut() {
+        let Some(instrument) = layout.instruments.get(*index) else {
+            continue;
+        };
+
+        visibility.set_if_neq(if instrument.shown(declutter.phase()) {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        });
+
+        if let Some(value) = instrument.text(hud, *units) {
+            if text.sections[0].value != value {
+                text.sections[0].value = value;
+            }
+        }
+    }
+}
+
+pub fn layout_instrument_text(
+    layout: Res<HudLayout>,
+    screen: Res<HudScreen>,
+    mut text_query: Query<(&mut Transform, &mut Text, &HudInstrument)>,
+) {
+    for (mut transform, mut text, HudInstrument(index)) in text_query.iter_mut() {
+        let Some(instrument) = layout.instruments.get(*index) else {
+            continue;
+        };
+
+        let position = screen.place(instrument.anchor, instrument.widget.position());
+        transform.translation = position.extend(0.);
+        text.sections[0].style.font_size = HUD_FONT_SIZE * screen.scale();
+    }
+}
+
+pub fn instrument_gizmos(
+    layout: Res<HudLayout>,
+    declutter: Res<HudDeclutter>,
+    hud_model: Query<&HudModel>,
+    screen: Res<HudScreen>,
+    mut gizmos: Gizmos,
+) {
+    let Ok(hud) = hud_model.get_single() else {
+        return;
+    };
+    let scale = screen.scale();
+
+    for instrument in layout.instruments.iter() {
+        if !instrument.shown(declutter.phase()) {
+            continue;
+        }
+
+        // Widgets are drawn around their own origin in design pixels, then placed and scaled
+        let origin = screen.place(instrument.anchor, instrument.widget.position());
+        let at = |p: Vec2| origin + p * scale;
+
+        match &instrument.widget {
+            Widget::Tape {
+                field,
+                length,
+                vertical,
+                scale: units_scale,
+                step,
+                ..
+            } => {
+                // Along the tape and across it, towards the side the ticks are drawn
+                let (along, across) = if *vertical {
+                    (Vec2::Y, Vec2::X)
+                } else {
+                    (Vec2::X, Vec2::Y)
+                };
+                let half = length * 0.5;
+                let value = field.value(hud);
+
+                gizmos.line_2d(at(-along * half), at(along * half), HUD_COLOR);
+
+                let first = ((value - half / units_scale) / step).ceil() as i32;
+                let last = ((value + half / units_scale) / step).floor() as i32;
+                for tick in first..=last {
+                    let offset = (tick as f32 * step - value) * units_scale;
+                    let size = if tick % 2 == 0 { 10. } else { 5. };
+                    let base = along * offset;
+                    gizmos.line_2d(at(base), at(base + across * size), HUD_COLOR);
+                }
+
+                gizmos.linestrip_2d(
+                    [
+                        -across * 8. - along * 5.,
+                        Vec2::ZERO,
+                        -across * 8. + along * 5.,
+                    ]
+                    .map(at),
+                    HUD_COLOR,
+                );
+            }
+            Widget::Bar {
+                field,
+                length,
+                vertical,
+                min,
+                max,
+                ..
+            } => {
+                let (along, across) = if *vertical {
+                    (Vec2::Y, Vec2::X)
+                } else {
+                    (Vec2::X, Vec2::Y)
+                };
+                let fraction = ((field.value(hud) - min) / (max - min)).clamp(0.0, 1.0);
+                let marker = along * (fraction - 0.5) * *length;
+
+                let size = along * (length + 8.) + across * 12.;
+                gizmos.rect_2d(origin, 0., size.abs() * scale, HUD_COLOR);
+                gizmos.line_2d(
+                    at(marker - across * 5.),
+                    at(marker + across * 5.),
+                    HUD_COLOR,
+                );
+            }
+            Widget::Dial {
+                field,
+                radius,
+                min,
+                max,
+                ..
+            } => {
+                let fraction = ((field.value(hud) - min) / (max - min)).clamp(0.0, 1.0);
+                // Scale runs clockwise from the bottom left
+                let angle = DIAL_SWEEP * 0.5 - fraction * DIAL_SWEEP;
+                let needle = Vec2::from_angle(angle).rotate(Vec2::Y);
+
+                gizmos
+                    .arc_2d(origin, 0., DIAL_SWEEP, radius * scale, HUD_COLOR)
+                    .segments(32);
+                for i in 0..=10 {
+                    let dir = Vec2::from_angle(DIAL_SWEEP * (0.5 - i as f32 / 10.)).rotate(Vec2::Y);
+                    gizmos.line_2d(at(dir * *radius), at(dir * (radius - 5.)), HUD_COLOR);
+                }
+                gizmos.line_2d(origin, at(needle * (radius - 8.)), HUD_COLOR);
+            }
+            Widget::SlipBall {
+                field, length, max, ..
+            } => {
+                let half = length * 0.5;
+                let fraction = (field.value(hud) / max).clamp(-1.0, 1.0);
+                // Tube curves up at the ends, as the ball sits at its lowest point when centred
+                let tube = |x: f32| Vec2::new(x * half, x * x * SLIP_TUBE_RISE);
+
+                gizmos.linestrip_2d(
+                    (0..=16).map(|i| at(tube(i as f32 / 8. - 1.) - Vec2::Y * SLIP_BALL_RADIUS)),
+                    HUD_COLOR,
+                );
+                for side in [-1., 1.] {
+                    let x = Vec2::X * side * SLIP_BALL_RADIUS * 1.5;
+                    gizmos.line_2d(
+                        at(x - Vec2::Y * SLIP_BALL_RADIUS),
+                        at(x + Vec2::Y * SLIP_BALL_RADIUS),
+                        HUD_COLOR,
+                    );
+                }
+                gizmos.circle_2d(at(tube(fraction)), SLIP_BALL_RADIUS * scale, HUD_COLOR);
+            }
+            Widget::Readout { .. } | Widget::Annunciator { .. } => {}
+        }
+    }
+}
@@ -0,0 +1,89 @@
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+
+use crate::{
+    camera::Follow,
+    plane::{
+        spec::PlaneSpec,
+        tuning::{self, ReferenceSpeeds},
+        Plane,
+    },
+    units::{Quantity, Units},
+};
+
+use super::{debounce::Debounced, WindowModel};
+
+/// Card of the speeds to fly the flown plane at, worked out again whenever it's rebuilt so a new
+/// design's stall speed doesn't have to be found by trial and error.
+pub fn update_kneeboard_ui(
+    mut contexts: EguiContexts,
+    mut window_model_query: Query<&mut WindowModel>,
+    plane_query: Query<&PlaneSpec, (With<Plane>, With<Follow>)>,
+    mut speeds: Local<Debounced<PlaneSpec, ReferenceSpeeds>>,
+    time: Res<Time>,
+    units: Res<Units>,
+) {
+    let Ok(mut window_model) = window_model_query.get_single_mut() else {
+        return;
+    };
+
+    // Worked out while the window is closed too, so a rebuild isn't missed. The speeds only
+    // depend on the design, so a rebuild of the same one keeps them.
+    let plane = plane_query.get_single().ok();
+    if let Some(spec) = plane {
+        speeds.update(spec, time.raw_elapsed_seconds(), tuning::reference_speeds);
+    }
+
+    egui::Window::new("Kneeboard")
+        .open(&mut window_model.show_kneeboard)
+        .show(contexts.ctx_mut(), |ui| {
+            let Some(spec) = plane else {
+                ui.label("No plane");
+                return;
+            };
+            if spec.rotor.is_some() {
+                ui.label("Helicopters have no reference speeds");
+                return;
+            }
+            let Some(speeds) = speeds.value().filter(|_| !speeds.is_calculating()) else {
+                ui.label("calculating…");
+                return;
+            };
+
+            let speed_or = |speed: Option<f32>, otherwise: &str| {
+                speed.map_or(otherwise.to_string(), |speed| {
+                    units.format(Quantity::Speed, speed, 0)
+                })
+            };
+            egui::Grid::new("reference speeds")
+                .striped(true)
+                .show(ui, |ui| {
+                    ui.label("Vs");
+                    ui.label("stall");
+                    ui.label(speed_or(speeds.stall, "can't fly level"));
+                    ui.end_row();
+
+                    ui.label("Vref");
+                    ui.label("approach");
+                    ui.label(speed_or(speeds.approach, "-"));
+                    ui.end_row();
+
+                    ui.label("Vy");
+                    ui.label("best climb");
+                    ui.label(speed_or(speeds.best_climb, "can't climb"));
+                    ui.end_row();
+
+                    ui.label("Vbg");
+                    ui.label("best glide");
+                    ui.label(speed_or(speeds.best_glide, "-"));
+                    ui.end_row();
+
+                    ui.label("Vh");
+                    ui.label("max level");
+                    ui.label(speed_or(speeds.max_level, "can't hold height"));
+                    ui.end_row();
+                });
+
+            ui.label("No flaps are fitted, so the stall speed holds for landing too.");
+        });
+}
@@ -0,0 +1,203 @@
+use bevy::{
+    math::{vec2, vec3},
+    prelude::*,
+};
+use bevy_egui::{
+    egui::{self, Color32, Pos2, Stroke},
+    EguiContexts,
+};
+use leafwing_input_manager::prelude::ActionState;
+
+use crate::{
+    input::PlaneAction,
+    navigation::Waypoints,
+    plane::Plane,
+    units::{Quantity, Units},
+    world::{compass_heading, Airports, Building, RunwaySurface, WorldGenConfig},
+};
+
+const MAP_SIZE: f32 = 200.0;
+/// Range of distances from the plane to the edge of the map, in metres.
+const MAP_RANGES: std::ops::RangeInclusive<f32> = 250.0..=8000.0;
+
+/// Distance shown from the plane to the edge of the minimap, zoomed with the map keys.
+#[derive(Resource)]
+pub struct Minimap {
+    range: f32,
+}
+
+impl Default for Minimap {
+    fn default() -> Self {
+        Self { range: 1000.0 }
+    }
+}
+
+pub fn control_minimap(
+    action_query: Query<&ActionState<PlaneAction>>,
+    mut minimap: ResMut<Minimap>,
+) {
+    let Ok(action_state) = action_query.get_single() else {
+        return;
+    };
+
+    if action_state.just_pressed(PlaneAction::ZoomMapIn) {
+        minimap.range = (minimap.range * 0.5).max(*MAP_RANGES.start());
+    }
+    if action_state.just_pressed(PlaneAction::ZoomMapOut) {
+        minimap.range = (minimap.range * 2.0).min(*MAP_RANGES.end());
+    }
+}
+
+/// Map in the corner of the screen turning with the plane, so what's ahead is always up. Clicking
+/// the map adds a waypoint there to the end of the route.
+#[allow(clippy::too_many_arguments)]
+pub fn update_minimap_ui(
+    mut contexts: EguiContexts,
+    minimap: Res<Minimap>,
+    plane_query: Query<&GlobalTransform, With<Plane>>,
+    building_query: Query<(&GlobalTransform, &Building)>,
+    airports: Res<Airports>,
+    world_gen_config: Res<WorldGenConfig>,
+    mut waypoints: ResMut<Waypoints>,
+    units: Res<Units>,
+) {
+    let Ok(plane_tx) = plane_query.get_single() else {
+        return;
+    };
+
+    let plane_pos = plane_tx.translation();
+    let heading = compass_heading(plane_tx.forward());
+    let scale = MAP_SIZE * 0.5 / minimap.range;
+    // Turn the map against the heading, with world -Z as screen -y when flying north
+    let rotation = Vec2::from_angle(-heading.to_radians());
+    let to_map = |centre: Pos2, pos: Vec3| -> Pos2 {
+        let offset = rotation.rotate(vec2(pos.x - plane_pos.x, pos.z - plane_pos.z)) * scale;
+        centre + egui::vec2(offset.x, offset.y)
+    };
+
+    egui::Area::new("minimap")
+        .anchor(egui::Align2::RIGHT_BOTTOM, egui::vec2(-10., -10.))
+        .show(contexts.ctx_mut(), |ui| {
+            let (response, painter) =
+                ui.allocate_painter(egui::vec2(MAP_SIZE, MAP_SIZE), egui::Sense::click());
+            let rect = response.rect;
+            let centre = rect.center();
+            let painter = painter.with_clip_rect(rect);
+
+            painter.rect_filled(rect, 4.0, Color32::from_rgba_unmultiplied(20, 40, 20, 200));
+
+            for airport in airports.0.iter() {
+                let half_length = airport.runway_length * 0.5;
+                let half_width = airport.runway_width * 0.5;
+                let corners = [
+                    (-half_width, -half_length),
+                    (half_width, -half_length),
+                    (half_width, half_length),
+                    (-half_width, half_length),
+                ]
+                .into_iter()
+                .map(|(x, z)| {
+                    to_map(
+                        centre,
+                        airport.position + airport.rotation() * vec3(x, 0., z),
+                    )
+                })
+                .collect();
+                let fill = match airport.surface {
+                    RunwaySurface::Asphalt => Color32::DARK_GRAY,
+                    RunwaySurface::Water => Color32::from_rgb(30, 80, 120),
+                };
+                painter.add(egui::Shape::convex_polygon(
+                    corners,
+                    fill,
+                    Stroke::new(1.0, Color32::WHITE),
+                ));
+            }
+
+            // Buildings are drawn square to the map rather than turned, as they're only a few pixels
+            for (building_tx, building) in building_query.iter() {
+                let pos = to_map(centre, building_tx.translation());
+                if !rect.expand(MAP_SIZE * 0.1).contains(pos) {
+                    continue;
+                }
+                // Towers are taller than the tallest buildings
+                let height = (building.height / world_gen_config.max_height).min(1.0);
+                let shade = 80 + (height * 175.0) as u8;
+                let half_side = (building.side * 0.5 * scale).max(1.0);
+                painter.rect_filled(
+                    egui::Rect::from_center_size(pos, egui::Vec2::splat(half_side * 2.0)),
+                    0.0,
+                    Color32::from_rgb(shade, shade, shade),
+                );
+            }
+
+            // Route from the plane through each waypoint in turn
+            let route: Vec<_> = std::iter::once(centre)
+                .chain(waypoints.0.iter().map(|waypoint| to_map(centre, *waypoint)))
+                .collect();
+            painter.add(egui::Shape::line(
+                route.clone(),
+                Stroke::new(1.0, Color32::LIGHT_BLUE),
+            ));
+            for (i, pos) in route.iter().skip(1).enumerate() {
+                if i == 0 {
+                    painter.circle_filled(*pos, 4.0, Color32::from_rgb(0, 255, 255));
+                } else {
+                    painter.circle_stroke(
+                        *pos,
+                        4.0,
+                        Stroke::new(1.0, Color32::from_rgb(0, 255, 255)),
+                    );
+                }
+            }
+
+            if let Some(click) = response
+                .interact_pointer_pos()
+                .filter(|_| response.clicked())
+            {
+                // Turn back by the heading to get from the map to the world
+                let offset = (click - centre) / scale;
+                let offset =
+                    Vec2::from_angle(heading.to_radians()).rotate(vec2(offset.x, offset.y));
+                waypoints
+                    .0
+                    .push(vec3(plane_pos.x + offset.x, 0., plane_pos.z + offset.y));
+            }
+
+            // North marker on the edge of the map, in the direction of north from the plane
+            let north = rotation.rotate(Vec2::NEG_Y) * (MAP_SIZE * 0.5 - 12.0);
+            painter.text(
+                centre + egui::vec2(north.x, north.y),
+                egui::Align2::CENTER_CENTER,
+                "N",
+                egui::FontId::proportional(14.0),
+                Color32::WHITE,
+            );
+
+            painter.add(egui::Shape::convex_polygon(
+                vec![
+                    centre + egui::vec2(0., -8.),
+                    centre + egui::vec2(6., 6.),
+                    centre + egui::vec2(-6., 6.),
+                ],
+                Color32::YELLOW,
+                Stroke::new(1.0, Color32::BLACK),
+            ));
+
+            let font = egui::FontId::proportional(12.0);
+            painter.text(
+                rect.center_top() + egui::vec2(0., 4.),
+                egui::Align2::CENTER_TOP,
+                format!("{:03.0}°", heading),
+                font.clone(),
+                Color32::WHITE,
+            );
+            painter.text(
+                rect.left_bottom() + egui::vec2(4., -4.),
+                egui::Align2::LEFT_BOTTOM,
+                units.format(Quantity::Distance, minimap.range, 1),
+                font,
+                Color32::WHITE,
+            );
+        });
+}
@@ -0,0 +1,75 @@
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+
+use crate::world::{AbortMissionEvent, Courses, Ghosts, Mission, StartMissionEvent};
+
+use super::{UiExt, WindowModel};
+
+#[allow(clippy::too_many_arguments)]
+pub fn update_missions_ui(
+    mut contexts: EguiContexts,
+    mut window_model_query: Query<&mut WindowModel>,
+    courses: Res<Courses>,
+    mission: Res<Mission>,
+    ghosts: Res<Ghosts>,
+    mut start_events: EventWriter<StartMissionEvent>,
+    mut abort_events: EventWriter<AbortMissionEvent>,
+    time: Res<Time>,
+) {
+    let Ok(mut window_model) = window_model_query.get_single_mut() else {
+        return;
+    };
+
+    let width = 8;
+    let color = egui::Color32::WHITE;
+
+    egui::Window::new("Missions")
+        .open(&mut window_model.show_missions)
+        .show(contexts.ctx_mut(), |ui| {
+            ui.group(|ui| {
+                ui.label("Ring courses");
+                for (i, course) in courses.0.iter().enumerate() {
+                    ui.horizontal(|ui| {
+                        ui.label(format!(
+                            "{} ({} rings, par {:.0}s)",
+                            course.name,
+                            course.rings.len(),
+                            course.par_time
+                        ));
+                        if let Some(best) = ghosts.best_time(i) {
+                            ui.label(format!("best {:.1}s", best));
+                        }
+                        if ui.button("Start").clicked() {
+                            start_events.send(StartMissionEvent(i));
+                        }
+                    });
+                }
+            });
+
+            let Some(course) = mission.course.and_then(|i| courses.0.get(i)) else {
+                return;
+            };
+
+            ui.group(|ui| {
+                ui.label(&course.name);
+                ui.label(format!(
+                    "ring {} / {}",
+                    mission.next_ring, mission.ring_count
+                ));
+                ui.float_label(
+                    "time (s)",
+                    mission.elapsed(time.elapsed_seconds()),
+                    color,
+                    width,
+                );
+                ui.float_label("score", mission.score, color, width);
+
+                if mission.finished {
+                    ui.colored_label(egui::Color32::GREEN, "Finished");
+                }
+                if ui.button("Abort").clicked() {
+                    abort_events.send(AbortMissionEvent);
+                }
+            });
+        });
+}
@@ -0,0 +1,97 @@
+use bevy::{math::vec3, prelude::*};
+use bevy_egui::{egui, EguiContexts};
+
+use crate::{
+    navigation::Waypoints,
+    plane::Plane,
+    units::{Quantity, Units},
+    world::WorldOrigin,
+};
+
+use super::WindowModel;
+
+/// Lists the route of waypoints, and adds waypoints at coordinates east and south of the world
+/// origin in the chosen distance unit, which in metric are the exported map's grid labels.
+pub fn update_navigation_ui(
+    mut contexts: EguiContexts,
+    mut window_model_query: Query<&mut WindowModel>,
+    mut waypoints: ResMut<Waypoints>,
+    origin: Res<WorldOrigin>,
+    plane_query: Query<&GlobalTransform, With<Plane>>,
+    mut coordinates: Local<Vec2>,
+    units: Res<Units>,
+) {
+    let Ok(mut window_model) = window_model_query.get_single_mut() else {
+        return;
+    };
+
+    let plane_pos = plane_query.get_single().map(|tx| tx.translation()).ok();
+
+    egui::Window::new("Navigation")
+        .open(&mut window_model.show_navigation)
+        .show(contexts.ctx_mut(), |ui| {
+            ui.label("Click the minimap to add a waypoint there.");
+
+            let mut remove = None;
+            egui::Grid::new("waypoints").striped(true).show(ui, |ui| {
+                for (i, waypoint) in waypoints.0.iter().enumerate() {
+                    let absolute = origin.to_absolute(*waypoint);
+                    ui.label(format!("{}", i + 1));
+                    ui.label(format!(
+                        "{} E {} S",
+                        units.format(Quantity::Distance, absolute.x, 1),
+                        units.format(Quantity::Distance, absolute.z, 1)
+                    ));
+                    if let Some(pos) = plane_pos {
+                        let distance = ((*waypoint - pos) * Vec3::new(1., 0., 1.)).length();
+                        ui.label(units.format(Quantity::Distance, distance, 1));
+                    }
+                    if ui.button("Remove").clicked() {
+                        remove = Some(i);
+                    }
+                    ui.end_row();
+                }
+            });
+            if let Some(i) = remove {
+                waypoints.0.remove(i);
+            }
+
+            // Typed in the chosen unit but kept in metres, so changing units doesn't move them
+            let symbol = units.symbol(Quantity::Distance);
+            ui.horizontal(|ui| {
+                let mut east = units.convert(Quantity::Distance, coordinates.x);
+                let mut south = units.convert(Quantity::Distance, coordinates.y);
+                if ui
+                    .add(
+                        egui::DragValue::new(&mut east)
+                            .speed(0.1)
+                            .suffix(format!(" {} E", symbol)),
+                    )
+                    .changed()
+                {
+                    coordinates.x = units.convert_back(Quantity::Distance, east);
+                }
+                if ui
+                    .add(
+                        egui::DragValue::new(&mut south)
+                            .speed(0.1)
+                            .suffix(format!(" {} S", symbol)),
+                    )
+                    .changed()
+                {
+                    coordinates.y = units.convert_back(Quantity::Distance, south);
+                }
+                if ui.button("Add").clicked() {
+                    let absolute = vec3(coordinates.x, 0., coordinates.y);
+                    waypoints.0.push(origin.to_local(absolute));
+                }
+            });
+
+            if ui
+                .add_enabled(!waypoints.0.is_empty(), egui::Button::new("Clear"))
+                .clicked()
+            {
+                waypoints.0.clear();
+            }
+        });
+}
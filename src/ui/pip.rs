@@ -0,0 +1,52 @@
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+
+use crate::camera::{FollowKind, PipView, PIP_SIZE};
+
+use super::WindowModel;
+
+/// Views the picture-in-picture can show, those fixed to the plane.
+const PIP_VIEWS: [FollowKind; 5] = [
+    FollowKind::Behind,
+    FollowKind::Above,
+    FollowKind::Side,
+    FollowKind::Inside,
+    FollowKind::Wing,
+];
+
+/// Shows the picture-in-picture view in its own window, drawing it only while the window is open.
+pub fn update_pip_ui(
+    mut contexts: EguiContexts,
+    mut window_model_query: Query<&mut WindowModel>,
+    mut pip: ResMut<PipView>,
+) {
+    let Ok(mut window_model) = window_model_query.get_single_mut() else {
+        return;
+    };
+
+    if pip.enabled != window_model.show_pip {
+        pip.enabled = window_model.show_pip;
+    }
+
+    let texture = contexts.add_image(pip.image.clone_weak());
+    let mut kind = pip.kind;
+
+    egui::Window::new("Picture in picture")
+        .open(&mut window_model.show_pip)
+        .resizable(false)
+        .show(contexts.ctx_mut(), |ui| {
+            egui::ComboBox::from_label("view")
+                .selected_text(format!("{:?}", kind))
+                .show_ui(ui, |ui| {
+                    for option in PIP_VIEWS {
+                        ui.selectable_value(&mut kind, option, format!("{:?}", option));
+                    }
+                });
+
+            ui.image(texture, [PIP_SIZE.x as f32, PIP_SIZE.y as f32]);
+        });
+
+    if pip.kind != kind {
+        pip.kind = kind;
+    }
+}
@@ -0,0 +1,89 @@
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+
+use crate::scenario::{
+    RunScenarioEvent, ScenarioMessageEvent, Scenarios, StopScenarioEvent, SCENARIOS_DIR,
+};
+
+use super::{UiExt, WindowModel};
+
+/// Time a message from a scenario is shown for.
+const TOAST_DURATION: f32 = 5.0;
+
+pub fn update_scenarios_ui(
+    mut contexts: EguiContexts,
+    mut window_model_query: Query<&mut WindowModel>,
+    mut scenarios: ResMut<Scenarios>,
+    mut run_events: EventWriter<RunScenarioEvent>,
+    mut stop_events: EventWriter<StopScenarioEvent>,
+) {
+    let Ok(mut window_model) = window_model_query.get_single_mut() else {
+        return;
+    };
+
+    egui::Window::new("Scenarios")
+        .open(&mut window_model.show_scenarios)
+        .show(contexts.ctx_mut(), |ui| {
+            ui.group(|ui| {
+                ui.label(format!("Scripts in {}/", SCENARIOS_DIR));
+                if scenarios.available.is_empty() {
+                    ui.label("none found");
+                }
+                for path in scenarios.available.iter() {
+                    ui.horizontal(|ui| {
+                        ui.label(path.file_stem().unwrap_or_default().to_string_lossy());
+                        if ui.button("Run").clicked() {
+                            run_events.send(RunScenarioEvent(path.clone()));
+                        }
+                    });
+                }
+                if ui.button("Rescan").clicked() {
+                    scenarios.rescan();
+                }
+            });
+
+            let Some(name) = scenarios.running() else {
+                return;
+            };
+
+            ui.group(|ui| {
+                ui.label(format!("Running {}", name));
+                ui.float_label("time (s)", scenarios.elapsed(), egui::Color32::WHITE, 8);
+                if ui.button("Stop").clicked() {
+                    stop_events.send(StopScenarioEvent);
+                }
+            });
+        });
+}
+
+/// Shows the messages the running scenario sends the pilot.
+pub fn scenario_toasts(
+    mut contexts: EguiContexts,
+    time: Res<Time>,
+    mut message_events: EventReader<ScenarioMessageEvent>,
+    mut toasts: Local<Vec<(f32, String)>>,
+) {
+    let now = time.raw_elapsed_seconds();
+
+    toasts.extend(
+        message_events
+            .iter()
+            .map(|ScenarioMessageEvent(message)| (now, message.clone())),
+    );
+    toasts.retain(|(shown, _)| now - shown < TOAST_DURATION);
+
+    if toasts.is_empty() {
+        return;
+    }
+
+    egui::Area::new("scenario_toasts")
+        .anchor(egui::Align2::CENTER_TOP, egui::vec2(0., 40.))
+        .interactable(false)
+        .show(contexts.ctx_mut(), |ui| {
+            for (_, message) in toasts.iter() {
+                egui::Frame::popup(ui.style()).show(ui, |ui| {
+                    ui.label(message);
+                });
+            }
+        });
+}
@@ -0,0 +1,135 @@
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+
+use crate::{
+    camera::CameraSettings,
+    damage::GForceLimits,
+    display::{save_display_settings, DisplayMode, DisplaySettings, RESOLUTIONS},
+    net::NetSession,
+    sound::MusicSettings,
+    units::Units,
+};
+
+use super::WindowModel;
+
+pub fn update_settings_ui(
+    mut contexts: EguiContexts,
+    mut window_model_query: Query<&mut WindowModel>,
+    mut display_settings: ResMut<DisplaySettings>,
+    mut net_session: ResMut<NetSession>,
+    mut music_settings: ResMut<MusicSettings>,
+    mut g_force_limits: ResMut<GForceLimits>,
+    mut camera_settings: ResMut<CameraSettings>,
+) {
+    let Ok(mut window_model) = window_model_query.get_single_mut() else {
+        return;
+    };
+
+    let mut settings = display_settings.clone();
+    // The UI scale is saved once its slider is let go, rather than on every frame it's dragged
+    let mut scale_dragged = false;
+    let mut scale_released = false;
+
+    egui::Window::new("Settings")
+        .open(&mut window_model.show_settings)
+        .show(contexts.ctx_mut(), |ui| {
+            ui.label("Display");
+
+            egui::ComboBox::from_label("mode")
+                .selected_text(format!("{:?}", settings.mode))
+                .show_ui(ui, |ui| {
+                    for option in DisplayMode::ALL {
+                        ui.selectable_value(&mut settings.mode, option, format!("{:?}", option));
+                    }
+                });
+
+            // Borderless always fills the screen at its native size
+            ui.add_enabled_ui(settings.mode != DisplayMode::Borderless, |ui| {
+                egui::ComboBox::from_label("resolution")
+                    .selected_text(format!(
+                        "{}x{}",
+                        settings.resolution.0, settings.resolution.1
+                    ))
+                    .show_ui(ui, |ui| {
+                        for option in RESOLUTIONS {
+                            ui.selectable_value(
+                                &mut settings.resolution,
+                                option,
+                                format!("{}x{}", option.0, option.1),
+                            );
+                        }
+                    });
+            });
+
+            ui.checkbox(&mut settings.vsync, "vsync");
+            let scale =
+                ui.add(egui::Slider::new(&mut settings.ui_scale, 0.5..=2.0).text("UI scale"));
+            scale_dragged = scale.dragged();
+            scale_released = scale.drag_released() || scale.lost_focus();
+            egui::ComboBox::from_label("units")
+                .selected_text(settings.units.name())
+                .show_ui(ui, |ui| {
+                    for option in Units::ALL {
+                        ui.selectable_value(&mut settings.units, option, option.name());
+                    }
+                });
+
+            if ui.button("Reset to defaults").clicked() {
+                settings = DisplaySettings::default();
+            }
+
+            ui.separator();
+            ui.label("Sound");
+
+            ui.checkbox(&mut music_settings.muted, "mute music");
+
+            ui.separator();
+            ui.label("Camera");
+
+            ui.add(egui::Slider::new(&mut camera_settings.shake, 0.0..=2.0).text("shake"));
+
+            ui.separator();
+            ui.label("Realism");
+
+            ui.checkbox(
+                &mut g_force_limits.structural,
+                "wings fail when overstressed",
+            );
+            ui.checkbox(
+                &mut g_force_limits.physiological,
+                "pilot greys out and blacks out under g",
+            );
+
+            ui.separator();
+            ui.label("Multiplayer");
+
+            ui.add_enabled_ui(!net_session.connected(), |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("port");
+                    ui.add(egui::DragValue::new(&mut net_session.port));
+                });
+                ui.horizontal(|ui| {
+                    ui.label("peer");
+                    ui.text_edit_singleline(&mut net_session.peer);
+                });
+            });
+
+            if net_session.connected() {
+                if ui.button("Disconnect").clicked() {
+                    net_session.disconnect();
+                }
+            } else if ui.button("Connect").clicked() {
+                net_session.connect();
+            }
+        });
+
+    // Changing the resource reconfigures the window, so it's left untouched unless a setting moved
+    if settings != *display_settings {
+        if !scale_dragged {
+            save_display_settings(&settings);
+        }
+        *display_settings = settings;
+    } else if scale_released {
+        save_display_settings(&display_settings);
+    }
+}
@@ -0,0 +1,38 @@
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+
+use crate::plane::smoke::{SmokeEmitter, SmokeSettings};
+
+use super::WindowModel;
+
+pub fn update_smoke_ui(
+    mut contexts: EguiContexts,
+    mut window_model_query: Query<&mut WindowModel>,
+    mut settings: ResMut<SmokeSettings>,
+) {
+    let Ok(mut window_model) = window_model_query.get_single_mut() else {
+        return;
+    };
+
+    egui::Window::new("Smoke")
+        .open(&mut window_model.show_smoke)
+        .show(contexts.ctx_mut(), |ui| {
+            ui.checkbox(&mut settings.enabled, "on");
+
+            egui::ComboBox::from_label("emitter")
+                .selected_text(format!("{:?}", settings.emitter))
+                .show_ui(ui, |ui| {
+                    for option in SmokeEmitter::ALL {
+                        ui.selectable_value(&mut settings.emitter, option, format!("{:?}", option));
+                    }
+                });
+
+            ui.horizontal(|ui| {
+                let mut rgb = [settings.color.r(), settings.color.g(), settings.color.b()];
+                if ui.color_edit_button_rgb(&mut rgb).changed() {
+                    settings.color = Color::rgb(rgb[0], rgb[1], rgb[2]);
+                }
+                ui.label("colour");
+            });
+        });
+}
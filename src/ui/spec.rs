@@ -1,26 +1,173 @@
 use bevy::{math::vec3, prelude::*};
 
-use crate::plane::spec::{FuselageSpec, PlaneSpec, TailSpec, WingSpec};
+use crate::plane::spec::{
+    BallastSpec, EngineSpec, FuselageSpec, GearLayout, Layout, PayloadKind, PayloadSpec, PlaneSpec,
+    RotorSpec, TailSpec, Undercarriage, WingRole, WingRowSpec, WingSpec,
+};
 
 use super::Vec3Model;
 
-#[derive(Component, Default)]
+/// Gap in seconds between edits that starts a new step to undo, so typing a value or dragging a
+/// point is undone in one go.
+const EDIT_GAP: f64 = 1.0;
+
+/// Reads a number from a text box, as NaN when it isn't one so the spec's validation reports it
+/// rather than it quietly becoming zero.
+fn parse_float(value: &str) -> f32 {
+    value.trim().parse().unwrap_or(f32::NAN)
+}
+
+#[derive(Component, Default, Clone, PartialEq)]
 pub struct PlaneSpecModel {
     pub thrust: String,
+    pub engine: EngineModel,
+    pub fly_by_wire: bool,
+    /// Never exceed speed in m/s, edited in the chosen units rather than as text.
+    pub never_exceed_speed: f32,
     pub fuselage: BodyModel,
     pub wings: WingModel,
     pub tail: Vec3Model,
     pub tail_horizontal: WingModel,
     pub tail_vertical: WingModel,
+    pub ballast: BallastModel,
+    pub layout: Layout,
+    pub wing_rows: Vec<WingRowModel>,
+    pub payloads: Vec<PayloadModel>,
+    pub rotor: Option<RotorModel>,
+}
+
+#[derive(Clone, PartialEq)]
+pub struct WingRowModel {
+    pub role: WingRole,
+    pub height: String,
+    pub position: String,
+    pub wing: WingModel,
+}
+
+impl WingRowModel {
+    pub fn new(spec: &WingRowSpec) -> Self {
+        Self {
+            role: spec.role,
+            height: spec.height.to_string(),
+            position: spec.position.to_string(),
+            wing: WingModel::new(&spec.wing),
+        }
+    }
+
+    fn to_spec(&self) -> WingRowSpec {
+        WingRowSpec {
+            role: self.role,
+            height: parse_float(&self.height),
+            position: parse_float(&self.position),
+            wing: self.wing.to_spec(),
+        }
+    }
+}
+
+#[derive(Clone, PartialEq)]
+pub struct PayloadModel {
+    pub kind: PayloadKind,
+    pub mass: String,
+    pub position: Vec3Model,
+}
+
+impl PayloadModel {
+    pub fn new(spec: &PayloadSpec) -> Self {
+        Self {
+            kind: spec.kind,
+            mass: spec.mass.to_string(),
+            position: Vec3Model::new(spec.position),
+        }
+    }
+
+    fn to_spec(&self) -> PayloadSpec {
+        PayloadSpec {
+            kind: self.kind,
+            mass: parse_float(&self.mass),
+            position: vec3(
+                parse_float(&self.position.x),
+                parse_float(&self.position.y),
+                parse_float(&self.position.z),
+            ),
+        }
+    }
+}
+
+#[derive(Clone, PartialEq)]
+pub struct RotorModel {
+    pub diameter: String,
+    pub height: String,
+    pub max_thrust: String,
+    /// Max cyclic in degrees.
+    pub max_cyclic: String,
+    pub anti_torque: String,
+}
+
+impl RotorModel {
+    pub fn new(spec: &RotorSpec) -> Self {
+        Self {
+            diameter: spec.diameter.to_string(),
+            height: spec.height.to_string(),
+            max_thrust: spec.max_thrust.to_string(),
+            max_cyclic: spec.max_cyclic.to_degrees().to_string(),
+            anti_torque: spec.anti_torque.to_string(),
+        }
+    }
+
+    fn to_spec(&self) -> RotorSpec {
+        RotorSpec {
+            diameter: parse_float(&self.diameter),
+            height: parse_float(&self.height),
+            max_thrust: parse_float(&self.max_thrust),
+            max_cyclic: parse_float(&self.max_cyclic).to_radians(),
+            anti_torque: parse_float(&self.anti_torque),
+        }
+    }
+}
+
+#[derive(Default, Clone, PartialEq)]
+pub struct BallastModel {
+    pub mass: String,
+    pub position: String,
+}
+
+#[derive(Default, Clone, PartialEq)]
+pub struct EngineModel {
+    pub cylinders: String,
+    pub idle_rpm: String,
+    pub max_rpm: String,
+    pub reverse_thrust: String,
 }
 
-#[derive(Default)]
+impl EngineModel {
+    pub fn new(spec: &EngineSpec) -> Self {
+        Self {
+            cylinders: spec.cylinders.to_string(),
+            idle_rpm: spec.idle_rpm.to_string(),
+            max_rpm: spec.max_rpm.to_string(),
+            reverse_thrust: spec.reverse_thrust.to_string(),
+        }
+    }
+
+    fn to_spec(&self) -> EngineSpec {
+        EngineSpec {
+            cylinders: self.cylinders.parse().unwrap_or_default(),
+            idle_rpm: parse_float(&self.idle_rpm),
+            max_rpm: parse_float(&self.max_rpm),
+            reverse_thrust: parse_float(&self.reverse_thrust),
+        }
+    }
+}
+
+#[derive(Default, Clone, PartialEq)]
 pub struct BodyModel {
     pub size: Vec3Model,
     pub mass: String,
     pub wheel_x_offset: String,
     pub wheel_y_offset: String,
     pub wheel_radius: String,
+    pub undercarriage: Undercarriage,
+    pub gear: GearLayout,
 }
 
 impl BodyModel {
@@ -31,62 +178,56 @@ impl BodyModel {
             wheel_radius: spec.wheel_radius.to_string(),
             wheel_x_offset: spec.wheel_x_offset.to_string(),
             wheel_y_offset: spec.wheel_y_offset.to_string(),
+            undercarriage: spec.undercarriage,
+            gear: spec.gear,
         }
     }
 }
 
-#[derive(Default)]
+#[derive(Default, Clone, PartialEq)]
 pub struct WingModel {
     pub size: Vec3Model,
-    pub lift_coefficient_curve: Vec<(String, String)>,
-    pub drag_coefficient_curve: Vec<(String, String)>,
+    /// Coefficient at each angle of attack in degrees, edited by dragging the points on a plot.
+    pub lift_coefficient_curve: Vec<(f32, f32)>,
+    pub drag_coefficient_curve: Vec<(f32, f32)>,
     pub angle: String,
     pub max_control_angle: String,
+    pub max_control_rate: String,
+    pub oswald_efficiency: String,
+    pub dihedral: String,
+    pub sweep: String,
 }
 
 impl WingModel {
     fn new(value: &WingSpec) -> Self {
         Self {
             size: Vec3Model::new(value.size),
-            lift_coefficient_curve: value
-                .lift_coefficient_curve
-                .iter()
-                .map(|(l, a)| (l.to_string(), a.to_string()))
-                .collect(),
-            drag_coefficient_curve: value
-                .drag_coefficient_curve
-                .iter()
-                .map(|(l, a)| (l.to_string(), a.to_string()))
-                .collect(),
+            lift_coefficient_curve: value.lift_coefficient_curve.clone(),
+            drag_coefficient_curve: value.drag_coefficient_curve.clone(),
             angle: value.angle.to_degrees().to_string(),
             max_control_angle: value.max_control_angle.to_degrees().to_string(),
+            max_control_rate: value.max_control_rate.to_degrees().to_string(),
+            oswald_efficiency: value.oswald_efficiency.to_string(),
+            dihedral: value.dihedral.to_degrees().to_string(),
+            sweep: value.sweep.to_degrees().to_string(),
         }
     }
 
     fn to_spec(&self) -> WingSpec {
         WingSpec {
             size: vec3(
-                self.size.x.parse().unwrap_or_default(),
-                self.size.y.parse().unwrap_or_default(),
-                self.size.z.parse().unwrap_or_default(),
+                parse_float(&self.size.x),
+                parse_float(&self.size.y),
+                parse_float(&self.size.z),
             ),
-            lift_coefficient_curve: self
-                .lift_coefficient_curve
-                .iter()
-                .map(|(l, a)| (l.parse().unwrap_or_default(), a.parse().unwrap_or_default()))
-                .collect(),
-            drag_coefficient_curve: self
-                .drag_coefficient_curve
-                .iter()
-                .map(|(l, a)| (l.parse().unwrap_or_default(), a.parse().unwrap_or_default()))
-                .collect(),
-            angle: self.angle.parse::<f32>().unwrap_or_default().to_radians(),
-            max_control_angle: self
-                .max_control_angle
-                .parse::<f32>()
-                .unwrap_or_default()
-                .to_radians(),
-            ..default()
+            lift_coefficient_curve: self.lift_coefficient_curve.clone(),
+            drag_coefficient_curve: self.drag_coefficient_curve.clone(),
+            angle: parse_float(&self.angle).to_radians(),
+            max_control_angle: parse_float(&self.max_control_angle).to_radians(),
+            max_control_rate: parse_float(&self.max_control_rate).to_radians(),
+            oswald_efficiency: parse_float(&self.oswald_efficiency),
+            dihedral: parse_float(&self.dihedral).to_radians(),
+            sweep: parse_float(&self.sweep).to_radians(),
         }
     }
 }
@@ -95,11 +236,22 @@ impl PlaneSpecModel {
     pub fn new(spec: &PlaneSpec) -> Self {
         Self {
             thrust: spec.thrust.to_string(),
+            engine: EngineModel::new(&spec.engine),
+            fly_by_wire: spec.fly_by_wire,
+            never_exceed_speed: spec.never_exceed_speed,
             fuselage: BodyModel::new(&spec.fuselage),
             wings: WingModel::new(&spec.wings),
             tail: Vec3Model::new(spec.tail.size),
             tail_horizontal: WingModel::new(&spec.tail.horizontal),
             tail_vertical: WingModel::new(&spec.tail.vertical),
+            ballast: BallastModel {
+                mass: spec.ballast.mass.to_string(),
+                position: spec.ballast.position.to_string(),
+            },
+            layout: spec.layout.clone(),
+            wing_rows: spec.wing_rows.iter().map(WingRowModel::new).collect(),
+            payloads: spec.payloads.iter().map(PayloadModel::new).collect(),
+            rotor: spec.rotor.as_ref().map(RotorModel::new),
         }
     }
 }
@@ -107,29 +259,106 @@ impl PlaneSpecModel {
 impl PlaneSpecModel {
     pub fn to_spec(&self) -> PlaneSpec {
         PlaneSpec {
-            thrust: self.thrust.parse().unwrap_or_default(),
+            thrust: parse_float(&self.thrust),
+            engine: self.engine.to_spec(),
+            fly_by_wire: self.fly_by_wire,
+            never_exceed_speed: self.never_exceed_speed,
             fuselage: FuselageSpec {
                 size: vec3(
-                    self.fuselage.size.x.parse().unwrap_or_default(),
-                    self.fuselage.size.y.parse().unwrap_or_default(),
-                    self.fuselage.size.z.parse().unwrap_or_default(),
+                    parse_float(&self.fuselage.size.x),
+                    parse_float(&self.fuselage.size.y),
+                    parse_float(&self.fuselage.size.z),
                 ),
-                mass: self.fuselage.mass.parse().unwrap_or_default(),
-                wheel_radius: self.fuselage.wheel_radius.parse().unwrap_or_default(),
-                wheel_x_offset: self.fuselage.wheel_x_offset.parse().unwrap_or_default(),
-                wheel_y_offset: self.fuselage.wheel_y_offset.parse().unwrap_or_default(),
+                mass: parse_float(&self.fuselage.mass),
+                wheel_radius: parse_float(&self.fuselage.wheel_radius),
+                wheel_x_offset: parse_float(&self.fuselage.wheel_x_offset),
+                wheel_y_offset: parse_float(&self.fuselage.wheel_y_offset),
+                undercarriage: self.fuselage.undercarriage,
+                gear: self.fuselage.gear,
             },
             wings: self.wings.to_spec(),
             tail: TailSpec {
                 size: vec3(
-                    self.tail.x.parse().unwrap_or_default(),
-                    self.tail.y.parse().unwrap_or_default(),
-                    self.tail.z.parse().unwrap_or_default(),
+                    parse_float(&self.tail.x),
+                    parse_float(&self.tail.y),
+                    parse_float(&self.tail.z),
                 ),
                 horizontal: self.tail_horizontal.to_spec(),
                 vertical: self.tail_vertical.to_spec(),
             },
+            ballast: BallastSpec {
+                mass: parse_float(&self.ballast.mass),
+                position: parse_float(&self.ballast.position),
+            },
+            layout: self.layout.clone(),
+            wing_rows: self.wing_rows.iter().map(WingRowModel::to_spec).collect(),
+            payloads: self.payloads.iter().map(PayloadModel::to_spec).collect(),
+            rotor: self.rotor.as_ref().map(RotorModel::to_spec),
             ..default()
         }
     }
 }
+
+/// Steps of the Build window's edits that can be undone and redone.
+#[derive(Component)]
+pub struct SpecHistory {
+    undo: Vec<PlaneSpecModel>,
+    redo: Vec<PlaneSpecModel>,
+    /// Model as it was last seen, and the time in seconds it last changed.
+    current: PlaneSpecModel,
+    last_edit: f64,
+}
+
+impl SpecHistory {
+    pub fn new(model: &PlaneSpecModel) -> Self {
+        Self {
+            undo: vec![],
+            redo: vec![],
+            current: model.clone(),
+            last_edit: f64::NEG_INFINITY,
+        }
+    }
+
+    /// Notes any change to the model since it was last seen, starting a new step to undo unless
+    /// it follows straight on from the last change.
+    pub fn record(&mut self, model: &PlaneSpecModel, time: f64) {
+        if *model == self.current {
+            return;
+        }
+
+        let previous = std::mem::replace(&mut self.current, model.clone());
+        if time - self.last_edit > EDIT_GAP {
+            self.undo.push(previous);
+        }
+        self.redo.clear();
+        self.last_edit = time;
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    pub fn undo(&mut self, model: &mut PlaneSpecModel) {
+        if let Some(previous) = self.undo.pop() {
+            self.redo.push(std::mem::replace(model, previous));
+            self.restart(model);
+        }
+    }
+
+    pub fn redo(&mut self, model: &mut PlaneSpecModel) {
+        if let Some(next) = self.redo.pop() {
+            self.undo.push(std::mem::replace(model, next));
+            self.restart(model);
+        }
+    }
+
+    /// Takes the model as it is, so the next edit is a step of its own.
+    fn restart(&mut self, model: &PlaneSpecModel) {
+        self.current = model.clone();
+        self.last_edit = f64::NEG_INFINITY;
+    }
+}
@@ -0,0 +1,42 @@
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+
+use crate::plane::tow::{AerotowEvent, Tow, TowEvent, TowKind};
+
+use super::WindowModel;
+
+pub fn update_tow_ui(
+    mut contexts: EguiContexts,
+    mut window_model_query: Query<&mut WindowModel>,
+    tow: Res<Tow>,
+    mut tow_events: EventWriter<TowEvent>,
+    mut aerotow_events: EventWriter<AerotowEvent>,
+) {
+    let Ok(mut window_model) = window_model_query.get_single_mut() else {
+        return;
+    };
+
+    egui::Window::new("Towing")
+        .open(&mut window_model.show_towing)
+        .show(contexts.ctx_mut(), |ui| {
+            ui.horizontal(|ui| {
+                for kind in TowKind::ALL {
+                    if ui
+                        .button(format!("Tow {:?}", kind).to_lowercase())
+                        .clicked()
+                    {
+                        tow_events.send(TowEvent(Some(kind)));
+                    }
+                }
+            });
+            if ui.button("Call tow plane").clicked() {
+                aerotow_events.send(AerotowEvent);
+            }
+            if ui
+                .add_enabled(tow.is_towing(), egui::Button::new("Release"))
+                .clicked()
+            {
+                tow_events.send(TowEvent(None));
+            }
+        });
+}
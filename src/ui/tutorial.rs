@@ -0,0 +1,75 @@
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+
+use crate::{
+    tutorial::{AbortLessonEvent, Lesson, StartLessonEvent, Tutorial},
+    units::Units,
+};
+
+use super::WindowModel;
+
+pub fn update_tutorial_ui(
+    mut contexts: EguiContexts,
+    mut window_model_query: Query<&mut WindowModel>,
+    tutorial: Res<Tutorial>,
+    mut start_events: EventWriter<StartLessonEvent>,
+    mut abort_events: EventWriter<AbortLessonEvent>,
+    units: Res<Units>,
+) {
+    let Ok(mut window_model) = window_model_query.get_single_mut() else {
+        return;
+    };
+
+    egui::Window::new("Tutorial")
+        .open(&mut window_model.show_tutorial)
+        .show(contexts.ctx_mut(), |ui| {
+            ui.group(|ui| {
+                ui.label("Lessons");
+                for lesson in Lesson::ALL {
+                    ui.horizontal(|ui| {
+                        let color = if tutorial.completed.contains(&lesson) {
+                            egui::Color32::GREEN
+                        } else {
+                            egui::Color32::WHITE
+                        };
+                        ui.colored_label(color, lesson.title());
+                        if ui.button("Start").clicked() {
+                            start_events.send(StartLessonEvent(lesson));
+                        }
+                    });
+                }
+            });
+
+            if let (Some(lesson), Some(step)) = (tutorial.lesson, tutorial.current_step()) {
+                ui.group(|ui| {
+                    ui.label(format!(
+                        "{} - step {} / {}",
+                        lesson.title(),
+                        tutorial.step + 1,
+                        lesson.steps().len()
+                    ));
+                    ui.label(egui::RichText::new(step.text(&units)).strong());
+
+                    let hold_time = step.goal.hold_time();
+                    if hold_time > 0.0 {
+                        ui.add(
+                            egui::ProgressBar::new(tutorial.held / hold_time)
+                                .text(format!("held {:.0} / {:.0} s", tutorial.held, hold_time)),
+                        );
+                    }
+                    if ui.button("Abort").clicked() {
+                        abort_events.send(AbortLessonEvent);
+                    }
+                });
+            }
+
+            if let Some(feedback) = &tutorial.feedback {
+                let color = if tutorial.lesson.is_some() {
+                    egui::Color32::YELLOW
+                } else {
+                    egui::Color32::GREEN
+                };
+                ui.colored_label(color, feedback);
+            }
+        });
+}
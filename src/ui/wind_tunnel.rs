@@ -0,0 +1,116 @@
+use bevy::prelude::*;
+use bevy_egui::{
+    egui::{
+        self,
+        plot::{Legend, Line, Plot, PlotPoints, VLine},
+    },
+    EguiContexts,
+};
+
+use crate::{
+    plane::wind_tunnel::{FlightMode, WindTunnel},
+    units::{Quantity, Units},
+};
+
+use super::{WindowModel, CURVE_PLOT_HEIGHT};
+
+/// Puts the selected plane in the wind tunnel and back out, setting the airflow and showing the
+/// forces it makes with their coefficients plotted against the angle of attack.
+pub fn update_wind_tunnel_ui(
+    mut contexts: EguiContexts,
+    mut window_model_query: Query<&mut WindowModel>,
+    mut tunnel: ResMut<WindTunnel>,
+    flight_mode: Res<State<FlightMode>>,
+    mut next_flight_mode: ResMut<NextState<FlightMode>>,
+    units: Res<Units>,
+) {
+    let Ok(mut window_model) = window_model_query.get_single_mut() else {
+        return;
+    };
+
+    egui::Window::new("Wind Tunnel")
+        .open(&mut window_model.show_wind_tunnel)
+        .show(contexts.ctx_mut(), |ui| {
+            if *flight_mode.get() == FlightMode::Flying {
+                if ui.button("Hold plane in tunnel").clicked() {
+                    next_flight_mode.set(FlightMode::WindTunnel);
+                }
+                return;
+            }
+            if ui.button("Release plane").clicked() {
+                next_flight_mode.set(FlightMode::Flying);
+            }
+
+            let mut speed = units.convert(Quantity::Speed, tunnel.speed);
+            let speed_range =
+                units.convert(Quantity::Speed, 5.0)..=units.convert(Quantity::Speed, 100.0);
+            if ui
+                .add(
+                    egui::Slider::new(&mut speed, speed_range)
+                        .text(format!("airspeed ({})", units.symbol(Quantity::Speed))),
+                )
+                .changed()
+            {
+                tunnel.speed = units.convert_back(Quantity::Speed, speed);
+            }
+            ui.add_enabled(
+                !tunnel.sweeping,
+                egui::Slider::new(&mut tunnel.angle, -10.0..=25.0).text("angle of attack (°)"),
+            );
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut tunnel.sweeping, "sweep");
+                if ui.button("Clear").clicked() {
+                    tunnel.samples.clear();
+                }
+            });
+
+            ui.separator();
+
+            let coefficients = tunnel.coefficients;
+            egui::Grid::new("wind tunnel forces")
+                .striped(true)
+                .show(ui, |ui| {
+                    ui.strong("");
+                    ui.strong("force");
+                    ui.strong("coefficient");
+                    ui.end_row();
+
+                    ui.label("lift");
+                    ui.label(format!("{:.0} N", tunnel.lift));
+                    ui.label(format!("{:.3}", coefficients.lift));
+                    ui.end_row();
+
+                    ui.label("drag");
+                    ui.label(format!("{:.0} N", tunnel.drag));
+                    ui.label(format!("{:.3}", coefficients.drag));
+                    ui.end_row();
+
+                    ui.label("pitching moment");
+                    ui.label(format!("{:.0} N m", tunnel.moment));
+                    ui.label(format!("{:.3}", coefficients.moment));
+                    ui.end_row();
+                });
+            if tunnel.drag > 0.0 {
+                ui.label(format!("lift to drag: {:.1}", tunnel.lift / tunnel.drag));
+            }
+
+            let line = |name: &str, value: fn(&_) -> f32| {
+                let points: PlotPoints = tunnel
+                    .samples
+                    .iter()
+                    .map(|sample| [sample.angle as f64, value(sample) as f64])
+                    .collect();
+                Line::new(points).name(name)
+            };
+            Plot::new("wind tunnel coefficients")
+                .height(CURVE_PLOT_HEIGHT * 2.0)
+                .include_y(0.0)
+                .legend(Legend::default())
+                .show(ui, |plot_ui| {
+                    plot_ui.line(line("lift", |sample| sample.lift));
+                    plot_ui.line(line("drag", |sample| sample.drag));
+                    plot_ui.line(line("moment", |sample| sample.moment));
+                    plot_ui.vline(VLine::new(tunnel.angle as f64));
+                });
+        });
+}
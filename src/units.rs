@@ -0,0 +1,106 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+const KMH_PER_MS: f32 = 3.6;
+const MPH_PER_MS: f32 = 2.236_936;
+const KNOTS_PER_MS: f32 = 1.943_844;
+const FEET_PER_METRE: f32 = 3.280_84;
+const METRES_PER_MILE: f32 = 1609.344;
+const METRES_PER_NAUTICAL_MILE: f32 = 1852.0;
+
+/// Units flight data is shown in, chosen in the Settings window. The simulation works in metres and
+/// seconds throughout, and values are only converted as they're formatted for display.
+#[derive(Resource, Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Units {
+    /// Kilometres per hour, metres, metres per second and kilometres.
+    #[default]
+    Metric,
+    /// Miles per hour, feet, feet per minute and miles.
+    Imperial,
+    /// Knots, feet, feet per minute and nautical miles, as flown in aviation.
+    Nautical,
+}
+
+/// Kind of value a unit is chosen for, each given in the simulation's SI unit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Quantity {
+    /// Airspeed or ground speed in m/s.
+    Speed,
+    /// Altitude, height or length in metres.
+    Altitude,
+    /// Rate of climb in m/s.
+    VerticalSpeed,
+    /// Distance over the ground in metres.
+    Distance,
+}
+
+impl Units {
+    pub const ALL: [Self; 3] = [Self::Metric, Self::Imperial, Self::Nautical];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Metric => "metric",
+            Self::Imperial => "imperial",
+            Self::Nautical => "nautical",
+        }
+    }
+
+    /// Displayed units per SI unit of the quantity.
+    fn factor(&self, quantity: Quantity) -> f32 {
+        match (quantity, self) {
+            (Quantity::Speed, Self::Metric) => KMH_PER_MS,
+            (Quantity::Speed, Self::Imperial) => MPH_PER_MS,
+            (Quantity::Speed, Self::Nautical) => KNOTS_PER_MS,
+            (Quantity::Altitude, Self::Metric) => 1.0,
+            (Quantity::Altitude, _) => FEET_PER_METRE,
+            (Quantity::VerticalSpeed, Self::Metric) => 1.0,
+            (Quantity::VerticalSpeed, _) => FEET_PER_METRE * 60.0,
+            (Quantity::Distance, Self::Metric) => 0.001,
+            (Quantity::Distance, Self::Imperial) => 1.0 / METRES_PER_MILE,
+            (Quantity::Distance, Self::Nautical) => 1.0 / METRES_PER_NAUTICAL_MILE,
+        }
+    }
+
+    pub fn symbol(&self, quantity: Quantity) -> &'static str {
+        match (quantity, self) {
+            (Quantity::Speed, Self::Metric) => "km/h",
+            (Quantity::Speed, Self::Imperial) => "mph",
+            (Quantity::Speed, Self::Nautical) => "kn",
+            (Quantity::Altitude, Self::Metric) => "m",
+            (Quantity::Altitude, _) => "ft",
+            (Quantity::VerticalSpeed, Self::Metric) => "m/s",
+            (Quantity::VerticalSpeed, _) => "ft/min",
+            (Quantity::Distance, Self::Metric) => "km",
+            (Quantity::Distance, Self::Imperial) => "mi",
+            (Quantity::Distance, Self::Nautical) => "NM",
+        }
+    }
+
+    /// Value in SI units converted to the displayed unit.
+    pub fn convert(&self, quantity: Quantity, value: f32) -> f32 {
+        value * self.factor(quantity)
+    }
+
+    /// Value in the displayed unit, such as one typed in, converted back to SI units.
+    pub fn convert_back(&self, quantity: Quantity, value: f32) -> f32 {
+        value / self.factor(quantity)
+    }
+
+    /// Value in SI units converted and written with its unit, e.g. `"120 km/h"`.
+    pub fn format(&self, quantity: Quantity, value: f32, decimals: usize) -> String {
+        format!(
+            "{:.decimals$} {}",
+            self.convert(quantity, value),
+            self.symbol(quantity)
+        )
+    }
+
+    /// As `format`, with a sign in front of positive values too, for rates of climb and the like.
+    pub fn format_signed(&self, quantity: Quantity, value: f32, decimals: usize) -> String {
+        format!(
+            "{:+.decimals$} {}",
+            self.convert(quantity, value),
+            self.symbol(quantity)
+        )
+    }
+}
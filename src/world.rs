@@ -1,53 +1,340 @@
+mod airport;
+mod airshow;
+mod birds;
+mod bridge;
+mod buildings;
+mod city;
+mod ghost;
+mod harbour;
+mod history;
+mod lights;
+mod lod;
+mod map;
+mod missions;
+mod origin;
+mod prop_wash;
+mod rooftop;
+mod terrain;
+mod thermals;
+mod weather;
+
+use std::f32::consts::PI;
+
 use bevy::{
+    math::vec2,
     pbr::CascadeShadowConfigBuilder,
     prelude::*,
+    tasks::{AsyncComputeTaskPool, Task},
     utils::{HashMap, HashSet},
 };
 use bevy_rapier3d::prelude::*;
-use noise::{NoiseFn, Perlin};
+use futures_lite::future;
+use noise::Perlin;
+use serde::{Deserialize, Serialize};
 
 use crate::{
-    camera::{CameraPlugin, Follow},
+    achievements::AchievementsPlugin,
+    camera::{CameraPlugin, FogControl, Follow, LookTarget},
+    capture::CapturePlugin,
+    cockpit::CockpitPlugin,
+    damage::{track_previous_velocity, DamagePlugin},
+    flight_model::FlightModelPlugin,
     input::InputPlugin,
+    navigation::NavigationPlugin,
+    net::NetPlugin,
     physics::PhysicsPlugin,
     plane::PlanePlugin,
+    recorder::RecorderPlugin,
+    scenario::ScenarioPlugin,
+    settings::SettingsPlugin,
+    snapshot::SnapshotPlugin,
+    sound::SoundPlugin,
+    tutorial::TutorialPlugin,
+};
+
+use self::{
+    buildings::BuildingInstancingPlugin,
+    city::{BlockBoxes, BlockKind},
+};
+
+pub use self::{
+    airport::{compass_heading, AirportSpec, Airports, Runway, RunwaySurface, TunedApproach},
+    airshow::AirshowVenue,
+    bridge::BridgeGap,
+    ghost::Ghosts,
+    history::{ClearWorldHistoryEvent, CrateSettledEvent, WorldHistory},
+    missions::{AbortMissionEvent, Courses, Mission, StartMissionEvent},
+    origin::{OriginShiftedEvent, WorldOrigin},
+    rooftop::PadLandingEvent,
+    thermals::Thermals,
+    weather::{update_visibility, Weather, WeatherPreset},
 };
 
 pub struct WorldPlugin;
 
-#[derive(Resource)]
+/// Noise the world is generated from, seeded by the config it was made with and copied into the
+/// generation tasks along with it.
+#[derive(Resource, Clone, Copy)]
 struct Rand {
     perlin: Perlin,
+    terrain: Perlin,
+    config: WorldGenConfig,
+}
+
+impl Rand {
+    fn new(config: WorldGenConfig) -> Self {
+        Self {
+            perlin: Perlin::new(config.seed),
+            terrain: Perlin::new(config.seed.wrapping_add(2)),
+            config,
+        }
+    }
+}
+
+/// Block spacings that divide a terrain chunk evenly.
+pub const BLOCK_SPACINGS: [i32; 5] = [100, 160, 200, 320, 400];
+
+/// Settings the terrain and city are generated from. Changing them rebuilds the world around the
+/// plane.
+#[derive(Resource, Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct WorldGenConfig {
+    pub seed: u32,
+    /// Roughly the share of blocks with a building on them, from `0` to `1`.
+    pub density: f32,
+    /// Height of the tallest buildings, not counting the landmark towers.
+    pub max_height: f32,
+    /// Distance between neighbouring blocks, one of `BLOCK_SPACINGS`.
+    pub spacing: i32,
+}
+
+impl Default for WorldGenConfig {
+    fn default() -> Self {
+        Self {
+            seed: 1,
+            density: 0.5,
+            max_height: 300.0,
+            spacing: SPACING,
+        }
+    }
+}
+
+impl WorldGenConfig {
+    /// Default config with the seed from a `--seed <number>` or `--seed=<number>` argument, if
+    /// there is one.
+    pub fn from_args(mut args: impl Iterator<Item = String>) -> Self {
+        let mut config = Self::default();
+
+        while let Some(arg) = args.next() {
+            let value = match arg.strip_prefix("--seed") {
+                Some("") => args.next(),
+                Some(value) => value.strip_prefix('=').map(str::to_string),
+                None => continue,
+            };
+
+            match value.as_deref().map(str::parse) {
+                Some(Ok(seed)) => config.seed = seed,
+                _ => warn!("Ignoring --seed without a whole number: {:?}", value),
+            }
+        }
+
+        config
+    }
+
+    /// Number of blocks along each side of a terrain chunk.
+    pub fn chunk_blocks(&self) -> i32 {
+        terrain::CHUNK_SIZE as i32 / self.spacing
+    }
 }
 
 impl Plugin for WorldPlugin {
     fn build(&self, app: &mut App) {
-        app.add_plugins(RapierPhysicsPlugin::<NoUserData>::default())
+        let rapier = RapierPhysicsPlugin::<NoUserData>::default().with_default_system_setup(false);
+        app.add_plugins(rapier)
             .add_plugins(CameraPlugin)
             .add_plugins(PhysicsPlugin)
+            .add_plugins(FlightModelPlugin)
             .add_plugins(PlanePlugin)
+            .add_plugins(DamagePlugin)
+            .add_plugins(AchievementsPlugin)
+            .add_plugins(TutorialPlugin)
+            .add_plugins(NavigationPlugin)
+            .add_plugins(CockpitPlugin)
+            .add_plugins(RecorderPlugin)
+            .add_plugins(ScenarioPlugin)
+            .add_plugins(SoundPlugin)
+            .add_plugins(SnapshotPlugin)
+            .add_plugins(CapturePlugin)
+            .add_plugins(SettingsPlugin)
+            .add_plugins(NetPlugin)
             .add_plugins(InputPlugin)
-            .insert_resource(Rand {
-                perlin: Perlin::new(1),
-            })
+            .add_plugins(BuildingInstancingPlugin);
+
+        // Given by the command line before the plugin is added, and needed by the startup systems
+        let config = *app
+            .world
+            .get_resource_or_insert_with(WorldGenConfig::default);
+
+        app.insert_resource(Rand::new(config))
             .insert_resource(GizmosControl::default())
-            .add_systems(Startup, (setup_lighting, setup_ground))
+            .insert_resource(TimeOfDay::default())
+            .init_resource::<lights::NightLights>()
+            .insert_resource(Airports::default())
+            .init_resource::<TunedApproach>()
+            .init_resource::<AirshowVenue>()
+            .init_resource::<Weather>()
+            .init_resource::<Thermals>()
+            .init_resource::<WorldOrigin>()
+            .init_resource::<map::ExploredChunks>()
+            .add_event::<OriginShiftedEvent>()
+            .init_resource::<Courses>()
+            .init_resource::<Mission>()
+            .init_resource::<Ghosts>()
+            .insert_resource(history::load_history().unwrap_or_default())
+            .add_event::<CrateSettledEvent>()
+            .add_event::<ClearWorldHistoryEvent>()
+            .add_event::<StartMissionEvent>()
+            .add_event::<AbortMissionEvent>()
+            .add_event::<PadLandingEvent>()
+            .add_systems(
+                Startup,
+                (
+                    setup_lighting,
+                    airport::setup_airports,
+                    prop_wash::setup_prop_wash,
+                    airshow::setup_airshow,
+                    bridge::setup_bridge,
+                    weather::setup_weather,
+                    missions::setup_missions,
+                    ghost::setup_ghosts,
+                    rooftop::setup_rooftops,
+                    birds::setup_birds,
+                ),
+            )
             .add_systems(
                 Update,
                 (
-                    update_sun,
+                    (
+                        advance_time_of_day,
+                        update_daylight.run_if(resource_changed::<TimeOfDay>().or_else(fog_added)),
+                        update_sun,
+                        lights::update_night_lights,
+                        lights::update_runway_lights
+                            .run_if(resource_changed::<lights::NightLights>()),
+                    )
+                        .chain(),
+                    (origin::rebase_origin, prop_wash::rebase_grass)
+                        .chain()
+                        .before(update_block_positions),
+                    (
+                        thermals::update_thermals.after(origin::rebase_origin),
+                        thermals::draw_thermal_gizmos.run_if(gizmos_shown),
+                    )
+                        .chain(),
                     update_block_positions,
+                    apply_world_gen_config
+                        .run_if(resource_changed::<WorldGenConfig>())
+                        .before(generate_infinite_buildings)
+                        .before(terrain::stream_terrain)
+                        .before(lod::stream_lod_chunks),
                     generate_infinite_buildings,
+                    (
+                        history::restore_world_history.run_if(resource_changed::<WorldGenConfig>()),
+                        history::record_settled_crates,
+                        history::clear_world_history,
+                    )
+                        .chain(),
+                    birds::update_flocks,
+                    terrain::stream_terrain,
+                    lod::stream_lod_chunks,
+                    map::export_map,
+                    (
+                        prop_wash::emit_dust,
+                        prop_wash::update_dust,
+                        prop_wash::flatten_grass,
+                    ),
+                    (
+                        weather::update_visibility
+                            .run_if(weather::preset_changed.or_else(fog_added)),
+                        weather::build_cloud_layer.run_if(weather::preset_changed),
+                        weather::wrap_clouds,
+                        weather::emit_rain,
+                        weather::update_rain,
+                    ),
+                    (
+                        missions::start_mission,
+                        missions::update_checkpoints.run_if(resource_changed::<Mission>()),
+                        ghost::record_ghost,
+                        ghost::update_ghost,
+                    )
+                        .chain(),
                 ),
+            )
+            .add_systems(
+                FixedUpdate,
+                (
+                    missions::track_checkpoints,
+                    // Touchdowns are scored on the velocity from before the contact was resolved
+                    rooftop::score_pad_landings.before(track_previous_velocity),
+                    birds::detect_bird_strikes,
+                )
+                    .after(PhysicsSet::Writeback),
+            )
+            .add_systems(
+                FixedUpdate,
+                rooftop::apply_arrestors.before(PhysicsSet::SyncBackend),
             );
     }
 }
 
+/// Height of the water surface at a position. Harbours have their own water, and elsewhere the
+/// rivers lie below all the ground outside their channels, so can be taken as a plane of water under
+/// the whole world.
+pub fn water_height(airports: &Airports, pos: Vec3) -> f32 {
+    airports
+        .0
+        .iter()
+        .filter(|airport| airport.surface == RunwaySurface::Water)
+        .find(|airport| airport.in_clear_zone(vec2(pos.x, pos.z)))
+        .map_or(terrain::RIVER_LEVEL, |airport| {
+            airport.position.y + harbour::WATER_LEVEL
+        })
+}
+
 #[derive(Resource, Default)]
 pub struct GizmosControl {
     pub show: bool,
 }
 
+pub fn gizmos_shown(gizmos_control: Res<GizmosControl>) -> bool {
+    gizmos_control.show
+}
+
+#[derive(Resource)]
+pub struct TimeOfDay {
+    /// Hour of the day in the range `0..24`.
+    pub hour: f32,
+    /// Real time in seconds for a full day to pass.
+    pub day_length: f32,
+    pub paused: bool,
+}
+
+impl Default for TimeOfDay {
+    fn default() -> Self {
+        Self {
+            hour: 10.0,
+            day_length: 20.0 * 60.0,
+            paused: false,
+        }
+    }
+}
+
+impl TimeOfDay {
+    /// Angle of the sun above the horizon, zero at 06:00 and `PI` at 18:00.
+    pub fn sun_elevation(&self) -> f32 {
+        (self.hour - 6.0) / 12.0 * PI
+    }
+}
+
 fn setup_lighting(mut commands: Commands) {
     // Configure a properly scaled cascade shadow map for this scene (defaults are too large, mesh units are in km)
     let cascade_shadow_config = CascadeShadowConfigBuilder {
@@ -75,55 +362,154 @@ fn setup_lighting(mut commands: Commands) {
                 0_f32.to_radians(),
                 0_f32.to_radians(),
             ),
+            color: Color::rgb(0.98, 0.95, 0.82),
+            illuminance: SUN_ILLUMINANCE,
         },
     ));
 }
 
+const SUN_ILLUMINANCE: f32 = 100000.0;
+
 #[derive(Component)]
 pub struct SunControl {
     pub rotation: Quat,
+    pub color: Color,
+    pub illuminance: f32,
 }
 
-fn update_sun(mut query: Query<(&SunControl, &mut Transform), Changed<SunControl>>) {
-    let Ok((sun_control, mut tx)) = query.get_single_mut() else {
+fn update_sun(
+    mut query: Query<(&SunControl, &mut Transform, &mut DirectionalLight), Changed<SunControl>>,
+) {
+    let Ok((sun_control, mut tx, mut light)) = query.get_single_mut() else {
         return;
     };
 
     tx.rotation = sun_control.rotation;
+    light.color = sun_control.color;
+    light.illuminance = sun_control.illuminance;
 }
 
-fn setup_ground(
-    mut commands: Commands,
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut materials: ResMut<Assets<StandardMaterial>>,
+fn advance_time_of_day(mut time_of_day: ResMut<TimeOfDay>, time: Res<Time>) {
+    if time_of_day.paused {
+        return;
+    }
+
+    let hours = time.delta_seconds() * 24.0 / time_of_day.day_length;
+    time_of_day.hour = (time_of_day.hour + hours).rem_euclid(24.0);
+}
+
+/// Colours for night, dawn/dusk and full day that are blended by sun elevation.
+struct DaylightColors {
+    night: Color,
+    twilight: Color,
+    day: Color,
+}
+
+impl DaylightColors {
+    fn blend(&self, daylight: f32, twilight: f32) -> Color {
+        let night = Vec4::from(self.night.as_rgba_f32());
+        let twilight_color = Vec4::from(self.twilight.as_rgba_f32());
+        let day = Vec4::from(self.day.as_rgba_f32());
+
+        Color::from(night.lerp(day, daylight).lerp(twilight_color, twilight))
+    }
+}
+
+const SKY_COLORS: DaylightColors = DaylightColors {
+    night: Color::rgb(0.01, 0.01, 0.04),
+    twilight: Color::rgb(0.8, 0.45, 0.35),
+    day: Color::rgb(0.5, 0.5, 0.8),
+};
+
+const SUN_COLORS: DaylightColors = DaylightColors {
+    night: Color::rgb(0.0, 0.0, 0.0),
+    twilight: Color::rgb(1.0, 0.55, 0.3),
+    day: Color::rgb(0.98, 0.95, 0.82),
+};
+
+const FOG_COLORS: DaylightColors = DaylightColors {
+    night: Color::rgb(0.01, 0.01, 0.03),
+    twilight: Color::rgb(0.5, 0.3, 0.3),
+    day: Color::rgb(0.1, 0.2, 0.4),
+};
+
+const EXTINCTION_COLORS: DaylightColors = DaylightColors {
+    night: Color::rgb(0.02, 0.02, 0.05),
+    twilight: Color::rgb(0.6, 0.35, 0.3),
+    day: Color::rgb(0.35, 0.5, 0.66),
+};
+
+const INSCATTERING_COLORS: DaylightColors = DaylightColors {
+    night: Color::rgb(0.05, 0.05, 0.1),
+    twilight: Color::rgb(1.0, 0.6, 0.4),
+    day: Color::rgb(0.8, 0.844, 1.0),
+};
+
+/// Newly spawned cameras start with default fog colours that need updating for the time of day.
+fn fog_added(query: Query<(), Added<FogControl>>) -> bool {
+    !query.is_empty()
+}
+
+fn update_daylight(
+    time_of_day: Res<TimeOfDay>,
+    mut sun_query: Query<&mut SunControl>,
+    mut fog_query: Query<&mut FogControl>,
+    mut ambient_light: ResMut<AmbientLight>,
+    mut clear_color: ResMut<ClearColor>,
 ) {
-    commands
-        .spawn((Collider::cuboid(10000.0, 0.1, 10000.0), Friction::new(0.01)))
-        .insert(PbrBundle {
-            mesh: meshes.add(Mesh::from(shape::Plane {
-                size: 20000.,
-                ..default()
-            })),
-            material: materials.add(Color::rgb(0.1, 0.2, 0.1).into()),
-            transform: Transform::from_xyz(0.0, 0.0, 0.0),
-            ..default()
-        });
+    let elevation = time_of_day.sun_elevation();
+    let daylight = elevation.sin().max(0.0);
+    // Strongest when the sun is on the horizon
+    let twilight = (1.0 - elevation.sin().abs() * 4.0).max(0.0);
+
+    if let Ok(mut sun_control) = sun_query.get_single_mut() {
+        sun_control.rotation =
+            Quat::from_rotation_y(30_f32.to_radians()) * Quat::from_rotation_x(-elevation);
+        sun_control.color = SUN_COLORS.blend(daylight, twilight);
+        sun_control.illuminance = SUN_ILLUMINANCE * (daylight + twilight * 0.1).min(1.0);
+    }
+
+    for mut fog_control in fog_query.iter_mut() {
+        fog_control.color = FOG_COLORS.blend(daylight, twilight);
+        fog_control.extinction_color = EXTINCTION_COLORS.blend(daylight, twilight);
+        fog_control.inscattering_color = INSCATTERING_COLORS.blend(daylight, twilight);
+    }
+
+    ambient_light.brightness = 0.02 + daylight * 0.08;
+    clear_color.0 = SKY_COLORS.blend(daylight, twilight);
 }
 
+/// Size of the grid that the world is streamed and rebased in, which is also the default distance
+/// between blocks.
 pub const SPACING: i32 = 200;
 const MAX_SIDE: f32 = 30.0;
-const MAX_HEIGHT: f32 = 300.0;
-const ACTIVE_BLOCK_DISTANCE: i32 = 20;
+/// Distance either side of the followed entity's block within which blocks have colliders. Further
+/// out they're only drawn.
+const COLLIDER_DISTANCE: f32 = (3 * SPACING) as f32;
 
 #[derive(Component)]
 pub struct BlockPos(pub i32, pub i32);
 
+/// Building or tower generated on a block, with its square footprint.
+#[derive(Component)]
+pub struct Building {
+    pub side: f32,
+    pub height: f32,
+}
+
+/// Reseeds the world when its config is edited, which the streaming systems pick up to rebuild it.
+fn apply_world_gen_config(config: Res<WorldGenConfig>, mut rand: ResMut<Rand>) {
+    info!("Generating the world from {:?}", *config);
+    *rand = Rand::new(*config);
+}
+
 fn update_block_positions(
     mut commands: Commands,
     query: Query<(Entity, &GlobalTransform, &BlockPos)>,
+    origin: Res<WorldOrigin>,
 ) {
     for (entity, global_tx, BlockPos(x, z)) in query.iter() {
-        let position = global_tx.translation();
+        let position = origin.to_absolute(global_tx.translation());
         let px = position.x as i32 / SPACING;
         let pz = position.z as i32 / SPACING;
 
@@ -133,94 +519,227 @@ fn update_block_positions(
     }
 }
 
-fn generate_infinite_buildings(
-    mut commands: Commands,
-    query: Query<&BlockPos, (Changed<BlockPos>, With<Follow>)>,
-    mut block_positions: Local<HashSet<(i32, i32)>>,
-    mut block_entities: Local<HashMap<(i32, i32), Entity>>,
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut materials: ResMut<Assets<StandardMaterial>>,
-    rand: Res<Rand>,
-) {
-    let Ok(BlockPos(px, pz)) = query.get_single() else {
-        return;
-    };
+/// Local ground position and height of the building or tower on a block, if it has one.
+fn block_building(
+    rand: &Rand,
+    airports: &Airports,
+    origin: &WorldOrigin,
+    block: (i32, i32),
+) -> Option<(Vec2, f32)> {
+    let (ground_pos, kind) = city::block_kind(rand, airports, origin, block)?;
+    kind.height().map(|height| (ground_pos, height))
+}
 
-    let px = *px;
-    let pz = *pz;
+/// Entity spawned for a block, with what it was built from so it can be rebuilt if that changes.
+struct BlockEntity {
+    entity: Entity,
+    kind: BlockKind,
+    /// Absolute position of the entity.
+    position: Vec3,
+    collider: Option<Collider>,
+}
 
-    let mut active_block_positions = HashSet::new();
+/// What's built on the active blocks around a block, generated off the main thread.
+struct ActiveBlocks {
+    /// Block of the streaming grid the blocks are around.
+    centre: (i32, i32),
+    /// Absolute position of the entity of each block with something built on it, what's built, and
+    /// the boxes making it up.
+    blocks: HashMap<(i32, i32), (Vec3, BlockKind, BlockBoxes)>,
+}
 
-    let mut num_misses = 0;
-    let mut num_hits = 0;
+/// Finds what's built on the blocks around a block. Takes its own copies of the world so it can
+/// run as a task.
+fn generate_active_blocks(
+    rand: &Rand,
+    airports: &Airports,
+    origin: &WorldOrigin,
+    (px, pz): (i32, i32),
+) -> ActiveBlocks {
+    let mut active_blocks = HashMap::new();
+
+    // Whole chunks are active, so the buildings line up with the full detail terrain and the low
+    // detail chunks beyond
+    let chunk_blocks = rand.config.chunk_blocks();
+    let blocks = |block: i32| {
+        let chunk = block.div_euclid(terrain::CHUNK_BLOCKS);
+        (chunk - terrain::ACTIVE_CHUNK_DISTANCE) * chunk_blocks
+            ..(chunk + terrain::ACTIVE_CHUNK_DISTANCE + 1) * chunk_blocks
+    };
 
-    for z in (pz - ACTIVE_BLOCK_DISTANCE)..(pz + ACTIVE_BLOCK_DISTANCE) {
-        for x in (px - ACTIVE_BLOCK_DISTANCE)..(px + ACTIVE_BLOCK_DISTANCE) {
+    for z in blocks(pz) {
+        for x in blocks(px) {
             let block_pos = (x, z);
-
-            // Perlin always returns 0 for whole numbers so need to multiply by a coefficient to maker finer grained samplings
-            let n = rand.perlin.get([x as f64 * 0.2, z as f64 * 0.2]);
-            if n <= 0.0 {
+            let Some((ground_pos, kind)) = city::block_kind(rand, airports, origin, block_pos)
+            else {
                 continue;
-            }
-
-            active_block_positions.insert(block_pos);
-
-            if block_positions.contains(&block_pos) {
-                num_hits += 1;
-            } else {
-                num_misses += 1;
-
-                let height = MAX_HEIGHT * n as f32;
-                let side = MAX_SIDE;
-
-                let building_pos =
-                    Vec3::new((x * SPACING) as f32, height * 0.5, (z * SPACING) as f32);
-
-                let building_entity = commands
-                    .spawn(PbrBundle {
-                        mesh: meshes.add(Mesh::from(shape::Box::new(side, height, side))),
-                        material: materials.add(Color::rgb(0.8, 0.7, 0.6).into()),
-                        transform: Transform::from_translation(building_pos),
-                        ..default()
-                    })
-                    .insert(RigidBody::Fixed)
-                    .insert(Collider::cuboid(side / 2.0, height / 2.0, side / 2.0))
-                    .id();
+            };
+
+            let ground = |pos| terrain::terrain_height(&rand.terrain, airports, origin, pos);
+            let ground_height = ground(ground_pos);
+            let block_ground = |offset| ground(ground_pos + offset) - ground_height;
+            let mut boxes = kind.boxes(block_pos, rand.config.spacing, block_ground);
+            boxes.extend(kind.street_props(block_pos, rand.config.spacing, block_ground));
+            let position = Vec3::new(
+                ground_pos.x,
+                ground_height + kind.origin_height(),
+                ground_pos.y,
+            );
 
-                block_entities.insert(block_pos, building_entity);
-            }
+            active_blocks.insert(
+                block_pos,
+                (origin.to_absolute(position), kind, BlockBoxes(boxes)),
+            );
         }
     }
 
-    let new_positions: Vec<_> = active_block_positions
-        .difference(&block_positions)
-        .copied()
-        .collect();
+    ActiveBlocks {
+        centre: (px, pz),
+        blocks: active_blocks,
+    }
+}
 
-    let old_positions: Vec<_> = block_positions
-        .difference(&active_block_positions)
-        .copied()
+/// Starts finding what's built around the followed entity each time it moves to another block, and
+/// spawns and prunes blocks to match once they're found, so crossing into a new block doesn't stall
+/// a frame.
+///
+/// When the world is reseeded only the blocks whose buildings have changed are rebuilt.
+#[allow(clippy::too_many_arguments)]
+fn generate_infinite_buildings(
+    mut commands: Commands,
+    query: Query<Ref<BlockPos>, With<Follow>>,
+    mut blocks_task: Local<Option<Task<ActiveBlocks>>>,
+    mut block_entities: Local<HashMap<(i32, i32), BlockEntity>>,
+    mut collider_blocks: Local<HashSet<(i32, i32)>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    rand: Res<Rand>,
+    airports: Res<Airports>,
+    origin: Res<WorldOrigin>,
+    rooftop_assets: Res<rooftop::RooftopAssets>,
+    bird_assets: Res<birds::BirdAssets>,
+) {
+    if let Some(block_pos) = query
+        .get_single()
+        .ok()
+        .filter(|block_pos| block_pos.is_changed() || rand.is_changed())
+    {
+        // Replacing a task that hasn't finished cancels it, as only the latest block matters
+        let BlockPos(px, pz) = *block_pos;
+        let block_pos = (px, pz);
+        let rand = *rand;
+        let airports = airports.clone();
+        let origin = WorldOrigin(origin.0);
+        *blocks_task =
+            Some(AsyncComputeTaskPool::get().spawn(async move {
+                generate_active_blocks(&rand, &airports, &origin, block_pos)
+            }));
+    }
+
+    let Some(active_blocks) = blocks_task
+        .as_mut()
+        .and_then(|task| future::block_on(future::poll_once(task)))
+    else {
+        return;
+    };
+    *blocks_task = None;
+
+    let old_positions: Vec<_> = block_entities
+        .iter()
+        .filter(|(block_pos, block)| {
+            active_blocks
+                .blocks
+                .get(*block_pos)
+                .is_none_or(|(position, kind, _)| {
+                    *position != block.position || *kind != block.kind
+                })
+        })
+        .map(|(block_pos, _)| *block_pos)
         .collect();
 
-    info!(
-        "hits={}, misses={}, block_positions={}, old_positions={}, new_positions={}",
-        num_hits,
-        num_misses,
-        block_positions.len(),
-        old_positions.len(),
-        new_positions.len()
+    debug!(
+        "blocks={}, old_positions={}",
+        block_entities.len(),
+        old_positions.len()
     );
 
-    info!("Pruning {} old positions", old_positions.len());
+    debug!("Pruning {} old positions", old_positions.len());
     for pos in old_positions {
-        if let Some(entity) = block_entities.get(&pos) {
-            commands.entity(*entity).despawn_recursive();
-            block_entities.remove(&pos);
+        if let Some(block) = block_entities.remove(&pos) {
+            commands.entity(block.entity).despawn_recursive();
         }
-        block_positions.remove(&pos);
+        collider_blocks.remove(&pos);
     }
 
-    info!("Adding {} new positions", new_positions.len());
-    block_positions.extend(new_positions.into_iter());
+    let mut num_new = 0;
+    for (block_pos, (absolute_pos, kind, boxes)) in active_blocks.blocks.into_iter() {
+        if block_entities.contains_key(&block_pos) {
+            continue;
+        }
+        num_new += 1;
+
+        // Placed by the origin as it is now, in case it was rebased while the blocks were generated.
+        // Drawn by the building instances rather than meshes of its own
+        let collider = boxes.collider();
+        let block_entity = commands
+            .spawn(SpatialBundle::from_transform(Transform::from_translation(
+                origin.to_local(absolute_pos),
+            )))
+            .insert(boxes)
+            .insert(RigidBody::Fixed)
+            .id();
+
+        if let Some(height) = kind.height() {
+            let side = kind.side();
+            commands
+                .entity(block_entity)
+                .insert(Building { side, height });
+
+            if let BlockKind::Tower { .. } = kind {
+                commands.entity(block_entity).insert(LookTarget("tower"));
+            }
+
+            // Towers' roofs are taken by their spires
+            if let BlockKind::Building { .. } = kind {
+                if let Some(pad) = rooftop::block_pad(block_pos, height) {
+                    commands.entity(block_entity).with_children(|parent| {
+                        rooftop::build_pad(parent, &mut meshes, &rooftop_assets, pad, side, height);
+                    });
+                }
+                if birds::block_has_flock(block_pos) {
+                    commands.entity(block_entity).with_children(|parent| {
+                        birds::build_flock(parent, &bird_assets, height);
+                    });
+                }
+            }
+        }
+
+        block_entities.insert(
+            block_pos,
+            BlockEntity {
+                entity: block_entity,
+                kind,
+                position: absolute_pos,
+                collider,
+            },
+        );
+    }
+    debug!("Added {} new positions", num_new);
+
+    // Only blocks the plane could reach soon need colliders, which keeps the physics broad phase
+    // small however far the blocks are drawn out to
+    let (px, pz) = active_blocks.centre;
+    let centre = Vec2::new((px * SPACING) as f32, (pz * SPACING) as f32);
+    for (block_pos, block) in block_entities.iter() {
+        let Some(collider) = &block.collider else {
+            continue;
+        };
+        let offset = (vec2(block.position.x, block.position.z) - centre).abs();
+        let near = offset.max_element() <= COLLIDER_DISTANCE;
+
+        if near && collider_blocks.insert(*block_pos) {
+            commands.entity(block.entity).insert(collider.clone());
+        } else if !near && collider_blocks.remove(block_pos) {
+            commands.entity(block.entity).remove::<Collider>();
+        }
+    }
 }
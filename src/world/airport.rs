@@ -0,0 +1,425 @@
+use bevy::{
+    math::{vec2, vec3},
+    prelude::*,
+    render::render_resource::{Extent3d, TextureDimension, TextureFormat},
+};
+use bevy_rapier3d::prelude::*;
+use noise::{NoiseFn, Perlin};
+
+use crate::camera::LookTarget;
+
+use super::{
+    harbour::build_harbour,
+    lights::{build_runway_lights, RunwayLightMaterials},
+    SPACING,
+};
+
+const RUNWAY_HEIGHT: f32 = 0.1;
+const MARKING_HEIGHT: f32 = 0.02;
+const THRESHOLD_STRIPES: usize = 8;
+const THRESHOLD_STRIPE_LENGTH: f32 = 30.0;
+const CENTRELINE_DASH_LENGTH: f32 = 30.0;
+const CENTRELINE_DASH_SPACING: f32 = 50.0;
+
+/// Distance kept clear of buildings around each runway.
+pub const CLEARANCE: f32 = 100.0;
+/// Distance over which terrain blends from flat back to its natural height.
+const TERRAIN_BLEND: f32 = 400.0;
+/// Height of the traffic pattern above the runway.
+const PATTERN_HEIGHT: f32 = 300.0;
+/// Angle of the approach path to the runway threshold in degrees.
+const GLIDESLOPE_ANGLE: f32 = 3.0;
+/// Distance from the threshold within which the glideslope and localizer can be followed.
+const GLIDESLOPE_RANGE: f32 = 10_000.0;
+/// Angle in degrees either side of the extended centreline the localizer can be received within.
+const LOCALIZER_COVERAGE: f32 = 35.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RunwaySurface {
+    Asphalt,
+    /// Lane on the water marked by buoys, for floatplanes.
+    Water,
+}
+
+#[derive(Debug, Clone)]
+pub struct AirportSpec {
+    pub name: String,
+    /// Centre of the runway strip at ground level.
+    pub position: Vec3,
+    /// Rotation about the Y axis. A heading of zero has the runway pointing along -Z.
+    pub heading: f32,
+    pub runway_length: f32,
+    pub runway_width: f32,
+    pub surface: RunwaySurface,
+}
+
+impl AirportSpec {
+    pub fn rotation(&self) -> Quat {
+        Quat::from_rotation_y(self.heading)
+    }
+
+    /// Position at the start of the runway, facing down its length.
+    pub fn threshold(&self) -> Transform {
+        let offset = self.runway_length * 0.5 - THRESHOLD_STRIPE_LENGTH;
+
+        Transform::from_translation(
+            self.position + self.rotation() * vec3(0., RUNWAY_HEIGHT, offset),
+        )
+        .with_rotation(self.rotation())
+    }
+
+    /// Compass heading of the runway in degrees, with north along -Z and east along +X.
+    pub fn runway_heading(&self) -> f32 {
+        compass_heading(self.rotation() * Vec3::NEG_Z)
+    }
+
+    /// Runway designators for both ends, e.g. `("36", "18")`.
+    pub fn designators(&self) -> (String, String) {
+        let designator = |heading: f32| {
+            let number = (heading / 10.0).round() as i32;
+            format!("{:02}", if number == 0 { 36 } else { number })
+        };
+        let heading = self.runway_heading();
+
+        (
+            designator(heading),
+            designator((heading + 180.0).rem_euclid(360.0)),
+        )
+    }
+
+    pub fn pattern_altitude(&self) -> f32 {
+        self.position.y + PATTERN_HEIGHT
+    }
+
+    /// Angle in degrees above (positive) or below the glideslope to the threshold, when on the approach.
+    pub fn glideslope_deviation(&self, pos: Vec3) -> Option<f32> {
+        let threshold = self.threshold();
+        let to_pos = pos - threshold.translation;
+        let distance = to_pos.dot(threshold.back());
+
+        (distance > 0.0 && distance < GLIDESLOPE_RANGE)
+            .then(|| to_pos.y.atan2(distance).to_degrees() - GLIDESLOPE_ANGLE)
+    }
+
+    /// Angle in degrees right (positive) or left of the extended runway centreline seen from the
+    /// threshold, when on the approach.
+    pub fn localizer_deviation(&self, pos: Vec3) -> Option<f32> {
+        let threshold = self.threshold();
+        let to_pos = pos - threshold.translation;
+        let distance = to_pos.dot(threshold.back());
+        let deviation = to_pos.dot(threshold.right()).atan2(distance).to_degrees();
+
+        (distance > 0.0 && distance < GLIDESLOPE_RANGE && deviation.abs() < LOCALIZER_COVERAGE)
+            .then_some(deviation)
+    }
+
+    /// Whether a world position falls inside the area kept free of buildings.
+    pub fn in_clear_zone(&self, pos: Vec2) -> bool {
+        self.distance_to_clear_zone(pos) <= 0.0
+    }
+
+    fn distance_to_clear_zone(&self, pos: Vec2) -> f32 {
+        let local =
+            self.rotation().inverse() * vec3(pos.x - self.position.x, 0., pos.y - self.position.z);
+
+        let dx = (local.x.abs() - (self.runway_width * 0.5 + CLEARANCE)).max(0.0);
+        let dz = (local.z.abs() - (self.runway_length * 0.5 + CLEARANCE)).max(0.0);
+
+        vec2(dx, dz).length()
+    }
+}
+
+#[derive(Resource, Clone)]
+pub struct Airports(pub Vec<AirportSpec>);
+
+/// Airport whose instrument approach is tuned, by its index in `Airports`, or the nearest airport's
+/// when none is.
+#[derive(Resource, Default)]
+pub struct TunedApproach(pub Option<usize>);
+
+impl Default for Airports {
+    fn default() -> Self {
+        Self(vec![
+            AirportSpec {
+                name: "Home".to_string(),
+                position: vec3(SPACING as f32 * 0.5, 0., -400.),
+                heading: 0.,
+                runway_length: 1000.,
+                runway_width: 30.,
+                surface: RunwaySurface::Asphalt,
+            },
+            AirportSpec {
+                name: "East".to_string(),
+                position: vec3(SPACING as f32 * 15.5, 0., -SPACING as f32 * 10.),
+                heading: 90_f32.to_radians(),
+                runway_length: 800.,
+                runway_width: 25.,
+                surface: RunwaySurface::Asphalt,
+            },
+            AirportSpec {
+                name: "West".to_string(),
+                position: vec3(-SPACING as f32 * 12.5, 0., SPACING as f32 * 8.),
+                heading: 45_f32.to_radians(),
+                runway_length: 1200.,
+                runway_width: 35.,
+                surface: RunwaySurface::Asphalt,
+            },
+            // On low ground with hills rising to the east, so the harbour sits on a stretch of coast
+            AirportSpec {
+                name: "Harbour".to_string(),
+                position: vec3(-SPACING as f32 * 9.5, 0., SPACING as f32 * 20.),
+                heading: 0.,
+                runway_length: 1000.,
+                runway_width: 60.,
+                surface: RunwaySurface::Water,
+            },
+        ])
+    }
+}
+
+impl Airports {
+    /// The airport the plane starts from.
+    pub fn home(&self) -> &AirportSpec {
+        &self.0[0]
+    }
+
+    /// The first airport with a water lane, for seaplanes to start from.
+    pub fn water(&self) -> Option<&AirportSpec> {
+        self.0
+            .iter()
+            .find(|airport| airport.surface == RunwaySurface::Water)
+    }
+
+    pub fn nearest(&self, pos: Vec3) -> &AirportSpec {
+        self.0
+            .iter()
+            .min_by(|a, b| {
+                let distance =
+                    |airport: &AirportSpec| (airport.position - pos) * Vec3::new(1., 0., 1.);
+                distance(a)
+                    .length_squared()
+                    .total_cmp(&distance(b).length_squared())
+            })
+            .unwrap_or(self.home())
+    }
+
+    /// The airport whose localizer and glideslope are followed from a position.
+    pub fn approach(&self, tuned: &TunedApproach, pos: Vec3) -> &AirportSpec {
+        tuned
+            .0
+            .and_then(|i| self.0.get(i))
+            .unwrap_or_else(|| self.nearest(pos))
+    }
+
+    pub fn in_clear_zone(&self, pos: Vec2) -> bool {
+        self.0.iter().any(|airport| airport.in_clear_zone(pos))
+    }
+
+    /// Scale applied to terrain height, zero at airports rising to one away from them.
+    pub fn flatten_factor(&self, pos: Vec2) -> f32 {
+        self.0
+            .iter()
+            .map(|airport| (airport.distance_to_clear_zone(pos) / TERRAIN_BLEND).min(1.0))
+            .fold(1.0, f32::min)
+    }
+}
+
+/// Compass heading in degrees of a world direction, with north along -Z and east along +X.
+pub fn compass_heading(dir: Vec3) -> f32 {
+    dir.x.atan2(-dir.z).to_degrees().rem_euclid(360.0)
+}
+
+#[derive(Component)]
+pub struct Runway;
+
+#[derive(Component)]
+pub struct Windsock;
+
+pub fn setup_airports(
+    mut commands: Commands,
+    airports: Res<Airports>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut images: ResMut<Assets<Image>>,
+) {
+    let asphalt_material = materials.add(StandardMaterial {
+        base_color_texture: Some(images.add(asphalt_texture(&Perlin::new(2)))),
+        perceptual_roughness: 0.9,
+        ..default()
+    });
+    let marking_material = materials.add(Color::WHITE.into());
+    let runway_lights = RunwayLightMaterials::new(&mut meshes, &mut materials);
+
+    for airport in airports.0.iter() {
+        info!("Building airport {}", airport.name);
+
+        match airport.surface {
+            RunwaySurface::Asphalt => {
+                commands
+                    .spawn((
+                        Runway,
+                        LookTarget("runway"),
+                        PbrBundle {
+                            mesh: meshes.add(Mesh::from(shape::Box::new(
+                                airport.runway_width,
+                                RUNWAY_HEIGHT,
+                                airport.runway_length,
+                            ))),
+                            material: asphalt_material.clone(),
+                            transform: Transform::from_translation(
+                                airport.position + Vec3::Y * RUNWAY_HEIGHT * 0.5,
+                            )
+                            .with_rotation(airport.rotation()),
+                            ..default()
+                        },
+                        RigidBody::Fixed,
+                        Collider::cuboid(
+                            airport.runway_width * 0.5,
+                            RUNWAY_HEIGHT * 0.5,
+                            airport.runway_length * 0.5,
+                        ),
+                        Friction::new(0.01),
+                    ))
+                    .with_children(|parent| {
+                        build_markings(parent, &mut meshes, &marking_material, airport);
+                        build_runway_lights(parent, &runway_lights, airport, RUNWAY_HEIGHT * 0.5);
+                    });
+            }
+            RunwaySurface::Water => {
+                build_harbour(&mut commands, &mut meshes, &mut materials, airport);
+            }
+        }
+
+        build_windsock(&mut commands, &mut meshes, &mut materials, airport);
+    }
+
+    commands.insert_resource(runway_lights);
+}
+
+fn build_markings(
+    parent: &mut ChildBuilder<'_, '_, '_>,
+    meshes: &mut ResMut<Assets<Mesh>>,
+    material: &Handle<StandardMaterial>,
+    airport: &AirportSpec,
+) {
+    let y = RUNWAY_HEIGHT * 0.5 + MARKING_HEIGHT * 0.5;
+
+    // Threshold "piano keys" at both ends of the runway
+    let stripe_width = airport.runway_width / (THRESHOLD_STRIPES as f32 * 2.0);
+    let stripe_mesh = meshes.add(Mesh::from(shape::Box::new(
+        stripe_width,
+        MARKING_HEIGHT,
+        THRESHOLD_STRIPE_LENGTH,
+    )));
+
+    for end in [-1.0, 1.0] {
+        let z = end * (airport.runway_length * 0.5 - THRESHOLD_STRIPE_LENGTH * 0.5 - 5.0);
+
+        for i in 0..THRESHOLD_STRIPES {
+            let x = (i as f32 + 0.5) * stripe_width * 2.0 - airport.runway_width * 0.5;
+
+            parent.spawn(PbrBundle {
+                mesh: stripe_mesh.clone(),
+                material: material.clone(),
+                transform: Transform::from_xyz(x, y, z),
+                ..default()
+            });
+        }
+    }
+
+    // Centreline dashes between the thresholds
+    let dash_mesh = meshes.add(Mesh::from(shape::Box::new(
+        0.9,
+        MARKING_HEIGHT,
+        CENTRELINE_DASH_LENGTH,
+    )));
+    let dash_extent = airport.runway_length * 0.5 - THRESHOLD_STRIPE_LENGTH * 2.0;
+    let mut z = -dash_extent;
+
+    while z <= dash_extent {
+        parent.spawn(PbrBundle {
+            mesh: dash_mesh.clone(),
+            material: material.clone(),
+            transform: Transform::from_xyz(0., y, z),
+            ..default()
+        });
+        z += CENTRELINE_DASH_SPACING;
+    }
+}
+
+fn build_windsock(
+    commands: &mut Commands,
+    meshes: &mut ResMut<Assets<Mesh>>,
+    materials: &mut ResMut<Assets<StandardMaterial>>,
+    airport: &AirportSpec,
+) {
+    let pole_height = 6.0;
+    // Harbours have theirs on the quay at the edge of the water
+    let setback = match airport.surface {
+        RunwaySurface::Asphalt => 20.0,
+        RunwaySurface::Water => CLEARANCE + 5.0,
+    };
+    let local_pos = vec2(
+        airport.runway_width * 0.5 + setback,
+        airport.runway_length * 0.5 - THRESHOLD_STRIPE_LENGTH * 3.0,
+    );
+    let pos = airport.position + airport.rotation() * vec3(local_pos.x, 0., local_pos.y);
+
+    commands
+        .spawn((
+            PbrBundle {
+                mesh: meshes.add(Mesh::from(shape::Cylinder {
+                    radius: 0.1,
+                    height: pole_height,
+                    ..default()
+                })),
+                material: materials.add(Color::GRAY.into()),
+                transform: Transform::from_translation(pos + Vec3::Y * pole_height * 0.5),
+                ..default()
+            },
+            RigidBody::Fixed,
+            Collider::cylinder(pole_height * 0.5, 0.1),
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Windsock,
+                PbrBundle {
+                    mesh: meshes.add(Mesh::from(shape::Cylinder {
+                        radius: 0.4,
+                        height: 2.5,
+                        ..default()
+                    })),
+                    material: materials.add(Color::ORANGE_RED.into()),
+                    transform: Transform::from_xyz(1.25, pole_height * 0.5, 0.)
+                        .with_rotation(Quat::from_rotation_z(90_f32.to_radians())),
+                    ..default()
+                },
+            ));
+        });
+}
+
+fn asphalt_texture(perlin: &Perlin) -> Image {
+    let width = 64;
+    let height = 512;
+    let mut data = Vec::with_capacity(width * height * 4);
+
+    for y in 0..height {
+        for x in 0..width {
+            let n = perlin.get([x as f64 * 0.3, y as f64 * 0.3]) as f32;
+            let shade = (0.22 + n * 0.05).clamp(0.0, 1.0);
+            let value = (shade * 255.0) as u8;
+            data.extend_from_slice(&[value, value, value, 255]);
+        }
+    }
+
+    Image::new(
+        Extent3d {
+            width: width as u32,
+            height: height as u32,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+    )
+}
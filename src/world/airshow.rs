@@ -0,0 +1,216 @@
+use bevy::{
+    math::{vec2, vec3},
+    prelude::*,
+};
+use bevy_rapier3d::prelude::*;
+use rand::{seq::SliceRandom, Rng};
+
+use super::{terrain::terrain_height, Airports, Rand, WorldOrigin};
+
+/// Index of the airport hosting the airshow.
+const VENUE_AIRPORT: usize = 1;
+/// Distance of the crowd line from the runway edge.
+const CROWD_LINE_OFFSET: f32 = 60.0;
+/// Distance from the crowd line to the near edge of the display box.
+const BOX_SETBACK: f32 = 150.0;
+const BOX_SIZE: Vec3 = vec3(500.0, 450.0, 1000.0);
+/// Lowest height above the ground counted as flying in the display box.
+const BOX_FLOOR: f32 = 30.0;
+/// Score multiplier for manoeuvres flown inside the display box.
+const DISPLAY_BOX_MULTIPLIER: f32 = 1.5;
+
+const GRANDSTANDS: usize = 5;
+const GRANDSTAND_SIZE: Vec3 = vec3(12.0, 1.0, 40.0);
+const GRANDSTAND_TIERS: usize = 4;
+const CROWD_PER_TIER: usize = 30;
+const MARKER_HEIGHT: f32 = 12.0;
+
+const CROWD_COLORS: [Color; 6] = [
+    Color::rgb(0.8, 0.2, 0.2),
+    Color::rgb(0.2, 0.3, 0.8),
+    Color::rgb(0.9, 0.8, 0.2),
+    Color::rgb(0.2, 0.6, 0.3),
+    Color::rgb(0.9, 0.9, 0.9),
+    Color::rgb(0.3, 0.3, 0.3),
+];
+
+/// Airshow venue alongside a runway, with the display box out beyond the runway from the crowd.
+#[derive(Resource)]
+pub struct AirshowVenue {
+    /// Centre of the crowd line at ground level.
+    pub position: Vec3,
+    /// Rotation about the Y axis, with local +X pointing from the crowd towards the display box.
+    pub rotation: Quat,
+}
+
+impl FromWorld for AirshowVenue {
+    fn from_world(world: &mut World) -> Self {
+        let airports = world.resource::<Airports>();
+        let airport = &airports.0[VENUE_AIRPORT];
+
+        Self {
+            position: airport.position
+                + airport.rotation()
+                    * vec3(-(airport.runway_width * 0.5 + CROWD_LINE_OFFSET), 0., 0.),
+            rotation: airport.rotation(),
+        }
+    }
+}
+
+impl AirshowVenue {
+    fn to_local(&self, pos: Vec3) -> Vec3 {
+        self.rotation.inverse() * (pos - self.position)
+    }
+
+    fn to_world(&self, local: Vec3) -> Vec3 {
+        self.position + self.rotation * local
+    }
+
+    pub fn in_display_box(&self, pos: Vec3) -> bool {
+        let local = self.to_local(pos);
+
+        (BOX_SETBACK..BOX_SETBACK + BOX_SIZE.x).contains(&local.x)
+            && (BOX_FLOOR..BOX_SIZE.y).contains(&local.y)
+            && local.z.abs() <= BOX_SIZE.z * 0.5
+    }
+
+    /// Multiplier applied to manoeuvre scores flown at a position.
+    pub fn score_multiplier(&self, pos: Vec3) -> f32 {
+        if self.in_display_box(pos) {
+            DISPLAY_BOX_MULTIPLIER
+        } else {
+            1.0
+        }
+    }
+}
+
+pub fn setup_airshow(
+    mut commands: Commands,
+    venue: Res<AirshowVenue>,
+    airports: Res<Airports>,
+    rand: Res<Rand>,
+    origin: Res<WorldOrigin>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    info!("Building airshow venue");
+
+    let stand_material = materials.add(Color::rgb(0.5, 0.5, 0.55).into());
+    let tier_mesh = meshes.add(Mesh::from(shape::Box::new(
+        GRANDSTAND_SIZE.x / GRANDSTAND_TIERS as f32,
+        GRANDSTAND_SIZE.y,
+        GRANDSTAND_SIZE.z,
+    )));
+    let person_mesh = meshes.add(Mesh::from(shape::Quad::new(vec2(0.6, 1.7))));
+    let crowd_materials: Vec<Handle<StandardMaterial>> = CROWD_COLORS
+        .iter()
+        .map(|color| {
+            materials.add(StandardMaterial {
+                base_color: *color,
+                unlit: true,
+                cull_mode: None,
+                ..default()
+            })
+        })
+        .collect();
+    let mut rng = rand::thread_rng();
+
+    let tier_depth = GRANDSTAND_SIZE.x / GRANDSTAND_TIERS as f32;
+    let spacing = BOX_SIZE.z / GRANDSTANDS as f32;
+
+    for i in 0..GRANDSTANDS {
+        let z = (i as f32 + 0.5) * spacing - BOX_SIZE.z * 0.5;
+
+        commands
+            .spawn((
+                SpatialBundle::from_transform(
+                    Transform::from_translation(venue.to_world(vec3(-GRANDSTAND_SIZE.x, 0., z)))
+                        .with_rotation(venue.rotation),
+                ),
+                RigidBody::Fixed,
+            ))
+            .with_children(|parent| {
+                // Tiers step up away from the display so everyone gets a view
+                for tier in 0..GRANDSTAND_TIERS {
+                    let height = GRANDSTAND_SIZE.y * (GRANDSTAND_TIERS - tier) as f32;
+                    let x = (tier as f32 + 0.5) * tier_depth;
+
+                    parent.spawn((
+                        PbrBundle {
+                            mesh: tier_mesh.clone(),
+                            material: stand_material.clone(),
+                            transform: Transform::from_xyz(x, height * 0.5, 0.).with_scale(vec3(
+                                1.,
+                                GRANDSTAND_TIERS as f32 - tier as f32,
+                                1.,
+                            )),
+                            ..default()
+                        },
+                        Collider::cuboid(
+                            tier_depth * 0.5,
+                            GRANDSTAND_SIZE.y * 0.5,
+                            GRANDSTAND_SIZE.z * 0.5,
+                        ),
+                    ));
+
+                    for _ in 0..CROWD_PER_TIER {
+                        let Some(material) = crowd_materials.choose(&mut rng) else {
+                            continue;
+                        };
+
+                        // Billboards face the display box along local +X
+                        parent.spawn(PbrBundle {
+                            mesh: person_mesh.clone(),
+                            material: material.clone(),
+                            transform: Transform::from_xyz(
+                                x + rng.gen_range(-0.5..0.5),
+                                height + 0.85,
+                                rng.gen_range(-0.5..0.5) * (GRANDSTAND_SIZE.z - 1.0),
+                            )
+                            .with_rotation(Quat::from_rotation_y(90_f32.to_radians())),
+                            ..default()
+                        });
+                    }
+                }
+            });
+    }
+
+    // Pylons marking the ground corners of the display box and the crowd line limit
+    let marker_mesh = meshes.add(Mesh::from(shape::Cylinder {
+        radius: 0.5,
+        height: MARKER_HEIGHT,
+        ..default()
+    }));
+    let marker_material = materials.add(StandardMaterial {
+        base_color: Color::ORANGE_RED,
+        unlit: true,
+        ..default()
+    });
+
+    let near = BOX_SETBACK;
+    let far = BOX_SETBACK + BOX_SIZE.x;
+    let half_length = BOX_SIZE.z * 0.5;
+    let corners = [
+        vec2(near, -half_length),
+        vec2(near, 0.),
+        vec2(near, half_length),
+        vec2(far, -half_length),
+        vec2(far, half_length),
+    ];
+
+    for corner in corners {
+        let pos = venue.to_world(vec3(corner.x, 0., corner.y));
+        let ground = terrain_height(&rand.terrain, &airports, &origin, vec2(pos.x, pos.z));
+
+        commands.spawn((
+            PbrBundle {
+                mesh: marker_mesh.clone(),
+                material: marker_material.clone(),
+                transform: Transform::from_xyz(pos.x, ground + MARKER_HEIGHT * 0.5, pos.z),
+                ..default()
+            },
+            RigidBody::Fixed,
+            Collider::cylinder(MARKER_HEIGHT * 0.5, 0.5),
+        ));
+    }
+}
@@ -0,0 +1,206 @@
+use bevy::{math::vec3, prelude::*};
+use bevy_rapier3d::{prelude::*, rapier::geometry::CollisionEventFlags};
+use rand::Rng;
+
+use crate::{damage::DamageEvent, flight_model::Airspeed, plane::Plane};
+
+/// One in this many buildings has a flock of birds circling above it.
+const FLOCK_CHANCE: i32 = 8;
+const FLOCK_SIZE: usize = 12;
+/// Height above the roof that a flock circles around.
+const FLOCK_HEIGHT: f32 = 20.0;
+/// Distance from the centre of the flock's home beyond which birds turn back towards it.
+const HOME_RADIUS: f32 = 40.0;
+const MIN_BIRD_SPEED: f32 = 8.0;
+const MAX_BIRD_SPEED: f32 = 14.0;
+/// Distance within which birds flock with each other.
+const NEIGHBOUR_DISTANCE: f32 = 10.0;
+/// Distance within which birds move apart so they don't bunch up.
+const SEPARATION_DISTANCE: f32 = 2.0;
+const COHESION: f32 = 0.5;
+const ALIGNMENT: f32 = 1.0;
+const SEPARATION: f32 = 4.0;
+/// Acceleration back towards home for each metre beyond the home radius.
+const HOMING: f32 = 0.2;
+const BIRD_RADIUS: f32 = 0.3;
+/// Damage from a bird hitting the plane at this airspeed, scaling with the energy of the strike.
+const STRIKE_DAMAGE: f32 = 40.0;
+const STRIKE_SPEED: f32 = 40.0;
+
+/// Flock of birds circling above a building, with the birds as its children.
+#[derive(Component)]
+pub struct Flock;
+
+#[derive(Component)]
+pub struct Bird {
+    velocity: Vec3,
+}
+
+#[derive(Resource)]
+pub struct BirdAssets {
+    mesh: Handle<Mesh>,
+    material: Handle<StandardMaterial>,
+}
+
+pub fn setup_birds(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    commands.insert_resource(BirdAssets {
+        mesh: meshes.add(Mesh::from(shape::Box::new(0.6, 0.08, 0.25))),
+        material: materials.add(Color::rgb(0.15, 0.15, 0.15).into()),
+    });
+}
+
+/// Whether the building on a block has a flock, picked from a hash of the block so the same
+/// buildings have them each time they're generated.
+pub fn block_has_flock((x, z): (i32, i32)) -> bool {
+    let hash = x.wrapping_mul(83_492_791) ^ z.wrapping_mul(25_165_843);
+    hash.rem_euclid(FLOCK_CHANCE) == 0
+}
+
+/// Builds a flock above the roof of a building, as a child of it so it streams in and out with the
+/// building.
+pub fn build_flock(parent: &mut ChildBuilder<'_, '_, '_>, assets: &BirdAssets, height: f32) {
+    let mut rng = rand::thread_rng();
+
+    parent
+        .spawn((
+            Flock,
+            SpatialBundle::from_transform(Transform::from_xyz(0., height * 0.5 + FLOCK_HEIGHT, 0.)),
+        ))
+        .with_children(|parent| {
+            for _ in 0..FLOCK_SIZE {
+                let pos = vec3(
+                    rng.gen_range(-1.0..1.0),
+                    rng.gen_range(-0.2..0.2),
+                    rng.gen_range(-1.0..1.0),
+                ) * HOME_RADIUS
+                    * 0.5;
+                let angle = rng.gen_range(0.0..std::f32::consts::TAU);
+
+                parent.spawn((
+                    Bird {
+                        velocity: vec3(angle.cos(), 0., angle.sin()) * MIN_BIRD_SPEED,
+                    },
+                    PbrBundle {
+                        mesh: assets.mesh.clone(),
+                        material: assets.material.clone(),
+                        transform: Transform::from_translation(pos),
+                        ..default()
+                    },
+                    Collider::ball(BIRD_RADIUS),
+                    Sensor,
+                ));
+            }
+        });
+}
+
+/// Moves the birds of each flock as boids, keeping together and heading the same way as the birds
+/// around them while not getting too close, and circling back when they stray from home.
+pub fn update_flocks(
+    time: Res<Time>,
+    flock_query: Query<&Children, With<Flock>>,
+    mut bird_query: Query<(&mut Bird, &mut Transform)>,
+) {
+    let dt = time.delta_seconds();
+
+    for children in flock_query.iter() {
+        let birds: Vec<_> = children
+            .iter()
+            .filter_map(|child| bird_query.get(*child).ok())
+            .map(|(bird, tx)| (tx.translation, bird.velocity))
+            .collect();
+
+        for child in children.iter() {
+            let Ok((mut bird, mut tx)) = bird_query.get_mut(*child) else {
+                continue;
+            };
+            let pos = tx.translation;
+
+            let mut centre = Vec3::ZERO;
+            let mut heading = Vec3::ZERO;
+            let mut separation = Vec3::ZERO;
+            let mut neighbours = 0;
+            for (other_pos, other_velocity) in birds.iter() {
+                let offset = *other_pos - pos;
+                let distance = offset.length();
+                if !(f32::EPSILON..=NEIGHBOUR_DISTANCE).contains(&distance) {
+                    continue;
+                }
+
+                centre += *other_pos;
+                heading += *other_velocity;
+                neighbours += 1;
+                if distance < SEPARATION_DISTANCE {
+                    separation -= offset / distance * (SEPARATION_DISTANCE - distance);
+                }
+            }
+
+            let mut acceleration = separation * SEPARATION;
+            if neighbours > 0 {
+                let count = neighbours as f32;
+                acceleration += (centre / count - pos) * COHESION
+                    + (heading / count - bird.velocity) * ALIGNMENT;
+            }
+            let stray = pos.length() - HOME_RADIUS;
+            if stray > 0.0 {
+                acceleration -= pos.normalize() * stray * HOMING;
+            }
+
+            let velocity = bird.velocity + acceleration * dt;
+            bird.velocity = velocity.normalize_or_zero()
+                * velocity.length().clamp(MIN_BIRD_SPEED, MAX_BIRD_SPEED);
+            tx.translation += bird.velocity * dt;
+            tx.look_to(bird.velocity, Vec3::Y);
+        }
+    }
+}
+
+/// Damages the part of the plane that hits a bird, harder the faster the plane is flying, and
+/// removes the bird.
+pub fn detect_bird_strikes(
+    mut commands: Commands,
+    mut collision_events: EventReader<CollisionEvent>,
+    bird_query: Query<(), With<Bird>>,
+    parent_query: Query<&Parent>,
+    plane_query: Query<&Airspeed, With<Plane>>,
+    mut damage_events: EventWriter<DamageEvent>,
+) {
+    // Birds hitting more than one part in a step only strike once
+    let mut struck = vec![];
+
+    for event in collision_events.iter() {
+        let CollisionEvent::Started(e1, e2, flags) = event else {
+            continue;
+        };
+        if !flags.contains(CollisionEventFlags::SENSOR) {
+            continue;
+        }
+
+        for (bird, other) in [(*e1, *e2), (*e2, *e1)] {
+            if !bird_query.contains(bird) || struck.contains(&bird) {
+                continue;
+            }
+            let Some(airspeed) = parent_query
+                .iter_ancestors(other)
+                .find_map(|entity| plane_query.get(entity).ok())
+            else {
+                continue;
+            };
+
+            let damage = STRIKE_DAMAGE * (airspeed.0 / STRIKE_SPEED).powi(2);
+            info!(
+                "Bird strike at {:.0} m/s caused {:.1} damage",
+                airspeed.0, damage
+            );
+            damage_events.send(DamageEvent {
+                part: other,
+                damage,
+            });
+            commands.entity(bird).despawn_recursive();
+            struck.push(bird);
+        }
+    }
+}
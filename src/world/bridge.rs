@@ -0,0 +1,190 @@
+use bevy::{
+    math::{vec2, vec3},
+    prelude::*,
+};
+use bevy_rapier3d::prelude::*;
+use noise::Perlin;
+
+use crate::camera::LookTarget;
+
+use super::{
+    terrain::{river_noise, terrain_height, RIVER_LEVEL, RIVER_WIDTH},
+    Airports, Rand, WorldOrigin, SPACING,
+};
+
+/// Distance north along Main street, between its first two rings.
+const STREET_DISTANCE: f32 = 1300.0;
+/// Distance between the towers, which stand clear of the buildings either side of the street.
+const STREET_SPAN: f32 = 120.0;
+const STREET_DECK_HEIGHT: f32 = 150.0;
+/// Lowest height of a river bridge's deck above the water.
+const RIVER_DECK_HEIGHT: f32 = 25.0;
+/// Height of a river bridge's deck above the higher of its towers' footings.
+const RIVER_DECK_CLEARANCE: f32 = 10.0;
+/// Extra span beyond the width of the river, so the towers stand on the banks.
+const RIVER_SPAN_MARGIN: f32 = 60.0;
+const DECK_THICKNESS: f32 = 4.0;
+const DECK_WIDTH: f32 = 10.0;
+const TOWER_SIDE: f32 = 8.0;
+/// Depth the towers go below their footings, for uneven ground.
+const FOOTING_DEPTH: f32 = 20.0;
+
+/// Sensor filling the gap under a bridge, which planes fly through to fly under it.
+#[derive(Component)]
+pub struct BridgeGap;
+
+/// Shape of a bridge spanning a gap along its local X axis, from an origin in the middle of the gap
+/// at the bottom.
+pub struct BridgeSpec {
+    /// Distance between the towers.
+    pub span: f32,
+    pub deck_height: f32,
+    /// Height of the ground under each tower relative to the origin.
+    pub footings: [f32; 2],
+}
+
+/// Builds a bridge across Main street, high enough to fly under.
+pub fn setup_bridge(
+    mut commands: Commands,
+    airports: Res<Airports>,
+    rand: Res<Rand>,
+    origin: Res<WorldOrigin>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let pos = vec2(SPACING as f32 * 0.5, -STREET_DISTANCE);
+    let ground = terrain_height(&rand.terrain, &airports, &origin, pos);
+    let spec = BridgeSpec {
+        span: STREET_SPAN,
+        deck_height: STREET_DECK_HEIGHT,
+        footings: [0.0, 0.0],
+    };
+
+    info!("Building bridge over Main street");
+
+    commands
+        .spawn((
+            Name::new("Main street bridge"),
+            SpatialBundle::from_transform(Transform::from_xyz(pos.x, ground, pos.y)),
+            RigidBody::Fixed,
+        ))
+        .with_children(|parent| {
+            build_bridge(
+                parent,
+                &mut meshes,
+                &mut materials,
+                Transform::IDENTITY,
+                &spec,
+            );
+        });
+}
+
+/// Finds where a river runs closest through the middle of an area and the bridge that would span
+/// it there, with its transform relative to the centre of the area.
+pub fn river_crossing(
+    perlin: &Perlin,
+    airports: &Airports,
+    origin: &WorldOrigin,
+    centre: Vec2,
+    size: f32,
+    samples: usize,
+) -> Option<(Transform, BridgeSpec)> {
+    let cell_size = size / (samples - 1) as f32;
+    let corner = centre - Vec2::splat(size * 0.5);
+
+    // Closest to the middle of a river, keeping away from the edges so the banks are in the area too
+    let (pos, noise) = (samples / 4..samples * 3 / 4)
+        .flat_map(|x| (samples / 4..samples * 3 / 4).map(move |z| (x, z)))
+        .map(|(x, z)| {
+            let pos = corner + vec2(x as f32, z as f32) * cell_size;
+            (pos, river_noise(perlin, origin, pos).abs())
+        })
+        .min_by(|a, b| a.1.total_cmp(&b.1))?;
+
+    // The river runs along the noise's contours, so its gradient points across it
+    let delta = 1.0;
+    let gradient = vec2(
+        river_noise(perlin, origin, pos + vec2(delta, 0.)) - river_noise(perlin, origin, pos),
+        river_noise(perlin, origin, pos + vec2(0., delta)) - river_noise(perlin, origin, pos),
+    ) / delta;
+    let across = gradient.try_normalize()?;
+
+    // No river unless the closest point is within a sample of its middle, and airports flatten the
+    // rivers away
+    if noise / gradient.length() > cell_size || airports.flatten_factor(pos) < 1.0 {
+        return None;
+    }
+    let river_width = RIVER_WIDTH * 2.0 / gradient.length();
+    let span = river_width + RIVER_SPAN_MARGIN;
+
+    let footings = [-0.5, 0.5].map(|side| {
+        terrain_height(perlin, airports, origin, pos + across * span * side) - RIVER_LEVEL
+    });
+    let deck_height = (footings[0].max(footings[1]) + RIVER_DECK_CLEARANCE).max(RIVER_DECK_HEIGHT);
+
+    let transform = Transform::from_xyz(pos.x - centre.x, RIVER_LEVEL, pos.y - centre.y)
+        .with_rotation(Quat::from_rotation_y(-across.y.atan2(across.x)));
+
+    Some((
+        transform,
+        BridgeSpec {
+            span,
+            deck_height,
+            footings,
+        },
+    ))
+}
+
+pub fn build_bridge(
+    parent: &mut ChildBuilder<'_, '_, '_>,
+    meshes: &mut ResMut<Assets<Mesh>>,
+    materials: &mut ResMut<Assets<StandardMaterial>>,
+    transform: Transform,
+    spec: &BridgeSpec,
+) {
+    let material = materials.add(Color::rgb(0.45, 0.3, 0.25).into());
+    let deck_size = vec3(spec.span + TOWER_SIDE, DECK_THICKNESS, DECK_WIDTH);
+
+    parent
+        .spawn(SpatialBundle::from_transform(transform))
+        .with_children(|parent| {
+            parent.spawn((
+                PbrBundle {
+                    mesh: meshes.add(Mesh::from(shape::Box::new(
+                        deck_size.x,
+                        deck_size.y,
+                        deck_size.z,
+                    ))),
+                    material: material.clone(),
+                    transform: Transform::from_xyz(0., spec.deck_height + deck_size.y * 0.5, 0.),
+                    ..default()
+                },
+                Collider::cuboid(deck_size.x * 0.5, deck_size.y * 0.5, deck_size.z * 0.5),
+            ));
+
+            for (side, footing) in [-0.5, 0.5].into_iter().zip(spec.footings) {
+                let bottom = footing - FOOTING_DEPTH;
+                let height = spec.deck_height - bottom;
+
+                parent.spawn((
+                    PbrBundle {
+                        mesh: meshes
+                            .add(Mesh::from(shape::Box::new(TOWER_SIDE, height, TOWER_SIDE))),
+                        material: material.clone(),
+                        transform: Transform::from_xyz(spec.span * side, bottom + height * 0.5, 0.),
+                        ..default()
+                    },
+                    Collider::cuboid(TOWER_SIDE * 0.5, height * 0.5, TOWER_SIDE * 0.5),
+                ));
+            }
+
+            let gap = vec3(spec.span - TOWER_SIDE, spec.deck_height, deck_size.z);
+            parent.spawn((
+                BridgeGap,
+                LookTarget("bridge"),
+                TransformBundle::from_transform(Transform::from_xyz(0., gap.y * 0.5, 0.)),
+                Collider::cuboid(gap.x * 0.5, gap.y * 0.5, gap.z * 0.5),
+                Sensor,
+            ));
+        });
+}
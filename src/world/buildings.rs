@@ -0,0 +1,452 @@
+use std::sync::Arc;
+
+use bevy::{
+    asset::load_internal_asset,
+    core_pipeline::{
+        core_3d::Opaque3d,
+        tonemapping::{DebandDither, Tonemapping},
+    },
+    ecs::system::{lifetimeless::*, SystemParamItem},
+    pbr::{MeshPipeline, MeshPipelineKey, MeshUniform, SetMeshBindGroup, SetMeshViewBindGroup},
+    prelude::*,
+    reflect::TypeUuid,
+    render::{
+        extract_component::{ExtractComponent, ExtractComponentPlugin},
+        extract_resource::ExtractResourcePlugin,
+        mesh::{GpuBufferInfo, MeshVertexBufferLayout},
+        render_asset::RenderAssets,
+        render_phase::{
+            AddRenderCommand, DrawFunctions, PhaseItem, RenderCommand, RenderCommandResult,
+            RenderPhase, SetItemPipeline, TrackedRenderPass,
+        },
+        render_resource::*,
+        renderer::RenderDevice,
+        view::{ExtractedView, NoFrustumCulling},
+        Render, RenderApp, RenderSet,
+    },
+};
+use bytemuck::{Pod, Zeroable};
+
+use super::{
+    city::{BlockBoxes, BoxLights},
+    lights::NightLights,
+};
+
+const BUILDINGS_SHADER_HANDLE: HandleUntyped =
+    HandleUntyped::weak_from_u64(Shader::TYPE_UUID, 0x5e1f_b11d_0a7c_4e21);
+/// Linear colour of the lit windows at full brightness.
+const WINDOW_LIGHT: Vec3 = Vec3::new(2.0, 1.5, 0.8);
+/// Emissive strength of glowing boxes at full brightness, relative to their colour.
+const GLOW_INTENSITY: f32 = 6.0;
+
+/// Draws every building in a single instanced draw call, rather than as a mesh and material each,
+/// so far more of them can be in view at once.
+///
+/// Block entities only hold their placement, the boxes making up their buildings, towers and trees,
+/// and any collider, and the instances are rebuilt from them as they stream in and out.
+pub struct BuildingInstancingPlugin;
+
+impl Plugin for BuildingInstancingPlugin {
+    fn build(&self, app: &mut App) {
+        load_internal_asset!(
+            app,
+            BUILDINGS_SHADER_HANDLE,
+            "buildings.wgsl",
+            Shader::from_wgsl
+        );
+
+        app.add_plugins(ExtractComponentPlugin::<BuildingInstances>::default())
+            .add_plugins(ExtractResourcePlugin::<NightLights>::default())
+            .add_systems(Startup, setup_building_instances)
+            .add_systems(Update, update_building_instances);
+
+        app.sub_app_mut(RenderApp)
+            .add_render_command::<Opaque3d, DrawBuildings>()
+            .init_resource::<SpecializedMeshPipelines<BuildingPipeline>>()
+            .add_systems(
+                Render,
+                (
+                    queue_buildings.in_set(RenderSet::Queue),
+                    prepare_building_buffers.in_set(RenderSet::Prepare),
+                    prepare_night_lighting.in_set(RenderSet::Prepare),
+                ),
+            );
+    }
+
+    fn finish(&self, app: &mut App) {
+        app.sub_app_mut(RenderApp)
+            .init_resource::<BuildingPipeline>();
+    }
+}
+
+/// Placement of each box, relative to the entity holding them. Shared so extracting them each frame
+/// doesn't copy the tens of thousands of street props.
+#[derive(Component, Clone, ExtractComponent)]
+pub struct BuildingInstances(Arc<Vec<BuildingInstance>>);
+
+#[derive(Clone, Copy, Pod, Zeroable)]
+#[repr(C)]
+struct BuildingInstance {
+    /// Centre of the box.
+    position: Vec3,
+    size: Vec3,
+    /// Linear RGBA colour.
+    color: [f32; 4],
+    /// How much its windows and its whole surface light up at night.
+    lights: [f32; 2],
+}
+
+fn setup_building_instances(mut commands: Commands, mut meshes: ResMut<Assets<Mesh>>) {
+    commands.spawn((
+        meshes.add(Mesh::from(shape::Cube { size: 1.0 })),
+        SpatialBundle::INHERITED_IDENTITY,
+        BuildingInstances(Arc::default()),
+        // Instances are spread across the whole active area, so the mesh's own bounds mean nothing
+        NoFrustumCulling,
+    ));
+}
+
+/// Rebuilds the instances when blocks are generated or pruned. Both the blocks and the instances
+/// entity are moved when the origin is rebased, so they stay in step without a rebuild.
+fn update_building_instances(
+    added_query: Query<(), Added<BlockBoxes>>,
+    mut removed: RemovedComponents<BlockBoxes>,
+    block_query: Query<(&Transform, &BlockBoxes)>,
+    mut instances_query: Query<(&Transform, &mut BuildingInstances)>,
+) {
+    let removed = removed.iter().count() > 0;
+    if added_query.is_empty() && !removed {
+        return;
+    }
+    let Ok((instances_tx, mut instances)) = instances_query.get_single_mut() else {
+        return;
+    };
+
+    instances.0 = Arc::new(
+        block_query
+            .iter()
+            .flat_map(|(tx, boxes)| {
+                let position = tx.translation - instances_tx.translation;
+                boxes.0.iter().map(move |block_box| BuildingInstance {
+                    position: position + block_box.centre,
+                    size: block_box.size,
+                    color: block_box.color.as_linear_rgba_f32(),
+                    lights: match block_box.lights {
+                        BoxLights::None => [0.0, 0.0],
+                        BoxLights::Windows => [1.0, 0.0],
+                        BoxLights::Glow => [0.0, 1.0],
+                    },
+                })
+            })
+            .collect(),
+    );
+
+    debug!("Instancing {} boxes", instances.0.len());
+}
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn queue_buildings(
+    opaque_3d_draw_functions: Res<DrawFunctions<Opaque3d>>,
+    building_pipeline: Res<BuildingPipeline>,
+    msaa: Res<Msaa>,
+    mut pipelines: ResMut<SpecializedMeshPipelines<BuildingPipeline>>,
+    pipeline_cache: Res<PipelineCache>,
+    meshes: Res<RenderAssets<Mesh>>,
+    instances_query: Query<(Entity, &MeshUniform, &Handle<Mesh>, &BuildingInstances)>,
+    mut views: Query<(
+        &ExtractedView,
+        Option<&Tonemapping>,
+        Option<&DebandDither>,
+        &mut RenderPhase<Opaque3d>,
+    )>,
+) {
+    let draw_buildings = opaque_3d_draw_functions.read().id::<DrawBuildings>();
+
+    for (view, tonemapping, dither, mut opaque_phase) in views.iter_mut() {
+        // Tonemapped in the shader the same way as the standard materials around the buildings
+        let mut view_key = MeshPipelineKey::from_msaa_samples(msaa.samples())
+            | MeshPipelineKey::from_hdr(view.hdr);
+        if !view.hdr {
+            if let Some(tonemapping) = tonemapping {
+                view_key |= MeshPipelineKey::TONEMAP_IN_SHADER
+                    | match tonemapping {
+                        Tonemapping::None => MeshPipelineKey::TONEMAP_METHOD_NONE,
+                        Tonemapping::Reinhard => MeshPipelineKey::TONEMAP_METHOD_REINHARD,
+                        Tonemapping::ReinhardLuminance => {
+                            MeshPipelineKey::TONEMAP_METHOD_REINHARD_LUMINANCE
+                        }
+                        Tonemapping::AcesFitted => MeshPipelineKey::TONEMAP_METHOD_ACES_FITTED,
+                        Tonemapping::AgX => MeshPipelineKey::TONEMAP_METHOD_AGX,
+                        Tonemapping::SomewhatBoringDisplayTransform => {
+                            MeshPipelineKey::TONEMAP_METHOD_SOMEWHAT_BORING_DISPLAY_TRANSFORM
+                        }
+                        Tonemapping::TonyMcMapface => {
+                            MeshPipelineKey::TONEMAP_METHOD_TONY_MC_MAPFACE
+                        }
+                        Tonemapping::BlenderFilmic => {
+                            MeshPipelineKey::TONEMAP_METHOD_BLENDER_FILMIC
+                        }
+                    };
+            }
+            if let Some(DebandDither::Enabled) = dither {
+                view_key |= MeshPipelineKey::DEBAND_DITHER;
+            }
+        }
+
+        let rangefinder = view.rangefinder3d();
+        for (entity, mesh_uniform, mesh_handle, instances) in instances_query.iter() {
+            if instances.0.is_empty() {
+                continue;
+            }
+            let Some(mesh) = meshes.get(mesh_handle) else {
+                continue;
+            };
+
+            let key = view_key | MeshPipelineKey::from_primitive_topology(mesh.primitive_topology);
+            let pipeline = match pipelines.specialize(
+                &pipeline_cache,
+                &building_pipeline,
+                key,
+                &mesh.layout,
+            ) {
+                Ok(pipeline) => pipeline,
+                Err(err) => {
+                    error!("Couldn't specialize the buildings pipeline: {}", err);
+                    continue;
+                }
+            };
+
+            opaque_phase.add(Opaque3d {
+                entity,
+                pipeline,
+                draw_function: draw_buildings,
+                distance: rangefinder.distance(&mesh_uniform.transform),
+            });
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct BuildingInstanceBuffer {
+    buffer: Buffer,
+    length: usize,
+}
+
+fn prepare_building_buffers(
+    mut commands: Commands,
+    instances_query: Query<(Entity, &BuildingInstances)>,
+    render_device: Res<RenderDevice>,
+) {
+    for (entity, instances) in instances_query.iter() {
+        if instances.0.is_empty() {
+            continue;
+        }
+
+        let buffer = render_device.create_buffer_with_data(&BufferInitDescriptor {
+            label: Some("building instance buffer"),
+            contents: bytemuck::cast_slice(instances.0.as_slice()),
+            usage: BufferUsages::VERTEX | BufferUsages::COPY_DST,
+        });
+        commands.entity(entity).insert(BuildingInstanceBuffer {
+            buffer,
+            length: instances.0.len(),
+        });
+    }
+}
+
+/// Brightness of the lights at night, shared by all the instances.
+#[derive(Clone, Copy, Pod, Zeroable)]
+#[repr(C)]
+struct NightLightingUniform {
+    /// Colour of the lit windows, with the strength of the glowing boxes in `w`.
+    window_light: Vec4,
+}
+
+#[derive(Resource)]
+pub struct NightLightingBindGroup(BindGroup);
+
+fn prepare_night_lighting(
+    mut commands: Commands,
+    night_lights: Res<NightLights>,
+    building_pipeline: Res<BuildingPipeline>,
+    render_device: Res<RenderDevice>,
+) {
+    let uniform = NightLightingUniform {
+        window_light: (WINDOW_LIGHT * night_lights.0).extend(GLOW_INTENSITY * night_lights.0),
+    };
+    let buffer = render_device.create_buffer_with_data(&BufferInitDescriptor {
+        label: Some("night lighting buffer"),
+        contents: bytemuck::bytes_of(&uniform),
+        usage: BufferUsages::UNIFORM,
+    });
+
+    let bind_group = render_device.create_bind_group(&BindGroupDescriptor {
+        label: Some("night lighting bind group"),
+        layout: &building_pipeline.night_lighting_layout,
+        entries: &[BindGroupEntry {
+            binding: 0,
+            resource: buffer.as_entire_binding(),
+        }],
+    });
+    commands.insert_resource(NightLightingBindGroup(bind_group));
+}
+
+#[derive(Resource)]
+pub struct BuildingPipeline {
+    mesh_pipeline: MeshPipeline,
+    night_lighting_layout: BindGroupLayout,
+}
+
+impl FromWorld for BuildingPipeline {
+    fn from_world(world: &mut World) -> Self {
+        let night_lighting_layout =
+            world
+                .resource::<RenderDevice>()
+                .create_bind_group_layout(&BindGroupLayoutDescriptor {
+                    label: Some("night lighting layout"),
+                    entries: &[BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: ShaderStages::FRAGMENT,
+                        ty: BindingType::Buffer {
+                            ty: BufferBindingType::Uniform,
+                            has_dynamic_offset: false,
+                            min_binding_size: BufferSize::new(std::mem::size_of::<
+                                NightLightingUniform,
+                            >()
+                                as u64),
+                        },
+                        count: None,
+                    }],
+                });
+
+        Self {
+            mesh_pipeline: world.resource::<MeshPipeline>().clone(),
+            night_lighting_layout,
+        }
+    }
+}
+
+impl SpecializedMeshPipeline for BuildingPipeline {
+    type Key = MeshPipelineKey;
+
+    fn specialize(
+        &self,
+        key: Self::Key,
+        layout: &MeshVertexBufferLayout,
+    ) -> Result<RenderPipelineDescriptor, SpecializedMeshPipelineError> {
+        let mut descriptor = self.mesh_pipeline.specialize(key, layout)?;
+        descriptor.layout.push(self.night_lighting_layout.clone());
+
+        // Without a material the mesh is bound in group 1 rather than 2
+        descriptor
+            .vertex
+            .shader_defs
+            .push("MESH_BINDGROUP_1".into());
+        descriptor.vertex.shader = BUILDINGS_SHADER_HANDLE.typed();
+        // Locations 0 to 2 are taken by the mesh's positions, normals and UVs
+        descriptor.vertex.buffers.push(VertexBufferLayout {
+            array_stride: std::mem::size_of::<BuildingInstance>() as u64,
+            step_mode: VertexStepMode::Instance,
+            attributes: vec![
+                VertexAttribute {
+                    format: VertexFormat::Float32x3,
+                    offset: 0,
+                    shader_location: 3,
+                },
+                VertexAttribute {
+                    format: VertexFormat::Float32x3,
+                    offset: VertexFormat::Float32x3.size(),
+                    shader_location: 4,
+                },
+                VertexAttribute {
+                    format: VertexFormat::Float32x4,
+                    offset: VertexFormat::Float32x3.size() * 2,
+                    shader_location: 5,
+                },
+                VertexAttribute {
+                    format: VertexFormat::Float32x2,
+                    offset: VertexFormat::Float32x3.size() * 2 + VertexFormat::Float32x4.size(),
+                    shader_location: 6,
+                },
+            ],
+        });
+
+        let fragment = descriptor.fragment.as_mut().unwrap();
+        fragment.shader_defs.push("MESH_BINDGROUP_1".into());
+        fragment.shader = BUILDINGS_SHADER_HANDLE.typed();
+
+        Ok(descriptor)
+    }
+}
+
+type DrawBuildings = (
+    SetItemPipeline,
+    SetMeshViewBindGroup<0>,
+    SetMeshBindGroup<1>,
+    SetNightLightingBindGroup<2>,
+    DrawBuildingInstances,
+);
+
+pub struct SetNightLightingBindGroup<const I: usize>;
+
+impl<P: PhaseItem, const I: usize> RenderCommand<P> for SetNightLightingBindGroup<I> {
+    type Param = Option<SRes<NightLightingBindGroup>>;
+    type ViewWorldQuery = ();
+    type ItemWorldQuery = ();
+
+    #[inline]
+    fn render<'w>(
+        _item: &P,
+        _view: (),
+        _entity: (),
+        bind_group: SystemParamItem<'w, '_, Self::Param>,
+        pass: &mut TrackedRenderPass<'w>,
+    ) -> RenderCommandResult {
+        let Some(bind_group) = bind_group else {
+            return RenderCommandResult::Failure;
+        };
+
+        pass.set_bind_group(I, &bind_group.into_inner().0, &[]);
+        RenderCommandResult::Success
+    }
+}
+
+pub struct DrawBuildingInstances;
+
+impl<P: PhaseItem> RenderCommand<P> for DrawBuildingInstances {
+    type Param = SRes<RenderAssets<Mesh>>;
+    type ViewWorldQuery = ();
+    type ItemWorldQuery = (Read<Handle<Mesh>>, Read<BuildingInstanceBuffer>);
+
+    #[inline]
+    fn render<'w>(
+        _item: &P,
+        _view: (),
+        (mesh_handle, instance_buffer): (&'w Handle<Mesh>, &'w BuildingInstanceBuffer),
+        meshes: SystemParamItem<'w, '_, Self::Param>,
+        pass: &mut TrackedRenderPass<'w>,
+    ) -> RenderCommandResult {
+        let Some(gpu_mesh) = meshes.into_inner().get(mesh_handle) else {
+            return RenderCommandResult::Failure;
+        };
+
+        pass.set_vertex_buffer(0, gpu_mesh.vertex_buffer.slice(..));
+        pass.set_vertex_buffer(1, instance_buffer.buffer.slice(..));
+
+        let instances = 0..instance_buffer.length as u32;
+        match &gpu_mesh.buffer_info {
+            GpuBufferInfo::Indexed {
+                buffer,
+                index_format,
+                count,
+            } => {
+                pass.set_index_buffer(buffer.slice(..), 0, *index_format);
+                pass.draw_indexed(0..*count, 0, instances);
+            }
+            GpuBufferInfo::NonIndexed => {
+                pass.draw(0..gpu_mesh.vertex_count, instances);
+            }
+        }
+        RenderCommandResult::Success
+    }
+}
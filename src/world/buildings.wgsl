@@ -0,0 +1,122 @@
+// Buildings drawn as instances of a unit box, each placed, sized and coloured by its instance
+// data, and lit like a `StandardMaterial`. At night their windows light up, and beacons glow.
+
+#import bevy_pbr::mesh_functions as mesh_functions
+#import bevy_pbr::mesh_bindings mesh
+#import bevy_pbr::mesh_view_bindings view, fog
+#import bevy_pbr::mesh_view_types FOG_MODE_OFF
+#import bevy_pbr::pbr_functions as pbr_functions
+#import bevy_core_pipeline::tonemapping screen_space_dither, powsafe, tone_mapping
+
+struct Vertex {
+    @location(0) position: vec3<f32>,
+    @location(1) normal: vec3<f32>,
+
+    @location(3) i_position: vec3<f32>,
+    @location(4) i_size: vec3<f32>,
+    @location(5) i_color: vec4<f32>,
+    // Windows in x and glow in y
+    @location(6) i_lights: vec2<f32>,
+};
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) world_position: vec4<f32>,
+    @location(1) world_normal: vec3<f32>,
+    @location(2) color: vec4<f32>,
+    // Position in metres from the centre of the box, and the box's own normal
+    @location(3) box_position: vec3<f32>,
+    @location(4) box_normal: vec3<f32>,
+    @location(5) size: vec3<f32>,
+    @location(6) instance_position: vec3<f32>,
+    @location(7) lights: vec2<f32>,
+};
+
+struct NightLighting {
+    // Colour of the lit windows, with the strength of glowing boxes in w
+    window_light: vec4<f32>,
+};
+
+@group(2) @binding(0)
+var<uniform> night: NightLighting;
+
+const WINDOW_WIDTH: f32 = 3.0;
+const FLOOR_HEIGHT: f32 = 3.5;
+// Share of the windows left dark
+const DARK_WINDOWS: f32 = 0.6;
+
+@vertex
+fn vertex(vertex: Vertex) -> VertexOutput {
+    let position = vertex.position * vertex.i_size + vertex.i_position;
+
+    var out: VertexOutput;
+    out.world_position = mesh_functions::mesh_position_local_to_world(mesh.model, vec4<f32>(position, 1.0));
+    out.clip_position = mesh_functions::mesh_position_world_to_clip(out.world_position);
+    // Faces of the box stay square to its axes when it's stretched, so the normals don't change
+    out.world_normal = mesh_functions::mesh_normal_local_to_world(vertex.normal);
+    out.color = vertex.i_color;
+    out.box_position = vertex.position * vertex.i_size;
+    out.box_normal = vertex.normal;
+    out.size = vertex.i_size;
+    out.instance_position = vertex.i_position;
+    out.lights = vertex.i_lights;
+    return out;
+}
+
+// Whether a point on a box is in a lit window, picked at random for each window of each wall.
+fn lit_window(in: VertexOutput) -> f32 {
+    // Only the walls have windows
+    if (abs(in.box_normal.y) > 0.5) {
+        return 0.0;
+    }
+
+    let across = select(in.box_position.x, in.box_position.z, abs(in.box_normal.x) > 0.5);
+    let height = in.box_position.y + in.size.y * 0.5;
+    let window = vec2<f32>(across / WINDOW_WIDTH, height / FLOOR_HEIGHT);
+    let within = fract(window);
+    if (any(within < vec2<f32>(0.2, 0.3)) || any(within > vec2<f32>(0.8, 0.8)) || height > in.size.y - 2.0) {
+        return 0.0;
+    }
+
+    // Whole metres of the box's position keep the pattern steady as the origin moves
+    let seed = dot(floor(window), vec2<f32>(12.9898, 78.233))
+        + dot(floor(in.instance_position.xz) % 1000.0, vec2<f32>(37.719, 4.581))
+        + dot(in.box_normal, vec3<f32>(1.0, 2.0, 3.0));
+    return step(DARK_WINDOWS, fract(sin(seed) * 43758.5453));
+}
+
+@fragment
+fn fragment(in: VertexOutput) -> @location(0) vec4<f32> {
+    var pbr_input = pbr_functions::pbr_input_new();
+    pbr_input.material.base_color = in.color;
+    pbr_input.frag_coord = in.clip_position;
+    pbr_input.world_position = in.world_position;
+    pbr_input.world_normal = normalize(in.world_normal);
+    pbr_input.N = pbr_input.world_normal;
+    pbr_input.is_orthographic = view.projection[3].w == 1.0;
+    pbr_input.V = pbr_functions::calculate_view(in.world_position, pbr_input.is_orthographic);
+    pbr_input.flags = mesh.flags;
+    pbr_input.material.emissive = vec4<f32>(
+        night.window_light.rgb * in.lights.x * lit_window(in) + in.color.rgb * in.lights.y * night.window_light.w,
+        1.0
+    );
+
+    var output_color = pbr_functions::pbr(pbr_input);
+
+    if (fog.mode != FOG_MODE_OFF) {
+        output_color = pbr_functions::apply_fog(fog, output_color, in.world_position.xyz, view.world_position.xyz);
+    }
+
+#ifdef TONEMAP_IN_SHADER
+    output_color = tone_mapping(output_color, view.color_grading);
+#ifdef DEBAND_DITHER
+    var output_rgb = output_color.rgb;
+    output_rgb = powsafe(output_rgb, 1.0 / 2.2);
+    output_rgb = output_rgb + screen_space_dither(in.clip_position.xy);
+    output_rgb = powsafe(output_rgb, 2.2);
+    output_color = vec4(output_rgb, output_color.a);
+#endif
+#endif
+
+    return output_color;
+}
@@ -0,0 +1,525 @@
+use bevy::{
+    math::vec2,
+    prelude::*,
+    render::{mesh::Indices, render_resource::PrimitiveTopology},
+};
+use bevy_rapier3d::prelude::*;
+use noise::NoiseFn;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use super::{
+    terrain::{in_river, terrain_height},
+    Airports, Rand, WorldOrigin, MAX_SIDE,
+};
+
+pub const BUILDING_COLOR: Color = Color::rgb(0.8, 0.7, 0.6);
+const TOWER_COLOR: Color = Color::rgb(0.55, 0.6, 0.7);
+const BEACON_COLOR: Color = Color::rgb(0.9, 0.1, 0.1);
+const TRUNK_COLOR: Color = Color::rgb(0.3, 0.2, 0.1);
+const CANOPY_COLOR: Color = Color::rgb(0.12, 0.3, 0.1);
+const POND_COLOR: Color = Color::rgb(0.1, 0.3, 0.45);
+const POLE_COLOR: Color = Color::rgb(0.25, 0.25, 0.27);
+const LAMP_COLOR: Color = Color::rgb(1.0, 0.85, 0.6);
+const VEHICLE_COLORS: [Color; 5] = [
+    Color::rgb(0.6, 0.05, 0.05),
+    Color::rgb(0.1, 0.2, 0.5),
+    Color::rgb(0.85, 0.85, 0.85),
+    Color::rgb(0.05, 0.05, 0.05),
+    Color::rgb(0.8, 0.65, 0.1),
+];
+const ROAD_COLOR: Color = Color::rgb(0.12, 0.12, 0.13);
+
+/// Band of the building noise just below where buildings start that's left as parkland, so parks
+/// fringe the built up areas however dense they are.
+const PARK_BAND: f64 = -0.15;
+/// One in this many built blocks has a landmark tower rather than a building.
+const TOWER_CHANCE: i32 = 60;
+/// Height of the shortest towers, which grow with the buildings around them.
+const TOWER_HEIGHT: f32 = 420.0;
+const TOWER_SIDE: f32 = 40.0;
+const SPIRE_HEIGHT: f32 = 60.0;
+/// One in this many parks has a pond in the middle.
+const POND_CHANCE: i32 = 3;
+const POND_SIZE: Vec2 = Vec2::new(60.0, 40.0);
+const TREES_PER_PARK: usize = 12;
+/// Depth trunks and poles are sunk into the ground, so they don't float where it slopes.
+const TRUNK_DEPTH: f32 = 3.0;
+/// Most trees planted between the buildings of a block.
+const MAX_STREET_TREES: usize = 3;
+/// Distance between the lamp posts along each side of a block.
+const LAMP_SPACING: f32 = 50.0;
+const LAMP_HEIGHT: f32 = 6.0;
+/// Most vehicles parked along the roads around a block.
+const MAX_VEHICLES: usize = 3;
+const VEHICLE_SIZE: Vec3 = Vec3::new(2.0, 1.5, 4.5);
+
+const ROAD_WIDTH: f32 = 12.0;
+/// Distance between the points a road follows the ground at, matching the terrain's vertices.
+const ROAD_STEP: f32 = 25.0;
+/// Height of the roads above the ground, with the roads running along X a little higher so they
+/// don't fight where they cross.
+const ROAD_LIFT: f32 = 0.5;
+const CROSS_ROAD_LIFT: f32 = 0.55;
+
+/// What's built on a block, picked from bands of the building noise.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BlockKind {
+    Building {
+        height: f32,
+    },
+    /// Landmark towering over the buildings around it, topped with a spire and a red beacon.
+    Tower {
+        height: f32,
+    },
+    /// Trees, around a pond in some.
+    Park {
+        pond: bool,
+    },
+}
+
+/// Box making up part of what's built on a block, relative to the block's entity.
+#[derive(Clone, Copy, Debug)]
+pub struct BlockBox {
+    pub centre: Vec3,
+    pub size: Vec3,
+    pub color: Color,
+    /// Whether it's part of the block's collider. Water is only drawn.
+    pub solid: bool,
+    pub lights: BoxLights,
+}
+
+/// How a box is lit up at night.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BoxLights {
+    None,
+    /// Lit windows scattered over its walls.
+    Windows,
+    /// Glows all over in its own colour, like an aircraft warning beacon.
+    Glow,
+}
+
+/// Boxes drawn for a block by the building instances.
+#[derive(Component, Clone)]
+pub struct BlockBoxes(pub Vec<BlockBox>);
+
+impl BlockBoxes {
+    /// Collider of the solid boxes, if there are any.
+    pub fn collider(&self) -> Option<Collider> {
+        let shapes: Vec<_> = self
+            .0
+            .iter()
+            .filter(|block_box| block_box.solid)
+            .map(|block_box| {
+                let half = block_box.size * 0.5;
+                (
+                    block_box.centre,
+                    Quat::IDENTITY,
+                    Collider::cuboid(half.x, half.y, half.z),
+                )
+            })
+            .collect();
+
+        (!shapes.is_empty()).then(|| Collider::compound(shapes))
+    }
+}
+
+impl BlockKind {
+    /// Height of the building or tower on the block, without any spire.
+    pub fn height(&self) -> Option<f32> {
+        match self {
+            Self::Building { height } | Self::Tower { height } => Some(*height),
+            Self::Park { .. } => None,
+        }
+    }
+
+    /// Side of the building or tower's square footprint.
+    pub fn side(&self) -> f32 {
+        match self {
+            Self::Tower { .. } => TOWER_SIDE,
+            _ => MAX_SIDE,
+        }
+    }
+
+    /// Height of the block's entity above the ground at the block's position. Buildings are placed
+    /// at their centre, so their roofs are half their height above it, and parks on the ground.
+    pub fn origin_height(&self) -> f32 {
+        self.height().map_or(0.0, |height| height * 0.5)
+    }
+
+    /// Boxes making up the block, relative to its entity, with blocks spaced `spacing` apart. Takes
+    /// the height of the ground at an offset from the block's position, relative to the ground at
+    /// the block's position.
+    pub fn boxes(
+        &self,
+        (x, z): (i32, i32),
+        spacing: i32,
+        ground: impl Fn(Vec2) -> f32,
+    ) -> Vec<BlockBox> {
+        match *self {
+            Self::Building { height } => vec![BlockBox {
+                centre: Vec3::ZERO,
+                size: Vec3::new(MAX_SIDE, height, MAX_SIDE),
+                color: BUILDING_COLOR,
+                solid: true,
+                lights: BoxLights::Windows,
+            }],
+            Self::Tower { height } => {
+                let roof = height * 0.5;
+                let crown = 20.0;
+                let solid_box = |centre, size, color, lights| BlockBox {
+                    centre,
+                    size,
+                    color,
+                    solid: true,
+                    lights,
+                };
+
+                vec![
+                    solid_box(
+                        Vec3::ZERO,
+                        Vec3::new(TOWER_SIDE, height, TOWER_SIDE),
+                        TOWER_COLOR,
+                        BoxLights::Windows,
+                    ),
+                    solid_box(
+                        Vec3::Y * (roof + crown * 0.5),
+                        Vec3::new(TOWER_SIDE * 0.7, crown, TOWER_SIDE * 0.7),
+                        TOWER_COLOR,
+                        BoxLights::None,
+                    ),
+                    solid_box(
+                        Vec3::Y * (roof + crown + SPIRE_HEIGHT * 0.5),
+                        Vec3::new(3.0, SPIRE_HEIGHT, 3.0),
+                        TOWER_COLOR,
+                        BoxLights::None,
+                    ),
+                    solid_box(
+                        Vec3::Y * (roof + crown + SPIRE_HEIGHT + 3.0),
+                        Vec3::splat(6.0),
+                        BEACON_COLOR,
+                        BoxLights::Glow,
+                    ),
+                ]
+            }
+            Self::Park { pond } => park_boxes((x, z), pond, spacing, ground),
+        }
+    }
+}
+
+impl BlockKind {
+    /// Trees between the buildings, lamp posts along the edges of the roads and vehicles parked on
+    /// them, relative to the block's entity like its other boxes. Only worth adding to blocks near
+    /// enough to be seen.
+    pub fn street_props(
+        &self,
+        (x, z): (i32, i32),
+        spacing: i32,
+        ground: impl Fn(Vec2) -> f32,
+    ) -> Vec<BlockBox> {
+        let mut boxes = Vec::new();
+        let half_spacing = spacing as f32 * 0.5;
+        // Heights are relative to the block's entity rather than the ground
+        let base = |offset| ground(offset) - self.origin_height();
+
+        let seed = x.wrapping_mul(67_867_967) ^ z.wrapping_mul(20_996_011);
+        let mut rng = StdRng::seed_from_u64(seed as u64);
+
+        // Parks have trees of their own
+        if self.height().is_some() {
+            let clearance = self.side() * 0.5 + 4.0;
+            let extent = half_spacing - ROAD_WIDTH - 3.0;
+            for _ in 0..rng.gen_range(0..=MAX_STREET_TREES) {
+                let offset = vec2(
+                    rng.gen_range(-extent..extent),
+                    rng.gen_range(-extent..extent),
+                );
+                if offset.abs().max_element() > clearance {
+                    let scale = rng.gen_range(0.6..1.0);
+                    boxes.extend(tree_boxes(offset, base(offset), scale));
+                }
+            }
+        }
+
+        // Each side of the block, outwards and along the road
+        let sides = [
+            (Vec2::X, Vec2::Y),
+            (Vec2::NEG_X, Vec2::Y),
+            (Vec2::Y, Vec2::X),
+            (Vec2::NEG_Y, Vec2::X),
+        ];
+
+        let kerb = half_spacing - ROAD_WIDTH * 0.5 - 1.5;
+        let lamps = (spacing as f32 / LAMP_SPACING) as usize;
+        for (out, along) in sides {
+            for i in 0..lamps {
+                let offset = out * kerb + along * ((i as f32 + 0.5) * LAMP_SPACING - half_spacing);
+                let ground_height = base(offset);
+                let head = Vec3::new(0.6 + out.x.abs() * 0.6, 0.3, 0.6 + out.y.abs() * 0.6);
+
+                boxes.push(BlockBox {
+                    centre: Vec3::new(
+                        offset.x,
+                        ground_height + (LAMP_HEIGHT - TRUNK_DEPTH) * 0.5,
+                        offset.y,
+                    ),
+                    size: Vec3::new(0.3, LAMP_HEIGHT + TRUNK_DEPTH, 0.3),
+                    color: POLE_COLOR,
+                    solid: true,
+                    lights: BoxLights::None,
+                });
+                boxes.push(BlockBox {
+                    centre: Vec3::new(
+                        offset.x + out.x * 0.6,
+                        ground_height + LAMP_HEIGHT,
+                        offset.y + out.y * 0.6,
+                    ),
+                    size: head,
+                    color: LAMP_COLOR,
+                    solid: false,
+                    lights: BoxLights::Glow,
+                });
+            }
+        }
+
+        // Parked in the lane on the block's side of the road, facing along it
+        let lane = half_spacing - ROAD_WIDTH * 0.25;
+        let reach = half_spacing - ROAD_WIDTH;
+        for _ in 0..rng.gen_range(0..=MAX_VEHICLES) {
+            let (out, along) = sides[rng.gen_range(0..sides.len())];
+            let offset = out * lane + along * rng.gen_range(-reach..reach);
+            let size = if along == Vec2::Y {
+                VEHICLE_SIZE
+            } else {
+                Vec3::new(VEHICLE_SIZE.z, VEHICLE_SIZE.y, VEHICLE_SIZE.x)
+            };
+
+            boxes.push(BlockBox {
+                centre: Vec3::new(
+                    offset.x,
+                    base(offset) + CROSS_ROAD_LIFT + size.y * 0.5,
+                    offset.y,
+                ),
+                size,
+                color: VEHICLE_COLORS[rng.gen_range(0..VEHICLE_COLORS.len())],
+                solid: true,
+                lights: BoxLights::None,
+            });
+        }
+
+        boxes
+    }
+}
+
+/// Trunk and canopy of a tree standing on the ground at `base`.
+fn tree_boxes(offset: Vec2, base: f32, scale: f32) -> [BlockBox; 2] {
+    let trunk_height = 6.0 * scale;
+    let canopy = 8.0 * scale;
+
+    [
+        BlockBox {
+            centre: Vec3::new(
+                offset.x,
+                base + (trunk_height - TRUNK_DEPTH) * 0.5,
+                offset.y,
+            ),
+            size: Vec3::new(1.5 * scale, trunk_height + TRUNK_DEPTH, 1.5 * scale),
+            color: TRUNK_COLOR,
+            solid: true,
+            lights: BoxLights::None,
+        },
+        BlockBox {
+            centre: Vec3::new(offset.x, base + trunk_height + canopy * 0.4, offset.y),
+            size: Vec3::splat(canopy),
+            color: CANOPY_COLOR,
+            solid: true,
+            lights: BoxLights::None,
+        },
+    ]
+}
+
+/// Trees scattered within the roads around a park, and its pond.
+fn park_boxes(
+    (x, z): (i32, i32),
+    pond: bool,
+    spacing: i32,
+    ground: impl Fn(Vec2) -> f32,
+) -> Vec<BlockBox> {
+    let mut boxes = Vec::new();
+    let half_pond = POND_SIZE * 0.5;
+
+    if pond {
+        // Water sits just above the lowest corner, so it's cut into the park where it slopes
+        let level = [vec2(-1., -1.), vec2(1., -1.), vec2(1., 1.), vec2(-1., 1.)]
+            .into_iter()
+            .map(|corner| ground(corner * half_pond))
+            .fold(f32::MAX, f32::min)
+            + 0.3;
+
+        boxes.push(BlockBox {
+            centre: Vec3::new(0., level - 2.0, 0.),
+            size: Vec3::new(POND_SIZE.x, 4.0, POND_SIZE.y),
+            color: POND_COLOR,
+            solid: false,
+            lights: BoxLights::None,
+        });
+    }
+
+    // Seeded by the block so the trees are the same each time it's generated, in either detail
+    let seed = x.wrapping_mul(49_979_687) ^ z.wrapping_mul(86_028_121);
+    let mut rng = StdRng::seed_from_u64(seed as u64);
+    let extent = spacing as f32 * 0.5 - ROAD_WIDTH - 5.0;
+
+    for _ in 0..TREES_PER_PARK {
+        let offset = vec2(
+            rng.gen_range(-extent..extent),
+            rng.gen_range(-extent..extent),
+        );
+        if pond && offset.abs().cmplt(half_pond + 5.0).all() {
+            continue;
+        }
+
+        let scale = rng.gen_range(0.7..1.3);
+        boxes.extend(tree_boxes(offset, ground(offset), scale));
+    }
+
+    boxes
+}
+
+/// Local ground position of a block and what's built on it, if anything.
+pub fn block_kind(
+    rand: &Rand,
+    airports: &Airports,
+    origin: &WorldOrigin,
+    (x, z): (i32, i32),
+) -> Option<(Vec2, BlockKind)> {
+    let config = &rand.config;
+
+    // Blocks are kept in absolute positions so they don't move when the origin does
+    let spacing = config.spacing;
+    let local_pos = origin.to_local(Vec3::new((x * spacing) as f32, 0., (z * spacing) as f32));
+    let ground_pos = vec2(local_pos.x, local_pos.z);
+
+    if airports.in_clear_zone(ground_pos) || in_river(&rand.terrain, origin, ground_pos) {
+        return None;
+    }
+
+    // Perlin always returns 0 for whole numbers so need to multiply by a coefficient to maker finer grained samplings
+    let n = rand.perlin.get([x as f64 * 0.2, z as f64 * 0.2]);
+    let hash = x.wrapping_mul(15_485_863) ^ z.wrapping_mul(32_452_843);
+
+    // Denser cities lower the noise that buildings start at, from the top of its range to the bottom
+    let threshold = 1.0 - 2.0 * config.density as f64;
+
+    let kind = if n > threshold {
+        let height = config.max_height * ((n - threshold) / (1.0 - threshold)) as f32;
+        if hash.rem_euclid(TOWER_CHANCE) == 0 {
+            BlockKind::Tower {
+                height: TOWER_HEIGHT + height * 0.5,
+            }
+        } else {
+            BlockKind::Building { height }
+        }
+    } else if n > threshold + PARK_BAND {
+        BlockKind::Park {
+            pond: hash.rem_euclid(POND_CHANCE) == 0,
+        }
+    } else {
+        return None;
+    };
+
+    Some((ground_pos, kind))
+}
+
+/// Builds the roads of a chunk, relative to its centre, around every block with something built on
+/// it. Each road runs the length of a block between it and its neighbour, and belongs to the chunk
+/// the blocks either side of it start in. Roads are left out where they'd cross a river or an
+/// airport.
+pub fn road_mesh(
+    rand: &Rand,
+    airports: &Airports,
+    origin: &WorldOrigin,
+    (cx, cz): (i32, i32),
+    centre: Vec2,
+) -> Option<Mesh> {
+    let mut positions = Vec::new();
+    let mut indices = Vec::new();
+    let chunk_blocks = rand.config.chunk_blocks();
+    let spacing = rand.config.spacing as f32;
+    let steps = (spacing / ROAD_STEP) as usize;
+
+    let built = |block| block_kind(rand, airports, origin, block).is_some();
+    let to_local = |absolute: Vec2| {
+        let local = origin.to_local(Vec3::new(absolute.x, 0., absolute.y));
+        vec2(local.x, local.z)
+    };
+
+    for a in cx * chunk_blocks..(cx + 1) * chunk_blocks {
+        for b in cz * chunk_blocks..(cz + 1) * chunk_blocks {
+            // Road along Z between blocks (a, b) and (a + 1, b), and along X between (b, a) and
+            // (b, a + 1), swapped so both are within the chunk
+            for (along, across, lift, blocks) in [
+                (Vec2::Y, Vec2::X, ROAD_LIFT, [(a, b), (a + 1, b)]),
+                (Vec2::X, Vec2::Y, CROSS_ROAD_LIFT, [(b, a), (b, a + 1)]),
+            ] {
+                if !blocks.into_iter().any(built) {
+                    continue;
+                }
+
+                // Overruns the block by half the road's width to fill the junctions
+                let line = (a as f32 + 0.5) * spacing;
+                let start = across * line + along * ((b as f32 - 0.5) * spacing - ROAD_WIDTH * 0.5);
+                let step = (spacing + ROAD_WIDTH) / steps as f32;
+                let points: Vec<_> = (0..=steps)
+                    .map(|i| to_local(start + along * step * i as f32))
+                    .collect();
+
+                if points.iter().any(|point| {
+                    airports.in_clear_zone(*point) || in_river(&rand.terrain, origin, *point)
+                }) {
+                    continue;
+                }
+
+                let first_index = positions.len() as u32;
+                let half_width = across * ROAD_WIDTH * 0.5;
+                for point in points {
+                    let height = terrain_height(&rand.terrain, airports, origin, point) + lift;
+                    for side in [point - half_width, point + half_width] {
+                        let local = side - centre;
+                        positions.push([local.x, height, local.y]);
+                    }
+                }
+
+                for i in 0..steps as u32 {
+                    let index = first_index + i * 2;
+                    // Wound to face up whichever way the road runs
+                    let quad = if along == Vec2::Y {
+                        [index, index + 2, index + 1, index + 1, index + 2, index + 3]
+                    } else {
+                        [index, index + 1, index + 2, index + 2, index + 1, index + 3]
+                    };
+                    indices.extend_from_slice(&quad);
+                }
+            }
+        }
+    }
+
+    if positions.is_empty() {
+        return None;
+    }
+
+    let normals = vec![[0., 1., 0.]; positions.len()];
+    let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
+    mesh.set_indices(Some(Indices::U32(indices)));
+    Some(mesh)
+}
+
+/// Material the roads are drawn with.
+pub fn road_material() -> StandardMaterial {
+    StandardMaterial {
+        base_color: ROAD_COLOR,
+        perceptual_roughness: 0.9,
+        ..default()
+    }
+}
@@ -0,0 +1,206 @@
+use bevy::{math::vec3, prelude::*, utils::HashMap};
+
+use crate::{camera::LookTarget, plane::Plane};
+
+use super::{Mission, WorldOrigin};
+
+/// Position of the plane at a time into a run, from when the clock started at the first ring.
+#[derive(Clone, Copy)]
+struct GhostFrame {
+    time: f32,
+    /// Absolute position, so runs still line up after the origin is rebased.
+    position: Vec3,
+    rotation: Quat,
+}
+
+/// Finished run through a course.
+struct GhostRun {
+    time: f32,
+    frames: Vec<GhostFrame>,
+}
+
+impl GhostRun {
+    /// Position and rotation at a time into the run, interpolated between the frames around it.
+    fn frame_at(&self, time: f32) -> Option<(Vec3, Quat)> {
+        let next = self.frames.partition_point(|frame| frame.time < time);
+        let after = self.frames.get(next).or(self.frames.last())?;
+        let before = next.checked_sub(1).map_or(after, |i| &self.frames[i]);
+
+        let span = after.time - before.time;
+        let t = if span > 0.0 {
+            ((time - before.time) / span).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+
+        Some((
+            before.position.lerp(after.position, t),
+            before.rotation.slerp(after.rotation, t),
+        ))
+    }
+}
+
+/// Run being recorded through the current course, and the fastest run through each course so far.
+#[derive(Resource, Default)]
+pub struct Ghosts {
+    recording: Vec<GhostFrame>,
+    best: HashMap<usize, GhostRun>,
+}
+
+impl Ghosts {
+    /// Time of the fastest finished run through a course.
+    pub fn best_time(&self, course: usize) -> Option<f32> {
+        self.best.get(&course).map(|run| run.time)
+    }
+}
+
+/// Translucent plane flying the best run through the course alongside the current attempt.
+#[derive(Component)]
+pub struct GhostPlane {
+    course: usize,
+    /// Time of the run it flies, to tell when a faster one replaces it.
+    run_time: f32,
+}
+
+#[derive(Resource)]
+pub struct GhostAssets {
+    fuselage: Handle<Mesh>,
+    wings: Handle<Mesh>,
+    tailplane: Handle<Mesh>,
+    fin: Handle<Mesh>,
+    material: Handle<StandardMaterial>,
+}
+
+pub fn setup_ghosts(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    commands.insert_resource(GhostAssets {
+        fuselage: meshes.add(Mesh::from(shape::Box::new(1.0, 1.2, 5.0))),
+        wings: meshes.add(Mesh::from(shape::Box::new(11.0, 0.2, 1.5))),
+        tailplane: meshes.add(Mesh::from(shape::Box::new(4.0, 0.2, 1.0))),
+        fin: meshes.add(Mesh::from(shape::Box::new(0.1, 1.5, 0.8))),
+        material: materials.add(StandardMaterial {
+            base_color: Color::rgba(0.6, 0.85, 1.0, 0.35),
+            alpha_mode: AlphaMode::Blend,
+            unlit: true,
+            ..default()
+        }),
+    });
+}
+
+/// Records the plane's path once the clock has started, keeping the run if it's the fastest
+/// through the course when it finishes.
+pub fn record_ghost(
+    mission: Res<Mission>,
+    time: Res<Time>,
+    origin: Res<WorldOrigin>,
+    plane_query: Query<&Transform, With<Plane>>,
+    mut ghosts: ResMut<Ghosts>,
+) {
+    let elapsed = mission.elapsed(time.elapsed_seconds());
+    if !mission.running() {
+        if let (true, Some(course)) = (mission.finished, mission.course) {
+            if !ghosts.recording.is_empty()
+                && ghosts.best_time(course).is_none_or(|best| elapsed < best)
+            {
+                info!("New best run through course in {:.1}s", elapsed);
+                let frames = std::mem::take(&mut ghosts.recording);
+                ghosts.best.insert(
+                    course,
+                    GhostRun {
+                        time: elapsed,
+                        frames,
+                    },
+                );
+            }
+        }
+        ghosts.recording.clear();
+        return;
+    }
+
+    if !mission.started() {
+        ghosts.recording.clear();
+        return;
+    }
+    let Ok(tx) = plane_query.get_single() else {
+        return;
+    };
+
+    ghosts.recording.push(GhostFrame {
+        time: elapsed,
+        position: origin.to_absolute(tx.translation),
+        rotation: tx.rotation,
+    });
+}
+
+/// Spawns a ghost for the course being flown if it has a best run, and moves it along the run in
+/// step with the mission clock. It waits at the start until the clock starts at the first ring,
+/// and disappears once the course is finished.
+pub fn update_ghost(
+    mut commands: Commands,
+    mission: Res<Mission>,
+    time: Res<Time>,
+    origin: Res<WorldOrigin>,
+    ghosts: Res<Ghosts>,
+    assets: Res<GhostAssets>,
+    mut ghost_query: Query<(Entity, &GhostPlane, &mut Transform, &mut Visibility)>,
+) {
+    let elapsed = mission.elapsed(time.elapsed_seconds());
+    let best = mission
+        .course
+        .and_then(|course| ghosts.best.get(&course).map(|run| (course, run)));
+
+    let mut current = false;
+    for (entity, ghost, mut tx, mut visibility) in ghost_query.iter_mut() {
+        let Some((_, run)) =
+            best.filter(|(course, run)| ghost.course == *course && ghost.run_time == run.time)
+        else {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        };
+        current = true;
+
+        if let Some((position, rotation)) = run.frame_at(elapsed) {
+            tx.translation = origin.to_local(position);
+            tx.rotation = rotation;
+        }
+        *visibility = if mission.finished {
+            Visibility::Hidden
+        } else {
+            Visibility::Inherited
+        };
+    }
+
+    let Some((course, run)) = best.filter(|_| !current && !mission.finished) else {
+        return;
+    };
+    let Some((position, rotation)) = run.frame_at(0.0) else {
+        return;
+    };
+
+    let part = |mesh: &Handle<Mesh>, translation: Vec3| PbrBundle {
+        mesh: mesh.clone(),
+        material: assets.material.clone(),
+        transform: Transform::from_translation(translation),
+        ..default()
+    };
+    commands
+        .spawn((
+            GhostPlane {
+                course,
+                run_time: run.time,
+            },
+            LookTarget("ghost"),
+            SpatialBundle::from_transform(
+                Transform::from_translation(origin.to_local(position)).with_rotation(rotation),
+            ),
+        ))
+        .with_children(|parent| {
+            parent.spawn(part(&assets.fuselage, Vec3::ZERO));
+            parent.spawn(part(&assets.wings, vec3(0., 0., -0.5)));
+            parent.spawn(part(&assets.tailplane, vec3(0., 0., 4.5)));
+            parent.spawn(part(&assets.fin, vec3(0., 0.75, 4.5)));
+        });
+}
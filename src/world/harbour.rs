@@ -0,0 +1,190 @@
+use bevy::{
+    math::{vec2, vec3},
+    prelude::*,
+};
+use bevy_rapier3d::prelude::*;
+
+use super::airport::{AirportSpec, CLEARANCE};
+
+/// Height of the water above the flattened ground, which planes touch down on.
+pub const WATER_LEVEL: f32 = 0.3;
+/// Spacing of the buoys along each side of the landing lane.
+const BUOY_SPACING: f32 = 100.0;
+const BUOY_RADIUS: f32 = 1.0;
+/// Distance between the piers, which reach out from the quay on the shore side of the lane.
+const PIER_SPACING: f32 = 150.0;
+const PIERS: usize = 3;
+const PIER_SIZE: Vec3 = vec3(50.0, 1.5, 4.0);
+const QUAY_SIZE: Vec3 = vec3(10.0, 1.5, 500.0);
+const BOAT_SIZE: Vec3 = vec3(10.0, 1.5, 3.0);
+const CABIN_SIZE: Vec3 = vec3(4.0, 1.5, 2.0);
+
+const BOAT_COLORS: [Color; 3] = [
+    Color::rgb(0.9, 0.9, 0.9),
+    Color::rgb(0.2, 0.3, 0.6),
+    Color::rgb(0.7, 0.2, 0.2),
+];
+
+/// Builds a harbour around a water lane, with the water filling the area kept clear around the lane
+/// and the quay along the edge of it on the lane's right hand side.
+pub fn build_harbour(
+    commands: &mut Commands,
+    meshes: &mut ResMut<Assets<Mesh>>,
+    materials: &mut ResMut<Assets<StandardMaterial>>,
+    airport: &AirportSpec,
+) {
+    let water_size = vec2(
+        airport.runway_width + CLEARANCE * 2.0,
+        airport.runway_length + CLEARANCE * 2.0,
+    );
+    let quay_x = water_size.x * 0.5;
+
+    let water_material = materials.add(StandardMaterial {
+        base_color: Color::rgba(0.1, 0.3, 0.45, 0.85),
+        alpha_mode: AlphaMode::Blend,
+        perceptual_roughness: 0.1,
+        ..default()
+    });
+    let wood_material = materials.add(Color::rgb(0.45, 0.35, 0.25).into());
+    let buoy_material = materials.add(StandardMaterial {
+        base_color: Color::YELLOW,
+        unlit: true,
+        ..default()
+    });
+
+    commands
+        .spawn((
+            Name::new(format!("{} harbour", airport.name)),
+            SpatialBundle::from_transform(
+                Transform::from_translation(airport.position).with_rotation(airport.rotation()),
+            ),
+            RigidBody::Fixed,
+        ))
+        .with_children(|parent| {
+            parent.spawn(PbrBundle {
+                mesh: meshes.add(Mesh::from(shape::Plane::from_size(1.0))),
+                material: water_material,
+                transform: Transform::from_xyz(0., WATER_LEVEL, 0.).with_scale(vec3(
+                    water_size.x,
+                    1.,
+                    water_size.y,
+                )),
+                ..default()
+            });
+
+            // Buoys marking both sides of the landing lane
+            let buoy_mesh = meshes.add(Mesh::from(shape::UVSphere {
+                radius: BUOY_RADIUS,
+                ..default()
+            }));
+            let buoys = (airport.runway_length / BUOY_SPACING) as i32;
+            for side in [-0.5, 0.5] {
+                for i in 0..=buoys {
+                    let z = (i as f32 / buoys as f32 - 0.5) * airport.runway_length;
+                    parent.spawn((
+                        PbrBundle {
+                            mesh: buoy_mesh.clone(),
+                            material: buoy_material.clone(),
+                            transform: Transform::from_xyz(
+                                airport.runway_width * side,
+                                WATER_LEVEL,
+                                z,
+                            ),
+                            ..default()
+                        },
+                        Collider::ball(BUOY_RADIUS),
+                    ));
+                }
+            }
+
+            parent.spawn((
+                PbrBundle {
+                    mesh: meshes.add(Mesh::from(shape::Box::new(
+                        QUAY_SIZE.x,
+                        QUAY_SIZE.y,
+                        QUAY_SIZE.z,
+                    ))),
+                    material: wood_material.clone(),
+                    transform: Transform::from_xyz(
+                        quay_x + QUAY_SIZE.x * 0.5,
+                        QUAY_SIZE.y * 0.5,
+                        0.,
+                    ),
+                    ..default()
+                },
+                Collider::cuboid(QUAY_SIZE.x * 0.5, QUAY_SIZE.y * 0.5, QUAY_SIZE.z * 0.5),
+            ));
+
+            let pier_mesh = meshes.add(Mesh::from(shape::Box::new(
+                PIER_SIZE.x,
+                PIER_SIZE.y,
+                PIER_SIZE.z,
+            )));
+            let hull_mesh = meshes.add(Mesh::from(shape::Box::new(
+                BOAT_SIZE.x,
+                BOAT_SIZE.y,
+                BOAT_SIZE.z,
+            )));
+            let cabin_mesh = meshes.add(Mesh::from(shape::Box::new(
+                CABIN_SIZE.x,
+                CABIN_SIZE.y,
+                CABIN_SIZE.z,
+            )));
+            let boat_materials: Vec<_> = BOAT_COLORS
+                .iter()
+                .map(|color| materials.add((*color).into()))
+                .collect();
+
+            for pier in 0..PIERS {
+                let z = (pier as f32 - (PIERS - 1) as f32 * 0.5) * PIER_SPACING;
+                let pier_x = quay_x - PIER_SIZE.x * 0.5;
+
+                parent.spawn((
+                    PbrBundle {
+                        mesh: pier_mesh.clone(),
+                        material: wood_material.clone(),
+                        transform: Transform::from_xyz(pier_x, PIER_SIZE.y * 0.5, z),
+                        ..default()
+                    },
+                    Collider::cuboid(PIER_SIZE.x * 0.5, PIER_SIZE.y * 0.5, PIER_SIZE.z * 0.5),
+                ));
+
+                // Boats moored either side of the pier, towards its seaward end
+                for (i, side) in [-1.0, 1.0].into_iter().enumerate() {
+                    let material = &boat_materials[(pier * 2 + i) % boat_materials.len()];
+                    let boat_pos = vec3(
+                        pier_x - PIER_SIZE.x * 0.25,
+                        WATER_LEVEL + BOAT_SIZE.y * 0.5,
+                        z + side * (PIER_SIZE.z + BOAT_SIZE.z) * 0.5,
+                    );
+
+                    parent
+                        .spawn((
+                            PbrBundle {
+                                mesh: hull_mesh.clone(),
+                                material: material.clone(),
+                                transform: Transform::from_translation(boat_pos),
+                                ..default()
+                            },
+                            Collider::cuboid(
+                                BOAT_SIZE.x * 0.5,
+                                BOAT_SIZE.y * 0.5,
+                                BOAT_SIZE.z * 0.5,
+                            ),
+                        ))
+                        .with_children(|boat| {
+                            boat.spawn(PbrBundle {
+                                mesh: cabin_mesh.clone(),
+                                material: boat_materials[0].clone(),
+                                transform: Transform::from_xyz(
+                                    0.,
+                                    (BOAT_SIZE.y + CABIN_SIZE.y) * 0.5,
+                                    0.,
+                                ),
+                                ..default()
+                            });
+                        });
+                }
+            }
+        });
+}
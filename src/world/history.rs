@@ -0,0 +1,207 @@
+use std::{collections::VecDeque, fs};
+
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::plane::{payload, spec::PayloadKind};
+
+use super::{WorldGenConfig, WorldOrigin};
+
+const HISTORY_PATH: &str = "world_history.ron";
+/// Most crates kept in each world, with the oldest cleared away to make room for new ones.
+const MAX_CRATES: usize = 200;
+
+/// Changes left in each world by flying in it, kept with the settings it's generated from, so
+/// they're put back whenever the same world is generated again, in this session or a later one.
+/// Dropped crates are the only changes kept, as nothing else in the world can be destroyed or
+/// built, so damage to buildings and the like would need recording here once it exists.
+#[derive(Resource, Default, Serialize, Deserialize)]
+pub struct WorldHistory {
+    worlds: Vec<WorldChanges>,
+    /// Entities put in the world for the current world's crates, in the same order.
+    #[serde(skip)]
+    spawned: VecDeque<Entity>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct WorldChanges {
+    /// Seed, density, spacing and height of the city the changes were made in, as any of them
+    /// moves the buildings.
+    config: WorldGenConfig,
+    /// Absolute positions and rotations of dropped crates where they came to rest.
+    crates: VecDeque<(Vec3, Quat)>,
+}
+
+impl WorldHistory {
+    /// Number of crates left in the world generated from a config.
+    pub fn crates(&self, config: &WorldGenConfig) -> usize {
+        WorldChanges::find(&self.worlds, config).map_or(0, |world| world.crates.len())
+    }
+}
+
+impl WorldChanges {
+    fn find<'a>(worlds: &'a [Self], config: &WorldGenConfig) -> Option<&'a Self> {
+        worlds.iter().find(|world| world.config == *config)
+    }
+
+    fn find_or_insert<'a>(worlds: &'a mut Vec<Self>, config: &WorldGenConfig) -> &'a mut Self {
+        match worlds.iter().position(|world| world.config == *config) {
+            Some(index) => &mut worlds[index],
+            None => {
+                worlds.push(Self {
+                    config: *config,
+                    crates: VecDeque::new(),
+                });
+                worlds.last_mut().unwrap()
+            }
+        }
+    }
+}
+
+/// Sent by a dropped crate once it has come to rest, with its transform, to leave it in the world.
+#[derive(Event)]
+pub struct CrateSettledEvent(pub Transform);
+
+/// Sent to clear everything left in the current world.
+#[derive(Event)]
+pub struct ClearWorldHistoryEvent;
+
+/// Crate fixed where it came to rest after being dropped, kept as part of the world.
+#[derive(Component)]
+pub struct SettledCrate;
+
+pub fn load_history() -> Option<WorldHistory> {
+    let contents = fs::read_to_string(HISTORY_PATH).ok()?;
+
+    match ron::from_str(&contents) {
+        Ok(history) => {
+            info!("Loaded world history from {}", HISTORY_PATH);
+            Some(history)
+        }
+        Err(err) => {
+            warn!(
+                "Ignoring invalid world history in {}: {}",
+                HISTORY_PATH, err
+            );
+            None
+        }
+    }
+}
+
+fn save_history(history: &WorldHistory) {
+    let result = ron::ser::to_string_pretty(history, ron::ser::PrettyConfig::default())
+        .map_err(|err| err.to_string())
+        .and_then(|contents| fs::write(HISTORY_PATH, contents).map_err(|err| err.to_string()));
+
+    match result {
+        Ok(()) => info!("Saved world history to {}", HISTORY_PATH),
+        Err(err) => warn!("Failed to save world history to {}: {}", HISTORY_PATH, err),
+    }
+}
+
+/// Puts back the crates left in the world generated from the current config, in place of any from
+/// before it was regenerated.
+pub fn restore_world_history(
+    mut commands: Commands,
+    mut history: ResMut<WorldHistory>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    config: Res<WorldGenConfig>,
+    origin: Res<WorldOrigin>,
+) {
+    let history = &mut *history;
+    for entity in history.spawned.drain(..) {
+        commands.entity(entity).despawn_recursive();
+    }
+    let Some(world) = WorldChanges::find(&history.worlds, &config) else {
+        return;
+    };
+
+    info!("Restoring {} crates left in the world", world.crates.len());
+    let mesh = meshes.add(payload::mesh(PayloadKind::Crate));
+    let material = materials.add(payload::color(PayloadKind::Crate).into());
+    for (translation, rotation) in world.crates.iter() {
+        let tx =
+            Transform::from_translation(origin.to_local(*translation)).with_rotation(*rotation);
+        let entity = spawn_crate(&mut commands, mesh.clone(), material.clone(), tx);
+        history.spawned.push_back(entity);
+    }
+}
+
+/// Fixes settled crates in place and adds them to the current world's history, clearing the
+/// oldest once there are too many.
+pub fn record_settled_crates(
+    mut commands: Commands,
+    mut settled_events: EventReader<CrateSettledEvent>,
+    mut history: ResMut<WorldHistory>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    config: Res<WorldGenConfig>,
+    origin: Res<WorldOrigin>,
+) {
+    if settled_events.is_empty() {
+        return;
+    }
+
+    let history = &mut *history;
+    let world = WorldChanges::find_or_insert(&mut history.worlds, &config);
+    let mesh = meshes.add(payload::mesh(PayloadKind::Crate));
+    let material = materials.add(payload::color(PayloadKind::Crate).into());
+    for CrateSettledEvent(tx) in settled_events.iter() {
+        world
+            .crates
+            .push_back((origin.to_absolute(tx.translation), tx.rotation));
+        let entity = spawn_crate(&mut commands, mesh.clone(), material.clone(), *tx);
+        history.spawned.push_back(entity);
+
+        if world.crates.len() > MAX_CRATES {
+            world.crates.pop_front();
+            if let Some(oldest) = history.spawned.pop_front() {
+                commands.entity(oldest).despawn_recursive();
+            }
+        }
+    }
+
+    save_history(history);
+}
+
+pub fn clear_world_history(
+    mut commands: Commands,
+    mut clear_events: EventReader<ClearWorldHistoryEvent>,
+    mut history: ResMut<WorldHistory>,
+    config: Res<WorldGenConfig>,
+) {
+    if clear_events.is_empty() {
+        return;
+    }
+    clear_events.clear();
+
+    info!("Clearing the history of world {:?}", *config);
+    for entity in history.spawned.drain(..) {
+        commands.entity(entity).despawn_recursive();
+    }
+    history.worlds.retain(|world| world.config != *config);
+    save_history(&history);
+}
+
+fn spawn_crate(
+    commands: &mut Commands,
+    mesh: Handle<Mesh>,
+    material: Handle<StandardMaterial>,
+    transform: Transform,
+) -> Entity {
+    commands
+        .spawn((
+            SettledCrate,
+            PbrBundle {
+                mesh,
+                material,
+                transform,
+                ..default()
+            },
+            RigidBody::Fixed,
+            payload::collider(PayloadKind::Crate),
+        ))
+        .id()
+}
@@ -0,0 +1,134 @@
+use bevy::{prelude::*, render::extract_resource::ExtractResource};
+
+use super::{airport::AirportSpec, TimeOfDay};
+
+/// Sun elevation in degrees below which lights start coming on, and the further drop over which
+/// they fade up to full brightness.
+const LIGHTS_ON_ELEVATION: f32 = 6.0;
+const LIGHTS_FADE: f32 = 8.0;
+
+const EDGE_LIGHT_COLOR: Color = Color::rgb(1.0, 0.9, 0.7);
+const THRESHOLD_LIGHT_COLOR: Color = Color::rgb(0.1, 1.0, 0.3);
+const END_LIGHT_COLOR: Color = Color::rgb(1.0, 0.1, 0.05);
+/// Emissive strength of the runway lights at full brightness, bright enough to pick out the
+/// runway from the approach.
+const RUNWAY_LIGHT_INTENSITY: f32 = 8.0;
+const LIGHT_SIZE: Vec3 = Vec3::new(0.6, 0.4, 0.6);
+/// Distance between the lights along each edge of the runway.
+const EDGE_LIGHT_SPACING: f32 = 60.0;
+/// Distance between the lights across the ends of the runway.
+const END_LIGHT_SPACING: f32 = 3.0;
+/// Bars of approach lights before the threshold, kept within the flattened ground around the
+/// runway.
+const APPROACH_BARS: usize = 3;
+const APPROACH_BAR_SPACING: f32 = 30.0;
+const APPROACH_BAR_WIDTH: f32 = 15.0;
+
+/// How far the lights of the city and airports are turned up, from `0` by day to `1` at night.
+#[derive(Resource, Clone, Copy, Default, PartialEq, ExtractResource)]
+pub struct NightLights(pub f32);
+
+/// Materials shared by all the runway lights of each colour, so they can be turned up and down
+/// together.
+#[derive(Resource)]
+pub struct RunwayLightMaterials {
+    mesh: Handle<Mesh>,
+    edge: Handle<StandardMaterial>,
+    threshold: Handle<StandardMaterial>,
+    end: Handle<StandardMaterial>,
+}
+
+impl RunwayLightMaterials {
+    pub fn new(meshes: &mut Assets<Mesh>, materials: &mut Assets<StandardMaterial>) -> Self {
+        let mut light_material = |color: Color| {
+            materials.add(StandardMaterial {
+                base_color: color * 0.3,
+                emissive: Color::BLACK,
+                ..default()
+            })
+        };
+
+        Self {
+            mesh: meshes.add(Mesh::from(shape::Box::new(
+                LIGHT_SIZE.x,
+                LIGHT_SIZE.y,
+                LIGHT_SIZE.z,
+            ))),
+            edge: light_material(EDGE_LIGHT_COLOR),
+            threshold: light_material(THRESHOLD_LIGHT_COLOR),
+            end: light_material(END_LIGHT_COLOR),
+        }
+    }
+}
+
+pub fn update_night_lights(time_of_day: Res<TimeOfDay>, mut night_lights: ResMut<NightLights>) {
+    let elevation = time_of_day.sun_elevation().sin().asin().to_degrees();
+    let level = ((LIGHTS_ON_ELEVATION - elevation) / LIGHTS_FADE).clamp(0.0, 1.0);
+
+    night_lights.set_if_neq(NightLights(level));
+}
+
+pub fn update_runway_lights(
+    night_lights: Res<NightLights>,
+    runway_lights: Option<Res<RunwayLightMaterials>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let Some(runway_lights) = runway_lights else {
+        return;
+    };
+
+    for (handle, color) in [
+        (&runway_lights.edge, EDGE_LIGHT_COLOR),
+        (&runway_lights.threshold, THRESHOLD_LIGHT_COLOR),
+        (&runway_lights.end, END_LIGHT_COLOR),
+    ] {
+        if let Some(material) = materials.get_mut(handle) {
+            material.emissive = color * night_lights.0 * RUNWAY_LIGHT_INTENSITY;
+        }
+    }
+}
+
+/// Builds the lights of a runway as children of it, with `top` the height of its surface. Edge
+/// lights run down both sides, green threshold lights cross the +Z end it's approached from, with
+/// bars of approach lights out in front, and red lights cross the far end.
+pub fn build_runway_lights(
+    parent: &mut ChildBuilder<'_, '_, '_>,
+    assets: &RunwayLightMaterials,
+    airport: &AirportSpec,
+    top: f32,
+) {
+    let y = top + LIGHT_SIZE.y * 0.5;
+    let half_width = airport.runway_width * 0.5 + 1.0;
+    let half_length = airport.runway_length * 0.5;
+    let mut spawn_light = |material: &Handle<StandardMaterial>, x: f32, z: f32| {
+        parent.spawn(PbrBundle {
+            mesh: assets.mesh.clone(),
+            material: material.clone(),
+            transform: Transform::from_xyz(x, y, z),
+            ..default()
+        });
+    };
+
+    let edge_lights = (airport.runway_length / EDGE_LIGHT_SPACING) as usize;
+    for i in 0..=edge_lights {
+        let z = -half_length + i as f32 * airport.runway_length / edge_lights as f32;
+        spawn_light(&assets.edge, -half_width, z);
+        spawn_light(&assets.edge, half_width, z);
+    }
+
+    let end_lights = (half_width * 2.0 / END_LIGHT_SPACING) as usize;
+    for i in 0..=end_lights {
+        let x = -half_width + i as f32 * half_width * 2.0 / end_lights as f32;
+        spawn_light(&assets.threshold, x, half_length);
+        spawn_light(&assets.end, x, -half_length);
+    }
+
+    let bar_lights = (APPROACH_BAR_WIDTH / END_LIGHT_SPACING) as usize;
+    for bar in 1..=APPROACH_BARS {
+        let z = half_length + bar as f32 * APPROACH_BAR_SPACING;
+        for i in 0..=bar_lights {
+            let x = (i as f32 / bar_lights as f32 - 0.5) * APPROACH_BAR_WIDTH;
+            spawn_light(&assets.edge, x, z);
+        }
+    }
+}
@@ -0,0 +1,308 @@
+use bevy::{
+    math::vec2,
+    pbr::NotShadowCaster,
+    prelude::*,
+    render::{mesh::Indices, render_resource::PrimitiveTopology},
+    tasks::{AsyncComputeTaskPool, Task},
+    utils::HashMap,
+};
+use futures_lite::future;
+use noise::Perlin;
+
+use crate::camera::Follow;
+
+use super::{
+    city::block_kind,
+    terrain::{
+        chunk_centre, terrain_color, terrain_height, ACTIVE_CHUNK_DISTANCE, CHUNK_BLOCKS,
+        CHUNK_SIZE, RIVER_LEVEL,
+    },
+    Airports, BlockPos, Rand, WorldOrigin,
+};
+
+/// Chunks either side of the followed entity's chunk that are drawn, beyond the full detail ones,
+/// as a single low detail mesh each.
+const FAR_CHUNK_DISTANCE: i32 = 6;
+/// Number of vertices along each side of a low detail chunk's terrain.
+const LOD_RESOLUTION: usize = 17;
+const WATER_COLOR: Vec3 = Vec3::new(0.1, 0.3, 0.45);
+
+/// Distant chunk drawn as one mesh of its terrain and blocks, without colliders.
+#[derive(Component)]
+pub struct LodChunk;
+
+/// Mesh built up from quads of flat coloured triangles.
+#[derive(Default)]
+struct LodMeshBuilder {
+    positions: Vec<[f32; 3]>,
+    normals: Vec<[f32; 3]>,
+    colors: Vec<[f32; 4]>,
+    indices: Vec<u32>,
+}
+
+impl LodMeshBuilder {
+    fn push_vertex(&mut self, position: Vec3, normal: Vec3, color: [f32; 4]) {
+        self.positions.push(position.to_array());
+        self.normals.push(normal.to_array());
+        self.colors.push(color);
+    }
+
+    /// Terrain of the chunk, with the river channels filled with water.
+    fn push_terrain(&mut self, heights: &[Vec<f32>]) {
+        let cell_size = CHUNK_SIZE / (LOD_RESOLUTION - 1) as f32;
+        let half_size = CHUNK_SIZE * 0.5;
+        let last = LOD_RESOLUTION - 1;
+        let first_index = self.positions.len() as u32;
+
+        for z in 0..LOD_RESOLUTION {
+            for x in 0..LOD_RESOLUTION {
+                let height = heights[x][z];
+                let (height, color) = if height < RIVER_LEVEL {
+                    (RIVER_LEVEL, WATER_COLOR)
+                } else {
+                    (height, terrain_color(height))
+                };
+
+                let dx = heights[(x + 1).min(last)][z] - heights[x.saturating_sub(1)][z];
+                let dz = heights[x][(z + 1).min(last)] - heights[x][z.saturating_sub(1)];
+
+                self.push_vertex(
+                    Vec3::new(
+                        x as f32 * cell_size - half_size,
+                        height,
+                        z as f32 * cell_size - half_size,
+                    ),
+                    Vec3::new(-dx, 2.0 * cell_size, -dz).normalize(),
+                    [color.x, color.y, color.z, 1.0],
+                );
+            }
+        }
+
+        let index = |x: usize, z: usize| first_index + (z * LOD_RESOLUTION + x) as u32;
+        for z in 0..last {
+            for x in 0..last {
+                self.indices.extend_from_slice(&[
+                    index(x, z),
+                    index(x, z + 1),
+                    index(x + 1, z),
+                    index(x + 1, z),
+                    index(x, z + 1),
+                    index(x + 1, z + 1),
+                ]);
+            }
+        }
+    }
+
+    /// Box of a building, tower or tree, without the bottom face that's never seen.
+    fn push_box(&mut self, centre: Vec3, size: Vec3, color: Color) {
+        let color = color.as_linear_rgba_f32();
+        let half = size * 0.5;
+
+        // Each face's axes, in the order that winds it to face outwards
+        for (normal, u, v) in [
+            (Vec3::X, Vec3::Y, Vec3::Z),
+            (Vec3::NEG_X, Vec3::Z, Vec3::Y),
+            (Vec3::Y, Vec3::Z, Vec3::X),
+            (Vec3::Z, Vec3::X, Vec3::Y),
+            (Vec3::NEG_Z, Vec3::Y, Vec3::X),
+        ] {
+            let face_centre = centre + normal * half;
+            let (u, v) = (u * half, v * half);
+            let first_index = self.positions.len() as u32;
+
+            for corner in [-u - v, u - v, u + v, -u + v] {
+                self.push_vertex(face_centre + corner, normal, color);
+            }
+            self.indices.extend(
+                [0, 1, 2, 0, 2, 3]
+                    .into_iter()
+                    .map(|index| first_index + index),
+            );
+        }
+    }
+
+    fn build(self) -> Mesh {
+        let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
+        mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, self.positions);
+        mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, self.normals);
+        mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, self.colors);
+        mesh.set_indices(Some(Indices::U32(self.indices)));
+        mesh
+    }
+}
+
+/// Builds the low detail mesh of a chunk, relative to its centre. Takes its own copies of the world
+/// so it can run as a task.
+fn generate_lod_chunk(
+    rand: &Rand,
+    airports: &Airports,
+    origin: &WorldOrigin,
+    (x, z): (i32, i32),
+) -> Mesh {
+    let centre = chunk_centre(origin, (x, z));
+    let mut builder = LodMeshBuilder::default();
+
+    builder.push_terrain(&lod_heights(&rand.terrain, airports, origin, centre));
+
+    let chunk_blocks = rand.config.chunk_blocks();
+    for block_z in z * chunk_blocks..(z + 1) * chunk_blocks {
+        for block_x in x * chunk_blocks..(x + 1) * chunk_blocks {
+            let block = (block_x, block_z);
+            let Some((ground_pos, kind)) = block_kind(rand, airports, origin, block) else {
+                continue;
+            };
+
+            let ground = |pos| terrain_height(&rand.terrain, airports, origin, pos);
+            let ground_height = ground(ground_pos);
+            let local_pos = ground_pos - centre;
+            let position = Vec3::new(
+                local_pos.x,
+                ground_height + kind.origin_height(),
+                local_pos.y,
+            );
+
+            let boxes = kind.boxes(block, rand.config.spacing, |offset| {
+                ground(ground_pos + offset) - ground_height
+            });
+            for block_box in boxes {
+                builder.push_box(position + block_box.centre, block_box.size, block_box.color);
+            }
+        }
+    }
+
+    builder.build()
+}
+
+/// Samples the terrain heights for a low detail chunk, indexed by `[x][z]`.
+fn lod_heights(
+    perlin: &Perlin,
+    airports: &Airports,
+    origin: &WorldOrigin,
+    centre: Vec2,
+) -> Vec<Vec<f32>> {
+    let cell_size = CHUNK_SIZE / (LOD_RESOLUTION - 1) as f32;
+    let corner = centre - Vec2::splat(CHUNK_SIZE * 0.5);
+
+    (0..LOD_RESOLUTION)
+        .map(|x| {
+            (0..LOD_RESOLUTION)
+                .map(|z| {
+                    let pos = corner + vec2(x as f32, z as f32) * cell_size;
+                    terrain_height(perlin, airports, origin, pos)
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Keeps the ring of chunks beyond the full detail terrain and buildings drawn in low detail,
+/// swapping each for its full detail terrain and buildings as it comes within range. When the world
+/// is reseeded each chunk is kept until its replacement has been generated.
+#[allow(clippy::too_many_arguments)]
+pub fn stream_lod_chunks(
+    mut commands: Commands,
+    query: Query<Ref<BlockPos>, With<Follow>>,
+    mut chunk_entities: Local<HashMap<(i32, i32), Entity>>,
+    mut chunk_tasks: Local<HashMap<(i32, i32), Task<Mesh>>>,
+    mut material: Local<Option<Handle<StandardMaterial>>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    rand: Res<Rand>,
+    airports: Res<Airports>,
+    origin: Res<WorldOrigin>,
+) {
+    if let Some(block_pos) = query
+        .get_single()
+        .ok()
+        .filter(|block_pos| block_pos.is_changed() || rand.is_changed())
+    {
+        let BlockPos(px, pz) = *block_pos;
+        let cx = px.div_euclid(CHUNK_BLOCKS);
+        let cz = pz.div_euclid(CHUNK_BLOCKS);
+
+        let in_range = |(x, z): (i32, i32)| {
+            let distance = (x - cx).abs().max((z - cz).abs());
+            distance > ACTIVE_CHUNK_DISTANCE && distance <= FAR_CHUNK_DISTANCE
+        };
+
+        let old_chunks: Vec<_> = chunk_entities
+            .keys()
+            .filter(|chunk| !in_range(**chunk))
+            .copied()
+            .collect();
+
+        for chunk in old_chunks {
+            if let Some(entity) = chunk_entities.remove(&chunk) {
+                commands.entity(entity).despawn_recursive();
+            }
+        }
+        // Dropping a task cancels it
+        chunk_tasks.retain(|chunk, _| in_range(*chunk));
+        if rand.is_changed() {
+            chunk_tasks.clear();
+        }
+
+        let task_pool = AsyncComputeTaskPool::get();
+        for z in (cz - FAR_CHUNK_DISTANCE)..=(cz + FAR_CHUNK_DISTANCE) {
+            for x in (cx - FAR_CHUNK_DISTANCE)..=(cx + FAR_CHUNK_DISTANCE) {
+                if !in_range((x, z))
+                    || (chunk_entities.contains_key(&(x, z)) && !rand.is_changed())
+                    || chunk_tasks.contains_key(&(x, z))
+                {
+                    continue;
+                }
+
+                let rand = *rand;
+                let airports = airports.clone();
+                let origin = WorldOrigin(origin.0);
+                let task = task_pool
+                    .spawn(async move { generate_lod_chunk(&rand, &airports, &origin, (x, z)) });
+                chunk_tasks.insert((x, z), task);
+            }
+        }
+    }
+
+    let generated: Vec<_> = chunk_tasks
+        .iter_mut()
+        .filter_map(|(chunk, task)| {
+            future::block_on(future::poll_once(task)).map(|mesh| (*chunk, mesh))
+        })
+        .collect();
+    if generated.is_empty() {
+        return;
+    }
+
+    let material = material
+        .get_or_insert_with(|| {
+            materials.add(StandardMaterial {
+                perceptual_roughness: 1.0,
+                ..default()
+            })
+        })
+        .clone();
+
+    for (chunk, mesh) in generated {
+        chunk_tasks.remove(&chunk);
+
+        // Placed by the origin as it is now, in case it was rebased while the chunk was generated
+        let centre = chunk_centre(&origin, chunk);
+
+        let entity = commands
+            .spawn((
+                LodChunk,
+                PbrBundle {
+                    mesh: meshes.add(mesh),
+                    material: material.clone(),
+                    transform: Transform::from_xyz(centre.x, 0., centre.y),
+                    ..default()
+                },
+                // Too far away for their shadows to be seen
+                NotShadowCaster,
+            ))
+            .id();
+
+        if let Some(old_entity) = chunk_entities.insert(chunk, entity) {
+            commands.entity(old_entity).despawn_recursive();
+        }
+    }
+}
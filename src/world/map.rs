@@ -0,0 +1,290 @@
+use bevy::{
+    math::{vec2, vec3},
+    prelude::*,
+    utils::HashSet,
+};
+use leafwing_input_manager::prelude::*;
+
+use crate::{canvas::Canvas, input::PlaneAction, plane::Plane};
+
+use super::{
+    airport::{RunwaySurface, CLEARANCE},
+    city::{block_kind, BlockKind},
+    terrain::{terrain_color, terrain_height, CHUNK_SIZE, RIVER_LEVEL},
+    Airports, Rand, WorldOrigin,
+};
+
+const MAP_PATH: &str = "map.png";
+/// Scale of the map, made coarser for large explored areas to keep within the largest size.
+const METRES_PER_PIXEL: f32 = 10.0;
+const MAX_MAP_SIZE: f32 = 4096.0;
+/// Distance between grid lines at the finest scale, labelled in kilometres.
+const GRID_SPACING: f32 = 1000.0;
+/// Size of each pixel of the label font.
+const LABEL_SCALE: u32 = 2;
+
+const UNEXPLORED_COLOR: [u8; 4] = [20, 20, 24, 255];
+const WATER_COLOR: Color = Color::rgb_linear(0.1, 0.3, 0.45);
+const RUNWAY_COLOR: [u8; 4] = [70, 70, 70, 255];
+const LANE_COLOR: [u8; 4] = [90, 150, 200, 255];
+const PLANE_COLOR: [u8; 4] = [255, 0, 0, 255];
+const GRID_COLOR: [u8; 4] = [255, 255, 255, 255];
+const LOW_BUILDING_COLOR: Vec3 = Vec3::new(0.55, 0.45, 0.35);
+const HIGH_BUILDING_COLOR: Vec3 = Vec3::new(0.95, 0.9, 0.85);
+const TOWER_COLOR: [u8; 4] = [220, 40, 40, 255];
+const PARK_COLOR: [u8; 4] = [40, 110, 40, 255];
+/// Size of the park squares, out to the roads around them.
+const PARK_SIDE: f32 = 170.0;
+
+/// Terrain chunks generated so far, by their absolute position in chunks.
+#[derive(Resource, Default)]
+pub struct ExploredChunks(pub HashSet<(i32, i32)>);
+
+/// Top down map image with north up, covering an area of the world in absolute positions.
+struct MapImage {
+    canvas: Canvas,
+    /// Absolute position of the top left corner.
+    corner: Vec2,
+    metres_per_pixel: f32,
+}
+
+impl MapImage {
+    fn new(corner: Vec2, size: Vec2) -> Self {
+        let metres_per_pixel = METRES_PER_PIXEL.max(size.max_element() / MAX_MAP_SIZE);
+        let width = (size.x / metres_per_pixel).ceil() as u32;
+        let height = (size.y / metres_per_pixel).ceil() as u32;
+
+        Self {
+            canvas: Canvas::new(width, height, UNEXPLORED_COLOR),
+            corner,
+            metres_per_pixel,
+        }
+    }
+
+    /// Absolute position of the middle of a pixel.
+    fn position(&self, x: u32, y: u32) -> Vec2 {
+        self.corner + (vec2(x as f32, y as f32) + 0.5) * self.metres_per_pixel
+    }
+
+    /// Pixel coordinates of an absolute position, which may be off the map.
+    fn pixel(&self, pos: Vec2) -> Vec2 {
+        (pos - self.corner) / self.metres_per_pixel
+    }
+
+    /// Fills a square around an absolute position.
+    fn fill_square(&mut self, centre: Vec2, side: f32, color: [u8; 4]) {
+        let min = self.pixel(centre - side * 0.5);
+        let max = self.pixel(centre + side * 0.5);
+        self.canvas.fill_rect(min, max, color);
+    }
+
+    /// Fills a rectangle of a width across and length along a heading, around an absolute position.
+    fn fill_rotated(&mut self, centre: Vec2, heading: f32, size: Vec2, color: [u8; 4]) {
+        let rotation = Quat::from_rotation_y(heading).inverse();
+        let radius = size.length() * 0.5;
+        let min = self.pixel(centre - radius).floor().max(Vec2::ZERO);
+        let max = self
+            .pixel(centre + radius)
+            .ceil()
+            .min(vec2(self.canvas.width as f32, self.canvas.height as f32));
+
+        for y in min.y as u32..max.y as u32 {
+            for x in min.x as u32..max.x as u32 {
+                let offset = self.position(x, y) - centre;
+                let local = rotation * vec3(offset.x, 0., offset.y);
+                if local.x.abs() <= size.x * 0.5 && local.z.abs() <= size.y * 0.5 {
+                    self.canvas.set(x as i64, y as i64, color);
+                }
+            }
+        }
+    }
+}
+
+/// Saves a map of the explored chunks when asked.
+pub fn export_map(
+    action_query: Query<&ActionState<PlaneAction>>,
+    plane_query: Query<&GlobalTransform, With<Plane>>,
+    explored_chunks: Res<ExploredChunks>,
+    rand: Res<Rand>,
+    airports: Res<Airports>,
+    origin: Res<WorldOrigin>,
+) {
+    let Ok(action_state) = action_query.get_single() else {
+        return;
+    };
+    if !action_state.just_pressed(PlaneAction::ExportMap) {
+        return;
+    }
+    if explored_chunks.0.is_empty() {
+        warn!("Nothing explored to map");
+        return;
+    }
+
+    let plane_pos = plane_query
+        .get_single()
+        .ok()
+        .map(|global_tx| origin.to_absolute(global_tx.translation()));
+    let map = render_map(&explored_chunks, &rand, &airports, &origin, plane_pos);
+
+    match map.canvas.save(MAP_PATH) {
+        Ok(()) => info!("Saved map to {}", MAP_PATH),
+        Err(err) => warn!("Failed to save map to {}: {}", MAP_PATH, err),
+    }
+}
+
+fn render_map(
+    explored_chunks: &ExploredChunks,
+    rand: &Rand,
+    airports: &Airports,
+    origin: &WorldOrigin,
+    plane_pos: Option<Vec3>,
+) -> MapImage {
+    let (min, max) =
+        explored_chunks
+            .0
+            .iter()
+            .fold((IVec2::MAX, IVec2::MIN), |(min, max), (x, z)| {
+                let chunk = IVec2::new(*x, *z);
+                (min.min(chunk), max.max(chunk))
+            });
+    let corner = min.as_vec2() * CHUNK_SIZE;
+    let size = (max - min + 1).as_vec2() * CHUNK_SIZE;
+    let mut map = MapImage::new(corner, size);
+
+    info!(
+        "Rendering {}x{} map at {} m per pixel",
+        map.canvas.width, map.canvas.height, map.metres_per_pixel
+    );
+
+    // Heights of the explored ground, with a border to shade the edges from
+    let to_local = |pos: Vec2| {
+        let local = origin.to_local(vec3(pos.x, 0., pos.y));
+        vec2(local.x, local.z)
+    };
+    let explored = |pos: Vec2| {
+        let chunk = (pos / CHUNK_SIZE).floor();
+        explored_chunks
+            .0
+            .contains(&(chunk.x as i32, chunk.y as i32))
+    };
+    let stride = map.canvas.width as usize + 2;
+    let heights: Vec<f32> = (0..map.canvas.height as i64 + 2)
+        .flat_map(|y| (0..map.canvas.width as i64 + 2).map(move |x| (x, y)))
+        .map(|(x, y)| {
+            let pos = map.corner + (vec2(x as f32, y as f32) - 0.5) * map.metres_per_pixel;
+            terrain_height(&rand.terrain, airports, origin, to_local(pos))
+        })
+        .collect();
+
+    // Hill shading lit from the north west
+    let light = Vec3::new(-1., 1., -1.).normalize();
+    for y in 0..map.canvas.height {
+        for x in 0..map.canvas.width {
+            if !explored(map.position(x, y)) {
+                continue;
+            }
+
+            let index = (y as usize + 1) * stride + x as usize + 1;
+            let height = heights[index];
+            let color = if height < RIVER_LEVEL {
+                WATER_COLOR.as_rgba_u8()
+            } else {
+                let dx = heights[index + 1] - heights[index - 1];
+                let dz = heights[index + stride] - heights[index - stride];
+                let normal = Vec3::new(-dx, 2.0 * map.metres_per_pixel, -dz).normalize();
+                let shade = normal.dot(light).clamp(0.3, 1.0) / light.y;
+                let color = terrain_color(height) * shade;
+                Color::rgb_linear(color.x, color.y, color.z).as_rgba_u8()
+            };
+            map.canvas.set(x as i64, y as i64, color);
+        }
+    }
+
+    // Buildings on each block of the explored chunks, brighter the taller they are, with the
+    // landmark towers picked out in red and the parks in green
+    let chunk_blocks = rand.config.chunk_blocks();
+    for (cx, cz) in explored_chunks.0.iter() {
+        for bz in cz * chunk_blocks..(cz + 1) * chunk_blocks {
+            for bx in cx * chunk_blocks..(cx + 1) * chunk_blocks {
+                let Some((ground_pos, kind)) = block_kind(rand, airports, origin, (bx, bz)) else {
+                    continue;
+                };
+                let pos = origin.to_absolute(vec3(ground_pos.x, 0., ground_pos.y));
+                let (side, color) = match kind {
+                    BlockKind::Building { height } => {
+                        let color = LOW_BUILDING_COLOR
+                            .lerp(HIGH_BUILDING_COLOR, height / rand.config.max_height);
+                        (
+                            kind.side(),
+                            Color::rgb(color.x, color.y, color.z).as_rgba_u8(),
+                        )
+                    }
+                    BlockKind::Tower { .. } => (kind.side(), TOWER_COLOR),
+                    BlockKind::Park { .. } => (PARK_SIDE, PARK_COLOR),
+                };
+                map.fill_square(vec2(pos.x, pos.z), side, color);
+            }
+        }
+    }
+
+    for airport in airports.0.iter() {
+        let pos = origin.to_absolute(airport.position);
+        let pos = vec2(pos.x, pos.z);
+        let runway = vec2(airport.runway_width, airport.runway_length);
+
+        match airport.surface {
+            RunwaySurface::Asphalt => {
+                map.fill_rotated(pos, airport.heading, runway, RUNWAY_COLOR);
+            }
+            RunwaySurface::Water => {
+                let water = runway + CLEARANCE * 2.0;
+                map.fill_rotated(pos, airport.heading, water, WATER_COLOR.as_rgba_u8());
+                map.fill_rotated(pos, airport.heading, runway, LANE_COLOR);
+            }
+        }
+    }
+
+    // Grid lines with their absolute positions in kilometres, stronger through the world origin
+    let grid_spacing = GRID_SPACING * (map.metres_per_pixel / METRES_PER_PIXEL).ceil();
+    let first = (map.corner / grid_spacing).ceil();
+    let last = ((map.corner + size) / grid_spacing).floor();
+
+    for i in first.x as i32..=last.x as i32 {
+        let x = map.pixel(vec2(i as f32 * grid_spacing, 0.)).x as u32;
+        if x >= map.canvas.width {
+            continue;
+        }
+        let alpha = if i == 0 { 0.7 } else { 0.3 };
+        for y in 0..map.canvas.height {
+            map.canvas.blend(x, y, GRID_COLOR, alpha);
+        }
+        let km = (i as f32 * grid_spacing / 1000.0) as i32;
+        map.canvas
+            .draw_text(x + 3, 3, &km.to_string(), LABEL_SCALE, GRID_COLOR);
+    }
+    for i in first.y as i32..=last.y as i32 {
+        let y = map.pixel(vec2(0., i as f32 * grid_spacing)).y as u32;
+        if y >= map.canvas.height {
+            continue;
+        }
+        let alpha = if i == 0 { 0.7 } else { 0.3 };
+        for x in 0..map.canvas.width {
+            map.canvas.blend(x, y, GRID_COLOR, alpha);
+        }
+        let km = (i as f32 * grid_spacing / 1000.0) as i32;
+        map.canvas
+            .draw_text(3, y + 3, &km.to_string(), LABEL_SCALE, GRID_COLOR);
+    }
+
+    if let Some(pos) = plane_pos {
+        let pixel = map.pixel(vec2(pos.x, pos.z));
+        for dy in -2..=2 {
+            for dx in -2..=2 {
+                map.canvas
+                    .set(pixel.x as i64 + dx, pixel.y as i64 + dy, PLANE_COLOR);
+            }
+        }
+    }
+
+    map
+}
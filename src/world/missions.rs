@@ -0,0 +1,328 @@
+use bevy::{
+    math::{vec2, vec3},
+    prelude::*,
+};
+use bevy_rapier3d::prelude::*;
+
+use crate::plane::Plane;
+
+use super::{terrain::terrain_height, Airports, AirshowVenue, Rand, WorldOrigin, SPACING};
+
+const RING_RADIUS: f32 = 15.0;
+const RING_THICKNESS: f32 = 1.5;
+/// Half the depth of the sensor filling each ring.
+const SENSOR_DEPTH: f32 = 1.0;
+/// Points for each ring passed, before the display box multiplier.
+const RING_POINTS: f32 = 100.0;
+/// Points for each second a course is finished under par.
+const TIME_BONUS: f32 = 20.0;
+
+#[derive(Event)]
+pub struct StartMissionEvent(pub usize);
+
+#[derive(Event)]
+pub struct AbortMissionEvent;
+
+/// A course of rings to fly through in order.
+pub struct Course {
+    pub name: String,
+    pub rings: Vec<Transform>,
+    /// Time in seconds to beat for a bonus.
+    pub par_time: f32,
+}
+
+/// Courses laid out around the airports and the airshow venue.
+#[derive(Resource)]
+pub struct Courses(pub Vec<Course>);
+
+impl FromWorld for Courses {
+    fn from_world(world: &mut World) -> Self {
+        let airports = world.resource::<Airports>();
+        let rand = world.resource::<Rand>();
+        let venue = world.resource::<AirshowVenue>();
+        let origin = world.resource::<WorldOrigin>();
+
+        // Ring positions are given as an offset and a height above the terrain
+        let above_ground = |start: Transform, points: &[Vec3]| -> Vec<Vec3> {
+            points
+                .iter()
+                .map(|point| {
+                    let pos = start.transform_point(vec3(point.x, 0., point.z));
+                    let ground =
+                        terrain_height(&rand.terrain, airports, origin, vec2(pos.x, pos.z));
+                    vec3(pos.x, ground + point.y, pos.z)
+                })
+                .collect()
+        };
+
+        let threshold = airports.home().threshold();
+        let circuit = above_ground(
+            threshold,
+            &[
+                vec3(0., 60., -900.),
+                vec3(0., 150., -1600.),
+                vec3(-500., 250., -2000.),
+                vec3(-1000., 300., -1500.),
+                vec3(-1000., 300., 0.),
+                vec3(-700., 200., 1200.),
+                vec3(0., 100., 1500.),
+                vec3(0., 40., 600.),
+            ],
+        );
+
+        // Down the street running north from the home runway, between the blocks of buildings
+        let street = Transform::from_xyz(SPACING as f32 * 0.5, 0., -1100.);
+        let main_street = above_ground(
+            street,
+            &(0..10)
+                .map(|i| vec3(0., if i % 2 == 0 { 40. } else { 120. }, i as f32 * -400.))
+                .collect::<Vec<_>>(),
+        );
+
+        let display_line =
+            Transform::from_translation(venue.position).with_rotation(venue.rotation);
+        let airshow = above_ground(
+            display_line,
+            &[
+                vec3(400., 80., 450.),
+                vec3(400., 150., 150.),
+                vec3(400., 250., -150.),
+                vec3(400., 150., -450.),
+            ],
+        );
+
+        Self(vec![
+            Course::new("Circuit", circuit, 120.0),
+            Course::new("Main street", main_street, 60.0),
+            Course::new("Airshow pass", airshow, 40.0),
+        ])
+    }
+}
+
+impl Course {
+    /// Builds a course from ring positions, each ring facing along the path to the next.
+    fn new(name: &str, points: Vec<Vec3>, par_time: f32) -> Self {
+        let rings = points
+            .iter()
+            .enumerate()
+            .map(|(i, pos)| {
+                let dir = match (points.get(i + 1), i.checked_sub(1).map(|j| points[j])) {
+                    (Some(next), _) => *next - *pos,
+                    (None, Some(prev)) => *pos - prev,
+                    (None, None) => Vec3::NEG_Z,
+                };
+
+                // Ring meshes and sensors have their axis along Y
+                Transform::from_translation(*pos)
+                    .with_rotation(Quat::from_rotation_arc(Vec3::Y, dir.normalize_or_zero()))
+            })
+            .collect();
+
+        Self {
+            name: name.to_string(),
+            rings,
+            par_time,
+        }
+    }
+}
+
+/// Progress through the current course.
+#[derive(Resource, Default)]
+pub struct Mission {
+    pub course: Option<usize>,
+    pub next_ring: usize,
+    pub ring_count: usize,
+    /// Time the first ring was passed, which starts the clock.
+    started: Option<f32>,
+    /// Time from the first ring to the last one passed, the course's time once it's finished.
+    elapsed: f32,
+    pub finished: bool,
+    pub score: f32,
+}
+
+impl Mission {
+    pub fn running(&self) -> bool {
+        self.course.is_some() && !self.finished
+    }
+
+    /// Whether the clock has started, which it does at the first ring.
+    pub fn started(&self) -> bool {
+        self.started.is_some()
+    }
+
+    /// Time on the clock at `now` in seconds of game time, worked out where it's read rather than
+    /// written into the resource every frame, which would mark it changed every frame.
+    pub fn elapsed(&self, now: f32) -> f32 {
+        match self.started {
+            Some(started) if self.running() => now - started,
+            _ => self.elapsed,
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct Checkpoint(pub usize);
+
+#[derive(Resource)]
+pub struct MissionAssets {
+    ring_mesh: Handle<Mesh>,
+    next_material: Handle<StandardMaterial>,
+    ring_material: Handle<StandardMaterial>,
+}
+
+pub fn setup_missions(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    commands.insert_resource(MissionAssets {
+        ring_mesh: meshes.add(Mesh::from(shape::Torus {
+            radius: RING_RADIUS,
+            ring_radius: RING_THICKNESS,
+            ..default()
+        })),
+        next_material: materials.add(StandardMaterial {
+            base_color: Color::LIME_GREEN,
+            unlit: true,
+            ..default()
+        }),
+        ring_material: materials.add(StandardMaterial {
+            base_color: Color::GOLD,
+            unlit: true,
+            ..default()
+        }),
+    });
+}
+
+pub fn start_mission(
+    mut commands: Commands,
+    mut start_events: EventReader<StartMissionEvent>,
+    mut abort_events: EventReader<AbortMissionEvent>,
+    courses: Res<Courses>,
+    assets: Res<MissionAssets>,
+    mut mission: ResMut<Mission>,
+    checkpoint_query: Query<Entity, With<Checkpoint>>,
+) {
+    let start = start_events.iter().last().map(|StartMissionEvent(i)| *i);
+    let abort = abort_events.iter().count() > 0;
+    if start.is_none() && !abort {
+        return;
+    }
+
+    for entity in checkpoint_query.iter() {
+        commands.entity(entity).despawn();
+    }
+    *mission = Mission::default();
+
+    let Some((index, course)) = start.and_then(|i| courses.0.get(i).map(|course| (i, course)))
+    else {
+        info!("Mission aborted");
+        return;
+    };
+
+    info!(
+        "Starting course {} with {} rings",
+        course.name,
+        course.rings.len()
+    );
+
+    for (i, tx) in course.rings.iter().enumerate() {
+        commands.spawn((
+            Checkpoint(i),
+            PbrBundle {
+                mesh: assets.ring_mesh.clone(),
+                material: if i == 0 {
+                    assets.next_material.clone()
+                } else {
+                    assets.ring_material.clone()
+                },
+                transform: *tx,
+                ..default()
+            },
+            Collider::cylinder(SENSOR_DEPTH, RING_RADIUS - RING_THICKNESS),
+            Sensor,
+        ));
+    }
+
+    mission.course = Some(index);
+    mission.ring_count = course.rings.len();
+}
+
+/// Counts rings passed in order using their sensors. Runs each physics step so no events are missed.
+#[allow(clippy::too_many_arguments)]
+pub fn track_checkpoints(
+    time: Res<Time>,
+    mut collision_events: EventReader<CollisionEvent>,
+    mut mission: ResMut<Mission>,
+    courses: Res<Courses>,
+    venue: Res<AirshowVenue>,
+    checkpoint_query: Query<(&Checkpoint, &GlobalTransform)>,
+    parent_query: Query<&Parent>,
+    plane_query: Query<(), With<Plane>>,
+) {
+    for event in collision_events.iter() {
+        let CollisionEvent::Started(e1, e2, _) = event else {
+            continue;
+        };
+
+        for (sensor, other) in [(*e1, *e2), (*e2, *e1)] {
+            let Ok((Checkpoint(index), ring_tx)) = checkpoint_query.get(sensor) else {
+                continue;
+            };
+            if !mission.running() || *index != mission.next_ring {
+                continue;
+            }
+            if !std::iter::once(other)
+                .chain(parent_query.iter_ancestors(other))
+                .any(|entity| plane_query.contains(entity))
+            {
+                continue;
+            }
+
+            let now = time.elapsed_seconds();
+            let started = *mission.started.get_or_insert(now);
+            mission.elapsed = now - started;
+            mission.score += RING_POINTS * venue.score_multiplier(ring_tx.translation());
+            mission.next_ring += 1;
+            info!(
+                "Passed ring {} of {}",
+                mission.next_ring, mission.ring_count
+            );
+
+            if mission.next_ring == mission.ring_count {
+                let par_time = mission
+                    .course
+                    .and_then(|i| courses.0.get(i))
+                    .map(|course| course.par_time)
+                    .unwrap_or_default();
+
+                mission.finished = true;
+                mission.score += (par_time - mission.elapsed).max(0.0) * TIME_BONUS;
+                info!(
+                    "Course finished in {:.1}s scoring {:.0}",
+                    mission.elapsed, mission.score
+                );
+            }
+        }
+    }
+}
+
+/// Highlights the next ring and hides those already passed.
+pub fn update_checkpoints(
+    mission: Res<Mission>,
+    assets: Res<MissionAssets>,
+    mut checkpoint_query: Query<(&Checkpoint, &mut Handle<StandardMaterial>, &mut Visibility)>,
+) {
+    for (Checkpoint(index), mut material, mut visibility) in checkpoint_query.iter_mut() {
+        *visibility = if *index < mission.next_ring {
+            Visibility::Hidden
+        } else {
+            Visibility::Inherited
+        };
+        *material = if *index == mission.next_ring {
+            assets.next_material.clone()
+        } else {
+            assets.ring_material.clone()
+        };
+    }
+}
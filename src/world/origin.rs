@@ -0,0 +1,96 @@
+use bevy::{core_pipeline::core_2d::Camera2d, math::ivec2, prelude::*};
+
+use crate::camera::Follow;
+
+use super::{Airports, AirshowVenue, Courses, SPACING};
+
+/// Horizontal distance of the followed plane from the origin that triggers a rebase.
+const REBASE_DISTANCE: f32 = 4000.0;
+
+/// Offset in building blocks of the origin used for transforms from the absolute world origin.
+///
+/// Transforms are kept near the origin so `f32` positions stay precise on long flights. Anything
+/// that must be the same wherever the origin is, like terrain noise and building placement, works
+/// in absolute positions.
+#[derive(Resource, Default)]
+pub struct WorldOrigin(pub IVec2);
+
+impl WorldOrigin {
+    /// Absolute position of the local origin.
+    pub fn offset(&self) -> Vec3 {
+        Vec3::new((self.0.x * SPACING) as f32, 0., (self.0.y * SPACING) as f32)
+    }
+
+    pub fn to_absolute(&self, local: Vec3) -> Vec3 {
+        local + self.offset()
+    }
+
+    pub fn to_local(&self, absolute: Vec3) -> Vec3 {
+        absolute - self.offset()
+    }
+}
+
+/// Sent with the amount everything was moved by when the origin is rebased, for positions held
+/// outside of transforms.
+#[derive(Event)]
+pub struct OriginShiftedEvent(pub Vec3);
+
+/// Moves the world back towards the origin once the followed plane strays too far from it.
+#[allow(clippy::type_complexity)]
+pub fn rebase_origin(
+    mut origin: ResMut<WorldOrigin>,
+    mut airports: ResMut<Airports>,
+    mut venue: ResMut<AirshowVenue>,
+    mut courses: ResMut<Courses>,
+    mut shifted_events: EventWriter<OriginShiftedEvent>,
+    follow_query: Query<&Transform, (With<Follow>, Without<Parent>)>,
+    // The HUD camera's screen space sprites and text, such as the indicators and the overlay
+    // fading the screen under g, aren't part of the world
+    mut root_query: Query<
+        &mut Transform,
+        (
+            Without<Parent>,
+            Without<Camera2d>,
+            Without<Text>,
+            Without<Sprite>,
+        ),
+    >,
+) {
+    let Ok(follow_tx) = follow_query.get_single() else {
+        return;
+    };
+    let pos = follow_tx.translation;
+    if Vec2::new(pos.x, pos.z).length() < REBASE_DISTANCE {
+        return;
+    }
+
+    // Shift by whole blocks so building and terrain grids line up the same either side of a rebase
+    let blocks = ivec2(
+        (pos.x / SPACING as f32).round() as i32,
+        (pos.z / SPACING as f32).round() as i32,
+    );
+    let shift = Vec3::new((blocks.x * SPACING) as f32, 0., (blocks.y * SPACING) as f32);
+
+    info!(
+        "Rebasing origin by {} to {}",
+        shift,
+        origin.offset() + shift
+    );
+
+    origin.0 += blocks;
+
+    for mut tx in root_query.iter_mut() {
+        tx.translation -= shift;
+    }
+    for airport in airports.0.iter_mut() {
+        airport.position -= shift;
+    }
+    venue.position -= shift;
+    for course in courses.0.iter_mut() {
+        for ring in course.rings.iter_mut() {
+            ring.translation -= shift;
+        }
+    }
+
+    shifted_events.send(OriginShiftedEvent(shift));
+}
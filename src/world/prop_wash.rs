@@ -0,0 +1,280 @@
+use bevy::{math::vec3, prelude::*};
+use rand::Rng;
+
+use crate::{
+    flight_model::Thrust,
+    plane::{spec::PlaneSpec, Plane, Propellor},
+};
+
+use super::{
+    terrain::terrain_height, Airports, OriginShiftedEvent, Rand, RunwaySurface, WorldOrigin,
+};
+
+/// Height of the propellor above the terrain beyond which no wash reaches the ground.
+const MAX_WASH_HEIGHT: f32 = 6.0;
+/// Wash strength below which no particles are kicked up.
+const MIN_WASH_STRENGTH: f32 = 0.2;
+/// Dust particles emitted per second at full wash strength.
+const DUST_RATE: f32 = 120.0;
+const DUST_LIFETIME: f32 = 1.5;
+const DUST_SPEED: f32 = 12.0;
+/// Length and half-angle of the cone behind the propellor in which grass is flattened.
+const WASH_LENGTH: f32 = 25.0;
+const WASH_HALF_ANGLE: f32 = 0.35;
+const MAX_GRASS_BEND: f32 = 1.2;
+const GRASS_RECOVERY_RATE: f32 = 2.0;
+const GRASS_STRIP_WIDTH: f32 = 30.0;
+const GRASS_TUFTS_PER_SIDE: usize = 600;
+/// Offset from the root of a grass tuft to the centre of its mesh.
+const GRASS_CENTRE: Vec3 = vec3(0., 0.25, 0.);
+
+#[derive(Resource)]
+pub struct PropWashAssets {
+    dust_mesh: Handle<Mesh>,
+    dust_material: Handle<StandardMaterial>,
+}
+
+#[derive(Component)]
+pub struct DustParticle {
+    velocity: Vec3,
+    age: f32,
+}
+
+#[derive(Component)]
+pub struct GrassTuft {
+    /// Position of the root of the tuft which it bends about.
+    base: Vec3,
+    /// Current bend away from vertical in radians.
+    bend: f32,
+    axis: Vec3,
+}
+
+pub fn setup_prop_wash(
+    mut commands: Commands,
+    airports: Res<Airports>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    commands.insert_resource(PropWashAssets {
+        dust_mesh: meshes.add(Mesh::from(shape::Cube { size: 0.3 })),
+        dust_material: materials.add(StandardMaterial {
+            base_color: Color::rgb(0.55, 0.48, 0.35),
+            unlit: true,
+            ..default()
+        }),
+    });
+
+    let grass_mesh = meshes.add(Mesh::from(shape::Box::new(0.08, 0.5, 0.08)));
+    let grass_material = materials.add(Color::rgb(0.3, 0.5, 0.15).into());
+    let mut rng = rand::thread_rng();
+
+    // Scatter grass along both sides of each runway where planes are most likely to be close to the ground
+    for airport in airports
+        .0
+        .iter()
+        .filter(|airport| airport.surface == RunwaySurface::Asphalt)
+    {
+        for side in [-1.0, 1.0] {
+            for _ in 0..GRASS_TUFTS_PER_SIDE {
+                let x = side * (airport.runway_width * 0.5 + rng.gen_range(1.0..GRASS_STRIP_WIDTH));
+                let z = rng.gen_range(-0.5..0.5) * airport.runway_length;
+                let base = airport.position + airport.rotation() * vec3(x, 0., z);
+
+                commands.spawn((
+                    GrassTuft {
+                        base,
+                        bend: 0.0,
+                        axis: Vec3::X,
+                    },
+                    PbrBundle {
+                        mesh: grass_mesh.clone(),
+                        material: grass_material.clone(),
+                        transform: Transform::from_translation(base + GRASS_CENTRE),
+                        ..default()
+                    },
+                ));
+            }
+        }
+    }
+}
+
+/// Strength of the wash reaching the ground in the range `0..1`, with the ground point below the propellor.
+fn wash_at_ground(
+    propellor_pos: Vec3,
+    thrust: f32,
+    spec: &PlaneSpec,
+    rand: &Rand,
+    airports: &Airports,
+    origin: &WorldOrigin,
+) -> (f32, Vec3) {
+    let ground_y = terrain_height(
+        &rand.terrain,
+        airports,
+        origin,
+        Vec2::new(propellor_pos.x, propellor_pos.z),
+    );
+    let height = propellor_pos.y - ground_y;
+    let power = (thrust.abs() / spec.thrust).clamp(0.0, 1.0);
+    let strength = power * (1.0 - height / MAX_WASH_HEIGHT).clamp(0.0, 1.0);
+
+    (strength, vec3(propellor_pos.x, ground_y, propellor_pos.z))
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn emit_dust(
+    mut commands: Commands,
+    time: Res<Time>,
+    rand: Res<Rand>,
+    airports: Res<Airports>,
+    origin: Res<WorldOrigin>,
+    assets: Res<PropWashAssets>,
+    plane_query: Query<(&Thrust, &PlaneSpec), With<Plane>>,
+    propellor_query: Query<&GlobalTransform, With<Propellor>>,
+    mut carry: Local<f32>,
+) {
+    let Ok((Thrust(thrust), spec)) = plane_query.get_single() else {
+        return;
+    };
+    let Ok(propellor_tx) = propellor_query.get_single() else {
+        return;
+    };
+
+    let (strength, ground_pos) = wash_at_ground(
+        propellor_tx.translation(),
+        *thrust,
+        spec,
+        &rand,
+        &airports,
+        &origin,
+    );
+    if strength < MIN_WASH_STRENGTH {
+        *carry = 0.0;
+        return;
+    }
+
+    // Accumulate fractional particles so low frame times still emit at the right rate
+    *carry += DUST_RATE * strength * time.delta_seconds();
+    let count = carry.floor();
+    *carry -= count;
+
+    let back = propellor_tx.back() * Vec3::new(1., 0., 1.);
+    let back = back.try_normalize().unwrap_or(Vec3::Z);
+    let mut rng = rand::thread_rng();
+
+    for _ in 0..count as usize {
+        let spread = Quat::from_rotation_y(rng.gen_range(-WASH_HALF_ANGLE..WASH_HALF_ANGLE));
+        let velocity = spread * back * DUST_SPEED * strength * rng.gen_range(0.5..1.0)
+            + Vec3::Y * rng.gen_range(0.5..2.0);
+
+        commands.spawn((
+            DustParticle { velocity, age: 0.0 },
+            PbrBundle {
+                mesh: assets.dust_mesh.clone(),
+                material: assets.dust_material.clone(),
+                transform: Transform::from_translation(
+                    ground_pos + back * rng.gen_range(1.0..3.0) + Vec3::Y * 0.2,
+                ),
+                ..default()
+            },
+        ));
+    }
+}
+
+pub fn update_dust(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut particle_query: Query<(Entity, &mut DustParticle, &mut Transform)>,
+) {
+    let dt = time.delta_seconds();
+
+    for (entity, mut particle, mut tx) in particle_query.iter_mut() {
+        particle.age += dt;
+        if particle.age > DUST_LIFETIME {
+            commands.entity(entity).despawn();
+            continue;
+        }
+
+        // Air drag slows the dust while it slowly settles
+        particle.velocity *= 1.0 - (1.5 * dt).min(1.0);
+        particle.velocity.y -= 0.5 * dt;
+        tx.translation += particle.velocity * dt;
+
+        let life = particle.age / DUST_LIFETIME;
+        tx.scale = Vec3::splat(1.0 + life * 3.0) * (1.0 - life);
+    }
+}
+
+pub fn flatten_grass(
+    time: Res<Time>,
+    rand: Res<Rand>,
+    airports: Res<Airports>,
+    origin: Res<WorldOrigin>,
+    plane_query: Query<(&Thrust, &PlaneSpec), With<Plane>>,
+    propellor_query: Query<&GlobalTransform, With<Propellor>>,
+    mut grass_query: Query<(&mut GrassTuft, &mut Transform)>,
+) {
+    let wash = plane_query
+        .get_single()
+        .ok()
+        .zip(propellor_query.get_single().ok())
+        .map(|((Thrust(thrust), spec), propellor_tx)| {
+            let (strength, ground_pos) = wash_at_ground(
+                propellor_tx.translation(),
+                *thrust,
+                spec,
+                &rand,
+                &airports,
+                &origin,
+            );
+            let back = (propellor_tx.back() * Vec3::new(1., 0., 1.)).normalize_or_zero();
+            (strength, ground_pos, back)
+        });
+
+    let dt = time.delta_seconds();
+
+    for (mut tuft, mut tx) in grass_query.iter_mut() {
+        let mut target = 0.0;
+
+        if let Some((strength, ground_pos, back)) = wash {
+            let offset = (tuft.base - ground_pos) * Vec3::new(1., 0., 1.);
+            let distance = offset.length();
+
+            if strength > 0.0 && distance > 0.0 && distance < WASH_LENGTH {
+                let angle = offset.angle_between(back);
+
+                if angle < WASH_HALF_ANGLE {
+                    // Strongest close to the propellor and along the centre of the cone
+                    let falloff = (1.0 - distance / WASH_LENGTH) * (1.0 - angle / WASH_HALF_ANGLE);
+                    target = MAX_GRASS_BEND * strength * falloff;
+                    tuft.axis = Vec3::Y.cross(offset / distance);
+                }
+            }
+        }
+
+        if target == 0.0 && tuft.bend == 0.0 {
+            continue;
+        }
+
+        // Flatten immediately but recover slowly once the wash has passed
+        tuft.bend = if target > tuft.bend {
+            target
+        } else {
+            (tuft.bend - GRASS_RECOVERY_RATE * dt).max(target)
+        };
+
+        tx.rotation = Quat::from_axis_angle(tuft.axis, tuft.bend);
+        tx.translation = tuft.base + tx.rotation * GRASS_CENTRE;
+    }
+}
+
+/// Grass roots are held outside the transform so need moving along with the origin.
+pub fn rebase_grass(
+    mut shifted_events: EventReader<OriginShiftedEvent>,
+    mut grass_query: Query<&mut GrassTuft>,
+) {
+    for OriginShiftedEvent(shift) in shifted_events.iter() {
+        for mut tuft in grass_query.iter_mut() {
+            tuft.base -= *shift;
+        }
+    }
+}
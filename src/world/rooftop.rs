@@ -0,0 +1,383 @@
+use bevy::{ecs::query::Has, math::vec3, prelude::*, utils::HashMap};
+use bevy_rapier3d::{prelude::*, rapier::geometry::CollisionEventFlags};
+
+use crate::{
+    damage::{Crashed, PreviousVelocity},
+    physics::PHYSICS_DT,
+    plane::{Plane, Wheel},
+};
+
+/// Buildings at least this tall can have a landing pad on the roof.
+const MIN_PAD_HEIGHT: f32 = 120.0;
+/// One in this many tall buildings has a landing pad.
+const PAD_CHANCE: i32 = 12;
+const STRIP_SIZE: Vec3 = vec3(24.0, 1.5, 160.0);
+/// Distance of the arrestor wires in from the approach end of a strip, and the spacing between them.
+const WIRES_START: f32 = 25.0;
+const WIRE_SPACING: f32 = 10.0;
+const WIRES: usize = 4;
+/// Distance a caught wire pays out over while bringing the plane to a stop.
+const ARRESTOR_RUNOUT: f32 = 80.0;
+const ARRESTOR_MAX_DECELERATION: f32 = 30.0;
+/// Speed below which the wire lets go of the plane.
+const ARRESTOR_RELEASE_SPEED: f32 = 1.0;
+/// Height of the sensors above a pad, tall enough to take in the wheels as they touch down.
+const SENSOR_HEIGHT: f32 = 4.0;
+/// Sink rate at which a touchdown scores nothing for its softness.
+const MAX_SINK_RATE: f32 = 3.0;
+const TOUCHDOWN_POINTS: f32 = 500.0;
+const ACCURACY_POINTS: f32 = 500.0;
+const WIRE_POINTS: f32 = 250.0;
+/// Time after touching down on a pad before touching down on it again is scored, so bounces don't
+/// count as landings.
+const TOUCHDOWN_INTERVAL: f32 = 5.0;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PadKind {
+    /// Square pad on the roof itself.
+    Helipad,
+    /// Deck along the roof with arrestor wires across its approach end, like a carrier's.
+    Strip,
+}
+
+impl PadKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Helipad => "Helipad",
+            Self::Strip => "Rooftop strip",
+        }
+    }
+}
+
+/// Sensor over a landing pad, scoring touchdowns on it.
+#[derive(Component)]
+pub struct LandingPad {
+    kind: PadKind,
+    /// Point to touch down on, relative to the sensor.
+    aim: Vec3,
+    /// Distance from the aim point at which a touchdown scores nothing for accuracy.
+    tolerance: f32,
+}
+
+/// Sensor over the arrestor wires of a strip, which catch planes touching down inside it.
+#[derive(Component)]
+pub struct ArrestorWires;
+
+/// Plane caught by an arrestor wire, slowed along the direction it was going when caught.
+#[derive(Component)]
+pub struct Arrested {
+    direction: Vec3,
+    travelled: f32,
+}
+
+/// Sent with the score for each touchdown on a rooftop pad.
+#[derive(Event, Clone, Debug)]
+pub struct PadLandingEvent {
+    pub kind: PadKind,
+    pub sink_rate: f32,
+    pub distance: f32,
+    pub caught_wire: bool,
+    pub score: f32,
+}
+
+#[derive(Resource)]
+pub struct RooftopAssets {
+    marking_material: Handle<StandardMaterial>,
+    deck_material: Handle<StandardMaterial>,
+    wire_material: Handle<StandardMaterial>,
+}
+
+pub fn setup_rooftops(mut commands: Commands, mut materials: ResMut<Assets<StandardMaterial>>) {
+    commands.insert_resource(RooftopAssets {
+        marking_material: materials.add(StandardMaterial {
+            base_color: Color::WHITE,
+            unlit: true,
+            ..default()
+        }),
+        deck_material: materials.add(Color::rgb(0.3, 0.3, 0.32).into()),
+        wire_material: materials.add(Color::rgb(0.1, 0.1, 0.1).into()),
+    });
+}
+
+/// Landing pad on the roof of the building on a block, picked from a hash of the block so the same
+/// buildings have them each time they're generated.
+pub fn block_pad((x, z): (i32, i32), height: f32) -> Option<PadKind> {
+    if height < MIN_PAD_HEIGHT {
+        return None;
+    }
+
+    let hash = x.wrapping_mul(73_856_093) ^ z.wrapping_mul(19_349_663);
+    match hash.rem_euclid(PAD_CHANCE * 2) {
+        0 => Some(PadKind::Helipad),
+        1 => Some(PadKind::Strip),
+        _ => None,
+    }
+}
+
+/// Builds a pad on the roof of a building, as children of it. Strips run along -Z, so are
+/// approached from the +Z end.
+pub fn build_pad(
+    parent: &mut ChildBuilder<'_, '_, '_>,
+    meshes: &mut ResMut<Assets<Mesh>>,
+    assets: &RooftopAssets,
+    kind: PadKind,
+    side: f32,
+    height: f32,
+) {
+    let roof = height * 0.5;
+
+    match kind {
+        PadKind::Helipad => {
+            let y = roof + 0.01;
+            let ring = meshes.add(Mesh::from(shape::Torus {
+                radius: side * 0.4,
+                ring_radius: 0.4,
+                ..default()
+            }));
+            parent.spawn(PbrBundle {
+                mesh: ring,
+                material: assets.marking_material.clone(),
+                transform: Transform::from_xyz(0., y, 0.).with_scale(vec3(1., 0.05, 1.)),
+                ..default()
+            });
+
+            // The H, with its bars along the approach
+            let bar = meshes.add(box_mesh(vec3(1.2, 0.02, 8.0)));
+            for x in [-2.0, 2.0] {
+                parent.spawn(PbrBundle {
+                    mesh: bar.clone(),
+                    material: assets.marking_material.clone(),
+                    transform: Transform::from_xyz(x, y, 0.),
+                    ..default()
+                });
+            }
+            parent.spawn(PbrBundle {
+                mesh: meshes.add(box_mesh(vec3(4.0, 0.02, 1.2))),
+                material: assets.marking_material.clone(),
+                transform: Transform::from_xyz(0., y, 0.),
+                ..default()
+            });
+
+            let sensor_size = vec3(side, SENSOR_HEIGHT, side);
+            parent.spawn((
+                LandingPad {
+                    kind,
+                    aim: Vec3::ZERO,
+                    tolerance: side * 0.5,
+                },
+                TransformBundle::from_transform(Transform::from_xyz(
+                    0.,
+                    roof + SENSOR_HEIGHT * 0.5,
+                    0.,
+                )),
+                Collider::cuboid(
+                    sensor_size.x * 0.5,
+                    sensor_size.y * 0.5,
+                    sensor_size.z * 0.5,
+                ),
+                Sensor,
+            ));
+        }
+        PadKind::Strip => {
+            let deck = STRIP_SIZE;
+            let top = roof + deck.y;
+            let approach_end = deck.z * 0.5;
+
+            parent.spawn((
+                PbrBundle {
+                    mesh: meshes.add(box_mesh(deck)),
+                    material: assets.deck_material.clone(),
+                    transform: Transform::from_xyz(0., roof + deck.y * 0.5, 0.),
+                    ..default()
+                },
+                Collider::cuboid(deck.x * 0.5, deck.y * 0.5, deck.z * 0.5),
+                Friction::new(0.01),
+            ));
+
+            // Edge lines down both sides, and a centreline from the wires on
+            let edge = meshes.add(box_mesh(vec3(0.5, 0.02, deck.z)));
+            for x in [-0.5, 0.5] {
+                parent.spawn(PbrBundle {
+                    mesh: edge.clone(),
+                    material: assets.marking_material.clone(),
+                    transform: Transform::from_xyz((deck.x - 1.0) * x, top + 0.01, 0.),
+                    ..default()
+                });
+            }
+            let centreline_length = deck.z - WIRES_START;
+            parent.spawn(PbrBundle {
+                mesh: meshes.add(box_mesh(vec3(0.5, 0.02, centreline_length))),
+                material: assets.marking_material.clone(),
+                transform: Transform::from_xyz(0., top + 0.01, -WIRES_START * 0.5),
+                ..default()
+            });
+
+            let wire = meshes.add(Mesh::from(shape::Cylinder {
+                radius: 0.08,
+                height: deck.x,
+                ..default()
+            }));
+            for i in 0..WIRES {
+                let z = approach_end - WIRES_START - i as f32 * WIRE_SPACING;
+                parent.spawn(PbrBundle {
+                    mesh: wire.clone(),
+                    material: assets.wire_material.clone(),
+                    transform: Transform::from_xyz(0., top + 0.1, z)
+                        .with_rotation(Quat::from_rotation_z(90_f32.to_radians())),
+                    ..default()
+                });
+            }
+
+            // Wires catch anything touching down from just short of the first to just past the last
+            let wires_length = WIRE_SPACING * WIRES as f32;
+            let wires_z = approach_end - WIRES_START - (WIRES - 1) as f32 * WIRE_SPACING * 0.5;
+            let sensor_y = top + SENSOR_HEIGHT * 0.5;
+            parent.spawn((
+                ArrestorWires,
+                TransformBundle::from_transform(Transform::from_xyz(0., sensor_y, wires_z)),
+                Collider::cuboid(deck.x * 0.5, SENSOR_HEIGHT * 0.5, wires_length * 0.5),
+                Sensor,
+            ));
+            parent.spawn((
+                LandingPad {
+                    kind,
+                    aim: vec3(0., 0., wires_z),
+                    tolerance: wires_length,
+                },
+                TransformBundle::from_transform(Transform::from_xyz(0., sensor_y, 0.)),
+                Collider::cuboid(deck.x * 0.5, SENSOR_HEIGHT * 0.5, deck.z * 0.5),
+                Sensor,
+            ));
+        }
+    }
+}
+
+fn box_mesh(size: Vec3) -> Mesh {
+    Mesh::from(shape::Box::new(size.x, size.y, size.z))
+}
+
+/// Scores wheels touching down on a pad by how softly and how close to its aim point they landed,
+/// hooking the plane onto the arrestor wires if they touched down among them.
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+pub fn score_pad_landings(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut collision_events: EventReader<CollisionEvent>,
+    rapier_context: Res<RapierContext>,
+    wheel_query: Query<(), With<Wheel>>,
+    parent_query: Query<&Parent>,
+    plane_query: Query<
+        (&GlobalTransform, &PreviousVelocity, Has<Arrested>),
+        (With<Plane>, Without<Crashed>),
+    >,
+    pad_query: Query<(&LandingPad, &GlobalTransform)>,
+    wires_query: Query<(), With<ArrestorWires>>,
+    mut last_touchdowns: Local<HashMap<Entity, f32>>,
+    mut landing_events: EventWriter<PadLandingEvent>,
+) {
+    let now = time.elapsed_seconds();
+
+    for event in collision_events.iter() {
+        let CollisionEvent::Started(e1, e2, flags) = event else {
+            continue;
+        };
+        if flags.contains(CollisionEventFlags::SENSOR) {
+            continue;
+        }
+
+        for wheel in [*e1, *e2] {
+            if !wheel_query.contains(wheel) {
+                continue;
+            }
+            let Some((plane, (plane_tx, PreviousVelocity(velocity), arrested))) = parent_query
+                .iter_ancestors(wheel)
+                .find_map(|entity| plane_query.get(entity).ok().map(|p| (entity, p)))
+            else {
+                continue;
+            };
+
+            let sensors: Vec<_> = rapier_context
+                .intersections_with(wheel)
+                .filter(|(_, _, intersecting)| *intersecting)
+                .map(|(a, b, _)| if a == wheel { b } else { a })
+                .collect();
+            let Some((pad_entity, (pad, pad_tx))) = sensors
+                .iter()
+                .find_map(|entity| pad_query.get(*entity).ok().map(|p| (*entity, p)))
+            else {
+                continue;
+            };
+
+            let last = last_touchdowns.insert(pad_entity, now);
+            if last.is_some_and(|last| now - last < TOUCHDOWN_INTERVAL) {
+                continue;
+            }
+
+            let caught_wire =
+                !arrested && sensors.iter().any(|entity| wires_query.contains(*entity));
+            if caught_wire {
+                info!("Caught an arrestor wire");
+                let direction = (*velocity * vec3(1., 0., 1.)).normalize_or_zero();
+                commands.entity(plane).insert((
+                    Arrested {
+                        direction,
+                        travelled: 0.0,
+                    },
+                    ExternalImpulse::default(),
+                ));
+            }
+
+            let local = pad_tx
+                .affine()
+                .inverse()
+                .transform_point3(plane_tx.translation());
+            let distance = ((local - pad.aim) * vec3(1., 0., 1.)).length();
+            let sink_rate = (-velocity.y).max(0.0);
+
+            let score = (1.0 - sink_rate / MAX_SINK_RATE).max(0.0) * TOUCHDOWN_POINTS
+                + (1.0 - distance / pad.tolerance).max(0.0) * ACCURACY_POINTS
+                + if caught_wire { WIRE_POINTS } else { 0.0 };
+            info!(
+                "Touched down on {:?} at {:.1} m/s sink, {:.1} m from the aim point, scoring {:.0}",
+                pad.kind, sink_rate, distance, score
+            );
+
+            landing_events.send(PadLandingEvent {
+                kind: pad.kind,
+                sink_rate,
+                distance,
+                caught_wire,
+                score,
+            });
+        }
+    }
+}
+
+/// Slows caught planes to a stop within the wire's run out, letting go once they've stopped.
+pub fn apply_arrestors(
+    mut commands: Commands,
+    mut plane_query: Query<(
+        Entity,
+        &mut Arrested,
+        &Velocity,
+        &ReadMassProperties,
+        &mut ExternalImpulse,
+    )>,
+) {
+    for (entity, mut arrested, velocity, ReadMassProperties(mass_properties), mut impulse) in
+        plane_query.iter_mut()
+    {
+        let speed = velocity.linvel.dot(arrested.direction);
+        if speed < ARRESTOR_RELEASE_SPEED {
+            commands
+                .entity(entity)
+                .remove::<(Arrested, ExternalImpulse)>();
+            continue;
+        }
+
+        arrested.travelled += speed * PHYSICS_DT;
+        let remaining = (ARRESTOR_RUNOUT - arrested.travelled).max(1.0);
+        let deceleration = (speed * speed / (2.0 * remaining)).min(ARRESTOR_MAX_DECELERATION);
+        impulse.impulse = -arrested.direction * mass_properties.mass * deceleration * PHYSICS_DT;
+    }
+}
@@ -0,0 +1,371 @@
+use bevy::{
+    math::{vec2, vec3},
+    pbr::NotShadowCaster,
+    prelude::*,
+    render::{mesh::Indices, render_resource::PrimitiveTopology},
+    tasks::{AsyncComputeTaskPool, Task},
+    utils::HashMap,
+};
+use bevy_rapier3d::prelude::*;
+use futures_lite::future;
+use noise::{NoiseFn, Perlin};
+
+use crate::camera::Follow;
+
+use super::{
+    bridge::{build_bridge, river_crossing, BridgeSpec},
+    city::{road_material, road_mesh},
+    map::ExploredChunks,
+    Airports, BlockPos, Rand, WorldOrigin, SPACING,
+};
+
+/// Number of building blocks along each side of a terrain chunk.
+pub const CHUNK_BLOCKS: i32 = 8;
+pub const CHUNK_SIZE: f32 = (CHUNK_BLOCKS * SPACING) as f32;
+/// Number of vertices along each side of a terrain chunk.
+const CHUNK_RESOLUTION: usize = 65;
+/// Chunks either side of the followed entity's chunk that have full detail terrain and buildings.
+pub const ACTIVE_CHUNK_DISTANCE: i32 = 2;
+const MAX_TERRAIN_HEIGHT: f32 = 150.0;
+/// Scale of the noise whose zero contours the rivers follow.
+const RIVER_FREQUENCY: f64 = 0.0003;
+/// Offset into the terrain noise for the rivers, far enough away to be unrelated to the hills.
+const RIVER_NOISE_OFFSET: f64 = 1000.5;
+/// Half the width of a river channel in units of the river noise, roughly 60m.
+pub const RIVER_WIDTH: f32 = 0.03;
+/// Depth of river beds below the lowest ground.
+const RIVER_DEPTH: f32 = 6.0;
+/// Height of the water in the rivers, below all ground outside the channels.
+pub const RIVER_LEVEL: f32 = -2.0;
+
+#[derive(Component)]
+pub struct TerrainChunk;
+
+/// Height of the terrain at a local position, with river channels cut into it and flattened around
+/// airports.
+pub fn terrain_height(
+    perlin: &Perlin,
+    airports: &Airports,
+    origin: &WorldOrigin,
+    pos: Vec2,
+) -> f32 {
+    // Noise is sampled at the absolute position so the terrain doesn't change when the origin moves
+    let absolute = origin.to_absolute(vec3(pos.x, 0., pos.y));
+    let broad = perlin.get([absolute.x as f64 * 0.0007, absolute.z as f64 * 0.0007]);
+    let detail = perlin.get([absolute.x as f64 * 0.004, absolute.z as f64 * 0.004]);
+    let n = (broad * 0.8 + detail * 0.2) as f32;
+
+    // Banks slope smoothly down to the bed in the middle of the channel
+    let bank = (river_noise(perlin, origin, pos).abs() / RIVER_WIDTH).min(1.0);
+    let bank = bank * bank * (3.0 - 2.0 * bank);
+    let height = n.max(0.0) * MAX_TERRAIN_HEIGHT * bank - RIVER_DEPTH * (1.0 - bank);
+
+    height * airports.flatten_factor(pos)
+}
+
+/// Noise that is zero along the middle of the rivers and grows away from them.
+pub fn river_noise(perlin: &Perlin, origin: &WorldOrigin, pos: Vec2) -> f32 {
+    let absolute = origin.to_absolute(vec3(pos.x, 0., pos.y));
+
+    perlin.get([
+        absolute.x as f64 * RIVER_FREQUENCY + RIVER_NOISE_OFFSET,
+        absolute.z as f64 * RIVER_FREQUENCY + RIVER_NOISE_OFFSET,
+    ]) as f32
+}
+
+/// Whether a local position is in a river channel, where nothing can be built.
+pub fn in_river(perlin: &Perlin, origin: &WorldOrigin, pos: Vec2) -> bool {
+    river_noise(perlin, origin, pos).abs() < RIVER_WIDTH
+}
+
+/// Terrain chunk generated off the main thread, relative to its centre, ready to be spawned.
+pub struct ChunkData {
+    mesh: Mesh,
+    collider: Collider,
+    has_river: bool,
+    crossing: Option<(Transform, BridgeSpec)>,
+    roads: Option<Mesh>,
+}
+
+/// Generates the terrain of a chunk. Takes its own copies of the world so it can run as a task,
+/// with the chunk's centre placed by the origin it was given.
+fn generate_chunk(
+    rand: &Rand,
+    airports: &Airports,
+    origin: &WorldOrigin,
+    (x, z): (i32, i32),
+) -> ChunkData {
+    let perlin = &rand.terrain;
+    let centre = chunk_centre(origin, (x, z));
+    let heights = chunk_heights(perlin, airports, origin, centre);
+    let has_river = heights.iter().flatten().any(|height| *height < RIVER_LEVEL);
+    let crossing = river_crossing(
+        perlin,
+        airports,
+        origin,
+        centre,
+        CHUNK_SIZE,
+        CHUNK_RESOLUTION,
+    );
+
+    ChunkData {
+        mesh: chunk_mesh(&heights),
+        collider: chunk_collider(&heights),
+        has_river,
+        crossing,
+        roads: road_mesh(rand, airports, origin, (x, z), centre),
+    }
+}
+
+/// Local position of the centre of a chunk. Chunks are keyed by their absolute position but placed
+/// relative to the origin.
+pub fn chunk_centre(origin: &WorldOrigin, (x, z): (i32, i32)) -> Vec2 {
+    let centre = origin.to_local(vec3(
+        (x as f32 + 0.5) * CHUNK_SIZE,
+        0.,
+        (z as f32 + 0.5) * CHUNK_SIZE,
+    ));
+    vec2(centre.x, centre.z)
+}
+
+/// Starts generating the chunks that come into range as the followed entity moves between blocks,
+/// and spawns each one once it's been generated, so crossing into a new block doesn't stall a
+/// frame. When the world is reseeded each chunk is kept until its replacement has been generated,
+/// so there's always ground under the plane.
+#[allow(clippy::too_many_arguments)]
+pub fn stream_terrain(
+    mut commands: Commands,
+    query: Query<Ref<BlockPos>, With<Follow>>,
+    mut chunk_entities: Local<HashMap<(i32, i32), Entity>>,
+    mut chunk_tasks: Local<HashMap<(i32, i32), Task<ChunkData>>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    rand: Res<Rand>,
+    airports: Res<Airports>,
+    origin: Res<WorldOrigin>,
+    mut explored_chunks: ResMut<ExploredChunks>,
+) {
+    if let Some(block_pos) = query
+        .get_single()
+        .ok()
+        .filter(|block_pos| block_pos.is_changed() || rand.is_changed())
+    {
+        let BlockPos(px, pz) = *block_pos;
+        let cx = px.div_euclid(CHUNK_BLOCKS);
+        let cz = pz.div_euclid(CHUNK_BLOCKS);
+
+        let in_range = |(x, z): (i32, i32)| {
+            (x - cx).abs() <= ACTIVE_CHUNK_DISTANCE && (z - cz).abs() <= ACTIVE_CHUNK_DISTANCE
+        };
+
+        let old_chunks: Vec<_> = chunk_entities
+            .keys()
+            .filter(|chunk| !in_range(**chunk))
+            .copied()
+            .collect();
+
+        for chunk in old_chunks {
+            if let Some(entity) = chunk_entities.remove(&chunk) {
+                commands.entity(entity).despawn_recursive();
+            }
+        }
+        // Dropping a task cancels it
+        chunk_tasks.retain(|chunk, _| in_range(*chunk));
+        if rand.is_changed() {
+            chunk_tasks.clear();
+        }
+
+        let task_pool = AsyncComputeTaskPool::get();
+        for z in (cz - ACTIVE_CHUNK_DISTANCE)..=(cz + ACTIVE_CHUNK_DISTANCE) {
+            for x in (cx - ACTIVE_CHUNK_DISTANCE)..=(cx + ACTIVE_CHUNK_DISTANCE) {
+                if (chunk_entities.contains_key(&(x, z)) && !rand.is_changed())
+                    || chunk_tasks.contains_key(&(x, z))
+                {
+                    continue;
+                }
+
+                info!("Generating terrain chunk {:?}", (x, z));
+
+                let rand = *rand;
+                let airports = airports.clone();
+                let origin = WorldOrigin(origin.0);
+                let task = task_pool
+                    .spawn(async move { generate_chunk(&rand, &airports, &origin, (x, z)) });
+                chunk_tasks.insert((x, z), task);
+            }
+        }
+    }
+
+    let generated: Vec<_> = chunk_tasks
+        .iter_mut()
+        .filter_map(|(chunk, task)| {
+            future::block_on(future::poll_once(task)).map(|data| (*chunk, data))
+        })
+        .collect();
+
+    for (chunk, data) in generated {
+        chunk_tasks.remove(&chunk);
+        explored_chunks.0.insert(chunk);
+
+        // Placed by the origin as it is now, in case it was rebased while the chunk was generated
+        let centre = chunk_centre(&origin, chunk);
+
+        let entity = commands
+            .spawn((
+                TerrainChunk,
+                PbrBundle {
+                    mesh: meshes.add(data.mesh),
+                    material: materials.add(StandardMaterial {
+                        perceptual_roughness: 1.0,
+                        ..default()
+                    }),
+                    transform: Transform::from_xyz(centre.x, 0., centre.y),
+                    ..default()
+                },
+                RigidBody::Fixed,
+                data.collider,
+                Friction::new(0.01),
+            ))
+            .with_children(|parent| {
+                // Water fills the chunk below all the ground outside the river channels
+                if data.has_river {
+                    parent.spawn(PbrBundle {
+                        mesh: meshes.add(Mesh::from(shape::Plane::from_size(CHUNK_SIZE))),
+                        material: materials.add(StandardMaterial {
+                            base_color: Color::rgba(0.1, 0.3, 0.45, 0.85),
+                            alpha_mode: AlphaMode::Blend,
+                            perceptual_roughness: 0.1,
+                            ..default()
+                        }),
+                        transform: Transform::from_xyz(0., RIVER_LEVEL, 0.),
+                        ..default()
+                    });
+                }
+
+                if let Some(roads) = data.roads {
+                    parent.spawn((
+                        PbrBundle {
+                            mesh: meshes.add(roads),
+                            material: materials.add(road_material()),
+                            ..default()
+                        },
+                        // Too close to the ground for a shadow to be seen
+                        NotShadowCaster,
+                    ));
+                }
+
+                if let Some((transform, spec)) = data.crossing {
+                    build_bridge(parent, &mut meshes, &mut materials, transform, &spec);
+                }
+            })
+            .id();
+
+        if let Some(old_entity) = chunk_entities.insert(chunk, entity) {
+            commands.entity(old_entity).despawn_recursive();
+        }
+    }
+}
+
+/// Linear colour of the ground at a height, from grass up to rock with mud in the river beds.
+pub fn terrain_color(height: f32) -> Vec3 {
+    let t = (height / MAX_TERRAIN_HEIGHT).clamp(0.0, 1.0);
+    let grass = Vec3::new(0.1, 0.2, 0.1);
+    let rock = Vec3::new(0.35, 0.3, 0.25);
+    let mud = Vec3::new(0.25, 0.2, 0.1);
+
+    if height < 0.0 {
+        mud
+    } else {
+        grass.lerp(rock, t)
+    }
+}
+
+/// Samples the terrain heights for a chunk, indexed by `[x][z]`.
+fn chunk_heights(
+    perlin: &Perlin,
+    airports: &Airports,
+    origin: &WorldOrigin,
+    centre: Vec2,
+) -> Vec<Vec<f32>> {
+    let cell_size = CHUNK_SIZE / (CHUNK_RESOLUTION - 1) as f32;
+    let corner = centre - Vec2::splat(CHUNK_SIZE * 0.5);
+
+    (0..CHUNK_RESOLUTION)
+        .map(|x| {
+            (0..CHUNK_RESOLUTION)
+                .map(|z| {
+                    let pos = corner + vec2(x as f32, z as f32) * cell_size;
+                    terrain_height(perlin, airports, origin, pos)
+                })
+                .collect()
+        })
+        .collect()
+}
+
+fn chunk_mesh(heights: &[Vec<f32>]) -> Mesh {
+    let cell_size = CHUNK_SIZE / (CHUNK_RESOLUTION - 1) as f32;
+    let half_size = CHUNK_SIZE * 0.5;
+    let last = CHUNK_RESOLUTION - 1;
+
+    let mut positions = Vec::with_capacity(CHUNK_RESOLUTION * CHUNK_RESOLUTION);
+    let mut normals = Vec::with_capacity(CHUNK_RESOLUTION * CHUNK_RESOLUTION);
+    let mut colors = Vec::with_capacity(CHUNK_RESOLUTION * CHUNK_RESOLUTION);
+    let mut uvs = Vec::with_capacity(CHUNK_RESOLUTION * CHUNK_RESOLUTION);
+
+    for z in 0..CHUNK_RESOLUTION {
+        for x in 0..CHUNK_RESOLUTION {
+            let height = heights[x][z];
+
+            positions.push([
+                x as f32 * cell_size - half_size,
+                height,
+                z as f32 * cell_size - half_size,
+            ]);
+
+            let dx = heights[(x + 1).min(last)][z] - heights[x.saturating_sub(1)][z];
+            let dz = heights[x][(z + 1).min(last)] - heights[x][z.saturating_sub(1)];
+            normals.push(Vec3::new(-dx, 2.0 * cell_size, -dz).normalize().to_array());
+
+            let color = terrain_color(height);
+            colors.push([color.x, color.y, color.z, 1.0]);
+
+            uvs.push([x as f32 / last as f32, z as f32 / last as f32]);
+        }
+    }
+
+    let mut indices = Vec::with_capacity(last * last * 6);
+    let index = |x: usize, z: usize| (z * CHUNK_RESOLUTION + x) as u32;
+
+    for z in 0..last {
+        for x in 0..last {
+            indices.extend_from_slice(&[
+                index(x, z),
+                index(x, z + 1),
+                index(x + 1, z),
+                index(x + 1, z),
+                index(x, z + 1),
+                index(x + 1, z + 1),
+            ]);
+        }
+    }
+
+    let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, colors);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, uvs);
+    mesh.set_indices(Some(Indices::U32(indices)));
+    mesh
+}
+
+fn chunk_collider(heights: &[Vec<f32>]) -> Collider {
+    // Rapier heightfields are column-major with rows along Z and columns along X
+    let flat_heights: Vec<f32> = heights.iter().flatten().copied().collect();
+
+    Collider::heightfield(
+        flat_heights,
+        CHUNK_RESOLUTION,
+        CHUNK_RESOLUTION,
+        Vec3::new(CHUNK_SIZE, 1.0, CHUNK_SIZE),
+    )
+}
@@ -0,0 +1,155 @@
+use std::f32::consts::TAU;
+
+use bevy::{math::vec2, prelude::*};
+
+use crate::camera::Follow;
+
+use super::{
+    block_building, terrain::terrain_height, weather::CLOUD_CEILING, Airports, Rand, TimeOfDay,
+    Weather, WorldOrigin, SPACING,
+};
+
+/// Size in blocks of the cells of the grid each holding a thermal, so they're spaced about 800m
+/// apart.
+const CELL_BLOCKS: i32 = 4;
+/// Cells either side of the followed plane's that thermals are kept for.
+const ACTIVE_CELLS: i32 = 3;
+const THERMAL_RADIUS: f32 = 80.0;
+/// Strength in m/s at the core of a thermal over open ground, before the sun and cloud are
+/// accounted for.
+const MIN_STRENGTH: f32 = 1.0;
+const MAX_STRENGTH: f32 = 2.5;
+/// Extra strength at the core of a thermal rising from the tallest building.
+const BUILDING_HEAT: f32 = 2.0;
+/// Height above the ground over which a thermal builds to full strength.
+const GROUND_LAYER: f32 = 50.0;
+/// Fraction of the column's height over which a thermal fades out at its top, at the cloud base.
+const TOP_LAYER: f32 = 0.2;
+/// Time for a thermal to build up and die away again.
+const CYCLE_TIME: f32 = 600.0;
+
+/// Column of rising air above a block, heated by the sun, reaching up to the cloud base.
+pub struct Thermal {
+    /// Local position of the centre of the column.
+    pub position: Vec2,
+    /// Height of the ground it rises from.
+    pub base: f32,
+    /// Upward speed in m/s at the core.
+    pub strength: f32,
+}
+
+impl Thermal {
+    fn updraft(&self, pos: Vec3) -> f32 {
+        let height = pos.y - self.base;
+        let depth = CLOUD_CEILING - self.base;
+        if height <= 0.0 || height >= depth {
+            return 0.0;
+        }
+
+        let distance = vec2(pos.x, pos.z).distance(self.position);
+        let core = (-(distance / THERMAL_RADIUS).powi(2)).exp();
+        let ground = (height / GROUND_LAYER).min(1.0);
+        let fade = ((depth - height) / (depth * TOP_LAYER)).min(1.0);
+
+        self.strength * core * ground * fade
+    }
+}
+
+/// Thermals around the followed plane, placed on a grid so the same ones are found each time an
+/// area is flown through.
+#[derive(Resource, Default)]
+pub struct Thermals(pub Vec<Thermal>);
+
+impl Thermals {
+    /// Upward speed of the air at a local position.
+    pub fn updraft(&self, pos: Vec3) -> f32 {
+        self.0.iter().map(|thermal| thermal.updraft(pos)).sum()
+    }
+}
+
+/// Places a thermal in each cell around the followed plane, on a block picked from a hash of the
+/// cell. Thermals rising from buildings are stronger, as cities heat up more than open ground, and
+/// all are weakened by cloud and fade away without the sun.
+#[allow(clippy::too_many_arguments)]
+pub fn update_thermals(
+    time: Res<Time>,
+    rand: Res<Rand>,
+    airports: Res<Airports>,
+    origin: Res<WorldOrigin>,
+    weather: Res<Weather>,
+    time_of_day: Res<TimeOfDay>,
+    follow_query: Query<&Transform, With<Follow>>,
+    mut thermals: ResMut<Thermals>,
+) {
+    thermals.0.clear();
+    let Ok(follow_tx) = follow_query.get_single() else {
+        return;
+    };
+
+    let sun = time_of_day.sun_elevation().sin().max(0.0);
+    let heating = sun * (1.0 - weather.preset.cloud_cover() * 0.8);
+    if heating <= 0.0 {
+        return;
+    }
+
+    let cell_size = (SPACING * CELL_BLOCKS) as f32;
+    let pos = origin.to_absolute(follow_tx.translation);
+    let cell_x = (pos.x / cell_size).floor() as i32;
+    let cell_z = (pos.z / cell_size).floor() as i32;
+
+    for cz in (cell_z - ACTIVE_CELLS)..=(cell_z + ACTIVE_CELLS) {
+        for cx in (cell_x - ACTIVE_CELLS)..=(cell_x + ACTIVE_CELLS) {
+            let hash = cx.wrapping_mul(50_331_653) ^ cz.wrapping_mul(12_582_917);
+            let block = (
+                cx * CELL_BLOCKS + hash.rem_euclid(CELL_BLOCKS),
+                cz * CELL_BLOCKS + (hash / CELL_BLOCKS).rem_euclid(CELL_BLOCKS),
+            );
+            let local = origin.to_local(Vec3::new(
+                (block.0 * SPACING) as f32,
+                0.,
+                (block.1 * SPACING) as f32,
+            ));
+            let position = vec2(local.x, local.z);
+
+            // Nearest block of the city, which may be spaced differently to the grid
+            let spacing = rand.config.spacing as f32;
+            let city_block = (
+                ((block.0 * SPACING) as f32 / spacing).round() as i32,
+                ((block.1 * SPACING) as f32 / spacing).round() as i32,
+            );
+            let building_heat = block_building(&rand, &airports, &origin, city_block)
+                .map_or(0.0, |(_, height)| {
+                    (height / rand.config.max_height).min(1.0) * BUILDING_HEAT
+                });
+            let variation = (hash / (CELL_BLOCKS * CELL_BLOCKS)).rem_euclid(100) as f32 / 100.0;
+            let phase = variation * TAU;
+            let cycle = 0.7 + 0.3 * (time.elapsed_seconds() * TAU / CYCLE_TIME + phase).sin();
+
+            thermals.0.push(Thermal {
+                position,
+                base: terrain_height(&rand.terrain, &airports, &origin, position),
+                strength: (MIN_STRENGTH
+                    + (MAX_STRENGTH - MIN_STRENGTH) * variation
+                    + building_heat)
+                    * heating
+                    * cycle,
+            });
+        }
+    }
+}
+
+/// Draws each thermal as rings up its column, brighter the stronger it is.
+pub fn draw_thermal_gizmos(mut gizmos: Gizmos, thermals: Res<Thermals>) {
+    for thermal in thermals.0.iter() {
+        let color = Color::rgba(1.0, 0.6, 0.2, (thermal.strength / MAX_STRENGTH).min(1.0));
+        for i in 1..=5 {
+            let height = thermal.base + (CLOUD_CEILING - thermal.base) * i as f32 / 6.0;
+            gizmos.circle(
+                Vec3::new(thermal.position.x, height, thermal.position.y),
+                Vec3::Y,
+                THERMAL_RADIUS,
+                color,
+            );
+        }
+    }
+}
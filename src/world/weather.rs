@@ -0,0 +1,284 @@
+use std::f32::consts::TAU;
+
+use bevy::{
+    math::{vec2, vec3},
+    prelude::*,
+};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::camera::{FogControl, MainCamera};
+
+use super::compass_heading;
+
+/// Height of the base of the cloud layer.
+pub const CLOUD_CEILING: f32 = 600.0;
+/// Random variation of individual clouds above and below the ceiling.
+const CLOUD_CEILING_VARIATION: f32 = 40.0;
+/// Horizontal extent of the cloud layer, which wraps around the camera.
+const CLOUD_LAYER_SIZE: f32 = 6000.0;
+/// Number of clouds in the layer at full cover.
+const MAX_CLOUDS: usize = 400;
+const CLOUD_SIZE: (f32, f32) = (150.0, 600.0);
+/// Radius around the camera in which rain is spawned.
+const RAIN_RADIUS: f32 = 40.0;
+/// Drops spawned per second at full intensity.
+const RAIN_RATE: f32 = 1500.0;
+const RAIN_SPEED: f32 = 9.0;
+/// Height above the camera that drops start falling from, and below it that they are removed.
+const RAIN_HEIGHT: f32 = 25.0;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+pub enum WeatherPreset {
+    #[default]
+    Clear,
+    Overcast,
+    Storm,
+}
+
+impl WeatherPreset {
+    pub const ALL: [WeatherPreset; 3] = [Self::Clear, Self::Overcast, Self::Storm];
+
+    /// Distance in metres at which objects fade into the fog.
+    pub fn visibility(&self) -> f32 {
+        match self {
+            Self::Clear => 1500.0,
+            Self::Overcast => 800.0,
+            Self::Storm => 350.0,
+        }
+    }
+
+    /// Fraction of the sky covered by the cloud layer.
+    pub fn cloud_cover(&self) -> f32 {
+        match self {
+            Self::Clear => 0.1,
+            Self::Overcast => 0.8,
+            Self::Storm => 1.0,
+        }
+    }
+
+    fn cloud_color(&self) -> Color {
+        match self {
+            Self::Clear => Color::rgba(1.0, 1.0, 1.0, 0.8),
+            Self::Overcast => Color::rgba(0.75, 0.75, 0.78, 0.9),
+            Self::Storm => Color::rgba(0.35, 0.36, 0.4, 0.95),
+        }
+    }
+
+    /// Rain intensity in the range `0..1`.
+    pub fn rain(&self) -> f32 {
+        match self {
+            Self::Clear | Self::Overcast => 0.0,
+            Self::Storm => 1.0,
+        }
+    }
+
+    /// Multiplier applied to lift, with heavy rain roughening the wing and reducing it.
+    pub fn lift_factor(&self) -> f32 {
+        1.0 - self.rain() * 0.08
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct Weather {
+    pub preset: WeatherPreset,
+    /// Steady wind the air moves with, in m/s.
+    pub wind: Vec3,
+}
+
+impl Weather {
+    /// Wind blowing from a compass heading in degrees, like a weather report, at a speed in m/s.
+    pub fn wind_from(from: f32, speed: f32) -> Vec3 {
+        let from = from.to_radians();
+        Vec3::new(-from.sin(), 0., from.cos()) * speed
+    }
+
+    /// Compass heading in degrees the wind blows from and its speed over the ground in m/s.
+    pub fn wind_heading_and_speed(&self) -> (f32, f32) {
+        let speed = (self.wind * vec3(1., 0., 1.)).length();
+        (compass_heading(-self.wind), speed)
+    }
+}
+
+#[derive(Resource)]
+pub struct WeatherAssets {
+    cloud_mesh: Handle<Mesh>,
+    rain_mesh: Handle<Mesh>,
+    rain_material: Handle<StandardMaterial>,
+}
+
+#[derive(Component)]
+pub struct Cloud;
+
+#[derive(Component)]
+pub struct RainDrop;
+
+pub fn setup_weather(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    commands.insert_resource(WeatherAssets {
+        cloud_mesh: meshes.add(Mesh::from(shape::Plane::from_size(1.0))),
+        rain_mesh: meshes.add(Mesh::from(shape::Box::new(0.01, 0.6, 0.01))),
+        rain_material: materials.add(StandardMaterial {
+            base_color: Color::rgba(0.7, 0.75, 0.85, 0.5),
+            alpha_mode: AlphaMode::Blend,
+            unlit: true,
+            ..default()
+        }),
+    });
+}
+
+/// Whether the preset has changed since the condition last ran, so changing only the wind doesn't
+/// rebuild the clouds or reset the fog.
+pub fn preset_changed(weather: Res<Weather>, mut last: Local<Option<WeatherPreset>>) -> bool {
+    let changed = *last != Some(weather.preset);
+    *last = Some(weather.preset);
+    changed
+}
+
+/// Sets the fog visibility for the current preset, including on newly spawned cameras.
+pub fn update_visibility(weather: Res<Weather>, mut fog_query: Query<&mut FogControl>) {
+    for mut fog_control in fog_query.iter_mut() {
+        fog_control.visibility = weather.preset.visibility();
+    }
+}
+
+/// Replaces the cloud layer when the preset changes.
+pub fn build_cloud_layer(
+    mut commands: Commands,
+    weather: Res<Weather>,
+    assets: Res<WeatherAssets>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    cloud_query: Query<Entity, With<Cloud>>,
+) {
+    for entity in cloud_query.iter() {
+        commands.entity(entity).despawn();
+    }
+
+    let preset = weather.preset;
+    let count = (MAX_CLOUDS as f32 * preset.cloud_cover()) as usize;
+    info!("Building cloud layer of {} clouds for {:?}", count, preset);
+
+    let material = materials.add(StandardMaterial {
+        base_color: preset.cloud_color(),
+        alpha_mode: AlphaMode::Blend,
+        unlit: true,
+        cull_mode: None,
+        ..default()
+    });
+    let mut rng = rand::thread_rng();
+
+    for _ in 0..count {
+        let size = rng.gen_range(CLOUD_SIZE.0..CLOUD_SIZE.1);
+
+        commands.spawn((
+            Cloud,
+            PbrBundle {
+                mesh: assets.cloud_mesh.clone(),
+                material: material.clone(),
+                transform: Transform::from_xyz(
+                    rng.gen_range(-0.5..0.5) * CLOUD_LAYER_SIZE,
+                    CLOUD_CEILING + rng.gen_range(-1.0..1.0) * CLOUD_CEILING_VARIATION,
+                    rng.gen_range(-0.5..0.5) * CLOUD_LAYER_SIZE,
+                )
+                .with_rotation(Quat::from_rotation_y(rng.gen_range(0.0..TAU)))
+                .with_scale(vec3(size, 1.0, size * rng.gen_range(0.4..1.0))),
+                ..default()
+            },
+        ));
+    }
+}
+
+/// Wraps clouds around the camera so the layer appears to go on forever.
+pub fn wrap_clouds(
+    camera_query: Query<&GlobalTransform, With<MainCamera>>,
+    mut cloud_query: Query<&mut Transform, With<Cloud>>,
+) {
+    let Ok(camera_tx) = camera_query.get_single() else {
+        return;
+    };
+    let camera_pos = camera_tx.translation();
+    let half_size = CLOUD_LAYER_SIZE * 0.5;
+
+    for mut tx in cloud_query.iter_mut() {
+        let offset = vec2(
+            tx.translation.x - camera_pos.x,
+            tx.translation.z - camera_pos.z,
+        );
+        let wrapped = (offset + half_size).rem_euclid(Vec2::splat(CLOUD_LAYER_SIZE)) - half_size;
+
+        if wrapped != offset {
+            tx.translation.x = camera_pos.x + wrapped.x;
+            tx.translation.z = camera_pos.z + wrapped.y;
+        }
+    }
+}
+
+pub fn emit_rain(
+    mut commands: Commands,
+    time: Res<Time>,
+    weather: Res<Weather>,
+    assets: Res<WeatherAssets>,
+    camera_query: Query<&GlobalTransform, With<MainCamera>>,
+    mut carry: Local<f32>,
+) {
+    let rain = weather.preset.rain();
+    if rain <= 0.0 {
+        return;
+    }
+    let Ok(camera_tx) = camera_query.get_single() else {
+        return;
+    };
+
+    // Accumulate fractional drops so low frame times still emit at the right rate
+    *carry += RAIN_RATE * rain * time.delta_seconds();
+    let count = carry.floor();
+    *carry -= count;
+
+    let camera_pos = camera_tx.translation();
+    let mut rng = rand::thread_rng();
+
+    for _ in 0..count as usize {
+        let offset = vec2(rng.gen_range(-1.0..1.0), rng.gen_range(-1.0..1.0)) * RAIN_RADIUS;
+
+        commands.spawn((
+            RainDrop,
+            PbrBundle {
+                mesh: assets.rain_mesh.clone(),
+                material: assets.rain_material.clone(),
+                transform: Transform::from_translation(
+                    camera_pos + vec3(offset.x, rng.gen_range(-RAIN_HEIGHT..RAIN_HEIGHT), offset.y),
+                ),
+                ..default()
+            },
+        ));
+    }
+}
+
+pub fn update_rain(
+    mut commands: Commands,
+    time: Res<Time>,
+    camera_query: Query<&GlobalTransform, With<MainCamera>>,
+    mut rain_query: Query<(Entity, &mut Transform), With<RainDrop>>,
+) {
+    let camera_pos = camera_query
+        .get_single()
+        .map(|tx| tx.translation())
+        .unwrap_or_default();
+    let fall = RAIN_SPEED * time.delta_seconds();
+
+    for (entity, mut tx) in rain_query.iter_mut() {
+        tx.translation.y -= fall;
+
+        // Drops left behind by the camera are removed as well as those that have fallen past it
+        let offset = tx.translation - camera_pos;
+        if offset.y < -RAIN_HEIGHT
+            || offset.x.abs() > RAIN_RADIUS * 2.0
+            || offset.z.abs() > RAIN_RADIUS * 2.0
+        {
+            commands.entity(entity).despawn();
+        }
+    }
+}